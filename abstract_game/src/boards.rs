@@ -0,0 +1,252 @@
+use std::{
+  fs::File,
+  io::{self, BufReader, BufWriter, Read, Write},
+  marker::PhantomData,
+  path::Path,
+};
+
+use crate::{Compress, Score};
+
+const BOARDS_MAGIC: &[u8; 8] = b"onorobrd";
+const BOARDS_VERSION: u32 = 1;
+/// Set in the header's flags if every record is followed by a score.
+const HAS_SCORES: u32 = 0x1;
+
+fn invalid_data(message: String) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Writes a stream of compressed game states, optionally each with a score,
+/// in the format:
+///
+/// ```text
+/// "onorobrd" <version: u32> <flags: u32>
+/// <length: u32> <compressed state> [<score>]
+/// ...
+/// ```
+///
+/// All integers are little-endian. Records are length-prefixed, so readers
+/// can detect files of game states of the wrong size instead of misreading
+/// them. Since there is no record count in the header, states can be
+/// streamed to the file as they're produced.
+pub struct BoardWriter<W, G> {
+  writer: W,
+  with_scores: bool,
+  buffer: Vec<u8>,
+  _game: PhantomData<fn(&G)>,
+}
+
+impl<G: Compress> BoardWriter<BufWriter<File>, G> {
+  /// Creates a board file at `path`, replacing the file if it exists.
+  pub fn create(path: impl AsRef<Path>, with_scores: bool) -> io::Result<Self> {
+    Self::new(BufWriter::new(File::create(path)?), with_scores)
+  }
+}
+
+impl<W: Write, G: Compress> BoardWriter<W, G> {
+  /// Writes the header of a board file to `writer`. If `with_scores` is set,
+  /// every game state must be written with a score.
+  pub fn new(mut writer: W, with_scores: bool) -> io::Result<Self> {
+    let flags = if with_scores { HAS_SCORES } else { 0 };
+    writer.write_all(BOARDS_MAGIC)?;
+    writer.write_all(&BOARDS_VERSION.to_le_bytes())?;
+    writer.write_all(&flags.to_le_bytes())?;
+    Ok(Self {
+      writer,
+      with_scores,
+      buffer: vec![0; G::COMPRESSED_SIZE],
+      _game: PhantomData,
+    })
+  }
+
+  /// Appends `game` to the file, along with `score`, which must be given
+  /// exactly when the file was created with scores.
+  pub fn write(&mut self, game: &G, score: Option<&Score>) -> io::Result<()> {
+    if score.is_some() != self.with_scores {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        if self.with_scores {
+          "Board file needs a score for every game state"
+        } else {
+          "Board file doesn't have scores"
+        },
+      ));
+    }
+
+    game.compress(&mut self.buffer);
+    self
+      .writer
+      .write_all(&(G::COMPRESSED_SIZE as u32).to_le_bytes())?;
+    self.writer.write_all(&self.buffer)?;
+    if let Some(score) = score {
+      self.writer.write_all(&score.to_bytes())?;
+    }
+    Ok(())
+  }
+
+  /// Flushes everything written so far, returning the underlying writer.
+  pub fn into_inner(mut self) -> io::Result<W> {
+    self.writer.flush()?;
+    Ok(self.writer)
+  }
+}
+
+/// Reads a stream of game states written by a `BoardWriter`. The reader is
+/// an iterator over the game states in the file, each with its score if the
+/// file has scores.
+pub struct BoardReader<R, G> {
+  reader: R,
+  with_scores: bool,
+  buffer: Vec<u8>,
+  _game: PhantomData<fn() -> G>,
+}
+
+impl<G: Compress> BoardReader<BufReader<File>, G> {
+  /// Opens the board file at `path`.
+  pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+    Self::new(BufReader::new(File::open(path)?))
+  }
+}
+
+impl<R: Read, G: Compress> BoardReader<R, G> {
+  /// Reads the header of a board file from `reader`.
+  pub fn new(mut reader: R) -> io::Result<Self> {
+    let mut header = [0; 16];
+    reader
+      .read_exact(&mut header)
+      .map_err(|_| invalid_data("Board file is missing its header".into()))?;
+    if &header[0..8] != BOARDS_MAGIC {
+      return Err(invalid_data("Not a board file".into()));
+    }
+    let version = u32::from_le_bytes(header[8..12].try_into().unwrap());
+    if version != BOARDS_VERSION {
+      return Err(invalid_data(format!(
+        "Unsupported board file version {version}, expected {BOARDS_VERSION}"
+      )));
+    }
+    let flags = u32::from_le_bytes(header[12..16].try_into().unwrap());
+
+    Ok(Self {
+      reader,
+      with_scores: flags & HAS_SCORES != 0,
+      buffer: vec![0; G::COMPRESSED_SIZE],
+      _game: PhantomData,
+    })
+  }
+
+  /// True if every game state in the file has a score.
+  pub fn has_scores(&self) -> bool {
+    self.with_scores
+  }
+
+  /// Reads the next game state from the file, or returns `None` at the end
+  /// of the file.
+  pub fn read(&mut self) -> io::Result<Option<(G, Option<Score>)>> {
+    let mut length = [0; 4];
+    // The file may only end between records.
+    match self.reader.read(&mut length[..1])? {
+      0 => return Ok(None),
+      _ => self.reader.read_exact(&mut length[1..])?,
+    }
+    let length = u32::from_le_bytes(length) as usize;
+    if length != G::COMPRESSED_SIZE {
+      return Err(invalid_data(format!(
+        "Board file has a game state of size {length}, expected {}",
+        G::COMPRESSED_SIZE
+      )));
+    }
+
+    self.reader.read_exact(&mut self.buffer)?;
+    let game = G::decompress(&self.buffer)
+      .ok_or_else(|| invalid_data("Board file has an invalid game state".into()))?;
+    let score = if self.with_scores {
+      let mut score = [0; Score::PACKED_SIZE];
+      self.reader.read_exact(&mut score)?;
+      Some(Score::from_bytes(score))
+    } else {
+      None
+    };
+    Ok(Some((game, score)))
+  }
+}
+
+impl<R: Read, G: Compress> Iterator for BoardReader<R, G> {
+  type Item = io::Result<(G, Option<Score>)>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.read().transpose()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::io::ErrorKind;
+
+  use crate::{Compress, Score};
+
+  use super::{BoardReader, BoardWriter};
+
+  #[derive(Debug, PartialEq, Eq)]
+  struct Pair(u8, u8);
+
+  impl Compress for Pair {
+    const COMPRESSED_SIZE: usize = 2;
+
+    fn compress(&self, bytes: &mut [u8]) {
+      bytes.copy_from_slice(&[self.0, self.1]);
+    }
+
+    fn decompress(bytes: &[u8]) -> Option<Self> {
+      (bytes[0] <= bytes[1]).then_some(Pair(bytes[0], bytes[1]))
+    }
+  }
+
+  #[test]
+  fn test_round_trip() {
+    let mut writer = BoardWriter::new(Vec::new(), true).unwrap();
+    writer.write(&Pair(1, 2), Some(&Score::win(3))).unwrap();
+    writer
+      .write(&Pair(4, 4), Some(&Score::guaranteed_tie()))
+      .unwrap();
+    assert_eq!(
+      writer.write(&Pair(0, 0), None).unwrap_err().kind(),
+      ErrorKind::InvalidInput
+    );
+    let bytes = writer.into_inner().unwrap();
+
+    let reader = BoardReader::<_, Pair>::new(bytes.as_slice()).unwrap();
+    assert!(reader.has_scores());
+    let boards: Vec<_> = reader.collect::<Result<_, _>>().unwrap();
+    assert_eq!(
+      boards,
+      vec![
+        (Pair(1, 2), Some(Score::win(3))),
+        (Pair(4, 4), Some(Score::guaranteed_tie())),
+      ]
+    );
+
+    // Truncated files are rejected.
+    let mut reader = BoardReader::<_, Pair>::new(&bytes[..bytes.len() - 1]).unwrap();
+    assert!(reader.read().unwrap().is_some());
+    assert!(reader.read().is_err());
+  }
+
+  #[test]
+  fn test_without_scores() {
+    let mut writer = BoardWriter::new(Vec::new(), false).unwrap();
+    writer.write(&Pair(1, 2), None).unwrap();
+    let mut bytes = writer.into_inner().unwrap();
+
+    let boards: Vec<_> = BoardReader::<_, Pair>::new(bytes.as_slice())
+      .unwrap()
+      .collect::<Result<_, _>>()
+      .unwrap();
+    assert_eq!(boards, vec![(Pair(1, 2), None)]);
+
+    // Invalid game states are rejected.
+    let len = bytes.len();
+    bytes[len - 2] = 3;
+    let mut reader = BoardReader::<_, Pair>::new(bytes.as_slice()).unwrap();
+    assert_eq!(reader.read().unwrap_err().kind(), ErrorKind::InvalidData);
+  }
+}
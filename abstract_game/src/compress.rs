@@ -0,0 +1,14 @@
+/// Game states which can be packed into a fixed number of bytes, e.g. for
+/// writing tables of game states to disk.
+pub trait Compress: Sized {
+  /// The number of bytes in the compressed form of a game state.
+  const COMPRESSED_SIZE: usize;
+
+  /// Writes the compressed form of this game state to `bytes`, which is exactly
+  /// `Self::COMPRESSED_SIZE` bytes long.
+  fn compress(&self, bytes: &mut [u8]);
+
+  /// Reconstructs a game state from the output of `Compress::compress`, or
+  /// returns `None` if `bytes` is not a valid game state.
+  fn decompress(bytes: &[u8]) -> Option<Self>;
+}
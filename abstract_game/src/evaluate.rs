@@ -0,0 +1,10 @@
+use crate::Game;
+
+/// Games with a heuristic estimate of how favorable a game state is, for
+/// comparing positions whose outcome a depth-limited search can't determine.
+pub trait Evaluate: Game {
+  /// Estimates how favorable this game state is for the current player.
+  /// Positive values favor the current player, and negative values favor the
+  /// opponent. The estimate is only meaningful for unfinished game states.
+  fn evaluate(&self) -> i32;
+}
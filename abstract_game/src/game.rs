@@ -1,3 +1,5 @@
+use crate::MoveArena;
+
 pub trait GameMoveGenerator: Sized {
   type Item;
   type Game;
@@ -28,6 +30,9 @@ where
   }
 }
 
+/// The result of a game as reported by `Game::finished`. A game isn't reported
+/// finished just because the player to move has no legal moves, though that
+/// also ends it, see `Game::finished`.
 #[derive(Debug, PartialEq, Eq)]
 pub enum GameResult<PlayerIdentifier> {
   NotFinished,
@@ -45,6 +50,13 @@ pub trait Game: Clone + Sized {
     self.move_generator().to_iter(self)
   }
 
+  /// Appends all moves from this game state to `arena`, returning the moves
+  /// that were added. Once the arena has grown large enough, this does not
+  /// allocate.
+  fn expand_into<'a>(&self, arena: &'a mut MoveArena<Self::Move>) -> &'a [Self::Move] {
+    arena.extend(self.each_move())
+  }
+
   fn make_move(&mut self, m: Self::Move);
 
   /// Returns the `Self::PlayerIdentifier` of the player to make the next move.
@@ -52,14 +64,33 @@ pub trait Game: Clone + Sized {
 
   /// Returns `Some(player_id)` if a player has won, otherwise `None` if no
   /// player has won yet.
+  ///
+  /// A player to move with no legal moves loses the game, but this doesn't
+  /// check for it, since searches call it on every state they make, and
+  /// generating moves is much more expensive. Searches apply the rule when a
+  /// state they expand has no moves, and anything else that needs it should
+  /// check `is_stuck`.
   fn finished(&self) -> GameResult<Self::PlayerIdentifier>;
 
+  /// True if the game isn't `finished`, but the player to move has no legal
+  /// moves, which loses them the game.
+  fn is_stuck(&self) -> bool {
+    self.finished() == GameResult::NotFinished && self.each_move().next().is_none()
+  }
+
   fn with_move(&self, m: Self::Move) -> Self {
     let mut copy = self.clone();
     copy.make_move(m);
     copy
   }
 
+  /// The state after the current player forfeits their turn, leaving
+  /// everything else as is, if the game has an equivalent of passing. Searches
+  /// use this for null-move-style pruning. By default, games have none.
+  fn pass_equivalent(&self) -> Option<Self> {
+    None
+  }
+
   /// Checks each possible move of this game, and returns any move that is an
   /// immediate win for the current player, or `None` if no such move exists.
   fn search_immediate_win(&self) -> Option<Self::Move> {
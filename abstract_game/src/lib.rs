@@ -1,8 +1,24 @@
+mod boards;
+mod compress;
+mod evaluate;
 mod game;
+mod max_n;
+mod move_arena;
 mod packed_score;
+mod proof_number;
+mod repetition;
 mod score;
+mod threats;
 mod util;
 
+pub use boards::*;
+pub use compress::*;
+pub use evaluate::*;
 pub use game::*;
+pub use max_n::*;
+pub use move_arena::*;
 pub use packed_score::*;
+pub use proof_number::*;
+pub use repetition::*;
 pub use score::*;
+pub use threats::*;
@@ -0,0 +1,158 @@
+use std::cmp::Ordering;
+
+use crate::{Game, Score, ScoreValue};
+
+/// A game with any number of players, who take turns in a fixed order. Games
+/// with more than two players aren't zero-sum between the player to move and
+/// "the opponent", so they are scored with `MaxNScore` instead of `Score`.
+///
+/// Two-player games don't need this: `Score`, and the solvers built on it,
+/// are the specialization of max-n scoring to two-player zero-sum games.
+pub trait MultiplayerGame: Game {
+  /// The number of players, which is at least 2.
+  fn player_count(&self) -> usize;
+
+  /// The position of `player` in the turn order, from 0 to `player_count() -
+  /// 1`.
+  fn player_index(&self, player: &Self::PlayerIdentifier) -> usize;
+}
+
+/// The outcome of a game state in a max-n search, where each player chooses
+/// the move best for themself, assuming every other player does the same.
+///
+/// Each player prefers their own win, the sooner the better, over no player
+/// winning, over another player winning, the later the better. Players are
+/// indifferent between which other player wins.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MaxNScore {
+  /// The player with index `player` wins after `plies` more moves. A move
+  /// which wins on the spot has `plies` 1.
+  Win { player: usize, plies: u32 },
+  /// No player can force a win within the search depth. This includes games
+  /// which end in a tie.
+  NoWin,
+}
+
+impl MaxNScore {
+  /// The score of the parent of a game state with this score.
+  pub fn backstep(&self) -> Self {
+    match *self {
+      MaxNScore::Win { player, plies } => MaxNScore::Win {
+        player,
+        plies: plies + 1,
+      },
+      MaxNScore::NoWin => MaxNScore::NoWin,
+    }
+  }
+
+  /// Orders scores by how much player `player` prefers them, with the most
+  /// preferred greatest.
+  pub fn cmp_for(&self, other: &Self, player: usize) -> Ordering {
+    // Ranks the outcome for `player` by tier, breaking ties within a tier by
+    // the number of plies.
+    let rank = |score: &Self| match *score {
+      MaxNScore::Win {
+        player: winner,
+        plies,
+      } if winner == player => (2, -(plies as i64)),
+      MaxNScore::NoWin => (1, 0),
+      MaxNScore::Win { plies, .. } => (0, plies as i64),
+    };
+    rank(self).cmp(&rank(other))
+  }
+
+  /// True if player `player` strictly prefers this score to `other`.
+  pub fn better_for(&self, other: &Self, player: usize) -> bool {
+    self.cmp_for(other, player) == Ordering::Greater
+  }
+
+  /// The max-n score matching the two-player `score` of a game state searched
+  /// to `depth`, where the player to move has index `current_player` of 0 or
+  /// 1.
+  pub fn from_two_player(score: &Score, current_player: usize, depth: u32) -> Self {
+    debug_assert!(current_player < 2);
+    match score.score_at_depth(depth) {
+      ScoreValue::CurrentPlayerWins => MaxNScore::Win {
+        player: current_player,
+        plies: score.turn_count_win(),
+      },
+      ScoreValue::OtherPlayerWins => MaxNScore::Win {
+        player: 1 - current_player,
+        plies: score.turn_count_win(),
+      },
+      ScoreValue::Tie => MaxNScore::NoWin,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::cmp::Ordering;
+
+  use crate::Score;
+
+  use super::MaxNScore;
+
+  #[test]
+  fn test_preference() {
+    let win0_fast = MaxNScore::Win {
+      player: 0,
+      plies: 1,
+    };
+    let win0_slow = MaxNScore::Win {
+      player: 0,
+      plies: 5,
+    };
+    let win1_fast = MaxNScore::Win {
+      player: 1,
+      plies: 2,
+    };
+    let win2_slow = MaxNScore::Win {
+      player: 2,
+      plies: 6,
+    };
+
+    let mut scores = [win2_slow, MaxNScore::NoWin, win0_slow, win1_fast, win0_fast];
+    scores.sort_by(|a, b| a.cmp_for(b, 0));
+    assert_eq!(
+      scores,
+      [win1_fast, win2_slow, MaxNScore::NoWin, win0_slow, win0_fast]
+    );
+
+    // Player 1 ranks player 0's wins like player 2's.
+    assert_eq!(
+      win0_fast.cmp_for(
+        &MaxNScore::Win {
+          player: 2,
+          plies: 1
+        },
+        1
+      ),
+      Ordering::Equal
+    );
+    assert!(win1_fast.better_for(&MaxNScore::NoWin, 1));
+    assert!(!win1_fast.better_for(&MaxNScore::NoWin, 2));
+  }
+
+  #[test]
+  fn test_from_two_player() {
+    assert_eq!(
+      MaxNScore::from_two_player(&Score::win(3), 1, 5),
+      MaxNScore::Win {
+        player: 1,
+        plies: 3
+      }
+    );
+    assert_eq!(
+      MaxNScore::from_two_player(&Score::lose(2), 1, 5),
+      MaxNScore::Win {
+        player: 0,
+        plies: 2
+      }
+    );
+    assert_eq!(
+      MaxNScore::from_two_player(&Score::tie(5), 0, 5),
+      MaxNScore::NoWin
+    );
+  }
+}
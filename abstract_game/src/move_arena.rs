@@ -0,0 +1,67 @@
+use std::ops::{Index, Range};
+
+/// A reusable buffer of moves. Solvers can expand each node's moves into the
+/// same arena, truncating back to where the node's moves began once the node
+/// has been searched, so that move lists don't need to be allocated per node.
+#[derive(Clone, Debug, Default)]
+pub struct MoveArena<M> {
+  moves: Vec<M>,
+}
+
+impl<M> MoveArena<M>
+where
+  M: Copy,
+{
+  pub fn new() -> Self {
+    Self { moves: Vec::new() }
+  }
+
+  pub fn with_capacity(capacity: usize) -> Self {
+    Self {
+      moves: Vec::with_capacity(capacity),
+    }
+  }
+
+  /// The total number of moves in the arena.
+  pub fn len(&self) -> usize {
+    self.moves.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.moves.is_empty()
+  }
+
+  /// Drops all moves after the first `len`, keeping the allocated capacity.
+  pub fn truncate(&mut self, len: usize) {
+    self.moves.truncate(len);
+  }
+
+  pub fn clear(&mut self) {
+    self.moves.clear();
+  }
+
+  /// Appends all moves from `moves` to the end of the arena, returning a slice
+  /// of the moves that were just added.
+  pub fn extend<I>(&mut self, moves: I) -> &[M]
+  where
+    I: IntoIterator<Item = M>,
+  {
+    let start = self.moves.len();
+    self.moves.extend(moves);
+    &self.moves[start..]
+  }
+
+  /// The moves in `range`, which can be reordered in place, e.g. to change the
+  /// order a node's moves are searched in.
+  pub fn moves_mut(&mut self, range: Range<usize>) -> &mut [M] {
+    &mut self.moves[range]
+  }
+}
+
+impl<M> Index<usize> for MoveArena<M> {
+  type Output = M;
+
+  fn index(&self, index: usize) -> &Self::Output {
+    &self.moves[index]
+  }
+}
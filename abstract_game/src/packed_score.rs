@@ -1,6 +1,6 @@
 use std::fmt::{Debug, Display};
 
-use crate::Score;
+use crate::{util::min_u32, Score};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PackedScore<P> {
@@ -39,3 +39,167 @@ where
     write!(f, "{} ({})", self.score(), self.packed_data())
   }
 }
+
+/// A `Score` packed into 16 bits, for tables where memory matters more than
+/// remembering how long distant wins take.
+///
+/// Ties are stored exactly, including guaranteed ties. Wins and losses are
+/// stored exactly up to `PackedScore16::MAX_WIN_DEPTH` moves; deeper ones
+/// saturate to the tie the score proves, which keeps the score compatible with
+/// the original but forgets the win. A later search finds it again.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PackedScore16(u16);
+
+impl PackedScore16 {
+  /// The deepest win or loss that can be stored.
+  pub const MAX_WIN_DEPTH: u32 = 0x7f;
+
+  /// Bits 14 - 15 hold the kind of score, one of the constants below.
+  const KIND_SHIFT: u32 = 14;
+  /// Bits 0 - 11 hold the tie depth.
+  const TIE: u16 = 0;
+  /// Bits 0 - 6 hold the tie depth, and bits 7 - 13 the win depth.
+  const CUR_WINS: u16 = 1;
+  const OTHER_WINS: u16 = 2;
+  const WIN_SHIFT: u32 = 7;
+
+  /// Packs `score`, saturating wins deeper than `MAX_WIN_DEPTH` to ties.
+  pub const fn new(score: &Score) -> Self {
+    let cur_player_wins = score.cur_player_wins();
+    let turn_count_tie = score.turn_count_tie();
+    let turn_count_win = score.turn_count_win();
+
+    if (!cur_player_wins && turn_count_win == 0) || turn_count_win > Self::MAX_WIN_DEPTH {
+      return Self(turn_count_tie as u16);
+    }
+
+    // The tie depth of a score with a win is less than its win depth, except in
+    // scores with no win depth, where the tie depth saturates too.
+    let turn_count_tie = min_u32(turn_count_tie, Self::MAX_WIN_DEPTH);
+    let kind = if cur_player_wins {
+      Self::CUR_WINS
+    } else {
+      Self::OTHER_WINS
+    };
+    Self(
+      (kind << Self::KIND_SHIFT)
+        | ((turn_count_win as u16) << Self::WIN_SHIFT)
+        | turn_count_tie as u16,
+    )
+  }
+
+  /// Unpacks the score.
+  pub const fn score(&self) -> Score {
+    match self.0 >> Self::KIND_SHIFT {
+      Self::TIE => Score::tie(self.0 as u32),
+      kind => Score::new(
+        kind == Self::CUR_WINS,
+        (self.0 as u32) & Self::MAX_WIN_DEPTH,
+        ((self.0 >> Self::WIN_SHIFT) as u32) & Self::MAX_WIN_DEPTH,
+      ),
+    }
+  }
+
+  /// True if `score` is packed without losing any information.
+  pub const fn is_exact(score: &Score) -> bool {
+    let (a, b) = (Self::new(score).score(), score);
+    a.cur_player_wins() == b.cur_player_wins()
+      && a.turn_count_tie() == b.turn_count_tie()
+      && a.turn_count_win() == b.turn_count_win()
+  }
+
+  pub const fn to_bits(&self) -> u16 {
+    self.0
+  }
+
+  pub const fn from_bits(bits: u16) -> Self {
+    Self(bits)
+  }
+}
+
+impl From<&Score> for PackedScore16 {
+  fn from(score: &Score) -> Self {
+    Self::new(score)
+  }
+}
+
+impl From<Score> for PackedScore16 {
+  fn from(score: Score) -> Self {
+    Self::new(&score)
+  }
+}
+
+impl From<PackedScore16> for Score {
+  fn from(packed: PackedScore16) -> Self {
+    packed.score()
+  }
+}
+
+impl Debug for PackedScore16 {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.score())
+  }
+}
+
+impl Display for PackedScore16 {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.score())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{PackedScore, PackedScore16, Score};
+
+  #[test]
+  fn test_sizes() {
+    assert_eq!(std::mem::size_of::<PackedScore<u8>>(), 4);
+    assert_eq!(std::mem::size_of::<PackedScore16>(), 2);
+    // `Score` packs into 3 bytes, but is padded to 4 in memory.
+    assert_eq!(std::mem::size_of::<Score>(), 4);
+  }
+
+  #[test]
+  fn test_round_trip_ties() {
+    for turn_count_tie in 0..=4095 {
+      let score = Score::tie(turn_count_tie);
+      assert!(PackedScore16::is_exact(&score), "{score}");
+      assert_eq!(PackedScore16::new(&score).score(), score);
+    }
+    assert_eq!(
+      PackedScore16::new(&Score::guaranteed_tie()).score(),
+      Score::guaranteed_tie()
+    );
+  }
+
+  #[test]
+  fn test_round_trip_wins() {
+    for cur_player_wins in [true, false] {
+      for turn_count_win in 1..=PackedScore16::MAX_WIN_DEPTH {
+        for turn_count_tie in 0..turn_count_win {
+          let score = Score::new(cur_player_wins, turn_count_tie, turn_count_win);
+          let packed = PackedScore16::new(&score);
+          assert_eq!(packed.score(), score);
+          assert_eq!(PackedScore16::from_bits(packed.to_bits()), packed);
+          assert!(PackedScore16::is_exact(&score));
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_saturation() {
+    for cur_player_wins in [true, false] {
+      for turn_count_win in (PackedScore16::MAX_WIN_DEPTH + 1)..=2047 {
+        for turn_count_tie in [0, 1, turn_count_win / 2, turn_count_win - 1] {
+          let score = Score::new(cur_player_wins, turn_count_tie, turn_count_win);
+          let saturated = PackedScore16::new(&score).score();
+          assert!(!PackedScore16::is_exact(&score));
+          assert_eq!(saturated, Score::tie(turn_count_tie));
+          assert!(saturated.compatible(&score));
+          assert_eq!(saturated.merge(&score), score);
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,356 @@
+use std::{
+  collections::{hash_map::RandomState, HashMap, HashSet},
+  hash::{BuildHasher, Hash},
+};
+
+use crate::{Game, GameResult};
+
+/// Proof and disproof numbers at or above this are infinite.
+const INF: u64 = u64::MAX / 4;
+
+/// The outcome of a proof-number search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Proof {
+  /// The player to move can force a win.
+  Win,
+  /// The player to move can't force a win without repeating a position or
+  /// exceeding the maximum depth, i.e. every line they can take leads to a
+  /// loss, a tie, or a repetition.
+  NoWin,
+  /// The search ran out of nodes before proving either.
+  Unknown,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Numbers {
+  proof: u64,
+  disproof: u64,
+}
+
+impl Numbers {
+  const UNKNOWN: Self = Self {
+    proof: 1,
+    disproof: 1,
+  };
+  const PROVEN: Self = Self {
+    proof: 0,
+    disproof: INF,
+  };
+  const DISPROVEN: Self = Self {
+    proof: INF,
+    disproof: 0,
+  };
+}
+
+/// A depth-first proof-number (df-pn) solver, which proves whether the player
+/// to move can force a win. Unlike a depth-limited minimax search, it expands
+/// the most promising lines first, so it can prove deep forced wins that a
+/// minimax search wouldn't reach.
+///
+/// Game states are keyed by their `Hash`/`Eq` implementations, so a game with
+/// a canonical view (like `OnoroView`) shares proofs between symmetric states,
+/// the same as in `cooperate`'s table.
+pub struct ProofNumberSearch<G, H = RandomState>
+where
+  G: Game,
+{
+  /// Proof numbers of the states explored so far, from the perspective of the
+  /// player trying to win.
+  table: HashMap<G, Numbers, H>,
+  /// States on the path from the root to the state being explored, for
+  /// detecting repetitions.
+  path: HashSet<G, H>,
+  /// The player trying to win.
+  attacker: Option<G::PlayerIdentifier>,
+  max_nodes: u64,
+  max_depth: u32,
+  nodes: u64,
+}
+
+impl<G> ProofNumberSearch<G, RandomState>
+where
+  G: Game + Hash + Eq,
+{
+  /// Constructs a solver which gives up after exploring `max_nodes` states,
+  /// and doesn't look further than `max_depth` moves ahead.
+  pub fn new(max_nodes: u64, max_depth: u32) -> Self {
+    Self::with_hasher(max_nodes, max_depth, RandomState::new())
+  }
+}
+
+impl<G, H> ProofNumberSearch<G, H>
+where
+  G: Game + Hash + Eq,
+  H: BuildHasher + Clone,
+{
+  pub fn with_hasher(max_nodes: u64, max_depth: u32, hasher: H) -> Self {
+    Self {
+      table: HashMap::with_hasher(hasher.clone()),
+      path: HashSet::with_hasher(hasher),
+      attacker: None,
+      max_nodes,
+      max_depth,
+      nodes: 0,
+    }
+  }
+
+  /// The number of states explored by the most recent call to `prove`.
+  pub fn nodes(&self) -> u64 {
+    self.nodes
+  }
+
+  /// Proves whether the player to move in `game` can force a win.
+  pub fn prove(&mut self, game: &G) -> Proof {
+    self.table.clear();
+    self.path.clear();
+    self.attacker = Some(game.current_player());
+    self.nodes = 0;
+
+    let numbers = match self.terminal_numbers(game, 0) {
+      Some(numbers) => numbers,
+      None => self.search(game, 0, Numbers::UNKNOWN, INF, INF),
+    };
+    if numbers.proof == 0 {
+      Proof::Win
+    } else if numbers.disproof == 0 {
+      Proof::NoWin
+    } else {
+      Proof::Unknown
+    }
+  }
+
+  /// A move from `game` that forces a win, if the most recent call to `prove`
+  /// proved that `game` is a win for the player to move.
+  pub fn winning_move(&self, game: &G) -> Option<G::Move> {
+    if self.attacker.as_ref() != Some(&game.current_player()) {
+      return None;
+    }
+    game.each_move().find(|&m| {
+      let child = game.with_move(m);
+      self.terminal_numbers(&child, 0) == Some(Numbers::PROVEN)
+        || self.table.get(&child) == Some(&Numbers::PROVEN)
+    })
+  }
+
+  fn is_attacker(&self, game: &G) -> bool {
+    self.attacker.as_ref() == Some(&game.current_player())
+  }
+
+  /// The proof numbers of `game` if they are known without searching it, i.e.
+  /// it's finished, repeats a state on the current path, or is too deep.
+  fn terminal_numbers(&self, game: &G, depth: u32) -> Option<Numbers> {
+    match game.finished() {
+      GameResult::Win(winner) => {
+        return Some(if self.attacker.as_ref() == Some(&winner) {
+          Numbers::PROVEN
+        } else {
+          Numbers::DISPROVEN
+        });
+      }
+      GameResult::Tie => return Some(Numbers::DISPROVEN),
+      GameResult::NotFinished => {}
+    }
+    if depth >= self.max_depth || self.path.contains(game) {
+      return Some(Numbers::DISPROVEN);
+    }
+    None
+  }
+
+  fn numbers(&self, game: &G, depth: u32) -> Numbers {
+    self
+      .terminal_numbers(game, depth)
+      .or_else(|| self.table.get(game).copied())
+      .unwrap_or(Numbers::UNKNOWN)
+  }
+
+  /// Searches `game` until its proof number reaches `proof_threshold` or its
+  /// disproof number reaches `disproof_threshold`, returning its new proof
+  /// numbers.
+  fn search(
+    &mut self,
+    game: &G,
+    depth: u32,
+    mut numbers: Numbers,
+    proof_threshold: u64,
+    disproof_threshold: u64,
+  ) -> Numbers {
+    let is_attacker = self.is_attacker(game);
+    let children: Vec<_> = game.each_move().map(|m| game.with_move(m)).collect();
+    self.path.insert(game.clone());
+
+    while self.nodes < self.max_nodes {
+      self.nodes += 1;
+
+      // The child with the smallest proof number at the attacker's turn (or
+      // disproof number at the defender's turn) is the most promising one to
+      // search next.
+      let mut best = None;
+      let mut second_best = INF;
+      let mut total = 0;
+      let mut min = INF;
+      for (idx, child) in children.iter().enumerate() {
+        let child_numbers = self.numbers(child, depth + 1);
+        let (minimized, summed) = if is_attacker {
+          (child_numbers.proof, child_numbers.disproof)
+        } else {
+          (child_numbers.disproof, child_numbers.proof)
+        };
+        total = (total + summed).min(INF);
+        if minimized < min {
+          second_best = min;
+          min = minimized;
+          best = Some((idx, child_numbers));
+        } else if minimized < second_best {
+          second_best = minimized;
+        }
+      }
+
+      numbers = if is_attacker {
+        Numbers {
+          proof: min,
+          disproof: total,
+        }
+      } else {
+        Numbers {
+          proof: total,
+          disproof: min,
+        }
+      };
+      let (best_idx, best_numbers) = match best {
+        Some(best) if numbers.proof < proof_threshold && numbers.disproof < disproof_threshold => {
+          best
+        }
+        // Either the thresholds were reached, or there are no moves and
+        // this state was resolved by the loop above.
+        _ => break,
+      };
+
+      let (child_proof_threshold, child_disproof_threshold) = if is_attacker {
+        (
+          proof_threshold.min(second_best.saturating_add(1)),
+          remaining(disproof_threshold, numbers.disproof, best_numbers.disproof),
+        )
+      } else {
+        (
+          remaining(proof_threshold, numbers.proof, best_numbers.proof),
+          disproof_threshold.min(second_best.saturating_add(1)),
+        )
+      };
+      let child = &children[best_idx];
+      let child_numbers = self.search(
+        child,
+        depth + 1,
+        best_numbers,
+        child_proof_threshold,
+        child_disproof_threshold,
+      );
+      self.table.insert(child.clone(), child_numbers);
+    }
+
+    self.path.remove(game);
+    numbers
+  }
+}
+
+/// The threshold for a child whose number is `child` and contributes to a sum
+/// of `total`, such that the sum reaches `threshold` when the child reaches
+/// the returned threshold.
+fn remaining(threshold: u64, total: u64, child: u64) -> u64 {
+  if threshold >= INF {
+    INF
+  } else {
+    (threshold - total + child).min(INF)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{Game, GameMoveGenerator, GameResult};
+
+  use super::{Proof, ProofNumberSearch};
+
+  /// A subtraction game, where players take turns removing 1 or 2 sticks, and
+  /// the player to take the last stick wins. The player to move loses exactly
+  /// when the number of sticks is a multiple of 3.
+  #[derive(Clone, Hash, PartialEq, Eq)]
+  struct Sticks {
+    sticks: u32,
+    first_player: bool,
+  }
+
+  struct SticksMoveGen(u32);
+
+  impl GameMoveGenerator for SticksMoveGen {
+    type Item = u32;
+    type Game = Sticks;
+
+    fn next(&mut self, game: &Sticks) -> Option<u32> {
+      self.0 += 1;
+      (self.0 <= game.sticks.min(2)).then_some(self.0)
+    }
+  }
+
+  impl Game for Sticks {
+    type Move = u32;
+    type MoveGenerator = SticksMoveGen;
+    type PlayerIdentifier = bool;
+
+    fn move_generator(&self) -> SticksMoveGen {
+      SticksMoveGen(0)
+    }
+
+    fn make_move(&mut self, m: u32) {
+      self.sticks -= m;
+      self.first_player = !self.first_player;
+    }
+
+    fn current_player(&self) -> bool {
+      self.first_player
+    }
+
+    fn finished(&self) -> GameResult<bool> {
+      if self.sticks == 0 {
+        // The previous player took the last stick.
+        GameResult::Win(!self.first_player)
+      } else {
+        GameResult::NotFinished
+      }
+    }
+  }
+
+  fn sticks(sticks: u32) -> Sticks {
+    Sticks {
+      sticks,
+      first_player: true,
+    }
+  }
+
+  #[test]
+  fn test_prove_sticks() {
+    for n in 1..=30 {
+      let mut pns = ProofNumberSearch::new(1_000_000, 100);
+      let expected = match n % 3 {
+        0 => Proof::NoWin,
+        _ => Proof::Win,
+      };
+      assert_eq!(pns.prove(&sticks(n)), expected, "{n} sticks");
+
+      if expected == Proof::Win {
+        let m = pns.winning_move(&sticks(n)).unwrap();
+        assert_eq!((n - m) % 3, 0);
+      }
+    }
+  }
+
+  #[test]
+  fn test_limits() {
+    let mut pns = ProofNumberSearch::new(3, 100);
+    assert_eq!(pns.prove(&sticks(30)), Proof::Unknown);
+
+    // The forced win from 7 sticks takes 5 moves.
+    let mut pns = ProofNumberSearch::new(1_000_000, 4);
+    assert_eq!(pns.prove(&sticks(7)), Proof::NoWin);
+    let mut pns = ProofNumberSearch::new(1_000_000, 5);
+    assert_eq!(pns.prove(&sticks(7)), Proof::Win);
+  }
+}
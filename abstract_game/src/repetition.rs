@@ -0,0 +1,146 @@
+use std::{
+  collections::{hash_map::RandomState, HashMap},
+  hash::{BuildHasher, Hash},
+};
+
+use crate::{Game, GameResult};
+
+/// The number of times a state must occur along a line of play for the game
+/// to be drawn.
+pub const DRAW_REPETITIONS: u32 = 3;
+
+/// Tracks the states along a line of play, to detect when one repeats. Once
+/// the pieces of a game like Onoro are all placed, play can cycle forever, so
+/// a state occurring for the `DRAW_REPETITIONS`th time ends the game in a tie.
+///
+/// States are identified by their hash, so games whose `Hash` is the same for
+/// symmetric states (like `OnoroView`) count symmetric states as repetitions
+/// of each other. Distinct states with the same hash are also counted as
+/// repetitions, which for 64-bit hashes is vanishingly rare.
+///
+/// Games are pushed as they are played, or as a search descends, and popped
+/// as a search backtracks.
+#[derive(Clone, Debug)]
+pub struct Repetitions<H = RandomState> {
+  hasher: H,
+  /// The hash of each state along the line, in order.
+  line: Vec<u64>,
+  counts: HashMap<u64, u32>,
+}
+
+impl Repetitions<RandomState> {
+  pub fn new() -> Self {
+    Self::with_hasher(RandomState::new())
+  }
+}
+
+impl Default for Repetitions<RandomState> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<H> Repetitions<H>
+where
+  H: BuildHasher,
+{
+  pub fn with_hasher(hasher: H) -> Self {
+    Self {
+      hasher,
+      line: Vec::new(),
+      counts: HashMap::new(),
+    }
+  }
+
+  /// Adds `game` to the end of the line, returning the number of times it has
+  /// now occurred.
+  pub fn push<G: Hash>(&mut self, game: &G) -> u32 {
+    let hash = self.hasher.hash_one(game);
+    self.line.push(hash);
+    let count = self.counts.entry(hash).or_default();
+    *count += 1;
+    *count
+  }
+
+  /// Removes the last state from the line.
+  pub fn pop(&mut self) {
+    let Some(hash) = self.line.pop() else {
+      return;
+    };
+    let count = self.counts.get_mut(&hash).unwrap();
+    *count -= 1;
+    if *count == 0 {
+      self.counts.remove(&hash);
+    }
+  }
+
+  /// The number of times `game` occurs along the line.
+  pub fn count<G: Hash>(&self, game: &G) -> u32 {
+    self
+      .counts
+      .get(&self.hasher.hash_one(game))
+      .copied()
+      .unwrap_or(0)
+  }
+
+  /// The number of states along the line.
+  pub fn len(&self) -> usize {
+    self.line.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.line.is_empty()
+  }
+
+  /// True if the last state of the line has occurred `DRAW_REPETITIONS`
+  /// times.
+  pub fn is_draw(&self) -> bool {
+    self
+      .line
+      .last()
+      .is_some_and(|hash| self.counts[hash] >= DRAW_REPETITIONS)
+  }
+
+  /// The result of `game`, the last state of the line: a tie if it has
+  /// repeated `DRAW_REPETITIONS` times, and otherwise `game.finished()`.
+  pub fn finished<G: Game>(&self, game: &G) -> GameResult<G::PlayerIdentifier> {
+    if self.is_draw() {
+      GameResult::Tie
+    } else {
+      game.finished()
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Repetitions, DRAW_REPETITIONS};
+
+  #[test]
+  fn test_repetitions() {
+    let mut repetitions = Repetitions::new();
+    assert!(!repetitions.is_draw());
+
+    // A line cycling between two states.
+    for i in 0..2 * DRAW_REPETITIONS - 1 {
+      assert!(!repetitions.is_draw());
+      assert_eq!(repetitions.push(&(i % 2)), i / 2 + 1);
+    }
+    assert!(repetitions.is_draw());
+    assert_eq!(repetitions.count(&0), DRAW_REPETITIONS);
+    assert_eq!(repetitions.count(&1), DRAW_REPETITIONS - 1);
+    assert_eq!(repetitions.count(&2), 0);
+
+    // Backtracking undoes the repetition.
+    repetitions.pop();
+    assert!(!repetitions.is_draw());
+    assert_eq!(repetitions.count(&0), DRAW_REPETITIONS - 1);
+    assert_eq!(repetitions.len(), 2 * DRAW_REPETITIONS as usize - 2);
+
+    while !repetitions.is_empty() {
+      repetitions.pop();
+    }
+    assert_eq!(repetitions.count(&0), 0);
+    assert_eq!(repetitions.count(&1), 0);
+  }
+}
@@ -18,6 +18,9 @@ impl Score {
   const MAX_WIN_DEPTH: u32 = 0x07ff;
   const MAX_TIE_DEPTH: u32 = 0x0fff;
 
+  /// The number of bytes in the serialized form of a score.
+  pub const PACKED_SIZE: usize = 3;
+
   pub const fn new(cur_player_wins: bool, turn_count_tie: u32, turn_count_win: u32) -> Self {
     Self {
       data: Self::pack(cur_player_wins, turn_count_tie, turn_count_win),
@@ -47,11 +50,19 @@ impl Score {
   }
 
   /// Construct a `Score` for no possible forcing win in any number of moves
-  /// into the future.
+  /// into the future, i.e. a proven draw. This is the score of games that end
+  /// in `GameResult::Tie`.
   pub const fn guaranteed_tie() -> Self {
     Score::tie(Self::MAX_TIE_DEPTH)
   }
 
+  /// True if this score is a proven draw: neither player can force a win, no
+  /// matter how long the game goes on.
+  pub const fn is_guaranteed_tie(&self) -> bool {
+    let (_, turn_count_tie, turn_count_win) = Self::unpack(self.data);
+    turn_count_win == 0 && turn_count_tie == Self::MAX_TIE_DEPTH
+  }
+
   /// Used to mark a game state as an ancestor of the current tree being
   /// explored. Will be overwritten with the actual score once its calculation
   /// is finished.
@@ -205,6 +216,19 @@ impl Score {
     Score::new(self.cur_player_wins(), 0, self.turn_count_win())
   }
 
+  /// Serializes the score into `Score::PACKED_SIZE` little-endian bytes.
+  pub const fn to_bytes(&self) -> [u8; Self::PACKED_SIZE] {
+    let [a0, a1] = self.data.0.to_le_bytes();
+    [a0, a1, self.data.1]
+  }
+
+  /// The inverse of `Score::to_bytes`.
+  pub const fn from_bytes([a0, a1, b]: [u8; Self::PACKED_SIZE]) -> Self {
+    Self {
+      data: (u16::from_le_bytes([a0, a1]), b),
+    }
+  }
+
   const fn pack(cur_player_wins: bool, turn_count_tie: u32, turn_count_win: u32) -> (u16, u8) {
     debug_assert!(turn_count_tie <= Self::MAX_TIE_DEPTH);
     debug_assert!(turn_count_win <= Self::MAX_WIN_DEPTH);
@@ -243,12 +267,10 @@ impl Display for Score {
 
     if self == &Self::ancestor() {
       write!(f, "[ancestor]")
+    } else if self.is_guaranteed_tie() {
+      write!(f, "[tie:∞]")
     } else if turn_count_win == 0 {
-      if turn_count_tie == Self::MAX_TIE_DEPTH {
-        write!(f, "[tie:∞]")
-      } else {
-        write!(f, "[tie:{turn_count_tie}]")
-      }
+      write!(f, "[tie:{turn_count_tie}]")
     } else {
       write!(
         f,
@@ -273,6 +295,20 @@ mod tests {
     assert!(!s2.compatible(s1));
   }
 
+  #[test]
+  fn test_bytes_round_trip() {
+    for score in [
+      Score::no_info(),
+      Score::guaranteed_tie(),
+      Score::win(1),
+      Score::lose(2047),
+      Score::new(true, 4095, 2047),
+      Score::new(false, 13, 200),
+    ] {
+      assert_eq!(Score::from_bytes(score.to_bytes()), score);
+    }
+  }
+
   #[test]
   fn test_compatible() {
     check_compatible(&Score::guaranteed_tie(), &Score::guaranteed_tie());
@@ -284,4 +320,25 @@ mod tests {
     check_incompatible(&Score::guaranteed_tie(), &Score::win(10));
     check_incompatible(&Score::guaranteed_tie(), &Score::lose(10));
   }
+
+  #[test]
+  fn test_guaranteed_tie() {
+    let tie = Score::guaranteed_tie();
+    assert!(tie.is_guaranteed_tie());
+    assert!(!Score::tie(10).is_guaranteed_tie());
+    assert!(!Score::new(false, 4095, 3).is_guaranteed_tie());
+
+    // A proven draw stays proven from every ancestor, and settles a search to
+    // any depth.
+    assert_eq!(tie.backstep(), tie);
+    assert!(tie.determined(1));
+    assert!(tie.determined(4095));
+    assert_eq!(Score::tie(10).merge(&tie), tie);
+
+    // A win is better than a draw, and a draw better than a loss, but a tie
+    // that may hide a win deeper down is better than a proven draw.
+    assert!(Score::win(5).better(&tie));
+    assert!(tie.better(&Score::lose(5)));
+    assert!(Score::tie(10).better(&tie));
+  }
 }
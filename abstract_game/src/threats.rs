@@ -0,0 +1,13 @@
+use crate::Game;
+
+/// Games that can find moves which win on the spot, or which stop the
+/// opponent from winning on their next turn, without searching. Searches can
+/// use these to try the most forcing moves first.
+pub trait Threats: Game {
+  /// The current player's moves that win the game immediately.
+  fn immediate_wins(&self) -> Vec<Self::Move>;
+
+  /// The current player's moves that block a move the opponent could win
+  /// with on their next turn.
+  fn forced_blocks(&self) -> Vec<Self::Move>;
+}
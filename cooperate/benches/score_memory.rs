@@ -0,0 +1,120 @@
+//! Compares the memory used by tables of resolved states storing `Score`s
+//! against ones storing `PackedScore16`s, for the key types a table might use:
+//! the full game state, its compressed bytes, or a 64 or 32-bit hash of it.
+//! Also measures the cost of converting between the two score formats.
+//!
+//! Run with `cargo bench --bench score_memory`. Savings depend on the key's
+//! alignment: entries are padded to a multiple of it, which can swallow the
+//! two bytes saved.
+
+use std::{
+  collections::{HashMap, HashSet},
+  hash::Hash,
+  mem::size_of,
+  time::Instant,
+};
+
+use abstract_game::{Compress, Game, GameResult, PackedScore16, Score};
+use onoro::{Onoro16, Onoro16View};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+const STATES: usize = 1 << 16;
+
+/// Distinct states from random playouts of the default start.
+fn states() -> Vec<Onoro16View> {
+  let mut rng = StdRng::seed_from_u64(1234);
+  let mut hashes = HashSet::new();
+  let mut states = Vec::new();
+  while states.len() < STATES {
+    let mut view = Onoro16View::new(Onoro16::default_start());
+    while view.finished() == GameResult::NotFinished && states.len() < STATES {
+      let moves: Vec<_> = view.each_move().collect();
+      view = view.with_move(*moves.choose(&mut rng).unwrap());
+      if hashes.insert(view.canonical_hash()) {
+        states.push(view.clone());
+      }
+    }
+  }
+  states
+}
+
+/// The heap memory of `map`, in bytes per entry, not counting the one control
+/// byte per bucket of the table.
+fn bytes_per_entry<K, V>(map: &HashMap<K, V>) -> f64 {
+  (map.capacity() * size_of::<(K, V)>()) as f64 / map.len() as f64
+}
+
+fn compare<K: Clone + Hash + Eq>(name: &str, keys: &[K]) {
+  let scores: HashMap<_, _> = keys
+    .iter()
+    .cloned()
+    .map(|key| (key, Score::tie(1)))
+    .collect();
+  let packed: HashMap<_, _> = keys
+    .iter()
+    .cloned()
+    .map(|key| (key, PackedScore16::new(&Score::tie(1))))
+    .collect();
+  println!(
+    "{name:>16}: {:>3} -> {:>3} bytes per entry ({:>6.1} -> {:>6.1} with load factor)",
+    size_of::<(K, Score)>(),
+    size_of::<(K, PackedScore16)>(),
+    bytes_per_entry(&scores),
+    bytes_per_entry(&packed),
+  );
+}
+
+fn conversions() {
+  let scores: Vec<_> = (1..=PackedScore16::MAX_WIN_DEPTH)
+    .flat_map(|turn_count_win| {
+      (0..turn_count_win).flat_map(move |turn_count_tie| {
+        [true, false]
+          .map(|cur_player_wins| Score::new(cur_player_wins, turn_count_tie, turn_count_win))
+      })
+    })
+    .collect();
+
+  const ROUNDS: usize = 256;
+  let start = Instant::now();
+  for _ in 0..ROUNDS {
+    for score in &scores {
+      std::hint::black_box(PackedScore16::new(std::hint::black_box(score)).score());
+    }
+  }
+  let elapsed = start.elapsed();
+  println!(
+    "Round trips: {:.2} ns each",
+    elapsed.as_nanos() as f64 / (ROUNDS * scores.len()) as f64
+  );
+}
+
+fn main() {
+  let states = states();
+  compare("Onoro16View", &states);
+  compare(
+    "compressed state",
+    &states
+      .iter()
+      .map(|view| {
+        let mut bytes = [0; Onoro16::COMPRESSED_SIZE];
+        view.onoro().compress(&mut bytes);
+        bytes
+      })
+      .collect::<Vec<_>>(),
+  );
+  compare(
+    "u64 hash",
+    &states
+      .iter()
+      .map(|view| view.canonical_hash())
+      .collect::<Vec<_>>(),
+  );
+  compare(
+    "u32 hash",
+    &states
+      .iter()
+      .map(|view| view.canonical_hash() as u32)
+      .collect::<Vec<_>>(),
+  );
+  conversions();
+}
@@ -0,0 +1,47 @@
+//! Compares the unit-splitting and lazy SMP search strategies, solving the
+//! same Onoro positions with increasing numbers of threads.
+//!
+//! Run with `cargo bench --bench search_strategies`.
+
+use std::collections::hash_map::RandomState;
+
+use cooperate::{solve_with_metrics, Options, SearchStrategy};
+use onoro::{Onoro16, Onoro16View};
+
+const SEARCH_DEPTH: u32 = 10;
+const UNIT_DEPTH: u32 = 4;
+
+fn run(name: &str, game: &Onoro16View, strategy: SearchStrategy, num_threads: u32) {
+  let (solution, metrics) = solve_with_metrics(
+    game,
+    Options {
+      num_threads,
+      search_depth: SEARCH_DEPTH,
+      unit_depth: UNIT_DEPTH,
+      strategy,
+      ..Default::default()
+    },
+    RandomState::new(),
+  );
+  println!(
+    "{name:>8} {:>14} {num_threads:>2} threads: {:>8.1?} ({} nodes, {:.2} Mnodes/s), score {}",
+    format!("{strategy:?}"),
+    metrics.elapsed(),
+    metrics.nodes(),
+    metrics.nodes_per_sec() / 1e6,
+    solution.score
+  );
+}
+
+fn main() {
+  for (name, game) in [
+    ("default", Onoro16View::new(Onoro16::default_start())),
+    ("hex", Onoro16View::new(Onoro16::hex_start())),
+  ] {
+    for num_threads in [1, 2, 4, 8] {
+      for strategy in [SearchStrategy::UnitSplitting, SearchStrategy::LazySmp] {
+        run(name, &game, strategy, num_threads);
+      }
+    }
+  }
+}
@@ -0,0 +1,95 @@
+//! Measures the throughput of the table of resolved states under contention,
+//! for increasing numbers of threads and shards. Each thread looks up random
+//! states from a fixed set, merging a score into one lookup in four, which is
+//! about the mix of a search with a warm table.
+//!
+//! Run with `cargo bench --bench table_contention`. With the `metrics`
+//! feature (on by default), the share of accesses that had to wait for a
+//! shard's lock is reported alongside.
+
+use std::{
+  collections::{hash_map::RandomState, HashSet},
+  thread,
+  time::Instant,
+};
+
+use abstract_game::{Game, GameResult, Score};
+use cooperate::Table;
+use onoro::{Onoro16, Onoro16View};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+const STATES: usize = 1 << 16;
+const OPS_PER_THREAD: usize = 1 << 20;
+
+/// Distinct states from random playouts of the default start.
+fn states() -> Vec<Onoro16View> {
+  let mut rng = StdRng::seed_from_u64(1234);
+  let mut hashes = HashSet::new();
+  let mut states = Vec::new();
+  while states.len() < STATES {
+    let mut view = Onoro16View::new(Onoro16::default_start());
+    while view.finished() == GameResult::NotFinished && states.len() < STATES {
+      let moves: Vec<_> = view.each_move().collect();
+      view = view.with_move(*moves.choose(&mut rng).unwrap());
+      if hashes.insert(view.canonical_hash()) {
+        states.push(view.clone());
+      }
+    }
+  }
+  states
+}
+
+fn run(states: &[Onoro16View], num_threads: u64, shard_bits: u32) {
+  let table = Table::with_shard_bits(RandomState::new(), shard_bits);
+  for state in states {
+    table.update(state.clone(), Score::tie(1));
+  }
+
+  let start = Instant::now();
+  thread::scope(|scope| {
+    for thread_idx in 0..num_threads {
+      let table = &table;
+      scope.spawn(move || {
+        let mut rng = StdRng::seed_from_u64(thread_idx);
+        for op in 0..OPS_PER_THREAD {
+          let state = &states[rng.gen_range(0..states.len())];
+          if op % 4 == 0 {
+            table.update(state.clone(), Score::tie(2));
+          } else {
+            std::hint::black_box(table.get(state));
+          }
+        }
+      });
+    }
+  });
+  let elapsed = start.elapsed();
+
+  let ops = num_threads as usize * OPS_PER_THREAD;
+  let stats = table.shard_stats();
+  let accesses: u64 = stats
+    .iter()
+    .map(|shard| shard.lookups + shard.updates)
+    .sum();
+  let contended: u64 = stats.iter().map(|shard| shard.contended).sum();
+  print!(
+    "{num_threads:>2} threads, {:>4} shards: {:>7.2} Mops/s",
+    table.num_shards(),
+    ops as f64 / elapsed.as_secs_f64() / 1e6
+  );
+  if accesses > 0 {
+    print!(
+      ", {:.2}% contended",
+      100. * contended as f64 / accesses as f64
+    );
+  }
+  println!();
+}
+
+fn main() {
+  let states = states();
+  for num_threads in [1, 4, 16, 32] {
+    for shard_bits in [1, 4, 6, 8] {
+      run(&states, num_threads, shard_bits);
+    }
+  }
+}
@@ -0,0 +1,221 @@
+//! Placement of worker threads and their memory on the machine's CPUs and NUMA
+//! nodes.
+//!
+//! Both kinds of placement are best-effort: on platforms other than Linux, or
+//! if the kernel refuses a request (e.g. inside a restricted container), the
+//! workers run wherever the scheduler puts them, with the default memory
+//! policy.
+
+use std::{fs, path::Path};
+
+const NODE_DIR: &str = "/sys/devices/system/node";
+
+/// Parses a kernel CPU or node list, e.g. `"0-3,8,10-11"`.
+fn parse_list(list: &str) -> Option<Vec<usize>> {
+  let list = list.trim();
+  if list.is_empty() {
+    return Some(Vec::new());
+  }
+  let mut ids = Vec::new();
+  for range in list.split(',') {
+    match range.split_once('-') {
+      Some((first, last)) => ids.extend(first.parse::<usize>().ok()?..=last.parse().ok()?),
+      None => ids.push(range.parse().ok()?),
+    }
+  }
+  Some(ids)
+}
+
+fn read_list(path: impl AsRef<Path>) -> Option<Vec<usize>> {
+  parse_list(&fs::read_to_string(path).ok()?)
+}
+
+/// The CPUs this process may run on, grouped by NUMA node, along with the
+/// nodes that have memory.
+#[derive(Debug, PartialEq, Eq)]
+struct Topology {
+  /// The CPUs of each node, leaving out nodes without any usable CPUs.
+  node_cpus: Vec<Vec<usize>>,
+  memory_nodes: Vec<usize>,
+}
+
+impl Topology {
+  /// Reads the topology from sysfs, falling back to a single node if it isn't
+  /// available.
+  fn detect() -> Self {
+    let allowed = sys::allowed_cpus().ok();
+    let node_ids = read_list(format!("{NODE_DIR}/online")).unwrap_or_default();
+    let node_cpus: Vec<_> = node_ids
+      .iter()
+      .filter_map(|node| read_list(format!("{NODE_DIR}/node{node}/cpulist")))
+      .map(|cpus| {
+        cpus
+          .into_iter()
+          .filter(|cpu| allowed.as_ref().is_none_or(|allowed| allowed.contains(cpu)))
+          .collect::<Vec<_>>()
+      })
+      .filter(|cpus| !cpus.is_empty())
+      .collect();
+
+    Self {
+      node_cpus: if node_cpus.is_empty() {
+        allowed
+          .into_iter()
+          .filter(|cpus| !cpus.is_empty())
+          .collect()
+      } else {
+        node_cpus
+      },
+      memory_nodes: read_list(format!("{NODE_DIR}/has_memory")).unwrap_or_default(),
+    }
+  }
+
+  /// The CPU to pin worker `thread_idx` to. Workers are dealt out to the
+  /// nodes in turn, so each node's memory is used by an equal share of the
+  /// workers, and wrap around once every CPU has a worker.
+  fn worker_cpu(&self, thread_idx: u32) -> Option<usize> {
+    let thread_idx = thread_idx as usize;
+    let num_nodes = self.node_cpus.len();
+    if num_nodes == 0 {
+      return None;
+    }
+    let cpus = &self.node_cpus[thread_idx % num_nodes];
+    Some(cpus[(thread_idx / num_nodes) % cpus.len()])
+  }
+}
+
+/// Where to run each worker of a search and where to allocate its memory.
+pub(crate) struct Placement {
+  topology: Topology,
+  pin_threads: bool,
+  numa_aware: bool,
+}
+
+impl Placement {
+  /// Returns the placement for workers with the given options, or `None` if
+  /// the workers may run anywhere.
+  pub fn new(pin_threads: bool, numa_aware: bool) -> Option<Self> {
+    (pin_threads || numa_aware).then(|| Self {
+      topology: Topology::detect(),
+      pin_threads,
+      numa_aware,
+    })
+  }
+
+  /// Places the calling thread, which is worker `thread_idx`.
+  pub fn place_worker(&self, thread_idx: u32) {
+    if self.pin_threads {
+      if let Some(cpu) = self.topology.worker_cpu(thread_idx) {
+        // Placement is only a hint, so failures are ignored.
+        let _ = sys::pin_to_cpu(cpu);
+      }
+    }
+    if self.numa_aware && self.topology.memory_nodes.len() > 1 {
+      let _ = sys::interleave_memory(&self.topology.memory_nodes);
+    }
+  }
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+  use std::{io, mem};
+
+  /// `MPOL_INTERLEAVE` from `linux/mempolicy.h`, which libc doesn't export.
+  const MPOL_INTERLEAVE: libc::c_int = 3;
+
+  pub fn allowed_cpus() -> io::Result<Vec<usize>> {
+    let mut set: libc::cpu_set_t = unsafe { mem::zeroed() };
+    if unsafe { libc::sched_getaffinity(0, mem::size_of_val(&set), &mut set) } != 0 {
+      return Err(io::Error::last_os_error());
+    }
+    Ok(
+      (0..libc::CPU_SETSIZE as usize)
+        .filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &set) })
+        .collect(),
+    )
+  }
+
+  pub fn pin_to_cpu(cpu: usize) -> io::Result<()> {
+    let mut set: libc::cpu_set_t = unsafe { mem::zeroed() };
+    unsafe { libc::CPU_SET(cpu, &mut set) };
+    if unsafe { libc::sched_setaffinity(0, mem::size_of_val(&set), &set) } != 0 {
+      return Err(io::Error::last_os_error());
+    }
+    Ok(())
+  }
+
+  /// Sets the memory policy of the calling thread to spread the pages it
+  /// allocates evenly across `nodes`.
+  pub fn interleave_memory(nodes: &[usize]) -> io::Result<()> {
+    const BITS: usize = libc::c_ulong::BITS as usize;
+    let max_node = nodes.iter().copied().max().unwrap_or(0);
+    let mut mask = vec![0 as libc::c_ulong; max_node / BITS + 1];
+    for &node in nodes {
+      mask[node / BITS] |= 1 << (node % BITS);
+    }
+    // The kernel ignores the last bit of the mask, so pass one more than the
+    // number of bits in it.
+    let max_nodes = mask.len() * BITS + 1;
+    let result = unsafe {
+      libc::syscall(
+        libc::SYS_set_mempolicy,
+        MPOL_INTERLEAVE,
+        mask.as_ptr(),
+        max_nodes as libc::c_ulong,
+      )
+    };
+    if result != 0 {
+      return Err(io::Error::last_os_error());
+    }
+    Ok(())
+  }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+  use std::io;
+
+  fn unsupported<T>() -> io::Result<T> {
+    Err(io::Error::new(
+      io::ErrorKind::Unsupported,
+      "Thread placement is only supported on Linux",
+    ))
+  }
+
+  pub fn allowed_cpus() -> io::Result<Vec<usize>> {
+    unsupported()
+  }
+
+  pub fn pin_to_cpu(_cpu: usize) -> io::Result<()> {
+    unsupported()
+  }
+
+  pub fn interleave_memory(_nodes: &[usize]) -> io::Result<()> {
+    unsupported()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{parse_list, Topology};
+
+  #[test]
+  fn test_parse_list() {
+    assert_eq!(parse_list("0\n"), Some(vec![0]));
+    assert_eq!(parse_list("0-3,8,10-11"), Some(vec![0, 1, 2, 3, 8, 10, 11]));
+    assert_eq!(parse_list(""), Some(vec![]));
+    assert_eq!(parse_list("0-x"), None);
+  }
+
+  #[test]
+  fn test_worker_cpu() {
+    let topology = Topology {
+      node_cpus: vec![vec![0, 1, 2], vec![8, 9]],
+      memory_nodes: vec![0, 1],
+    };
+    let cpus: Vec<_> = (0..7)
+      .map(|thread_idx| topology.worker_cpu(thread_idx).unwrap())
+      .collect();
+    assert_eq!(cpus, vec![0, 8, 1, 9, 2, 8, 0]);
+  }
+}
@@ -0,0 +1,313 @@
+use std::{
+  fmt::{Debug, Display},
+  hash::Hash,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Condvar, Mutex,
+  },
+  thread::{self, JoinHandle},
+};
+
+use abstract_game::Game;
+
+use crate::{
+  cooperate::{solve_with_table, Options},
+  principal_variation::Solution,
+  table::Table,
+  Metrics, SearchProgress,
+};
+
+#[derive(Default)]
+struct TokenState {
+  cancelled: AtomicBool,
+  /// Mirrors `paused_lock`, so workers can check for a pause without locking.
+  paused: AtomicBool,
+  paused_lock: Mutex<bool>,
+  resumed: Condvar,
+}
+
+/// Lets another thread cancel, pause, or resume a search. The workers check
+/// the token every few thousand states they explore, returning their work and
+/// exiting once it's cancelled, and blocking while it's paused. Clones share
+/// the same state.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+  state: Arc<TokenState>,
+}
+
+impl CancellationToken {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Stops every search observing this token. Cancelling is permanent, and
+  /// also wakes paused searches so they can exit.
+  pub fn cancel(&self) {
+    self.state.cancelled.store(true, Ordering::Relaxed);
+    let _paused = self.state.paused_lock.lock().unwrap();
+    self.state.resumed.notify_all();
+  }
+
+  pub fn is_cancelled(&self) -> bool {
+    self.state.cancelled.load(Ordering::Relaxed)
+  }
+
+  /// Suspends the workers of every search observing this token until
+  /// `resume` is called. Time spent paused still counts towards time limits.
+  pub fn pause(&self) {
+    let mut paused = self.state.paused_lock.lock().unwrap();
+    *paused = true;
+    self.state.paused.store(true, Ordering::Relaxed);
+  }
+
+  pub fn resume(&self) {
+    let mut paused = self.state.paused_lock.lock().unwrap();
+    *paused = false;
+    self.state.paused.store(false, Ordering::Relaxed);
+    self.state.resumed.notify_all();
+  }
+
+  pub fn is_paused(&self) -> bool {
+    self.state.paused.load(Ordering::Relaxed)
+  }
+
+  /// Returns a guard which cancels the token when dropped, e.g. to stop a
+  /// search on behalf of a request when the request is abandoned.
+  pub fn drop_guard(self) -> DropGuard {
+    DropGuard { token: self }
+  }
+
+  /// Blocks while the token is paused, returning true if it's cancelled.
+  pub(crate) fn checkpoint(&self) -> bool {
+    if self.state.paused.load(Ordering::Relaxed) {
+      let paused = self.state.paused_lock.lock().unwrap();
+      let _paused = self
+        .state
+        .resumed
+        .wait_while(paused, |paused| *paused && !self.is_cancelled())
+        .unwrap();
+    }
+    self.is_cancelled()
+  }
+}
+
+/// Cancels its token when dropped. See `CancellationToken::drop_guard`.
+pub struct DropGuard {
+  token: CancellationToken,
+}
+
+impl DropGuard {
+  /// Returns the token without cancelling it.
+  pub fn disarm(self) -> CancellationToken {
+    let token = self.token.clone();
+    std::mem::forget(self);
+    token
+  }
+}
+
+impl Drop for DropGuard {
+  fn drop(&mut self) {
+    self.token.cancel();
+  }
+}
+
+/// A search running in the background, started by `solve_async`. Dropping the
+/// handle cancels the search.
+pub struct SolveHandle<M> {
+  token: CancellationToken,
+  progress: Arc<SearchProgress>,
+  thread: Option<JoinHandle<(Option<Solution<M>>, Metrics)>>,
+}
+
+impl<M> SolveHandle<M> {
+  /// Stops the search. If it had a time limit and has completed a search to
+  /// some depth, `join` still returns the deepest result found.
+  pub fn cancel(&self) {
+    self.token.cancel();
+  }
+
+  pub fn pause(&self) {
+    self.token.pause();
+  }
+
+  pub fn resume(&self) {
+    self.token.resume();
+  }
+
+  /// A token controlling this search, which can be shared with other threads.
+  pub fn token(&self) -> &CancellationToken {
+    &self.token
+  }
+
+  /// The live progress of the search.
+  pub fn progress(&self) -> &Arc<SearchProgress> {
+    &self.progress
+  }
+
+  /// True once the search has finished, so `join` won't block.
+  pub fn is_finished(&self) -> bool {
+    self
+      .thread
+      .as_ref()
+      .is_none_or(|thread| thread.is_finished())
+  }
+
+  /// Waits for the search to finish, returning its solution, or `None` if it
+  /// was cancelled before finding one.
+  pub fn join(self) -> Option<Solution<M>> {
+    self.join_with_metrics().0
+  }
+
+  /// Like `join`, additionally returning the metrics of the search.
+  pub fn join_with_metrics(mut self) -> (Option<Solution<M>>, Metrics) {
+    // A paused search would never finish.
+    self.token.resume();
+    match self.thread.take().unwrap().join() {
+      Ok(result) => result,
+      Err(err) => std::panic::resume_unwind(err),
+    }
+  }
+}
+
+impl<M> Drop for SolveHandle<M> {
+  fn drop(&mut self) {
+    if let Some(thread) = self.thread.take() {
+      self.token.cancel();
+      // The workers exit promptly once cancelled, so this doesn't block for
+      // long, and no search outlives its handle.
+      let _ = thread.join();
+    }
+  }
+}
+
+/// Starts solving `game` in the background, returning a handle which can
+/// cancel, pause, or resume the search, and wait for its result.
+pub fn solve_async<G>(game: &G, options: Options) -> SolveHandle<G::Move>
+where
+  G: Game + Display + Send + Sync + Hash + PartialEq + Eq + 'static,
+  G::Move: Display + Send,
+  G::PlayerIdentifier: Debug,
+{
+  let token = CancellationToken::new();
+  let progress = Arc::new(SearchProgress::new());
+  let thread = {
+    let (game, token, progress) = (game.clone(), token.clone(), progress.clone());
+    thread::spawn(move || {
+      let (solution, _, metrics) = solve_with_table(
+        &game,
+        options,
+        Table::new(),
+        Some(&progress),
+        None,
+        Some(&token),
+      );
+      (solution, metrics)
+    })
+  };
+  SolveHandle {
+    token,
+    progress,
+    thread: Some(thread),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{thread, time::Duration};
+
+  use crate::{
+    cooperate::{Options, TimeLimit},
+    test::{gomoku::Gomoku, tic_tac_toe::Ttt},
+    SearchStrategy,
+  };
+
+  use super::{solve_async, CancellationToken};
+
+  fn options(search_depth: u32, strategy: SearchStrategy) -> Options {
+    Options {
+      num_threads: 2,
+      search_depth,
+      unit_depth: 3,
+      strategy,
+      ..Default::default()
+    }
+  }
+
+  const STRATEGIES: [SearchStrategy; 2] = [SearchStrategy::UnitSplitting, SearchStrategy::LazySmp];
+
+  #[test]
+  fn test_finishes() {
+    for strategy in STRATEGIES {
+      let handle = solve_async(&Ttt::new(), options(9, strategy));
+      let solution = handle.join().unwrap();
+      assert!(solution
+        .score
+        .compatible(&Ttt::new().compute_expected_score(9)));
+    }
+  }
+
+  #[test]
+  fn test_cancel() {
+    for strategy in STRATEGIES {
+      // Far too deep to finish during the test.
+      let handle = solve_async(&Gomoku::new(4, 4, 4), options(16, strategy));
+      thread::sleep(Duration::from_millis(50));
+      assert!(!handle.is_finished());
+      handle.cancel();
+      assert!(handle.join().is_none());
+    }
+  }
+
+  #[test]
+  fn test_cancel_keeps_completed_depths() {
+    let handle = solve_async(
+      &Gomoku::new(4, 4, 4),
+      Options {
+        time_limit: Some(TimeLimit {
+          soft: Duration::from_secs(600),
+          hard: Duration::from_secs(600),
+        }),
+        ..options(16, SearchStrategy::UnitSplitting)
+      },
+    );
+    while handle.progress().report().depth < 3 {
+      thread::sleep(Duration::from_millis(1));
+    }
+    handle.cancel();
+    let solution = handle.join().unwrap();
+    assert!((1..16).contains(&solution.depth));
+  }
+
+  #[test]
+  fn test_pause() {
+    for strategy in STRATEGIES {
+      let handle = solve_async(&Gomoku::new(4, 4, 4), options(16, strategy));
+      thread::sleep(Duration::from_millis(20));
+      handle.pause();
+      // Give the workers time to reach a checkpoint.
+      thread::sleep(Duration::from_millis(50));
+      let nodes = handle.progress().report().nodes;
+      thread::sleep(Duration::from_millis(50));
+      assert_eq!(handle.progress().report().nodes, nodes);
+      assert!(!handle.is_finished());
+
+      handle.resume();
+      // Cancelling a paused search wakes it up so it can exit, as does
+      // dropping the handle.
+      handle.pause();
+      handle.cancel();
+      assert!(handle.join().is_none());
+    }
+  }
+
+  #[test]
+  fn test_drop_guard() {
+    let token = CancellationToken::new();
+    drop(token.clone().drop_guard());
+    assert!(token.is_cancelled());
+
+    let token = CancellationToken::new();
+    let token = token.drop_guard().disarm();
+    assert!(!token.is_cancelled());
+  }
+}
@@ -2,29 +2,101 @@ use std::{
   collections::{hash_map::RandomState, HashSet},
   fmt::{Debug, Display},
   hash::{BuildHasher, Hash},
-  sync::Arc,
+  sync::{
+    mpsc::{self, RecvTimeoutError},
+    Arc,
+  },
   thread,
+  time::{Duration, Instant},
 };
 
 use abstract_game::{Game, Score};
-use rand::prelude::*;
 
 use crate::{
+  affinity::Placement,
+  cancellation::CancellationToken,
   global_data::GlobalData,
+  lazy_smp::run_lazy_smp,
   null_lock::NullLock,
+  principal_variation::{principal_variation, Solution},
+  pruning::Pruning,
   search_worker::{start_worker, WorkerData},
   serial_search::find_best_move_serial_table,
   stack::Stack,
+  table::{Table, TableLimit},
+  Metrics, MoveOrdering, SearchProgress, SearchStrategy,
 };
 
+/// Bounds on how long a search may take.
+#[derive(Clone, Debug)]
+pub struct TimeLimit {
+  /// After this much time has passed, no deeper searches are started, but the
+  /// search in progress is allowed to finish.
+  pub soft: Duration,
+  /// After this much time has passed, the search in progress is abandoned.
+  pub hard: Duration,
+}
+
 #[derive(Clone)]
 pub struct Options {
   /// The number of worker threads to use in the thread pool.
   pub num_threads: u32,
   /// The depth to search the game to.
   pub search_depth: u32,
-  /// The depth to expand to for generating work units.
+  /// The depth to expand to for generating work units. Only used by
+  /// `SearchStrategy::UnitSplitting`.
   pub unit_depth: u32,
+  /// If set, the game is searched with increasing depth up to `search_depth`
+  /// until the time limit is reached, and the result of the deepest completed
+  /// search is returned.
+  pub time_limit: Option<TimeLimit>,
+  /// If set, bounds the memory used by the table of resolved states, evicting
+  /// entries once it is full. Otherwise, the table grows without bound.
+  pub table_limit: Option<TableLimit>,
+  /// If set, each worker thread is pinned to its own CPU, with the workers
+  /// spread evenly across NUMA nodes, so the scheduler doesn't move them away
+  /// from the memory they've touched.
+  pub pin_threads: bool,
+  /// If set, the memory the workers allocate is interleaved across all NUMA
+  /// nodes. The table of resolved states grows from whichever worker inserts
+  /// into it, so without this, its shards end up on arbitrary nodes, and the
+  /// nodes holding the busiest shards are saturated by remote accesses.
+  pub numa_aware: bool,
+  /// How the search is split among the worker threads.
+  pub strategy: SearchStrategy,
+  /// If set, the search is made reproducible for debugging: it runs on a
+  /// single unpinned worker, explores states in a fixed order, and ignores
+  /// `time_limit`. The workers log every state they explore in the search's
+  /// `Metrics` (see `Metrics::visit_log`), so the logs of two searches can be
+  /// compared to find where they diverge. Ties between equally good moves are
+  /// broken by the move ordering, which `ShuffledOrdering` makes seedable.
+  ///
+  /// `table_limit` evicts states by their hash, so searches with a table
+  /// limit are only reproducible with a fixed hasher.
+  pub deterministic: bool,
+  /// Heuristics which skip parts of the search, trading exactness for speed.
+  /// Only used by `SearchStrategy::UnitSplitting`.
+  pub pruning: Pruning,
+}
+
+impl Default for Options {
+  /// A single-threaded, single-ply search with every optional feature turned
+  /// off. Callers are expected to set at least `num_threads` and
+  /// `search_depth`.
+  fn default() -> Self {
+    Self {
+      num_threads: 1,
+      search_depth: 1,
+      unit_depth: 0,
+      time_limit: None,
+      table_limit: None,
+      pin_threads: false,
+      numa_aware: false,
+      strategy: SearchStrategy::default(),
+      deterministic: false,
+      pruning: Pruning::default(),
+    }
+  }
 }
 
 fn generate_frontier<G>(initial_state: G, options: &Options) -> Vec<*mut Stack<G>>
@@ -61,6 +133,7 @@ where
     .collect()
 }
 
+#[cfg(test)]
 fn construct_globals<G, H>(game: &G, options: Options, hasher: H) -> Arc<GlobalData<G, H>>
 where
   G: Game + Display + Hash + PartialEq + Eq + 'static,
@@ -68,74 +141,372 @@ where
   G::PlayerIdentifier: Debug,
   H: BuildHasher + Clone,
 {
-  let globals = Arc::new(GlobalData::with_hasher(
-    options.search_depth,
-    options.num_threads,
+  construct_globals_with_table(
+    game,
+    options,
+    hasher.clone(),
+    Table::with_hasher(hasher),
+    None,
+    None,
+    None,
+  )
+}
+
+fn construct_globals_with_table<G, H>(
+  game: &G,
+  options: Options,
+  hasher: H,
+  table: Table<G, H>,
+  progress: Option<Arc<SearchProgress>>,
+  move_ordering: Option<Arc<dyn MoveOrdering<G>>>,
+  cancellation: Option<CancellationToken>,
+) -> Arc<GlobalData<G, H>>
+where
+  G: Game + Display + Hash + PartialEq + Eq + 'static,
+  G::Move: Display,
+  G::PlayerIdentifier: Debug,
+  H: BuildHasher + Clone,
+{
+  let globals = Arc::new(GlobalData::with_table(
+    &options,
     hasher,
+    table,
+    progress,
+    move_ordering,
+    cancellation,
   ));
 
-  let mut rng = thread_rng();
   for stack in generate_frontier(game.clone(), &options).into_iter() {
-    let rand_idx = rng.gen_range(0..options.num_threads);
     globals
-      .queue(rand_idx)
-      .push(unsafe { NullLock::new(stack) });
+      .work_queues()
+      .inject(unsafe { NullLock::new(stack) });
   }
 
   globals
 }
 
-pub fn solve<G>(game: &G, options: Options) -> Score
-where
-  G: Game + Display + Send + Sync + Hash + PartialEq + Eq + 'static,
-  G::Move: Display,
-  G::PlayerIdentifier: Debug,
-{
-  solve_with_hasher(game, options, RandomState::new())
-}
-
-pub fn solve_with_hasher<G, H>(game: &G, options: Options, hasher: H) -> Score
+/// Runs `options.num_threads` workers on `globals` until all work units have
+/// been resolved, or until `deadline` passes, returning the fused metrics of
+/// all the workers.
+fn run_workers<G, H>(
+  globals: &Arc<GlobalData<G, H>>,
+  options: &Options,
+  deadline: Option<Instant>,
+) -> Metrics
 where
   G: Game + Display + Send + Sync + Hash + PartialEq + Eq + 'static,
   G::Move: Display,
   G::PlayerIdentifier: Debug,
   H: BuildHasher + Clone + Send + Sync + 'static,
 {
-  let globals = construct_globals(game, options.clone(), hasher);
+  let start = Instant::now();
+  // The watchdog tells the workers to stop once the deadline passes, unless
+  // it's notified that the workers finished first.
+  let (done_sender, done_receiver) = mpsc::channel::<()>();
+  let watchdog = deadline.map(|deadline| {
+    let globals = globals.clone();
+    thread::spawn(move || {
+      let timeout = deadline.saturating_duration_since(Instant::now());
+      if let Err(RecvTimeoutError::Timeout) = done_receiver.recv_timeout(timeout) {
+        globals.stop();
+      }
+    })
+  });
+
+  let placement = Placement::new(options.pin_threads, options.numa_aware).map(Arc::new);
   let thread_handles: Vec<_> = (0..options.num_threads)
     .map(|thread_idx| {
       let globals = globals.clone();
+      let placement = placement.clone();
       thread::Builder::new()
         .name(format!("worker_{thread_idx}"))
         .spawn(move || {
-          start_worker(WorkerData::new(thread_idx, globals));
+          if let Some(placement) = placement {
+            placement.place_worker(thread_idx);
+          }
+          start_worker(WorkerData::new(thread_idx, globals))
         })
         .unwrap()
     })
     .collect();
 
+  // Each worker's metrics are only touched by that worker during the search,
+  // and are combined here after it has finished.
   let mut any_bad = false;
+  let mut metrics = Metrics::new();
   for thread in thread_handles.into_iter() {
-    any_bad = thread.join().is_err() || any_bad;
+    match thread.join() {
+      Ok(worker_metrics) => metrics += worker_metrics,
+      Err(_) => any_bad = true,
+    }
   }
   assert!(!any_bad);
 
-  find_best_move_serial_table(game, options.search_depth, globals.resolved_states_table())
-    .0
-    .unwrap()
+  drop(done_sender);
+  if let Some(watchdog) = watchdog {
+    assert!(watchdog.join().is_ok());
+  }
+  metrics.record_elapsed(start.elapsed());
+  metrics
+}
+
+/// Searches `game` to `options.search_depth`, seeding the search with the
+/// scores in `table` and hashing states with its hasher. Progress is reported
+/// to `progress`, and moves are ordered with `move_ordering`, which lazy SMP
+/// searches don't use. Returns the score of `game`, or `None` if the search
+/// was stopped by `deadline` or `cancellation`, along with the table of
+/// resolved states and the metrics of the search.
+fn search<G, H>(
+  game: &G,
+  options: &Options,
+  table: Table<G, H>,
+  deadline: Option<Instant>,
+  progress: Option<&Arc<SearchProgress>>,
+  move_ordering: Option<&Arc<dyn MoveOrdering<G>>>,
+  cancellation: Option<&CancellationToken>,
+) -> (Option<Score>, Table<G, H>, Metrics)
+where
+  G: Game + Display + Send + Sync + Hash + PartialEq + Eq + 'static,
+  G::Move: Display,
+  G::PlayerIdentifier: Debug,
+  H: BuildHasher + Clone + Send + Sync + 'static,
+{
+  let mut table = table;
+  table.set_limit(options.table_limit.clone());
+  if let Some(progress) = progress {
+    progress.set_depth(options.search_depth);
+  }
+
+  let (stopped, table, mut metrics) = match options.strategy {
+    SearchStrategy::UnitSplitting => {
+      let globals = construct_globals_with_table(
+        game,
+        options.clone(),
+        table.hasher().clone(),
+        table,
+        progress.cloned(),
+        move_ordering.cloned(),
+        cancellation.cloned(),
+      );
+      let metrics = run_workers(&globals, options, deadline);
+
+      // All worker threads have been joined, so nothing else is accessing the
+      // abandoned work units.
+      let stopped = unsafe { globals.free_abandoned_stacks() };
+
+      // All worker threads have been joined, so this is the only reference
+      // left.
+      let table = match Arc::try_unwrap(globals) {
+        Ok(globals) => globals.into_resolved_states_table(),
+        Err(_) => panic!("Global data still referenced after all workers finished"),
+      };
+      (stopped, table, metrics)
+    }
+    SearchStrategy::LazySmp => {
+      let (finished, metrics) =
+        run_lazy_smp(game, options, &table, deadline, progress, cancellation);
+      (!finished, table, metrics)
+    }
+  };
+
+  let score = if stopped {
+    None
+  } else {
+    find_best_move_serial_table(game, options.search_depth, &table).0
+  };
+  metrics.record_evictions(table.take_evictions());
+  let verification = table.take_verification_stats();
+  metrics.record_hash_collisions(verification.hash_collisions);
+  metrics.record_false_hits(verification.false_hits);
+  (score, table, metrics)
+}
+
+pub fn solve<G>(game: &G, options: Options) -> Solution<G::Move>
+where
+  G: Game + Display + Send + Sync + Hash + PartialEq + Eq + 'static,
+  G::Move: Display,
+  G::PlayerIdentifier: Debug,
+{
+  solve_with_hasher(game, options, RandomState::new())
+}
+
+pub fn solve_with_hasher<G, H>(game: &G, options: Options, hasher: H) -> Solution<G::Move>
+where
+  G: Game + Display + Send + Sync + Hash + PartialEq + Eq + 'static,
+  G::Move: Display,
+  G::PlayerIdentifier: Debug,
+  H: BuildHasher + Clone + Send + Sync + 'static,
+{
+  solve_with_metrics(game, options, hasher).0
+}
+
+/// Solves `game` the same way as `solve_with_hasher`, additionally returning
+/// the metrics collected by all of the workers.
+pub fn solve_with_metrics<G, H>(
+  game: &G,
+  options: Options,
+  hasher: H,
+) -> (Solution<G::Move>, Metrics)
+where
+  G: Game + Display + Send + Sync + Hash + PartialEq + Eq + 'static,
+  G::Move: Display,
+  G::PlayerIdentifier: Debug,
+  H: BuildHasher + Clone + Send + Sync + 'static,
+{
+  let table = Table::with_hasher(hasher);
+  let (solution, _, metrics) = solve_with_table(game, options, table, None, None, None);
+  // Nothing can cancel the search.
+  (solution.unwrap(), metrics)
+}
+
+/// Solves `game` the same way as `solve_with_metrics`, reporting the progress
+/// of the search to `progress` as it runs. `progress` may be polled from
+/// another thread while this is running.
+pub fn solve_with_progress<G, H>(
+  game: &G,
+  options: Options,
+  hasher: H,
+  progress: &Arc<SearchProgress>,
+) -> (Solution<G::Move>, Metrics)
+where
+  G: Game + Display + Send + Sync + Hash + PartialEq + Eq + 'static,
+  G::Move: Display,
+  G::PlayerIdentifier: Debug,
+  H: BuildHasher + Clone + Send + Sync + 'static,
+{
+  let table = Table::with_hasher(hasher);
+  let (solution, _, metrics) = solve_with_table(game, options, table, Some(progress), None, None);
+  // Nothing can cancel the search.
+  (solution.unwrap(), metrics)
+}
+
+/// Solves `game` the same way as `solve_with_hasher`, but seeds the search with
+/// the scores already in `table`, and hashes states with its hasher. Returns
+/// the solution along with the table of resolved states, which will contain
+/// everything from `table` plus all states resolved during this search, and the
+/// metrics of the search. If given, the progress of the search is reported to
+/// `progress`, moves are ordered by `move_ordering`, and the search can be
+/// cancelled or paused through `cancellation`.
+///
+/// The solution is `None` only if the search was cancelled before finding one.
+/// Searches with a time limit return the result of the deepest search that
+/// completed before the cancellation.
+pub(crate) fn solve_with_table<G, H>(
+  game: &G,
+  options: Options,
+  table: Table<G, H>,
+  progress: Option<&Arc<SearchProgress>>,
+  move_ordering: Option<&Arc<dyn MoveOrdering<G>>>,
+  cancellation: Option<&CancellationToken>,
+) -> (Option<Solution<G::Move>>, Table<G, H>, Metrics)
+where
+  G: Game + Display + Send + Sync + Hash + PartialEq + Eq + 'static,
+  G::Move: Display,
+  G::PlayerIdentifier: Debug,
+  H: BuildHasher + Clone + Send + Sync + 'static,
+{
+  let make_solution = |depth: u32, score: Score, table: &Table<G, H>| Solution {
+    score,
+    principal_variation: principal_variation(game, depth, table),
+    depth,
+  };
+
+  let options = if options.deterministic {
+    Options {
+      num_threads: 1,
+      pin_threads: false,
+      time_limit: None,
+      ..options
+    }
+  } else {
+    options
+  };
+
+  if let Some(progress) = progress {
+    progress.start(options.num_threads);
+  }
+
+  let time_limit = match &options.time_limit {
+    Some(time_limit) => time_limit,
+    None => {
+      let (score, table, metrics) = search(
+        game,
+        &options,
+        table,
+        None,
+        progress,
+        move_ordering,
+        cancellation,
+      );
+      let solution = score.map(|score| make_solution(options.search_depth, score, &table));
+      return (solution, table, metrics);
+    }
+  };
+
+  let start = Instant::now();
+  let mut table = table;
+  let mut metrics = Metrics::new();
+  let mut solution = None;
+  for depth in 1..=options.search_depth {
+    if solution.is_some() && start.elapsed() >= time_limit.soft
+      || cancellation.is_some_and(CancellationToken::is_cancelled)
+    {
+      break;
+    }
+
+    let depth_options = Options {
+      search_depth: depth,
+      unit_depth: options.unit_depth.min(depth - 1),
+      ..options.clone()
+    };
+    // The first search runs to completion unless cancelled, so there is a
+    // result to return.
+    let deadline = solution.as_ref().map(|_| start + time_limit.hard);
+    let (score, depth_table, depth_metrics) = search(
+      game,
+      &depth_options,
+      table,
+      deadline,
+      progress,
+      move_ordering,
+      cancellation,
+    );
+    table = depth_table;
+    metrics += depth_metrics;
+
+    match score {
+      Some(score) => solution = Some(make_solution(depth, score, &table)),
+      None => break,
+    }
+  }
+
+  (solution, table, metrics)
 }
 
 #[cfg(test)]
 mod tests {
-  use std::{collections::hash_map::RandomState, thread, time::SystemTime};
+  use std::{
+    collections::hash_map::RandomState,
+    thread,
+    time::{Duration, Instant, SystemTime},
+  };
 
-  use abstract_game::{Game, GameResult};
+  use abstract_game::{Game, GameResult, ScoreValue};
 
   use crate::{
-    cooperate::construct_globals,
+    cooperate::{construct_globals, solve, solve_with_metrics, TimeLimit},
+    metrics::first_divergence,
     search_worker::{start_worker, WorkerData},
     serial_search::{find_best_move_serial, find_best_move_serial_table},
-    test::{gomoku::Gomoku, nim::Nim, tic_tac_toe::Ttt},
+    table::{ReplacementPolicy, TableLimit},
+    test::{
+      connect_four::ConnectFour,
+      gomoku::Gomoku,
+      hex::{Hex, HexMove},
+      nim::Nim,
+      tic_tac_toe::Ttt,
+    },
+    Metrics, SearchStrategy,
   };
 
   #[test]
@@ -147,7 +518,7 @@ mod tests {
       crate::Options {
         search_depth: STICKS + 1,
         num_threads: 1,
-        unit_depth: 0,
+        ..Default::default()
       },
       RandomState::new(),
     );
@@ -157,7 +528,148 @@ mod tests {
     for sticks in 1..=STICKS {
       let cached_score = globals.resolved_states_table().get(&Nim::new(sticks));
       assert!(cached_score.is_some());
-      assert_eq!(cached_score.unwrap(), Nim::new(sticks).expected_score());
+      assert_eq!(
+        cached_score.unwrap(),
+        Nim::new(sticks).expected_search_score()
+      );
+    }
+  }
+
+  #[test]
+  fn test_placed_workers() {
+    const DEPTH: u32 = 9;
+    // More workers than most machines running the tests have CPUs, so some
+    // share a CPU.
+    let solution = solve(
+      &Ttt::new(),
+      crate::Options {
+        search_depth: DEPTH,
+        num_threads: 64,
+        unit_depth: 2,
+        pin_threads: true,
+        numa_aware: true,
+        ..Default::default()
+      },
+    );
+    assert!(solution
+      .score
+      .compatible(&Ttt::new().compute_expected_score(DEPTH)));
+  }
+
+  #[test]
+  fn test_time_limit_reaches_full_depth() {
+    const DEPTH: u32 = 10;
+    let solution = solve(
+      &Ttt::new(),
+      crate::Options {
+        search_depth: DEPTH,
+        num_threads: 2,
+        unit_depth: 2,
+        time_limit: Some(TimeLimit {
+          soft: Duration::from_secs(600),
+          hard: Duration::from_secs(600),
+        }),
+        ..Default::default()
+      },
+    );
+    assert_eq!(solution.depth, DEPTH);
+    assert!(solution
+      .score
+      .compatible(&Ttt::new().compute_expected_score(DEPTH)));
+  }
+
+  #[test]
+  fn test_time_limit_stops_early() {
+    const DEPTH: u32 = 16;
+    let game = Gomoku::new(4, 4, 4);
+    let start = Instant::now();
+    let solution = solve(
+      &game,
+      crate::Options {
+        search_depth: DEPTH,
+        num_threads: 2,
+        unit_depth: 3,
+        // Only the hard limit can end this search.
+        time_limit: Some(TimeLimit {
+          soft: Duration::from_secs(600),
+          hard: Duration::from_millis(100),
+        }),
+        ..Default::default()
+      },
+    );
+
+    // The full search takes far longer than the time limit, so it must have
+    // been cut short, but the first depth always completes.
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert!((1..DEPTH).contains(&solution.depth));
+    assert!(!solution.principal_variation.is_empty());
+  }
+
+  #[test]
+  fn test_table_limit() {
+    const DEPTH: u32 = 10;
+    for policy in [
+      ReplacementPolicy::DepthPreferred,
+      ReplacementPolicy::AlwaysReplace,
+      ReplacementPolicy::TwoTier,
+    ] {
+      let (solution, metrics) = solve_with_metrics(
+        &Ttt::new(),
+        crate::Options {
+          search_depth: DEPTH,
+          num_threads: 2,
+          unit_depth: 2,
+          // Far fewer than the few thousand states of tic tac toe.
+          table_limit: Some(TableLimit {
+            max_bytes: 16 * 1024,
+            policy,
+          }),
+          ..Default::default()
+        },
+        RandomState::new(),
+      );
+      assert!(solution
+        .score
+        .compatible(&Ttt::new().compute_expected_score(DEPTH)));
+      if Metrics::ENABLED {
+        assert!(metrics.evictions() > 0);
+      }
+    }
+  }
+
+  #[test]
+  fn test_deterministic_replays() {
+    const DEPTH: u32 = 6;
+    for strategy in [SearchStrategy::UnitSplitting, SearchStrategy::LazySmp] {
+      let options = crate::Options {
+        search_depth: DEPTH,
+        num_threads: 4,
+        unit_depth: 2,
+        // Ignored by deterministic searches.
+        time_limit: Some(TimeLimit {
+          soft: Duration::ZERO,
+          hard: Duration::ZERO,
+        }),
+        strategy,
+        deterministic: true,
+        ..Default::default()
+      };
+      let (solution, metrics) =
+        solve_with_metrics(&Gomoku::new(4, 4, 3), options.clone(), RandomState::new());
+      let (replay, replay_metrics) =
+        solve_with_metrics(&Gomoku::new(4, 4, 3), options, RandomState::new());
+
+      assert_eq!(solution.score, replay.score);
+      assert_eq!(solution.principal_variation, replay.principal_variation);
+      assert_eq!(solution.depth, DEPTH);
+      if Metrics::ENABLED {
+        let visit_log = metrics.visit_log().unwrap();
+        assert!(!visit_log.is_empty());
+        assert_eq!(
+          first_divergence(visit_log, replay_metrics.visit_log().unwrap()),
+          None
+        );
+      }
     }
   }
 
@@ -171,6 +683,7 @@ mod tests {
         search_depth: STICKS + 1,
         num_threads: 2,
         unit_depth: 1,
+        ..Default::default()
       },
       RandomState::new(),
     );
@@ -191,7 +704,10 @@ mod tests {
     for sticks in 1..=(STICKS - 1) {
       let cached_score = globals.resolved_states_table().get(&Nim::new(sticks));
       assert!(cached_score.is_some());
-      assert_eq!(cached_score.unwrap(), Nim::new(sticks).expected_score());
+      assert_eq!(
+        cached_score.unwrap(),
+        Nim::new(sticks).expected_search_score()
+      );
     }
   }
 
@@ -206,6 +722,7 @@ mod tests {
         search_depth: DEPTH,
         num_threads: THREADS,
         unit_depth: 1,
+        ..Default::default()
       },
       RandomState::new(),
     );
@@ -259,6 +776,7 @@ mod tests {
         search_depth: DEPTH,
         num_threads: THREADS,
         unit_depth: 2,
+        ..Default::default()
       },
       RandomState::new(),
     );
@@ -301,6 +819,93 @@ mod tests {
     }
   }
 
+  #[test]
+  fn test_connect_four_4x4_p2() {
+    const DEPTH: u32 = 16;
+    let solution = solve(
+      &ConnectFour::new(4, 4, 4),
+      crate::Options {
+        search_depth: DEPTH,
+        num_threads: 2,
+        unit_depth: 3,
+        ..Default::default()
+      },
+    );
+    // Known to be a tie.
+    assert_eq!(solution.score.score_at_depth(DEPTH), ScoreValue::Tie);
+  }
+
+  #[test]
+  fn test_hex_3x3_p2() {
+    const DEPTH: u32 = 9;
+    let solution = solve(
+      &Hex::new(3),
+      crate::Options {
+        search_depth: DEPTH,
+        num_threads: 2,
+        unit_depth: 2,
+        ..Default::default()
+      },
+    );
+    assert_eq!(
+      solution.score.score_at_depth(DEPTH),
+      ScoreValue::CurrentPlayerWins
+    );
+
+    // Only the tiles of the short diagonal and the ends of the middle row win
+    // as first moves, which the serial search must agree with.
+    let hex = Hex::new(3);
+    let winning_moves: Vec<_> = hex
+      .each_move()
+      .filter(|&m| {
+        let score = find_best_move_serial(&hex.with_move(m), DEPTH - 1)
+          .0
+          .unwrap();
+        score.score_at_depth(DEPTH - 1) == ScoreValue::OtherPlayerWins
+      })
+      .collect();
+    assert!(winning_moves.contains(&solution.best_move().unwrap()));
+    assert!(
+      winning_moves
+        == [
+          HexMove::new(2, 0),
+          HexMove::new(0, 1),
+          HexMove::new(1, 1),
+          HexMove::new(2, 1),
+          HexMove::new(0, 2)
+        ],
+      "{}",
+      winning_moves
+        .iter()
+        .map(|m| m.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+    );
+  }
+
+  /// A benchmark with a wider branching factor than connect four.
+  #[test]
+  #[ignore]
+  fn test_hex_4x4_p8() {
+    const DEPTH: u32 = 16;
+    let start = Instant::now();
+    let solution = solve(
+      &Hex::new(4),
+      crate::Options {
+        search_depth: DEPTH,
+        num_threads: 8,
+        unit_depth: 4,
+        ..Default::default()
+      },
+    );
+    println!("Done: {:?}", start.elapsed());
+    // The first player wins Hex on any board.
+    assert_eq!(
+      solution.score.score_at_depth(DEPTH),
+      ScoreValue::CurrentPlayerWins
+    );
+  }
+
   #[test]
   #[ignore]
   fn test_gomoku_4x4_p2() {
@@ -313,6 +918,7 @@ mod tests {
         search_depth: DEPTH,
         num_threads: THREADS,
         unit_depth: 3,
+        ..Default::default()
       },
       RandomState::new(),
     );
@@ -377,6 +983,7 @@ mod tests {
         search_depth: DEPTH,
         num_threads: THREADS,
         unit_depth: 3,
+        ..Default::default()
       },
       RandomState::new(),
     );
@@ -441,6 +1048,7 @@ mod tests {
         search_depth: DEPTH,
         num_threads: THREADS,
         unit_depth: 5,
+        ..Default::default()
       },
       RandomState::new(),
     );
@@ -505,6 +1113,7 @@ mod tests {
         search_depth: DEPTH,
         num_threads: THREADS,
         unit_depth: 5,
+        ..Default::default()
       },
       RandomState::new(),
     );
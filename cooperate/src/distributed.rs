@@ -0,0 +1,501 @@
+//! Solving a game across machines over TCP.
+//!
+//! A `Coordinator` expands the root of the game into work units, the same way
+//! a local search does, and hands each unit to whichever remote worker is free.
+//! Workers, started with `run_worker`, solve each unit with a local search and
+//! report its score back. Along with each score, a worker sends the states it
+//! resolved to at least `share_depth` plies, which the coordinator relays to
+//! every other worker before their next unit, so deep results found by one
+//! worker aren't searched again by the others. Once every unit is resolved,
+//! the coordinator merges their scores into the score of the root.
+//!
+//! Game states and scores are sent in their compressed forms, framed as a
+//! one-byte message tag followed by the length of the payload.
+
+use std::{
+  collections::{HashSet, VecDeque},
+  fmt::{Debug, Display},
+  hash::Hash,
+  io::{self, BufReader, BufWriter, Read, Write},
+  net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+  sync::{Condvar, Mutex},
+  thread,
+};
+
+use abstract_game::{Compress, Game, GameResult, Score};
+
+use crate::{
+  cooperate::solve_with_table, serial_search::find_best_move_serial_table, table::Table, Options,
+};
+
+/// Coordinator -> worker: a unit to solve. The payload is the unit's id, the
+/// depth to solve it to, and the share depth, each as a little-endian `u32`,
+/// followed by the compressed state.
+const UNIT: u8 = 1;
+/// Either direction: resolved states to add to the table, each as a
+/// compressed state followed by its packed score.
+const SHARED: u8 = 2;
+/// Worker -> coordinator: the id of a unit as a little-endian `u32`, followed
+/// by its packed score.
+const SCORE: u8 = 3;
+
+fn invalid_data(message: String) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_message(stream: &mut impl Write, tag: u8, payload: &[u8]) -> io::Result<()> {
+  stream.write_all(&[tag])?;
+  stream.write_all(&(payload.len() as u32).to_le_bytes())?;
+  stream.write_all(payload)?;
+  stream.flush()
+}
+
+/// Reads the next message from `stream`, or returns `None` if the other end
+/// closed the connection between messages.
+fn read_message(stream: &mut impl Read) -> io::Result<Option<(u8, Vec<u8>)>> {
+  let mut header = [0u8; 5];
+  match stream.read_exact(&mut header[..1]) {
+    Ok(()) => {}
+    Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+    Err(err) => return Err(err),
+  }
+  stream.read_exact(&mut header[1..])?;
+  let len = u32::from_le_bytes(header[1..].try_into().unwrap()) as usize;
+  let mut payload = vec![0u8; len];
+  stream.read_exact(&mut payload)?;
+  Ok(Some((header[0], payload)))
+}
+
+fn read_u32(bytes: &[u8], idx: usize) -> io::Result<u32> {
+  bytes
+    .get(4 * idx..4 * idx + 4)
+    .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+    .ok_or_else(|| invalid_data("Message is too short".into()))
+}
+
+fn decompress<G: Compress>(bytes: &[u8]) -> io::Result<G> {
+  G::decompress(bytes).ok_or_else(|| invalid_data("Received an invalid game state".into()))
+}
+
+fn decode_score(bytes: &[u8]) -> io::Result<Score> {
+  bytes
+    .try_into()
+    .map(Score::from_bytes)
+    .map_err(|_| invalid_data(format!("Expected a score of {} bytes", Score::PACKED_SIZE)))
+}
+
+fn encode_entry<G: Compress>(state: &G, score: &Score, bytes: &mut Vec<u8>) {
+  let start = bytes.len();
+  bytes.resize(start + G::COMPRESSED_SIZE, 0);
+  state.compress(&mut bytes[start..]);
+  bytes.extend_from_slice(&score.to_bytes());
+}
+
+fn decode_entries<G: Compress>(bytes: &[u8]) -> io::Result<Vec<(G, Score)>> {
+  let entry_size = G::COMPRESSED_SIZE + Score::PACKED_SIZE;
+  if !bytes.len().is_multiple_of(entry_size) {
+    return Err(invalid_data(format!(
+      "Shared entries are {} bytes long, which isn't a multiple of {entry_size}",
+      bytes.len()
+    )));
+  }
+  bytes
+    .chunks_exact(entry_size)
+    .map(|entry| {
+      let (state, score) = entry.split_at(G::COMPRESSED_SIZE);
+      Ok((decompress(state)?, decode_score(score)?))
+    })
+    .collect()
+}
+
+/// How a `Coordinator` splits up a search.
+#[derive(Clone, Debug)]
+pub struct DistributedOptions {
+  /// The depth to search the game to.
+  pub search_depth: u32,
+  /// The depth to expand the root to for generating work units. Each unit is
+  /// solved by a single worker, to `search_depth - unit_depth`.
+  pub unit_depth: u32,
+  /// Workers share the states they resolve to at least this many plies with
+  /// the other workers. Lower depths share more of the search, at the cost of
+  /// sending more over the network.
+  pub share_depth: u32,
+}
+
+/// The game states `depth` plies from `game`, without duplicates. Finished
+/// states are left out and not expanded, since the serial search that merges
+/// the units' scores scores them itself.
+fn frontier<G>(game: &G, depth: u32) -> Vec<G>
+where
+  G: Game + Hash + Eq,
+{
+  let mut visited_states = HashSet::new();
+  let mut frontier = vec![game.clone()];
+  for _ in 0..depth {
+    frontier = frontier
+      .iter()
+      .flat_map(|state| state.each_move().map(|m| state.with_move(m)))
+      .filter(|child| child.finished() == GameResult::NotFinished)
+      .filter(|child| visited_states.insert(child.clone()))
+      .collect();
+  }
+  frontier
+}
+
+/// The units of a search that are waiting for a worker.
+struct WorkQueue {
+  units: VecDeque<usize>,
+  /// The number of units without a score, including those being solved.
+  unresolved: usize,
+}
+
+impl WorkQueue {
+  /// Waits for a unit to solve, or returns `None` once every unit has a score.
+  fn next(queue: &Mutex<Self>, changed: &Condvar) -> Option<usize> {
+    let mut queue = queue.lock().unwrap();
+    loop {
+      if let Some(idx) = queue.units.pop_front() {
+        return Some(idx);
+      }
+      if queue.unresolved == 0 {
+        return None;
+      }
+      // Another worker may fail, returning its unit to the queue.
+      queue = changed.wait(queue).unwrap();
+    }
+  }
+}
+
+/// Hands out the work units of a search to remote workers, which connect to
+/// it with `run_worker`.
+pub struct Coordinator {
+  listener: TcpListener,
+  workers: Vec<TcpStream>,
+}
+
+impl Coordinator {
+  /// Listens for workers on `addr`.
+  pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+    Ok(Self {
+      listener: TcpListener::bind(addr)?,
+      workers: Vec::new(),
+    })
+  }
+
+  /// The address workers should connect to.
+  pub fn local_addr(&self) -> io::Result<SocketAddr> {
+    self.listener.local_addr()
+  }
+
+  /// The number of connected workers.
+  pub fn num_workers(&self) -> usize {
+    self.workers.len()
+  }
+
+  /// Waits for `count` more workers to connect.
+  pub fn accept_workers(&mut self, count: usize) -> io::Result<()> {
+    for _ in 0..count {
+      let (stream, _) = self.listener.accept()?;
+      stream.set_nodelay(true)?;
+      self.workers.push(stream);
+    }
+    Ok(())
+  }
+
+  /// Solves `game` to `options.search_depth` on the connected workers,
+  /// returning its score.
+  ///
+  /// Workers that disconnect or send malformed messages are dropped, and the
+  /// unit they were solving is handed to another worker. This fails if there
+  /// are no workers left to finish the search.
+  pub fn solve<G>(&mut self, game: &G, options: &DistributedOptions) -> io::Result<Score>
+  where
+    G: Game + Display + Hash + Eq + Compress + Send + Sync,
+  {
+    if options.unit_depth >= options.search_depth {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "The unit depth must be less than the search depth",
+      ));
+    }
+
+    let units = frontier(game, options.unit_depth);
+    let scores = Mutex::new(vec![None; units.len()]);
+    let queue = Mutex::new(WorkQueue {
+      units: (0..units.len()).collect(),
+      unresolved: units.len(),
+    });
+    let queue_changed = Condvar::new();
+    // Entries shared by other workers, waiting to be sent to each worker
+    // before its next unit. They aren't sent right away, since the worker only
+    // reads between units.
+    let pending: Vec<_> = self
+      .workers
+      .iter()
+      .map(|_| Mutex::new(Vec::new()))
+      .collect();
+
+    let failed: Vec<_> = thread::scope(|scope| {
+      let handles: Vec<_> = self
+        .workers
+        .iter()
+        .enumerate()
+        .map(|(worker_idx, stream)| {
+          let (units, scores, pending) = (&units, &scores, &pending);
+          let (queue, queue_changed) = (&queue, &queue_changed);
+          scope.spawn(move || {
+            let mut unit_idx = None;
+            let result = (|| -> io::Result<()> {
+              let mut reader = BufReader::new(stream.try_clone()?);
+              let mut writer = BufWriter::new(stream.try_clone()?);
+              loop {
+                unit_idx = WorkQueue::next(queue, queue_changed);
+                let Some(idx) = unit_idx else {
+                  return Ok(());
+                };
+
+                let shared = std::mem::take(&mut *pending[worker_idx].lock().unwrap());
+                if !shared.is_empty() {
+                  write_message(&mut writer, SHARED, &shared)?;
+                }
+
+                let mut payload = Vec::new();
+                for word in [
+                  idx as u32,
+                  options.search_depth - options.unit_depth,
+                  options.share_depth,
+                ] {
+                  payload.extend_from_slice(&word.to_le_bytes());
+                }
+                payload.resize(payload.len() + G::COMPRESSED_SIZE, 0);
+                units[idx].compress(&mut payload[12..]);
+                write_message(&mut writer, UNIT, &payload)?;
+
+                loop {
+                  let (tag, payload) = read_message(&mut reader)?
+                    .ok_or_else(|| invalid_data("Worker disconnected mid-unit".into()))?;
+                  match tag {
+                    SHARED => {
+                      for (other_idx, other) in pending.iter().enumerate() {
+                        if other_idx != worker_idx {
+                          other.lock().unwrap().extend_from_slice(&payload);
+                        }
+                      }
+                    }
+                    SCORE if read_u32(&payload, 0)? as usize == idx => {
+                      scores.lock().unwrap()[idx] = Some(decode_score(&payload[4..])?);
+                      unit_idx = None;
+                      queue.lock().unwrap().unresolved -= 1;
+                      queue_changed.notify_all();
+                      break;
+                    }
+                    _ => return Err(invalid_data(format!("Unexpected message {tag}"))),
+                  }
+                }
+              }
+            })();
+
+            if let Some(idx) = unit_idx {
+              queue.lock().unwrap().units.push_back(idx);
+              queue_changed.notify_all();
+            }
+            result
+          })
+        })
+        .collect();
+
+      handles
+        .into_iter()
+        .enumerate()
+        .filter_map(|(worker_idx, handle)| handle.join().unwrap().err().map(|_| worker_idx))
+        .collect()
+    });
+
+    for &worker_idx in failed.iter().rev() {
+      self.workers.remove(worker_idx);
+    }
+
+    let table = Table::new();
+    for (unit, score) in units.into_iter().zip(scores.into_inner().unwrap()) {
+      let score = score.ok_or_else(|| {
+        io::Error::new(
+          io::ErrorKind::NotConnected,
+          "Workers disconnected before the search finished",
+        )
+      })?;
+      table.update(unit, score);
+    }
+    Ok(
+      find_best_move_serial_table(game, options.search_depth, &table)
+        .0
+        .unwrap(),
+    )
+  }
+}
+
+/// Connects to the coordinator at `coordinator` and solves the units it sends
+/// until it disconnects. Each unit is searched with `options`, except for the
+/// search depth, which is chosen by the coordinator.
+///
+/// The table of resolved states is kept between units, so later units reuse
+/// the results of earlier ones and of other workers.
+pub fn run_worker<G>(coordinator: impl ToSocketAddrs, options: Options) -> io::Result<()>
+where
+  G: Game + Display + Send + Sync + Hash + Eq + Compress + 'static,
+  G::Move: Display,
+  G::PlayerIdentifier: Debug,
+{
+  let stream = TcpStream::connect(coordinator)?;
+  stream.set_nodelay(true)?;
+  let mut reader = BufReader::new(stream.try_clone()?);
+  let mut writer = BufWriter::new(stream);
+
+  let mut table = Table::new();
+  // States already sent to or received from the coordinator, which aren't
+  // sent again.
+  let mut shared = HashSet::new();
+  while let Some((tag, payload)) = read_message(&mut reader)? {
+    match tag {
+      SHARED => {
+        for (state, score) in decode_entries::<G>(&payload)? {
+          table.update(state.clone(), score);
+          shared.insert(state);
+        }
+      }
+      UNIT => {
+        let unit_idx = read_u32(&payload, 0)?;
+        let depth = read_u32(&payload, 1)?;
+        let share_depth = read_u32(&payload, 2)?;
+        let unit: G = decompress(&payload[12..])?;
+
+        let unit_options = Options {
+          search_depth: depth,
+          unit_depth: options.unit_depth.min(depth - 1),
+          time_limit: None,
+          ..options.clone()
+        };
+        let (solution, unit_table, _) =
+          solve_with_table(&unit, unit_options, table, None, None, None);
+        table = unit_table;
+        // Nothing can cancel the search.
+        let solution = solution.unwrap();
+
+        let mut entries = Vec::new();
+        for entry in table.table().iter() {
+          if entry.value().determined_depth() >= share_depth && !shared.contains(entry.key()) {
+            encode_entry(entry.key(), entry.value(), &mut entries);
+            shared.insert(entry.key().clone());
+          }
+        }
+        if !entries.is_empty() {
+          write_message(&mut writer, SHARED, &entries)?;
+        }
+
+        let mut payload = unit_idx.to_le_bytes().to_vec();
+        payload.extend_from_slice(&solution.score.to_bytes());
+        write_message(&mut writer, SCORE, &payload)?;
+      }
+      _ => return Err(invalid_data(format!("Unexpected message {tag}"))),
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{net::TcpStream, thread};
+
+  use crate::{test::tic_tac_toe::Ttt, Options};
+
+  use super::{run_worker, Coordinator, DistributedOptions};
+
+  fn worker_options() -> Options {
+    Options {
+      num_threads: 2,
+      search_depth: 0,
+      unit_depth: 1,
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn test_distributed_ttt() {
+    const WORKERS: usize = 3;
+    const DEPTH: u32 = 9;
+
+    let mut coordinator = Coordinator::bind("127.0.0.1:0").unwrap();
+    let addr = coordinator.local_addr().unwrap();
+    let workers: Vec<_> = (0..WORKERS)
+      .map(|_| thread::spawn(move || run_worker::<Ttt>(addr, worker_options())))
+      .collect();
+    coordinator.accept_workers(WORKERS).unwrap();
+
+    // The workers stay connected between searches.
+    for unit_depth in 1..=3 {
+      let score = coordinator
+        .solve(
+          &Ttt::new(),
+          &DistributedOptions {
+            search_depth: DEPTH,
+            unit_depth,
+            share_depth: 2,
+          },
+        )
+        .unwrap();
+      assert!(
+        score.compatible(&Ttt::new().compute_expected_score(DEPTH)),
+        "unit depth {unit_depth}: {score}"
+      );
+    }
+
+    drop(coordinator);
+    for worker in workers {
+      assert!(worker.join().unwrap().is_ok());
+    }
+  }
+
+  #[test]
+  fn test_disconnected_worker() {
+    const DEPTH: u32 = 8;
+
+    let mut coordinator = Coordinator::bind("127.0.0.1:0").unwrap();
+    let addr = coordinator.local_addr().unwrap();
+    // Disconnects without solving anything, so its unit must be solved by the
+    // other worker.
+    drop(TcpStream::connect(addr).unwrap());
+    coordinator.accept_workers(1).unwrap();
+    let worker = thread::spawn(move || run_worker::<Ttt>(addr, worker_options()));
+    coordinator.accept_workers(1).unwrap();
+
+    let score = coordinator
+      .solve(
+        &Ttt::new(),
+        &DistributedOptions {
+          search_depth: DEPTH,
+          unit_depth: 2,
+          share_depth: 2,
+        },
+      )
+      .unwrap();
+    assert!(score.compatible(&Ttt::new().compute_expected_score(DEPTH)));
+    assert_eq!(coordinator.num_workers(), 1);
+
+    drop(coordinator);
+    assert!(worker.join().unwrap().is_ok());
+  }
+
+  #[test]
+  fn test_unit_depth_too_deep() {
+    let mut coordinator = Coordinator::bind("127.0.0.1:0").unwrap();
+    assert!(coordinator
+      .solve(
+        &Ttt::new(),
+        &DistributedOptions {
+          search_depth: 2,
+          unit_depth: 2,
+          share_depth: 0,
+        },
+      )
+      .is_err());
+  }
+}
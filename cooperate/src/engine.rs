@@ -0,0 +1,355 @@
+use std::{
+  collections::hash_map::RandomState,
+  fmt::{Debug, Display},
+  hash::{BuildHasher, Hash},
+  io,
+  path::Path,
+  sync::Arc,
+};
+
+use abstract_game::{Compress, Evaluate, Game, Score};
+
+use crate::{
+  cancellation::CancellationToken,
+  cooperate::{solve_with_table, Options},
+  principal_variation::{best_move, best_move_by_evaluation, move_scores, principal_variation},
+  proof::Certificate,
+  table::Table,
+  tablebase::Tablebase,
+  Metrics, MoveOrdering, SearchProgress,
+};
+
+/// Describes how much of the engine's cached analysis carried over to a new
+/// root position.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RerootStats {
+  /// The number of resolved game states retained in the table.
+  pub retained_states: usize,
+  /// The score of the new root, if it had already been resolved to some degree
+  /// by a previous search.
+  pub root_score: Option<Score>,
+}
+
+/// A solver which keeps its table of resolved states between searches. When
+/// the engine plays a move and the opponent replies, most of the analysis
+/// below the played line is still valid, so subsequent searches from the new
+/// root can reuse it instead of starting from scratch.
+pub struct Engine<G, H = RandomState>
+where
+  G: Game,
+{
+  options: Options,
+  hasher: H,
+  root: G,
+  /// Scores are properties of game states, not of the path taken to reach
+  /// them, so every entry in this table stays valid after re-rooting.
+  table: Table<G, H>,
+  /// The metrics collected by the most recent search.
+  metrics: Metrics,
+  /// The live progress of the search in progress, or of the most recent one.
+  progress: Arc<SearchProgress>,
+  /// The depth the most recent search reached, which may be less than
+  /// `options.search_depth` if it had a time limit.
+  solved_depth: u32,
+  /// If set, searches explore moves in the order this decides.
+  move_ordering: Option<Arc<dyn MoveOrdering<G>>>,
+}
+
+impl<G> Engine<G, RandomState>
+where
+  G: Game + Display + Send + Sync + Hash + PartialEq + Eq + 'static,
+  G::Move: Display,
+  G::PlayerIdentifier: Debug,
+{
+  pub fn new(root: G, options: Options) -> Self {
+    Self::with_hasher(root, options, RandomState::new())
+  }
+}
+
+impl<G, H> Engine<G, H>
+where
+  G: Game + Display + Send + Sync + Hash + PartialEq + Eq + 'static,
+  G::Move: Display,
+  G::PlayerIdentifier: Debug,
+  H: BuildHasher + Clone + Send + Sync + 'static,
+{
+  pub fn with_hasher(root: G, options: Options, hasher: H) -> Self {
+    Self {
+      solved_depth: options.search_depth,
+      options,
+      table: Table::with_hasher(hasher.clone()),
+      hasher,
+      root,
+      metrics: Metrics::new(),
+      progress: Arc::new(SearchProgress::new()),
+      move_ordering: None,
+    }
+  }
+
+  /// The position the next search will start from.
+  pub fn root(&self) -> &G {
+    &self.root
+  }
+
+  pub fn options(&self) -> &Options {
+    &self.options
+  }
+
+  pub fn set_options(&mut self, options: Options) {
+    self.options = options;
+  }
+
+  /// Sets the order searches explore moves in, or restores the order moves
+  /// are generated in if `None`.
+  pub fn set_move_ordering(&mut self, move_ordering: Option<Arc<dyn MoveOrdering<G>>>) {
+    self.move_ordering = move_ordering;
+  }
+
+  /// The depth the most recent search reached, which is less than
+  /// `options().search_depth` if a time limit ended it early.
+  pub fn solved_depth(&self) -> u32 {
+    self.solved_depth
+  }
+
+  /// The number of resolved game states cached by the engine.
+  pub fn cached_states(&self) -> usize {
+    self.table.len()
+  }
+
+  /// The metrics collected by the most recent call to `solve`.
+  pub fn metrics(&self) -> &Metrics {
+    &self.metrics
+  }
+
+  /// A handle to the live progress of the engine's searches, which can be
+  /// polled from another thread while `solve` is running.
+  pub fn progress(&self) -> Arc<SearchProgress> {
+    self.progress.clone()
+  }
+
+  /// Adds the exact scores of `tablebase` to the engine's cache, so searches
+  /// stop as soon as they reach a state in the tablebase.
+  pub fn add_tablebase(&mut self, tablebase: &Tablebase<G, H>) {
+    self.table.extend(tablebase.table());
+  }
+
+  /// Solves the current root position, caching all resolved states for future
+  /// searches.
+  pub fn solve(&mut self) -> Score {
+    // Nothing can cancel the search.
+    self.solve_cancellable(None).unwrap()
+  }
+
+  /// Like `solve`, but stops early if `cancellation` is cancelled, and pauses
+  /// while it's paused. Returns `None` if the search was cancelled before
+  /// finding a score, though states resolved before then are still cached.
+  pub fn solve_cancellable(&mut self, cancellation: Option<&CancellationToken>) -> Option<Score> {
+    let table = std::mem::replace(&mut self.table, Table::with_hasher(self.hasher.clone()));
+    let (solution, table, metrics) = solve_with_table(
+      &self.root,
+      self.options.clone(),
+      table,
+      Some(&self.progress),
+      self.move_ordering.as_ref(),
+      cancellation,
+    );
+    self.table = table;
+    self.metrics = metrics;
+    let solution = solution?;
+    self.solved_depth = solution.depth;
+    Some(solution.score)
+  }
+
+  /// The scores of every move from the root position, from the perspective of
+  /// the player making the move, as found by the most recent search. Returns
+  /// `None` if the root hasn't been solved.
+  pub fn move_scores(&self) -> Option<Vec<(G::Move, Score)>> {
+    self.table.get(&self.root)?;
+    Some(move_scores(&self.root, self.solved_depth, &self.table))
+  }
+
+  /// The best move from the root position found by the most recent search, or
+  /// `None` if the root hasn't been solved or has no legal moves.
+  pub fn best_move(&self) -> Option<G::Move> {
+    self.table.get(&self.root)?;
+    best_move(&self.root, self.solved_depth, &self.table).map(|(m, _)| m)
+  }
+
+  /// The line of best play from the root position found by the most recent
+  /// search, which is empty if the root hasn't been solved.
+  pub fn principal_variation(&self) -> Vec<G::Move> {
+    if self.table.get(&self.root).is_none() {
+      return Vec::new();
+    }
+    principal_variation(&self.root, self.solved_depth, &self.table)
+  }
+
+  /// Moves the root of the engine to `root`, which is typically a descendant
+  /// of the previous root. The cached analysis is kept, and the returned stats
+  /// report how much of it applies to the new root.
+  pub fn reroot(&mut self, root: G) -> RerootStats {
+    self.root = root;
+    RerootStats {
+      retained_states: self.table.len(),
+      root_score: self.table.get(&self.root),
+    }
+  }
+
+  /// Makes move `m` from the current root, re-rooting the engine at the
+  /// resulting position.
+  pub fn make_move(&mut self, m: G::Move) -> RerootStats {
+    let root = self.root.with_move(m);
+    self.reroot(root)
+  }
+
+  /// Builds a certificate for the score of the root position from the cached
+  /// analysis, or returns `None` if the root hasn't been solved.
+  pub fn certificate(&self) -> Option<Certificate> {
+    Certificate::from_table(&self.root, &self.table)
+  }
+
+  /// Drops all cached analysis.
+  pub fn clear(&mut self) {
+    self.table = Table::with_hasher(self.hasher.clone());
+  }
+}
+
+impl<G, H> Engine<G, H>
+where
+  G: Game + Display + Hash + Eq + Evaluate,
+  H: BuildHasher + Clone,
+{
+  /// Like `best_move`, but chooses between moves the most recent search found
+  /// equally good by the heuristic evaluation of the states they lead to.
+  pub fn best_move_by_evaluation(&self) -> Option<G::Move> {
+    self.table.get(&self.root)?;
+    best_move_by_evaluation(&self.root, self.solved_depth, &self.table).map(|(m, _)| m)
+  }
+}
+
+impl<G, H> Engine<G, H>
+where
+  G: Game + Hash + Eq + Compress,
+  H: BuildHasher + Clone,
+{
+  /// Saves the engine's cached analysis to `path`, so that it can be resumed
+  /// later with `Engine::load_table`.
+  pub fn save_table(&self, path: impl AsRef<Path>) -> io::Result<()> {
+    self.table.save(path)
+  }
+
+  /// Merges analysis saved by `Engine::save_table` into the engine's cache,
+  /// returning the number of game states read.
+  pub fn load_table(&mut self, path: impl AsRef<Path>) -> io::Result<usize> {
+    self.table.load(path)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use abstract_game::Game;
+
+  use crate::{test::tic_tac_toe::Ttt, Metrics, Options};
+
+  use super::Engine;
+
+  const DEPTH: u32 = 10;
+
+  fn options() -> Options {
+    Options {
+      num_threads: 2,
+      search_depth: DEPTH,
+      unit_depth: 1,
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn test_ttt_reroot() {
+    let mut engine = Engine::new(Ttt::new(), options());
+    let score = engine.solve();
+    let expected_score = Ttt::new().compute_expected_score(DEPTH);
+    assert!(score.compatible(&expected_score));
+
+    let cached_states = engine.cached_states();
+    assert!(cached_states > 0);
+
+    // Play two moves, which should land on a position that was already
+    // resolved by the first search.
+    let m1 = engine.root().each_move().next().unwrap();
+    engine.make_move(m1);
+    let m2 = engine.root().each_move().next().unwrap();
+    let stats = engine.make_move(m2);
+    assert_eq!(stats.retained_states, cached_states);
+    assert!(stats.root_score.is_some());
+
+    let expected_score = engine.root().compute_expected_score(DEPTH);
+    let score = engine.solve();
+    assert!(
+      score.compatible(&expected_score),
+      "Expect computed score {} to be compatible with true score {}",
+      score,
+      expected_score
+    );
+    assert!(engine.cached_states() >= cached_states);
+  }
+
+  #[test]
+  fn test_best_move() {
+    let mut engine = Engine::new(Ttt::new(), options());
+    let score = engine.solve();
+
+    let move_scores = engine.move_scores().unwrap();
+    assert_eq!(move_scores.len(), 9);
+
+    let m = engine.best_move().unwrap();
+    let child_score = Ttt::new()
+      .with_move(m)
+      .compute_expected_score(DEPTH - 1)
+      .backstep();
+    assert!(child_score.compatible(&score));
+  }
+
+  #[test]
+  fn test_best_move_by_evaluation() {
+    let mut engine = Engine::new(
+      Ttt::new(),
+      Options {
+        search_depth: 2,
+        ..options()
+      },
+    );
+    engine.solve();
+
+    // No move decides the game within two moves, and the center is on the most
+    // lines.
+    let m = engine.best_move_by_evaluation().unwrap();
+    assert_eq!(m.to_string(), "(1, 1)");
+  }
+
+  #[test]
+  fn test_progress() {
+    let mut engine = Engine::new(Ttt::new(), options());
+    let progress = engine.progress();
+    engine.solve();
+
+    let report = progress.report();
+    assert_eq!(report.depth, DEPTH);
+    assert_eq!(report.thread_utilization.len(), 2);
+    if Metrics::ENABLED {
+      assert_eq!(report.nodes, engine.metrics().nodes());
+      assert!(report.nodes > 0);
+      assert!(engine.metrics().depth_distribution().iter().sum::<u64>() > 0);
+    }
+  }
+
+  #[test]
+  fn test_clear() {
+    let mut engine = Engine::new(Ttt::new(), options());
+    engine.solve();
+    assert!(engine.certificate().is_some());
+    engine.clear();
+    assert!(engine.certificate().is_none());
+    assert_eq!(engine.cached_states(), 0);
+  }
+}
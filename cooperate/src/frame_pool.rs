@@ -0,0 +1,133 @@
+use abstract_game::Game;
+
+use crate::{stack::StackFrame, Metrics};
+
+/// The most buffers of each kind a `FramePool` holds on to. Only as many work
+/// units as a worker has finished since it last started one are returned to
+/// its pool, so this is rarely reached, but it bounds the memory a worker can
+/// hoard after a burst of finished units.
+const MAX_POOLED_BUFFERS: usize = 64;
+
+/// A free list of the memory work units' stack frames live in, owned by a
+/// single worker. Units take the storage for their frames from the pool of the
+/// worker that starts searching them, and give it back to the pool of the
+/// worker that finishes them, so after the first few units, deep searches
+/// reuse frame storage instead of going to the allocator for every unit.
+///
+/// Buffers move freely between workers along with the units that hold them,
+/// so the pool itself needs no synchronization.
+pub struct FramePool<G>
+where
+  G: Game,
+{
+  frames: Vec<Vec<StackFrame<G>>>,
+  move_buffers: Vec<Vec<G::Move>>,
+}
+
+impl<G> FramePool<G>
+where
+  G: Game,
+{
+  pub fn new() -> Self {
+    Self {
+      frames: Vec::new(),
+      move_buffers: Vec::new(),
+    }
+  }
+
+  /// Empty storage for at least `capacity` stack frames.
+  pub fn take_frames(&mut self, capacity: usize, metrics: &mut Metrics) -> Vec<StackFrame<G>> {
+    match self.frames.pop() {
+      Some(mut frames) => {
+        metrics.record_frame_reuse();
+        frames.reserve(capacity);
+        frames
+      }
+      None => {
+        metrics.record_frame_allocation();
+        Vec::with_capacity(capacity)
+      }
+    }
+  }
+
+  /// An empty buffer for the moves of a stack frame.
+  pub fn take_move_buffer(&mut self, metrics: &mut Metrics) -> Vec<G::Move> {
+    match self.move_buffers.pop() {
+      Some(buffer) => {
+        metrics.record_frame_reuse();
+        buffer
+      }
+      None => {
+        metrics.record_frame_allocation();
+        Vec::new()
+      }
+    }
+  }
+
+  pub fn recycle_frames(&mut self, mut frames: Vec<StackFrame<G>>) {
+    if self.frames.len() < MAX_POOLED_BUFFERS && frames.capacity() != 0 {
+      frames.clear();
+      self.frames.push(frames);
+    }
+  }
+
+  pub fn recycle_move_buffer(&mut self, mut buffer: Vec<G::Move>) {
+    if self.move_buffers.len() < MAX_POOLED_BUFFERS && buffer.capacity() != 0 {
+      buffer.clear();
+      self.move_buffers.push(buffer);
+    }
+  }
+}
+
+impl<G> Default for FramePool<G>
+where
+  G: Game,
+{
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use abstract_game::Score;
+
+  use crate::{
+    stack::{Stack, StackFrame},
+    test::nim::Nim,
+    Metrics,
+  };
+
+  use super::FramePool;
+
+  #[test]
+  fn test_reuse() {
+    let mut pool = FramePool::<Nim>::new();
+    let mut metrics = Metrics::new();
+
+    for _ in 0..3 {
+      let mut stack = Stack::make_root(Nim::new(10), 8);
+      stack.prepare(&mut pool, &mut metrics);
+      for sticks in 5..10 {
+        stack.push(Nim::new(sticks));
+        stack.order_bottom_moves(&mut pool, &mut metrics, |_, moves| moves.reverse());
+        stack.pop_with_backstepped_score(Score::guaranteed_tie());
+      }
+      stack.pop_with_backstepped_score(Score::guaranteed_tie());
+      stack.recycle(&mut pool);
+    }
+
+    if Metrics::ENABLED {
+      // Only the first unit allocates, for its frames and the moves of the
+      // first frame it orders. Later frames reuse the move buffer of the frame
+      // popped before them, and later units the memory of the first.
+      assert_eq!(metrics.frame_allocations(), 2);
+      assert_eq!(metrics.frame_reuses(), 4 + 2 * (2 + 4));
+    }
+
+    let frames = pool.take_frames(4, &mut metrics);
+    assert!(frames.is_empty() && frames.capacity() >= 8);
+    pool.recycle_frames(Vec::<StackFrame<Nim>>::new());
+    assert!(pool.frames.is_empty());
+  }
+}
@@ -2,13 +2,25 @@ use std::{
   collections::hash_map::RandomState,
   fmt::{Debug, Display},
   hash::{BuildHasher, Hash},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
 };
 
 use abstract_game::{Game, GameResult, Score};
-use crossbeam_queue::SegQueue;
 use dashmap::{mapref::entry::Entry, DashMap};
 
-use crate::{null_lock::NullLock, stack::Stack, table::Table, Metrics};
+use crate::{
+  cancellation::CancellationToken,
+  cooperate::Options,
+  null_lock::NullLock,
+  pruning::Pruning,
+  stack::Stack,
+  table::{Bound, Table},
+  work_queue::{LocalQueue, WorkQueues},
+  Metrics, MoveOrdering, SearchProgress,
+};
 
 struct PendingFrame<G>
 where
@@ -36,16 +48,30 @@ pub struct GlobalData<G, H>
 where
   G: Game,
 {
-  /// All of the queues for the worker threads. Each thread's queue is at index
-  /// `thread_idx` in this vector. The entries in these queues can be "stolen"
-  /// from by other workers when they run out of work to do.
-  queues: Vec<SegQueue<NullLock<*mut Stack<G>>>>,
+  /// The work units of every worker thread, which can be "stolen" from by
+  /// other workers when they run out of work to do.
+  work_queues: WorkQueues<NullLock<*mut Stack<G>>>,
   /// There is a hash table of all pending states for each search depth.
   pending_states: Vec<DashMap<G, PendingFrame<G>, H>>,
   /// There is a hash table for all states which have been resolved to some
   /// degree. They may need to be recomputed to a greater depth, but the
   /// information in this table will only ever accumulate over time.
   resolved_states: Table<G, H>,
+  /// Set when the workers should stop searching, e.g. because the search ran
+  /// out of time.
+  stop: AtomicBool,
+  /// If set, the workers stop when this is cancelled, and wait while it's
+  /// paused.
+  cancellation: Option<CancellationToken>,
+  /// If set, the workers publish their metrics here as they search.
+  progress: Option<Arc<SearchProgress>>,
+  /// If set, the moves of each state are explored in the order this decides,
+  /// instead of the order they are generated in.
+  move_ordering: Option<Arc<dyn MoveOrdering<G>>>,
+  /// The heuristics which may skip searching some moves.
+  pruning: Pruning,
+  /// Set for deterministic searches, whose workers log the states they visit.
+  log_visits: bool,
 }
 
 impl<G> GlobalData<G, RandomState>
@@ -56,11 +82,17 @@ where
   #[cfg(test)]
   pub fn new(search_depth: u32, num_threads: u32) -> Self {
     Self {
-      queues: (0..num_threads).map(|_| SegQueue::new()).collect(),
+      work_queues: WorkQueues::new(num_threads, search_depth),
       pending_states: (0..search_depth)
         .map(|_| DashMap::<G, PendingFrame<G>, RandomState>::new())
         .collect(),
       resolved_states: Table::new(),
+      stop: AtomicBool::new(false),
+      cancellation: None,
+      progress: None,
+      move_ordering: None,
+      pruning: Pruning::default(),
+      log_visits: false,
     }
   }
 }
@@ -72,24 +104,93 @@ where
   G::PlayerIdentifier: Debug,
   H: BuildHasher + Clone,
 {
-  pub fn with_hasher(search_depth: u32, num_threads: u32, hasher: H) -> Self {
+  /// Constructs the global data for a search with `options` that starts off
+  /// with the information in `resolved_states`, e.g. from a previous search,
+  /// and reports its progress to `progress`, orders moves with
+  /// `move_ordering`, and observes `cancellation` if given.
+  pub fn with_table(
+    options: &Options,
+    hasher: H,
+    resolved_states: Table<G, H>,
+    progress: Option<Arc<SearchProgress>>,
+    move_ordering: Option<Arc<dyn MoveOrdering<G>>>,
+    cancellation: Option<CancellationToken>,
+  ) -> Self {
     Self {
-      queues: (0..num_threads).map(|_| SegQueue::new()).collect(),
-      pending_states: (0..search_depth)
+      work_queues: WorkQueues::new(options.num_threads, options.search_depth),
+      pending_states: (0..options.search_depth)
         .map(|_| DashMap::<G, PendingFrame<G>, H>::with_hasher(hasher.clone()))
         .collect(),
-      resolved_states: Table::with_hasher(hasher),
+      resolved_states,
+      stop: AtomicBool::new(false),
+      cancellation,
+      progress,
+      move_ordering,
+      pruning: options.pruning.clone(),
+      log_visits: options.deterministic,
+    }
+  }
+
+  pub fn work_queues(&self) -> &WorkQueues<NullLock<*mut Stack<G>>> {
+    &self.work_queues
+  }
+
+  /// Tells all workers to stop searching. Each worker will return its current
+  /// work unit to its queue and exit.
+  pub fn stop(&self) {
+    self.stop.store(true, Ordering::Relaxed);
+  }
+
+  pub fn stopped(&self) -> bool {
+    self.stop.load(Ordering::Relaxed)
+  }
+
+  /// Blocks while the search is paused, returning true if the workers should
+  /// stop.
+  pub fn checkpoint(&self) -> bool {
+    let cancelled = self
+      .cancellation
+      .as_ref()
+      .is_some_and(CancellationToken::checkpoint);
+    cancelled || self.stopped()
+  }
+
+  pub fn progress(&self) -> Option<&Arc<SearchProgress>> {
+    self.progress.as_ref()
+  }
+
+  pub fn log_visits(&self) -> bool {
+    self.log_visits
+  }
+
+  /// Frees all work units that were left unfinished in the worker queues,
+  /// along with every work unit suspended on them. Returns true if there were
+  /// any, meaning the search was stopped before completing.
+  ///
+  /// This is unsafe because no workers may be running.
+  pub unsafe fn free_abandoned_stacks(&self) -> bool {
+    let mut any_abandoned = false;
+    for stack_ptr in self.work_queues.drain() {
+      unsafe { Stack::free_with_dependants(*stack_ptr) };
+      any_abandoned = true;
     }
+    any_abandoned
   }
 
-  pub fn queue(&self, thread_idx: u32) -> &SegQueue<NullLock<*mut Stack<G>>> {
-    self.queues.get(thread_idx as usize).unwrap()
+  pub fn move_ordering(&self) -> Option<&dyn MoveOrdering<G>> {
+    self.move_ordering.as_deref()
   }
 
   pub fn resolved_states_table(&self) -> &Table<G, H> {
     &self.resolved_states
   }
 
+  /// Consumes the global data, returning the table of resolved states so it
+  /// can outlive the search.
+  pub fn into_resolved_states_table(self) -> Table<G, H> {
+    self.resolved_states
+  }
+
   /// Will try to find the bottom frame of the stack in the state tables. If it
   /// isn't found, or it is found but wasn't searched deep enough, it will
   /// reserve a spot in `pending_states` by placing the bottom game state of the
@@ -98,10 +199,21 @@ where
     let stack = unsafe { &mut *stack_ptr };
     let bottom_state = stack.bottom_frame().unwrap();
     let game = bottom_state.game();
-    if let Some(score) = self.resolved_states.get(game) {
-      if score.determined(stack.bottom_depth()) {
-        metrics.hits += 1;
-        return LookupResult::Found { score };
+    if let Some(probe) = self.resolved_states.probe(game, stack.bottom_depth()) {
+      match probe.bound {
+        Some(Bound::Exact) => {
+          metrics.record_hit();
+          return LookupResult::Found { score: probe.score };
+        }
+        // The player to move can't lose, so the parent can't do better than
+        // the tie it already has by making this move. The score is only a
+        // bound, but it can't displace the parent's best score, which is all
+        // it's used for.
+        Some(Bound::Lower) if stack.parent_has_tie() => {
+          metrics.record_bound_hit();
+          return LookupResult::Found { score: probe.score };
+        }
+        _ => {}
       }
     }
 
@@ -121,7 +233,7 @@ where
           frame.queue_dependant_unlocked(stack_ptr);
         }
 
-        metrics.queues += 1;
+        metrics.record_queue();
         LookupResult::Queued
       }
       Entry::Vacant(entry) => {
@@ -131,7 +243,7 @@ where
         });
 
         // We claimed the pending slot.
-        metrics.claims += 1;
+        metrics.record_claim();
         LookupResult::NotFound
       }
     }
@@ -150,7 +262,8 @@ where
   pub fn explore_next_state(
     &self,
     stack_ptr: *mut Stack<G>,
-    queue: &SegQueue<NullLock<*mut Stack<G>>>,
+    queue: &LocalQueue<NullLock<*mut Stack<G>>>,
+    metrics: &mut Metrics,
   ) {
     let stack = unsafe { &mut *stack_ptr };
 
@@ -191,13 +304,24 @@ where
             stack.update_parent_score_and_advance(score);
           } else {
             // println!("  move {} for\n{}", m, bottom_state.game());
-            let next_state = bottom_state.game().with_move(m);
-            stack.push(next_state);
-            break;
+            let move_rank = bottom_state.move_rank();
+            match self.pruning.reduced_score(
+              &game,
+              bottom_depth,
+              move_rank,
+              stack.bottom_has_tie(),
+              metrics,
+            ) {
+              Some(score) => stack.update_parent_score_and_advance(score),
+              None => {
+                stack.push(game);
+                break;
+              }
+            }
           }
         }
         None => {
-          self.commit_score(stack, stack_ptr, queue);
+          self.commit_score(stack, stack_ptr, queue, metrics);
           bottom_depth += 1
         }
       }
@@ -211,14 +335,22 @@ where
     &self,
     stack: &mut Stack<G>,
     stack_ptr: *mut Stack<G>,
-    queue: &SegQueue<NullLock<*mut Stack<G>>>,
+    queue: &LocalQueue<NullLock<*mut Stack<G>>>,
+    metrics: &mut Metrics,
   ) {
-    let depth_idx = stack.bottom_depth() as usize - 1;
+    let depth = stack.bottom_depth();
+    metrics.record_commit(depth);
+    let depth_idx = depth as usize - 1;
     let bottom_frame_idx = stack.bottom_frame_idx();
 
     let bottom_state = stack.bottom_frame_mut().unwrap();
-    let score = bottom_state.best_score().0.clone();
+    let (score, best_move) = bottom_state.best_score();
     let game = bottom_state.game().clone();
+    if let (Some(move_ordering), Some(best_move)) = (&self.move_ordering, best_move) {
+      if score.cur_player_wins() {
+        move_ordering.record_cutoff(&game, best_move, depth);
+      }
+    }
     // println!("  Out of moves, committing score {} for\n{}", score, game);
     self.commit_game_with_score(game.clone(), score);
 
@@ -240,8 +372,10 @@ where
 
     // Re-queue all pending states.
     while let Some(dependant) = unsafe { bottom_state.pop_dependant_unlocked() } {
-      unsafe { &mut *dependant }.revive();
-      queue.push(unsafe { NullLock::new(dependant) });
+      let dependant_stack = unsafe { &mut *dependant };
+      dependant_stack.revive();
+      let dependant_depth = dependant_stack.bottom_depth();
+      queue.push(unsafe { NullLock::new(dependant) }, dependant_depth);
     }
 
     // Pop this state from the stack.
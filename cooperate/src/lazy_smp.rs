@@ -0,0 +1,321 @@
+use std::{
+  fmt::Display,
+  hash::{BuildHasher, Hash},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+  thread,
+  time::Instant,
+};
+
+use abstract_game::{Game, GameResult, MoveArena, Score, ScoreValue};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{
+  affinity::Placement, cancellation::CancellationToken, cooperate::Options,
+  metrics::ProgressPublisher, search_worker::STOP_CHECK_INTERVAL, table::Table, Metrics,
+  SearchProgress,
+};
+
+/// How the search is split among the worker threads.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchStrategy {
+  /// The game is expanded to `Options::unit_depth`, and the workers claim the
+  /// states of that frontier as units of work, waiting on each other's
+  /// results where the units share subtrees.
+  #[default]
+  UnitSplitting,
+  /// Every worker searches the whole game on its own, sharing only the table
+  /// of resolved states. The first worker searches moves in order, and the
+  /// others in a shuffled order, with every other one searching a move deeper,
+  /// so they tend to resolve different subtrees and pick up each other's
+  /// results from the table. `Options::unit_depth` is ignored.
+  LazySmp,
+}
+
+/// Returned by a lazy SMP search that was stopped before it finished.
+struct Stopped;
+
+struct LazySmpWorker<'a, G: Game, H> {
+  table: &'a Table<G, H>,
+  done: &'a AtomicBool,
+  /// Shuffles the moves of each state, or `None` to search them in order.
+  rng: Option<StdRng>,
+  arena: MoveArena<G::Move>,
+  metrics: Metrics,
+  progress: Option<ProgressPublisher>,
+  cancellation: Option<&'a CancellationToken>,
+  until_stop_check: u32,
+}
+
+impl<G, H> LazySmpWorker<'_, G, H>
+where
+  G: Game + Hash + Eq,
+  H: BuildHasher + Clone,
+{
+  /// A min-max search of `game` to `depth`, like
+  /// `find_best_move_serial_table`, which gives up as soon as `done` is set or
+  /// the search is cancelled. Scores are only written to the table once they
+  /// are complete, so a stopped search leaves nothing wrong behind.
+  fn search(&mut self, game: &G, depth: u32) -> Result<Option<Score>, Stopped> {
+    if self.done.load(Ordering::Relaxed) {
+      return Err(Stopped);
+    }
+    self.until_stop_check -= 1;
+    if self.until_stop_check == 0 {
+      self.until_stop_check = STOP_CHECK_INTERVAL;
+      if let Some(progress) = &mut self.progress {
+        progress.publish(&self.metrics);
+      }
+      if self.cancellation.is_some_and(CancellationToken::checkpoint) {
+        self.done.store(true, Ordering::Relaxed);
+        return Err(Stopped);
+      }
+    }
+    if depth == 0 {
+      return Ok(Some(Score::no_info()));
+    }
+    if let Some(cached_score) = self.table.get(game) {
+      if cached_score.determined(depth) {
+        return Ok(Some(cached_score));
+      }
+    }
+    self.metrics.record_node();
+    self.metrics.record_visit(game, depth);
+
+    // This state's moves occupy `moves_start..moves_end` of the arena, and must
+    // be truncated away before returning.
+    let moves_start = self.arena.len();
+    let moves_end = moves_start + game.expand_into(&mut self.arena).len();
+    if let Some(rng) = &mut self.rng {
+      self.arena.moves_mut(moves_start..moves_end).shuffle(rng);
+    }
+
+    let result = self.search_moves(game, depth, moves_start..moves_end);
+    self.arena.truncate(moves_start);
+
+    if let Ok(Some(score)) = &result {
+      self.table.update(game.clone(), score.clone());
+    }
+    result
+  }
+
+  fn search_moves(
+    &mut self,
+    game: &G,
+    depth: u32,
+    moves: std::ops::Range<usize>,
+  ) -> Result<Option<Score>, Stopped> {
+    let mut best_score: Option<Score> = None;
+    for move_idx in moves {
+      let g = game.with_move(self.arena[move_idx]);
+      let score = match g.finished() {
+        GameResult::Win(player) => {
+          return Ok(Some(if player == game.current_player() {
+            Score::win(1)
+          } else {
+            Score::lose(1)
+          }));
+        }
+        GameResult::Tie => Score::guaranteed_tie(),
+        GameResult::NotFinished => match self.search(&g, depth - 1)? {
+          Some(score) => score.backstep(),
+          // See `find_best_move_serial_table` for why having no legal moves
+          // scores like this.
+          None => Score::win(2),
+        },
+      };
+
+      if score.score_at_depth(depth) == ScoreValue::CurrentPlayerWins {
+        return Ok(Some(score.break_early()));
+      }
+      if best_score.as_ref().is_none_or(|best| score.better(best)) {
+        best_score = Some(score);
+      }
+    }
+    Ok(best_score)
+  }
+}
+
+/// Searches `game` to `options.search_depth` with `options.num_threads` lazy
+/// SMP workers (see `SearchStrategy::LazySmp`) sharing `table`, until one of
+/// the workers searching to at least that depth finishes, `deadline` passes, or
+/// `cancellation` is cancelled. The workers report their progress to
+/// `progress` if given. Returns whether the search finished, and the fused
+/// metrics of the workers.
+pub(crate) fn run_lazy_smp<G, H>(
+  game: &G,
+  options: &Options,
+  table: &Table<G, H>,
+  deadline: Option<Instant>,
+  progress: Option<&Arc<SearchProgress>>,
+  cancellation: Option<&CancellationToken>,
+) -> (bool, Metrics)
+where
+  G: Game + Display + Send + Sync + Hash + Eq,
+  H: BuildHasher + Clone + Send + Sync,
+{
+  let start = Instant::now();
+  let done = AtomicBool::new(false);
+  let finished = AtomicBool::new(false);
+  let search_depth = options.search_depth;
+  let placement = Placement::new(options.pin_threads, options.numa_aware).map(Arc::new);
+
+  let mut metrics = Metrics::new();
+  thread::scope(|scope| {
+    let workers: Vec<_> = (0..options.num_threads)
+      .map(|thread_idx| {
+        let (done, finished, placement) = (&done, &finished, placement.clone());
+        thread::Builder::new()
+          .name(format!("worker_{thread_idx}"))
+          .spawn_scoped(scope, move || {
+            if let Some(placement) = placement {
+              placement.place_worker(thread_idx);
+            }
+            let mut worker = LazySmpWorker {
+              table,
+              done,
+              rng: (thread_idx != 0).then(|| StdRng::seed_from_u64(thread_idx as u64)),
+              arena: MoveArena::with_capacity(search_depth as usize * 16),
+              metrics: if options.deterministic {
+                Metrics::with_visit_log()
+              } else {
+                Metrics::new()
+              },
+              progress: progress
+                .map(|progress| ProgressPublisher::new(thread_idx, progress.clone())),
+              cancellation,
+              until_stop_check: STOP_CHECK_INTERVAL,
+            };
+            let depth = search_depth + thread_idx % 2;
+            let worker_start = Instant::now();
+            if worker.search(game, depth).is_ok() {
+              finished.store(true, Ordering::Relaxed);
+              done.store(true, Ordering::Relaxed);
+            }
+            worker
+              .metrics
+              .record_busy(thread_idx, worker_start.elapsed());
+            if let Some(progress) = &mut worker.progress {
+              progress.publish(&worker.metrics);
+            }
+            worker.metrics
+          })
+          .unwrap()
+      })
+      .collect();
+
+    if let Some(deadline) = deadline {
+      while !done.load(Ordering::Relaxed) && Instant::now() < deadline {
+        thread::sleep(
+          deadline
+            .saturating_duration_since(Instant::now())
+            .min(std::time::Duration::from_millis(1)),
+        );
+      }
+      done.store(true, Ordering::Relaxed);
+    }
+
+    let mut any_bad = false;
+    for worker in workers {
+      match worker.join() {
+        Ok(worker_metrics) => metrics += worker_metrics,
+        Err(_) => any_bad = true,
+      }
+    }
+    assert!(!any_bad);
+  });
+
+  metrics.record_elapsed(start.elapsed());
+  (finished.load(Ordering::Relaxed), metrics)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::{Duration, Instant};
+
+  use crate::{
+    cooperate::{solve, Options},
+    lazy_smp::run_lazy_smp,
+    serial_search::find_best_move_serial_table,
+    table::Table,
+    test::{gomoku::Gomoku, nim::Nim, tic_tac_toe::Ttt},
+    SearchStrategy,
+  };
+
+  fn lazy_smp_options(search_depth: u32, num_threads: u32) -> Options {
+    Options {
+      num_threads,
+      search_depth,
+      strategy: SearchStrategy::LazySmp,
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn test_nim() {
+    for sticks in [1, 2, 3, 10, 31] {
+      let solution = solve(&Nim::new(sticks), lazy_smp_options(sticks + 1, 4));
+      assert!(solution
+        .score
+        .compatible(&Nim::new(sticks).expected_score()));
+    }
+  }
+
+  #[test]
+  fn test_ttt() {
+    const DEPTH: u32 = 9;
+    for num_threads in [1, 2, 8] {
+      let solution = solve(&Ttt::new(), lazy_smp_options(DEPTH, num_threads));
+      assert!(solution
+        .score
+        .compatible(&Ttt::new().compute_expected_score(DEPTH)));
+      assert!(!solution.principal_variation.is_empty());
+    }
+  }
+
+  #[test]
+  fn test_table_scores_are_complete() {
+    const DEPTH: u32 = 9;
+    let table = Table::new();
+    let (finished, _) = run_lazy_smp(
+      &Ttt::new(),
+      &lazy_smp_options(DEPTH, 4),
+      &table,
+      None,
+      None,
+      None,
+    );
+    assert!(finished);
+
+    // Every score the workers committed must agree with a fresh search.
+    let expected_table = Table::new();
+    for entry in table.table().iter() {
+      find_best_move_serial_table(entry.key(), DEPTH, &expected_table);
+      let expected = expected_table.get(entry.key()).unwrap();
+      assert!(
+        entry.value().compatible(&expected),
+        "{} vs {expected} for\n{}",
+        entry.value(),
+        entry.key()
+      );
+    }
+  }
+
+  #[test]
+  fn test_deadline() {
+    let table = Table::new();
+    let start = Instant::now();
+    let (finished, _) = run_lazy_smp(
+      &Gomoku::new(4, 4, 4),
+      &lazy_smp_options(16, 2),
+      &table,
+      Some(start + Duration::from_millis(100)),
+      None,
+      None,
+    );
+    assert!(!finished);
+    assert!(start.elapsed() < Duration::from_secs(10));
+  }
+}
@@ -1,15 +1,43 @@
+mod affinity;
+mod cancellation;
 mod cooperate;
+mod distributed;
+mod engine;
+mod frame_pool;
 mod global_data;
+mod lazy_smp;
+mod max_n;
 mod metrics;
+mod move_ordering;
 mod null_lock;
+mod principal_variation;
+mod proof;
+mod pruning;
+mod puzzle;
 mod search_worker;
 mod serial_search;
 mod stack;
 mod table;
+mod tablebase;
 mod transparent_iterator;
+mod work_queue;
 
 #[cfg(test)]
 mod test;
 
+pub use cancellation::*;
 pub use cooperate::*;
+pub use distributed::*;
+pub use engine::*;
+pub use lazy_smp::SearchStrategy;
+pub use max_n::*;
 pub use metrics::*;
+pub use move_ordering::*;
+pub use principal_variation::Solution;
+pub use proof::*;
+pub use pruning::*;
+pub use puzzle::*;
+pub use table::{
+  Bound, Probe, ReplacementPolicy, ShardStats, Table, TableLimit, VerificationStats,
+};
+pub use tablebase::Tablebase;
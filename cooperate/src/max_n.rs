@@ -0,0 +1,129 @@
+use abstract_game::{GameResult, MaxNScore, MultiplayerGame};
+
+/// A serial max-n search of the game state to `depth` moves, for games with
+/// any number of players. Each player picks the move whose score they prefer
+/// (see `MaxNScore`), taking the first such move on ties. Returns the score of
+/// `game` and the best move for the player to move.
+///
+/// Max-n scores don't admit alpha-beta pruning, so this explores every line of
+/// play up to `depth`. Two-player games should use the cooperative solver
+/// instead, whose scores convert with `MaxNScore::from_two_player`.
+pub fn find_best_move_max_n<G: MultiplayerGame>(
+  game: &G,
+  depth: u32,
+) -> (MaxNScore, Option<G::Move>) {
+  // Can't score games that are already over.
+  debug_assert!(game.finished() == GameResult::NotFinished);
+
+  if depth == 0 {
+    return (MaxNScore::NoWin, None);
+  }
+
+  let player = game.player_index(&game.current_player());
+  let mut best: Option<(MaxNScore, G::Move)> = None;
+  for m in game.each_move() {
+    let g = game.with_move(m);
+    let score = match g.finished() {
+      GameResult::Win(winner) => MaxNScore::Win {
+        player: g.player_index(&winner),
+        plies: 1,
+      },
+      GameResult::Tie => MaxNScore::NoWin,
+      GameResult::NotFinished => find_best_move_max_n(&g, depth - 1).0.backstep(),
+    };
+
+    if best
+      .as_ref()
+      .is_none_or(|(best_score, _)| score.better_for(best_score, player))
+    {
+      best = Some((score, m));
+    }
+  }
+
+  match best {
+    Some((score, m)) => (score, Some(m)),
+    None => (MaxNScore::NoWin, None),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use abstract_game::{Game, MaxNScore, MultiplayerGame};
+
+  use crate::test::{multi_nim::MultiNim, nim::Nim, tic_tac_toe::Ttt};
+
+  use super::find_best_move_max_n;
+
+  #[test]
+  fn test_two_player_nim() {
+    for sticks in 1..=10 {
+      let nim = Nim::new(sticks);
+      let (score, _) = find_best_move_max_n(&nim, sticks);
+      assert_eq!(
+        score,
+        MaxNScore::from_two_player(&nim.expected_score(), 0, sticks),
+        "{sticks} sticks"
+      );
+    }
+  }
+
+  #[test]
+  fn test_two_player_ttt() {
+    // The serial solver stops at the first winning move it finds, so it may
+    // not find the fastest win, and only the winners can be compared.
+    let winner = |score: MaxNScore| match score {
+      MaxNScore::Win { player, .. } => Some(player),
+      MaxNScore::NoWin => None,
+    };
+
+    let mut ttt = Ttt::new();
+    for _ in 0..3 {
+      let (score, _) = find_best_move_max_n(&ttt, 9);
+      let expected = ttt.compute_expected_score(9);
+      let player = ttt.player_index(&ttt.current_player());
+      assert_eq!(
+        winner(score),
+        winner(MaxNScore::from_two_player(&expected, player, 9)),
+        "{ttt}"
+      );
+      ttt.make_move(ttt.each_move().next().unwrap());
+    }
+  }
+
+  #[test]
+  fn test_three_player_nim() {
+    // (sticks, winner, plies)
+    let expected = [
+      (1, 0, 1),
+      (2, 0, 1),
+      // Every move hands the next player an immediate win.
+      (3, 1, 2),
+      // Rather than let the next player win at once, player 0 delays the
+      // loss to player 2.
+      (4, 2, 3),
+      (5, 0, 4),
+      (6, 0, 4),
+    ];
+    for (sticks, player, plies) in expected {
+      let (score, m) = find_best_move_max_n(&MultiNim::new(sticks, 3), sticks);
+      assert_eq!(score, MaxNScore::Win { player, plies }, "{sticks} sticks");
+      assert!(m.is_some());
+    }
+  }
+
+  #[test]
+  fn test_depth_limit() {
+    // Player 0's win with 5 sticks takes 4 moves, and every faster win
+    // belongs to someone else, which player 0 avoids.
+    let (score, _) = find_best_move_max_n(&MultiNim::new(5, 3), 3);
+    assert_eq!(score, MaxNScore::NoWin);
+    let (score, _) = find_best_move_max_n(&MultiNim::new(5, 3), 4);
+    assert_eq!(
+      score,
+      MaxNScore::Win {
+        player: 0,
+        plies: 4
+      }
+    );
+  }
+}
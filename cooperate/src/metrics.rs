@@ -1,30 +1,1046 @@
-#[derive(Clone, Debug, Default)]
+use std::{
+  collections::HashMap,
+  hash::{DefaultHasher, Hash, Hasher},
+  sync::{
+    atomic::{AtomicU32, AtomicU64, Ordering},
+    Arc, Mutex, RwLock,
+  },
+  time::{Duration, Instant},
+};
+
+/// Counters for events in the search. Each worker owns its own `Metrics` and
+/// records into it without any synchronization, and the per-worker counters
+/// are only fused together (by adding them) once the workers have finished.
+///
+/// Metrics are collected when the `metrics` feature is enabled, which it is by
+/// default. Without it, `Metrics` is a zero-sized type and every `record_*`
+/// method is an empty inline function, so the search loop compiles to the same
+/// code as if it didn't record anything at all.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Metrics {
-  pub hits: u64,
-  pub queues: u64,
-  pub claims: u64,
+  #[cfg(feature = "metrics")]
+  hits: u64,
+  /// The number of lookups which found a score that only bounds the state's
+  /// score, but tightly enough to skip searching it.
+  #[cfg(feature = "metrics")]
+  bound_hits: u64,
+  /// The number of moves scored by a search of reduced depth instead of a full
+  /// search, see `Pruning::late_move_reduction`.
+  #[cfg(feature = "metrics")]
+  late_move_reductions: u64,
+  /// The number of states skipped because passing in them didn't lose, see
+  /// `Pruning::pass_reduction`.
+  #[cfg(feature = "metrics")]
+  pass_reductions: u64,
+  #[cfg(feature = "metrics")]
+  queues: u64,
+  #[cfg(feature = "metrics")]
+  claims: u64,
+  #[cfg(feature = "metrics")]
+  evictions: u64,
+  /// The number of states inserted into a table in verification mode whose
+  /// hash was already held by a different state.
+  #[cfg(feature = "metrics")]
+  hash_collisions: u64,
+  /// The number of lookups in a table in verification mode that a table keyed
+  /// by hash alone would have answered with the score of a different state.
+  #[cfg(feature = "metrics")]
+  false_hits: u64,
+  #[cfg(feature = "metrics")]
+  nodes: u64,
+  #[cfg(feature = "metrics")]
+  cutoffs: u64,
+  /// The number of work units taken from another worker's queue.
+  #[cfg(feature = "metrics")]
+  steals: u64,
+  /// The number of attempts to take a work unit that lost a race with another
+  /// worker and had to be retried.
+  #[cfg(feature = "metrics")]
+  steal_retries: u64,
+  /// The number of buffers for stack frames, or the moves of a frame, that
+  /// had to be allocated.
+  #[cfg(feature = "metrics")]
+  frame_allocations: u64,
+  /// The number of buffers for stack frames, or the moves of a frame, that
+  /// were reused from a finished frame or work unit.
+  #[cfg(feature = "metrics")]
+  frame_reuses: u64,
+  /// How long workers spent waiting for work, summed over all workers.
+  #[cfg(feature = "metrics")]
+  idle: Duration,
+  /// The number of states committed to the table, indexed by the depth they
+  /// were searched to.
+  #[cfg(feature = "metrics")]
+  depth_distribution: Vec<u64>,
+  /// How long each worker spent searching, indexed by thread.
+  #[cfg(feature = "metrics")]
+  thread_busy: Vec<Duration>,
+  /// The wall time of the search.
+  #[cfg(feature = "metrics")]
+  elapsed: Duration,
+  /// Every state explored, in order, if this is logging visits.
+  #[cfg(feature = "metrics")]
+  visit_log: Option<Vec<Visit>>,
+}
+
+/// A state explored by a deterministic search. See `Options::deterministic`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Visit {
+  /// The hash of the state, which is the same from run to run.
+  pub state_hash: u64,
+  /// The depth the state was searched to.
+  pub depth: u32,
+}
+
+impl Visit {
+  pub fn new<G: Hash>(game: &G, depth: u32) -> Self {
+    Self {
+      state_hash: stable_hash(game),
+      depth,
+    }
+  }
+}
+
+/// A hash of `game` which is the same from run to run, since
+/// `DefaultHasher::new` always uses the same keys, unlike `RandomState`.
+pub(crate) fn stable_hash<G: Hash>(game: &G) -> u64 {
+  let mut hasher = DefaultHasher::new();
+  game.hash(&mut hasher);
+  hasher.finish()
+}
+
+/// The index of the first visit where two visit logs differ, or `None` if
+/// they're identical. If one log is a prefix of the other, this is the length
+/// of the shorter one.
+pub fn first_divergence(log: &[Visit], other: &[Visit]) -> Option<usize> {
+  match log
+    .iter()
+    .zip(other)
+    .position(|(visit, other)| visit != other)
+  {
+    Some(idx) => Some(idx),
+    None => (log.len() != other.len()).then(|| log.len().min(other.len())),
+  }
 }
 
 impl Metrics {
+  /// True if metrics are being collected in this build.
+  pub const ENABLED: bool = cfg!(feature = "metrics");
+
   pub fn new() -> Self {
     Self::default()
   }
+
+  /// Constructs metrics which additionally log every state recorded with
+  /// `record_visit`.
+  pub fn with_visit_log() -> Self {
+    Self {
+      #[cfg(feature = "metrics")]
+      visit_log: Some(Vec::new()),
+      ..Self::default()
+    }
+  }
+
+  /// Records a lookup which found an already-resolved state.
+  #[inline(always)]
+  pub fn record_hit(&mut self) {
+    #[cfg(feature = "metrics")]
+    {
+      self.hits += 1;
+    }
+  }
+
+  /// Records a lookup which found a bound on a state's score that let it be
+  /// skipped.
+  #[inline(always)]
+  pub fn record_bound_hit(&mut self) {
+    #[cfg(feature = "metrics")]
+    {
+      self.bound_hits += 1;
+    }
+  }
+
+  /// Records a move scored by a search of reduced depth.
+  #[inline(always)]
+  pub fn record_late_move_reduction(&mut self) {
+    #[cfg(feature = "metrics")]
+    {
+      self.late_move_reductions += 1;
+    }
+  }
+
+  /// Records a state skipped because passing in it didn't lose.
+  #[inline(always)]
+  pub fn record_pass_reduction(&mut self) {
+    #[cfg(feature = "metrics")]
+    {
+      self.pass_reductions += 1;
+    }
+  }
+
+  /// Records a lookup which queued on a state another worker is exploring.
+  #[inline(always)]
+  pub fn record_queue(&mut self) {
+    #[cfg(feature = "metrics")]
+    {
+      self.queues += 1;
+    }
+  }
+
+  /// Records a lookup which claimed a state for this worker to explore.
+  #[inline(always)]
+  pub fn record_claim(&mut self) {
+    #[cfg(feature = "metrics")]
+    {
+      self.claims += 1;
+    }
+  }
+
+  /// Records entries evicted from the table of resolved states to stay within
+  /// its memory limit.
+  #[inline(always)]
+  #[allow(unused_variables)]
+  pub fn record_evictions(&mut self, evictions: u64) {
+    #[cfg(feature = "metrics")]
+    {
+      self.evictions += evictions;
+    }
+  }
+
+  /// Records hash collisions found by the verification mode of the table of
+  /// resolved states.
+  #[inline(always)]
+  #[allow(unused_variables)]
+  pub fn record_hash_collisions(&mut self, collisions: u64) {
+    #[cfg(feature = "metrics")]
+    {
+      self.hash_collisions += collisions;
+    }
+  }
+
+  /// Records false hits found by the verification mode of the table of
+  /// resolved states.
+  #[inline(always)]
+  #[allow(unused_variables)]
+  pub fn record_false_hits(&mut self, false_hits: u64) {
+    #[cfg(feature = "metrics")]
+    {
+      self.false_hits += false_hits;
+    }
+  }
+
+  /// Records a state visited by a worker.
+  #[inline(always)]
+  pub fn record_node(&mut self) {
+    #[cfg(feature = "metrics")]
+    {
+      self.nodes += 1;
+    }
+  }
+
+  /// Logs the exploration of `game` to `depth`, if this is logging visits.
+  #[inline(always)]
+  #[allow(unused_variables)]
+  pub fn record_visit<G: Hash>(&mut self, game: &G, depth: u32) {
+    #[cfg(feature = "metrics")]
+    if let Some(visit_log) = &mut self.visit_log {
+      visit_log.push(Visit::new(game, depth));
+    }
+  }
+
+  /// Records moves that were skipped because an earlier move already won as
+  /// quickly as possible.
+  #[inline(always)]
+  #[allow(unused_variables)]
+  pub fn record_cutoffs(&mut self, cutoffs: u64) {
+    #[cfg(feature = "metrics")]
+    {
+      self.cutoffs += cutoffs;
+    }
+  }
+
+  /// Records a work unit taken from another worker's queue.
+  #[inline(always)]
+  pub fn record_steal(&mut self) {
+    #[cfg(feature = "metrics")]
+    {
+      self.steals += 1;
+    }
+  }
+
+  /// Records an attempt to take a work unit that raced with another worker
+  /// and has to be retried.
+  #[inline(always)]
+  pub fn record_steal_retry(&mut self) {
+    #[cfg(feature = "metrics")]
+    {
+      self.steal_retries += 1;
+    }
+  }
+
+  /// Records a newly allocated buffer for stack frames or their moves.
+  #[inline(always)]
+  pub fn record_frame_allocation(&mut self) {
+    #[cfg(feature = "metrics")]
+    {
+      self.frame_allocations += 1;
+    }
+  }
+
+  /// Records a buffer for stack frames or their moves reused from a finished
+  /// frame or work unit.
+  #[inline(always)]
+  pub fn record_frame_reuse(&mut self) {
+    #[cfg(feature = "metrics")]
+    {
+      self.frame_reuses += 1;
+    }
+  }
+
+  /// Records time a worker spent waiting for work.
+  #[allow(unused_variables)]
+  pub fn record_idle(&mut self, idle: Duration) {
+    #[cfg(feature = "metrics")]
+    {
+      self.idle += idle;
+    }
+  }
+
+  /// Records a state committed to the table after being searched to `depth`.
+  #[inline(always)]
+  #[allow(unused_variables)]
+  pub fn record_commit(&mut self, depth: u32) {
+    #[cfg(feature = "metrics")]
+    {
+      let depth = depth as usize;
+      if self.depth_distribution.len() <= depth {
+        self.depth_distribution.resize(depth + 1, 0);
+      }
+      self.depth_distribution[depth] += 1;
+    }
+  }
+
+  /// Records that worker `thread_idx` spent `busy` searching.
+  #[allow(unused_variables)]
+  pub fn record_busy(&mut self, thread_idx: u32, busy: Duration) {
+    #[cfg(feature = "metrics")]
+    {
+      let thread_idx = thread_idx as usize;
+      if self.thread_busy.len() <= thread_idx {
+        self.thread_busy.resize(thread_idx + 1, Duration::ZERO);
+      }
+      self.thread_busy[thread_idx] += busy;
+    }
+  }
+
+  /// Records the wall time of a search.
+  #[allow(unused_variables)]
+  pub fn record_elapsed(&mut self, elapsed: Duration) {
+    #[cfg(feature = "metrics")]
+    {
+      self.elapsed += elapsed;
+    }
+  }
+
+  /// The number of lookups that found an already-resolved state. Always 0 if
+  /// metrics are disabled.
+  pub fn hits(&self) -> u64 {
+    #[cfg(feature = "metrics")]
+    return self.hits;
+    #[cfg(not(feature = "metrics"))]
+    0
+  }
+
+  /// The number of lookups that found a bound on a state's score which let it
+  /// be skipped. Always 0 if metrics are disabled.
+  pub fn bound_hits(&self) -> u64 {
+    #[cfg(feature = "metrics")]
+    return self.bound_hits;
+    #[cfg(not(feature = "metrics"))]
+    0
+  }
+
+  /// The number of moves scored by a search of reduced depth. Always 0 if
+  /// metrics are disabled.
+  pub fn late_move_reductions(&self) -> u64 {
+    #[cfg(feature = "metrics")]
+    return self.late_move_reductions;
+    #[cfg(not(feature = "metrics"))]
+    0
+  }
+
+  /// The number of states skipped because passing in them didn't lose.
+  /// Always 0 if metrics are disabled.
+  pub fn pass_reductions(&self) -> u64 {
+    #[cfg(feature = "metrics")]
+    return self.pass_reductions;
+    #[cfg(not(feature = "metrics"))]
+    0
+  }
+
+  /// The number of lookups that queued on a state being explored by another
+  /// worker. Always 0 if metrics are disabled.
+  pub fn queues(&self) -> u64 {
+    #[cfg(feature = "metrics")]
+    return self.queues;
+    #[cfg(not(feature = "metrics"))]
+    0
+  }
+
+  /// The number of lookups that claimed a state to explore. Always 0 if
+  /// metrics are disabled.
+  pub fn claims(&self) -> u64 {
+    #[cfg(feature = "metrics")]
+    return self.claims;
+    #[cfg(not(feature = "metrics"))]
+    0
+  }
+
+  /// The number of entries evicted from the table of resolved states. Always 0
+  /// if metrics are disabled.
+  pub fn evictions(&self) -> u64 {
+    #[cfg(feature = "metrics")]
+    return self.evictions;
+    #[cfg(not(feature = "metrics"))]
+    0
+  }
+
+  /// The number of states inserted into the table whose hash was already held
+  /// by a different state. Always 0 if metrics are disabled or the table isn't
+  /// in verification mode (see `Table::set_verification`).
+  pub fn hash_collisions(&self) -> u64 {
+    #[cfg(feature = "metrics")]
+    return self.hash_collisions;
+    #[cfg(not(feature = "metrics"))]
+    0
+  }
+
+  /// The number of table lookups whose hash was held by a different state.
+  /// Always 0 if metrics are disabled or the table isn't in verification mode.
+  pub fn false_hits(&self) -> u64 {
+    #[cfg(feature = "metrics")]
+    return self.false_hits;
+    #[cfg(not(feature = "metrics"))]
+    0
+  }
+
+  /// The number of states visited by all workers. Always 0 if metrics are
+  /// disabled.
+  pub fn nodes(&self) -> u64 {
+    #[cfg(feature = "metrics")]
+    return self.nodes;
+    #[cfg(not(feature = "metrics"))]
+    0
+  }
+
+  /// The number of moves skipped because an earlier move already won as
+  /// quickly as possible. Always 0 if metrics are disabled.
+  pub fn cutoffs(&self) -> u64 {
+    #[cfg(feature = "metrics")]
+    return self.cutoffs;
+    #[cfg(not(feature = "metrics"))]
+    0
+  }
+
+  /// The number of work units taken from another worker's queue. Always 0 if
+  /// metrics are disabled.
+  pub fn steals(&self) -> u64 {
+    #[cfg(feature = "metrics")]
+    return self.steals;
+    #[cfg(not(feature = "metrics"))]
+    0
+  }
+
+  /// The number of attempts to take a work unit that raced with another
+  /// worker, a measure of contention on the work queues. Always 0 if metrics
+  /// are disabled.
+  pub fn steal_retries(&self) -> u64 {
+    #[cfg(feature = "metrics")]
+    return self.steal_retries;
+    #[cfg(not(feature = "metrics"))]
+    0
+  }
+
+  /// The number of buffers for stack frames or their moves that were
+  /// allocated. Always 0 if metrics are disabled.
+  pub fn frame_allocations(&self) -> u64 {
+    #[cfg(feature = "metrics")]
+    return self.frame_allocations;
+    #[cfg(not(feature = "metrics"))]
+    0
+  }
+
+  /// The number of buffers for stack frames or their moves that were reused
+  /// instead of allocated. Always 0 if metrics are disabled.
+  pub fn frame_reuses(&self) -> u64 {
+    #[cfg(feature = "metrics")]
+    return self.frame_reuses;
+    #[cfg(not(feature = "metrics"))]
+    0
+  }
+
+  /// How long workers spent waiting for work, summed over all workers. Always
+  /// 0 if metrics are disabled.
+  pub fn idle(&self) -> Duration {
+    #[cfg(feature = "metrics")]
+    return self.idle;
+    #[cfg(not(feature = "metrics"))]
+    Duration::ZERO
+  }
+
+  /// The number of states committed to the table, indexed by the depth they
+  /// were searched to. Always empty if metrics are disabled.
+  pub fn depth_distribution(&self) -> &[u64] {
+    #[cfg(feature = "metrics")]
+    return &self.depth_distribution;
+    #[cfg(not(feature = "metrics"))]
+    &[]
+  }
+
+  /// The wall time of the search. Always 0 if metrics are disabled.
+  pub fn elapsed(&self) -> Duration {
+    #[cfg(feature = "metrics")]
+    return self.elapsed;
+    #[cfg(not(feature = "metrics"))]
+    Duration::ZERO
+  }
+
+  /// Every state explored by the search, in order, or `None` if it wasn't
+  /// deterministic. Always `None` if metrics are disabled.
+  pub fn visit_log(&self) -> Option<&[Visit]> {
+    #[cfg(feature = "metrics")]
+    return self.visit_log.as_deref();
+    #[cfg(not(feature = "metrics"))]
+    None
+  }
+
+  /// The number of states visited per second of wall time.
+  pub fn nodes_per_sec(&self) -> f64 {
+    per_sec(self.nodes(), self.elapsed())
+  }
+
+  /// The fraction of table lookups that found an already-resolved state.
+  pub fn hit_rate(&self) -> f64 {
+    ratio(self.hits(), self.hits() + self.queues() + self.claims())
+  }
+
+  /// The fraction of buffers for stack frames or their moves that were reused
+  /// instead of allocated.
+  pub fn frame_reuse_rate(&self) -> f64 {
+    ratio(
+      self.frame_reuses(),
+      self.frame_allocations() + self.frame_reuses(),
+    )
+  }
+
+  /// The fraction of the wall time each worker spent searching, indexed by
+  /// thread. Always empty if metrics are disabled.
+  pub fn thread_utilization(&self) -> Vec<f64> {
+    #[cfg(feature = "metrics")]
+    return self
+      .thread_busy
+      .iter()
+      .map(|busy| utilization(*busy, self.elapsed))
+      .collect();
+    #[cfg(not(feature = "metrics"))]
+    Vec::new()
+  }
+}
+
+fn ratio(count: u64, total: u64) -> f64 {
+  if total == 0 {
+    0.
+  } else {
+    count as f64 / total as f64
+  }
+}
+
+fn per_sec(count: u64, elapsed: Duration) -> f64 {
+  if elapsed.is_zero() {
+    0.
+  } else {
+    count as f64 / elapsed.as_secs_f64()
+  }
+}
+
+fn utilization(busy: Duration, elapsed: Duration) -> f64 {
+  if elapsed.is_zero() {
+    0.
+  } else {
+    (busy.as_secs_f64() / elapsed.as_secs_f64()).min(1.)
+  }
 }
 
 impl std::ops::Add for Metrics {
   type Output = Self;
 
-  fn add(self, rhs: Self) -> Self::Output {
-    Self {
-      hits: self.hits + rhs.hits,
-      queues: self.queues + rhs.queues,
-      claims: self.claims + rhs.claims,
-    }
+  fn add(mut self, rhs: Self) -> Self::Output {
+    self += rhs;
+    self
   }
 }
 
 impl std::ops::AddAssign for Metrics {
+  #[allow(unused_variables)]
   fn add_assign(&mut self, rhs: Self) {
-    *self = self.clone() + rhs;
+    #[cfg(feature = "metrics")]
+    {
+      if let Some(rhs_visit_log) = rhs.visit_log {
+        self
+          .visit_log
+          .get_or_insert_with(Vec::new)
+          .extend(rhs_visit_log);
+      }
+      self.hits += rhs.hits;
+      self.bound_hits += rhs.bound_hits;
+      self.late_move_reductions += rhs.late_move_reductions;
+      self.pass_reductions += rhs.pass_reductions;
+      self.queues += rhs.queues;
+      self.claims += rhs.claims;
+      self.evictions += rhs.evictions;
+      self.hash_collisions += rhs.hash_collisions;
+      self.false_hits += rhs.false_hits;
+      self.nodes += rhs.nodes;
+      self.cutoffs += rhs.cutoffs;
+      self.steals += rhs.steals;
+      self.steal_retries += rhs.steal_retries;
+      self.frame_allocations += rhs.frame_allocations;
+      self.frame_reuses += rhs.frame_reuses;
+      self.idle += rhs.idle;
+      if self.depth_distribution.len() < rhs.depth_distribution.len() {
+        self
+          .depth_distribution
+          .resize(rhs.depth_distribution.len(), 0);
+      }
+      for (count, rhs_count) in self
+        .depth_distribution
+        .iter_mut()
+        .zip(rhs.depth_distribution)
+      {
+        *count += rhs_count;
+      }
+      if self.thread_busy.len() < rhs.thread_busy.len() {
+        self
+          .thread_busy
+          .resize(rhs.thread_busy.len(), Duration::ZERO);
+      }
+      for (busy, rhs_busy) in self.thread_busy.iter_mut().zip(rhs.thread_busy) {
+        *busy += rhs_busy;
+      }
+      self.elapsed += rhs.elapsed;
+    }
+  }
+}
+
+impl std::iter::Sum for Metrics {
+  fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+    iter.fold(Self::new(), |total, metrics| total + metrics)
+  }
+}
+
+/// A snapshot of a running search, taken by `SearchProgress::report`.
+#[derive(Clone, Debug)]
+pub struct ProgressReport {
+  /// The time since the search started.
+  pub elapsed: Duration,
+  /// The depth currently being searched.
+  pub depth: u32,
+  /// The number of states visited so far.
+  pub nodes: u64,
+  /// The number of states visited per second so far.
+  pub nodes_per_sec: f64,
+  /// The fraction of table lookups that found an already-resolved state.
+  pub hit_rate: f64,
+  /// The number of moves skipped because an earlier move already won as
+  /// quickly as possible.
+  pub cutoffs: u64,
+  /// The fraction of the elapsed time each worker has spent searching, indexed
+  /// by thread.
+  pub thread_utilization: Vec<f64>,
+}
+
+/// Live counters of a running search, which can be polled from other threads
+/// while the search runs, e.g. to display its progress. Workers publish the
+/// growth of their `Metrics` into it every so often with relaxed atomics, so
+/// the counters lag slightly behind the search, and like `Metrics` they are
+/// only counted when the `metrics` feature is enabled.
+#[derive(Debug)]
+pub struct SearchProgress {
+  start: Mutex<Instant>,
+  depth: AtomicU32,
+  nodes: AtomicU64,
+  hits: AtomicU64,
+  lookups: AtomicU64,
+  cutoffs: AtomicU64,
+  /// Nanoseconds each worker has spent searching, indexed by thread. Only
+  /// written to (rather than resized) while the search is running.
+  thread_busy: RwLock<Vec<AtomicU64>>,
+}
+
+impl SearchProgress {
+  pub fn new() -> Self {
+    Self {
+      start: Mutex::new(Instant::now()),
+      depth: AtomicU32::new(0),
+      nodes: AtomicU64::new(0),
+      hits: AtomicU64::new(0),
+      lookups: AtomicU64::new(0),
+      cutoffs: AtomicU64::new(0),
+      thread_busy: RwLock::new(Vec::new()),
+    }
+  }
+
+  /// Resets all counters for a new search with `num_threads` workers.
+  pub(crate) fn start(&self, num_threads: u32) {
+    *self.start.lock().unwrap() = Instant::now();
+    self.depth.store(0, Ordering::Relaxed);
+    self.nodes.store(0, Ordering::Relaxed);
+    self.hits.store(0, Ordering::Relaxed);
+    self.lookups.store(0, Ordering::Relaxed);
+    self.cutoffs.store(0, Ordering::Relaxed);
+    *self.thread_busy.write().unwrap() = (0..num_threads).map(|_| AtomicU64::new(0)).collect();
+  }
+
+  pub(crate) fn set_depth(&self, depth: u32) {
+    self.depth.store(depth, Ordering::Relaxed);
+  }
+
+  /// Takes a snapshot of the counters.
+  pub fn report(&self) -> ProgressReport {
+    let elapsed = self.start.lock().unwrap().elapsed();
+    let nodes = self.nodes.load(Ordering::Relaxed);
+    ProgressReport {
+      elapsed,
+      depth: self.depth.load(Ordering::Relaxed),
+      nodes,
+      nodes_per_sec: per_sec(nodes, elapsed),
+      hit_rate: ratio(
+        self.hits.load(Ordering::Relaxed),
+        self.lookups.load(Ordering::Relaxed),
+      ),
+      cutoffs: self.cutoffs.load(Ordering::Relaxed),
+      thread_utilization: self
+        .thread_busy
+        .read()
+        .unwrap()
+        .iter()
+        .map(|busy| utilization(Duration::from_nanos(busy.load(Ordering::Relaxed)), elapsed))
+        .collect(),
+    }
+  }
+}
+
+impl Default for SearchProgress {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// A snapshot of the searches of a `MetricsRegistry`, taken by
+/// `MetricsRegistry::report`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegistryReport {
+  /// The number of searches registered right now.
+  pub running_searches: u64,
+  /// The number of searches that have been registered and since finished.
+  pub finished_searches: u64,
+  /// The number of states visited by every search ever registered, including
+  /// the ones still running.
+  pub nodes: u64,
+  /// The number of states the running searches are visiting per second, added
+  /// together.
+  pub nodes_per_sec: f64,
+}
+
+/// Tracks every search of a process that runs many of them, e.g. a server, so
+/// that their combined progress can be monitored. A search is registered by
+/// its `SearchProgress` for as long as it runs, and its final counts are kept
+/// in the registry's totals once it finishes.
+#[derive(Debug, Default)]
+pub struct MetricsRegistry {
+  next_id: AtomicU64,
+  running: Mutex<HashMap<u64, Arc<SearchProgress>>>,
+  /// Only changed with `running` locked, so reports count each search once.
+  finished_searches: AtomicU64,
+  finished_nodes: AtomicU64,
+}
+
+impl MetricsRegistry {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Registers the search reporting its progress to `progress`, until the
+  /// returned guard is dropped, which should be once the search is over.
+  pub fn register(&self, progress: Arc<SearchProgress>) -> RegisteredSearch<'_> {
+    let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+    self.running.lock().unwrap().insert(id, progress);
+    RegisteredSearch { registry: self, id }
+  }
+
+  /// Takes a snapshot of the running searches and the totals of the finished
+  /// ones.
+  pub fn report(&self) -> RegistryReport {
+    let running = self.running.lock().unwrap();
+    let (nodes, nodes_per_sec) = running
+      .values()
+      .map(|progress| progress.report())
+      .fold((0, 0.), |(nodes, nodes_per_sec), report| {
+        (nodes + report.nodes, nodes_per_sec + report.nodes_per_sec)
+      });
+    RegistryReport {
+      running_searches: running.len() as u64,
+      finished_searches: self.finished_searches.load(Ordering::Relaxed),
+      nodes: self.finished_nodes.load(Ordering::Relaxed) + nodes,
+      nodes_per_sec,
+    }
+  }
+}
+
+/// Keeps a search registered with a `MetricsRegistry` until dropped, see
+/// `MetricsRegistry::register`.
+pub struct RegisteredSearch<'a> {
+  registry: &'a MetricsRegistry,
+  id: u64,
+}
+
+impl Drop for RegisteredSearch<'_> {
+  fn drop(&mut self) {
+    let mut running = self.registry.running.lock().unwrap();
+    if let Some(progress) = running.remove(&self.id) {
+      let registry = self.registry;
+      registry
+        .finished_nodes
+        .fetch_add(progress.report().nodes, Ordering::Relaxed);
+      registry.finished_searches.fetch_add(1, Ordering::Relaxed);
+    }
+  }
+}
+
+/// Publishes the metrics of a single worker to a `SearchProgress`, keeping
+/// track of what it has already published.
+pub(crate) struct ProgressPublisher {
+  thread_idx: u32,
+  progress: Arc<SearchProgress>,
+  last_publish: Instant,
+  nodes: u64,
+  hits: u64,
+  lookups: u64,
+  cutoffs: u64,
+}
+
+impl ProgressPublisher {
+  pub fn new(thread_idx: u32, progress: Arc<SearchProgress>) -> Self {
+    Self {
+      thread_idx,
+      progress,
+      last_publish: Instant::now(),
+      nodes: 0,
+      hits: 0,
+      lookups: 0,
+      cutoffs: 0,
+    }
+  }
+
+  /// Adds everything recorded in `metrics` since the last call to the shared
+  /// progress, along with the time spent searching since then.
+  pub fn publish(&mut self, metrics: &Metrics) {
+    let progress = &self.progress;
+    let lookups = metrics.hits() + metrics.queues() + metrics.claims();
+    progress
+      .nodes
+      .fetch_add(metrics.nodes() - self.nodes, Ordering::Relaxed);
+    progress
+      .hits
+      .fetch_add(metrics.hits() - self.hits, Ordering::Relaxed);
+    progress
+      .lookups
+      .fetch_add(lookups - self.lookups, Ordering::Relaxed);
+    progress
+      .cutoffs
+      .fetch_add(metrics.cutoffs() - self.cutoffs, Ordering::Relaxed);
+    self.nodes = metrics.nodes();
+    self.hits = metrics.hits();
+    self.lookups = lookups;
+    self.cutoffs = metrics.cutoffs();
+
+    let now = Instant::now();
+    if let Some(busy) = progress
+      .thread_busy
+      .read()
+      .unwrap()
+      .get(self.thread_idx as usize)
+    {
+      let nanos = now.duration_since(self.last_publish).as_nanos() as u64;
+      busy.fetch_add(nanos, Ordering::Relaxed);
+    }
+    self.last_publish = now;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{sync::Arc, time::Duration};
+
+  use super::{
+    first_divergence, Metrics, MetricsRegistry, ProgressPublisher, SearchProgress, Visit,
+  };
+
+  #[test]
+  fn test_fuse() {
+    let mut worker1 = Metrics::new();
+    worker1.record_hit();
+    worker1.record_claim();
+    let mut worker2 = Metrics::new();
+    worker2.record_hit();
+    worker2.record_queue();
+    worker1.record_bound_hit();
+    worker1.record_late_move_reduction();
+    worker2.record_pass_reduction();
+    worker2.record_evictions(3);
+    worker1.record_hash_collisions(1);
+    worker2.record_false_hits(2);
+    for metrics in [&mut worker1, &mut worker2] {
+      metrics.record_node();
+      metrics.record_cutoffs(2);
+      metrics.record_commit(1);
+    }
+    worker1.record_commit(3);
+    worker1.record_steal();
+    worker2.record_steal_retry();
+    worker1.record_frame_allocation();
+    worker2.record_frame_reuse();
+    worker2.record_frame_reuse();
+    worker2.record_idle(Duration::from_secs(1));
+    worker1.record_busy(0, Duration::from_secs(1));
+    worker2.record_busy(1, Duration::from_secs(2));
+
+    let total: Metrics = [worker1, worker2].into_iter().sum();
+    if Metrics::ENABLED {
+      assert_eq!(total.hits(), 2);
+      assert_eq!(total.bound_hits(), 1);
+      assert_eq!(total.late_move_reductions(), 1);
+      assert_eq!(total.pass_reductions(), 1);
+      assert_eq!(total.queues(), 1);
+      assert_eq!(total.claims(), 1);
+      assert_eq!(total.evictions(), 3);
+      assert_eq!(total.hash_collisions(), 1);
+      assert_eq!(total.false_hits(), 2);
+      assert_eq!(total.nodes(), 2);
+      assert_eq!(total.cutoffs(), 4);
+      assert_eq!(total.depth_distribution(), &[0, 2, 0, 1]);
+      assert_eq!(total.steals(), 1);
+      assert_eq!(total.steal_retries(), 1);
+      assert_eq!(total.frame_allocations(), 1);
+      assert_eq!(total.frame_reuses(), 2);
+      assert_eq!(total.idle(), Duration::from_secs(1));
+      assert_eq!(total.hit_rate(), 0.5);
+    } else {
+      assert_eq!(total, Metrics::new());
+      assert_eq!(std::mem::size_of::<Metrics>(), 0);
+    }
+  }
+
+  #[test]
+  fn test_visit_log() {
+    let mut worker = Metrics::with_visit_log();
+    worker.record_visit(&1, 2);
+    worker.record_visit(&2, 1);
+    let mut total = Metrics::new();
+    total.record_visit(&3, 1);
+    total += worker;
+
+    if Metrics::ENABLED {
+      let log = total.visit_log().unwrap();
+      assert_eq!(log, &[Visit::new(&1, 2), Visit::new(&2, 1)]);
+      assert_eq!(first_divergence(log, log), None);
+      assert_eq!(first_divergence(log, &log[..1]), Some(1));
+      assert_eq!(first_divergence(log, &[Visit::new(&1, 3)]), Some(0));
+    } else {
+      assert_eq!(total.visit_log(), None);
+    }
+  }
+
+  #[test]
+  fn test_utilization() {
+    let mut metrics = Metrics::new();
+    metrics.record_busy(0, Duration::from_secs(4));
+    metrics.record_busy(1, Duration::from_secs(2));
+    metrics.record_elapsed(Duration::from_secs(4));
+    metrics += Metrics::new();
+
+    if Metrics::ENABLED {
+      assert_eq!(metrics.thread_utilization(), vec![1., 0.5]);
+    } else {
+      assert!(metrics.thread_utilization().is_empty());
+    }
+  }
+
+  #[test]
+  fn test_progress() {
+    let progress = Arc::new(SearchProgress::new());
+    progress.start(2);
+    progress.set_depth(5);
+
+    let mut metrics = Metrics::new();
+    let mut publisher = ProgressPublisher::new(1, progress.clone());
+    metrics.record_node();
+    metrics.record_hit();
+    metrics.record_claim();
+    publisher.publish(&metrics);
+    metrics.record_node();
+    publisher.publish(&metrics);
+
+    let report = progress.report();
+    assert_eq!(report.depth, 5);
+    assert_eq!(report.thread_utilization.len(), 2);
+    if Metrics::ENABLED {
+      assert_eq!(report.nodes, 2);
+      assert_eq!(report.hit_rate, 0.5);
+    } else {
+      assert_eq!(report.nodes, 0);
+    }
+  }
+
+  #[test]
+  fn test_registry() {
+    let registry = MetricsRegistry::new();
+    let publish_nodes = |progress: &Arc<SearchProgress>, nodes: u64| {
+      progress.start(1);
+      let mut metrics = Metrics::new();
+      for _ in 0..nodes {
+        metrics.record_node();
+      }
+      ProgressPublisher::new(0, progress.clone()).publish(&metrics);
+    };
+
+    let first = Arc::new(SearchProgress::new());
+    let second = Arc::new(SearchProgress::new());
+    let first_search = registry.register(first.clone());
+    let second_search = registry.register(second.clone());
+    publish_nodes(&first, 3);
+    publish_nodes(&second, 4);
+
+    let report = registry.report();
+    assert_eq!(report.running_searches, 2);
+    assert_eq!(report.finished_searches, 0);
+
+    drop(first_search);
+    // The finished search's nodes are still counted.
+    let finished = registry.report();
+    assert_eq!(finished.running_searches, 1);
+    assert_eq!(finished.finished_searches, 1);
+    assert_eq!(finished.nodes, report.nodes);
+    if Metrics::ENABLED {
+      assert_eq!(report.nodes, 7);
+    } else {
+      assert_eq!(report.nodes, 0);
+    }
+
+    drop(second_search);
+    let report = registry.report();
+    assert_eq!(report.running_searches, 0);
+    assert_eq!(report.finished_searches, 2);
+    assert_eq!(report.nodes_per_sec, 0.);
   }
 }
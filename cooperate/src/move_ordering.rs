@@ -0,0 +1,264 @@
+use std::hash::Hash;
+
+use abstract_game::{Game, Score, Threats};
+use dashmap::DashMap;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::metrics::stable_hash;
+
+/// What a `MoveOrdering` may consult about the state whose moves it orders.
+pub struct OrderingContext<'a, G> {
+  depth: u32,
+  lookup: &'a dyn Fn(&G) -> Option<Score>,
+}
+
+impl<'a, G> OrderingContext<'a, G>
+where
+  G: Game,
+{
+  pub(crate) fn new(depth: u32, lookup: &'a dyn Fn(&G) -> Option<Score>) -> Self {
+    Self { depth, lookup }
+  }
+
+  /// The depth the state is being searched to.
+  pub fn depth(&self) -> u32 {
+    self.depth
+  }
+
+  /// The move from `game` to its best child with a score in the table of
+  /// resolved states, e.g. from an earlier, shallower search. This makes each
+  /// of `moves` to look up its child, so it is about as costly as expanding
+  /// `game`.
+  pub fn table_move(&self, game: &G, moves: &[G::Move]) -> Option<G::Move> {
+    moves
+      .iter()
+      .filter_map(|&m| Some((m, (self.lookup)(&game.with_move(m))?.backstep())))
+      .reduce(|best, candidate| {
+        if candidate.1.better(&best.1) {
+          candidate
+        } else {
+          best
+        }
+      })
+      .map(|(m, _)| m)
+  }
+}
+
+/// Decides the order the search explores the moves of each state in. A state
+/// is resolved as soon as one of its moves is found to win, cutting off the
+/// rest, so searching likely winning moves first shrinks the search.
+///
+/// The ordering is shared by all worker threads.
+pub trait MoveOrdering<G: Game>: Send + Sync {
+  /// Whether to order the moves of states searched to `depth`. Ordering
+  /// generates all of a state's moves up front, which may cost more than it
+  /// saves close to the leaves of the search.
+  fn orders_depth(&self, _depth: u32) -> bool {
+    true
+  }
+
+  /// Reorders `moves`, all the moves of `game`, into the order they should be
+  /// searched in.
+  fn order_moves(&self, game: &G, moves: &mut [G::Move], context: &OrderingContext<G>);
+
+  /// Called when searching `m` from `game` to `depth` found that it wins,
+  /// cutting off the remaining moves of `game`.
+  fn record_cutoff(&self, _game: &G, _m: G::Move, _depth: u32) {}
+}
+
+/// Searches the moves of each state in tiers: the move to the best child in
+/// the table of resolved states, then moves that win on the spot, then moves
+/// that block the opponent from winning, then the rest. Within each tier,
+/// moves are ordered by the history heuristic, i.e. by how often and how deep
+/// they have caused cutoffs elsewhere in the search.
+///
+/// Each tier can be turned off, to measure what it's worth.
+pub struct StandardOrdering<G>
+where
+  G: Game,
+{
+  pub table_move: bool,
+  pub threats: bool,
+  pub history: bool,
+  /// States searched to less than this depth are searched in the order their
+  /// moves are generated in.
+  pub min_depth: u32,
+  history_scores: DashMap<G::Move, u64>,
+}
+
+impl<G> StandardOrdering<G>
+where
+  G: Game,
+  G::Move: Hash + Eq,
+{
+  /// Constructs an ordering with every tier turned on, for states searched to
+  /// depth 3 or more.
+  pub fn new() -> Self {
+    Self {
+      table_move: true,
+      threats: true,
+      history: true,
+      min_depth: 3,
+      history_scores: DashMap::new(),
+    }
+  }
+}
+
+impl<G> Default for StandardOrdering<G>
+where
+  G: Game,
+  G::Move: Hash + Eq,
+{
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<G> MoveOrdering<G> for StandardOrdering<G>
+where
+  G: Threats + Send + Sync,
+  G::Move: Hash + Eq + Send + Sync,
+{
+  fn orders_depth(&self, depth: u32) -> bool {
+    depth >= self.min_depth
+  }
+
+  fn order_moves(&self, game: &G, moves: &mut [G::Move], context: &OrderingContext<G>) {
+    let table_move = if self.table_move {
+      context.table_move(game, moves)
+    } else {
+      None
+    };
+    let (wins, blocks) = if self.threats {
+      (game.immediate_wins(), game.forced_blocks())
+    } else {
+      (Vec::new(), Vec::new())
+    };
+
+    let tier = |m: &G::Move| {
+      if table_move.as_ref() == Some(m) {
+        0
+      } else if wins.contains(m) {
+        1
+      } else if blocks.contains(m) {
+        2
+      } else {
+        3
+      }
+    };
+    let history = |m: &G::Move| {
+      if self.history {
+        self.history_scores.get(m).map_or(0, |score| *score)
+      } else {
+        0
+      }
+    };
+    moves.sort_by_cached_key(|m| (tier(m), std::cmp::Reverse(history(m))));
+  }
+
+  fn record_cutoff(&self, _game: &G, m: G::Move, depth: u32) {
+    if self.history {
+      *self.history_scores.entry(m).or_default() += (depth * depth) as u64;
+    }
+  }
+}
+
+/// Searches the moves of each state in a random order determined by `seed`
+/// and the state, so the order is the same every time the state is searched.
+/// The search picks the first of equally good moves, so with a deterministic
+/// search (see `Options::deterministic`), this breaks ties reproducibly, and
+/// different seeds explore the game in different orders.
+pub struct ShuffledOrdering {
+  seed: u64,
+}
+
+impl ShuffledOrdering {
+  pub fn new(seed: u64) -> Self {
+    Self { seed }
+  }
+}
+
+impl<G> MoveOrdering<G> for ShuffledOrdering
+where
+  G: Game + Hash,
+{
+  fn order_moves(&self, game: &G, moves: &mut [G::Move], _context: &OrderingContext<G>) {
+    moves.shuffle(&mut StdRng::seed_from_u64(self.seed ^ stable_hash(game)));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use crate::{
+    test::{gomoku::Gomoku, tic_tac_toe::Ttt},
+    Engine, Metrics, Options, Pruning,
+  };
+
+  use super::{ShuffledOrdering, StandardOrdering};
+
+  fn options(search_depth: u32) -> Options {
+    Options {
+      num_threads: 2,
+      search_depth,
+      unit_depth: 2,
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn test_ordering_preserves_score() {
+    for depth in 3..=6 {
+      let mut unordered = Engine::new(Gomoku::new(4, 4, 3), options(depth));
+      let mut ordered = Engine::new(Gomoku::new(4, 4, 3), options(depth));
+      ordered.set_move_ordering(Some(Arc::new(StandardOrdering::new())));
+      assert_eq!(ordered.solve(), unordered.solve(), "depth {depth}");
+    }
+  }
+
+  #[test]
+  fn test_ordering_tiers_preserve_score() {
+    let expected = Engine::new(Ttt::new(), options(9)).solve();
+    for tiers in 0..8 {
+      let mut ordering = StandardOrdering::new();
+      ordering.table_move = tiers & 1 != 0;
+      ordering.threats = tiers & 2 != 0;
+      ordering.history = tiers & 4 != 0;
+      ordering.min_depth = 1;
+
+      let mut engine = Engine::new(Ttt::new(), options(9));
+      engine.set_move_ordering(Some(Arc::new(ordering)));
+      assert_eq!(engine.solve(), expected, "tiers {tiers:b}");
+    }
+  }
+
+  #[test]
+  fn test_shuffled_ordering() {
+    let deterministic = |seed: u64| {
+      let mut engine = Engine::new(
+        Gomoku::new(4, 4, 3),
+        Options {
+          deterministic: true,
+          pruning: Pruning::default(),
+          ..options(6)
+        },
+      );
+      engine.set_move_ordering(Some(Arc::new(ShuffledOrdering::new(seed))));
+      let score = engine.solve();
+      let visit_log = engine.metrics().visit_log().map(<[_]>::to_vec);
+      (score, engine.principal_variation(), visit_log)
+    };
+
+    let expected = Engine::new(Gomoku::new(4, 4, 3), options(6)).solve();
+    let replay = deterministic(1);
+    assert_eq!(replay.0, expected);
+    assert_eq!(deterministic(1), replay);
+
+    let other_seed = deterministic(2);
+    assert_eq!(other_seed.0, expected);
+    if Metrics::ENABLED {
+      assert_ne!(other_seed.2, replay.2);
+    }
+  }
+}
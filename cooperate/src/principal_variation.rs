@@ -0,0 +1,156 @@
+use std::{
+  fmt::Display,
+  hash::{BuildHasher, Hash},
+};
+
+use abstract_game::{Evaluate, Game, GameResult, Score};
+
+use crate::{proof::child_score, serial_search::find_best_move_serial_table, table::Table};
+
+/// The result of solving a game state: its score, along with the line of play
+/// that achieves it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Solution<M> {
+  pub score: Score,
+  /// The sequence of moves both players make under best play from the solved
+  /// state, reconstructed from the table of resolved states. This ends either
+  /// when the game does, or at the search depth.
+  pub principal_variation: Vec<M>,
+  /// The depth the game was searched to. This is less than the requested
+  /// search depth if a time limit ended the search early.
+  pub depth: u32,
+}
+
+impl<M> Solution<M>
+where
+  M: Copy,
+{
+  /// The best move for the current player, or `None` if the game has no legal
+  /// moves.
+  pub fn best_move(&self) -> Option<M> {
+    self.principal_variation.first().copied()
+  }
+}
+
+/// The scores of every move from `game`, from the perspective of the player
+/// making the move, using the scores of `depth - 1` searches of each child from
+/// `table`.
+pub(crate) fn move_scores<G, H>(game: &G, depth: u32, table: &Table<G, H>) -> Vec<(G::Move, Score)>
+where
+  G: Display + Game + Hash + Eq,
+  H: BuildHasher + Clone,
+{
+  let child_depth = depth.saturating_sub(1);
+  game
+    .each_move()
+    .map(|m| {
+      let child = game.with_move(m);
+      let score = if child.finished() == GameResult::NotFinished {
+        find_best_move_serial_table(&child, child_depth, table).0
+      } else {
+        None
+      };
+      (m, child_score(game, &child, score))
+    })
+    .collect()
+}
+
+/// The best move from `game`, along with its score, or `None` if there are no
+/// legal moves.
+pub(crate) fn best_move<G, H>(game: &G, depth: u32, table: &Table<G, H>) -> Option<(G::Move, Score)>
+where
+  G: Display + Game + Hash + Eq,
+  H: BuildHasher + Clone,
+{
+  move_scores(game, depth, table)
+    .into_iter()
+    .reduce(|best, candidate| {
+      if candidate.1.better(&best.1) {
+        candidate
+      } else {
+        best
+      }
+    })
+}
+
+/// Like `best_move`, but chooses between moves with equally good scores by the
+/// heuristic evaluation of the states they lead to. This distinguishes between
+/// moves whose outcome couldn't be determined within the search depth.
+pub(crate) fn best_move_by_evaluation<G, H>(
+  game: &G,
+  depth: u32,
+  table: &Table<G, H>,
+) -> Option<(G::Move, Score)>
+where
+  G: Display + Game + Hash + Eq + Evaluate,
+  H: BuildHasher + Clone,
+{
+  let move_scores = move_scores(game, depth, table);
+  let best_score = move_scores
+    .iter()
+    .map(|(_, score)| score)
+    .reduce(|best, candidate| {
+      if candidate.better(best) {
+        candidate
+      } else {
+        best
+      }
+    })?
+    .clone();
+
+  move_scores
+    .into_iter()
+    .filter(|(_, score)| !best_score.better(score))
+    // Evaluations of the child are from the opponent's perspective.
+    .max_by_key(|(m, _)| -game.with_move(*m).evaluate())
+}
+
+/// Follows the best move from each state starting at `game`, until either the
+/// game ends or `depth` moves have been made.
+pub(crate) fn principal_variation<G, H>(game: &G, depth: u32, table: &Table<G, H>) -> Vec<G::Move>
+where
+  G: Display + Game + Hash + Eq,
+  H: BuildHasher + Clone,
+{
+  let mut game = game.clone();
+  let mut moves = Vec::new();
+  for depth in (1..=depth).rev() {
+    if game.finished() != GameResult::NotFinished {
+      break;
+    }
+    let Some((m, _)) = best_move(&game, depth, table) else {
+      break;
+    };
+    moves.push(m);
+    game.make_move(m);
+  }
+  moves
+}
+
+#[cfg(test)]
+mod tests {
+  use abstract_game::{Game, GameResult};
+
+  use crate::{serial_search::find_best_move_serial, test::tic_tac_toe::Ttt};
+
+  use super::principal_variation;
+
+  #[test]
+  fn test_ttt_principal_variation() {
+    const DEPTH: u32 = 10;
+    let game = Ttt::new();
+    let (_, _, table) = find_best_move_serial(&game, DEPTH);
+
+    // Tic tac toe is a tie under perfect play, so the principal variation
+    // should fill the whole board.
+    let pv = principal_variation(&game, DEPTH, &table);
+    assert_eq!(pv.len(), 9);
+
+    let mut game = game;
+    for m in pv {
+      assert_eq!(game.finished(), GameResult::NotFinished);
+      game.make_move(m);
+    }
+    assert_eq!(game.finished(), GameResult::Tie);
+  }
+}
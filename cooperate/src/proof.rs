@@ -0,0 +1,383 @@
+use std::{
+  collections::HashMap,
+  error::Error,
+  fmt::Display,
+  hash::{BuildHasher, Hash},
+  str::FromStr,
+};
+
+use abstract_game::{Game, GameResult, Score};
+
+use crate::table::Table;
+
+/// The version of the serialized certificate format.
+const PROOF_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub struct ProofError {
+  message: String,
+}
+
+impl ProofError {
+  fn new(message: String) -> Self {
+    Self { message }
+  }
+}
+
+impl Error for ProofError {}
+
+impl Display for ProofError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Proof error: {}", self.message)
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ProofNode {
+  /// The node this one is a child of, and the index of the move (in move
+  /// generation order) leading from the parent to this node. The root has no
+  /// parent.
+  parent: Option<(u32, u32)>,
+  /// The score this node is claimed to have.
+  score: Score,
+}
+
+/// A certificate for the score of a game state. It consists of claimed scores
+/// for the root and the critical positions below it: for winning positions, a
+/// single winning reply, and for all other positions, every reply. Each claim
+/// can be checked locally against the claims of its children, so verifying a
+/// certificate only takes time proportional to its size, instead of repeating
+/// the search.
+///
+/// The serialized format is line-based text, with one line per node:
+/// ```text
+/// onoro-proof <version>
+/// - - <cur player wins (0|1)> <turn count tie> <turn count win>
+/// <parent> <move index> <cur player wins (0|1)> <turn count tie> <turn count win>
+/// ...
+/// ```
+/// where the first node is the root, and every other node's parent precedes
+/// it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Certificate {
+  nodes: Vec<ProofNode>,
+}
+
+/// The score of `game` from the perspective of its parent, given the (claimed)
+/// score of `game` itself.
+pub(crate) fn child_score<G: Game>(parent: &G, child: &G, score: Option<Score>) -> Score {
+  match child.finished() {
+    GameResult::Win(player) => {
+      if player == parent.current_player() {
+        Score::win(1)
+      } else {
+        Score::lose(1)
+      }
+    }
+    GameResult::Tie => Score::guaranteed_tie(),
+    GameResult::NotFinished => {
+      if child.each_move().next().is_none() {
+        // Mirrors the serial search, which considers winning by no legal moves
+        // as not winning until after the other player's attempt at a move.
+        Score::win(2)
+      } else {
+        score.unwrap_or_else(Score::no_info).backstep()
+      }
+    }
+  }
+}
+
+/// Derives the score of `game` from the scores of its children, in the same
+/// way the serial search does.
+fn derive_score<G, F>(game: &G, child_claim: F) -> Option<Score>
+where
+  G: Game,
+  F: Fn(&G) -> Option<Score>,
+{
+  game.each_move().fold(None, |best_score, m| {
+    let child = game.with_move(m);
+    let score = child_score(game, &child, child_claim(&child));
+    match best_score {
+      Some(best_score) if !score.better(&best_score) => Some(best_score),
+      _ => Some(score),
+    }
+  })
+}
+
+impl Certificate {
+  /// Builds a certificate for `root` from the scores in `table`, or returns
+  /// `None` if the root has no score in the table.
+  pub(crate) fn from_table<G, H>(root: &G, table: &Table<G, H>) -> Option<Self>
+  where
+    G: Game + Hash + Eq,
+    H: BuildHasher + Clone,
+  {
+    let mut certificate = Self { nodes: Vec::new() };
+    let mut visited = HashMap::new();
+
+    // Only claim the part of a winning score that needs proving: the win.
+    let claim = |score: Score| {
+      if score.turn_count_win() != 0 && score.cur_player_wins() {
+        Score::win(score.turn_count_win())
+      } else {
+        score
+      }
+    };
+
+    let root_score = claim(table.get(root)?);
+    visited.insert(root.clone(), 0);
+    certificate.nodes.push(ProofNode {
+      parent: None,
+      score: root_score,
+    });
+
+    let mut to_visit = vec![(root.clone(), 0u32)];
+    while let Some((game, node_idx)) = to_visit.pop() {
+      let score = certificate.nodes[node_idx as usize].score.clone();
+
+      // All children with scores in the table, along with their scores from
+      // this game's perspective.
+      let mut children: Vec<_> = game
+        .each_move()
+        .enumerate()
+        .filter_map(|(move_idx, m)| {
+          let child = game.with_move(m);
+          let child_table_score = table.get(&child)?;
+          let score = child_score(&game, &child, Some(child_table_score.clone()));
+          Some((move_idx as u32, child, child_table_score, score))
+        })
+        .collect();
+
+      let proves_win = |child_score: &Score| {
+        child_score.cur_player_wins()
+          && child_score.turn_count_win() != 0
+          && child_score.turn_count_win() <= score.turn_count_win()
+      };
+      if score.turn_count_win() != 0 && score.cur_player_wins() {
+        // A single winning reply proves a win. If the win is by a move that
+        // ends the game, no child needs to be included at all.
+        let immediate_win = game.each_move().any(|m| {
+          let child = game.with_move(m);
+          let game_over =
+            child.finished() != GameResult::NotFinished || child.each_move().next().is_none();
+          game_over && proves_win(&child_score(&game, &child, None))
+        });
+        let winning_child = children
+          .iter()
+          .enumerate()
+          .filter(|(_, (_, _, _, child_score))| proves_win(child_score))
+          .min_by_key(|(_, (_, _, _, child_score))| child_score.turn_count_win())
+          .map(|(idx, _)| idx);
+
+        if immediate_win {
+          children.clear();
+        } else if let Some(idx) = winning_child {
+          children = vec![children.swap_remove(idx)];
+        }
+      }
+
+      for (move_idx, child, child_table_score, _) in children {
+        if visited.contains_key(&child) {
+          continue;
+        }
+        let child_idx = certificate.nodes.len() as u32;
+        visited.insert(child.clone(), child_idx);
+        certificate.nodes.push(ProofNode {
+          parent: Some((node_idx, move_idx)),
+          score: claim(child_table_score),
+        });
+        to_visit.push((child, child_idx));
+      }
+    }
+
+    Some(certificate)
+  }
+
+  /// The claimed score of the root game state.
+  pub fn root_score(&self) -> Option<Score> {
+    self.nodes.first().map(|node| node.score.clone())
+  }
+
+  /// The number of game states with claimed scores in the certificate.
+  pub fn len(&self) -> usize {
+    self.nodes.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.nodes.is_empty()
+  }
+
+  /// Checks that every claim in the certificate follows from the claims of its
+  /// children, returning the score of `root` if it does.
+  pub fn verify<G>(&self, root: &G) -> Result<Score, ProofError>
+  where
+    G: Game + Hash + Eq,
+  {
+    let root_score = self
+      .root_score()
+      .ok_or_else(|| ProofError::new("Certificate is empty".into()))?;
+
+    // Reconstruct the game state of every node from its parent.
+    let mut games: Vec<G> = Vec::with_capacity(self.nodes.len());
+    let mut claims = HashMap::with_capacity(self.nodes.len());
+    for (node_idx, node) in self.nodes.iter().enumerate() {
+      let game = match node.parent {
+        None if node_idx == 0 => root.clone(),
+        Some((parent_idx, move_idx)) if (parent_idx as usize) < node_idx => {
+          let parent = &games[parent_idx as usize];
+          let m = parent.each_move().nth(move_idx as usize).ok_or_else(|| {
+            ProofError::new(format!(
+              "Node {node_idx} refers to move {move_idx} of node {parent_idx}, which doesn't exist"
+            ))
+          })?;
+          parent.with_move(m)
+        }
+        _ => {
+          return Err(ProofError::new(format!(
+            "Node {node_idx} has an invalid parent"
+          )));
+        }
+      };
+
+      if claims.insert(game.clone(), node.score.clone()).is_some() {
+        return Err(ProofError::new(format!(
+          "Node {node_idx} is a duplicate game state"
+        )));
+      }
+      games.push(game);
+    }
+
+    for (node_idx, (game, node)) in games.iter().zip(self.nodes.iter()).enumerate() {
+      if game.finished() != GameResult::NotFinished {
+        return Err(ProofError::new(format!(
+          "Node {node_idx} is a finished game state"
+        )));
+      }
+
+      let derived = derive_score(game, |child| claims.get(child).cloned())
+        .ok_or_else(|| ProofError::new(format!("Node {node_idx} has no legal moves")))?;
+      if !derived.compatible(&node.score) || derived.merge(&node.score) != derived {
+        return Err(ProofError::new(format!(
+          "Node {node_idx} claims score {}, but its children only prove {}",
+          node.score, derived
+        )));
+      }
+    }
+
+    Ok(root_score)
+  }
+}
+
+impl Display for Certificate {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    writeln!(f, "onoro-proof {PROOF_VERSION}")?;
+    for node in &self.nodes {
+      match node.parent {
+        Some((parent_idx, move_idx)) => write!(f, "{parent_idx} {move_idx} ")?,
+        None => write!(f, "- - ")?,
+      }
+      writeln!(
+        f,
+        "{} {} {}",
+        node.score.cur_player_wins() as u32,
+        node.score.turn_count_tie(),
+        node.score.turn_count_win()
+      )?;
+    }
+    Ok(())
+  }
+}
+
+impl FromStr for Certificate {
+  type Err = ProofError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut lines = s.lines().filter(|line| !line.trim().is_empty());
+    if lines.next().map(str::trim) != Some(&format!("onoro-proof {PROOF_VERSION}")) {
+      return Err(ProofError::new(format!(
+        "Missing \"onoro-proof {PROOF_VERSION}\" header"
+      )));
+    }
+
+    let parse_u32 = |token: &str| {
+      token
+        .parse::<u32>()
+        .map_err(|err| ProofError::new(format!("Invalid number \"{token}\": {err}")))
+    };
+
+    let nodes = lines
+      .map(
+        |line| match line.split_whitespace().collect::<Vec<_>>()[..] {
+          [parent_idx, move_idx, cur_player_wins, turn_count_tie, turn_count_win] => {
+            let parent = if parent_idx == "-" && move_idx == "-" {
+              None
+            } else {
+              Some((parse_u32(parent_idx)?, parse_u32(move_idx)?))
+            };
+            Ok(ProofNode {
+              parent,
+              score: Score::new(
+                parse_u32(cur_player_wins)? != 0,
+                parse_u32(turn_count_tie)?,
+                parse_u32(turn_count_win)?,
+              ),
+            })
+          }
+          _ => Err(ProofError::new(format!("Invalid line \"{line}\""))),
+        },
+      )
+      .collect::<Result<_, _>>()?;
+
+    Ok(Self { nodes })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use abstract_game::Score;
+
+  use crate::{
+    serial_search::find_best_move_serial,
+    test::{nim::Nim, tic_tac_toe::Ttt},
+  };
+
+  use super::Certificate;
+
+  #[test]
+  fn test_nim_certificate() {
+    for sticks in 1..=20 {
+      let game = Nim::new(sticks);
+      let (_, _, table) = find_best_move_serial(&game, sticks);
+      let certificate = Certificate::from_table(&game, &table).unwrap();
+
+      let score = certificate.verify(&game).unwrap();
+      assert!(score.compatible(&game.expected_score()));
+    }
+  }
+
+  #[test]
+  fn test_ttt_certificate() {
+    let game = Ttt::new();
+    let (_, _, table) = find_best_move_serial(&game, 10);
+    let certificate = Certificate::from_table(&game, &table).unwrap();
+
+    let parsed: Certificate = certificate.to_string().parse().unwrap();
+    assert_eq!(parsed, certificate);
+    assert!(parsed
+      .verify(&game)
+      .unwrap()
+      .compatible(&game.compute_expected_score(10)));
+  }
+
+  #[test]
+  fn test_bad_claim() {
+    let game = Nim::new(3);
+    let (_, _, table) = find_best_move_serial(&game, 3);
+    let mut certificate = Certificate::from_table(&game, &table).unwrap();
+
+    // Nim with 3 sticks is a loss for the first player, so claiming a win must
+    // fail.
+    certificate.nodes[0].score = Score::win(3);
+    assert!(certificate.verify(&game).is_err());
+  }
+}
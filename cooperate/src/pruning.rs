@@ -0,0 +1,240 @@
+use std::{fmt::Display, hash::Hash};
+
+use abstract_game::{Game, GameResult, Score};
+
+use crate::{serial_search::find_best_move_serial, Metrics};
+
+/// Searches late moves to a reduced depth, see `Pruning::late_move_reduction`.
+#[derive(Clone, Debug)]
+pub struct LateMoveReduction {
+  /// Only moves of states searched at least this deep are reduced.
+  pub min_depth: u32,
+  /// The first `min_rank` moves of each state, in the order they're explored,
+  /// are always searched to full depth. With a `MoveOrdering`, these are the
+  /// moves it ranked best.
+  pub min_rank: u32,
+  /// How many moves shallower than a full search late moves are searched.
+  pub reduction: u32,
+}
+
+/// Skips states in which passing doesn't lose, see `Pruning::pass_reduction`.
+#[derive(Clone, Debug)]
+pub struct PassReduction {
+  /// Only states searched at least this deep are skipped.
+  pub min_depth: u32,
+  /// How many moves shallower than a full search of the state the state after
+  /// passing is searched.
+  pub reduction: u32,
+}
+
+/// Heuristics which skip parts of the search that are unlikely to change its
+/// result. None are enabled by default.
+///
+/// Both heuristics only apply to the moves of a state which already has a
+/// move that ties to its full search depth, since then only a win can
+/// improve its score. A move found to win or lose by a reduced search is a
+/// proven result, which is used as is. A move whose reduced search finds
+/// neither is assumed to tie to full depth, which is the guess that may be
+/// wrong: it can hide a win that only a full search would have found.
+///
+/// Pruned searches are therefore not exact. Their scores, and the scores they
+/// commit to the table, should only be trusted as far as they agree with an
+/// unpruned search, and a table filled by a pruned search shouldn't be reused
+/// for an exact one.
+#[derive(Clone, Debug, Default)]
+pub struct Pruning {
+  /// If set, moves past the first few of each state are searched to a reduced
+  /// depth, on the assumption that the best moves are explored first.
+  pub late_move_reduction: Option<LateMoveReduction>,
+  /// If set, and the game has an equivalent of passing (see
+  /// `Game::pass_equivalent`), a state whose player to move doesn't lose
+  /// within a reduced depth even after passing is assumed not to lose at all,
+  /// since some real move should do at least as well as passing. This is the
+  /// null move heuristic, which fails in zugzwang.
+  pub pass_reduction: Option<PassReduction>,
+}
+
+impl Pruning {
+  /// The score, relative to the parent, to resolve `child` to without
+  /// searching it, or `None` if it must be searched. `child` is the state
+  /// after the parent's `move_rank`-th move, and the parent is searched to
+  /// `depth`. `parent_has_tie` is true if the parent already has a move that
+  /// ties to `depth`.
+  pub(crate) fn reduced_score<G>(
+    &self,
+    child: &G,
+    depth: u32,
+    move_rank: u32,
+    parent_has_tie: bool,
+    metrics: &mut Metrics,
+  ) -> Option<Score>
+  where
+    G: Display + Game + Hash + Eq,
+  {
+    if !parent_has_tie || child.finished() != GameResult::NotFinished {
+      return None;
+    }
+    let child_depth = depth - 1;
+
+    if let Some(pass) = &self.pass_reduction {
+      if child_depth >= pass.min_depth && child_depth > pass.reduction + 1 {
+        if let Some(passed) = child.pass_equivalent() {
+          // After passing, it's the parent's player to move again. If they
+          // can't force a win even with the extra move, assume the child's
+          // player to move doesn't lose.
+          let parent_wins = find_best_move_serial(&passed, child_depth - 1 - pass.reduction)
+            .0
+            .is_some_and(|score| score.cur_player_wins() && score.turn_count_win() != 0);
+          if !parent_wins {
+            metrics.record_pass_reduction();
+            return Some(Score::tie(depth));
+          }
+        }
+      }
+    }
+
+    if let Some(late_move) = &self.late_move_reduction {
+      if move_rank >= late_move.min_rank
+        && child_depth >= late_move.min_depth
+        && child_depth > late_move.reduction
+      {
+        metrics.record_late_move_reduction();
+        let score = match find_best_move_serial(child, child_depth - late_move.reduction).0 {
+          Some(score) => score.backstep(),
+          // The child has no moves, so it's lost for its player to move.
+          None => Score::win(2),
+        };
+        return Some(if score.turn_count_win() != 0 {
+          score
+        } else {
+          Score::tie(depth)
+        });
+      }
+    }
+
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{
+    collections::hash_map::RandomState,
+    fmt::{Debug, Display},
+    hash::Hash,
+  };
+
+  use abstract_game::{Game, GameResult, ScoreValue};
+
+  use crate::{
+    cooperate::solve_with_metrics,
+    test::{gomoku::Gomoku, tic_tac_toe::Ttt},
+    Metrics, Options,
+  };
+
+  use super::{LateMoveReduction, PassReduction, Pruning};
+
+  fn options(search_depth: u32, pruning: Pruning) -> Options {
+    Options {
+      num_threads: 1,
+      search_depth,
+      pruning,
+      ..Default::default()
+    }
+  }
+
+  /// `game` and every unfinished position at most `plies` moves from it.
+  fn positions<G: Game>(game: G, plies: u32) -> Vec<G> {
+    let mut positions = vec![game];
+    let mut frontier = positions.clone();
+    for _ in 0..plies {
+      frontier = frontier
+        .iter()
+        .flat_map(|game| game.each_move().map(|m| game.with_move(m)))
+        .filter(|game| game.finished() == GameResult::NotFinished)
+        .collect();
+      positions.extend(frontier.iter().cloned());
+    }
+    positions
+  }
+
+  /// Solves each of `positions` with and without `pruning`, returning the
+  /// number of positions the two searches disagree on, along with the metrics
+  /// of the pruned searches.
+  ///
+  /// Pruning only ever turns a win or loss into a tie, since a position is
+  /// only resolved to a win or loss by proof, and a move assumed to tie can't
+  /// make any position lose or win. So they may disagree on which positions
+  /// are ties, but a position won or lost by the pruned search must be won or
+  /// lost in the same way.
+  fn check_against_unpruned<G>(positions: &[G], depth: u32, pruning: &Pruning) -> (usize, Metrics)
+  where
+    G: Game + Display + Send + Sync + Hash + Eq + 'static,
+    G::Move: Display,
+    G::PlayerIdentifier: Debug,
+  {
+    let mut disagreements = 0;
+    let mut total = Metrics::new();
+    for game in positions {
+      let exact =
+        solve_with_metrics(game, options(depth, Pruning::default()), RandomState::new()).0;
+      let (pruned, metrics) =
+        solve_with_metrics(game, options(depth, pruning.clone()), RandomState::new());
+      let pruned_value = pruned.score.score_at_depth(depth);
+      let exact_value = exact.score.score_at_depth(depth);
+      if pruned_value != exact_value {
+        assert_eq!(
+          pruned_value,
+          ScoreValue::Tie,
+          "Pruned score {} contradicts {} for\n{game}",
+          pruned.score,
+          exact.score
+        );
+        disagreements += 1;
+      }
+      total += metrics;
+    }
+    (disagreements, total)
+  }
+
+  #[test]
+  fn test_late_move_reduction() {
+    let pruning = Pruning {
+      late_move_reduction: Some(LateMoveReduction {
+        min_depth: 3,
+        min_rank: 2,
+        reduction: 2,
+      }),
+      pass_reduction: None,
+    };
+    // Tic tac toe is a tie, and so are most positions near the start.
+    let (disagreements, mut metrics) =
+      check_against_unpruned(&positions(Ttt::new(), 1), 9, &pruning);
+    assert_eq!(disagreements, 0);
+    // This is a first player win, and reducing the search hides some wins.
+    metrics += check_against_unpruned(&positions(Gomoku::new(4, 4, 3), 1), 5, &pruning).1;
+    if Metrics::ENABLED {
+      assert!(metrics.late_move_reductions() > 0);
+      assert_eq!(metrics.pass_reductions(), 0);
+    }
+  }
+
+  #[test]
+  fn test_pass_reduction() {
+    let pruning = Pruning {
+      late_move_reduction: None,
+      pass_reduction: Some(PassReduction {
+        min_depth: 3,
+        reduction: 2,
+      }),
+    };
+    let (_, metrics) = check_against_unpruned(&positions(Gomoku::new(4, 4, 3), 1), 5, &pruning);
+    if Metrics::ENABLED {
+      assert!(metrics.pass_reductions() > 0);
+    }
+
+    // Tic tac toe has no equivalent of passing.
+    let (_, metrics) = check_against_unpruned(&[Ttt::new()], 9, &pruning);
+    assert_eq!(metrics.pass_reductions(), 0);
+  }
+}
@@ -0,0 +1,117 @@
+use std::{
+  fmt::{Debug, Display},
+  hash::Hash,
+};
+
+use abstract_game::Game;
+
+use crate::{cooperate::Options, engine::Engine};
+
+/// Checks whether `game` makes a puzzle: whether the player to move has
+/// exactly one move which wins within `max_moves_to_win` of their own moves
+/// ("mate in N"). If so, returns the line of play from `game` which ends in
+/// the win, alternating between the winning player's moves and their
+/// opponent's best replies.
+///
+/// Uniqueness is verified one move deeper than the win: no other move may win
+/// within `max_moves_to_win + 1` moves either, so the alternatives to the
+/// solution aren't merely slightly slower wins. `options` configures the
+/// searches, other than their depth and time limit.
+pub fn find_unique_win<G>(game: &G, max_moves_to_win: u32, options: Options) -> Option<Vec<G::Move>>
+where
+  G: Game + Display + Send + Sync + Hash + PartialEq + Eq + 'static,
+  G::Move: Display,
+  G::PlayerIdentifier: Debug,
+{
+  if max_moves_to_win == 0 {
+    return None;
+  }
+  let options_for_depth = |search_depth: u32| Options {
+    search_depth,
+    unit_depth: options.unit_depth.min(search_depth / 2),
+    time_limit: None,
+    ..options.clone()
+  };
+
+  // A win in N moves takes 2N - 1 plies: the winning player's N moves, and
+  // the opponent's N - 1 replies.
+  let win_depth = 2 * max_moves_to_win - 1;
+  let mut engine = Engine::new(game.clone(), options_for_depth(win_depth));
+  let score = engine.solve();
+  if !score.cur_player_wins() || score.turn_count_win() == 0 || score.turn_count_win() > win_depth {
+    return None;
+  }
+
+  // Searching two plies further finds wins one move slower, which would also
+  // solve the puzzle in all but name.
+  engine.set_options(options_for_depth(win_depth + 2));
+  engine.solve();
+  let winning_moves = engine
+    .move_scores()?
+    .iter()
+    .filter(|(_, score)| score.cur_player_wins() && score.turn_count_win() != 0)
+    .count();
+  if winning_moves != 1 {
+    return None;
+  }
+
+  let mut solution = engine.principal_variation();
+  solution.truncate(score.turn_count_win() as usize);
+  Some(solution)
+}
+
+#[cfg(test)]
+mod tests {
+  use abstract_game::{Game, GameResult};
+
+  use crate::{test::tic_tac_toe::Ttt, Options};
+
+  use super::find_unique_win;
+
+  fn options() -> Options {
+    Options {
+      num_threads: 2,
+      search_depth: 1,
+      unit_depth: 1,
+      ..Default::default()
+    }
+  }
+
+  /// Plays the moves at `tiles` in order, starting with X.
+  fn play(tiles: &[(u32, u32)]) -> Ttt {
+    let mut ttt = Ttt::new();
+    for &(x, y) in tiles {
+      let m = ttt
+        .each_move()
+        .find(|m| m.to_string() == format!("({x}, {y})"))
+        .unwrap();
+      ttt.make_move(m);
+    }
+    ttt
+  }
+
+  #[test]
+  fn test_unique_win() {
+    // X to move can only win at (2, 0), and O wins next turn otherwise.
+    let ttt = play(&[(0, 0), (0, 1), (1, 0), (1, 1)]);
+    let solution = find_unique_win(&ttt, 1, options()).unwrap();
+    assert_eq!(solution.len(), 1);
+    assert_eq!(solution[0].to_string(), "(2, 0)");
+    assert!(matches!(
+      ttt.with_move(solution[0]).finished(),
+      GameResult::Win(_)
+    ));
+  }
+
+  #[test]
+  fn test_two_wins() {
+    // X to move wins at both (2, 0) and (0, 2).
+    let ttt = play(&[(0, 0), (2, 2), (1, 0), (1, 2), (0, 1), (2, 1)]);
+    assert!(find_unique_win(&ttt, 1, options()).is_none());
+  }
+
+  #[test]
+  fn test_no_win() {
+    assert!(find_unique_win(&Ttt::new(), 2, options()).is_none());
+  }
+}
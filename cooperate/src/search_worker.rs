@@ -2,62 +2,107 @@ use std::{
   fmt::{Debug, Display},
   hash::{BuildHasher, Hash},
   sync::Arc,
+  time::Instant,
 };
 
 use abstract_game::{Game, GameResult, Score};
 
 use crate::{
+  frame_pool::FramePool,
   global_data::{GlobalData, LookupResult},
+  metrics::ProgressPublisher,
+  move_ordering::OrderingContext,
+  null_lock::NullLock,
   stack::{Stack, StackType},
   Metrics,
 };
 
+/// How many states a worker explores between checks of whether it should
+/// stop or pause, which is also how often it publishes its progress.
+pub(crate) const STOP_CHECK_INTERVAL: u32 = 1024;
+
 pub struct WorkerData<G, H>
 where
   G: Game,
 {
   /// Index of this worker thread, which corresponds to the position of the
-  /// thread's queue in the globals struct.
+  /// thread's deques in the globals struct's work queues.
   thread_idx: u32,
 
   globals: Arc<GlobalData<G, H>>,
   metrics: Metrics,
+  progress: Option<ProgressPublisher>,
+  /// The memory of the stack frames of finished work units, for the next
+  /// units this worker searches to reuse.
+  frame_pool: FramePool<G>,
 }
 
 impl<G, H> WorkerData<G, H>
 where
-  G: Game,
+  G: Game + Display + Hash + Eq + 'static,
+  G::Move: Display,
+  G::PlayerIdentifier: Debug,
+  H: BuildHasher + Clone,
 {
   pub fn new(thread_idx: u32, globals: Arc<GlobalData<G, H>>) -> Self {
+    let progress = globals
+      .progress()
+      .map(|progress| ProgressPublisher::new(thread_idx, progress.clone()));
+    let metrics = if globals.log_visits() {
+      Metrics::with_visit_log()
+    } else {
+      Metrics::new()
+    };
     Self {
       thread_idx,
       globals,
-      metrics: Metrics::new(),
+      metrics,
+      progress,
+      frame_pool: FramePool::new(),
     }
   }
 }
 
-pub fn start_worker<G, H>(mut data: WorkerData<G, H>)
+/// Runs a worker until no work is left to do or steal, returning the metrics
+/// it collected.
+pub fn start_worker<G, H>(mut data: WorkerData<G, H>) -> Metrics
 where
   G: Display + Game + Hash + Eq + 'static,
   G::Move: Display,
   G::PlayerIdentifier: Debug,
   H: BuildHasher + Clone,
 {
-  let queue = data.globals.queue(data.thread_idx);
+  let start = Instant::now();
+  let queue = data.globals.work_queues().local(data.thread_idx);
+  let mut until_stop_check = STOP_CHECK_INTERVAL;
 
-  loop {
-    let unit = queue.pop();
+  'units: loop {
+    let unit = queue.next(&mut data.metrics, || data.globals.checkpoint());
 
     let stack_ptr = match unit {
       Some(stack_ptr) => *stack_ptr,
-      // TODO: steal
       None => break,
     };
     // We own stack here, so we can access it without atomics.
     let stack = unsafe { &mut *stack_ptr };
+    stack.prepare(&mut data.frame_pool, &mut data.metrics);
 
     loop {
+      data.metrics.record_node();
+      until_stop_check -= 1;
+      if until_stop_check == 0 {
+        until_stop_check = STOP_CHECK_INTERVAL;
+        if let Some(progress) = &mut data.progress {
+          progress.publish(&data.metrics);
+        }
+        if data.globals.checkpoint() {
+          // Hand the unit back, so it can be cleaned up once all workers have
+          // stopped.
+          queue.push(unsafe { NullLock::new(stack_ptr) }, stack.bottom_depth());
+          break 'units;
+        }
+      }
+
       if stack.bottom_frame().is_none() {
         // We've finished exploring this stack frame.
         match stack.stack_type() {
@@ -67,8 +112,8 @@ where
           }
         }
 
-        // Delete the stack pointer.
-        unsafe { drop(Box::from_raw(stack_ptr)) };
+        // Delete the stack pointer, keeping its frames for the next unit.
+        unsafe { Box::from_raw(stack_ptr) }.recycle(&mut data.frame_pool);
         break;
       }
 
@@ -125,11 +170,24 @@ where
             // If the state was not found, then we can continue on exploring it.
             LookupResult::NotFound => {
               // println!("    [{}] Inserted placeholder in table", data.thread_idx);
+              data.metrics.record_visit(game, stack.bottom_depth());
+              if let Some(move_ordering) = data
+                .globals
+                .move_ordering()
+                .filter(|move_ordering| move_ordering.orders_depth(stack.bottom_depth()))
+              {
+                let table = data.globals.resolved_states_table();
+                let lookup = |game: &G| table.get(game);
+                let context = OrderingContext::new(stack.bottom_depth(), &lookup);
+                stack.order_bottom_moves(&mut data.frame_pool, &mut data.metrics, |game, moves| {
+                  move_ordering.order_moves(game, moves, &context)
+                });
+              }
             }
             // If the state was queued, then it was added to the list of states
             // waiting on the result of some game state. After this result is
-            // found, all states which are pending are re-added to some worker's
-            // queue (randomly distributed).
+            // found, all states which are pending are re-added to the queue of
+            // the worker that found it, where idle workers may steal them.
             LookupResult::Queued => {
               // println!("    [{}] Queued on other state", data.thread_idx);
               break;
@@ -138,11 +196,19 @@ where
         }
       }
 
-      data.globals.explore_next_state(stack_ptr, queue);
+      data
+        .globals
+        .explore_next_state(stack_ptr, &queue, &mut data.metrics);
+      data.metrics.record_cutoffs(stack.take_cutoffs());
     }
   }
 
-  println!("Worker {} done: {:?}", data.thread_idx, data.metrics);
+  let busy = start.elapsed().saturating_sub(data.metrics.idle());
+  data.metrics.record_busy(data.thread_idx, busy);
+  if let Some(progress) = &mut data.progress {
+    progress.publish(&data.metrics);
+  }
+  data.metrics
 }
 
 #[cfg(test)]
@@ -165,7 +231,7 @@ mod tests {
   fn test_nim_serial() {
     const STICKS: u32 = 100;
     let globals = Arc::new(GlobalData::new(STICKS + 1, 1));
-    globals.queue(0).push(unsafe {
+    globals.work_queues().inject(unsafe {
       NullLock::new(Box::into_raw(Box::new(Stack::make_root(
         Nim::new(STICKS),
         STICKS + 1,
@@ -177,7 +243,10 @@ mod tests {
     for sticks in 1..=STICKS {
       let cached_score = globals.resolved_states_table().get(&Nim::new(sticks));
       assert!(cached_score.is_some());
-      assert_eq!(cached_score.unwrap(), Nim::new(sticks).expected_score());
+      assert_eq!(
+        cached_score.unwrap(),
+        Nim::new(sticks).expected_search_score()
+      );
     }
   }
 
@@ -185,9 +254,9 @@ mod tests {
   fn test_ttt_serial() {
     const DEPTH: u32 = 10;
     let globals = Arc::new(GlobalData::new(DEPTH, 1));
-    globals
-      .queue(0)
-      .push(unsafe { NullLock::new(Box::into_raw(Box::new(Stack::make_root(Ttt::new(), DEPTH)))) });
+    globals.work_queues().inject(unsafe {
+      NullLock::new(Box::into_raw(Box::new(Stack::make_root(Ttt::new(), DEPTH))))
+    });
 
     start_worker(WorkerData::new(0, globals.clone()));
 
@@ -222,7 +291,7 @@ mod tests {
   fn test_gomoku_4x4_serial() {
     const DEPTH: u32 = 16;
     let globals = Arc::new(GlobalData::new(DEPTH, 1));
-    globals.queue(0).push(unsafe {
+    globals.work_queues().inject(unsafe {
       NullLock::new(Box::into_raw(Box::new(Stack::make_root(
         Gomoku::new(4, 4, 4),
         DEPTH,
@@ -4,7 +4,7 @@ use std::{
   hash::{BuildHasher, Hash},
 };
 
-use abstract_game::{Game, GameResult, Score, ScoreValue};
+use abstract_game::{Game, GameResult, MoveArena, Score, ScoreValue};
 
 use crate::table::Table;
 
@@ -27,6 +27,23 @@ pub fn find_best_move_serial_table<G: Clone + Game, H>(
   depth: u32,
   table: &Table<G, H>,
 ) -> (Option<Score>, Option<G::Move>)
+where
+  G: Display + Game + Hash + Eq,
+  H: BuildHasher + Clone,
+{
+  let mut arena = MoveArena::with_capacity(depth as usize * 16);
+  find_best_move_serial_arena(game, depth, table, &mut arena)
+}
+
+/// Recursive helper for `find_best_move_serial_table`. The moves of every
+/// state along the current search path are kept in `arena`, so no move lists
+/// are allocated per node.
+fn find_best_move_serial_arena<G, H>(
+  game: &G,
+  depth: u32,
+  table: &Table<G, H>,
+  arena: &mut MoveArena<G::Move>,
+) -> (Option<Score>, Option<G::Move>)
 where
   G: Display + Game + Hash + Eq,
   H: BuildHasher + Clone,
@@ -47,12 +64,19 @@ where
   let mut best_score = None;
   let mut best_move = None;
 
-  for m in game.each_move() {
+  // This state's moves occupy `moves_start..moves_end` of the arena, and must
+  // be truncated away before returning.
+  let moves_start = arena.len();
+  let moves_end = moves_start + game.expand_into(arena).len();
+
+  for move_idx in moves_start..moves_end {
+    let m = arena[move_idx];
     let mut g = game.clone();
     g.make_move(m);
 
-    match g.finished() {
+    let score = match g.finished() {
       GameResult::Win(player) => {
+        arena.truncate(moves_start);
         if player == game.current_player() {
           check_score(game.clone(), Score::win(1), table);
           return (Some(Score::win(1)), Some(m));
@@ -61,20 +85,18 @@ where
           return (Some(Score::lose(1)), Some(m));
         }
       }
-      GameResult::Tie => {
-        check_score(game.clone(), Score::tie(1), table);
-        return (Some(Score::tie(1)), None);
+      // A move that ends the game in a tie is a proven draw, but the other
+      // moves may still win, so the search carries on.
+      GameResult::Tie => Score::guaranteed_tie(),
+      GameResult::NotFinished => {
+        match find_best_move_serial_arena(&g, depth - 1, table, arena).0 {
+          Some(score) => score.backstep(),
+          // Consider winning by no legal moves as not winning until after the
+          // other player's attempt at making a move, since all game states
+          // that don't have 4 in a row of a pawn are considered a tie.
+          None => Score::win(2),
+        }
       }
-      GameResult::NotFinished => {}
-    }
-
-    let (score, _) = find_best_move_serial_table(&g, depth - 1, table);
-    let score = match score {
-      Some(score) => score.backstep(),
-      // Consider winning by no legal moves as not winning until after the
-      // other player's attempt at making a move, since all game states that
-      // don't have 4 in a row of a pawn are considered a tie.
-      None => Score::win(2),
     };
 
     match best_score.clone() {
@@ -97,6 +119,8 @@ where
     }
   }
 
+  arena.truncate(moves_start);
+
   if let Some(ref score) = best_score {
     check_score(game.clone(), score.clone(), table);
   }
@@ -7,7 +7,7 @@ use std::{
 
 use abstract_game::{Game, Score};
 
-use crate::transparent_iterator::TransparentIterator;
+use crate::{frame_pool::FramePool, transparent_iterator::TransparentIterator, Metrics};
 
 /// Algorithm:
 /// ```rs
@@ -92,8 +92,14 @@ where
   /// An iterator over the moves at this game state. If `None`, then no moves
   /// have been iterated over yet.
   move_gen: Option<G::MoveGenerator>,
+  /// The moves left to explore after `current_move`, if they were put in order
+  /// by `order_moves`, in which case they are taken from here instead of
+  /// `move_gen`. They are stored in reverse, so the next move is the last.
+  ordered_moves: Option<Vec<G::Move>>,
   /// The current move being explored by the child of this frame.
   current_move: Option<G::Move>,
+  /// The number of moves explored before `current_move`.
+  move_rank: u32,
   /// The best score found for this game so far.
   best_score: Score,
   /// The corresponding best move found for `best_score`.
@@ -114,7 +120,9 @@ where
     let mut s = Self {
       game,
       move_gen: None,
+      ordered_moves: None,
       current_move: None,
+      move_rank: 0,
       best_score: Score::no_info(),
       best_move: None,
       dependents: null_mut(),
@@ -132,6 +140,20 @@ where
     self.current_move
   }
 
+  /// The position of `current_move` in the order the moves of this frame are
+  /// explored, starting from 0.
+  pub fn move_rank(&self) -> u32 {
+    self.move_rank
+  }
+
+  /// True if this frame has already found a move that ties to `depth` without
+  /// any forced win or loss beyond it.
+  fn has_tie_to(&self, depth: u32) -> bool {
+    self.best_move.is_some()
+      && self.best_score.turn_count_win() == 0
+      && self.best_score.turn_count_tie() >= depth
+  }
+
   pub fn best_score(&self) -> (Score, Option<G::Move>) {
     // The state should have been fully explored.
     debug_assert!(self.current_move.is_none());
@@ -149,7 +171,22 @@ where
 
   /// Updates the best score/move pair of this frame if `score` is better than
   /// the current best score, and advances the current move to the next move.
-  fn update_score_and_advance(&mut self, score: Score) {
+  ///
+  /// If `score` is a win for the current player within `depth` moves, no
+  /// other move can do better, so the remaining moves are cut off, and this
+  /// returns true.
+  fn update_score_and_advance(&mut self, score: Score, depth: u32) -> bool {
+    if score.cur_player_wins() && score.turn_count_win() != 0 && score.turn_count_win() <= depth {
+      // The remaining moves weren't explored, so nothing can be said about
+      // ties.
+      if self.best_move.is_none() || score.better(&self.best_score) {
+        self.best_score = score.break_early();
+        self.best_move = self.current_move;
+      }
+      self.current_move = None;
+      return true;
+    }
+
     if self.best_move.is_none() || score.better(&self.best_score) {
       // println!(
       //   "    Updating {} ({}) to {} ({}) for\n{}\n",
@@ -176,6 +213,50 @@ where
       // );
     }
     self.advance();
+    self.move_rank += 1;
+    false
+  }
+
+  /// Puts the moves of this frame in the order `order` sorts them into, in a
+  /// buffer from `take_buffer` unless they were already ordered. This may only
+  /// be called before any move of the frame has been explored.
+  pub fn order_moves(
+    &mut self,
+    take_buffer: impl FnOnce() -> Vec<G::Move>,
+    order: impl FnOnce(&G, &mut [G::Move]),
+  ) {
+    debug_assert!(self.best_move.is_none());
+    let Some(first_move) = self.current_move else {
+      return;
+    };
+
+    let mut moves = match self.ordered_moves.take() {
+      Some(mut ordered_moves) => {
+        ordered_moves.push(first_move);
+        ordered_moves.reverse();
+        ordered_moves
+      }
+      None => {
+        let mut moves = take_buffer();
+        moves.push(first_move);
+        if let Some(move_gen) = &mut self.move_gen {
+          while let Some(m) = move_gen.next(&self.game) {
+            moves.push(m);
+          }
+        }
+        moves
+      }
+    };
+    order(&self.game, &mut moves);
+
+    moves.reverse();
+    self.current_move = moves.pop();
+    self.ordered_moves = Some(moves);
+  }
+
+  /// Takes the buffer the moves of this frame were ordered in, if they were.
+  fn take_move_buffer(&mut self) -> Option<Vec<G::Move>> {
+    self.ordered_moves.take()
   }
 
   pub unsafe fn queue_dependant_unlocked(&mut self, dependant: *mut Stack<G>) {
@@ -197,6 +278,10 @@ where
 
   /// Advances the current move to the next possible move.
   fn advance(&mut self) {
+    if let Some(ordered_moves) = &mut self.ordered_moves {
+      self.current_move = ordered_moves.pop();
+      return;
+    }
     self.current_move = match &mut self.move_gen {
       Some(move_gen) => move_gen.next(&self.game),
       None => {
@@ -216,8 +301,12 @@ where
   /// The search depth this stack frame will go out to starting from the root
   /// frame.
   root_depth: u32,
-  /// The frames of this stack.
+  /// The frames of this stack. Until the stack is first searched, this only
+  /// has room for the root frame, see `prepare`.
   frames: Vec<StackFrame<G>>,
+  /// Move buffers of popped frames, for the frames pushed after them to order
+  /// their moves in.
+  spare_move_buffers: Vec<Vec<G::Move>>,
   ty: StackType<G>,
   /// TODO: Can remove state? Implicit from where the stack lies in the data
   /// structure.
@@ -234,6 +323,9 @@ where
   /// outstanding children. The child to decrease this number to 0 is the one to
   /// revive the state.
   outstanding_children: AtomicU32,
+  /// The number of frames whose remaining moves were cut off since the last
+  /// call to `take_cutoffs`.
+  cutoffs: u64,
 }
 
 impl<G> Stack<G>
@@ -245,10 +337,12 @@ where
     let mut root = Self {
       root_depth: depth,
       ty: StackType::Root,
-      frames: Vec::with_capacity(depth as usize),
+      frames: Vec::with_capacity(1),
+      spare_move_buffers: Vec::new(),
       state: StackState::Live {},
       next: null_mut(),
       outstanding_children: AtomicU32::new(0),
+      cutoffs: 0,
     };
     root.frames.push(StackFrame::new(initial_game));
     root
@@ -257,11 +351,13 @@ where
   fn make_child(game: G, depth: u32, parent: *mut Self) -> Self {
     let mut root = Self {
       root_depth: depth,
-      frames: Vec::with_capacity(depth as usize),
+      frames: Vec::with_capacity(1),
+      spare_move_buffers: Vec::new(),
       ty: StackType::Child { parent },
       state: StackState::Live {},
       next: null_mut(),
       outstanding_children: AtomicU32::new(0),
+      cutoffs: 0,
     };
     root.frames.push(StackFrame::new(game));
     root
@@ -271,22 +367,80 @@ where
     &self.ty
   }
 
+  /// Gives this stack room for all of its frames, from `pool`, before it is
+  /// searched. Work units are created long before they're searched, so they
+  /// only hold their root frame until then, and the frames of finished units
+  /// are reused through the pool.
+  pub fn prepare(&mut self, pool: &mut FramePool<G>, metrics: &mut Metrics) {
+    if self.frames.capacity() < self.root_depth as usize {
+      let mut frames = pool.take_frames(self.root_depth as usize, metrics);
+      frames.append(&mut self.frames);
+      self.frames = frames;
+    }
+  }
+
+  /// Returns the memory of this finished stack to `pool`, before it's freed.
+  pub fn recycle(&mut self, pool: &mut FramePool<G>) {
+    debug_assert!(self.frames.is_empty());
+    pool.recycle_frames(std::mem::take(&mut self.frames));
+    for buffer in self.spare_move_buffers.drain(..) {
+      pool.recycle_move_buffer(buffer);
+    }
+  }
+
+  /// Orders the moves of the bottom frame with `order`, see
+  /// `StackFrame::order_moves`.
+  pub fn order_bottom_moves(
+    &mut self,
+    pool: &mut FramePool<G>,
+    metrics: &mut Metrics,
+    order: impl FnOnce(&G, &mut [G::Move]),
+  ) {
+    let spare_move_buffers = &mut self.spare_move_buffers;
+    let take_buffer = || match spare_move_buffers.pop() {
+      Some(buffer) => {
+        metrics.record_frame_reuse();
+        buffer
+      }
+      None => pool.take_move_buffer(metrics),
+    };
+    if let Some(frame) = self.frames.last_mut() {
+      frame.order_moves(take_buffer, order);
+    }
+  }
+
   pub fn push(&mut self, game: G) {
     debug_assert!(!self.is_full());
     self.frames.push(StackFrame::new(game));
   }
 
   pub fn update_parent_score_and_advance(&mut self, score: Score) {
+    if self.frames.is_empty() {
+      return;
+    }
+    let depth = self.bottom_depth();
     if let Some(parent_frame) = self.frames.last_mut() {
-      parent_frame.update_score_and_advance(score);
+      if parent_frame.update_score_and_advance(score, depth) {
+        self.cutoffs += 1;
+      }
     }
   }
 
+  /// Returns the number of frames whose remaining moves were cut off since
+  /// the last call, and resets the count.
+  pub fn take_cutoffs(&mut self) -> u64 {
+    std::mem::take(&mut self.cutoffs)
+  }
+
   /// To be called to resolve the bottom frame to the given score which is
   /// already relative to the parent frame. This will remove the bottom stack
   /// frame and update the score/current move of the parent stack frame.
   pub fn pop_with_backstepped_score(&mut self, score: Score) -> StackFrame<G> {
-    let completed_frame = self.frames.pop().unwrap();
+    let mut completed_frame = self.frames.pop().unwrap();
+    if let Some(mut buffer) = completed_frame.take_move_buffer() {
+      buffer.clear();
+      self.spare_move_buffers.push(buffer);
+    }
     self.update_parent_score_and_advance(score);
     completed_frame
   }
@@ -306,6 +460,23 @@ where
     self.pop_with_score(completed_frame.best_score().0.clone())
   }
 
+  /// Frees the stack at `stack_ptr`, along with all stacks suspended on any of
+  /// its frames (and all stacks suspended on those, etc.).
+  ///
+  /// This is unsafe because there must not be any other references to these
+  /// stacks.
+  pub unsafe fn free_with_dependants(stack_ptr: *mut Self) {
+    let mut to_free = vec![stack_ptr];
+    while let Some(stack_ptr) = to_free.pop() {
+      let mut stack = unsafe { Box::from_raw(stack_ptr) };
+      for frame in stack.frames.iter_mut() {
+        while let Some(dependant) = unsafe { frame.pop_dependant_unlocked() } {
+          to_free.push(dependant);
+        }
+      }
+    }
+  }
+
   pub fn stack_state(&self) -> StackState {
     self.state
   }
@@ -394,4 +565,29 @@ where
   pub fn bottom_depth(&self) -> u32 {
     self.root_depth - self.frames.len() as u32 + 1
   }
+
+  /// True if the parent of the bottom frame is in this stack, and has already
+  /// found a move that ties to its full depth without any forced win or loss
+  /// beyond it. The parent's score can then only change if the bottom frame
+  /// is a loss for its player to move, so a bottom frame known not to lose can
+  /// be skipped, and resolved to any score it can't lose with.
+  pub fn parent_has_tie(&self) -> bool {
+    let Some(parent) = self
+      .frames
+      .len()
+      .checked_sub(2)
+      .map(|idx| &self.frames[idx])
+    else {
+      return false;
+    };
+    parent.has_tie_to(self.bottom_depth() + 1)
+  }
+
+  /// True if the bottom frame has already found a move that ties to its full
+  /// depth. See `parent_has_tie`.
+  pub fn bottom_has_tie(&self) -> bool {
+    self
+      .bottom_frame()
+      .is_some_and(|frame| frame.has_tie_to(self.bottom_depth()))
+  }
 }
@@ -1,13 +1,253 @@
 use std::{
-  collections::hash_map::RandomState,
+  collections::{hash_map::RandomState, BTreeMap},
+  fs::File,
   hash::{BuildHasher, Hash},
+  io::{self, BufWriter, Write},
+  path::Path,
+  sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
 };
 
-use abstract_game::{Game, Score};
+use abstract_game::{Compress, Game, Score};
 use dashmap::{mapref::entry::Entry, DashMap};
+use memmap2::Mmap;
 
+/// Identifies table files.
+const TABLE_MAGIC: &[u8; 8] = b"onorotbl";
+
+/// The version of the table file format.
+const TABLE_VERSION: u32 = 1;
+
+/// The size of the table file header: the magic bytes, the version, the size
+/// of each compressed game state, and the number of entries.
+const HEADER_SIZE: usize = 8 + 4 + 4 + 8;
+
+fn invalid_data(message: String) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Which entries to evict once a table reaches its memory limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReplacementPolicy {
+  /// Evict the entries determined to the shallowest depth, which are the
+  /// cheapest to recompute.
+  #[default]
+  DepthPreferred,
+  /// Evict arbitrary entries regardless of their depth, favoring the entries
+  /// the search is currently producing over everything it found before.
+  AlwaysReplace,
+  /// Always keep the deepest half of the table, and evict arbitrary entries
+  /// from the other half.
+  TwoTier,
+}
+
+/// Bounds the memory used by the table of resolved states.
+#[derive(Clone, Debug)]
+pub struct TableLimit {
+  /// The approximate number of bytes the table may use.
+  pub max_bytes: usize,
+  pub policy: ReplacementPolicy,
+}
+
+struct Limit {
+  max_entries: usize,
+  policy: ReplacementPolicy,
+}
+
+/// The accesses to one shard of a `Table`, padded to a cache line so the
+/// counters of different shards don't contend with each other.
+#[cfg(feature = "metrics")]
+#[derive(Default)]
+#[repr(align(64))]
+struct ShardCounters {
+  lookups: AtomicU64,
+  hits: AtomicU64,
+  updates: AtomicU64,
+  contended: AtomicU64,
+}
+
+#[cfg(feature = "metrics")]
+impl ShardCounters {
+  fn stats(&self) -> ShardStats {
+    ShardStats {
+      entries: 0,
+      lookups: self.lookups.load(Ordering::Relaxed),
+      hits: self.hits.load(Ordering::Relaxed),
+      updates: self.updates.load(Ordering::Relaxed),
+      contended: self.contended.load(Ordering::Relaxed),
+    }
+  }
+}
+
+/// Statistics on one shard of a `Table`. The access counts are always 0 if
+/// the `metrics` feature is disabled.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShardStats {
+  /// The number of entries in the shard.
+  pub entries: usize,
+  /// The number of lookups of states in the shard.
+  pub lookups: u64,
+  /// The number of lookups that found a score.
+  pub hits: u64,
+  /// The number of scores merged into the shard.
+  pub updates: u64,
+  /// The number of accesses that found the shard locked by another thread,
+  /// and had to wait for it.
+  pub contended: u64,
+}
+
+/// How a score from the table bounds the score of its state searched to the
+/// depth of a query, from the perspective of the player to move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bound {
+  /// The score is determined to the depth of the query, so it is the score a
+  /// search that deep would find.
+  Exact,
+  /// The player to move has a forced win, but only deeper than the query, so
+  /// a search to its depth would find a win or a tie: the player can't lose.
+  Lower,
+  /// The other player has a forced win, but only deeper than the query, so a
+  /// search to its depth would find a loss or a tie: the player can't win.
+  Upper,
+}
+
+impl Bound {
+  /// The bound `score` places on a search `depth` moves deep, or `None` if it
+  /// says nothing about a search that deep.
+  pub fn of(score: &Score, depth: u32) -> Option<Self> {
+    if score.determined(depth) {
+      Some(Bound::Exact)
+    } else if score.turn_count_win() == 0 {
+      None
+    } else if score.cur_player_wins() {
+      Some(Bound::Lower)
+    } else {
+      Some(Bound::Upper)
+    }
+  }
+}
+
+/// A score found in the table by `Table::probe`, along with what it says
+/// about the search that probed for it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Probe {
+  pub score: Score,
+  /// Whether the score was determined to at least the depth of the query.
+  /// This alone doesn't make the score exact: a forced win found deeper than
+  /// the query only bounds shallower searches, while a forced win found within
+  /// the query's depth is exact for every deeper search.
+  pub sufficient_depth: bool,
+  /// The bound the score places on a search to the depth of the query, if
+  /// any.
+  pub bound: Option<Bound>,
+}
+
+/// Counts of how often the hash the table files states under is shared by
+/// distinct states, from a table's verification mode (see
+/// `Table::set_verification`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VerificationStats {
+  /// The number of states inserted into the table whose hash was already held
+  /// by a different state.
+  pub hash_collisions: u64,
+  /// The number of lookups whose hash was held by a different state than the
+  /// one looked up, which a table keyed by hash alone would have answered
+  /// with the score of the wrong state.
+  pub false_hits: u64,
+}
+
+/// Keeps the compressed form of every state inserted into a table, by the
+/// hash the table files it under, so lookups and inserts can be checked
+/// against the full states sharing their hash. The table's own entries can't
+/// be found by hash alone, so they are duplicated here.
+struct Verifier<G> {
+  states: DashMap<u64, Vec<Box<[u8]>>>,
+  compress: fn(&G, &mut [u8]),
+  decompress: fn(&[u8]) -> Option<G>,
+  compressed_size: usize,
+  hash_collisions: AtomicU64,
+  false_hits: AtomicU64,
+}
+
+impl<G> Verifier<G>
+where
+  G: Eq,
+{
+  fn new() -> Self
+  where
+    G: Compress,
+  {
+    Self {
+      states: DashMap::new(),
+      compress: G::compress,
+      decompress: G::decompress,
+      compressed_size: G::COMPRESSED_SIZE,
+      hash_collisions: AtomicU64::new(0),
+      false_hits: AtomicU64::new(0),
+    }
+  }
+
+  /// Whether any state recorded under `hash` differs from `state`, and
+  /// whether any is the same.
+  fn compare(&self, states: &[Box<[u8]>], state: &G) -> (bool, bool) {
+    states.iter().fold((false, false), |(differ, same), bytes| {
+      let other = (self.decompress)(bytes).expect("Corrupt state in table verifier");
+      let equal = other == *state;
+      (differ || !equal, same || equal)
+    })
+  }
+
+  /// Records a state newly inserted into the table under `hash`.
+  fn check_insert(&self, state: &G, hash: u64) {
+    let mut states = self.states.entry(hash).or_default();
+    let (differ, same) = self.compare(&states, state);
+    if differ {
+      self.hash_collisions.fetch_add(1, Ordering::Relaxed);
+    }
+    // States evicted from the table stay here, so reinserting one doesn't
+    // record it twice.
+    if !same {
+      let mut bytes = vec![0; self.compressed_size].into_boxed_slice();
+      (self.compress)(state, &mut bytes);
+      states.push(bytes);
+    }
+  }
+
+  fn check_lookup(&self, state: &G, hash: u64) {
+    let Some(states) = self.states.get(&hash) else {
+      return;
+    };
+    if self.compare(&states, state).0 {
+      self.false_hits.fetch_add(1, Ordering::Relaxed);
+    }
+  }
+
+  fn take_stats(&self) -> VerificationStats {
+    VerificationStats {
+      hash_collisions: self.hash_collisions.swap(0, Ordering::Relaxed),
+      false_hits: self.false_hits.swap(0, Ordering::Relaxed),
+    }
+  }
+}
+
+/// The table of resolved states, split into `2^k` shards which are each
+/// behind their own lock. Which shard a state belongs to is chosen by the
+/// high bits of its hash, so threads only contend with each other when they
+/// access states in the same shard.
 pub struct Table<G, H> {
   table: DashMap<G, Score, H>,
+  /// The access counters of each shard, indexed like the shards of `table`.
+  #[cfg(feature = "metrics")]
+  shard_counters: Box<[ShardCounters]>,
+  limit: Option<Limit>,
+  /// The number of entries in the table, which is only tracked when the table
+  /// is limited, since `DashMap::len` has to lock every shard.
+  entries: AtomicUsize,
+  /// Set while a thread is evicting entries, so other threads don't start
+  /// evicting at the same time.
+  evicting: AtomicBool,
+  /// The number of entries evicted since the last call to `take_evictions`.
+  evictions: AtomicU64,
+  verifier: Option<Verifier<G>>,
 }
 
 impl<G> Table<G, RandomState>
@@ -15,9 +255,16 @@ where
   G: Game + Hash + Eq,
 {
   pub fn new() -> Self {
-    Self {
-      table: DashMap::new(),
-    }
+    Self::with_hasher(RandomState::new())
+  }
+}
+
+impl<G> Default for Table<G, RandomState>
+where
+  G: Game + Hash + Eq,
+{
+  fn default() -> Self {
+    Self::new()
   }
 }
 
@@ -26,31 +273,593 @@ where
   G: Game + Hash + Eq,
   H: BuildHasher + Clone,
 {
+  /// A rough estimate of the memory used per entry, including the hash
+  /// table's control bytes and the spare capacity it keeps to stay below its
+  /// maximum load factor.
+  const ENTRY_BYTES: usize = std::mem::size_of::<(G, Score)>() * 8 / 7 + 1;
+
+  /// Once a limited table is full, entries are evicted until it is this
+  /// fraction full, so that evictions happen in batches.
+  const EVICTION_TARGET: (usize, usize) = (7, 8);
+
+  /// Constructs a table with a shard count chosen for the number of CPUs, of
+  /// four shards per CPU.
   pub fn with_hasher(hasher: H) -> Self {
+    Self::from_map(DashMap::with_hasher(hasher))
+  }
+
+  /// Constructs a table with `2^shard_bits` shards. `shard_bits` must be at
+  /// least 1.
+  pub fn with_shard_bits(hasher: H, shard_bits: u32) -> Self {
+    Self::from_map(DashMap::with_hasher_and_shard_amount(
+      hasher,
+      1 << shard_bits,
+    ))
+  }
+
+  fn from_map(table: DashMap<G, Score, H>) -> Self {
     Self {
-      table: DashMap::with_hasher(hasher),
+      #[cfg(feature = "metrics")]
+      shard_counters: table.shards().iter().map(|_| Default::default()).collect(),
+      table,
+      limit: None,
+      entries: AtomicUsize::new(0),
+      evicting: AtomicBool::new(false),
+      evictions: AtomicU64::new(0),
+      verifier: None,
+    }
+  }
+
+  /// Limits the memory used by the table, evicting entries right away if it
+  /// is already over the limit. Passing `None` lets the table grow without
+  /// bound.
+  pub fn set_limit(&mut self, limit: Option<TableLimit>) {
+    self.limit = limit.map(|limit| Limit {
+      max_entries: (limit.max_bytes / Self::ENTRY_BYTES).max(1),
+      policy: limit.policy,
+    });
+    self.entries.store(self.table.len(), Ordering::Relaxed);
+    if let Some(limit) = &self.limit {
+      if self.table.len() > limit.max_entries {
+        self.evict(limit);
+      }
     }
   }
 
-  #[cfg(test)]
+  /// Returns the number of entries evicted since the last call, and resets
+  /// the count.
+  pub fn take_evictions(&self) -> u64 {
+    self.evictions.swap(0, Ordering::Relaxed)
+  }
+
+  /// Turns on verification mode, which checks every access to the table
+  /// against the full states sharing its hash, counting how often distinct
+  /// states share a hash. This quantifies whether the hash is wide enough for
+  /// the number of states in the table, at the cost of a copy of every state
+  /// inserted, which is never evicted, and decompressing the states sharing a
+  /// hash on every access.
+  ///
+  /// The counts can be read with `take_verification_stats`, and are recorded
+  /// in the metrics of searches of this table. Turning verification off
+  /// discards the copies and the counts.
+  pub fn set_verification(&mut self, enabled: bool)
+  where
+    G: Compress,
+  {
+    self.verifier = enabled.then(Verifier::new);
+  }
+
+  /// Returns the counts of verification mode since the last call, and resets
+  /// them. Always 0 if verification is off.
+  pub fn take_verification_stats(&self) -> VerificationStats {
+    self
+      .verifier
+      .as_ref()
+      .map_or_else(VerificationStats::default, Verifier::take_stats)
+  }
+
+  /// The hasher the table was constructed with.
+  pub(crate) fn hasher(&self) -> &H {
+    self.table.hasher()
+  }
+
   pub fn table(&self) -> &DashMap<G, Score, H> {
     &self.table
   }
 
+  /// The number of game states with scores in the table.
+  pub fn len(&self) -> usize {
+    self.table.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.table.is_empty()
+  }
+
+  /// The number of shards the table is split into.
+  pub fn num_shards(&self) -> usize {
+    self.table.shards().len()
+  }
+
+  /// Statistics on each shard of the table, which show how evenly states are
+  /// spread across the shards and how often threads wait on each other.
+  pub fn shard_stats(&self) -> Vec<ShardStats> {
+    #[cfg(feature = "metrics")]
+    let counters = self.shard_counters.iter().map(ShardCounters::stats);
+    #[cfg(not(feature = "metrics"))]
+    let counters = std::iter::repeat_with(ShardStats::default);
+
+    self
+      .table
+      .shards()
+      .iter()
+      .zip(counters)
+      .map(|(shard, stats)| ShardStats {
+        entries: shard.read().len(),
+        ..stats
+      })
+      .collect()
+  }
+
+  /// Returns the counters of the shard of `key`, recording whether the
+  /// shard's lock is held by another thread. `exclusive` is set if the access
+  /// will need the lock exclusively.
+  ///
+  /// Probing the lock takes it and releases it right away, so this costs an
+  /// extra pair of atomic operations on the shard's lock, which is only paid
+  /// when metrics are enabled.
+  #[cfg(feature = "metrics")]
+  fn shard_counters(&self, key: &G, exclusive: bool) -> &ShardCounters {
+    let idx = self.table.determine_map(key);
+    let counters = &self.shard_counters[idx];
+    let lock = &self.table.shards()[idx];
+    let available = if exclusive {
+      lock.try_write().is_some()
+    } else {
+      lock.try_read().is_some()
+    };
+    if !available {
+      counters.contended.fetch_add(1, Ordering::Relaxed);
+    }
+    counters
+  }
+
   pub fn get(&self, key: &G) -> Option<Score> {
-    self.table.get(key).map(|entry| entry.value().clone())
+    #[cfg(feature = "metrics")]
+    let counters = self.shard_counters(key, false);
+    let score = self.table.get(key).map(|entry| entry.value().clone());
+    if let Some(verifier) = &self.verifier {
+      verifier.check_lookup(key, self.hasher().hash_one(key));
+    }
+    #[cfg(feature = "metrics")]
+    {
+      counters.lookups.fetch_add(1, Ordering::Relaxed);
+      if score.is_some() {
+        counters.hits.fetch_add(1, Ordering::Relaxed);
+      }
+    }
+    score
+  }
+
+  /// Looks up `key` like `get`, additionally returning how its score bounds a
+  /// search of it `depth` moves deep.
+  pub fn probe(&self, key: &G, depth: u32) -> Option<Probe> {
+    self.get(key).map(|score| Probe {
+      sufficient_depth: score.determined_depth() >= depth,
+      bound: Bound::of(&score, depth),
+      score,
+    })
+  }
+
+  /// Merges every entry of `other` into this table.
+  pub fn extend(&self, other: &Self) {
+    for entry in other.table.iter() {
+      self.update(entry.key().clone(), entry.value().clone());
+    }
   }
 
   /// Updates an Onoro view in the table, potentially modifying the passed view
   /// to match the merged view that is in the table upon returning.
   pub fn update(&self, state: G, score: Score) {
-    match self.table.entry(state) {
+    #[cfg(feature = "metrics")]
+    self
+      .shard_counters(&state, true)
+      .updates
+      .fetch_add(1, Ordering::Relaxed);
+    let verified_state = self.verifier.as_ref().map(|_| state.clone());
+    let inserted = match self.table.entry(state) {
       Entry::Occupied(mut entry) => {
         entry.insert(entry.get().merge(&score));
+        false
       }
       Entry::Vacant(entry) => {
         entry.insert(score);
+        true
+      }
+    };
+
+    if let (Some(verifier), Some(state), true) = (&self.verifier, verified_state, inserted) {
+      verifier.check_insert(&state, self.hasher().hash_one(&state));
+    }
+
+    // The entry must be released before evicting, since evicting locks every
+    // shard of the table.
+    if let Some(limit) = &self.limit {
+      if inserted && self.entries.fetch_add(1, Ordering::Relaxed) + 1 > limit.max_entries {
+        self.evict(limit);
+      }
+    }
+  }
+
+  /// Evicts entries according to `limit.policy` until the table is
+  /// `EVICTION_TARGET` full.
+  fn evict(&self, limit: &Limit) {
+    if self.evicting.swap(true, Ordering::Acquire) {
+      // Another thread is already evicting.
+      return;
+    }
+
+    let (numerator, denominator) = Self::EVICTION_TARGET;
+    let target = limit.max_entries * numerator / denominator;
+    let mut to_evict = self.table.len().saturating_sub(target);
+
+    let mut depths = BTreeMap::<u32, usize>::new();
+    if limit.policy != ReplacementPolicy::AlwaysReplace {
+      for entry in self.table.iter() {
+        *depths.entry(entry.value().determined_depth()).or_default() += 1;
+      }
+    }
+
+    let mut evicted = 0;
+    match limit.policy {
+      ReplacementPolicy::DepthPreferred => {
+        // Evict everything below `cutoff`, and `at_cutoff` of the entries at
+        // exactly `cutoff`.
+        let (cutoff, mut at_cutoff) =
+          depth_cutoff(depths.iter(), to_evict).unwrap_or((u32::MAX, usize::MAX));
+        self.table.retain(|_, score| {
+          let depth = score.determined_depth();
+          let evict = depth < cutoff || (depth == cutoff && at_cutoff > 0);
+          if evict {
+            if depth == cutoff {
+              at_cutoff -= 1;
+            }
+            evicted += 1;
+          }
+          !evict
+        });
+      }
+      ReplacementPolicy::AlwaysReplace => {
+        self.table.retain(|_, _| {
+          let evict = to_evict > 0;
+          if evict {
+            to_evict -= 1;
+            evicted += 1;
+          }
+          !evict
+        });
+      }
+      ReplacementPolicy::TwoTier => {
+        // Keep everything above `cutoff`, and `at_cutoff` of the entries at
+        // exactly `cutoff`.
+        let (cutoff, mut at_cutoff) =
+          depth_cutoff(depths.iter().rev(), limit.max_entries / 2).unwrap_or((0, usize::MAX));
+        self.table.retain(|_, score| {
+          let depth = score.determined_depth();
+          if depth > cutoff || (depth == cutoff && at_cutoff > 0) {
+            if depth == cutoff {
+              at_cutoff -= 1;
+            }
+            return true;
+          }
+
+          let evict = to_evict > 0;
+          if evict {
+            to_evict -= 1;
+            evicted += 1;
+          }
+          !evict
+        });
+      }
+    }
+
+    self.entries.fetch_sub(evicted, Ordering::Relaxed);
+    self.evictions.fetch_add(evicted as u64, Ordering::Relaxed);
+    self.evicting.store(false, Ordering::Release);
+  }
+}
+
+/// Given the number of entries at each depth in the order they should be
+/// chosen, returns the depth at which `count` entries have been chosen, along
+/// with the number of entries to choose at exactly that depth. Returns `None`
+/// if there are fewer than `count` entries.
+fn depth_cutoff<'a>(
+  depths: impl Iterator<Item = (&'a u32, &'a usize)>,
+  count: usize,
+) -> Option<(u32, usize)> {
+  let mut remaining = count;
+  for (&depth, &entries) in depths {
+    if entries >= remaining {
+      return Some((depth, remaining));
+    }
+    remaining -= entries;
+  }
+  None
+}
+
+/// Tables are saved as a header followed by fixed-size entries, each holding
+/// a compressed game state and its packed score. All integers are
+/// little-endian:
+/// ```text
+/// "onorotbl" <version: u32> <compressed state size: u32> <entries: u64>
+/// <compressed state> <score>
+/// ...
+/// ```
+/// Since entries have a fixed size, saved tables are read by memory-mapping
+/// them rather than copying the file into memory first.
+impl<G, H> Table<G, H>
+where
+  G: Game + Hash + Eq + Compress,
+  H: BuildHasher + Clone,
+{
+  const ENTRY_SIZE: usize = G::COMPRESSED_SIZE + Score::PACKED_SIZE;
+
+  /// Writes every entry of the table to `path`, replacing the file if it
+  /// exists.
+  pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(TABLE_MAGIC)?;
+    writer.write_all(&TABLE_VERSION.to_le_bytes())?;
+    writer.write_all(&(G::COMPRESSED_SIZE as u32).to_le_bytes())?;
+    writer.write_all(&(self.len() as u64).to_le_bytes())?;
+
+    let mut entry = vec![0u8; Self::ENTRY_SIZE];
+    for item in self.table.iter() {
+      item.key().compress(&mut entry[..G::COMPRESSED_SIZE]);
+      entry[G::COMPRESSED_SIZE..].copy_from_slice(&item.value().to_bytes());
+      writer.write_all(&entry)?;
+    }
+    writer.flush()
+  }
+
+  /// Merges every entry of the table saved at `path` into this table,
+  /// returning the number of entries read.
+  pub fn load(&self, path: impl AsRef<Path>) -> io::Result<usize> {
+    let file = File::open(path)?;
+    // Safety: the map is only read from while the file is open here, and
+    // table files aren't expected to be modified while they are being loaded.
+    let map = unsafe { Mmap::map(&file)? };
+
+    let (header, entries) = map
+      .split_at_checked(HEADER_SIZE)
+      .ok_or_else(|| invalid_data("Table file is missing its header".into()))?;
+    if &header[0..8] != TABLE_MAGIC {
+      return Err(invalid_data("Not a table file".into()));
+    }
+    let version = u32::from_le_bytes(header[8..12].try_into().unwrap());
+    if version != TABLE_VERSION {
+      return Err(invalid_data(format!(
+        "Unsupported table version {version}, expected {TABLE_VERSION}"
+      )));
+    }
+    let state_size = u32::from_le_bytes(header[12..16].try_into().unwrap()) as usize;
+    if state_size != G::COMPRESSED_SIZE {
+      return Err(invalid_data(format!(
+        "Table has game states of size {state_size}, expected {}",
+        G::COMPRESSED_SIZE
+      )));
+    }
+    let num_entries = u64::from_le_bytes(header[16..24].try_into().unwrap()) as usize;
+    if entries.len() != num_entries * Self::ENTRY_SIZE {
+      return Err(invalid_data(format!(
+        "Table should have {num_entries} entries, but is {} bytes long",
+        map.len()
+      )));
+    }
+
+    for (idx, entry) in entries.chunks_exact(Self::ENTRY_SIZE).enumerate() {
+      let (state, score) = entry.split_at(G::COMPRESSED_SIZE);
+      let state = G::decompress(state)
+        .ok_or_else(|| invalid_data(format!("Entry {idx} is not a valid game state")))?;
+      self.update(state, Score::from_bytes(score.try_into().unwrap()));
+    }
+    Ok(num_entries)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasherDefault, DefaultHasher, Hasher},
+  };
+
+  use crate::{serial_search::find_best_move_serial, test::tic_tac_toe::Ttt, Metrics};
+
+  use super::{ReplacementPolicy, Table, TableLimit, VerificationStats};
+
+  /// A hasher with only 2 bits of output, so distinct states collide.
+  #[derive(Default)]
+  struct TwoBitHasher(DefaultHasher);
+
+  impl Hasher for TwoBitHasher {
+    fn write(&mut self, bytes: &[u8]) {
+      self.0.write(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+      self.0.finish() & 3
+    }
+  }
+
+  #[test]
+  fn test_save_load() {
+    let (_, _, table) = find_best_move_serial(&Ttt::new(), 10);
+    let path = std::env::temp_dir().join(format!("onoro-table-{}.bin", std::process::id()));
+    table.save(&path).unwrap();
+
+    let loaded = Table::new();
+    assert_eq!(loaded.load(&path).unwrap(), table.len());
+    assert_eq!(loaded.len(), table.len());
+    for entry in table.table().iter() {
+      assert_eq!(loaded.get(entry.key()), Some(entry.value().clone()));
+    }
+
+    // Truncated files are rejected.
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+    assert!(Table::<Ttt, _>::new().load(&path).is_err());
+    std::fs::remove_file(&path).unwrap();
+  }
+
+  #[test]
+  fn test_shard_stats() {
+    let (_, _, full_table) = find_best_move_serial(&Ttt::new(), 10);
+    let table = Table::with_shard_bits(RandomState::new(), 3);
+    assert_eq!(table.num_shards(), 8);
+    for entry in full_table.table().iter() {
+      table.update(entry.key().clone(), entry.value().clone());
+      assert!(table.get(entry.key()).is_some());
+    }
+
+    let stats = table.shard_stats();
+    assert_eq!(stats.len(), 8);
+    assert_eq!(
+      stats.iter().map(|shard| shard.entries).sum::<usize>(),
+      full_table.len()
+    );
+    if Metrics::ENABLED {
+      let updates: u64 = stats.iter().map(|shard| shard.updates).sum();
+      let hits: u64 = stats.iter().map(|shard| shard.hits).sum();
+      assert_eq!(updates as usize, full_table.len());
+      assert_eq!(hits as usize, full_table.len());
+      // Each shard counts the accesses to its own entries.
+      for shard in &stats {
+        assert_eq!(shard.updates as usize, shard.entries);
       }
     }
   }
+
+  #[test]
+  fn test_limit() {
+    let (_, _, full_table) = find_best_move_serial(&Ttt::new(), 10);
+    let deepest = full_table
+      .table()
+      .iter()
+      .map(|entry| entry.value().determined_depth())
+      .max()
+      .unwrap();
+
+    for policy in [
+      ReplacementPolicy::DepthPreferred,
+      ReplacementPolicy::AlwaysReplace,
+      ReplacementPolicy::TwoTier,
+    ] {
+      const MAX_ENTRIES: usize = 100;
+      let mut table = Table::new();
+      table.set_limit(Some(TableLimit {
+        max_bytes: MAX_ENTRIES * Table::<Ttt, RandomState>::ENTRY_BYTES,
+        policy,
+      }));
+      for entry in full_table.table().iter() {
+        table.update(entry.key().clone(), entry.value().clone());
+      }
+
+      assert!(table.len() <= MAX_ENTRIES, "{policy:?}");
+      assert_eq!(
+        table.take_evictions() as usize,
+        full_table.len() - table.len(),
+        "{policy:?}"
+      );
+      assert_eq!(table.take_evictions(), 0);
+
+      if policy != ReplacementPolicy::AlwaysReplace {
+        // The deepest entries are never evicted.
+        assert!(
+          table
+            .table()
+            .iter()
+            .any(|entry| entry.value().determined_depth() == deepest),
+          "{policy:?}"
+        );
+      }
+    }
+  }
+  #[test]
+  fn test_verification() {
+    let (_, _, full_table) = find_best_move_serial(&Ttt::new(), 10);
+
+    let mut table = Table::new();
+    table.set_verification(true);
+    for entry in full_table.table().iter() {
+      table.update(entry.key().clone(), entry.value().clone());
+      assert!(table.get(entry.key()).is_some());
+    }
+    assert_eq!(
+      table.take_verification_stats(),
+      VerificationStats::default()
+    );
+
+    let mut table = Table::with_hasher(BuildHasherDefault::<TwoBitHasher>::default());
+    table.set_verification(true);
+    for entry in full_table.table().iter() {
+      table.update(entry.key().clone(), entry.value().clone());
+    }
+    // Every state but the first of each of the 4 hashes collides.
+    let stats = table.take_verification_stats();
+    assert_eq!(stats.hash_collisions as usize, full_table.len() - 4);
+    assert_eq!(stats.false_hits, 0);
+
+    // Reinserting states doesn't count them again.
+    for entry in full_table.table().iter() {
+      table.update(entry.key().clone(), entry.value().clone());
+      assert_eq!(table.get(entry.key()), Some(entry.value().clone()));
+    }
+    assert_eq!(
+      table.take_verification_stats(),
+      VerificationStats {
+        hash_collisions: 0,
+        false_hits: full_table.len() as u64,
+      }
+    );
+
+    table.set_verification(false);
+    table.get(full_table.table().iter().next().unwrap().key());
+    assert_eq!(
+      table.take_verification_stats(),
+      VerificationStats::default()
+    );
+  }
+
+  #[test]
+  fn test_probe() {
+    use abstract_game::Score;
+
+    use super::{Bound, Probe};
+
+    assert_eq!(Bound::of(&Score::win(3), 5), Some(Bound::Exact));
+    assert_eq!(Bound::of(&Score::win(7), 5), Some(Bound::Lower));
+    assert_eq!(Bound::of(&Score::lose(7), 5), Some(Bound::Upper));
+    assert_eq!(Bound::of(&Score::tie(5), 5), Some(Bound::Exact));
+    assert_eq!(Bound::of(&Score::tie(4), 5), None);
+    assert_eq!(Bound::of(&Score::new(true, 5, 7), 5), Some(Bound::Exact));
+    assert_eq!(Bound::of(&Score::new(false, 3, 7), 5), Some(Bound::Upper));
+
+    let (_, _, full_table) = find_best_move_serial(&Ttt::new(), 10);
+    let table = Table::new();
+    let state = full_table.table().iter().next().unwrap().key().clone();
+    assert_eq!(table.probe(&state, 1), None);
+    table.update(state.clone(), Score::new(false, 2, 6));
+    assert_eq!(
+      table.probe(&state, 4),
+      Some(Probe {
+        score: Score::new(false, 2, 6),
+        sufficient_depth: true,
+        bound: Some(Bound::Upper),
+      })
+    );
+    let probe = table.probe(&state, 8).unwrap();
+    assert!(!probe.sufficient_depth);
+    assert_eq!(probe.bound, Some(Bound::Exact));
+  }
 }
@@ -0,0 +1,264 @@
+use std::{
+  collections::{hash_map::RandomState, HashMap, VecDeque},
+  hash::{BuildHasher, Hash},
+  io,
+  path::Path,
+};
+
+use abstract_game::{Compress, Game, GameResult, Score};
+
+use crate::table::Table;
+
+/// The outcome of a game state found by retrograde analysis, with the number
+/// of moves until the game ends under optimal play.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+  Unknown,
+  Win(u32),
+  Loss(u32),
+}
+
+impl Outcome {
+  /// The score of a state with this outcome. A win or loss in `n` moves
+  /// can't be forced any sooner, so the state is also a tie at every depth
+  /// below `n`.
+  fn score(self) -> Score {
+    match self {
+      Outcome::Unknown => Score::guaranteed_tie(),
+      Outcome::Win(moves) => Score::new(true, moves - 1, moves),
+      Outcome::Loss(moves) => Score::new(false, moves - 1, moves),
+    }
+  }
+}
+
+/// Exact scores for every game state reachable from a set of starting
+/// states, computed by retrograde analysis: starting from the states whose
+/// outcome is decided in one move, outcomes are propagated backwards through
+/// the moves that lead to them, and every state that is never reached this
+/// way is a tie, since neither player can force a win from it.
+///
+/// Unlike a depth-limited search, this resolves states whose games can go on
+/// forever, like Onoro positions with all pawns placed, where pawns can move
+/// back and forth indefinitely. Every state reachable from the starting states
+/// is held in memory during generation, so the starting states must be chosen
+/// such that few enough states are reachable from them, e.g. all pawns placed
+/// in a small cluster. Enumerating every phase-2 Onoro position this way isn't
+/// feasible.
+///
+/// Since the scores are exact to any depth, a tablebase can be added to an
+/// `Engine` to act as an oracle at the leaves of its searches.
+pub struct Tablebase<G, H = RandomState>
+where
+  G: Game,
+{
+  table: Table<G, H>,
+}
+
+impl<G> Tablebase<G, RandomState>
+where
+  G: Game + Hash + Eq,
+{
+  /// Generates a tablebase of every game state reachable from `roots`, or
+  /// returns `None` if more than `max_states` states are reachable.
+  pub fn generate(roots: impl IntoIterator<Item = G>, max_states: usize) -> Option<Self> {
+    Self::generate_with_hasher(roots, max_states, RandomState::new())
+  }
+}
+
+impl<G, H> Tablebase<G, H>
+where
+  G: Game + Hash + Eq,
+  H: BuildHasher + Clone,
+{
+  pub fn generate_with_hasher(
+    roots: impl IntoIterator<Item = G>,
+    max_states: usize,
+    hasher: H,
+  ) -> Option<Self> {
+    // Enumerate every unfinished state reachable from the roots, along with
+    // the moves between them.
+    let mut indices = HashMap::with_hasher(hasher.clone());
+    let mut states = Vec::new();
+    for root in roots {
+      if root.finished() == GameResult::NotFinished && !indices.contains_key(&root) {
+        indices.insert(root.clone(), states.len() as u32);
+        states.push(root);
+      }
+    }
+
+    let mut outcomes = Vec::new();
+    // The number of moves from each state whose outcome isn't yet known to be
+    // a win for the opponent. Once this reaches 0, the state is lost.
+    let mut remaining_moves = Vec::new();
+    let mut predecessors: Vec<Vec<u32>> = Vec::new();
+    let mut resolved = VecDeque::new();
+    let mut idx = 0;
+    while idx < states.len() {
+      let state = states[idx].clone();
+      let mut outcome = Outcome::Unknown;
+      let mut moves = 0;
+      for m in state.each_move() {
+        let child = state.with_move(m);
+        match child.finished() {
+          GameResult::Win(winner) if winner == state.current_player() => {
+            outcome = Outcome::Win(1);
+          }
+          // Moves that lose right away don't count towards the moves that
+          // could save this state, and moves that tie always do.
+          GameResult::Win(_) => {}
+          GameResult::Tie => moves += 1,
+          GameResult::NotFinished => {
+            let child_idx = match indices.get(&child) {
+              Some(&child_idx) => child_idx,
+              None => {
+                if states.len() >= max_states {
+                  return None;
+                }
+                let child_idx = states.len() as u32;
+                indices.insert(child.clone(), child_idx);
+                states.push(child);
+                child_idx
+              }
+            };
+            if predecessors.len() <= child_idx as usize {
+              predecessors.resize_with(child_idx as usize + 1, Vec::new);
+            }
+            predecessors[child_idx as usize].push(idx as u32);
+            moves += 1;
+          }
+        }
+      }
+
+      // States with no moves that don't end the game are lost, as they are in
+      // the search.
+      if outcome == Outcome::Unknown && moves == 0 {
+        outcome = Outcome::Loss(1);
+      }
+      if outcome != Outcome::Unknown {
+        resolved.push_back(idx as u32);
+      }
+      outcomes.push(outcome);
+      remaining_moves.push(moves);
+      idx += 1;
+    }
+    predecessors.resize_with(states.len(), Vec::new);
+
+    // States are resolved in order of increasing distance to the end of the
+    // game, so each win is found by its quickest line, and each loss by its
+    // slowest.
+    while let Some(idx) = resolved.pop_front() {
+      let outcome = outcomes[idx as usize];
+      for &pred in &predecessors[idx as usize] {
+        let pred = pred as usize;
+        if outcomes[pred] != Outcome::Unknown {
+          continue;
+        }
+        match outcome {
+          Outcome::Loss(moves) => {
+            outcomes[pred] = Outcome::Win(moves + 1);
+            resolved.push_back(pred as u32);
+          }
+          Outcome::Win(moves) => {
+            remaining_moves[pred] -= 1;
+            if remaining_moves[pred] == 0 {
+              outcomes[pred] = Outcome::Loss(moves + 1);
+              resolved.push_back(pred as u32);
+            }
+          }
+          Outcome::Unknown => unreachable!(),
+        }
+      }
+    }
+
+    let table = Table::with_hasher(hasher);
+    for (state, outcome) in states.into_iter().zip(outcomes) {
+      table.update(state, outcome.score());
+    }
+    Some(Self { table })
+  }
+
+  /// The number of game states in the tablebase.
+  pub fn len(&self) -> usize {
+    self.table.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.table.len() == 0
+  }
+
+  /// The exact score of `state`, or `None` if it isn't in the tablebase.
+  pub fn probe(&self, state: &G) -> Option<Score> {
+    self.table.get(state)
+  }
+
+  /// The tablebase's scores, as a table of resolved states.
+  pub(crate) fn table(&self) -> &Table<G, H> {
+    &self.table
+  }
+}
+
+/// Tablebases are saved in the same format as `Table::save`, so the file of
+/// a tablebase can also be loaded with `Engine::load_table`.
+impl<G, H> Tablebase<G, H>
+where
+  G: Game + Hash + Eq + Compress,
+  H: BuildHasher + Clone,
+{
+  /// Writes the tablebase to `path`, replacing the file if it exists.
+  pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+    self.table.save(path)
+  }
+
+  /// Reads a tablebase saved with `Tablebase::save`.
+  pub fn load_with_hasher(path: impl AsRef<Path>, hasher: H) -> io::Result<Self> {
+    let table = Table::with_hasher(hasher);
+    table.load(path)?;
+    Ok(Self { table })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use abstract_game::{Game, GameResult, Score};
+
+  use crate::test::{nim::Nim, tic_tac_toe::Ttt};
+
+  use super::Tablebase;
+
+  #[test]
+  fn test_ttt_tablebase() {
+    let tablebase = Tablebase::generate([Ttt::new()], usize::MAX).unwrap();
+    assert_eq!(tablebase.probe(&Ttt::new()), Some(Score::guaranteed_tie()));
+
+    // Tic tac toe games last at most 9 moves, so a search that deep finds the
+    // exact score of every state.
+    for state in tablebase.table().table().iter() {
+      assert_eq!(state.key().finished(), GameResult::NotFinished);
+      let expected_score = state.key().compute_expected_score(9);
+      assert!(
+        state.value().compatible(&expected_score),
+        "Expect tablebase score {} to be compatible with true score {}",
+        state.value(),
+        expected_score
+      );
+    }
+  }
+
+  #[test]
+  fn test_nim_tablebase() {
+    const STICKS: u32 = 20;
+    let tablebase = Tablebase::generate([Nim::new(STICKS)], usize::MAX).unwrap();
+    for sticks in 1..=STICKS {
+      assert_eq!(
+        tablebase.probe(&Nim::new(sticks)),
+        Some(Nim::new(sticks).expected_score()),
+        "{sticks} sticks"
+      );
+    }
+  }
+
+  #[test]
+  fn test_max_states() {
+    assert!(Tablebase::generate([Ttt::new()], 100).is_none());
+  }
+}
@@ -0,0 +1,167 @@
+use std::{fmt::Display, hash::Hash};
+
+use abstract_game::{Game, GameMoveGenerator, GameResult};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConnectFourPlayer {
+  First,
+  Second,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConnectFourMove {
+  column: u32,
+}
+
+impl Display for ConnectFourMove {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.column)
+  }
+}
+
+pub struct ConnectFourMoveIter {
+  column: u32,
+}
+
+impl GameMoveGenerator for ConnectFourMoveIter {
+  type Item = ConnectFourMove;
+  type Game = ConnectFour;
+
+  fn next(&mut self, game: &ConnectFour) -> Option<Self::Item> {
+    while self.column < game.width && game.column_full(self.column) {
+      self.column += 1;
+    }
+    if self.column != game.width {
+      self.column += 1;
+      Some(ConnectFourMove {
+        column: self.column - 1,
+      })
+    } else {
+      None
+    }
+  }
+}
+
+/// Connect four on a board of any size, where pieces are dropped into columns
+/// and stack from the bottom. The first player to line up `to_win` pieces in
+/// a row, column, or diagonal wins.
+///
+/// Known values, from John Tromp's solutions of small boards: 4x4 with 4 to
+/// win is a tie.
+#[derive(Clone)]
+pub struct ConnectFour {
+  /// The pieces of each player, with each column occupying `height + 1` bits
+  /// starting from the bottom. The extra bit of each column is always empty,
+  /// so lines can be found by shifting without wrapping between columns.
+  pieces: [u64; 2],
+  width: u32,
+  height: u32,
+  to_win: u32,
+  turn: u32,
+}
+
+impl ConnectFour {
+  pub fn new(width: u32, height: u32, to_win: u32) -> Self {
+    debug_assert!(width * (height + 1) <= 64);
+    Self {
+      pieces: [0, 0],
+      width,
+      height,
+      to_win,
+      turn: 0,
+    }
+  }
+
+  fn column_bits(&self, column: u32) -> u64 {
+    ((1u64 << self.height) - 1) << (column * (self.height + 1))
+  }
+
+  fn column_height(&self, column: u32) -> u32 {
+    ((self.pieces[0] | self.pieces[1]) & self.column_bits(column)).count_ones()
+  }
+
+  fn column_full(&self, column: u32) -> bool {
+    self.column_height(column) == self.height
+  }
+
+  fn has_line(&self, pieces: u64) -> bool {
+    // Vertical, horizontal, and the two diagonal directions.
+    [1, self.height + 1, self.height, self.height + 2]
+      .into_iter()
+      .any(|step| {
+        (1..self.to_win).fold(pieces, |line, offset| line & (pieces >> (offset * step))) != 0
+      })
+  }
+}
+
+impl Game for ConnectFour {
+  type Move = ConnectFourMove;
+  type MoveGenerator = ConnectFourMoveIter;
+  type PlayerIdentifier = ConnectFourPlayer;
+
+  fn move_generator(&self) -> Self::MoveGenerator {
+    ConnectFourMoveIter { column: 0 }
+  }
+
+  fn make_move(&mut self, m: Self::Move) {
+    debug_assert!(!self.column_full(m.column));
+    let bit = 1u64 << (m.column * (self.height + 1) + self.column_height(m.column));
+    self.pieces[(self.turn % 2) as usize] |= bit;
+    self.turn += 1;
+  }
+
+  fn current_player(&self) -> Self::PlayerIdentifier {
+    match self.turn % 2 {
+      0 => ConnectFourPlayer::First,
+      _ => ConnectFourPlayer::Second,
+    }
+  }
+
+  fn finished(&self) -> GameResult<Self::PlayerIdentifier> {
+    if self.has_line(self.pieces[0]) {
+      GameResult::Win(ConnectFourPlayer::First)
+    } else if self.has_line(self.pieces[1]) {
+      GameResult::Win(ConnectFourPlayer::Second)
+    } else if self.turn == self.width * self.height {
+      GameResult::Tie
+    } else {
+      GameResult::NotFinished
+    }
+  }
+}
+
+impl Hash for ConnectFour {
+  fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    self.pieces.hash(state);
+  }
+}
+
+impl PartialEq for ConnectFour {
+  fn eq(&self, other: &Self) -> bool {
+    self.pieces == other.pieces
+  }
+}
+
+impl Eq for ConnectFour {}
+
+impl Display for ConnectFour {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    for y in (0..self.height).rev() {
+      for x in 0..self.width {
+        let bit = 1u64 << (x * (self.height + 1) + y);
+        let tile = if self.pieces[0] & bit != 0 {
+          "X"
+        } else if self.pieces[1] & bit != 0 {
+          "O"
+        } else {
+          "."
+        };
+        write!(f, "{tile} ")?;
+      }
+      if y != 0 {
+        writeln!(f)?;
+      }
+    }
+    Ok(())
+  }
+}
@@ -1,6 +1,6 @@
 use std::{fmt::Display, hash::Hash};
 
-use abstract_game::{Game, GameMoveGenerator, GameResult};
+use abstract_game::{Game, GameMoveGenerator, GameResult, Threats};
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum GomokuPlayer {
@@ -18,7 +18,7 @@ impl From<GomokuTile> for GomokuPlayer {
   }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct GomokuMove {
   x: u32,
   y: u32,
@@ -136,6 +136,14 @@ impl Game for Gomoku {
     self.turn += 1;
   }
 
+  /// Both players can play on any empty tile, so passing only changes whose
+  /// turn it is.
+  fn pass_equivalent(&self) -> Option<Self> {
+    let mut passed = self.clone();
+    passed.turn += 1;
+    Some(passed)
+  }
+
   fn current_player(&self) -> Self::PlayerIdentifier {
     if self.turn % 2 == 0 {
       GomokuPlayer::First
@@ -190,7 +198,9 @@ impl Game for Gomoku {
       }
     }
 
-    if self.turn == self.width * self.height {
+    // Passing advances the turn without placing a piece, so the turn can't be
+    // used to tell whether the board is full.
+    if !self.tiles.contains(&GomokuTile::Empty) {
       GameResult::Tie
     } else {
       GameResult::NotFinished
@@ -198,6 +208,23 @@ impl Game for Gomoku {
   }
 }
 
+impl Threats for Gomoku {
+  fn immediate_wins(&self) -> Vec<GomokuMove> {
+    self
+      .each_move()
+      .filter(|&m| self.with_move(m).finished() == GameResult::Win(self.current_player()))
+      .collect()
+  }
+
+  fn forced_blocks(&self) -> Vec<GomokuMove> {
+    // Both players can play on any empty tile, so the tiles to block are the
+    // ones the opponent would win on if it were their turn.
+    let mut opponent = self.clone();
+    opponent.turn += 1;
+    opponent.immediate_wins()
+  }
+}
+
 impl Hash for Gomoku {
   fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
     self.tiles.hash(state);
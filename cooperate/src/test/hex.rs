@@ -0,0 +1,190 @@
+use std::{fmt::Display, hash::Hash};
+
+use abstract_game::{Game, GameMoveGenerator, GameResult};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum HexPlayer {
+  First,
+  Second,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HexMove {
+  x: u32,
+  y: u32,
+}
+
+impl HexMove {
+  pub fn new(x: u32, y: u32) -> Self {
+    Self { x, y }
+  }
+}
+
+impl Display for HexMove {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "({}, {})", self.x, self.y)
+  }
+}
+
+pub struct HexMoveIter {
+  idx: u32,
+}
+
+impl GameMoveGenerator for HexMoveIter {
+  type Item = HexMove;
+  type Game = Hex;
+
+  fn next(&mut self, hex: &Hex) -> Option<Self::Item> {
+    let occupied = hex.pieces[0] | hex.pieces[1];
+    while self.idx < Hex::STRIDE * hex.size {
+      let m = HexMove {
+        x: self.idx % Hex::STRIDE,
+        y: self.idx / Hex::STRIDE,
+      };
+      self.idx += 1;
+      if m.x < hex.size && occupied & (1 << Hex::idx(m.x, m.y)) == 0 {
+        return Some(m);
+      }
+    }
+    None
+  }
+}
+
+/// Hex on a `size` x `size` rhombus, where tile (x, y) neighbors (x ± 1, y),
+/// (x, y ± 1), (x + 1, y - 1), and (x - 1, y + 1). The first player wins by
+/// connecting the rows y = 0 and y = size - 1, and the second player by
+/// connecting the columns x = 0 and x = size - 1.
+///
+/// Hex can't end in a tie, and by strategy stealing the first player wins on
+/// every board size. On 3x3, the winning first moves are the three tiles of
+/// the short diagonal, from (2, 0) to (0, 2), and the two edge tiles of the
+/// middle row.
+#[derive(Clone)]
+pub struct Hex {
+  /// The tiles of each player, with tile (x, y) at bit `x + STRIDE * y`. The
+  /// unused columns keep neighbor shifts from wrapping between rows.
+  pieces: [u64; 2],
+  size: u32,
+  turn: u32,
+}
+
+impl Hex {
+  const STRIDE: u32 = 8;
+  pub const MAX_SIZE: u32 = Self::STRIDE - 1;
+
+  pub fn new(size: u32) -> Self {
+    debug_assert!(size <= Self::MAX_SIZE);
+    Self {
+      pieces: [0, 0],
+      size,
+      turn: 0,
+    }
+  }
+
+  fn idx(x: u32, y: u32) -> u32 {
+    x + Self::STRIDE * y
+  }
+
+  fn neighbors(tiles: u64) -> u64 {
+    let s = Self::STRIDE;
+    (tiles << 1)
+      | (tiles >> 1)
+      | (tiles << s)
+      | (tiles >> s)
+      | (tiles << (s - 1))
+      | (tiles >> (s - 1))
+  }
+
+  /// True if `pieces` connect the tiles of `start` to the tiles of `end`.
+  fn connects(pieces: u64, start: u64, end: u64) -> bool {
+    let mut reached = pieces & start;
+    loop {
+      let next = reached | (Self::neighbors(reached) & pieces);
+      if next == reached {
+        return reached & end != 0;
+      }
+      reached = next;
+    }
+  }
+
+  fn row(&self, y: u32) -> u64 {
+    ((1 << self.size) - 1) << Self::idx(0, y)
+  }
+
+  fn column(&self, x: u32) -> u64 {
+    (0..self.size).fold(0, |column, y| column | (1 << Self::idx(x, y)))
+  }
+}
+
+impl Game for Hex {
+  type Move = HexMove;
+  type MoveGenerator = HexMoveIter;
+  type PlayerIdentifier = HexPlayer;
+
+  fn move_generator(&self) -> Self::MoveGenerator {
+    HexMoveIter { idx: 0 }
+  }
+
+  fn make_move(&mut self, m: Self::Move) {
+    let bit = 1 << Self::idx(m.x, m.y);
+    debug_assert_eq!((self.pieces[0] | self.pieces[1]) & bit, 0);
+    self.pieces[(self.turn % 2) as usize] |= bit;
+    self.turn += 1;
+  }
+
+  fn current_player(&self) -> Self::PlayerIdentifier {
+    match self.turn % 2 {
+      0 => HexPlayer::First,
+      _ => HexPlayer::Second,
+    }
+  }
+
+  fn finished(&self) -> GameResult<Self::PlayerIdentifier> {
+    let last = self.size - 1;
+    if Self::connects(self.pieces[0], self.row(0), self.row(last)) {
+      GameResult::Win(HexPlayer::First)
+    } else if Self::connects(self.pieces[1], self.column(0), self.column(last)) {
+      GameResult::Win(HexPlayer::Second)
+    } else {
+      GameResult::NotFinished
+    }
+  }
+}
+
+impl Hash for Hex {
+  fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    self.pieces.hash(state);
+  }
+}
+
+impl PartialEq for Hex {
+  fn eq(&self, other: &Self) -> bool {
+    self.pieces == other.pieces
+  }
+}
+
+impl Eq for Hex {}
+
+impl Display for Hex {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    for y in 0..self.size {
+      // Offset each row to draw the rhombus.
+      write!(f, "{:width$}", "", width = y as usize)?;
+      for x in 0..self.size {
+        let bit = 1 << Self::idx(x, y);
+        let tile = if self.pieces[0] & bit != 0 {
+          "X"
+        } else if self.pieces[1] & bit != 0 {
+          "O"
+        } else {
+          "."
+        };
+        write!(f, "{tile} ")?;
+      }
+      if y != self.size - 1 {
+        writeln!(f)?;
+      }
+    }
+    Ok(())
+  }
+}
@@ -1,3 +1,6 @@
+pub mod connect_four;
 pub mod gomoku;
+pub mod hex;
+pub mod multi_nim;
 pub mod nim;
 pub mod tic_tac_toe;
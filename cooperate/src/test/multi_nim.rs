@@ -0,0 +1,102 @@
+use std::fmt::Display;
+
+use abstract_game::{Game, GameMoveGenerator, GameResult, MultiplayerGame};
+
+#[derive(Clone, Copy)]
+pub struct MultiNimMove {
+  sticks: u32,
+}
+
+impl Display for MultiNimMove {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.sticks)
+  }
+}
+
+pub struct MultiNimMoveIter {
+  sticks: u32,
+  max_sticks: u32,
+}
+
+impl GameMoveGenerator for MultiNimMoveIter {
+  type Item = MultiNimMove;
+  type Game = MultiNim;
+
+  fn next(&mut self, _nim: &MultiNim) -> Option<Self::Item> {
+    if self.sticks > self.max_sticks {
+      None
+    } else {
+      self.sticks += 1;
+      Some(MultiNimMove {
+        sticks: self.sticks - 1,
+      })
+    }
+  }
+}
+
+/// Nim for any number of players, who take turns removing one or two sticks.
+/// The player to take the last stick wins.
+#[derive(Clone)]
+pub struct MultiNim {
+  sticks: u32,
+  players: u32,
+  turn: u32,
+}
+
+impl MultiNim {
+  pub fn new(sticks: u32, players: u32) -> Self {
+    Self {
+      sticks,
+      players,
+      turn: 0,
+    }
+  }
+}
+
+impl Game for MultiNim {
+  type Move = MultiNimMove;
+  type MoveGenerator = MultiNimMoveIter;
+  /// The index of the player in the turn order.
+  type PlayerIdentifier = u32;
+
+  fn move_generator(&self) -> Self::MoveGenerator {
+    MultiNimMoveIter {
+      sticks: 1,
+      max_sticks: self.sticks.min(2),
+    }
+  }
+
+  fn make_move(&mut self, m: Self::Move) {
+    self.sticks -= m.sticks;
+    self.turn += 1;
+  }
+
+  fn current_player(&self) -> Self::PlayerIdentifier {
+    self.turn % self.players
+  }
+
+  fn finished(&self) -> GameResult<Self::PlayerIdentifier> {
+    if self.sticks == 0 {
+      // The winner is the player to take the last stick.
+      GameResult::Win((self.turn + self.players - 1) % self.players)
+    } else {
+      GameResult::NotFinished
+    }
+  }
+}
+
+impl MultiplayerGame for MultiNim {
+  fn player_count(&self) -> usize {
+    self.players as usize
+  }
+
+  fn player_index(&self, player: &Self::PlayerIdentifier) -> usize {
+    *player as usize
+  }
+}
+
+impl Display for MultiNim {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{} (turn {})", self.sticks, self.turn)
+  }
+}
@@ -1,6 +1,6 @@
 use std::{fmt::Display, hash::Hash};
 
-use abstract_game::{Game, GameMoveGenerator, GameResult, Score};
+use abstract_game::{Game, GameMoveGenerator, GameResult, MultiplayerGame, Score};
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum NimPlayer {
@@ -60,6 +60,19 @@ impl Nim {
       Score::new(true, turn_count_win, turn_count_win + 1)
     }
   }
+
+  /// The score a search which stops at the first winning move finds. Winning
+  /// states don't explore every move, so they don't prove how long the game
+  /// stays tied, and losing states only learn that from their children up to
+  /// depth 1.
+  pub fn expected_search_score(&self) -> Score {
+    let score = self.expected_score();
+    if score.cur_player_wins() {
+      score.break_early()
+    } else {
+      Score::new(false, 1, score.turn_count_win())
+    }
+  }
 }
 
 impl Game for Nim {
@@ -101,6 +114,19 @@ impl Game for Nim {
   }
 }
 
+impl MultiplayerGame for Nim {
+  fn player_count(&self) -> usize {
+    2
+  }
+
+  fn player_index(&self, player: &Self::PlayerIdentifier) -> usize {
+    match player {
+      NimPlayer::First => 0,
+      NimPlayer::Second => 1,
+    }
+  }
+}
+
 impl Hash for Nim {
   fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
     self.sticks.hash(state);
@@ -1,6 +1,8 @@
 use std::{fmt::Display, hash::Hash};
 
-use abstract_game::{Game, GameMoveGenerator, GameResult, Score};
+use abstract_game::{
+  Compress, Evaluate, Game, GameMoveGenerator, GameResult, MultiplayerGame, Score, Threats,
+};
 
 use crate::serial_search::find_best_move_serial;
 
@@ -10,7 +12,7 @@ pub enum TttPlayer {
   Second,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct TttMove {
   x: u32,
   y: u32,
@@ -168,6 +170,83 @@ impl Game for Ttt {
   }
 }
 
+impl MultiplayerGame for Ttt {
+  fn player_count(&self) -> usize {
+    2
+  }
+
+  fn player_index(&self, player: &Self::PlayerIdentifier) -> usize {
+    match player {
+      TttPlayer::First => 0,
+      TttPlayer::Second => 1,
+    }
+  }
+}
+
+impl Threats for Ttt {
+  fn immediate_wins(&self) -> Vec<TttMove> {
+    self
+      .each_move()
+      .filter(|&m| self.with_move(m).finished() == GameResult::Win(self.current_player()))
+      .collect()
+  }
+
+  fn forced_blocks(&self) -> Vec<TttMove> {
+    // Both players can play on any empty tile, so the tiles to block are the
+    // ones the opponent would win on if it were their turn.
+    let mut opponent = self.clone();
+    opponent.turn += 1;
+    opponent.immediate_wins()
+  }
+}
+
+/// Evaluates positions by the number of lines each player could still
+/// complete which they already have a piece in.
+impl Evaluate for Ttt {
+  fn evaluate(&self) -> i32 {
+    const LINES: [[(u32, u32); 3]; 8] = [
+      [(0, 0), (1, 0), (2, 0)],
+      [(0, 1), (1, 1), (2, 1)],
+      [(0, 2), (1, 2), (2, 2)],
+      [(0, 0), (0, 1), (0, 2)],
+      [(1, 0), (1, 1), (1, 2)],
+      [(2, 0), (2, 1), (2, 2)],
+      [(0, 0), (1, 1), (2, 2)],
+      [(2, 0), (1, 1), (0, 2)],
+    ];
+    let (player, opponent) = match self.current_player() {
+      TttPlayer::First => (TttTile::X, TttTile::O),
+      TttPlayer::Second => (TttTile::O, TttTile::X),
+    };
+    let open_lines = |tile: TttTile, other: TttTile| {
+      LINES
+        .iter()
+        .filter(|line| {
+          let tiles = line.map(|(x, y)| self.tile_at(x, y));
+          tiles.contains(&tile) && !tiles.contains(&other)
+        })
+        .count() as i32
+    };
+    open_lines(player, opponent) - open_lines(opponent, player)
+  }
+}
+
+impl Compress for Ttt {
+  const COMPRESSED_SIZE: usize = 8;
+
+  fn compress(&self, bytes: &mut [u8]) {
+    bytes[0..4].copy_from_slice(&self.tile_mask.to_le_bytes());
+    bytes[4..8].copy_from_slice(&self.turn.to_le_bytes());
+  }
+
+  fn decompress(bytes: &[u8]) -> Option<Self> {
+    Some(Self {
+      tile_mask: u32::from_le_bytes(bytes.get(0..4)?.try_into().ok()?),
+      turn: u32::from_le_bytes(bytes.get(4..8)?.try_into().ok()?),
+    })
+  }
+}
+
 impl Hash for Ttt {
   fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
     self.tile_mask.hash(state);
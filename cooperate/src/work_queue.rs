@@ -0,0 +1,277 @@
+use std::{
+  sync::{
+    atomic::{AtomicU32, Ordering},
+    Mutex,
+  },
+  thread,
+  time::{Duration, Instant},
+};
+
+use crossbeam_deque::{Injector, Steal, Stealer, Worker};
+
+use crate::Metrics;
+
+/// How many times an idle worker looks for work to steal before it starts
+/// sleeping between attempts.
+const IDLE_SPINS: u32 = 64;
+
+/// How long an idle worker sleeps between attempts to steal work, once it has
+/// spun for `IDLE_SPINS` attempts.
+const IDLE_SLEEP: Duration = Duration::from_micros(50);
+
+/// The work units of a search, spread across a lock-free Chase-Lev deque per
+/// worker per search depth, plus a global injector that the initial units are
+/// pushed to.
+///
+/// Each work unit is filed under the depth it remains to be searched to. A
+/// worker takes its own units shallowest-first, finishing off small pieces of
+/// work that are likely to be cache-hot, while idle workers steal the deepest
+/// units they can find, since those are the most work to take on per steal.
+pub struct WorkQueues<T> {
+  injector: Injector<T>,
+  /// The stealing ends of every worker's deques, indexed by thread and then
+  /// by depth.
+  stealers: Vec<Vec<Stealer<T>>>,
+  /// The owning ends of every worker's deques, which are handed to each worker
+  /// when it starts.
+  deques: Vec<Mutex<Option<Vec<Worker<T>>>>>,
+  /// The number of workers that are holding a work unit. Work is only ever
+  /// pushed to a worker's deques by that worker while it's active, so once no
+  /// workers are active and every deque is empty, the search is done.
+  active: AtomicU32,
+}
+
+impl<T> WorkQueues<T> {
+  /// Constructs the queues for `num_threads` workers, holding units to be
+  /// searched to at most `max_depth`.
+  pub fn new(num_threads: u32, max_depth: u32) -> Self {
+    let deques: Vec<Vec<_>> = (0..num_threads)
+      .map(|_| (0..=max_depth).map(|_| Worker::new_lifo()).collect())
+      .collect();
+    Self {
+      injector: Injector::new(),
+      stealers: deques
+        .iter()
+        .map(|deques| deques.iter().map(Worker::stealer).collect())
+        .collect(),
+      deques: deques
+        .into_iter()
+        .map(|deques| Mutex::new(Some(deques)))
+        .collect(),
+      active: AtomicU32::new(0),
+    }
+  }
+
+  /// Adds a unit that any worker may take.
+  pub fn inject(&self, unit: T) {
+    self.injector.push(unit);
+  }
+
+  /// Takes the deques of worker `thread_idx`, which may only be done once.
+  pub fn local(&self, thread_idx: u32) -> LocalQueue<'_, T> {
+    let deques = self.deques[thread_idx as usize]
+      .lock()
+      .unwrap()
+      .take()
+      .expect("Worker queue taken twice");
+    self.active.fetch_add(1, Ordering::SeqCst);
+    LocalQueue {
+      thread_idx,
+      deques,
+      queues: self,
+    }
+  }
+
+  /// Removes every unit left in the queues.
+  pub fn drain(&self) -> Vec<T> {
+    fn steal_all<T>(steal: impl Fn() -> Steal<T>, units: &mut Vec<T>) {
+      loop {
+        match steal() {
+          Steal::Success(unit) => units.push(unit),
+          Steal::Empty => return,
+          Steal::Retry => {}
+        }
+      }
+    }
+
+    let mut units = Vec::new();
+    steal_all(|| self.injector.steal(), &mut units);
+    for stealer in self.stealers.iter().flatten() {
+      steal_all(|| stealer.steal(), &mut units);
+    }
+    units
+  }
+}
+
+/// A worker's view of the `WorkQueues`, through which it pushes and pops its
+/// own units and steals from the others.
+pub struct LocalQueue<'a, T> {
+  thread_idx: u32,
+  deques: Vec<Worker<T>>,
+  queues: &'a WorkQueues<T>,
+}
+
+impl<T> LocalQueue<'_, T> {
+  /// Adds a unit which remains to be searched to `depth` to this worker's
+  /// deques. Units deeper than the queues were made for are filed with the
+  /// deepest ones.
+  pub fn push(&self, unit: T, depth: u32) {
+    let depth = (depth as usize).min(self.deques.len() - 1);
+    self.deques[depth].push(unit);
+  }
+
+  /// Takes the next unit to search, stealing one if this worker has none
+  /// left. If there is nothing to steal, this waits until some other worker
+  /// makes work available, or returns `None` if no worker has any work left
+  /// or `stopped` returns true. Time spent waiting is recorded as idle time
+  /// in `metrics`.
+  pub fn next(&self, metrics: &mut Metrics, stopped: impl Fn() -> bool) -> Option<T> {
+    if let Some(unit) = self.deques.iter().find_map(Worker::pop) {
+      return Some(unit);
+    }
+
+    let idle_start = Instant::now();
+    self.queues.active.fetch_sub(1, Ordering::SeqCst);
+    let mut attempts = 0;
+    let unit = loop {
+      if let Some(unit) = self.steal(metrics) {
+        self.queues.active.fetch_add(1, Ordering::SeqCst);
+        break Some(unit);
+      }
+      if self.queues.active.load(Ordering::SeqCst) == 0 || stopped() {
+        break None;
+      }
+
+      attempts += 1;
+      if attempts < IDLE_SPINS {
+        thread::yield_now();
+      } else {
+        thread::sleep(IDLE_SLEEP);
+      }
+    };
+    metrics.record_idle(idle_start.elapsed());
+    unit
+  }
+
+  /// Takes a unit from the injector, or else the deepest unit of any other
+  /// worker.
+  fn steal(&self, metrics: &mut Metrics) -> Option<T> {
+    loop {
+      match self.queues.injector.steal() {
+        Steal::Success(unit) => return Some(unit),
+        Steal::Empty => break,
+        Steal::Retry => metrics.record_steal_retry(),
+      }
+    }
+
+    let num_threads = self.queues.stealers.len();
+    for depth in (0..self.deques.len()).rev() {
+      for offset in 1..num_threads {
+        let victim = (self.thread_idx as usize + offset) % num_threads;
+        loop {
+          match self.queues.stealers[victim][depth].steal() {
+            Steal::Success(unit) => {
+              metrics.record_steal();
+              return Some(unit);
+            }
+            Steal::Empty => break,
+            Steal::Retry => metrics.record_steal_retry(),
+          }
+        }
+      }
+    }
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{sync::Arc, thread};
+
+  use crate::Metrics;
+
+  use super::WorkQueues;
+
+  #[test]
+  fn test_local_order() {
+    let queues = WorkQueues::new(1, 3);
+    let local = queues.local(0);
+    local.push(1, 1);
+    local.push(3, 3);
+    local.push(2, 2);
+    local.push(4, 1);
+
+    let mut metrics = Metrics::new();
+    let units: Vec<_> = std::iter::from_fn(|| local.next(&mut metrics, || false)).collect();
+    assert_eq!(units, vec![4, 1, 2, 3]);
+  }
+
+  #[test]
+  fn test_steal_deepest() {
+    let queues = WorkQueues::new(2, 3);
+    let victim = queues.local(0);
+    let thief = queues.local(1);
+    victim.push(1, 1);
+    victim.push(3, 3);
+    victim.push(2, 2);
+
+    let mut metrics = Metrics::new();
+    assert_eq!(thief.next(&mut metrics, || false), Some(3));
+    assert_eq!(thief.next(&mut metrics, || false), Some(2));
+    assert_eq!(victim.next(&mut metrics, || false), Some(1));
+    if Metrics::ENABLED {
+      assert_eq!(metrics.steals(), 2);
+    }
+  }
+
+  #[test]
+  fn test_waits_for_active_workers() {
+    const UNITS: u32 = 1000;
+    let queues = Arc::new(WorkQueues::new(4, 0));
+    queues.inject(0);
+
+    // Units spawn more units as they're searched, so the workers can only
+    // find work by stealing it, and must not give up while any worker holds a
+    // unit.
+    let handles: Vec<_> = (0..4)
+      .map(|thread_idx| {
+        let queues = queues.clone();
+        thread::spawn(move || {
+          let local = queues.local(thread_idx);
+          let mut metrics = Metrics::new();
+          let mut units = Vec::new();
+          while let Some(unit) = local.next(&mut metrics, || false) {
+            units.push(unit);
+            for child in [2 * unit + 1, 2 * unit + 2] {
+              if child < UNITS {
+                local.push(child, 0);
+              }
+            }
+          }
+          units
+        })
+      })
+      .collect();
+
+    let mut units: Vec<_> = handles
+      .into_iter()
+      .flat_map(|handle| handle.join().unwrap())
+      .collect();
+    units.sort_unstable();
+    assert_eq!(units, (0..UNITS).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn test_drain() {
+    let queues = WorkQueues::new(2, 1);
+    queues.inject(0);
+    {
+      let local = queues.local(1);
+      local.push(1, 0);
+      local.push(2, 1);
+    }
+    let mut units = queues.drain();
+    units.sort_unstable();
+    assert_eq!(units, vec![0, 1, 2]);
+  }
+}
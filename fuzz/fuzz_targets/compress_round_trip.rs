@@ -0,0 +1,29 @@
+#![no_main]
+
+use abstract_game::Compress;
+use libfuzzer_sys::fuzz_target;
+use onoro::Onoro16;
+
+fuzz_target!(|data: &[u8]| {
+  let Ok(bytes) = <[u8; Onoro16::COMPRESSED_SIZE]>::try_from(data) else {
+    return;
+  };
+  let Some(onoro) = Onoro16::decompress(&bytes) else {
+    return;
+  };
+  if let Err(err) = onoro.validate() {
+    panic!("Decompressed an invalid state from {bytes:?}: {err}\n{onoro}");
+  }
+
+  let mut compressed = [0u8; Onoro16::COMPRESSED_SIZE];
+  onoro.compress(&mut compressed);
+  assert_eq!(
+    compressed, bytes,
+    "Round trip changed the bytes of\n{onoro}"
+  );
+
+  let decompressed = Onoro16::decompress(&compressed).unwrap();
+  assert_eq!(decompressed.to_string(), onoro.to_string());
+  assert_eq!(decompressed.sum_of_mass(), onoro.sum_of_mass());
+  assert_eq!(decompressed.player_color(), onoro.player_color());
+});
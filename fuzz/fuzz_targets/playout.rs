@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use onoro_fuzz::playout;
+
+fuzz_target!(|data: &[u8]| {
+  playout(data, |onoro| {
+    if let Err(err) = onoro.validate() {
+      panic!("{err}\n{onoro}");
+    }
+  });
+});
@@ -0,0 +1,83 @@
+#![no_main]
+
+use std::hash::{BuildHasher, RandomState};
+
+use libfuzzer_sys::fuzz_target;
+use onoro::{Onoro16, Onoro16View};
+use onoro_fuzz::playout;
+
+/// A pawn as its hex coordinates, along with whether it belongs to the player
+/// to move.
+type Pawn = (i32, i32, bool);
+
+fn pawns(onoro: &Onoro16) -> Vec<Pawn> {
+  let player = onoro.player_color();
+  onoro
+    .pawns()
+    .map(|pawn| {
+      (
+        pawn.pos.x() as i32,
+        pawn.pos.y() as i32,
+        pawn.color == player,
+      )
+    })
+    .collect()
+}
+
+/// Sorts `pawns` and translates them so the first is at the origin, so two
+/// sets of pawns are a translation of each other iff they normalize to the
+/// same list.
+fn normalized(mut pawns: Vec<Pawn>) -> Vec<Pawn> {
+  pawns.sort_unstable();
+  let (x0, y0) = pawns.first().map_or((0, 0), |&(x, y, _)| (x, y));
+  pawns
+    .into_iter()
+    .map(|(x, y, to_move)| (x - x0, y - y0, to_move))
+    .collect()
+}
+
+/// Whether some rotation or reflection of the hex grid, followed by a
+/// translation, maps the pawns of `a` onto the pawns of `b`.
+fn symmetric(a: &Onoro16, b: &Onoro16) -> bool {
+  let target = normalized(pawns(b));
+  let mut pawns = pawns(a);
+  for _ in 0..2 {
+    for _ in 0..6 {
+      if normalized(pawns.clone()) == target {
+        return true;
+      }
+      // Rotate 60 degrees, e.g. (1, 0) -> (1, 1) -> (0, 1).
+      for (x, y, _) in pawns.iter_mut() {
+        (*x, *y) = (*x - *y, *x);
+      }
+    }
+    // Reflect across the line through (0, 0) and (1, 1).
+    for (x, y, _) in pawns.iter_mut() {
+      (*x, *y) = (*y, *x);
+    }
+  }
+  false
+}
+
+fuzz_target!(|data: &[u8]| {
+  let Some((&split, data)) = data.split_first() else {
+    return;
+  };
+  let (first, second) = data.split_at((split as usize).min(data.len()));
+  let a = Onoro16View::new(playout(first, |_| {}));
+  let b = Onoro16View::new(playout(second, |_| {}));
+
+  if a == b {
+    assert_eq!(
+      a.canonical_hash(),
+      b.canonical_hash(),
+      "Equal views with different hashes:\n{a}\n\n{b}"
+    );
+    let hasher = RandomState::new();
+    assert_eq!(hasher.hash_one(&a), hasher.hash_one(&b));
+    assert!(
+      symmetric(a.onoro(), b.onoro()),
+      "Equal views of pawns that aren't symmetric:\n{a}\n\n{b}"
+    );
+  }
+});
@@ -0,0 +1,30 @@
+//! Helpers shared by the fuzz targets in `fuzz_targets/`.
+//!
+//! Run a target with `cargo fuzz run <target>` from the repository root, e.g.
+//! `cargo fuzz run playout`.
+
+use onoro::Onoro16;
+
+/// Plays a game from the default start, choosing each move by the next byte of
+/// `data`, and calls `visit` on every state reached, including the start. The
+/// playout ends when `data` runs out, a player wins, or the player to move has
+/// no legal moves.
+pub fn playout(data: &[u8], mut visit: impl FnMut(&Onoro16)) -> Onoro16 {
+  let mut onoro = Onoro16::default_start();
+  visit(&onoro);
+
+  for &byte in data {
+    if onoro.finished().is_some() {
+      break;
+    }
+
+    let moves: Vec<_> = onoro.each_move().collect();
+    if moves.is_empty() {
+      break;
+    }
+    onoro.make_move(moves[byte as usize % moves.len()]);
+    visit(&onoro);
+  }
+
+  onoro
+}
@@ -0,0 +1,112 @@
+//! Counts the heap allocations made while expanding nodes of the game tree,
+//! comparing collecting each node's moves into a fresh `Vec` against expanding
+//! them into a shared `MoveArena`, and cloning the game state for each child
+//! against making and undoing moves on a single game state.
+//!
+//! Run with `cargo bench --bench expand_into`.
+
+use std::{
+  alloc::{GlobalAlloc, Layout, System},
+  sync::atomic::{AtomicU64, Ordering},
+  time::Instant,
+};
+
+use abstract_game::MoveArena;
+use onoro::{Move, Onoro16};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    System.alloc(layout)
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    System.dealloc(ptr, layout)
+  }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const DEPTH: u32 = 6;
+
+fn expand_vec(onoro: &Onoro16, depth: u32) -> u64 {
+  if depth == 0 {
+    return 1;
+  }
+
+  let moves: Vec<Move> = onoro.each_move().collect();
+  1 + moves
+    .into_iter()
+    .map(|m| {
+      let mut child = onoro.clone();
+      child.make_move(m);
+      expand_vec(&child, depth - 1)
+    })
+    .sum::<u64>()
+}
+
+fn expand_arena(onoro: &Onoro16, depth: u32, arena: &mut MoveArena<Move>) -> u64 {
+  if depth == 0 {
+    return 1;
+  }
+
+  let moves_start = arena.len();
+  let moves_end = moves_start + onoro.expand_into(arena).len();
+  let mut nodes = 1;
+  for move_idx in moves_start..moves_end {
+    let mut child = onoro.clone();
+    child.make_move(arena[move_idx]);
+    nodes += expand_arena(&child, depth - 1, arena);
+  }
+  arena.truncate(moves_start);
+  nodes
+}
+
+fn expand_arena_undo(onoro: &mut Onoro16, depth: u32, arena: &mut MoveArena<Move>) -> u64 {
+  if depth == 0 {
+    return 1;
+  }
+
+  let moves_start = arena.len();
+  let moves_end = moves_start + onoro.expand_into(arena).len();
+  let mut nodes = 1;
+  for move_idx in moves_start..moves_end {
+    let undo = onoro.make_move_with_undo(arena[move_idx]);
+    nodes += expand_arena_undo(onoro, depth - 1, arena);
+    onoro.undo_move(undo);
+  }
+  arena.truncate(moves_start);
+  nodes
+}
+
+fn report(name: &str, run: impl FnOnce() -> u64) {
+  let allocations_before = ALLOCATIONS.load(Ordering::Relaxed);
+  let start = Instant::now();
+  let nodes = run();
+  let elapsed = start.elapsed();
+  let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations_before;
+
+  println!(
+    "{name}: {nodes} nodes in {elapsed:?}, {:.1} allocations per million nodes",
+    allocations as f64 * 1_000_000. / nodes as f64
+  );
+}
+
+fn main() {
+  let onoro = Onoro16::default_start();
+
+  report("Vec per node", || expand_vec(&onoro, DEPTH));
+
+  let mut arena = MoveArena::with_capacity(1024);
+  report("MoveArena", || expand_arena(&onoro, DEPTH, &mut arena));
+
+  let mut onoro = onoro;
+  report("MoveArena with undo", || {
+    expand_arena_undo(&mut onoro, DEPTH, &mut arena)
+  });
+}
@@ -0,0 +1,99 @@
+//! Measures move generation, and making and undoing moves, on random phase 1
+//! and phase 2 positions. These are the paths that check tile occupancy, so
+//! this is the bench to compare changes to the board representation with: run
+//! it before and after, on the same machine. It also compares generating moves
+//! through the iterator with `MoveGenerator::collect_into`.
+//!
+//! Run with `cargo bench --bench movegen`.
+
+use std::time::Instant;
+
+use arrayvec::ArrayVec;
+use onoro::Onoro16;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+const POSITIONS: usize = 2000;
+const ROUNDS: usize = 100;
+
+/// Random positions from playouts of the default start, split into those in
+/// phase 1 and those in phase 2.
+fn positions() -> (Vec<Onoro16>, Vec<Onoro16>) {
+  let mut rng = StdRng::seed_from_u64(1);
+  let mut phase1 = Vec::new();
+  let mut phase2 = Vec::new();
+  let mut onoro = Onoro16::default_start();
+  while phase1.len() < POSITIONS || phase2.len() < POSITIONS {
+    if onoro.finished().is_some() {
+      onoro = Onoro16::default_start();
+    }
+    if onoro.in_phase1() {
+      if phase1.len() < POSITIONS {
+        phase1.push(onoro.clone());
+      }
+    } else if phase2.len() < POSITIONS {
+      phase2.push(onoro.clone());
+    }
+    let moves: Vec<_> = onoro.each_move().collect();
+    onoro.make_move(*moves.choose(&mut rng).unwrap());
+  }
+  (phase1, phase2)
+}
+
+fn report(name: &str, run: impl FnOnce() -> usize) {
+  let start = Instant::now();
+  let moves = run();
+  let elapsed = start.elapsed();
+  println!(
+    "{name}: {moves} moves in {elapsed:?}, {:.1} ns per move",
+    elapsed.as_nanos() as f64 / moves as f64
+  );
+}
+
+fn main() {
+  let (phase1, phase2) = positions();
+  for (name, positions) in [("phase 1", &phase1), ("phase 2", &phase2)] {
+    report(&format!("{name}, generate"), || {
+      (0..ROUNDS)
+        .map(|_| {
+          positions
+            .iter()
+            .map(|onoro| onoro.each_move().count())
+            .sum::<usize>()
+        })
+        .sum()
+    });
+
+    let mut moves = ArrayVec::new();
+    report(&format!("{name}, collect_into"), || {
+      (0..ROUNDS)
+        .map(|_| {
+          positions
+            .iter()
+            .map(|onoro| {
+              moves.clear();
+              onoro.each_move_gen().collect_into(onoro, &mut moves);
+              moves.len()
+            })
+            .sum::<usize>()
+        })
+        .sum()
+    });
+
+    let mut positions = positions.clone();
+    report(&format!("{name}, make and undo"), || {
+      let mut moves = 0;
+      for onoro in &mut positions {
+        let each_move: Vec<_> = onoro.each_move().collect();
+        for _ in 0..ROUNDS {
+          for &m in &each_move {
+            let undo = onoro.make_move_with_undo(m);
+            std::hint::black_box(&onoro);
+            onoro.undo_move(undo);
+          }
+        }
+        moves += ROUNDS * each_move.len();
+      }
+      moves
+    });
+  }
+}
@@ -0,0 +1,128 @@
+//! Compares finding the canonical hash of each child state incrementally from
+//! its parent, as `OnoroView::make_move` does, against recomputing it from
+//! scratch for every child, in the pattern of the solver's hot loop: each
+//! state is hashed (to look it up in the table) before its children are
+//! generated.
+//!
+//! Also compares hashing a batch of states one view at a time against
+//! `OnoroView::canonicalize_batch`, and measures hashing views of phase 1 and
+//! phase 2 states on their own.
+//!
+//! Run with `cargo bench --bench view_hash --features incremental-hash`.
+//! Without the feature, both expansions recompute every hash. Run with
+//! `--features avx2-hash` as well to compare hashing with AVX2 against the
+//! scalar path.
+
+use std::time::Instant;
+
+use abstract_game::Game;
+use onoro::{Onoro16, Onoro16View};
+
+fn expand_incremental(view: &Onoro16View, depth: u32) -> (u64, u64) {
+  let hash = view.canonical_hash();
+  if depth == 0 {
+    return (1, hash);
+  }
+
+  view.each_move().fold((1, hash), |(nodes, hash), m| {
+    let (child_nodes, child_hash) = expand_incremental(&view.with_move(m), depth - 1);
+    (nodes + child_nodes, hash ^ child_hash)
+  })
+}
+
+fn expand_full(view: &Onoro16View, depth: u32) -> (u64, u64) {
+  let hash = view.canonical_hash();
+  if depth == 0 {
+    return (1, hash);
+  }
+
+  view
+    .onoro()
+    .each_move()
+    .fold((1, hash), |(nodes, hash), m| {
+      let mut child = view.onoro().clone();
+      child.make_move(m);
+      let (child_nodes, child_hash) = expand_full(&Onoro16View::new(child), depth - 1);
+      (nodes + child_nodes, hash ^ child_hash)
+    })
+}
+
+/// Every state within `depth` moves of `onoro`.
+fn collect_states(onoro: &Onoro16, depth: u32, states: &mut Vec<Onoro16>) {
+  states.push(onoro.clone());
+  if depth == 0 {
+    return;
+  }
+  for m in onoro.each_move() {
+    let mut child = onoro.clone();
+    child.make_move(m);
+    collect_states(&child, depth - 1, states);
+  }
+}
+
+/// Hashes a view of each of `states`, xoring the hashes together.
+fn hash_views(states: &[Onoro16]) -> (u64, u64) {
+  let hash = states.iter().fold(0, |hash, onoro| {
+    hash ^ Onoro16View::new(onoro.clone()).canonical_hash()
+  });
+  (states.len() as u64, hash)
+}
+
+fn report(name: &str, run: impl FnOnce() -> (u64, u64)) -> u64 {
+  let start = Instant::now();
+  let (nodes, hash) = run();
+  let elapsed = start.elapsed();
+  println!(
+    "{name}: {nodes} nodes in {elapsed:?}, {:.1} ns per node",
+    elapsed.as_nanos() as f64 / nodes as f64
+  );
+  hash
+}
+
+fn main() {
+  let phase2 = Onoro16::from_board_string(
+    ". . . . .
+      . B W W B
+       . W B B W
+        . B W W B
+         . W B B W",
+  )
+  .unwrap();
+
+  for (name, onoro, depth) in [
+    ("default start", Onoro16::default_start(), 5),
+    ("phase 2", phase2.clone(), 3),
+  ] {
+    let view = Onoro16View::new(onoro);
+    let incremental = report(&format!("{name}, incremental"), || {
+      expand_incremental(&view, depth)
+    });
+    let full = report(&format!("{name}, full recomputation"), || {
+      expand_full(&Onoro16View::new(view.onoro().clone()), depth)
+    });
+    assert_eq!(incremental, full, "Hashes differ from {name}");
+  }
+
+  let mut states = Vec::new();
+  collect_states(&Onoro16::default_start(), 6, &mut states);
+  let one_at_a_time = report("batch, one view at a time", || {
+    let views: Vec<_> = states.iter().cloned().map(Onoro16View::new).collect();
+    let hash = views
+      .iter()
+      .fold(0, |hash, view| hash ^ view.canonical_hash());
+    (states.len() as u64, hash)
+  });
+  let batch = report("batch, canonicalize_batch", || {
+    let hash = Onoro16View::canonicalize_batch(&states)
+      .iter()
+      .fold(0, |hash, view| hash ^ view.canonical_hash());
+    (states.len() as u64, hash)
+  });
+  assert_eq!(one_at_a_time, batch, "Batch hashes differ");
+
+  let mut phase2_states = Vec::new();
+  collect_states(&phase2, 3, &mut phase2_states);
+  for (name, states) in [("phase 1", &states), ("phase 2", &phase2_states)] {
+    report(&format!("hashing {name} views"), || hash_views(states));
+  }
+}
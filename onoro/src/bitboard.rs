@@ -0,0 +1,116 @@
+/// The number of words in a `Bitboard`, which is enough for the largest
+/// (16x16) board.
+const WORDS: usize = 256 / 64;
+
+/// A set of tiles on the board, with one bit per tile, indexed by
+/// `Onoro::hex_pos_ord`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Bitboard {
+  words: [u64; WORDS],
+}
+
+impl Bitboard {
+  pub const fn new() -> Self {
+    Self { words: [0; WORDS] }
+  }
+
+  pub fn insert(&mut self, ord: usize) {
+    debug_assert!(ord < WORDS * 64);
+    unsafe {
+      *self.words.get_unchecked_mut(ord / 64) |= 1 << (ord % 64);
+    }
+  }
+
+  pub fn remove(&mut self, ord: usize) {
+    debug_assert!(ord < WORDS * 64);
+    unsafe {
+      *self.words.get_unchecked_mut(ord / 64) &= !(1 << (ord % 64));
+    }
+  }
+
+  /// True if the tile at `ord` is in the set. Tiles past the end of the
+  /// board, which neighbors of tiles on its edge may be, are never in the set.
+  pub fn contains(&self, ord: usize) -> bool {
+    self
+      .words
+      .get(ord / 64)
+      .is_some_and(|word| (word >> (ord % 64)) & 1 != 0)
+  }
+
+  /// Moves every tile of the set `by` places, towards higher `ord`s if `by`
+  /// is positive. Tiles shifted past either end of the board are dropped.
+  pub fn shift(&mut self, by: i32) {
+    let n = by.unsigned_abs();
+    debug_assert!(n < 64);
+    if n == 0 {
+      return;
+    }
+
+    if by > 0 {
+      for i in (0..WORDS).rev() {
+        let carry = if i > 0 {
+          self.words[i - 1] >> (64 - n)
+        } else {
+          0
+        };
+        self.words[i] = (self.words[i] << n) | carry;
+      }
+    } else {
+      for i in 0..WORDS {
+        let carry = if i + 1 < WORDS {
+          self.words[i + 1] << (64 - n)
+        } else {
+          0
+        };
+        self.words[i] = (self.words[i] >> n) | carry;
+      }
+    }
+  }
+
+  pub fn count(&self) -> u32 {
+    self.words.iter().map(|word| word.count_ones()).sum()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Bitboard;
+
+  #[test]
+  fn test_bitboard() {
+    let mut bitboard = Bitboard::new();
+    for ord in [0, 63, 64, 200, 255] {
+      assert!(!bitboard.contains(ord));
+      bitboard.insert(ord);
+      assert!(bitboard.contains(ord));
+    }
+    assert_eq!(bitboard.count(), 5);
+    assert!(!bitboard.contains(1));
+    assert!(!bitboard.contains(usize::MAX));
+
+    bitboard.remove(64);
+    assert!(!bitboard.contains(64));
+    assert!(bitboard.contains(63));
+    assert_eq!(bitboard.count(), 4);
+  }
+
+  #[test]
+  fn test_shift() {
+    let mut bitboard = Bitboard::new();
+    for ord in [1, 62, 100, 240] {
+      bitboard.insert(ord);
+    }
+
+    bitboard.shift(17);
+    for ord in [18, 79, 117] {
+      assert!(bitboard.contains(ord));
+    }
+    assert_eq!(bitboard.count(), 3);
+
+    bitboard.shift(-18);
+    for ord in [0, 61, 99] {
+      assert!(bitboard.contains(ord));
+    }
+    assert_eq!(bitboard.count(), 3);
+  }
+}
@@ -1,5 +1,8 @@
+use algebra::ordinal::Ordinal;
+
 use crate::{
   groups::{SymmetryClass, D6},
+  onoro_defs::{SYMM_TABLE_16, SYMM_TABLE_8},
   util::{max_u32, min_u32, unreachable},
   Onoro,
 };
@@ -8,7 +11,7 @@ use super::hex_pos::HexPosOffset;
 
 /// Describes the layout of the game state, and provides enough information to
 /// canonicalize the state for hash computation.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct BoardSymmetryState {
   /// The group operation to perform on the board before calculating the hash.
   /// This is used to align board states on all symmetry axes which the board
@@ -27,13 +30,39 @@ pub struct BoardSymmetryState {
 }
 
 impl BoardSymmetryState {
-  const fn blank() -> Self {
+  pub(crate) const fn blank() -> Self {
     Self {
       op: D6::const_identity(),
       symm_class: SymmetryClass::C,
       center_offset: HexPosOffset::origin(),
     }
   }
+
+  /// Packs this state into a byte, which is how `Onoro` caches it. The center
+  /// offset is determined by `op`, so it doesn't need to be stored.
+  pub(crate) fn pack(&self) -> u8 {
+    self.op.ord() as u8 | ((self.symm_class as u8) << 4)
+  }
+
+  /// The inverse of `pack`.
+  pub(crate) fn unpack(packed: u8) -> Self {
+    let op = D6::from_ord((packed & 0xf) as usize);
+    let symm_class = match packed >> 4 {
+      0 => SymmetryClass::C,
+      1 => SymmetryClass::V,
+      2 => SymmetryClass::E,
+      3 => SymmetryClass::CV,
+      4 => SymmetryClass::CE,
+      5 => SymmetryClass::EV,
+      6 => SymmetryClass::Trivial,
+      _ => unreachable(),
+    };
+    Self {
+      op,
+      symm_class,
+      center_offset: com_offset_to_hex_pos(board_symm_state_op_to_com_offset(&op)),
+    }
+  }
 }
 
 enum COMOffset {
@@ -304,16 +333,38 @@ pub const fn gen_symm_state_table<const N: usize, const N2: usize>() -> [BoardSy
 /// since they are symmetries of each other in this K4 group.
 pub fn board_symm_state<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
   onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+) -> BoardSymmetryState {
+  let symm_state = onoro.symm_state();
+  #[cfg(feature = "verify-simd")]
+  assert_eq!(symm_state, compute_board_symm_state(onoro));
+  symm_state
+}
+
+/// Computes the symmetry state of `onoro` from its center of mass, which
+/// `Onoro` does whenever it changes, see `board_symm_state`.
+pub(crate) fn compute_board_symm_state<
+  const N: usize,
+  const N2: usize,
+  const ADJ_CNT_SIZE: usize,
+>(
+  onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
 ) -> BoardSymmetryState {
   let sum_of_mass = onoro.sum_of_mass();
   let pawns_in_play = onoro.pawns_in_play();
 
-  // TODO: use table for pawns_in_play == N
-  // if pawns_in_play == N as u32 { ... }
-
   let x = sum_of_mass.x() as u32 % pawns_in_play;
   let y = sum_of_mass.y() as u32 % pawns_in_play;
 
+  // Every pawn is in play in phase 2, where the states have been precomputed.
+  if pawns_in_play == N as u32 {
+    let table: &[BoardSymmetryState] = if N == 8 {
+      &SYMM_TABLE_8
+    } else {
+      &SYMM_TABLE_16
+    };
+    return table[(x + y * N as u32) as usize];
+  }
+
   let op = symm_state_op(x, y, pawns_in_play);
   let symm_class = symm_state_class(x, y, pawns_in_play);
   let center_offset = com_offset_to_hex_pos(board_symm_state_op_to_com_offset(&op));
@@ -324,3 +375,43 @@ pub fn board_symm_state<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usi
     center_offset,
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use crate::onoro_defs::{SYMM_TABLE_16, SYMM_TABLE_8};
+
+  use super::{
+    board_symm_state_op_to_com_offset, com_offset_to_hex_pos, symm_state_class, symm_state_op,
+    BoardSymmetryState,
+  };
+
+  #[test]
+  fn test_pack() {
+    for n_pawns in 1..=16 {
+      for y in 0..n_pawns {
+        for x in 0..n_pawns {
+          let op = symm_state_op(x, y, n_pawns);
+          let symm_state = BoardSymmetryState {
+            op,
+            symm_class: symm_state_class(x, y, n_pawns),
+            center_offset: com_offset_to_hex_pos(board_symm_state_op_to_com_offset(&op)),
+          };
+          assert_eq!(BoardSymmetryState::unpack(symm_state.pack()), symm_state);
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_tables() {
+    for (n_pawns, table) in [(8, &SYMM_TABLE_8[..]), (16, &SYMM_TABLE_16[..])] {
+      for y in 0..n_pawns {
+        for x in 0..n_pawns {
+          let symm_state = table[(x + y * n_pawns) as usize];
+          assert_eq!(symm_state.op, symm_state_op(x, y, n_pawns));
+          assert_eq!(symm_state.symm_class, symm_state_class(x, y, n_pawns));
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,334 @@
+use std::fmt::Display;
+
+use crate::{
+  hex_pos::{HexPos, HexPosOffset},
+  PackedIdx,
+};
+
+/// The distance between the centers of horizontally neighboring tiles,
+/// relative to the radius of a tile.
+pub(crate) const SQRT_3: f64 = 1.732_050_807_568_877_2;
+
+/// A tile in the standard axial coordinates of a grid of pointy-topped
+/// hexagons, with `q` increasing to the right and `r` increasing down and to
+/// the right. The six neighbors of a tile are offset by (±1, 0), (0, ±1),
+/// (1, -1) and (-1, 1).
+///
+/// The board's own coordinates (those of `PackedIdx`) are axial coordinates
+/// skewed the other way, with `y` increasing up and to the right, so that
+/// neighbors are offset by (1, 1) and (-1, -1) instead. Converting between them
+/// only flips the sign of the row: `q = x` and `r = -y`. This is the layout
+/// `Onoro`'s `Display` and `render::svg` draw boards in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Axial {
+  pub q: i32,
+  pub r: i32,
+}
+
+/// A tile in cube coordinates, which are axial coordinates with a redundant
+/// third coordinate `s = -q - r`. The three axes are symmetric, which makes
+/// distances and rounding simple.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Cube {
+  pub q: i32,
+  pub r: i32,
+  pub s: i32,
+}
+
+/// A tile in "odd-r" offset coordinates, i.e. a column and row in a grid of
+/// pointy-topped hexagons where odd rows are shifted half a tile right. This
+/// is how hex grids are often stored in rectangular arrays.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct OddR {
+  pub col: i32,
+  pub row: i32,
+}
+
+impl Axial {
+  pub const fn new(q: i32, r: i32) -> Self {
+    Self { q, r }
+  }
+
+  pub const fn to_cube(&self) -> Cube {
+    Cube::new(self.q, self.r)
+  }
+
+  pub const fn to_odd_r(&self) -> OddR {
+    OddR {
+      col: self.q + (self.r - (self.r & 1)) / 2,
+      row: self.r,
+    }
+  }
+
+  /// The tile at these coordinates on the board, or `None` if they're off the
+  /// board.
+  pub fn to_packed_idx(&self) -> Option<PackedIdx> {
+    match (u32::try_from(self.q), u32::try_from(-self.r)) {
+      (Ok(x @ 0..=15), Ok(y @ 0..=15)) => Some(PackedIdx::new(x, y)),
+      _ => None,
+    }
+  }
+
+  /// The number of steps between adjacent tiles it takes to get from `self` to
+  /// `other`.
+  pub const fn distance(&self, other: &Self) -> u32 {
+    self.to_cube().distance(&other.to_cube())
+  }
+
+  /// The tiles on the straight line from `self` to `other`, including both,
+  /// with each tile adjacent to the one before it.
+  pub fn line_to(&self, other: &Self) -> Vec<Self> {
+    self
+      .to_cube()
+      .line_to(&other.to_cube())
+      .into_iter()
+      .map(Self::from)
+      .collect()
+  }
+
+  /// The center of this tile in a drawing with tiles of radius `radius` (the
+  /// distance from the center of a tile to its corners), with (0, 0) centered
+  /// on the origin and y increasing down, as in SVG.
+  pub fn to_pixel(&self, radius: f64) -> (f64, f64) {
+    (
+      SQRT_3 * radius * (self.q as f64 + self.r as f64 / 2.),
+      1.5 * radius * self.r as f64,
+    )
+  }
+}
+
+impl Cube {
+  /// The tile `(q, r, -q - r)`.
+  pub const fn new(q: i32, r: i32) -> Self {
+    Self { q, r, s: -q - r }
+  }
+
+  pub const fn to_axial(&self) -> Axial {
+    Axial::new(self.q, self.r)
+  }
+
+  pub const fn distance(&self, other: &Self) -> u32 {
+    let dq = self.q.abs_diff(other.q);
+    let dr = self.r.abs_diff(other.r);
+    let ds = self.s.abs_diff(other.s);
+    let max = if dq > dr { dq } else { dr };
+    if max > ds {
+      max
+    } else {
+      ds
+    }
+  }
+
+  /// The tile containing the point with fractional cube coordinates `(q, r,
+  /// s)`.
+  fn round(q: f64, r: f64, s: f64) -> Self {
+    let (rq, rr, rs) = (q.round(), r.round(), s.round());
+    let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+    // Rounding each coordinate may break `q + r + s = 0`, so the coordinate
+    // that moved the most is recomputed from the other two.
+    if dq > dr && dq > ds {
+      Self::new(-(rr as i32) - rs as i32, rr as i32)
+    } else if dr > ds {
+      Self::new(rq as i32, -(rq as i32) - rs as i32)
+    } else {
+      Self::new(rq as i32, rr as i32)
+    }
+  }
+
+  /// Like `Axial::line_to`.
+  pub fn line_to(&self, other: &Self) -> Vec<Self> {
+    let steps = self.distance(other);
+    if steps == 0 {
+      return vec![*self];
+    }
+
+    // Nudges the line off of the edges between tiles, so points on it round
+    // consistently.
+    const EPSILON: f64 = 1e-6;
+    let (q0, r0, s0) = (
+      self.q as f64 + EPSILON,
+      self.r as f64 + EPSILON,
+      self.s as f64 - 2. * EPSILON,
+    );
+    (0..=steps)
+      .map(|step| {
+        let t = step as f64 / steps as f64;
+        Self::round(
+          q0 + (other.q as f64 - q0) * t,
+          r0 + (other.r as f64 - r0) * t,
+          s0 + (other.s as f64 - s0) * t,
+        )
+      })
+      .collect()
+  }
+}
+
+impl OddR {
+  pub const fn new(col: i32, row: i32) -> Self {
+    Self { col, row }
+  }
+
+  pub const fn to_axial(&self) -> Axial {
+    Axial::new(self.col - (self.row - (self.row & 1)) / 2, self.row)
+  }
+
+  pub const fn distance(&self, other: &Self) -> u32 {
+    self.to_axial().distance(&other.to_axial())
+  }
+}
+
+impl From<Cube> for Axial {
+  fn from(cube: Cube) -> Self {
+    cube.to_axial()
+  }
+}
+
+impl From<Axial> for Cube {
+  fn from(axial: Axial) -> Self {
+    axial.to_cube()
+  }
+}
+
+impl From<OddR> for Axial {
+  fn from(odd_r: OddR) -> Self {
+    odd_r.to_axial()
+  }
+}
+
+impl From<Axial> for OddR {
+  fn from(axial: Axial) -> Self {
+    axial.to_odd_r()
+  }
+}
+
+impl From<PackedIdx> for Axial {
+  fn from(pos: PackedIdx) -> Self {
+    Self::new(pos.x() as i32, -(pos.y() as i32))
+  }
+}
+
+impl From<HexPos> for Axial {
+  fn from(pos: HexPos) -> Self {
+    Self::new(pos.x() as i32, -(pos.y() as i32))
+  }
+}
+
+impl From<HexPosOffset> for Axial {
+  fn from(offset: HexPosOffset) -> Self {
+    Self::new(offset.x(), -offset.y())
+  }
+}
+
+impl Display for Axial {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "(q {}, r {})", self.q, self.r)
+  }
+}
+
+impl Display for Cube {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "(q {}, r {}, s {})", self.q, self.r, self.s)
+  }
+}
+
+impl Display for OddR {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "(col {}, row {})", self.col, self.row)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    hex_pos::{HexPos, HexPosOffset},
+    PackedIdx,
+  };
+
+  use super::{Axial, Cube, OddR};
+
+  #[test]
+  fn test_round_trips() {
+    for q in -8..8 {
+      for r in -8..8 {
+        let axial = Axial::new(q, r);
+        let cube = axial.to_cube();
+        assert_eq!(cube.q + cube.r + cube.s, 0);
+        assert_eq!(Axial::from(cube), axial);
+        assert_eq!(Axial::from(axial.to_odd_r()), axial);
+      }
+    }
+
+    for x in 0..16 {
+      for y in 0..16 {
+        let pos = PackedIdx::new(x, y);
+        assert_eq!(Axial::from(pos).to_packed_idx(), Some(pos));
+        assert_eq!(Axial::from(pos), Axial::from(HexPos::from(pos)));
+      }
+    }
+    assert_eq!(Axial::new(-1, 0).to_packed_idx(), None);
+    assert_eq!(Axial::new(0, 1).to_packed_idx(), None);
+    assert_eq!(Axial::new(16, 0).to_packed_idx(), None);
+  }
+
+  #[test]
+  fn test_odd_r() {
+    // Odd rows are shifted right, so the tile below and to the right of (0, 0)
+    // is (0, 1), while below and to the right of (0, 1) is (1, 2).
+    assert_eq!(OddR::new(0, 0).to_axial(), Axial::new(0, 0));
+    assert_eq!(OddR::new(0, 1).to_axial(), Axial::new(0, 1));
+    assert_eq!(OddR::new(1, 2).to_axial(), Axial::new(0, 2));
+    assert_eq!(OddR::new(0, -1).to_axial(), Axial::new(1, -1));
+    assert_eq!(OddR::new(0, 0).distance(&OddR::new(1, 2)), 2);
+  }
+
+  #[test]
+  fn test_neighbors() {
+    let origin = HexPos::new(4, 4);
+    for neighbor in origin.each_neighbor() {
+      let axial = Axial::from(neighbor);
+      assert_eq!(Axial::from(origin).distance(&axial), 1);
+      assert_eq!(Axial::from(origin).line_to(&axial).len(), 2);
+
+      // Neighboring tiles are drawn one tile width apart.
+      let (x0, y0) = Axial::from(origin).to_pixel(1.);
+      let (x1, y1) = axial.to_pixel(1.);
+      assert!(((x1 - x0).hypot(y1 - y0) - 3f64.sqrt()).abs() < 1e-9);
+    }
+  }
+
+  #[test]
+  fn test_distance() {
+    for x in -5..=5 {
+      for y in -5..=5 {
+        let offset = HexPosOffset::new(x, y);
+        assert_eq!(
+          Axial::from(offset).distance(&Axial::default()),
+          offset.hex_distance()
+        );
+      }
+    }
+    assert_eq!(Cube::new(0, 0).distance(&Cube::new(3, -1)), 3);
+  }
+
+  #[test]
+  fn test_line() {
+    assert_eq!(
+      Axial::new(1, 1).line_to(&Axial::new(1, 1)),
+      vec![Axial::new(1, 1)]
+    );
+    assert_eq!(
+      Axial::new(0, 0).line_to(&Axial::new(3, 0)),
+      (0..=3).map(|q| Axial::new(q, 0)).collect::<Vec<_>>()
+    );
+
+    for end in [Axial::new(4, -1), Axial::new(-3, 5), Axial::new(2, 2)] {
+      let line = Axial::default().line_to(&end);
+      assert_eq!(line.len() as u32, end.distance(&Axial::default()) + 1);
+      assert_eq!(line.first(), Some(&Axial::default()));
+      assert_eq!(line.last(), Some(&end));
+      for pair in line.windows(2) {
+        assert_eq!(pair[0].distance(&pair[1]), 1);
+      }
+    }
+  }
+}
@@ -0,0 +1,318 @@
+//! Training examples for machine-learned policy and value models, and the
+//! binary format they are stored in.
+//!
+//! A dataset file is a header followed by any number of examples, with all
+//! integers and floats little-endian:
+//!
+//! ```text
+//! header:  b"ONDS", version: u32 (1), board width N: u32
+//! example: board: [i8; N * N], value: f32, move count M: u16,
+//!          M times: from: u16, to: u16, probability: f32
+//! ```
+//!
+//! Tiles are indexed `y * N + x`. On the board, the player to move's pawns are
+//! 1, the other player's are -1, and empty tiles are 0. The value is the final
+//! result of the game for the player to move: 1 for a win, -1 for a loss and 0
+//! for a draw. Each legal move has the tile it moves a pawn from, or
+//! `NO_TILE` for phase 1 placements, the tile it moves a pawn to, and its
+//! probability under the policy being learned.
+//!
+//! Boards are centered on the tile `(N / 2, N / 2)` by the same origin the
+//! game state's canonical hash is found relative to, so translations of a
+//! board give the same example.
+
+use std::io::{self, Read, Write};
+
+use algebra::monoid::Monoid;
+
+use crate::{
+  canonicalize::board_symm_state,
+  groups::D6,
+  hex_pos::{HexPos, HexPosOffset},
+  Move, Onoro, PawnColor,
+};
+
+/// The magic bytes a dataset file starts with.
+pub const DATASET_MAGIC: [u8; 4] = *b"ONDS";
+
+/// The version of the dataset format described in the module docs.
+pub const DATASET_VERSION: u32 = 1;
+
+/// The `from` tile of moves that place a pawn rather than move one.
+pub const NO_TILE: u16 = u16::MAX;
+
+/// A legal move of a training example, and its probability.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PolicyEntry {
+  pub from: u16,
+  pub to: u16,
+  pub probability: f32,
+}
+
+/// One position of a game, labeled with the policy to learn in it and the
+/// final result of the game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrainingExample {
+  pub board: Vec<i8>,
+  pub value: f32,
+  pub policy: Vec<PolicyEntry>,
+}
+
+impl TrainingExample {
+  /// The examples for `onoro`, in which each move of `policy` is played with
+  /// its probability and the game ends with `value` for the player to move.
+  ///
+  /// With `augment`, there is an example for each of the 12 rotations and
+  /// reflections of the board, in the order of `D6::for_each`, the first
+  /// being the board as it is. Otherwise there is only that first example.
+  /// Orientations whose pawns or moves would fall off the board are skipped.
+  pub fn from_position<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+    onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+    policy: &[(Move, f32)],
+    value: f32,
+    augment: bool,
+  ) -> Vec<Self> {
+    let origin = onoro.origin(&board_symm_state(onoro));
+    let ops = if augment {
+      D6::for_each().collect()
+    } else {
+      vec![D6::identity()]
+    };
+    ops
+      .into_iter()
+      .filter_map(|op| Self::oriented(onoro, policy, value, origin, &op))
+      .collect()
+  }
+
+  fn oriented<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+    onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+    policy: &[(Move, f32)],
+    value: f32,
+    origin: HexPos,
+    op: &D6,
+  ) -> Option<Self> {
+    let tile = |pos: HexPos| -> Option<u16> {
+      let pos = (pos - origin).apply_d6_c(op) + HexPosOffset::new(N as i32 / 2, N as i32 / 2);
+      let range = 0..N as i32;
+      (range.contains(&pos.x()) && range.contains(&pos.y()))
+        .then_some((pos.y() * N as i32 + pos.x()) as u16)
+    };
+
+    let mover = onoro.player_color();
+    let mut board = vec![0; N * N];
+    for pawn in onoro.pawns() {
+      board[tile(pawn.pos.into())? as usize] = if pawn.color == mover { 1 } else { -1 };
+    }
+
+    let policy = policy
+      .iter()
+      .map(|&(m, probability)| {
+        let (from, to) = match m {
+          Move::Phase1Move { to } => (NO_TILE, to),
+          Move::Phase2Move { to, from_idx } => {
+            (tile(onoro.pawn_pos(from_idx as usize).into())?, to)
+          }
+        };
+        Some(PolicyEntry {
+          from,
+          to: tile(to.into())?,
+          probability,
+        })
+      })
+      .collect::<Option<_>>()?;
+
+    Some(Self {
+      board,
+      value,
+      policy,
+    })
+  }
+
+  /// The value of a game which `winner` won, or which was drawn if `None`,
+  /// for the player to move in `onoro`.
+  pub fn value_for<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+    onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+    winner: Option<PawnColor>,
+  ) -> f32 {
+    match winner {
+      Some(color) if color == onoro.player_color() => 1.,
+      Some(_) => -1.,
+      None => 0.,
+    }
+  }
+
+  /// Writes the dataset header for boards `board_width` tiles wide.
+  pub fn write_header(out: &mut impl Write, board_width: usize) -> io::Result<()> {
+    out.write_all(&DATASET_MAGIC)?;
+    out.write_all(&DATASET_VERSION.to_le_bytes())?;
+    out.write_all(&(board_width as u32).to_le_bytes())
+  }
+
+  /// Reads a dataset header, returning the width of the boards that follow.
+  pub fn read_header(input: &mut impl Read) -> io::Result<usize> {
+    let mut header = [0; 12];
+    input.read_exact(&mut header)?;
+    if header[0..4] != DATASET_MAGIC {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "Not an Onoro dataset",
+      ));
+    }
+    let version = u32::from_le_bytes(header[4..8].try_into().unwrap());
+    if version != DATASET_VERSION {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Unsupported dataset version {version}"),
+      ));
+    }
+    Ok(u32::from_le_bytes(header[8..12].try_into().unwrap()) as usize)
+  }
+
+  pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+    let board: Vec<u8> = self.board.iter().map(|&tile| tile as u8).collect();
+    out.write_all(&board)?;
+    out.write_all(&self.value.to_le_bytes())?;
+    out.write_all(&(self.policy.len() as u16).to_le_bytes())?;
+    for entry in &self.policy {
+      out.write_all(&entry.from.to_le_bytes())?;
+      out.write_all(&entry.to.to_le_bytes())?;
+      out.write_all(&entry.probability.to_le_bytes())?;
+    }
+    Ok(())
+  }
+
+  /// Reads the next example of a dataset of boards `board_width` tiles wide,
+  /// or returns `None` at the end of the dataset.
+  pub fn read(input: &mut impl Read, board_width: usize) -> io::Result<Option<Self>> {
+    let mut board = vec![0; board_width * board_width];
+    match input.read_exact(&mut board) {
+      Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+      result => result?,
+    }
+
+    let mut buf = [0; 6];
+    input.read_exact(&mut buf)?;
+    let value = f32::from_le_bytes(buf[0..4].try_into().unwrap());
+    let n_moves = u16::from_le_bytes(buf[4..6].try_into().unwrap());
+
+    let mut policy = Vec::with_capacity(n_moves as usize);
+    for _ in 0..n_moves {
+      let mut entry = [0; 8];
+      input.read_exact(&mut entry)?;
+      policy.push(PolicyEntry {
+        from: u16::from_le_bytes(entry[0..2].try_into().unwrap()),
+        to: u16::from_le_bytes(entry[2..4].try_into().unwrap()),
+        probability: f32::from_le_bytes(entry[4..8].try_into().unwrap()),
+      });
+    }
+
+    Ok(Some(Self {
+      board: board.into_iter().map(|tile| tile as i8).collect(),
+      value,
+      policy,
+    }))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use algebra::monoid::Monoid;
+  use rand::{rngs::StdRng, SeedableRng};
+
+  use crate::{groups::D6, testing::random_playout, Move, Onoro16};
+
+  use super::{TrainingExample, NO_TILE};
+
+  /// Uniform policies over the legal moves of positions along a random game.
+  fn random_positions() -> Vec<(Onoro16, Vec<(Move, f32)>)> {
+    let (mut states, moves) =
+      random_playout(Onoro16::default_start(), 40, &mut StdRng::seed_from_u64(31));
+    states.truncate(moves.len());
+    states
+      .into_iter()
+      .map(|onoro| {
+        let moves: Vec<_> = onoro.each_move().collect();
+        let probability = 1. / moves.len() as f32;
+        let policy = moves.iter().map(|&m| (m, probability)).collect();
+        (onoro, policy)
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_augmented_examples() {
+    for (onoro, policy) in random_positions() {
+      let examples = TrainingExample::from_position(&onoro, &policy, 1., true);
+      assert_eq!(examples.len(), 12);
+      assert_eq!(
+        examples[0],
+        TrainingExample::from_position(&onoro, &policy, 1., false)[0]
+      );
+
+      for example in &examples {
+        let mover_pawns = example.board.iter().filter(|&&tile| tile == 1).count();
+        let other_pawns = example.board.iter().filter(|&&tile| tile == -1).count();
+        assert_eq!(mover_pawns + other_pawns, onoro.pawns_in_play() as usize);
+        assert_eq!(example.policy.len(), policy.len());
+
+        // Moves go from the mover's pawns to empty tiles.
+        for entry in &example.policy {
+          assert_eq!(example.board[entry.to as usize], 0);
+          assert_eq!(entry.from == NO_TILE, onoro.in_phase1());
+          if entry.from != NO_TILE {
+            assert_eq!(example.board[entry.from as usize], 1);
+          }
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_translation_invariant() {
+    for (onoro, policy) in random_positions() {
+      let rotated = onoro.rotated_d6_c(D6::identity());
+      let policy_at = |game: &Onoro16| {
+        let moves: Vec<_> = game.each_move().collect();
+        let probability = 1. / moves.len() as f32;
+        moves
+          .into_iter()
+          .map(|m| (m, probability))
+          .collect::<Vec<_>>()
+      };
+      assert_eq!(policy_at(&onoro).len(), policy.len());
+
+      let boards = |game: &Onoro16| {
+        TrainingExample::from_position(game, &policy_at(game), 0., true)
+          .into_iter()
+          .map(|example| example.board)
+          .collect::<Vec<_>>()
+      };
+      assert_eq!(boards(&onoro), boards(&rotated));
+    }
+  }
+
+  #[test]
+  fn test_read_write() {
+    let mut bytes = Vec::new();
+    TrainingExample::write_header(&mut bytes, 16).unwrap();
+    let mut examples = Vec::new();
+    for (onoro, policy) in random_positions().into_iter().step_by(9) {
+      for example in TrainingExample::from_position(&onoro, &policy, -1., true) {
+        example.write(&mut bytes).unwrap();
+        examples.push(example);
+      }
+    }
+
+    let mut input = bytes.as_slice();
+    assert_eq!(TrainingExample::read_header(&mut input).unwrap(), 16);
+    for example in examples {
+      assert_eq!(
+        TrainingExample::read(&mut input, 16).unwrap(),
+        Some(example)
+      );
+    }
+    assert_eq!(TrainingExample::read(&mut input, 16).unwrap(), None);
+
+    assert!(TrainingExample::read_header(&mut &b"ONDX\x01\0\0\0\x10\0\0\0"[..]).is_err());
+  }
+}
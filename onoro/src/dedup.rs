@@ -0,0 +1,172 @@
+use crate::OnoroView;
+
+/// Filters a stream of game states down to the first occurrence of each
+/// canonical state, in memory bounded by a Bloom filter of canonical hashes
+/// instead of a set that grows with every state seen.
+///
+/// The filter can report states as seen that weren't (a false positive),
+/// which drops them from the stream, but never lets a repeated state through.
+/// The chance of dropping a new state grows as the filter fills, staying
+/// near the rate it was sized for until `capacity` states have been kept.
+pub struct DedupSink {
+  bits: Vec<u64>,
+  num_hashes: u32,
+  capacity: u64,
+  kept: u64,
+}
+
+impl DedupSink {
+  /// A filter sized to keep `capacity` states while wrongly dropping new
+  /// states with probability at most `false_positive_rate`.
+  pub fn new(capacity: u64, false_positive_rate: f64) -> Self {
+    debug_assert!(0. < false_positive_rate && false_positive_rate < 1.);
+    // The optimal size of a Bloom filter is -n ln(p) / ln(2)^2 bits, with
+    // m / n ln(2) hash functions.
+    let ln2 = std::f64::consts::LN_2;
+    let num_bits = (-(capacity.max(1) as f64) * false_positive_rate.ln() / (ln2 * ln2)).ceil();
+    let num_hashes = (num_bits / capacity.max(1) as f64 * ln2).round();
+    Self::with_size(num_bits as usize, num_hashes as u32, capacity)
+  }
+
+  /// A filter using `bytes` bytes of memory, sized for `capacity` states.
+  pub fn with_memory(bytes: usize, capacity: u64) -> Self {
+    let num_bits = bytes * 8;
+    let num_hashes = (num_bits as f64 / capacity.max(1) as f64 * std::f64::consts::LN_2).round();
+    Self::with_size(num_bits, num_hashes as u32, capacity)
+  }
+
+  fn with_size(num_bits: usize, num_hashes: u32, capacity: u64) -> Self {
+    Self {
+      bits: vec![0; num_bits.div_ceil(64).max(1)],
+      num_hashes: num_hashes.max(1),
+      capacity,
+      kept: 0,
+    }
+  }
+
+  /// Records `hash`, returning true if it wasn't seen before. May return false
+  /// for an unseen hash with the probability of `false_positive_rate`.
+  pub fn insert_hash(&mut self, hash: u64) -> bool {
+    // Canonical hashes are uniformly random, so the bit indices can be derived
+    // from the two halves of the hash by double hashing.
+    let num_bits = self.bits.len() as u64 * 64;
+    let h1 = hash;
+    let h2 = hash.rotate_left(32) | 1;
+    let mut new = false;
+    for i in 0..self.num_hashes as u64 {
+      let bit = h1.wrapping_add(i.wrapping_mul(h2)) % num_bits;
+      let (word, mask) = ((bit / 64) as usize, 1 << (bit % 64));
+      new |= self.bits[word] & mask == 0;
+      self.bits[word] |= mask;
+    }
+    if new {
+      self.kept += 1;
+    }
+    new
+  }
+
+  /// Records `view`, returning true if no symmetric state was seen before.
+  pub fn insert<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+    &mut self,
+    view: &OnoroView<N, N2, ADJ_CNT_SIZE>,
+  ) -> bool {
+    self.insert_hash(view.canonical_hash())
+  }
+
+  /// The states of `views` that no state before them, in this stream or any
+  /// other that passed through this filter, was symmetric to.
+  pub fn filter<'a, const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+    &'a mut self,
+    views: impl IntoIterator<Item = OnoroView<N, N2, ADJ_CNT_SIZE>> + 'a,
+  ) -> impl Iterator<Item = OnoroView<N, N2, ADJ_CNT_SIZE>> + 'a {
+    views.into_iter().filter(|view| self.insert(view))
+  }
+
+  /// The number of states kept so far.
+  pub fn len(&self) -> u64 {
+    self.kept
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.kept == 0
+  }
+
+  /// The number of states the filter was sized for.
+  pub fn capacity(&self) -> u64 {
+    self.capacity
+  }
+
+  /// The memory used by the filter, in bytes.
+  pub fn memory_bytes(&self) -> usize {
+    self.bits.len() * std::mem::size_of::<u64>()
+  }
+
+  /// The probability that the next new state will be dropped, from the
+  /// fraction of bits set.
+  pub fn false_positive_rate(&self) -> f64 {
+    let set_bits: u32 = self.bits.iter().map(|word| word.count_ones()).sum();
+    let fill = set_bits as f64 / (self.bits.len() * 64) as f64;
+    fill.powi(self.num_hashes as i32)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+  use crate::{Onoro16, Onoro16View};
+
+  use super::DedupSink;
+
+  #[test]
+  fn test_no_repeats() {
+    let mut rng = StdRng::seed_from_u64(23);
+    let hashes: Vec<u64> = (0..10_000).map(|_| rng.gen()).collect();
+    let mut sink = DedupSink::new(10_000, 0.01);
+    let kept = hashes
+      .iter()
+      .filter(|&&hash| sink.insert_hash(hash))
+      .count();
+    assert_eq!(kept as u64, sink.len());
+    // Roughly 1% of new hashes may be dropped.
+    assert!(kept > 9_700, "Kept only {kept} of 10000 new hashes");
+    assert!(sink.false_positive_rate() < 0.02);
+
+    // Nothing seen before is ever kept again.
+    assert!(hashes.iter().all(|&hash| !sink.insert_hash(hash)));
+    assert_eq!(sink.len(), kept as u64);
+  }
+
+  #[test]
+  fn test_sizing() {
+    let sink = DedupSink::new(1_000_000, 0.01);
+    // About 9.6 bits per state for a 1% false positive rate.
+    assert!((1_150_000..1_250_000).contains(&sink.memory_bytes()));
+    assert_eq!(sink.capacity(), 1_000_000);
+    assert!(sink.is_empty());
+
+    let sink = DedupSink::with_memory(1 << 20, 1_000_000);
+    assert_eq!(sink.memory_bytes(), 1 << 20);
+  }
+
+  #[test]
+  fn test_symmetric_states() {
+    let onoro = Onoro16::from_board_string(
+      ". W . .
+        B B W .
+         . W B B",
+    )
+    .unwrap();
+    let view = Onoro16View::new(onoro);
+    let mut orbit = view.orbit();
+    assert!(orbit.len() > 1);
+    orbit.shuffle(&mut StdRng::seed_from_u64(23));
+
+    let mut sink = DedupSink::new(100, 0.001);
+    let kept: Vec<_> = sink
+      .filter(orbit.into_iter().map(Onoro16View::new))
+      .collect();
+    assert_eq!(kept.len(), 1);
+    assert!(!sink.insert(&view));
+  }
+}
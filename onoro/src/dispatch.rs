@@ -0,0 +1,222 @@
+//! Checks every fast path against the scalar implementation it replaces.
+//!
+//! Each pair is a `Kernel`: the two implementations of one operation and the
+//! inputs to compare them on for a given game state. Every kernel is run on
+//! the same states, from random playouts and a few boards built by hand, and
+//! must agree on every input. When adding a new fast path, keep its scalar
+//! counterpart around and add a kernel for the pair here.
+
+use std::fmt::Debug;
+
+use abstract_game::Game;
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+  canonicalize::{compute_board_symm_state, BoardSymmetryState},
+  hex_pos::HexPos,
+  onoro_defs::{Onoro16, Onoro16View, Onoro8},
+  packed_idx::PackedIdx,
+  testing::random_states,
+  Onoro, OnoroView, PawnColor, TileState,
+};
+
+/// Two implementations of an operation on game states of type `G`, which take
+/// an `I` and produce an `O`.
+struct Kernel<G, I, O> {
+  name: &'static str,
+  /// The inputs to compare the implementations on for a game state.
+  inputs: fn(&G) -> Vec<I>,
+  fast: fn(&G, &I) -> O,
+  scalar: fn(&G, &I) -> O,
+}
+
+impl<G, I, O> Kernel<G, I, O>
+where
+  G: Debug,
+  I: Debug,
+  O: PartialEq + Debug,
+{
+  fn assert_agree(&self, game: &G) {
+    for input in (self.inputs)(game) {
+      assert_eq!(
+        (self.fast)(game, &input),
+        (self.scalar)(game, &input),
+        "{} mismatch for {input:?} in\n{game:?}",
+        self.name
+      );
+    }
+  }
+
+  fn assert_agree_all(&self, games: &[G]) {
+    for game in games {
+      self.assert_agree(game);
+    }
+  }
+}
+
+/// Every tile of the board, and the null index.
+fn every_idx<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+  _onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+) -> Vec<PackedIdx> {
+  let width = Onoro::<N, N2, ADJ_CNT_SIZE>::board_width() as u32;
+  (0..width)
+    .flat_map(|y| (0..width).map(move |x| PackedIdx::new(x, y)))
+    .chain([PackedIdx::null()])
+    .collect()
+}
+
+fn get_pawn_idx<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+) -> Kernel<Onoro<N, N2, ADJ_CNT_SIZE>, PackedIdx, Option<u32>> {
+  Kernel {
+    name: "get_pawn_idx",
+    inputs: every_idx,
+    fast: |onoro, &idx| onoro.get_pawn_idx_fast(idx),
+    scalar: |onoro, &idx| onoro.get_pawn_idx_slow(idx),
+  }
+}
+
+fn get_tile<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+) -> Kernel<Onoro<N, N2, ADJ_CNT_SIZE>, PackedIdx, TileState> {
+  Kernel {
+    name: "get_tile",
+    inputs: every_idx,
+    fast: |onoro, &idx| onoro.get_tile(idx),
+    scalar: |onoro, &idx| onoro.get_tile_slow(idx),
+  }
+}
+
+/// `check_win` only looks at lines through a pawn of the player who just
+/// moved, so it is compared at each of their pawns. The fast path assumes there
+/// was no four in a row before the last move, so on a board that is already
+/// won, it finds the win from any pawn on a line through the winning run. Won
+/// boards are only compared at the pawns of the winning run.
+fn check_win<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+) -> Kernel<Onoro<N, N2, ADJ_CNT_SIZE>, HexPos, bool> {
+  Kernel {
+    name: "check_win",
+    inputs: |onoro| {
+      let last_color = match onoro.player_color() {
+        PawnColor::Black => PawnColor::White,
+        PawnColor::White => PawnColor::Black,
+      };
+      let won = onoro.finished().is_some();
+      onoro
+        .color_pawns(last_color)
+        .map(|pawn| HexPos::from(pawn.pos))
+        .filter(|&pos| !won || onoro.check_win_slow(pos))
+        .collect()
+    },
+    fast: |onoro, &pos| onoro.check_win_fast(pos),
+    scalar: |onoro, &pos| onoro.check_win_slow(pos),
+  }
+}
+
+/// The canonical hash of each child of a view, as found by `make_move`, which
+/// may derive it from the parent's, against hashing the child from scratch.
+fn hash<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+) -> Kernel<OnoroView<N, N2, ADJ_CNT_SIZE>, crate::Move, u64> {
+  Kernel {
+    name: "hash",
+    inputs: |view| view.each_move().collect(),
+    fast: |view, &m| {
+      // Hashing the parent lets the child's hash be found incrementally.
+      view.canonical_hash();
+      view.with_move(m).canonical_hash()
+    },
+    scalar: |view, &m| {
+      let mut child = view.onoro().clone();
+      child.make_move(m);
+      OnoroView::new(child).canonical_hash()
+    },
+  }
+}
+
+/// The symmetry state of each child, as updated by `make_move`, against
+/// computing it from the child's center of mass.
+fn symm_state<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+) -> Kernel<Onoro<N, N2, ADJ_CNT_SIZE>, crate::Move, BoardSymmetryState> {
+  Kernel {
+    name: "symm_state",
+    inputs: |onoro| onoro.each_move().collect(),
+    fast: |onoro, &m| {
+      let mut child = onoro.clone();
+      child.make_move(m);
+      child.symm_state()
+    },
+    scalar: |onoro, &m| {
+      let mut child = onoro.clone();
+      child.make_move(m);
+      compute_board_symm_state(&child)
+    },
+  }
+}
+
+fn states16() -> Vec<Onoro16> {
+  let boards = [
+    // A win for black along each of the three axes.
+    ". . . . . .
+      . B B B B .
+       . W W W . .
+        . . . . . .",
+    ". . . . .
+      . B W . .
+       . B W . .
+        . B W . .
+         . B . . .",
+    ". . . . . .
+      . B W . . .
+       . . B W . .
+        . . . B W .
+         . . . . B .",
+    // Phase 2, with the pawns at the edge of the packed pawn list.
+    ". . . . .
+      . B W W B
+       . W B B W
+        . B W W B
+         . W B B W",
+  ];
+  boards
+    .into_iter()
+    .map(|board| Onoro16::from_board_string(board).unwrap())
+    .chain(random_states(20, 60, &mut StdRng::seed_from_u64(1234)))
+    .collect()
+}
+
+fn views16() -> Vec<Onoro16View> {
+  states16().into_iter().map(OnoroView::new).collect()
+}
+
+fn states8() -> Vec<Onoro8> {
+  random_states(20, 60, &mut StdRng::seed_from_u64(1234))
+}
+
+#[test]
+fn test_get_pawn_idx() {
+  get_pawn_idx().assert_agree_all(&states16());
+  get_pawn_idx().assert_agree_all(&states8());
+}
+
+#[test]
+fn test_get_tile() {
+  get_tile().assert_agree_all(&states16());
+  get_tile().assert_agree_all(&states8());
+}
+
+#[test]
+fn test_check_win() {
+  let states = states16();
+  assert!(states.iter().any(|onoro| onoro.finished().is_some()));
+  check_win().assert_agree_all(&states);
+  check_win().assert_agree_all(&states8());
+}
+
+#[test]
+fn test_symm_state() {
+  symm_state().assert_agree_all(&states16());
+  symm_state().assert_agree_all(&states8());
+}
+
+#[test]
+fn test_hash() {
+  hash().assert_agree_all(&views16());
+}
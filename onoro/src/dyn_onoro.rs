@@ -0,0 +1,323 @@
+use std::{
+  any::Any,
+  fmt::{Debug, Display},
+  str::FromStr,
+};
+
+use abstract_game::Compress;
+
+use crate::{
+  error::{OnoroError, OnoroResult},
+  make_onoro_error,
+  render::{self, RenderOptions},
+  Move, Onoro, Onoro16, Onoro8, Pawn, PawnColor, Phase, RuleViolation, Undo, Variant,
+};
+
+/// The methods of `Onoro` that don't depend on its board size, as an
+/// object-safe trait. `Box<dyn DynOnoro>` holds a game of either size, so the
+/// size can be chosen at runtime (see `BoardSize`). Search needs the concrete
+/// type, which `as_any` recovers.
+pub trait DynOnoro: Debug + Display + Send + Sync {
+  fn pawns_per_player(&self) -> u32;
+
+  /// See `Onoro::board_width`.
+  fn board_width(&self) -> usize;
+
+  fn variant(&self) -> Variant;
+
+  fn player_color(&self) -> PawnColor;
+
+  fn finished(&self) -> Option<PawnColor>;
+
+  /// See `Onoro::winner`.
+  fn winner(&self) -> Option<PawnColor>;
+
+  fn in_phase1(&self) -> bool;
+
+  fn phase(&self) -> Phase;
+
+  fn pawns_in_play(&self) -> u32;
+
+  fn pawns(&self) -> Box<dyn Iterator<Item = Pawn> + '_>;
+
+  fn each_move(&self) -> Box<dyn Iterator<Item = Move> + '_>;
+
+  fn make_move(&mut self, m: Move);
+
+  fn try_make_move(&mut self, m: Move) -> OnoroResult<()>;
+
+  fn make_move_with_undo(&mut self, m: Move) -> Undo;
+
+  fn undo_move(&mut self, undo: Undo);
+
+  fn parse_move(&self, notation: &str) -> OnoroResult<Move>;
+
+  /// `m` in the notation of `Move::to_notation`.
+  fn move_notation(&self, m: Move) -> String;
+
+  fn explain_illegal(&self, m: Move) -> Vec<RuleViolation>;
+
+  fn evaluate(&self) -> i32;
+
+  fn perft(&self, depth: u32) -> u64;
+
+  /// This game in the format of `Compress`, which is `BoardSize::decompress`'s
+  /// input.
+  fn compress(&self) -> Vec<u8>;
+
+  /// See `render::svg`.
+  fn svg(&self, options: &RenderOptions) -> String;
+
+  fn clone_box(&self) -> Box<dyn DynOnoro>;
+
+  /// The concrete `Onoro` behind this trait object, for downcasting.
+  fn as_any(&self) -> &dyn Any;
+}
+
+impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> DynOnoro
+  for Onoro<N, N2, ADJ_CNT_SIZE>
+{
+  fn pawns_per_player(&self) -> u32 {
+    N as u32 / 2
+  }
+
+  fn board_width(&self) -> usize {
+    Self::board_width()
+  }
+
+  fn variant(&self) -> Variant {
+    self.variant()
+  }
+
+  fn player_color(&self) -> PawnColor {
+    self.player_color()
+  }
+
+  fn finished(&self) -> Option<PawnColor> {
+    self.finished()
+  }
+
+  fn winner(&self) -> Option<PawnColor> {
+    self.winner()
+  }
+
+  fn in_phase1(&self) -> bool {
+    self.in_phase1()
+  }
+
+  fn phase(&self) -> Phase {
+    self.phase()
+  }
+
+  fn pawns_in_play(&self) -> u32 {
+    self.pawns_in_play()
+  }
+
+  fn pawns(&self) -> Box<dyn Iterator<Item = Pawn> + '_> {
+    Box::new(self.pawns())
+  }
+
+  fn each_move(&self) -> Box<dyn Iterator<Item = Move> + '_> {
+    Box::new(self.each_move())
+  }
+
+  fn make_move(&mut self, m: Move) {
+    self.make_move(m)
+  }
+
+  fn try_make_move(&mut self, m: Move) -> OnoroResult<()> {
+    self.try_make_move(m)
+  }
+
+  fn make_move_with_undo(&mut self, m: Move) -> Undo {
+    self.make_move_with_undo(m)
+  }
+
+  fn undo_move(&mut self, undo: Undo) {
+    self.undo_move(undo)
+  }
+
+  fn parse_move(&self, notation: &str) -> OnoroResult<Move> {
+    self.parse_move(notation)
+  }
+
+  fn move_notation(&self, m: Move) -> String {
+    m.to_notation(self)
+  }
+
+  fn explain_illegal(&self, m: Move) -> Vec<RuleViolation> {
+    self.explain_illegal(m)
+  }
+
+  fn evaluate(&self) -> i32 {
+    self.evaluate()
+  }
+
+  fn perft(&self, depth: u32) -> u64 {
+    self.perft(depth)
+  }
+
+  fn compress(&self) -> Vec<u8> {
+    let mut bytes = vec![0; Self::COMPRESSED_SIZE];
+    Compress::compress(self, &mut bytes);
+    bytes
+  }
+
+  fn svg(&self, options: &RenderOptions) -> String {
+    render::svg(self, options)
+  }
+
+  fn clone_box(&self) -> Box<dyn DynOnoro> {
+    Box::new(self.clone())
+  }
+
+  fn as_any(&self) -> &dyn Any {
+    self
+  }
+}
+
+impl Clone for Box<dyn DynOnoro> {
+  fn clone(&self) -> Self {
+    self.clone_box()
+  }
+}
+
+impl dyn DynOnoro + '_ {
+  /// The concrete game behind this trait object, if it is a `T`.
+  pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+    self.as_any().downcast_ref()
+  }
+}
+
+/// The sizes `Onoro` can be played at, named by their type.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BoardSize {
+  /// 4 pawns per player.
+  Onoro8,
+  /// 8 pawns per player, the standard game.
+  #[default]
+  Onoro16,
+}
+
+impl BoardSize {
+  pub const fn pawns_per_player(&self) -> u32 {
+    match self {
+      BoardSize::Onoro8 => 4,
+      BoardSize::Onoro16 => 8,
+    }
+  }
+
+  /// See `Onoro::default_start`.
+  pub fn default_start(&self) -> Box<dyn DynOnoro> {
+    match self {
+      BoardSize::Onoro8 => Box::new(Onoro8::default_start()),
+      BoardSize::Onoro16 => Box::new(Onoro16::default_start()),
+    }
+  }
+
+  /// See `Onoro::from_board_string`.
+  pub fn from_board_string(&self, board_layout: &str) -> OnoroResult<Box<dyn DynOnoro>> {
+    let game: Result<Box<dyn DynOnoro>, _> = match self {
+      BoardSize::Onoro8 => Onoro8::from_board_string(board_layout).map(|game| Box::new(game) as _),
+      BoardSize::Onoro16 => {
+        Onoro16::from_board_string(board_layout).map(|game| Box::new(game) as _)
+      }
+    };
+    game.map_err(|err| make_onoro_error!("{err}"))
+  }
+
+  /// The inverse of `DynOnoro::compress`, or `None` if `bytes` isn't a valid
+  /// game of this size.
+  pub fn decompress(&self, bytes: &[u8]) -> Option<Box<dyn DynOnoro>> {
+    match self {
+      BoardSize::Onoro8 => Some(Box::new(Onoro8::decompress(bytes)?)),
+      BoardSize::Onoro16 => Some(Box::new(Onoro16::decompress(bytes)?)),
+    }
+  }
+}
+
+impl Display for BoardSize {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.pawns_per_player())
+  }
+}
+
+impl FromStr for BoardSize {
+  type Err = OnoroError;
+
+  /// Accepts the number of pawns per player, "4" or "8".
+  fn from_str(s: &str) -> OnoroResult<Self> {
+    match s.trim() {
+      "4" => Ok(BoardSize::Onoro8),
+      "8" => Ok(BoardSize::Onoro16),
+      _ => Err(make_onoro_error!(
+        "Invalid board size \"{s}\", expected \"4\" or \"8\" pawns per player"
+      )),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::{rngs::StdRng, SeedableRng};
+
+  use crate::{testing::random_playout, Move, Onoro16, Onoro8};
+
+  use super::BoardSize;
+
+  #[test]
+  fn test_board_sizes() {
+    for size in [BoardSize::Onoro8, BoardSize::Onoro16] {
+      let game = size.default_start();
+      assert_eq!(game.pawns_per_player(), size.pawns_per_player());
+      assert_eq!(size.to_string().parse::<BoardSize>().unwrap(), size);
+      assert_eq!(game.pawns().count(), 3);
+      assert_eq!(game.each_move().count(), 3);
+    }
+    assert!("16".parse::<BoardSize>().is_err());
+
+    let game = BoardSize::Onoro8.default_start();
+    assert!(game.downcast_ref::<Onoro8>().is_some());
+    assert!(game.downcast_ref::<Onoro16>().is_none());
+  }
+
+  /// Replays `moves` through the facade of a game of `size`.
+  fn replay(size: BoardSize, moves: Vec<Move>) {
+    let mut game = size.default_start();
+    for m in moves {
+      assert!(game.each_move().any(|legal| legal == m));
+      assert!(game.explain_illegal(m).is_empty());
+      let notation = game.move_notation(m);
+      assert_eq!(game.parse_move(&notation).unwrap(), m);
+      game.make_move(m);
+      assert!(game.pawns_in_play() <= 2 * size.pawns_per_player());
+
+      let decompressed = size.decompress(&game.compress()).unwrap();
+      assert_eq!(decompressed.to_string(), game.to_string());
+    }
+  }
+
+  #[test]
+  fn test_playouts() {
+    let mut rng = StdRng::seed_from_u64(71);
+    for _ in 0..20 {
+      let (_, moves) = random_playout(Onoro8::default_start(), 200, &mut rng);
+      replay(BoardSize::Onoro8, moves);
+      let (_, moves) = random_playout(Onoro16::default_start(), 200, &mut rng);
+      replay(BoardSize::Onoro16, moves);
+    }
+  }
+
+  #[test]
+  fn test_undo() {
+    let mut game = BoardSize::Onoro8
+      .from_board_string(". B W\n W . B")
+      .unwrap();
+    let before = game.clone();
+    let m = game.each_move().next().unwrap();
+    let undo = game.make_move_with_undo(m);
+    assert_ne!(game.to_string(), before.to_string());
+    game.undo_move(undo);
+    assert_eq!(game.to_string(), before.to_string());
+  }
+}
@@ -0,0 +1,300 @@
+use std::{
+  collections::HashSet,
+  fs::File,
+  io::{self, BufReader, BufWriter, Read, Write},
+  path::{Path, PathBuf},
+};
+
+use abstract_game::Compress;
+
+use crate::{DedupSink, Onoro, OnoroView};
+
+/// How `enumerate_positions` walks the game.
+#[derive(Clone, Debug)]
+pub struct EnumerateOptions {
+  /// The number of moves from the start to enumerate positions up to.
+  pub max_ply: u32,
+  /// The most positions of a ply to hold in memory. The rest are spilled to a
+  /// file in `spill_dir` until the ply is expanded.
+  pub frontier_memory_limit: usize,
+  pub spill_dir: PathBuf,
+  /// If set, positions are deduplicated with a `DedupSink` of this many bytes,
+  /// which bounds the memory of the walk but may drop a few positions from
+  /// the counts. Otherwise the canonical hash of every position is kept, and
+  /// the counts are exact.
+  pub approximate_dedup_bytes: Option<usize>,
+}
+
+impl Default for EnumerateOptions {
+  fn default() -> Self {
+    Self {
+      max_ply: 6,
+      frontier_memory_limit: 1 << 20,
+      spill_dir: std::env::temp_dir(),
+      approximate_dedup_bytes: None,
+    }
+  }
+}
+
+/// The positions first reached at one ply of an enumeration.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PlyCount {
+  /// The number of moves from the start.
+  pub ply: u32,
+  /// The number of distinct positions, up to symmetry, first reachable in
+  /// `ply` moves.
+  pub positions: u64,
+  /// How many of `positions` are won games.
+  pub finished: u64,
+  /// How many of `positions` were spilled to disk.
+  pub spilled: u64,
+}
+
+/// The canonical hashes of every position seen so far.
+enum Seen {
+  Exact(HashSet<u64>),
+  Approximate(DedupSink),
+}
+
+impl Seen {
+  fn insert<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+    &mut self,
+    view: &OnoroView<N, N2, ADJ_CNT_SIZE>,
+  ) -> bool {
+    match self {
+      Seen::Exact(hashes) => hashes.insert(view.canonical_hash()),
+      Seen::Approximate(sink) => sink.insert(view),
+    }
+  }
+}
+
+/// A queue of positions which holds up to `memory_limit` of them in memory,
+/// and writes the rest to a file, compressed.
+struct Frontier<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> {
+  memory: Vec<Onoro<N, N2, ADJ_CNT_SIZE>>,
+  memory_limit: usize,
+  path: PathBuf,
+  spill: Option<BufWriter<File>>,
+  spilled: u64,
+}
+
+impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Frontier<N, N2, ADJ_CNT_SIZE> {
+  fn new(memory_limit: usize, spill_dir: &Path, ply: u32) -> Self {
+    Self {
+      memory: Vec::new(),
+      memory_limit,
+      path: spill_dir.join(format!(
+        "onoro-frontier-{}-{N}-{ply}.bin",
+        std::process::id()
+      )),
+      spill: None,
+      spilled: 0,
+    }
+  }
+
+  fn len(&self) -> u64 {
+    self.memory.len() as u64 + self.spilled
+  }
+
+  fn push(&mut self, onoro: Onoro<N, N2, ADJ_CNT_SIZE>) -> io::Result<()> {
+    if self.memory.len() < self.memory_limit {
+      self.memory.push(onoro);
+      return Ok(());
+    }
+
+    let spill = match &mut self.spill {
+      Some(spill) => spill,
+      None => self.spill.insert(BufWriter::new(File::create(&self.path)?)),
+    };
+    let mut bytes = [0; 32];
+    let bytes = &mut bytes[..Onoro::<N, N2, ADJ_CNT_SIZE>::COMPRESSED_SIZE];
+    onoro.compress(bytes);
+    spill.write_all(bytes)?;
+    self.spilled += 1;
+    Ok(())
+  }
+
+  /// Calls `f` on every position in the queue, emptying it.
+  fn drain(
+    &mut self,
+    mut f: impl FnMut(Onoro<N, N2, ADJ_CNT_SIZE>) -> io::Result<()>,
+  ) -> io::Result<()> {
+    for onoro in std::mem::take(&mut self.memory) {
+      f(onoro)?;
+    }
+
+    let Some(spill) = self.spill.take() else {
+      return Ok(());
+    };
+    drop(spill.into_inner().map_err(|err| err.into_error())?);
+    let mut reader = BufReader::new(File::open(&self.path)?);
+    let mut bytes = [0; 32];
+    let bytes = &mut bytes[..Onoro::<N, N2, ADJ_CNT_SIZE>::COMPRESSED_SIZE];
+    for _ in 0..self.spilled {
+      reader.read_exact(bytes)?;
+      let onoro = Onoro::decompress(bytes)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Corrupt spilled position"))?;
+      f(onoro)?;
+    }
+    self.spilled = 0;
+    std::fs::remove_file(&self.path)
+  }
+}
+
+impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Drop
+  for Frontier<N, N2, ADJ_CNT_SIZE>
+{
+  fn drop(&mut self) {
+    if self.spill.take().is_some() {
+      let _ = std::fs::remove_file(&self.path);
+    }
+  }
+}
+
+/// Walks every position reachable from `start` in up to `options.max_ply`
+/// moves, breadth first, counting each position up to symmetry once, at the
+/// first ply it can be reached at. Won games are counted but not expanded.
+/// Calls `report` with the counts of each ply as soon as it's finished, and
+/// returns them all, starting with ply 0 for `start` itself.
+pub fn enumerate_positions<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+  start: &Onoro<N, N2, ADJ_CNT_SIZE>,
+  options: &EnumerateOptions,
+  mut report: impl FnMut(&PlyCount),
+) -> io::Result<Vec<PlyCount>> {
+  let mut seen = match options.approximate_dedup_bytes {
+    Some(bytes) => {
+      // Sized for a 1% false positive rate.
+      Seen::Approximate(DedupSink::with_memory(bytes, bytes as u64 * 8 / 10))
+    }
+    None => Seen::Exact(HashSet::new()),
+  };
+  seen.insert(&OnoroView::new(start.clone()));
+
+  let mut frontier = Frontier::new(options.frontier_memory_limit, &options.spill_dir, 0);
+  frontier.push(start.clone())?;
+  let start_count = PlyCount {
+    ply: 0,
+    positions: 1,
+    finished: start.finished().is_some() as u64,
+    spilled: 0,
+  };
+  report(&start_count);
+  let mut counts = vec![start_count];
+
+  for ply in 1..=options.max_ply {
+    let mut next = Frontier::new(options.frontier_memory_limit, &options.spill_dir, ply);
+    let mut finished = 0;
+    frontier.drain(|onoro| {
+      if onoro.finished().is_some() {
+        return Ok(());
+      }
+      for m in onoro.each_move() {
+        let mut child = onoro.clone();
+        child.make_move(m);
+        if !seen.insert(&OnoroView::new(child.clone())) {
+          continue;
+        }
+        finished += child.finished().is_some() as u64;
+        next.push(child)?;
+      }
+      Ok(())
+    })?;
+
+    let count = PlyCount {
+      ply,
+      positions: next.len(),
+      finished,
+      spilled: next.spilled,
+    };
+    report(&count);
+    counts.push(count);
+    frontier = next;
+  }
+  Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashSet;
+
+  use crate::{Onoro16, Onoro16View, Onoro8};
+
+  use super::{enumerate_positions, EnumerateOptions};
+
+  fn options(max_ply: u32) -> EnumerateOptions {
+    EnumerateOptions {
+      max_ply,
+      ..EnumerateOptions::default()
+    }
+  }
+
+  #[test]
+  fn test_phase1_counts() {
+    // Phase 1 positions have one more pawn each ply, so they can't be reached
+    // at two plies, and each ply's positions are the distinct canonical
+    // hashes of all lines of that length.
+    let start = Onoro16::default_start();
+    let counts = enumerate_positions(&start, &options(3), |_| {}).unwrap();
+    let mut frontier = vec![start];
+    for count in &counts[1..] {
+      frontier = frontier
+        .iter()
+        .flat_map(|onoro| {
+          onoro.each_move().map(|m| {
+            let mut child = onoro.clone();
+            child.make_move(m);
+            child
+          })
+        })
+        .collect();
+      let distinct: HashSet<_> = frontier
+        .iter()
+        .map(|onoro| Onoro16View::new(onoro.clone()).canonical_hash())
+        .collect();
+      assert_eq!(count.positions, distinct.len() as u64, "ply {}", count.ply);
+    }
+    assert_eq!(counts[0].positions, 1);
+    // Two of the three placements from the default start are mirror images.
+    assert_eq!(counts[1].positions, 2);
+  }
+
+  #[test]
+  fn test_spilling() {
+    let start = Onoro8::default_start();
+    let in_memory = enumerate_positions(&start, &options(7), |_| {}).unwrap();
+    let mut reported = Vec::new();
+    let spilled = enumerate_positions(
+      &start,
+      &EnumerateOptions {
+        frontier_memory_limit: 2,
+        ..options(7)
+      },
+      |count| reported.push(*count),
+    )
+    .unwrap();
+    assert_eq!(reported, spilled);
+    assert!(spilled.iter().any(|count| count.spilled > 0));
+    for (a, b) in in_memory.iter().zip(&spilled) {
+      assert_eq!((a.positions, a.finished), (b.positions, b.finished));
+    }
+  }
+
+  #[test]
+  fn test_approximate() {
+    let start = Onoro16::default_start();
+    let exact = enumerate_positions(&start, &options(4), |_| {}).unwrap();
+    let approximate = enumerate_positions(
+      &start,
+      &EnumerateOptions {
+        approximate_dedup_bytes: Some(1 << 16),
+        ..options(4)
+      },
+      |_| {},
+    )
+    .unwrap();
+    for (exact, approximate) in exact.iter().zip(&approximate) {
+      assert!(approximate.positions <= exact.positions);
+      assert!(approximate.positions * 100 >= exact.positions * 95);
+    }
+  }
+}
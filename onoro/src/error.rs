@@ -1,16 +1,41 @@
 use std::{error::Error, fmt::Display};
 
+use crate::RuleViolation;
+
 #[derive(Debug)]
 pub struct OnoroError {
   message: String,
+  /// The rules broken by the move that caused this error, if it was caused by
+  /// an illegal move.
+  violations: Vec<RuleViolation>,
 }
 
 impl OnoroError {
   pub(crate) fn new(message: &str) -> Self {
     OnoroError {
       message: message.to_owned(),
+      violations: Vec::new(),
+    }
+  }
+
+  pub(crate) fn illegal_move(message: &str, violations: Vec<RuleViolation>) -> Self {
+    OnoroError {
+      message: message.to_owned(),
+      violations,
     }
   }
+
+  /// The description of the error, without the "Error: " prefix of its
+  /// `Display` implementation.
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+
+  /// Every rule broken by the move that caused this error, which is empty if
+  /// the error wasn't caused by an illegal move.
+  pub fn violations(&self) -> &[RuleViolation] {
+    &self.violations
+  }
 }
 
 impl Error for OnoroError {}
@@ -24,7 +49,7 @@ impl Display for OnoroError {
 #[macro_export]
 macro_rules! make_onoro_error {
   ($($args:expr),+) => {
-    $crate::onoro::OnoroError::new(&format!($($args),+))
+    $crate::error::OnoroError::new(&format!($($args),+))
   };
 }
 
@@ -0,0 +1,393 @@
+use std::{fmt::Display, str::FromStr};
+
+use abstract_game::Compress;
+
+use crate::{
+  error::{OnoroError, OnoroResult},
+  make_onoro_error, Move, Onoro16, PawnColor,
+};
+
+/// The outcome of a recorded game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordResult {
+  BlackWins,
+  WhiteWins,
+  Draw,
+  /// The game hadn't finished when it was recorded.
+  Unfinished,
+}
+
+impl RecordResult {
+  const ALL: [RecordResult; 4] = [
+    RecordResult::BlackWins,
+    RecordResult::WhiteWins,
+    RecordResult::Draw,
+    RecordResult::Unfinished,
+  ];
+}
+
+impl Display for RecordResult {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      RecordResult::BlackWins => write!(f, "1-0"),
+      RecordResult::WhiteWins => write!(f, "0-1"),
+      RecordResult::Draw => write!(f, "1/2-1/2"),
+      RecordResult::Unfinished => write!(f, "*"),
+    }
+  }
+}
+
+impl FromStr for RecordResult {
+  type Err = OnoroError;
+
+  fn from_str(s: &str) -> OnoroResult<Self> {
+    Self::ALL
+      .into_iter()
+      .find(|result| result.to_string() == s)
+      .ok_or_else(|| make_onoro_error!("Invalid game result \"{s}\""))
+  }
+}
+
+/// A full game of Onoro, in a text format modeled after PGN for chess:
+///
+/// ```text
+/// [Black "alice"]
+/// [White "bob"]
+/// [Result "1-0"]
+///
+/// 1. w@h9 b@h7 2. w@j9 b@g8 ... 1-0
+/// ```
+///
+/// A record starts with tags of the form `[Name "value"]`, followed by the
+/// moves in the notation of `Move::to_notation`, numbered every two moves,
+/// and ends with the result. Games that don't start from the default start
+/// have a `Start` tag holding the hex of the compressed starting position.
+/// Any number of records can be written one after another, e.g. to store
+/// every game of a match in one file.
+#[derive(Clone, Debug)]
+pub struct GameRecord {
+  /// Tags describing the game, like the names of the players, in the order
+  /// they are written. `Result` and `Start` are derived from the other fields,
+  /// so they aren't included here.
+  pub tags: Vec<(String, String)>,
+  pub start: Onoro16,
+  pub moves: Vec<Move>,
+  pub result: RecordResult,
+}
+
+impl GameRecord {
+  /// A record of an unfinished game from `start` with no moves or tags.
+  pub fn new(start: Onoro16) -> Self {
+    Self {
+      tags: Vec::new(),
+      start,
+      moves: Vec::new(),
+      result: RecordResult::Unfinished,
+    }
+  }
+
+  /// The value of tag `name`, if the record has it.
+  pub fn tag(&self, name: &str) -> Option<&str> {
+    self
+      .tags
+      .iter()
+      .find(|(tag, _)| tag == name)
+      .map(|(_, value)| value.as_str())
+  }
+
+  /// Sets tag `name` to `value`, replacing its value if the record already
+  /// has it.
+  pub fn set_tag(&mut self, name: impl Into<String>, value: impl Into<String>) {
+    let (name, value) = (name.into(), value.into());
+    match self.tags.iter_mut().find(|(tag, _)| *tag == name) {
+      Some((_, old_value)) => *old_value = value,
+      None => self.tags.push((name, value)),
+    }
+  }
+
+  /// The position after every move of the game, checking that each move is
+  /// legal.
+  pub fn final_position(&self) -> OnoroResult<Onoro16> {
+    let mut onoro = self.start.clone();
+    for (idx, &m) in self.moves.iter().enumerate() {
+      if let Some(violation) = onoro.explain_illegal(m).first() {
+        return Err(make_onoro_error!(
+          "Move {} ({}) is illegal: {violation}",
+          idx + 1,
+          m.to_notation(&onoro)
+        ));
+      }
+      onoro.make_move(m);
+    }
+    Ok(onoro)
+  }
+
+  /// Parses every record in `text`, which may hold any number of records one
+  /// after another.
+  pub fn parse_all(text: &str) -> OnoroResult<Vec<Self>> {
+    let mut records = Vec::new();
+    let mut tags = Vec::new();
+    let mut movetext = Vec::new();
+    for line in text.lines().map(str::trim) {
+      if line.starts_with('[') {
+        if !movetext.is_empty() {
+          return Err(make_onoro_error!(
+            "Tag \"{line}\" found in the middle of the moves of a game"
+          ));
+        }
+        tags.push(parse_tag(line)?);
+        continue;
+      }
+
+      for token in line.split_whitespace() {
+        if let Ok(result) = token.parse::<RecordResult>() {
+          records.push(Self::from_parts(
+            std::mem::take(&mut tags),
+            &movetext,
+            result,
+          )?);
+          movetext.clear();
+        } else {
+          movetext.push(token);
+        }
+      }
+    }
+
+    if !tags.is_empty() || !movetext.is_empty() {
+      return Err(make_onoro_error!("Game record is missing its result"));
+    }
+    Ok(records)
+  }
+
+  fn from_parts(
+    mut tags: Vec<(String, String)>,
+    movetext: &[&str],
+    result: RecordResult,
+  ) -> OnoroResult<Self> {
+    let mut start = Onoro16::default_start();
+    if let Some(idx) = tags.iter().position(|(name, _)| name == "Start") {
+      let (_, hex) = tags.remove(idx);
+      start = from_hex(&hex)
+        .and_then(|bytes| Onoro16::decompress(&bytes))
+        .ok_or_else(|| make_onoro_error!("Invalid starting position \"{hex}\""))?;
+    }
+    if let Some(idx) = tags.iter().position(|(name, _)| name == "Result") {
+      let (_, tag_result) = tags.remove(idx);
+      if tag_result != result.to_string() {
+        return Err(make_onoro_error!(
+          "Result tag \"{tag_result}\" doesn't match the result {result} after the moves"
+        ));
+      }
+    }
+
+    let mut onoro = start.clone();
+    let mut moves = Vec::new();
+    for &token in movetext {
+      // Skip move numbers.
+      if token
+        .strip_suffix('.')
+        .is_some_and(|number| number.parse::<u32>().is_ok())
+      {
+        continue;
+      }
+
+      let m = onoro.parse_move(token)?;
+      if let Some(violation) = onoro.explain_illegal(m).first() {
+        return Err(make_onoro_error!("Illegal move {token}: {violation}"));
+      }
+      onoro.make_move(m);
+      moves.push(m);
+    }
+
+    if let Some(winner) = onoro.winner() {
+      let expected_result = match winner {
+        PawnColor::Black => RecordResult::BlackWins,
+        PawnColor::White => RecordResult::WhiteWins,
+      };
+      if expected_result != result {
+        return Err(make_onoro_error!(
+          "Game ends with {winner:?} winning, but the result is {result}"
+        ));
+      }
+    }
+
+    Ok(Self {
+      tags,
+      start,
+      moves,
+      result,
+    })
+  }
+}
+
+fn parse_tag(line: &str) -> OnoroResult<(String, String)> {
+  let (name, value) = line
+    .strip_prefix('[')
+    .and_then(|line| line.strip_suffix(']'))
+    .and_then(|line| line.split_once(' '))
+    .ok_or_else(|| make_onoro_error!("Expected a tag like [Name \"value\"], found \"{line}\""))?;
+  let value = value
+    .trim()
+    .strip_prefix('"')
+    .and_then(|value| value.strip_suffix('"'))
+    .ok_or_else(|| make_onoro_error!("Tag value in \"{line}\" must be quoted"))?;
+
+  let mut unescaped = String::new();
+  let mut chars = value.chars();
+  while let Some(c) = chars.next() {
+    unescaped.push(match c {
+      '\\' => chars
+        .next()
+        .ok_or_else(|| make_onoro_error!("Unterminated escape in \"{line}\""))?,
+      c => c,
+    });
+  }
+  Ok((name.to_owned(), unescaped))
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub(crate) fn from_hex(hex: &str) -> Option<Vec<u8>> {
+  if !hex.is_ascii() || hex.len() & 1 != 0 {
+    return None;
+  }
+  (0..hex.len())
+    .step_by(2)
+    .map(|idx| u8::from_str_radix(&hex[idx..idx + 2], 16).ok())
+    .collect()
+}
+
+impl Display for GameRecord {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let write_tag = |f: &mut std::fmt::Formatter<'_>, name: &str, value: &str| {
+      let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+      writeln!(f, "[{name} \"{value}\"]")
+    };
+    for (name, value) in &self.tags {
+      write_tag(f, name, value)?;
+    }
+
+    let mut start_bytes = [0; Onoro16::COMPRESSED_SIZE];
+    let mut default_bytes = [0; Onoro16::COMPRESSED_SIZE];
+    self.start.compress(&mut start_bytes);
+    Onoro16::default_start().compress(&mut default_bytes);
+    if start_bytes != default_bytes {
+      write_tag(f, "Start", &to_hex(&start_bytes))?;
+    }
+    write_tag(f, "Result", &self.result.to_string())?;
+    writeln!(f)?;
+
+    let mut onoro = self.start.clone();
+    for (idx, &m) in self.moves.iter().enumerate() {
+      if idx & 1 == 0 {
+        write!(f, "{}. ", idx / 2 + 1)?;
+      }
+      write!(f, "{} ", m.to_notation(&onoro))?;
+      onoro.make_move(m);
+    }
+    writeln!(f, "{}", self.result)
+  }
+}
+
+impl FromStr for GameRecord {
+  type Err = OnoroError;
+
+  /// Parses a single game record.
+  fn from_str(s: &str) -> OnoroResult<Self> {
+    let mut records = Self::parse_all(s)?;
+    match records.len() {
+      1 => Ok(records.pop().unwrap()),
+      n => Err(make_onoro_error!("Expected one game record, found {n}")),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::{rngs::StdRng, SeedableRng};
+
+  use crate::{testing::random_playout, Onoro16, PawnColor};
+
+  use super::{GameRecord, RecordResult};
+
+  /// The record of a random game from `start`.
+  fn random_game(start: Onoro16, max_moves: usize, rng: &mut StdRng) -> GameRecord {
+    let (states, moves) = random_playout(start.clone(), max_moves, rng);
+    let mut record = GameRecord::new(start);
+    record.moves = moves;
+    record.result = match states.last().unwrap().winner() {
+      Some(PawnColor::Black) => RecordResult::BlackWins,
+      Some(PawnColor::White) => RecordResult::WhiteWins,
+      None => RecordResult::Unfinished,
+    };
+    record
+  }
+
+  #[test]
+  fn test_round_trip() {
+    let mut rng = StdRng::seed_from_u64(1234);
+    let mut records = Vec::new();
+    for idx in 0..10 {
+      // Every other game starts from a random position, which is written
+      // with a Start tag.
+      let start = if idx & 1 == 0 {
+        Onoro16::default_start()
+      } else {
+        random_game(Onoro16::default_start(), 6, &mut rng)
+          .final_position()
+          .unwrap()
+      };
+      let mut record = random_game(start, 40, &mut rng);
+      record.set_tag("Game", idx.to_string());
+      record.set_tag("Black", "quote \" and backslash \\");
+      assert_eq!(record.to_string().contains("[Start "), idx & 1 != 0);
+      records.push(record);
+    }
+
+    let text: String = records
+      .iter()
+      .map(|record| record.to_string() + "\n")
+      .collect();
+    let parsed = GameRecord::parse_all(&text).unwrap();
+    assert_eq!(parsed.len(), records.len());
+    for (record, parsed) in records.iter().zip(parsed) {
+      assert_eq!(parsed.tags, record.tags);
+      assert_eq!(parsed.start.to_string(), record.start.to_string());
+      assert_eq!(parsed.moves, record.moves);
+      assert_eq!(parsed.result, record.result);
+      assert_eq!(
+        parsed.final_position().unwrap().to_string(),
+        record.final_position().unwrap().to_string()
+      );
+    }
+  }
+
+  #[test]
+  fn test_parse() {
+    let record: GameRecord = "[White \"bob\"]\n\n1. w@h9 b@h7 *".parse().unwrap();
+    assert_eq!(record.tag("White"), Some("bob"));
+    assert_eq!(record.tag("Black"), None);
+    assert_eq!(record.moves.len(), 2);
+    assert_eq!(record.result, RecordResult::Unfinished);
+
+    for text in [
+      // No result.
+      "1. w@h9",
+      // The result tag doesn't match the result.
+      "[Result \"1-0\"]\n1. w@h9 *",
+      // Black placing a pawn on white's turn.
+      "1. b@h9 *",
+      // Not adjacent to any pawns.
+      "1. w@a1 *",
+      // Two records.
+      "1. w@h9 * 1. w@h9 *",
+    ] {
+      assert!(
+        text.parse::<GameRecord>().is_err(),
+        "{text} should not parse"
+      );
+    }
+  }
+}
@@ -1,9 +1,13 @@
+use std::fmt::Display;
+
 use algebra::{
   direct_product_type,
   group::{Cyclic, Dihedral},
 };
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Where the center of mass of a board lies relative to the hex grid, which
+/// decides the symmetries the board can have.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum SymmetryClass {
   /// Center of mass lies in the center of a hexagonal tile.
   C,
@@ -23,6 +27,39 @@ pub enum SymmetryClass {
   Trivial,
 }
 
+impl SymmetryClass {
+  /// Every symmetry class, from most to least symmetric.
+  pub const ALL: [Self; 7] = [
+    Self::C,
+    Self::V,
+    Self::E,
+    Self::CV,
+    Self::CE,
+    Self::EV,
+    Self::Trivial,
+  ];
+
+  /// The name of the class, one of "C", "V", "E", "CV", "CE", "EV", or
+  /// "Trivial".
+  pub const fn name(self) -> &'static str {
+    match self {
+      Self::C => "C",
+      Self::V => "V",
+      Self::E => "E",
+      Self::CV => "CV",
+      Self::CE => "CE",
+      Self::EV => "EV",
+      Self::Trivial => "Trivial",
+    }
+  }
+}
+
+impl Display for SymmetryClass {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
 pub type D6 = Dihedral<6>;
 pub type D3 = Dihedral<3>;
 pub type C2 = Cyclic<2>;
@@ -12,7 +12,7 @@ use crate::{
   groups::{SymmetryClass, C2, D3, D6, K4},
   hex_pos::{HexPos, HexPosOffset},
   tile_hash::{TileHash, C_MASK, E_MASK, V_MASK},
-  Onoro,
+  Onoro, PackedIdx, PawnColor,
 };
 
 #[derive(Debug)]
@@ -21,38 +21,155 @@ pub struct HashTable<const N: usize, const N2: usize, G: Group> {
 }
 
 impl<const N: usize, const N2: usize, G: Group> HashTable<N, N2, G> {
-  /// Computes the hash of a game state on a given hash table.
-  pub fn hash<const ONORO_N: usize, const ONORO_N2: usize, const ADJ_CNT_SIZE: usize>(
+  /// Computes the hash of a game state on a given hash table as if each
+  /// player were the one to move, as [black to move, white to move]. The
+  /// hash of the game state is the one for the player whose turn it is.
+  /// Keeping both lets the hash of a child state be found incrementally,
+  /// since the player to move changes with every move.
+  pub fn player_hashes<const ONORO_N: usize, const ONORO_N2: usize, const ADJ_CNT_SIZE: usize>(
     &self,
     onoro: &Onoro<ONORO_N, ONORO_N2, ADJ_CNT_SIZE>,
     symm_state: &BoardSymmetryState,
-  ) -> u64 {
-    let origin = onoro.origin(symm_state);
-    onoro.pawns().fold(0u64, |hash, pawn| {
-      // The position of the pawn relative to the rotation-invariant origin of
-      // the board.
-      let pos = HexPos::from(pawn.pos) - origin;
-      // The position of the pawn normalized to align board states on all
-      // symmetry axes which the board isn't possibly symmetric about itself.
-      let normalized_pos = pos.apply_d6_c(&symm_state.op);
-      // The position of the pawn in table space, relative to the center of the
-      // hash table.
-      let table_pos = normalized_pos + Self::center();
-      // The index of the tile this pawn is on.
-      let table_idx = Self::hex_pos_ord(&table_pos);
-      let tile_hash = &self[table_idx];
-
-      let pawn_hash = if pawn.color == onoro.player_color() {
-        tile_hash.cur_player_hash()
-      } else {
-        tile_hash.other_player_hash()
-      };
+  ) -> [u64; 2] {
+    #[cfg(all(feature = "avx2-hash", target_arch = "x86_64"))]
+    if std::arch::is_x86_feature_detected!("avx2") {
+      // Safety: the CPU supports AVX2.
+      let hashes = unsafe { self.player_hashes_avx2(onoro, symm_state) };
+      #[cfg(feature = "verify-simd")]
+      assert_eq!(
+        hashes,
+        self.player_hashes_scalar(onoro, symm_state),
+        "player_hashes mismatch for\n{onoro}"
+      );
+      return hashes;
+    }
+    self.player_hashes_scalar(onoro, symm_state)
+  }
 
+  fn player_hashes_scalar<
+    const ONORO_N: usize,
+    const ONORO_N2: usize,
+    const ADJ_CNT_SIZE: usize,
+  >(
+    &self,
+    onoro: &Onoro<ONORO_N, ONORO_N2, ADJ_CNT_SIZE>,
+    symm_state: &BoardSymmetryState,
+  ) -> [u64; 2] {
+    let origin = onoro.origin(symm_state);
+    onoro.pawns().fold([0, 0], |[black, white], pawn| {
+      let [pawn_black, pawn_white] = self.pawn_hashes(pawn.pos, pawn.color, origin, symm_state);
       // Zobrist hashing accumulates all hashes with xor.
-      hash ^ pawn_hash
+      [black ^ pawn_black, white ^ pawn_white]
     })
   }
 
+  /// `player_hashes`, hashing a black and a white pawn at a time in one 256-bit
+  /// register. Black pawns are at even indices and white pawns at odd ones, so
+  /// the pawns pair up by index, with the black pawn's tile hash in the low
+  /// half of the register and the white pawn's in the high half.
+  ///
+  /// Each half accumulates its color's (current player, other player) hashes.
+  /// For black pawns, these are the hashes with black and white to move, and
+  /// for white pawns the reverse, so xor is linear enough to swap the high
+  /// half once at the end instead of for every white pawn.
+  ///
+  /// # Safety
+  /// The CPU must support AVX2.
+  #[cfg(all(feature = "avx2-hash", target_arch = "x86_64"))]
+  #[target_feature(enable = "avx2")]
+  unsafe fn player_hashes_avx2<
+    const ONORO_N: usize,
+    const ONORO_N2: usize,
+    const ADJ_CNT_SIZE: usize,
+  >(
+    &self,
+    onoro: &Onoro<ONORO_N, ONORO_N2, ADJ_CNT_SIZE>,
+    symm_state: &BoardSymmetryState,
+  ) -> [u64; 2] {
+    use std::arch::x86_64::{
+      __m128i, _mm256_castsi256_si128, _mm256_extracti128_si256, _mm256_loadu2_m128i,
+      _mm256_setzero_si256, _mm256_xor_si256, _mm_loadu_si128, _mm_shuffle_epi32, _mm_storeu_si128,
+      _mm_xor_si128,
+    };
+
+    let origin = onoro.origin(symm_state);
+    // `TileHash` is laid out as the current player's hash followed by the
+    // other player's, which is one 128-bit lane.
+    let tile_hash = |pawn_idx: usize| {
+      self.tile_hash(onoro.pawn_pos(pawn_idx), origin, symm_state) as *const TileHash<G>
+        as *const __m128i
+    };
+
+    // Pawns are placed in index order, so the first `pawns_in_play` are the
+    // ones on the board.
+    let pawns = onoro.pawns_in_play() as usize;
+    let mut pairs = _mm256_setzero_si256();
+    for black_idx in (0..pawns - pawns % 2).step_by(2) {
+      pairs = _mm256_xor_si256(
+        pairs,
+        _mm256_loadu2_m128i(tile_hash(black_idx + 1), tile_hash(black_idx)),
+      );
+    }
+
+    let black = _mm256_castsi256_si128(pairs);
+    let white = _mm256_extracti128_si256::<1>(pairs);
+    let mut hashes = _mm_xor_si128(black, _mm_shuffle_epi32::<0b0100_1110>(white));
+    if pawns % 2 == 1 {
+      // The last pawn placed is black, without a white pawn to pair with.
+      hashes = _mm_xor_si128(hashes, _mm_loadu_si128(tile_hash(pawns - 1)));
+    }
+
+    let mut player_hashes = [0; 2];
+    _mm_storeu_si128(player_hashes.as_mut_ptr() as *mut __m128i, hashes);
+    player_hashes
+  }
+
+  /// The hashes a pawn of `color` on `pos` contributes to `player_hashes` of
+  /// a game state with origin `origin`. Since hashes are accumulated with
+  /// xor, this both adds the pawn to and removes it from the hashes.
+  pub fn pawn_hashes(
+    &self,
+    pos: PackedIdx,
+    color: PawnColor,
+    origin: HexPos,
+    symm_state: &BoardSymmetryState,
+  ) -> [u64; 2] {
+    let tile_hash = self.tile_hash(pos, origin, symm_state);
+    match color {
+      PawnColor::Black => [tile_hash.cur_player_hash(), tile_hash.other_player_hash()],
+      PawnColor::White => [tile_hash.other_player_hash(), tile_hash.cur_player_hash()],
+    }
+  }
+
+  fn tile_hash(
+    &self,
+    pos: PackedIdx,
+    origin: HexPos,
+    symm_state: &BoardSymmetryState,
+  ) -> &TileHash<G> {
+    // The position of the pawn relative to the rotation-invariant origin of
+    // the board.
+    let pos = HexPos::from(pos) - origin;
+    // The position of the pawn normalized to align board states on all
+    // symmetry axes which the board isn't possibly symmetric about itself.
+    let normalized_pos = pos.apply_d6_c(&symm_state.op);
+    // The position of the pawn in table space, relative to the center of the
+    // hash table.
+    let table_pos = normalized_pos + Self::center();
+    // The index of the tile this pawn is on.
+    let table_idx = Self::hex_pos_ord(&table_pos);
+    &self[table_idx]
+  }
+
+  /// The hashes of each tile, as (current player, other player) pairs, in
+  /// order of tile ordinal (`x + y * N`).
+  pub fn tile_hashes(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+    self
+      .table
+      .iter()
+      .map(|tile| (tile.cur_player_hash(), tile.other_player_hash()))
+  }
+
   const fn center() -> HexPos {
     HexPos::new((N / 2) as u32, (N / 2) as u32)
   }
@@ -82,8 +199,14 @@ impl<const N: usize, const N2: usize, G: Group> HashTable<N, N2, G> {
 impl<const N: usize, const N2: usize> HashTable<N, N2, D6> {
   /// Generates a hash table for boards with symmetry class C.
   pub const fn new_c() -> Self {
+    Self::new_c_seeded([const_random!(u64), const_random!(u64)])
+  }
+
+  /// Generates the same kind of table as `new_c`, from random numbers
+  /// seeded by `seed`.
+  pub const fn new_c_seeded(seed: [u64; 2]) -> Self {
     let mut table = [TileHash::<D6>::uninitialized(); N2];
-    let mut rng = Xoroshiro128::from_seed(&[const_random!(u64), const_random!(u64)]);
+    let mut rng = Xoroshiro128::from_seed(&seed);
 
     let mut i = 0usize;
     'tile_loop: while i < N2 {
@@ -166,8 +289,14 @@ impl<const N: usize, const N2: usize> HashTable<N, N2, D6> {
 impl<const N: usize, const N2: usize> HashTable<N, N2, D3> {
   /// Generates a hash table for boards with symmetry class V.
   pub const fn new_v() -> Self {
+    Self::new_v_seeded([const_random!(u64), const_random!(u64)])
+  }
+
+  /// Generates the same kind of table as `new_v`, from random numbers
+  /// seeded by `seed`.
+  pub const fn new_v_seeded(seed: [u64; 2]) -> Self {
     let mut table = [TileHash::<D3>::uninitialized(); N2];
-    let mut rng = Xoroshiro128::from_seed(&[const_random!(u64), const_random!(u64)]);
+    let mut rng = Xoroshiro128::from_seed(&seed);
 
     let mut i = 0usize;
     'tile_loop: while i < N2 {
@@ -244,8 +373,14 @@ impl<const N: usize, const N2: usize> HashTable<N, N2, D3> {
 impl<const N: usize, const N2: usize> HashTable<N, N2, K4> {
   /// Generates a hash table for boards with symmetry class E.
   pub const fn new_e() -> Self {
+    Self::new_e_seeded([const_random!(u64), const_random!(u64)])
+  }
+
+  /// Generates the same kind of table as `new_e`, from random numbers
+  /// seeded by `seed`.
+  pub const fn new_e_seeded(seed: [u64; 2]) -> Self {
     let mut table = [TileHash::<K4>::uninitialized(); N2];
-    let mut rng = Xoroshiro128::from_seed(&[const_random!(u64), const_random!(u64)]);
+    let mut rng = Xoroshiro128::from_seed(&seed);
 
     let mut i = 0usize;
     'tile_loop: while i < N2 {
@@ -343,26 +478,41 @@ impl<const N: usize, const N2: usize> HashTable<N, N2, C2> {
   }
 
   pub const fn new_cv() -> Self {
-    let rng = Xoroshiro128::from_seed(&[const_random!(u64), const_random!(u64)]);
-    Self::new_c2(SymmetryClass::CV, rng)
+    Self::new_cv_seeded([const_random!(u64), const_random!(u64)])
+  }
+
+  pub const fn new_cv_seeded(seed: [u64; 2]) -> Self {
+    Self::new_c2(SymmetryClass::CV, Xoroshiro128::from_seed(&seed))
   }
 
   pub const fn new_ce() -> Self {
-    let rng = Xoroshiro128::from_seed(&[const_random!(u64), const_random!(u64)]);
-    Self::new_c2(SymmetryClass::CE, rng)
+    Self::new_ce_seeded([const_random!(u64), const_random!(u64)])
+  }
+
+  pub const fn new_ce_seeded(seed: [u64; 2]) -> Self {
+    Self::new_c2(SymmetryClass::CE, Xoroshiro128::from_seed(&seed))
   }
 
   pub const fn new_ev() -> Self {
-    let rng = Xoroshiro128::from_seed(&[const_random!(u64), const_random!(u64)]);
-    Self::new_c2(SymmetryClass::EV, rng)
+    Self::new_ev_seeded([const_random!(u64), const_random!(u64)])
+  }
+
+  pub const fn new_ev_seeded(seed: [u64; 2]) -> Self {
+    Self::new_c2(SymmetryClass::EV, Xoroshiro128::from_seed(&seed))
   }
 }
 
 impl<const N: usize, const N2: usize> HashTable<N, N2, Trivial> {
   /// Generates a hash table for boards with symmetry class E.
   pub const fn new_trivial() -> Self {
+    Self::new_trivial_seeded([const_random!(u64), const_random!(u64)])
+  }
+
+  /// Generates the same kind of table as `new_trivial`, from random numbers
+  /// seeded by `seed`.
+  pub const fn new_trivial_seeded(seed: [u64; 2]) -> Self {
     let mut table = [TileHash::<Trivial>::uninitialized(); N2];
-    let mut rng = Xoroshiro128::from_seed(&[const_random!(u64), const_random!(u64)]);
+    let mut rng = Xoroshiro128::from_seed(&seed);
 
     let mut i = 0usize;
     while i < N2 {
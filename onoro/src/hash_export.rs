@@ -0,0 +1,326 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+  error::{OnoroError, OnoroResult},
+  groups::SymmetryClass,
+  hash::HashTable,
+  make_onoro_error,
+  onoro_view::export_view_hash_tables,
+  Onoro16, Onoro16View, PawnColor,
+};
+
+use algebra::group::Group;
+
+/// The version of the serialized format, written in the header line. Bump this
+/// whenever the format or the way hashes are computed changes.
+const EXPORT_VERSION: u32 = 1;
+
+/// The number of moves to play out from the default start when generating test
+/// vectors.
+const TEST_VECTOR_PLIES: u32 = 8;
+
+fn parse_symm_class(name: &str) -> OnoroResult<SymmetryClass> {
+  match name {
+    "C" => Ok(SymmetryClass::C),
+    "V" => Ok(SymmetryClass::V),
+    "E" => Ok(SymmetryClass::E),
+    "CV" => Ok(SymmetryClass::CV),
+    "CE" => Ok(SymmetryClass::CE),
+    "EV" => Ok(SymmetryClass::EV),
+    "Trivial" => Ok(SymmetryClass::Trivial),
+    _ => Err(make_onoro_error!("Unknown symmetry class \"{name}\"")),
+  }
+}
+
+fn parse_hash(hash: &str) -> OnoroResult<u64> {
+  u64::from_str_radix(hash, 16).map_err(|err| make_onoro_error!("Invalid hash \"{hash}\": {err}"))
+}
+
+/// The tile hashes of the Zobrist hash table for one symmetry class.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HashTableExport {
+  symm_class: SymmetryClass,
+  width: usize,
+  tiles: Vec<(u64, u64)>,
+}
+
+impl HashTableExport {
+  pub(crate) fn new<const N: usize, const N2: usize, G: Group>(
+    symm_class: SymmetryClass,
+    table: &HashTable<N, N2, G>,
+  ) -> Self {
+    Self {
+      symm_class,
+      width: N,
+      tiles: table.tile_hashes().collect(),
+    }
+  }
+
+  /// The name of the symmetry class this table is used for, one of "C", "V",
+  /// "E", "CV", "CE", "EV", or "Trivial".
+  pub fn symm_class_name(&self) -> &'static str {
+    self.symm_class.name()
+  }
+
+  /// The width of the table, which covers a `width` x `width` area of tiles
+  /// centered on the board's origin.
+  pub fn width(&self) -> usize {
+    self.width
+  }
+
+  /// The (current player, other player) hashes of each tile, in order of tile
+  /// ordinal (`x + y * width`).
+  pub fn tiles(&self) -> &[(u64, u64)] {
+    &self.tiles
+  }
+}
+
+/// A pawn in a hash test vector.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HashTestPawn {
+  pub x: u32,
+  pub y: u32,
+  pub black: bool,
+}
+
+/// A game state along with its expected canonical hash, for checking that an
+/// external implementation hashes game states identically.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HashTestVector {
+  pub black_turn: bool,
+  pub pawns: Vec<HashTestPawn>,
+  pub hash: u64,
+}
+
+impl HashTestVector {
+  fn from_onoro(onoro: &Onoro16) -> Self {
+    Self {
+      black_turn: onoro.player_color() == PawnColor::Black,
+      pawns: onoro
+        .pawns()
+        .map(|pawn| HashTestPawn {
+          x: pawn.pos.x(),
+          y: pawn.pos.y(),
+          black: pawn.color == PawnColor::Black,
+        })
+        .collect(),
+      hash: Onoro16View::new(onoro.clone()).canonical_hash(),
+    }
+  }
+}
+
+/// All of the Zobrist hash tables used to compute canonical hashes of game
+/// states, along with test vectors. The hash tables are randomly generated at
+/// compile time, so external tools which need to compute identical hashes
+/// must import the tables from the same build they are interoperating with.
+///
+/// The serialized format is line-based text:
+/// ```text
+/// onoro-hash-tables <version>
+/// table <symmetry class> <width>
+/// <current player hash> <other player hash>   (width * width lines)
+/// ...
+/// vector <hash> <black|white> <B|W>:<x>,<y> ...
+/// ```
+/// where all hashes are 16-digit hexadecimal numbers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HashExport {
+  tables: Vec<HashTableExport>,
+  test_vectors: Vec<HashTestVector>,
+}
+
+impl HashExport {
+  /// Exports the hash tables compiled into this build.
+  pub fn current() -> Self {
+    let mut test_vectors = vec![HashTestVector::from_onoro(&Onoro16::hex_start())];
+
+    let mut onoro = Onoro16::default_start();
+    test_vectors.push(HashTestVector::from_onoro(&onoro));
+    for _ in 0..TEST_VECTOR_PLIES {
+      let m = match onoro.each_move().next() {
+        Some(m) => m,
+        None => break,
+      };
+      onoro.make_move(m);
+      test_vectors.push(HashTestVector::from_onoro(&onoro));
+    }
+
+    Self {
+      tables: export_view_hash_tables(),
+      test_vectors,
+    }
+  }
+
+  pub fn tables(&self) -> &[HashTableExport] {
+    &self.tables
+  }
+
+  pub fn test_vectors(&self) -> &[HashTestVector] {
+    &self.test_vectors
+  }
+}
+
+impl Display for HashExport {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    writeln!(f, "onoro-hash-tables {EXPORT_VERSION}")?;
+    for table in &self.tables {
+      writeln!(f, "table {} {}", table.symm_class_name(), table.width)?;
+      for (cur_hash, other_hash) in &table.tiles {
+        writeln!(f, "{cur_hash:016x} {other_hash:016x}")?;
+      }
+    }
+    for vector in &self.test_vectors {
+      write!(
+        f,
+        "vector {:016x} {}",
+        vector.hash,
+        if vector.black_turn { "black" } else { "white" }
+      )?;
+      for pawn in &vector.pawns {
+        write!(
+          f,
+          " {}:{},{}",
+          if pawn.black { "B" } else { "W" },
+          pawn.x,
+          pawn.y
+        )?;
+      }
+      writeln!(f)?;
+    }
+    Ok(())
+  }
+}
+
+impl FromStr for HashExport {
+  type Err = OnoroError;
+
+  fn from_str(s: &str) -> OnoroResult<Self> {
+    let mut lines = s.lines().filter(|line| !line.trim().is_empty());
+
+    match lines
+      .next()
+      .map(|line| line.split_whitespace().collect::<Vec<_>>())
+    {
+      Some(header) if header.len() == 2 && header[0] == "onoro-hash-tables" => {
+        if header[1] != EXPORT_VERSION.to_string() {
+          return Err(make_onoro_error!(
+            "Unsupported hash table version {}, expected {EXPORT_VERSION}",
+            header[1]
+          ));
+        }
+      }
+      _ => return Err(make_onoro_error!("Missing onoro-hash-tables header")),
+    }
+
+    let mut tables = Vec::new();
+    let mut test_vectors = Vec::new();
+    while let Some(line) = lines.next() {
+      let tokens: Vec<_> = line.split_whitespace().collect();
+      match tokens[..] {
+        ["table", symm_class, width] => {
+          let width: usize = width
+            .parse()
+            .map_err(|err| make_onoro_error!("Invalid table width \"{width}\": {err}"))?;
+          let tiles = (0..width * width)
+            .map(|_| {
+              let line = lines
+                .next()
+                .ok_or_else(|| make_onoro_error!("Unexpected end of table {symm_class}"))?;
+              match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [cur_hash, other_hash] => Ok((parse_hash(cur_hash)?, parse_hash(other_hash)?)),
+                _ => Err(make_onoro_error!("Invalid tile hash line \"{line}\"")),
+              }
+            })
+            .collect::<OnoroResult<_>>()?;
+          tables.push(HashTableExport {
+            symm_class: parse_symm_class(symm_class)?,
+            width,
+            tiles,
+          });
+        }
+        ["vector", hash, turn, ref pawns @ ..] => {
+          let black_turn = match turn {
+            "black" => true,
+            "white" => false,
+            _ => return Err(make_onoro_error!("Invalid player \"{turn}\"")),
+          };
+          let pawns = pawns
+            .iter()
+            .map(|pawn| {
+              let (color, pos) = pawn
+                .split_once(':')
+                .ok_or_else(|| make_onoro_error!("Invalid pawn \"{pawn}\""))?;
+              let (x, y) = pos
+                .split_once(',')
+                .ok_or_else(|| make_onoro_error!("Invalid pawn \"{pawn}\""))?;
+              Ok(HashTestPawn {
+                x: x
+                  .parse()
+                  .map_err(|err| make_onoro_error!("Invalid pawn \"{pawn}\": {err}"))?,
+                y: y
+                  .parse()
+                  .map_err(|err| make_onoro_error!("Invalid pawn \"{pawn}\": {err}"))?,
+                black: match color {
+                  "B" => true,
+                  "W" => false,
+                  _ => return Err(make_onoro_error!("Invalid pawn color \"{color}\"")),
+                },
+              })
+            })
+            .collect::<OnoroResult<_>>()?;
+          test_vectors.push(HashTestVector {
+            black_turn,
+            pawns,
+            hash: parse_hash(hash)?,
+          });
+        }
+        _ => return Err(make_onoro_error!("Unexpected line \"{line}\"")),
+      }
+    }
+
+    Ok(Self {
+      tables,
+      test_vectors,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{Onoro16, Onoro16View};
+
+  use super::HashExport;
+
+  #[test]
+  fn test_round_trip() {
+    let export = HashExport::current();
+    let parsed: HashExport = export.to_string().parse().unwrap();
+    assert_eq!(parsed, export);
+  }
+
+  #[test]
+  fn test_tables() {
+    let export = HashExport::current();
+    assert_eq!(export.tables().len(), 7);
+    for table in export.tables() {
+      assert_eq!(table.tiles().len(), table.width() * table.width());
+    }
+  }
+
+  #[test]
+  fn test_vectors() {
+    let export = HashExport::current();
+    assert_eq!(
+      export.test_vectors()[1].hash,
+      Onoro16View::new(Onoro16::default_start()).canonical_hash()
+    );
+
+    // Hashes are invariant under symmetry, so the rotated start position must
+    // have the same hash.
+    let rotated = Onoro16::default_start().rotated_d6_c(crate::groups::D6::Rot(2));
+    assert_eq!(
+      export.test_vectors()[1].hash,
+      Onoro16View::new(rotated).canonical_hash()
+    );
+  }
+}
@@ -196,6 +196,12 @@ impl HexPosOffset {
     self.y
   }
 
+  /// The number of steps between adjacent tiles it takes to travel this
+  /// offset.
+  pub const fn hex_distance(&self) -> u32 {
+    (self.x.unsigned_abs() + self.y.unsigned_abs() + (self.x - self.y).unsigned_abs()) / 2
+  }
+
   /// Returns the sectant this point lies in, treating (0, 0) as the origin. The
   /// first sectant (0) is only the origin tile. The second (1) is every hex
   /// with (x >= 0, y >= 0, y < x). The third sectant (2) is the second sectant
@@ -519,3 +525,22 @@ impl Display for HexPosOffset {
     write!(f, "({}, {})", self.x, self.y)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::{HexPos, HexPosOffset};
+
+  #[test]
+  fn test_hex_distance() {
+    let origin = HexPos::new(4, 4);
+    assert_eq!(HexPosOffset::origin().hex_distance(), 0);
+    for neighbor in origin.each_neighbor() {
+      assert_eq!((neighbor - origin).hex_distance(), 1);
+    }
+
+    assert_eq!(HexPosOffset::new(2, 2).hex_distance(), 2);
+    assert_eq!(HexPosOffset::new(-3, -1).hex_distance(), 3);
+    assert_eq!(HexPosOffset::new(2, -1).hex_distance(), 3);
+    assert_eq!(HexPosOffset::new(-1, 3).hex_distance(), 4);
+  }
+}
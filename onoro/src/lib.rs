@@ -1,9 +1,19 @@
+mod bitboard;
 mod canonicalize;
 mod color_print;
 mod const_rand;
+mod coords;
+mod dataset;
+mod dedup;
+#[cfg(test)]
+mod dispatch;
+mod dyn_onoro;
+mod enumerate;
 mod error;
+mod game_record;
 mod groups;
 mod hash;
+mod hash_export;
 mod hex_pos;
 mod r#move;
 mod onoro;
@@ -12,12 +22,43 @@ mod onoro_state;
 mod onoro_view;
 mod packed_hex_pos;
 mod packed_idx;
+mod position_suite;
+mod puzzle;
+mod rank;
+pub mod render;
+mod replay;
+mod rule_violation;
+mod symm_stats;
+#[cfg(test)]
+mod testing;
 mod tile_hash;
 mod util;
+mod variant;
+#[cfg(feature = "wasm")]
+mod wasm;
 
 pub use crate::onoro::*;
 pub use color_print::*;
+pub use coords::*;
+pub use dataset::*;
+pub use dedup::*;
+pub use dyn_onoro::*;
+pub use enumerate::*;
+pub use error::OnoroError;
+pub use game_record::*;
+pub use groups::{SymmetryClass, D6};
+pub use hash_export::*;
 pub use onoro_defs::*;
+#[cfg(feature = "move-metadata")]
+pub use onoro_state::LastMove;
 pub use onoro_view::*;
 pub use packed_idx::*;
+pub use position_suite::*;
+pub use puzzle::*;
 pub use r#move::*;
+pub use replay::*;
+pub use rule_violation::*;
+pub use symm_stats::*;
+pub use variant::*;
+#[cfg(feature = "wasm")]
+pub use wasm::*;
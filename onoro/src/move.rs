@@ -1,8 +1,32 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+  error::{OnoroError, OnoroResult},
+  groups::D6,
+  make_onoro_error, Onoro, PawnColor,
+};
 
 use super::{hex_pos::HexPos, packed_idx::PackedIdx};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// The two phases of an Onoro game. In phase 1, players take turns placing
+/// pawns until all pawns are on the board. In phase 2, players take turns
+/// moving their pawns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Phase {
+  Phase1,
+  Phase2,
+}
+
+impl Display for Phase {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Phase::Phase1 => write!(f, "phase 1"),
+      Phase::Phase2 => write!(f, "phase 2"),
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Move {
   Phase1Move {
     /// Position to place the pawn at.
@@ -16,6 +40,92 @@ pub enum Move {
   },
 }
 
+impl Move {
+  /// The phase of the game this move can be made in.
+  pub const fn phase(&self) -> Phase {
+    match self {
+      Move::Phase1Move { .. } => Phase::Phase1,
+      Move::Phase2Move { .. } => Phase::Phase2,
+    }
+  }
+
+  /// The tile this move places or moves a pawn to.
+  pub const fn to(&self) -> PackedIdx {
+    match *self {
+      Move::Phase1Move { to } | Move::Phase2Move { to, .. } => to,
+    }
+  }
+
+  /// This move with its destination rotated or reflected by `op` about the
+  /// center of the tile `origin`. The pawn a phase 2 move refers to is left as
+  /// is, since transforming a board in place doesn't change which index each
+  /// pawn is at.
+  pub fn transform(&self, op: &D6, origin: PackedIdx) -> Move {
+    let origin = HexPos::from(origin);
+    let to = ((HexPos::from(self.to()) - origin).apply_d6_c(op) + origin).into();
+    match *self {
+      Move::Phase1Move { .. } => Move::Phase1Move { to },
+      Move::Phase2Move { from_idx, .. } => Move::Phase2Move { to, from_idx },
+    }
+  }
+}
+
+impl Move {
+  /// Formats this move in compact notation, for the game state `onoro` it is
+  /// made from: "b@c4" for black placing a pawn on c4, and "c4-d5" for moving
+  /// the pawn on c4 to d5. Tiles are named by a column letter and a row
+  /// number, from "a1" for (0, 0) to "p16" for (15, 15). This is the inverse
+  /// of `Onoro::parse_move`.
+  pub fn to_notation<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+    &self,
+    onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+  ) -> String {
+    match *self {
+      Move::Phase1Move { to } => {
+        let color = match onoro.player_color() {
+          PawnColor::Black => 'b',
+          PawnColor::White => 'w',
+        };
+        format!("{color}@{}", square_notation(to))
+      }
+      Move::Phase2Move { to, from_idx } => match onoro.pawn_position(from_idx) {
+        Some(from) => format!("{}-{}", square_notation(from), square_notation(to)),
+        // There is no pawn to name, so fall back to the pawn index.
+        None => self.to_string(),
+      },
+    }
+  }
+}
+
+/// The name of a tile in move notation: a column letter followed by a row
+/// number, e.g. "c4" for (2, 3).
+pub fn square_notation(pos: PackedIdx) -> String {
+  format!("{}{}", (b'a' + pos.x() as u8) as char, pos.y() + 1)
+}
+
+/// Parses the name of a tile in move notation, the inverse of
+/// `square_notation`.
+pub fn parse_square(square: &str) -> OnoroResult<PackedIdx> {
+  let mut chars = square.chars();
+  let x = match chars.next().map(|column| column.to_ascii_lowercase()) {
+    Some(column @ 'a'..='p') => column as u32 - 'a' as u32,
+    _ => {
+      return Err(make_onoro_error!(
+        "Expected a tile like \"c4\" with a column from a to p, found \"{square}\""
+      ))
+    }
+  };
+  let y = match chars.as_str().parse::<u32>() {
+    Ok(row @ 1..=16) => row - 1,
+    _ => {
+      return Err(make_onoro_error!(
+        "Expected a tile like \"c4\" with a row from 1 to 16, found \"{square}\""
+      ))
+    }
+  };
+  Ok(PackedIdx::new(x, y))
+}
+
 impl Display for Move {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     match self {
@@ -24,3 +134,123 @@ impl Display for Move {
     }
   }
 }
+
+/// Parses a tile position formatted as "(x, y)".
+fn parse_pos(pos: &str) -> OnoroResult<PackedIdx> {
+  let coords = pos
+    .trim()
+    .strip_prefix('(')
+    .and_then(|pos| pos.strip_suffix(')'))
+    .and_then(|pos| pos.split_once(','))
+    .ok_or_else(|| make_onoro_error!("Expected a position like \"(x, y)\", found \"{pos}\""))?;
+
+  let parse_coord = |coord: &str| {
+    let coord = coord.trim();
+    match coord.parse::<u32>() {
+      Ok(value) if value < 0x10 => Ok(value),
+      Ok(_) => Err(make_onoro_error!(
+        "Coordinate {coord} in \"{pos}\" is off the board, must be less than 16"
+      )),
+      Err(err) => Err(make_onoro_error!(
+        "Invalid coordinate \"{coord}\" in \"{pos}\": {err}"
+      )),
+    }
+  };
+  Ok(PackedIdx::new(
+    parse_coord(coords.0)?,
+    parse_coord(coords.1)?,
+  ))
+}
+
+impl FromStr for Move {
+  type Err = OnoroError;
+
+  /// Parses moves in the same notation they are displayed in: "(x, y)" for
+  /// phase 1 moves, and "(x, y) from idx i" for phase 2 moves.
+  fn from_str(s: &str) -> OnoroResult<Self> {
+    match s.split_once("from idx") {
+      Some((to, from_idx)) => Ok(Move::Phase2Move {
+        to: parse_pos(to)?,
+        from_idx: from_idx.trim().parse().map_err(|err| {
+          make_onoro_error!(
+            "Invalid pawn index \"{}\" in \"{s}\": {err}",
+            from_idx.trim()
+          )
+        })?,
+      }),
+      None => Ok(Move::Phase1Move { to: parse_pos(s)? }),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use algebra::{group::Group, monoid::Monoid};
+
+  use crate::{PackedIdx, D6};
+
+  use super::Move;
+
+  #[test]
+  fn test_transform() {
+    let origin = PackedIdx::new(7, 8);
+    let m = Move::Phase2Move {
+      to: PackedIdx::new(9, 8),
+      from_idx: 5,
+    };
+    assert_eq!(m.transform(&D6::identity(), origin), m);
+    assert_eq!(
+      m.transform(&D6::Rot(3), origin),
+      Move::Phase2Move {
+        to: PackedIdx::new(5, 8),
+        from_idx: 5
+      }
+    );
+
+    for op in D6::for_each() {
+      let transformed = m.transform(&op, origin);
+      assert_eq!(transformed.transform(&op.inverse(), origin), m);
+    }
+    // The origin stays put.
+    let m = Move::Phase1Move { to: origin };
+    assert!(D6::for_each().all(|op| m.transform(&op, origin) == m));
+  }
+
+  #[test]
+  fn test_round_trip() {
+    for m in [
+      Move::Phase1Move {
+        to: PackedIdx::new(3, 12),
+      },
+      Move::Phase2Move {
+        to: PackedIdx::new(15, 0),
+        from_idx: 7,
+      },
+    ] {
+      assert_eq!(m.to_string().parse::<Move>().unwrap(), m);
+    }
+
+    assert_eq!(
+      " ( 4,5 )from idx 2".parse::<Move>().unwrap(),
+      Move::Phase2Move {
+        to: PackedIdx::new(4, 5),
+        from_idx: 2
+      }
+    );
+  }
+
+  #[test]
+  fn test_parse_errors() {
+    for s in [
+      "",
+      "4, 5",
+      "(4 5)",
+      "(16, 0)",
+      "(-1, 0)",
+      "(1, 2) from idx",
+      "(1, 2) to (3, 4)",
+    ] {
+      assert!(s.parse::<Move>().is_err(), "\"{s}\" should not parse");
+    }
+  }
+}
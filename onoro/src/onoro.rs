@@ -1,28 +1,35 @@
 use std::{
   cmp,
   fmt::{Debug, Display},
+  str::FromStr,
 };
 
-use abstract_game::{GameIterator, GameMoveGenerator};
+use abstract_game::{Compress, GameIterator, GameMoveGenerator, MoveArena};
 use algebra::group::Group;
+use arrayvec::ArrayVec;
 use itertools::interleave;
 use union_find::ConstUnionFind;
 
 use crate::{
-  canonicalize::{board_symm_state, BoardSymmetryState},
-  groups::{C2, D3, D6, K4},
+  canonicalize::{board_symm_state, compute_board_symm_state, BoardSymmetryState},
+  groups::{SymmetryClass, C2, D3, D6, K4},
   make_onoro_error,
   util::broadcast_u8_to_u64,
   Color, Colored,
 };
 
+#[cfg(feature = "move-metadata")]
+use super::onoro_state::LastMove;
 use super::{
+  bitboard::Bitboard,
   error::{OnoroError, OnoroResult},
   hex_pos::{HexPos, HexPosOffset},
   onoro_state::OnoroState,
   packed_hex_pos::PackedHexPos,
   packed_idx::{IdxOffset, PackedIdx},
-  r#move::Move,
+  r#move::{parse_square, Move, Phase},
+  rule_violation::RuleViolation,
+  variant::Variant,
 };
 
 /// For move generation, the number of bits to use per-tile (for counting
@@ -56,6 +63,17 @@ pub struct Onoro<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> {
   state: OnoroState,
   // Sum of all HexPos's of pieces on the board
   sum_of_mass: PackedHexPos,
+  /// The tiles with a pawn on them, kept in sync with `pawn_poses`. Checking
+  /// whether a tile is empty, which move generation does for every neighbor
+  /// of every candidate tile, is a single bit test instead of a search of
+  /// `pawn_poses`.
+  occupied: Bitboard,
+  variant: Variant,
+  /// The `BoardSymmetryState` of the board, packed with
+  /// `BoardSymmetryState::pack`. It only depends on `sum_of_mass` modulo the
+  /// number of pawns in play, so it's updated whenever a pawn is placed or
+  /// moved, and is unaffected by shifting the board.
+  symm_state: u8,
 }
 
 impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, ADJ_CNT_SIZE> {
@@ -63,28 +81,84 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
   /// state. Any constructor returning an owned instance of `Onoro` _must_ make
   /// at least one move after initializing an `Onoro` with this function.
   pub unsafe fn new() -> Self {
+    // `PackedIdx` packs each coordinate into 4 bits, so boards can be at most
+    // 16 tiles wide. `get_pawn_idx_fast` also searches the pawns 8 at a time,
+    // and `check_win_fast` packs 3 lines of up to 16 tiles into a `u64`.
+    const { assert!(N <= 16 && N & 7 == 0, "Onoro only supports 8 or 16 pawns") };
     Self {
       pawn_poses: [PackedIdx::null(); N],
       state: OnoroState::new(),
       sum_of_mass: HexPos::zero().into(),
+      occupied: Bitboard::new(),
+      variant: Variant::STANDARD,
+      symm_state: BoardSymmetryState::blank().pack(),
     }
   }
 
+  /// Constructs a game from a drawing of the board, with rows of "B", "W"
+  /// and "." tiles separated by spaces. Pawns are placed in
+  /// phase 1 order, alternating between black and white starting with black,
+  /// so there must be as many black pawns as white, or one more. See
+  /// `from_board_string_with_metadata` for the annotations the drawing may
+  /// have.
   pub fn from_board_string(board_layout: &str) -> Result<Self, String> {
+    Self::from_board_string_with_metadata(board_layout).map(|(onoro, _)| onoro)
+  }
+
+  /// Like `from_board_string`, but also returns the annotations of the
+  /// drawing:
+  ///
+  /// - Header lines of the form "key: value" before the board. The only key
+  ///   is "turn", which sets the player to move ("B" or "W"). This can only
+  ///   differ from the player implied by the pawn counts once all pawns are
+  ///   placed.
+  /// - A "*" after a pawn, e.g. "B*", marking it as the last pawn moved. It
+  ///   must belong to the player who isn't to move.
+  ///
+  /// ```text
+  /// turn: W
+  /// . B W W B
+  ///  . W B* B W
+  /// ```
+  pub fn from_board_string_with_metadata(
+    board_layout: &str,
+  ) -> Result<(Self, BoardMetadata), String> {
+    let mut lines = board_layout.split('\n').peekable();
+    let mut turn = None;
+    while let Some((key, value)) = lines.peek().and_then(|line| line.split_once(':')) {
+      match key.trim() {
+        "turn" => turn = Some(value.parse::<PawnColor>().map_err(|err| err.to_string())?),
+        key => return Err(format!("Unknown board header \"{key}\"")),
+      }
+      lines.next();
+    }
+
     let mut black_pawns = Vec::new();
     let mut while_pawns = Vec::new();
+    let mut last_moved = None;
 
-    for (y, line) in board_layout.split('\n').enumerate() {
+    for (y, line) in lines.enumerate() {
       for (x, tile) in line.split_ascii_whitespace().enumerate() {
         let pos = PackedIdx::from(HexPos::new(x as u32 + 1, (N - y - 2) as u32));
-        match tile {
-          "B" | "b" => black_pawns.push(pos),
-          "W" | "w" => while_pawns.push(pos),
-          "." => {}
+        let (tile, marked) = match tile.strip_suffix('*') {
+          Some(tile) => (tile, true),
+          None => (tile, false),
+        };
+        let (color, pawns) = match tile {
+          "B" | "b" => (PawnColor::Black, &mut black_pawns),
+          "W" | "w" => (PawnColor::White, &mut while_pawns),
+          "." if !marked => continue,
           _ => {
             return Err(format!("Invalid character in game state string: {tile}"));
           }
+        };
+        if marked {
+          if last_moved.is_some() {
+            return Err("Only one pawn may be marked as the last moved".into());
+          }
+          last_moved = Some((color, pawns.len()));
         }
+        pawns.push(pos);
       }
     }
 
@@ -109,6 +183,22 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
       ));
     }
 
+    // Place the marked pawn last among the pawns of its color, so it's placed
+    // after all the pawns that were on the board before it moved. Black pawns
+    // are at even indices in placement order, and white pawns at odd ones.
+    let last_moved_idx = last_moved.map(|(color, list_idx)| {
+      let pawns = match color {
+        PawnColor::Black => &mut black_pawns,
+        PawnColor::White => &mut while_pawns,
+      };
+      let marked = pawns.remove(list_idx);
+      pawns.push(marked);
+      match color {
+        PawnColor::Black => 2 * pawns.len() - 2,
+        PawnColor::White => 2 * pawns.len() - 1,
+      }
+    });
+
     let mut game = unsafe { Self::new() };
     unsafe {
       game.make_move_unchecked(Move::Phase1Move { to: black_pawns[0] });
@@ -117,7 +207,35 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
       game.make_move(Move::Phase1Move { to: pos });
     }
 
-    Ok(game)
+    if let Some(turn) = turn {
+      if turn != game.player_color() {
+        if game.in_phase1() {
+          return Err(format!(
+            "It can't be {turn}'s turn until all pawns are placed, since {} placed fewer pawns",
+            game.player_color()
+          ));
+        }
+        game.mut_onoro_state().swap_player_turn();
+      }
+    }
+    if last_moved.is_some_and(|(color, _)| color == game.player_color()) {
+      return Err(format!(
+        "The last pawn moved can't be {}'s, since it is their turn",
+        game.player_color()
+      ));
+    }
+
+    let metadata = BoardMetadata {
+      turn,
+      last_moved: last_moved_idx.map(|idx| game.pawn_poses[idx]),
+    };
+    #[cfg(feature = "move-metadata")]
+    game.state.set_last_move(last_moved_idx.map(|idx| LastMove {
+      pawn_idx: idx as u8,
+      from: None,
+      to: game.pawn_poses[idx],
+    }));
+    Ok((game, metadata))
   }
 
   pub fn default_start() -> Self {
@@ -134,6 +252,8 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
     game.make_move(Move::Phase1Move {
       to: PackedIdx::new(mid_idx + 1, mid_idx),
     });
+    #[cfg(feature = "move-metadata")]
+    game.state.set_last_move(None);
     game
   }
 
@@ -146,8 +266,49 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
     .unwrap()
   }
 
-  /// Constructs an identical Onoro game rotated by `op`.
-  fn rotated<G: Group, OpFn: FnMut(&HexPosOffset, &G) -> HexPosOffset>(
+  /// The rules this game is played by.
+  pub fn variant(&self) -> Variant {
+    self.variant
+  }
+
+  /// This game, played by the rules of `variant` from now on. Fails if the
+  /// game is already finished, if a player couldn't ever win because
+  /// `variant`'s lines are longer than their pawn count, or if either player
+  /// already has a line that wins under `variant`. Variants aren't kept by
+  /// `Compress`, so decompressed games follow the standard rules.
+  pub fn with_variant(mut self, variant: Variant) -> OnoroResult<Self> {
+    if self.finished().is_some() {
+      return Err(make_onoro_error!(
+        "Can't change the rules of a finished game"
+      ));
+    }
+    if variant.win_length() as usize > N / 2 {
+      return Err(make_onoro_error!(
+        "Win length {} is longer than the {} pawns each player has",
+        variant.win_length(),
+        N / 2
+      ));
+    }
+
+    self.variant = variant;
+    for color in [PawnColor::Black, PawnColor::White] {
+      if self
+        .color_pawns(color)
+        .any(|pawn| self.completes_line(color, Move::Phase1Move { to: pawn.pos }))
+      {
+        return Err(make_onoro_error!(
+          "{color} already has {} in a row",
+          variant.win_length()
+        ));
+      }
+    }
+    Ok(self)
+  }
+
+  /// Constructs an identical Onoro game rotated by `op`. The pawns of each
+  /// color are placed in order of their rotated positions, so boards with the
+  /// same pawns in the same orientation get the same pawn indices.
+  pub(crate) fn rotated<G: Group, OpFn: FnMut(&HexPosOffset, &G) -> HexPosOffset>(
     &self,
     op: G,
     mut op_fn: OpFn,
@@ -172,6 +333,8 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
         }
       }
     }
+    black_pawns.sort_unstable_by_key(|pos: &HexPos| (pos.y(), pos.x()));
+    white_pawns.sort_unstable_by_key(|pos: &HexPos| (pos.y(), pos.x()));
 
     unsafe {
       game.make_move_unchecked(Move::Phase1Move {
@@ -189,6 +352,47 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
     game
   }
 
+  /// Constructs the game with `black_pawns` and `white_pawns` on the board
+  /// directly, rather than by placing them one at a time, which would check
+  /// for a win after every placement. The pawns must already be positioned
+  /// away from the edges of the board, and in phase 1 `black_turn` must
+  /// agree with the number of pawns placed.
+  pub(crate) fn from_pawns(
+    black_pawns: &[PackedIdx],
+    white_pawns: &[PackedIdx],
+    black_turn: bool,
+  ) -> Self {
+    let mut game = unsafe { Self::new() };
+    for (i, &pos) in black_pawns.iter().enumerate() {
+      game.pawn_poses[2 * i] = pos;
+    }
+    for (i, &pos) in white_pawns.iter().enumerate() {
+      game.pawn_poses[2 * i + 1] = pos;
+    }
+    game.rebuild_occupied();
+
+    let pawns_in_play = (black_pawns.len() + white_pawns.len()) as u32;
+    game.state = OnoroState::from_turn(pawns_in_play - 1, black_turn, false);
+    let mut sum_of_mass = HexPos::zero();
+    for pawn in game.pawns() {
+      sum_of_mass += pawn.pos.into();
+    }
+    game.sum_of_mass = sum_of_mass.into();
+    game.update_symm_state();
+
+    // Only the player who moved last can have a line.
+    let last_mover = if black_turn {
+      PawnColor::White
+    } else {
+      PawnColor::Black
+    };
+    let finished = game
+      .color_pawns(last_mover)
+      .any(|pawn| game.check_win(pawn.pos.into()));
+    game.mut_onoro_state().set_finished(finished);
+    game
+  }
+
   pub fn rotated_d6_c(&self, op: D6) -> Self {
     self.rotated(op, HexPosOffset::apply_d6_c)
   }
@@ -295,13 +499,21 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
     pos.x() as usize + (pos.y() as usize) * N
   }
 
+  /// The change in `hex_pos_ord` of a tile moved by `offset`.
+  const fn ord_offset(offset: &HexPosOffset) -> i32 {
+    offset.x() + offset.y() * N as i32
+  }
+
   /// The inverse of `self.hex_pos_ord`.
   pub const fn ord_to_hex_pos(ord: usize) -> HexPos {
     HexPos::new((ord % N) as u32, (ord / N) as u32)
   }
 
-  /// If the game is finished, returns `Some(<player color who won>)`, or `None`
-  /// if the game is not over yet.
+  /// If a player has four in a row, returns `Some(<player color who won>)`, or
+  /// `None` if no one has yet. This doesn't check whether the player to move
+  /// has any legal moves, which would need the much slower move generator, so
+  /// a game lost by being stuck (see `each_move`) isn't reported here. Use
+  /// `winner` to check for both.
   pub fn finished(&self) -> Option<PawnColor> {
     if self.onoro_state().finished() {
       if self.onoro_state().black_turn() {
@@ -314,10 +526,31 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
     }
   }
 
+  /// The player who has won the game, either by getting four in a row or by
+  /// leaving their opponent with no legal moves on their turn, or `None` if
+  /// the game is still going.
+  pub fn winner(&self) -> Option<PawnColor> {
+    self.finished().or_else(|| {
+      self.each_move().next().is_none().then(|| {
+        if self.onoro_state().black_turn() {
+          PawnColor::White
+        } else {
+          PawnColor::Black
+        }
+      })
+    })
+  }
+
   pub fn pawns_in_play(&self) -> u32 {
     self.onoro_state().turn() + 1
   }
 
+  /// The position of the pawn at `pawn_idx`, the index phase 2 moves refer to
+  /// pawns by.
+  pub(crate) fn pawn_pos(&self, pawn_idx: usize) -> PackedIdx {
+    self.pawn_poses[pawn_idx]
+  }
+
   pub fn pawns_gen(&self) -> PawnMoveGenerator<N, N2, ADJ_CNT_SIZE> {
     PawnMoveGenerator {
       pawn_idx: 0,
@@ -346,6 +579,31 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
     self.color_pawns_gen(color).to_iter(self)
   }
 
+  /// The tiles adjacent to `pos` that are on the board.
+  fn adjacent_tiles(pos: PackedIdx) -> impl Iterator<Item = HexPos> {
+    // Neighbors off of the low edges wrap around to huge coordinates, so
+    // they're also caught by the upper bound.
+    HexPos::from(pos)
+      .each_neighbor()
+      .filter(|neighbor| neighbor.x() < N as u32 && neighbor.y() < N as u32)
+  }
+
+  /// The positions of the pawns adjacent to the tile at `pos`.
+  pub fn occupied_neighbors(&self, pos: PackedIdx) -> impl Iterator<Item = PackedIdx> + '_ {
+    Self::adjacent_tiles(pos)
+      .filter(|&neighbor| self.is_occupied(neighbor))
+      .map(PackedIdx::from)
+  }
+
+  /// The empty tiles adjacent to the tile at `pos`. Tiles off the edge of
+  /// the board aren't included, but pawns are never close enough to the edge
+  /// for that to matter.
+  pub fn empty_adjacent_tiles(&self, pos: PackedIdx) -> impl Iterator<Item = PackedIdx> + '_ {
+    Self::adjacent_tiles(pos)
+      .filter(|&neighbor| !self.is_occupied(neighbor))
+      .map(PackedIdx::from)
+  }
+
   pub fn pawns_mathematica_list(&self) -> String {
     format!(
       "{{{}}}",
@@ -378,6 +636,226 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
     self.sum_of_mass
   }
 
+  /// The symmetry state of the board, see `board_symm_state`.
+  pub(crate) fn symm_state(&self) -> BoardSymmetryState {
+    BoardSymmetryState::unpack(self.symm_state)
+  }
+
+  /// The symmetry class of the board, which decides which symmetries are
+  /// checked when canonicalizing it.
+  pub fn symmetry_class(&self) -> SymmetryClass {
+    self.symm_state().symm_class
+  }
+
+  fn update_symm_state(&mut self) {
+    self.symm_state = compute_board_symm_state(self).pack();
+  }
+
+  /// Measures how spread out `color`'s pawns are from the center of mass of all
+  /// pawns in play, as the sum of the hex distances of each pawn from the
+  /// center of mass. Pawns near the center of mass tend to have more neighbors
+  /// and more room to move, so a lower value means a more central position.
+  ///
+  /// The result is scaled by the number of pawns in play, which keeps the
+  /// center of mass on integer coordinates.
+  pub fn centralization(&self, color: PawnColor) -> u32 {
+    let pawns_in_play = self.pawns_in_play() as i32;
+    let sum_of_mass = HexPos::from(self.sum_of_mass) - HexPos::zero();
+    self
+      .color_pawns(color)
+      .map(|pawn| (HexPosOffset::from(pawn.pos) * pawns_in_play - sum_of_mass).hex_distance())
+      .sum()
+  }
+
+  /// The number of moves `color` could make if it were their turn. This is
+  /// only meaningful in phase 2, since both players may place pawns on the
+  /// same tiles in phase 1.
+  pub fn mobility(&self, color: PawnColor) -> u32 {
+    if color == self.player_color() {
+      self.each_move().count() as u32
+    } else if self.in_phase1() {
+      0
+    } else {
+      let onoro = self.with_turn_passed().unwrap();
+      onoro.each_move().count() as u32
+    }
+  }
+
+  /// This state with the other player to move, if that's a legal position.
+  /// Onoro has no pass move, but in phase 2 any board can be reached with
+  /// either player to move, so passing stands in for the null move of other
+  /// games. In phase 1, the player to move is fixed by the number of pawns
+  /// placed.
+  pub fn with_turn_passed(&self) -> Option<Self> {
+    if self.in_phase1() {
+      return None;
+    }
+    let mut onoro = self.clone();
+    onoro.mut_onoro_state().swap_player_turn();
+    Some(onoro)
+  }
+
+  /// Counts the lines of `color`'s pawns one short of winning with an empty
+  /// tile at either end, which `color` threatens to complete. In the standard
+  /// rules, these are lines of three.
+  pub fn threats(&self, color: PawnColor) -> u32 {
+    let tile_color = match color {
+      PawnColor::Black => TileState::Black,
+      PawnColor::White => TileState::White,
+    };
+    let line_length = self.variant.win_length() as usize - 1;
+    let tile = |pos: HexPosOffset| {
+      if (0..N as i32).contains(&pos.x()) && (0..N as i32).contains(&pos.y()) {
+        Some(self.get_tile(PackedIdx::new(pos.x() as u32, pos.y() as u32)))
+      } else {
+        None
+      }
+    };
+
+    self
+      .color_pawns(color)
+      .map(|pawn| {
+        let pos = HexPosOffset::from(pawn.pos);
+        [
+          HexPosOffset::new(1, 0),
+          HexPosOffset::new(0, 1),
+          HexPosOffset::new(1, 1),
+        ]
+        .into_iter()
+        .filter(|&dir| {
+          // Only count each line from the pawn at its start.
+          if tile(pos - dir).as_ref() == Some(&tile_color) {
+            return false;
+          }
+          let run = (1..)
+            .take_while(|&steps| tile(pos + dir * steps).as_ref() == Some(&tile_color))
+            .count();
+          run + 1 == line_length
+            && (tile(pos - dir) == Some(TileState::Empty)
+              || tile(pos + dir * line_length as i32) == Some(TileState::Empty))
+        })
+        .count() as u32
+      })
+      .sum()
+  }
+
+  /// The current player's moves that complete a line, winning the game on the
+  /// spot.
+  pub fn immediate_wins(&self) -> Vec<Move> {
+    if self.finished().is_some() {
+      return Vec::new();
+    }
+    let color = self.player_color();
+    self
+      .each_move()
+      .filter(|&m| self.completes_line(color, m))
+      .collect()
+  }
+
+  /// The current player's moves onto a tile where the opponent threatens to
+  /// complete a line on their next turn, by placing a pawn there in
+  /// phase 1 or moving one there in phase 2. Unless the current player can
+  /// win first (see `immediate_wins`), they must make one of these moves if
+  /// there are any.
+  pub fn forced_blocks(&self) -> Vec<Move> {
+    if self.finished().is_some() {
+      return Vec::new();
+    }
+    let opponent = match self.player_color() {
+      PawnColor::Black => PawnColor::White,
+      PawnColor::White => PawnColor::Black,
+    };
+
+    let threatened: Vec<_> = if self.in_phase1() {
+      // Both players can place pawns on the same tiles.
+      self.winning_squares(opponent)
+    } else {
+      let mut onoro = self.clone();
+      onoro.mut_onoro_state().swap_player_turn();
+      onoro
+        .each_move()
+        .filter(|&m| onoro.completes_line(opponent, m))
+        .map(|m| m.to())
+        .collect()
+    };
+    if threatened.is_empty() {
+      return Vec::new();
+    }
+
+    self
+      .each_move()
+      .filter(|m| threatened.contains(&m.to()))
+      .collect()
+  }
+
+  /// The tiles `color`'s pawns occupy along the three lines through `center`.
+  pub fn line_masks(&self, center: PackedIdx, color: PawnColor) -> LineMasks {
+    let center = HexPos::from(center);
+    let first_pawn = match color {
+      PawnColor::Black => 0,
+      PawnColor::White => 1,
+    };
+    let mut bits = 0;
+    for &pos in self.pawn_poses[first_pawn..].iter().step_by(2) {
+      if pos != PackedIdx::null() {
+        bits |= LineMasks::tile_bits(pos.into(), center);
+      }
+    }
+    LineMasks(bits)
+  }
+
+  /// The empty tiles where a pawn of `color` would complete a winning line,
+  /// with `color`'s other pawns where they are now. In phase 2, the pawn moved
+  /// there may have to leave the line to get there, so not every move onto
+  /// these tiles wins (see `immediate_wins` for those that do).
+  pub fn winning_squares(&self, color: PawnColor) -> Vec<PackedIdx> {
+    // Any tile that completes a line is next to a pawn of that line.
+    let mut squares = Vec::new();
+    for pawn in self.color_pawns(color) {
+      for pos in self.empty_adjacent_tiles(pawn.pos) {
+        if !squares.contains(&pos)
+          && self
+            .line_masks(pos, color)
+            .with_center(pos)
+            .has_line(self.variant.win_length())
+        {
+          squares.push(pos);
+        }
+      }
+    }
+    squares
+  }
+
+  /// A heuristic estimate of how favorable the game is for the current player,
+  /// for comparing positions the search couldn't determine the outcome of.
+  /// Positive values favor the current player, and negative values favor the
+  /// opponent.
+  ///
+  /// The estimate weighs the difference between the two players in lines of
+  /// three they threaten to complete, in mobility, and in how compactly their
+  /// pawns are gathered around the center of mass.
+  pub fn evaluate(&self) -> i32 {
+    const THREAT_WEIGHT: i32 = 16;
+    const MOBILITY_WEIGHT: i32 = 1;
+    const CENTRALIZATION_WEIGHT: i32 = 2;
+
+    let player = self.player_color();
+    let opponent = match player {
+      PawnColor::Black => PawnColor::White,
+      PawnColor::White => PawnColor::Black,
+    };
+    let pawns_in_play = self.pawns_in_play() as i32;
+
+    let threats = self.threats(player) as i32 - self.threats(opponent) as i32;
+    let mobility = self.mobility(player) as i32 - self.mobility(opponent) as i32;
+    // Lower centralization is better, and it is scaled by the number of pawns
+    // in play.
+    let centralization =
+      (self.centralization(opponent) as i32 - self.centralization(player) as i32) / pawns_in_play;
+
+    THREAT_WEIGHT * threats + MOBILITY_WEIGHT * mobility + CENTRALIZATION_WEIGHT * centralization
+  }
+
   /// Returns the origin tile, which all group operations operate with respect
   /// to. This is orientation-invariant, meaning for any symmetry of this board
   /// state, the same origin tile will be chosen.
@@ -409,7 +887,22 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
   }
 
   pub fn in_phase1(&self) -> bool {
-    self.onoro_state().turn() < 0xf
+    self.onoro_state().turn() < N as u32 - 1
+  }
+
+  /// The phase the game is currently in.
+  pub fn phase(&self) -> Phase {
+    if self.in_phase1() {
+      Phase::Phase1
+    } else {
+      Phase::Phase2
+    }
+  }
+
+  /// The number of moves (from both players) remaining until the game enters
+  /// phase 2. This is 0 if the game is already in phase 2.
+  pub fn plies_until_phase2(&self) -> u32 {
+    (N as u32 - 1).saturating_sub(self.onoro_state().turn())
   }
 
   /// Make move without checking that we are in the right phase.
@@ -418,6 +911,12 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
   /// This function should not be called unless the move being made is
   /// certainly in the right phase.
   pub unsafe fn make_move_unchecked(&mut self, m: Move) {
+    #[cfg(feature = "move-metadata")]
+    let prev_pos = match m {
+      Move::Phase1Move { .. } => PackedIdx::null(),
+      Move::Phase2Move { from_idx, .. } => self.pawn_poses[from_idx as usize],
+    };
+
     match m {
       Move::Phase1Move { to } => {
         // Increment the turn first, so self.onoro_state().turn() is 0 for turn
@@ -431,6 +930,49 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
         self.move_pawn(from_idx as usize, to);
       }
     }
+
+    #[cfg(feature = "move-metadata")]
+    self.record_last_move(m, prev_pos);
+  }
+
+  /// Records `m`, which was just made, as the last move, where `prev_pos` is
+  /// the position of the pawn it moved before the move, or null if it placed
+  /// a pawn.
+  #[cfg(feature = "move-metadata")]
+  fn record_last_move(&mut self, m: Move, prev_pos: PackedIdx) {
+    let (pawn_idx, to) = match m {
+      Move::Phase1Move { to } => (self.onoro_state().turn() as usize, to),
+      Move::Phase2Move { to, from_idx } => (from_idx as usize, to),
+    };
+    // The move may have shifted the board, which moved the tile the pawn came
+    // from along with every pawn.
+    let from = (prev_pos != PackedIdx::null()).then(|| {
+      let mut from = prev_pos;
+      from += IdxOffset::from(Self::calc_move_shift(&to));
+      from
+    });
+    self.state.record_move(LastMove {
+      pawn_idx: pawn_idx as u8,
+      from,
+      to: self.pawn_poses[pawn_idx],
+    });
+  }
+
+  /// The last move made, or `None` if no move has been made since the game
+  /// was started or decompressed. Games made from board strings start with
+  /// the pawn marked as the last moved, if any, as their last move.
+  #[cfg(feature = "move-metadata")]
+  pub fn last_move(&self) -> Option<LastMove> {
+    self.onoro_state().last_move()
+  }
+
+  /// The number of moves made to reach this game, counting the placement of
+  /// every pawn in play, including the starting pawns. Unlike
+  /// `pawns_in_play`, this keeps counting in phase 2, though only the phase 2
+  /// moves made since the game was created from a board or decompressed.
+  #[cfg(feature = "move-metadata")]
+  pub fn move_number(&self) -> u32 {
+    self.pawns_in_play() + self.onoro_state().phase2_moves()
   }
 
   pub fn make_move(&mut self, m: Move) {
@@ -445,6 +987,219 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
     unsafe { self.make_move_unchecked(m) }
   }
 
+  /// Makes move `m` if it is legal, or returns an error listing the rules it
+  /// breaks (see `OnoroError::violations`) and leaves the game unchanged.
+  /// Unlike `make_move`, which only checks the phase of the move in debug
+  /// builds, this is safe to call with moves from untrusted sources, like
+  /// clients of the server.
+  pub fn try_make_move(&mut self, m: Move) -> OnoroResult<()> {
+    if self.finished().is_some() || !self.each_move().any(|legal_move| legal_move == m) {
+      let violations = self.explain_illegal(m);
+      let message = match violations.first() {
+        Some(violation) => format!("Illegal move {m}: {violation}"),
+        None => format!("Illegal move {m}"),
+      };
+      return Err(OnoroError::illegal_move(&message, violations));
+    }
+
+    self.make_move(m);
+    Ok(())
+  }
+
+  /// Makes move `m`, returning an `Undo` which `Onoro::undo_move` can use to
+  /// take the move back. This lets searches mutate a single game state in
+  /// place, instead of cloning it for every child.
+  pub fn make_move_with_undo(&mut self, m: Move) -> Undo {
+    let (pawn_idx, to) = match m {
+      // The new pawn goes in the slot after the current turn, see
+      // `Onoro::make_move_unchecked`.
+      Move::Phase1Move { to } => (self.onoro_state().turn() as usize + 1, to),
+      Move::Phase2Move { to, from_idx } => (from_idx as usize, to),
+    };
+    let undo = Undo {
+      pawn_idx: pawn_idx as u8,
+      prev_pos: self.pawn_poses[pawn_idx],
+      shift: Self::calc_move_shift(&to),
+      state: self.onoro_state().clone(),
+      sum_of_mass: self.sum_of_mass,
+      symm_state: self.symm_state,
+    };
+
+    self.make_move(m);
+    undo
+  }
+
+  /// Takes back the move `undo` was returned for by
+  /// `Onoro::make_move_with_undo`. This must be the most recent move made that
+  /// hasn't been undone.
+  pub fn undo_move(&mut self, undo: Undo) {
+    let pawn_idx = undo.pawn_idx as usize;
+    if undo.shift != HexPosOffset::origin() {
+      let idx_offset = IdxOffset::from(undo.shift * -1);
+      self.pawn_poses.iter_mut().for_each(|pos| {
+        if *pos != PackedIdx::null() {
+          *pos += idx_offset;
+        }
+      });
+      self.occupied.shift(-Self::ord_offset(&undo.shift));
+    }
+
+    self
+      .occupied
+      .remove(Self::hex_pos_ord(&self.pawn_poses[pawn_idx].into()));
+    self.pawn_poses[pawn_idx] = undo.prev_pos;
+    if undo.prev_pos != PackedIdx::null() {
+      self
+        .occupied
+        .insert(Self::hex_pos_ord(&undo.prev_pos.into()));
+    }
+
+    self.state = undo.state;
+    self.sum_of_mass = undo.sum_of_mass;
+    self.symm_state = undo.symm_state;
+  }
+
+  /// Parses a move in the notation of `Move::to_notation` for this game
+  /// state, e.g. "b@c4" or "c4-d5". This only checks that the notation names
+  /// a move of the current player, i.e. that they are placing a pawn of their
+  /// own color or moving one of their own pawns. Use `explain_illegal` to
+  /// check that the move follows the rules.
+  pub fn parse_move(&self, notation: &str) -> OnoroResult<Move> {
+    let notation = notation.trim();
+    if let Some((color, to)) = notation.split_once('@') {
+      let color: PawnColor = color.parse()?;
+      if color != self.player_color() {
+        return Err(make_onoro_error!(
+          "Can't place a {color} pawn on {}'s turn",
+          self.player_color()
+        ));
+      }
+      return Ok(Move::Phase1Move {
+        to: parse_square(to)?,
+      });
+    }
+
+    let (from, to) = notation.split_once('-').ok_or_else(|| {
+      make_onoro_error!("Expected a move like \"b@c4\" or \"c4-d5\", found \"{notation}\"")
+    })?;
+    let from_pos = parse_square(from)?;
+    let from_idx = self
+      .get_pawn_idx(from_pos)
+      .filter(|&from_idx| (from_idx % 2 == 0) == (self.player_color() == PawnColor::Black))
+      .ok_or_else(|| {
+        make_onoro_error!(
+          "There is no {} pawn on {}",
+          self.player_color(),
+          from.trim()
+        )
+      })?;
+    Ok(Move::Phase2Move {
+      to: parse_square(to)?,
+      from_idx,
+    })
+  }
+
+  /// Returns every rule that making move `m` would violate, or an empty list if
+  /// `m` is a legal move.
+  pub fn explain_illegal(&self, m: Move) -> Vec<RuleViolation> {
+    let mut violations = Vec::new();
+
+    if self.finished().is_some() {
+      violations.push(RuleViolation::GameFinished);
+    }
+    if m.phase() != self.phase() {
+      violations.push(RuleViolation::WrongPhase {
+        move_phase: m.phase(),
+        game_phase: self.phase(),
+      });
+    }
+
+    let (to, from) = match m {
+      Move::Phase1Move { to } => (to, None),
+      Move::Phase2Move { to, from_idx } => {
+        let from = self.pawn_position(from_idx);
+        let own_pawn = (from_idx % 2 == 0) == (self.player_color() == PawnColor::Black);
+        if from.is_none() || !own_pawn {
+          violations.push(RuleViolation::NotOwnPawn { from_idx });
+        }
+        (to, from.map(HexPos::from))
+      }
+    };
+
+    if self.get_tile(to) != TileState::Empty {
+      violations.push(RuleViolation::TileOccupied { pos: to });
+    }
+
+    // The positions of all pawns after making the move.
+    let to_pos = HexPos::from(to);
+    let mut pawns: Vec<HexPos> = self
+      .pawns()
+      .map(|pawn| HexPos::from(pawn.pos))
+      .filter(|&pos| Some(pos) != from && pos != to_pos)
+      .collect();
+    pawns.push(to_pos);
+    let neighbor_count = |pos: &HexPos| {
+      pos
+        .each_neighbor()
+        .filter(|neighbor| pawns.contains(neighbor))
+        .count() as u32
+    };
+
+    let to_neighbors = neighbor_count(&to_pos);
+    if (to_neighbors as u64) < MIN_NEIGHBORS_PER_PAWN {
+      violations.push(RuleViolation::TooFewNeighbors {
+        pos: to,
+        neighbors: to_neighbors,
+      });
+    }
+
+    // Placing a pawn can only add neighbors, so the remaining checks only apply
+    // to moving pawns.
+    if from.is_none() {
+      return violations;
+    }
+
+    for pos in pawns.iter().filter(|&&pos| pos != to_pos) {
+      let neighbors = neighbor_count(pos);
+      if (neighbors as u64) < MIN_NEIGHBORS_PER_PAWN {
+        violations.push(RuleViolation::PawnIsolated {
+          pos: (*pos).into(),
+          neighbors,
+        });
+      }
+    }
+
+    // Flood fill from each unvisited pawn to find the connected groups.
+    let mut groups: Vec<Vec<PackedIdx>> = Vec::new();
+    let mut visited = vec![false; pawns.len()];
+    for start in 0..pawns.len() {
+      if visited[start] {
+        continue;
+      }
+      visited[start] = true;
+
+      let mut group = Vec::new();
+      let mut to_visit = vec![start];
+      while let Some(idx) = to_visit.pop() {
+        group.push(PackedIdx::from(pawns[idx]));
+        for neighbor in pawns[idx].each_neighbor() {
+          if let Some(neighbor_idx) = pawns.iter().position(|&pos| pos == neighbor) {
+            if !visited[neighbor_idx] {
+              visited[neighbor_idx] = true;
+              to_visit.push(neighbor_idx);
+            }
+          }
+        }
+      }
+      groups.push(group);
+    }
+    if groups.len() > 1 {
+      violations.push(RuleViolation::BoardDisconnected { groups });
+    }
+
+    violations
+  }
+
   pub fn each_move_gen(&self) -> MoveGenerator<N, N2, ADJ_CNT_SIZE> {
     if self.in_phase1() {
       MoveGenerator::P1Moves(self.p1_move_gen())
@@ -453,10 +1208,71 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
     }
   }
 
+  /// Iterates over the legal moves of the player to move. In phase 2 this can
+  /// be empty, if every pawn of the player is either pinned in place by the
+  /// board staying connected or has nowhere to go with two neighbors. A player
+  /// with no legal moves loses the game, there is no passing.
   pub fn each_move(&self) -> GameIterator<'_, MoveGenerator<N, N2, ADJ_CNT_SIZE>, Self> {
     self.each_move_gen().to_iter(self)
   }
 
+  /// Appends all legal moves to `arena`, returning the moves that were added.
+  /// This does not allocate once the arena has grown large enough, so solvers
+  /// can reuse one arena for every node they expand. The moves are generated
+  /// with `MoveGenerator::collect_into`, in the order `each_move` yields them.
+  pub fn expand_into<'a>(&self, arena: &'a mut MoveArena<Move>) -> &'a [Move] {
+    let mut moves = ArrayVec::new();
+    self.each_move_gen().collect_into(self, &mut moves);
+    arena.extend(moves)
+  }
+
+  /// The number of sequences of `depth` legal moves from this game state.
+  /// Finished games have no moves, so lines that end the game early aren't
+  /// counted. Comparing these counts before and after a change to the move
+  /// generator checks that it still generates exactly the same moves.
+  pub fn perft(&self, depth: u32) -> u64 {
+    self.clone().perft_in_place(depth)
+  }
+
+  /// Like `perft`, but split by the first move of each sequence.
+  pub fn perft_divide(&self, depth: u32) -> Vec<(Move, u64)> {
+    if depth == 0 || self.finished().is_some() {
+      return Vec::new();
+    }
+    let mut onoro = self.clone();
+    self
+      .each_move()
+      .map(|m| {
+        let undo = onoro.make_move_with_undo(m);
+        let count = onoro.perft_in_place(depth - 1);
+        onoro.undo_move(undo);
+        (m, count)
+      })
+      .collect()
+  }
+
+  fn perft_in_place(&mut self, depth: u32) -> u64 {
+    if depth == 0 {
+      return 1;
+    }
+    if self.finished().is_some() {
+      return 0;
+    }
+    let moves: Vec<_> = self.each_move().collect();
+    if depth == 1 {
+      return moves.len() as u64;
+    }
+    moves
+      .into_iter()
+      .map(|m| {
+        let undo = self.make_move_with_undo(m);
+        let count = self.perft_in_place(depth - 1);
+        self.undo_move(undo);
+        count
+      })
+      .sum()
+  }
+
   fn p1_move_gen(&self) -> P1MoveGenerator<N, N2, ADJ_CNT_SIZE> {
     debug_assert!(self.in_phase1());
     P1MoveGenerator {
@@ -481,8 +1297,10 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
     unsafe {
       *self.pawn_poses.get_unchecked_mut(i) = pos;
     }
+    self.occupied.insert(Self::hex_pos_ord(&pos.into()));
 
     self.sum_of_mass = (HexPos::from(self.sum_of_mass) + pos.into()).into();
+    self.update_symm_state();
     self.adjust_to_new_pawn_and_check_win(pos);
   }
 
@@ -501,8 +1319,11 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
     unsafe {
       *self.pawn_poses.get_unchecked_mut(i) = pos;
     }
+    self.occupied.remove(Self::hex_pos_ord(&prev_idx.into()));
+    self.occupied.insert(Self::hex_pos_ord(&pos.into()));
 
     self.sum_of_mass = (HexPos::from(self.sum_of_mass) + com_offset).into();
+    self.update_symm_state();
     self.adjust_to_new_pawn_and_check_win(pos);
   }
 
@@ -523,6 +1344,7 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
           *pos += idx_offset;
         }
       });
+      self.occupied.shift(Self::ord_offset(&shift));
       self.sum_of_mass =
         (HexPos::from(self.sum_of_mass) + shift * (self.pawns_in_play() as i32)).into();
     }
@@ -552,19 +1374,21 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
   }
 
   fn check_win(&self, last_move: HexPos) -> bool {
+    let won = self.check_win_fast(last_move);
+    #[cfg(feature = "verify-simd")]
+    assert_eq!(
+      won,
+      self.check_win_slow(last_move),
+      "check_win mismatch for last move {last_move} in\n{self}"
+    );
+    won
+  }
+
+  /// Bit-parallel implementation of `check_win`. Like `get_pawn_idx_fast`, it
+  /// doesn't depend on any target-specific instructions.
+  pub(crate) fn check_win_fast(&self, last_move: HexPos) -> bool {
     // Bitvector of positions occupied by pawns of this color along the 3 lines
-    // extending out from last_move. Intentionally leave a zero bit between each
-    // of the 3 sets so they can't form a continuous string of 1's across
-    // borders.
-    // - s[0-15]: line running along the x-axis, with bit i corresponding to
-    //     (x, i)
-    // - s[17-32]: line running along the line x = y, with bit i corresponding to
-    //     (x - min(x, y) + i, y - min(x, y) + i).
-    // - s[34-49]: line running along the y-axis, with bit i corresponding to
-    //     (i, y)
-    // let mut s = (0x1u64 << last_move.x())
-    //   | (0x20000u64 << cmp::min(last_move.x(), last_move.y()))
-    //   | (0x400000000u64 << last_move.y());
+    // extending out from last_move, laid out as in `LineMasks`.
     let mut s = 0;
 
     // Unsafe pawn iteration: rely on the fact that idx_t::null_idx() will not
@@ -579,25 +1403,60 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
       .step_by(2)
     {
       let pos: HexPos = unsafe { *self.pawn_poses.get_unchecked(i) }.into();
-      let delta = pos - last_move;
-      let dx = delta.x();
-      let dy = delta.y();
+      s |= LineMasks::tile_bits(pos, last_move);
+    }
+
+    LineMasks(s).has_line(self.variant.win_length())
+  }
 
-      s |= if dy == 0 { 0x1u64 } else { 0 } << pos.x();
-      s |= if dx == dy { 0x20000u64 } else { 0 } << cmp::min(pos.x(), pos.y());
-      s |= if dx == 0 { 0x400000000u64 } else { 0 } << pos.y();
+  /// Whether move `m` would complete a winning line for `color`, were it
+  /// `color`'s turn. Phase 1 moves are taken to place a pawn of `color`. This
+  /// doesn't check that `m` is legal.
+  pub(crate) fn completes_line(&self, color: PawnColor, m: Move) -> bool {
+    let mut masks = self.line_masks(m.to(), color).with_center(m.to());
+    if let Move::Phase2Move { from_idx, .. } = m {
+      // The moved pawn set its own bits, so it can be taken back out.
+      masks.0 &= !LineMasks::tile_bits(self.pawn_poses[from_idx as usize].into(), m.to().into());
     }
+    masks.has_line(self.variant.win_length())
+  }
+
+  /// Scalar implementation of `check_win`, which walks along each line through
+  /// `last_move` counting the pawns of the player who just moved.
+  #[cfg(any(test, feature = "verify-simd"))]
+  pub(crate) fn check_win_slow(&self, last_move: HexPos) -> bool {
+    let color = if self.onoro_state().black_turn() {
+      TileState::White
+    } else {
+      TileState::Black
+    };
+    let is_color = |pos: HexPosOffset| {
+      (0..N as i32).contains(&pos.x())
+        && (0..N as i32).contains(&pos.y())
+        && self.get_tile_slow(PackedIdx::new(pos.x() as u32, pos.y() as u32)) == color
+    };
 
-    // Check if any 4 bits in a row are set:
-    s = s & (s << 2);
-    s = s & (s << 1);
-    s != 0
+    let origin = last_move - HexPos::zero();
+    [
+      HexPosOffset::new(1, 0),
+      HexPosOffset::new(0, 1),
+      HexPosOffset::new(1, 1),
+    ]
+    .into_iter()
+    .any(|dir| {
+      let run = |dir: HexPosOffset| {
+        (1..)
+          .take_while(|&steps| is_color(origin + dir * steps))
+          .count()
+      };
+      is_color(origin) && 1 + run(dir) + run(dir * -1) >= self.variant.win_length() as usize
+    })
   }
 
   /// Given a position on the board, returns the tile state of that position,
   /// i.e. the color of the piece on that tile, or `Empty` if no piece is there.
-  #[cfg(test)]
-  fn get_tile_slow(&self, idx: PackedIdx) -> TileState {
+  #[cfg(any(test, feature = "verify-simd"))]
+  pub(crate) fn get_tile_slow(&self, idx: PackedIdx) -> TileState {
     if idx == PackedIdx::null() {
       return TileState::Empty;
     }
@@ -619,9 +1478,47 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
     }
   }
 
+  /// The position of the pawn at index `pawn_idx` of the pawn list, or
+  /// `None` if it hasn't been placed.
+  pub(crate) fn pawn_position(&self, pawn_idx: u32) -> Option<PackedIdx> {
+    self
+      .pawn_poses
+      .get(pawn_idx as usize)
+      .copied()
+      .filter(|&pos| pos != PackedIdx::null())
+  }
+
   /// Given a position on the board, returns the index of the pawn with that
   /// position, or `None` if no such pawn exists.
-  fn get_pawn_idx(&self, idx: PackedIdx) -> Option<u32> {
+  pub(crate) fn get_pawn_idx(&self, idx: PackedIdx) -> Option<u32> {
+    let pawn_idx = self.get_pawn_idx_fast(idx);
+    #[cfg(feature = "verify-simd")]
+    assert_eq!(
+      pawn_idx,
+      self.get_pawn_idx_slow(idx),
+      "get_pawn_idx mismatch for {} in\n{self}",
+      HexPos::from(idx)
+    );
+    pawn_idx
+  }
+
+  /// Scalar implementation of `get_pawn_idx`.
+  #[cfg(any(test, feature = "verify-simd"))]
+  pub(crate) fn get_pawn_idx_slow(&self, idx: PackedIdx) -> Option<u32> {
+    if idx == PackedIdx::null() {
+      return None;
+    }
+    self
+      .pawn_poses
+      .iter()
+      .position(|&pos| pos == idx)
+      .map(|i| i as u32)
+  }
+
+  /// SWAR implementation of `get_pawn_idx`, which searches 8 pawn positions at
+  /// a time. This only uses 64-bit integer operations, so it is the fast path
+  /// on every target, including aarch64.
+  pub(crate) fn get_pawn_idx_fast(&self, idx: PackedIdx) -> Option<u32> {
     if idx == PackedIdx::null() {
       return None;
     }
@@ -653,9 +1550,36 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
     None
   }
 
-  /// Given a position on the board, returns the tile state of that position,
-  /// i.e. the color of the piece on that tile, or `Empty` if no piece is there.
+  /// True if there is a pawn at `pos`. `pos` may be just off the edge of the
+  /// board, where there are never pawns.
+  pub(crate) fn is_occupied(&self, pos: HexPos) -> bool {
+    self.occupied.contains(Self::hex_pos_ord(&pos))
+  }
+
+  /// Recomputes `occupied` from `pawn_poses`.
+  fn rebuild_occupied(&mut self) {
+    self.occupied = Bitboard::new();
+    for &pos in &self.pawn_poses {
+      if pos != PackedIdx::null() {
+        self.occupied.insert(Self::hex_pos_ord(&pos.into()));
+      }
+    }
+  }
+
+  /// Given a position on the board, returns the tile state of that position,
+  /// i.e. the color of the piece on that tile, or `Empty` if no piece is there.
   pub(crate) fn get_tile(&self, idx: PackedIdx) -> TileState {
+    if !self.is_occupied(idx.into()) {
+      #[cfg(feature = "verify-simd")]
+      assert_eq!(
+        self.get_pawn_idx_slow(idx),
+        None,
+        "Bitboard is missing {} in\n{self}",
+        HexPos::from(idx)
+      );
+      return TileState::Empty;
+    }
+
     match self.get_pawn_idx(idx) {
       Some(i) => {
         if i % 2 == 0 {
@@ -719,7 +1643,7 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
       HexPos::from(pawn.pos)
         .each_top_left_neighbor()
         .for_each(|neighbor_pos| {
-          if self.get_tile(neighbor_pos.into()) != TileState::Empty {
+          if self.is_occupied(neighbor_pos) {
             uf.union(
               Self::hex_pos_ord(&HexPos::from(pawn.pos)),
               Self::hex_pos_ord(&neighbor_pos),
@@ -736,6 +1660,14 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
       ));
     }
 
+    if self.occupied.count() != self.pawns_in_play() {
+      return Err(make_onoro_error!(
+        "Expected {} occupied tiles, but found {}",
+        self.pawns_in_play(),
+        self.occupied.count()
+      ));
+    }
+
     if self.in_phase1() && self.onoro_state().black_turn() as u32 != (self.onoro_state().turn() & 1)
     {
       return Err(make_onoro_error!(
@@ -770,6 +1702,14 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
       ));
     }
 
+    if self.symm_state() != compute_board_symm_state(self) {
+      return Err(make_onoro_error!(
+        "Symmetry state not correct: expect {:?}, but have {:?}",
+        compute_board_symm_state(self),
+        self.symm_state()
+      ));
+    }
+
     let empty_tiles = Self::board_size() - self.pawns_in_play() as usize;
     let pawn_groups = uf.unique_sets() - empty_tiles;
 
@@ -784,6 +1724,71 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Onoro<N, N2, AD
   }
 }
 
+/// The compressed form of an Onoro game state is the packed position of every
+/// pawn, in order of pawn index, followed by the packed game state byte. The
+/// sum of mass is recomputed from the pawns.
+///
+/// The state byte holds whose turn it is, whether the game is finished, and
+/// the turn count, which gives the number of pawns in play and so the phase of
+/// the game. Pawns that haven't been placed yet are null, so phase 1 boards
+/// with any number of pawns decompress exactly, without guessing.
+impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Compress
+  for Onoro<N, N2, ADJ_CNT_SIZE>
+{
+  const COMPRESSED_SIZE: usize = N + 1;
+
+  fn compress(&self, bytes: &mut [u8]) {
+    debug_assert_eq!(bytes.len(), Self::COMPRESSED_SIZE);
+    for (byte, pos) in bytes.iter_mut().zip(self.pawn_poses.iter()) {
+      *byte = unsafe { pos.bytes() };
+    }
+    bytes[N] = self.state.data();
+  }
+
+  fn decompress(bytes: &[u8]) -> Option<Self> {
+    if bytes.len() != Self::COMPRESSED_SIZE {
+      return None;
+    }
+
+    let mut game = unsafe { Self::new() };
+    for (pos, &byte) in game.pawn_poses.iter_mut().zip(bytes.iter()) {
+      *pos = PackedIdx::from_bytes(byte);
+    }
+    game.rebuild_occupied();
+    game.state = OnoroState::from_data(bytes[N]);
+
+    // Pawns that haven't been placed yet must not appear on the board.
+    let pawns_in_play = game.pawns_in_play() as usize;
+    if pawns_in_play > N
+      || game.pawn_poses[pawns_in_play..]
+        .iter()
+        .any(|&pos| pos != PackedIdx::null())
+    {
+      return None;
+    }
+
+    let mut sum_of_mass = HexPos::zero();
+    for pawn in game.pawns() {
+      sum_of_mass += pawn.pos.into();
+    }
+    game.sum_of_mass = sum_of_mass.into();
+    game.update_symm_state();
+    game.validate().ok()?;
+
+    // Every pawn has at least `MIN_NEIGHBORS_PER_PAWN` neighbors once enough
+    // pawns have been placed, since no move can leave a pawn with fewer.
+    if game.pawns_in_play() as u64 > MIN_NEIGHBORS_PER_PAWN
+      && game
+        .pawns()
+        .any(|pawn| (game.occupied_neighbors(pawn.pos).count() as u64) < MIN_NEIGHBORS_PER_PAWN)
+    {
+      return None;
+    }
+
+    Some(game)
+  }
+}
+
 impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Debug
   for Onoro<N, N2, ADJ_CNT_SIZE>
 {
@@ -850,12 +1855,126 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Display
   }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// The part of a game state that making a move changes, for taking back the
+/// move with `Onoro::undo_move`.
+#[derive(Clone, Debug)]
+pub struct Undo {
+  /// The index of the pawn that was placed or moved.
+  pawn_idx: u8,
+  /// The position of that pawn before the move, which is null for pawns
+  /// placed in phase 1.
+  prev_pos: PackedIdx,
+  /// The amount the move shifted every pawn by, to keep pawns off the edge of
+  /// the board.
+  shift: HexPosOffset,
+  state: OnoroState,
+  sum_of_mass: PackedHexPos,
+  symm_state: u8,
+}
+
+/// The tiles occupied by one color's pawns along the three lines through a
+/// center tile, as bitmasks, from `Onoro::line_masks`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LineMasks(u64);
+
+impl LineMasks {
+  /// The line along the x-axis, with bit `i` for the tile `(i, y)`.
+  pub const fn x_line(&self) -> u16 {
+    self.0 as u16
+  }
+
+  /// The line along the diagonal `x - y = c`, with bit `i` for the tile
+  /// `(x - min(x, y) + i, y - min(x, y) + i)`.
+  pub const fn diagonal(&self) -> u16 {
+    (self.0 >> 17) as u16
+  }
+
+  /// The line along the y-axis, with bit `i` for the tile `(x, i)`.
+  pub const fn y_line(&self) -> u16 {
+    (self.0 >> 34) as u16
+  }
+
+  /// These masks with the center tile, `center`, occupied as well.
+  pub fn with_center(self, center: PackedIdx) -> Self {
+    let center = HexPos::from(center);
+    Self(self.0 | Self::tile_bits(center, center))
+  }
+
+  /// The bits of a pawn at `pos` in the masks of the lines through `center`.
+  /// Pawns on different tiles of the same line set different bits, and pawns
+  /// on none of the lines set none.
+  #[inline(always)]
+  pub(crate) fn tile_bits(pos: HexPos, center: HexPos) -> u64 {
+    let delta = pos - center;
+    let dx = delta.x();
+    let dy = delta.y();
+
+    (if dy == 0 { 0x1u64 } else { 0 } << pos.x())
+      | (if dx == dy { 0x20000u64 } else { 0 } << cmp::min(pos.x(), pos.y()))
+      | (if dx == 0 { 0x400000000u64 } else { 0 } << pos.y())
+  }
+
+  /// Checks if any line has `win_length` occupied tiles in a row. The three
+  /// lines are packed into one `u64` with a zero bit between each, so runs
+  /// never cross from one line to the next.
+  #[inline(always)]
+  pub fn has_line(&self, win_length: u32) -> bool {
+    // Each set bit of `run` ends a run of `len` set bits. Masking `run` with
+    // itself shifted by at most `len` extends the runs it marks by the shift,
+    // so `len` doubles each step until it is close to `win_length`.
+    let mut run = self.0;
+    let mut len = 1;
+    while 2 * len <= win_length {
+      run &= run << len;
+      len *= 2;
+    }
+    if len < win_length {
+      run &= run << (win_length - len);
+    }
+    run != 0
+  }
+}
+
+/// Annotations of a board drawing, see
+/// `Onoro::from_board_string_with_metadata`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BoardMetadata {
+  /// The player to move, if given by a "turn" header.
+  pub turn: Option<PawnColor>,
+  /// The position of the pawn marked as the last moved, if any.
+  pub last_moved: Option<PackedIdx>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PawnColor {
   Black,
   White,
 }
 
+impl Display for PawnColor {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      PawnColor::Black => write!(f, "black"),
+      PawnColor::White => write!(f, "white"),
+    }
+  }
+}
+
+impl FromStr for PawnColor {
+  type Err = OnoroError;
+
+  /// Accepts "B"/"W" or "black"/"white", ignoring case.
+  fn from_str(s: &str) -> OnoroResult<Self> {
+    match s.trim().to_ascii_lowercase().as_str() {
+      "b" | "black" => Ok(PawnColor::Black),
+      "w" | "white" => Ok(PawnColor::White),
+      _ => Err(make_onoro_error!(
+        "Invalid pawn color \"{s}\", expected one of \"B\", \"W\", \"black\", or \"white\""
+      )),
+    }
+  }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct Pawn {
   pub pos: PackedIdx,
@@ -877,6 +1996,7 @@ impl Display for Pawn {
   }
 }
 
+#[derive(Clone)]
 pub struct PawnMoveGenerator<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> {
   pawn_idx: usize,
   /// If true, only iterates over pawns of one color, otherwise iterating over
@@ -910,11 +2030,61 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> GameMoveGenerat
   }
 }
 
+/// An upper bound on the number of legal moves of any state of a game with `n`
+/// pawns.
+///
+/// Every move puts a pawn on an empty tile next to at least two of the other
+/// pawns. At most `n - 1` other pawns are on the board, with 6 neighbors each,
+/// so there are at most `3 * (n - 1)` such tiles. In phase 1 that bounds the
+/// moves, and in phase 2 each of the `n / 2` pawns of the player to move can
+/// go to at most that many tiles.
+pub const fn max_moves(n: usize) -> usize {
+  3 * (n - 1) * (n / 2)
+}
+
+/// An upper bound on the number of legal moves of any state of any Onoro
+/// variant, the capacity of the buffers `MoveGenerator::collect_into` fills.
+pub const MAX_MOVES: usize = max_moves(16);
+
 pub enum MoveGenerator<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> {
   P1Moves(P1MoveGenerator<N, N2, ADJ_CNT_SIZE>),
   P2Moves(P2MoveGenerator<N, N2, ADJ_CNT_SIZE>),
 }
 
+impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>
+  MoveGenerator<N, N2, ADJ_CNT_SIZE>
+{
+  /// Pushes every legal move of `onoro` onto `moves`, in the order the
+  /// generator would yield them, which must be fresh from
+  /// `Onoro::each_move_gen`. This generates all moves in one pass, without
+  /// saving the generator's state between moves like `next` has to, so it's
+  /// the faster way to expand a state when all of its moves are needed.
+  pub fn collect_into(
+    &self,
+    onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+    moves: &mut ArrayVec<Move, MAX_MOVES>,
+  ) {
+    const {
+      assert!(
+        max_moves(N) <= MAX_MOVES,
+        "MAX_MOVES is too small for N pawns"
+      );
+    }
+
+    #[cfg(feature = "verify-simd")]
+    let start = moves.len();
+    match self {
+      Self::P1Moves(p1_gen) => p1_gen.collect_into(onoro, moves),
+      Self::P2Moves(p2_gen) => p2_gen.collect_into(onoro, moves),
+    }
+    #[cfg(feature = "verify-simd")]
+    assert!(
+      moves[start..].iter().copied().eq(onoro.each_move()),
+      "collect_into mismatch for\n{onoro}"
+    );
+  }
+}
+
 impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> GameMoveGenerator
   for MoveGenerator<N, N2, ADJ_CNT_SIZE>
 {
@@ -947,7 +2117,7 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> GameMoveGenerat
   fn next(&mut self, onoro: &Self::Game) -> Option<Self::Item> {
     loop {
       if let Some(neighbor) = self.neighbor_iter.as_mut().and_then(|iter| iter.next()) {
-        if onoro.get_tile(neighbor.into()) != TileState::Empty {
+        if onoro.is_occupied(neighbor) {
           continue;
         }
 
@@ -980,6 +2150,43 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> GameMoveGenerat
   }
 }
 
+impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>
+  P1MoveGenerator<N, N2, ADJ_CNT_SIZE>
+{
+  /// Pushes every move `next` would yield onto `moves`, without saving the
+  /// search state between moves. `self` must not have been advanced.
+  fn collect_into(
+    &self,
+    onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+    moves: &mut ArrayVec<Move, MAX_MOVES>,
+  ) {
+    debug_assert!(self.pawn_iter.pawn_idx == 0 && self.neighbor_iter.is_none());
+    let mut adjacency_counts = self.adjacency_counts;
+    for pawn in &onoro.pawn_poses[..onoro.pawns_in_play() as usize] {
+      for neighbor in HexPos::from(*pawn).each_neighbor() {
+        if onoro.is_occupied(neighbor) {
+          continue;
+        }
+
+        let ord = Onoro::<N, N2, ADJ_CNT_SIZE>::hex_pos_ord(&neighbor);
+        let tb_shift = TILE_BITS * (ord % (64 / TILE_BITS));
+        let tbb = unsafe { adjacency_counts.get_unchecked_mut(ord / (64 / TILE_BITS)) };
+        let mask = TILE_MASK << tb_shift;
+        let full_mask = MIN_NEIGHBORS_PER_PAWN << tb_shift;
+
+        if (*tbb & mask) != full_mask {
+          *tbb += 1u64 << tb_shift;
+          if (*tbb & mask) == full_mask {
+            moves.push(Move::Phase1Move {
+              to: neighbor.into(),
+            });
+          }
+        }
+      }
+    }
+  }
+}
+
 struct P2PawnMeta<const N2: usize> {
   uf: ConstUnionFind<N2>,
   /// The index of the pawn being considered in `onoro.pawn_poses`.
@@ -1055,6 +2262,23 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>
     pawn_pos: PackedIdx,
     onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
   ) {
+    self.pawn_meta = Some(Self::lift_pawn(
+      &mut self.adjacency_counts,
+      pawn_idx,
+      pawn_pos,
+      onoro,
+    ));
+  }
+
+  /// Computes the `P2PawnMeta` of the pawn at `pawn_pos`, decreasing the
+  /// adjacency count of all of its neighbors in `adjacency_counts` as if it
+  /// were lifted off the board. Undone by `drop_pawn`.
+  fn lift_pawn(
+    adjacency_counts: &mut [u64; ADJ_CNT_SIZE],
+    pawn_idx: usize,
+    pawn_pos: PackedIdx,
+    onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+  ) -> P2PawnMeta<N2> {
     let mut uf = ConstUnionFind::new();
     let pawn_hex_pos: HexPos = pawn_pos.into();
 
@@ -1068,7 +2292,7 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>
       let pawn_ord = Onoro::<N, N2, ADJ_CNT_SIZE>::hex_pos_ord(&pawn.pos.into());
 
       for neighbor in HexPos::from(pawn.pos).each_top_left_neighbor() {
-        if onoro.get_tile(neighbor.into()) != TileState::Empty && pawn_hex_pos != neighbor {
+        if onoro.is_occupied(neighbor) && pawn_hex_pos != neighbor {
           uf.union(
             pawn_ord,
             Onoro::<N, N2, ADJ_CNT_SIZE>::hex_pos_ord(&neighbor),
@@ -1090,18 +2314,18 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>
       let tb_shift = TILE_BITS * (neighbor_ord % (64 / TILE_BITS));
 
       unsafe {
-        *self.adjacency_counts.get_unchecked_mut(tb_idx) -= 1u64 << tb_shift;
+        *adjacency_counts.get_unchecked_mut(tb_idx) -= 1u64 << tb_shift;
       }
       // If this neighbor has only one neighbor itself now, and it isn't empty,
       // we have to place our pawn next to it.
-      if ((unsafe { *self.adjacency_counts.get_unchecked(tb_idx) } >> tb_shift) & TILE_MASK) == 1
-        && onoro.get_tile(neighbor.into()) != TileState::Empty
+      if ((unsafe { *adjacency_counts.get_unchecked(tb_idx) } >> tb_shift) & TILE_MASK) == 1
+        && onoro.is_occupied(neighbor)
       {
         neighbors_to_satisfy += 1;
       }
     }
 
-    self.pawn_meta = Some(P2PawnMeta {
+    P2PawnMeta {
       uf,
       pawn_idx,
       pawn_pos,
@@ -1109,22 +2333,122 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>
       pawn_groups,
       adj_cnt_idx: 0,
       adj_cnt_bitmask: 0,
-    });
+    }
   }
 
   /// Cleans up the mutated data in `self` from `prepare_move_pawn`.
   fn cleanup_pawn_visit(&mut self, pawn_pos: PackedIdx) {
+    Self::drop_pawn(&mut self.adjacency_counts, pawn_pos);
+    self.pawn_meta = None;
+  }
+
+  /// Restores the adjacency counts decreased by `lift_pawn`.
+  fn drop_pawn(adjacency_counts: &mut [u64; ADJ_CNT_SIZE], pawn_pos: PackedIdx) {
     for neighbor in HexPos::from(pawn_pos).each_neighbor() {
       let neighbor_ord = Onoro::<N, N2, ADJ_CNT_SIZE>::hex_pos_ord(&neighbor);
       let tb_idx = neighbor_ord / (64 / TILE_BITS);
       let tb_shift = TILE_BITS * (neighbor_ord % (64 / TILE_BITS));
 
       unsafe {
-        *self.adjacency_counts.get_unchecked_mut(tb_idx) += 1u64 << tb_shift;
+        *adjacency_counts.get_unchecked_mut(tb_idx) += 1u64 << tb_shift;
       }
     }
+  }
 
-    self.pawn_meta = None;
+  /// True if the pawn described by `pawn_meta`, lifted off the board by
+  /// `lift_pawn`, can be placed on the empty tile `place_to_consider`, which
+  /// has at least two neighbors other than the pawn.
+  fn can_place(
+    adjacency_counts: &[u64; ADJ_CNT_SIZE],
+    pawn_meta: &mut P2PawnMeta<N2>,
+    place_to_consider: HexPos,
+    onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+  ) -> bool {
+    // A count of the number of neighbors with only one other adjacent pawn.
+    let mut n_satisfied = 0;
+    // The first group ID of any neighbor from the union find.
+    let mut g1 = None;
+    // The second group ID of any neighbor from the union find.
+    let mut g2 = None;
+    // The number of distinct groups of pawns adjacent to the place being
+    // considered.
+    let mut groups_touching = 0;
+    for neighbor in place_to_consider.each_neighbor() {
+      if !onoro.is_occupied(neighbor) {
+        continue;
+      }
+      let neighbor_ord = Onoro::<N, N2, ADJ_CNT_SIZE>::hex_pos_ord(&neighbor);
+
+      let tb_idx = neighbor_ord / (64 / TILE_BITS);
+      let tb_shift = TILE_BITS * (neighbor_ord % (64 / TILE_BITS));
+      if ((unsafe { *adjacency_counts.get_unchecked(tb_idx) } >> tb_shift) & TILE_MASK) == 1 {
+        n_satisfied += 1;
+      }
+
+      if neighbor != pawn_meta.pawn_pos.into() {
+        let group_id = pawn_meta.uf.find(neighbor_ord);
+        // There can be at most 3 distinct groups of pawns adjacent to this
+        // spot, since there are 6 neighboring tiles, and each tile touches
+        // two other neighbors. The first neighbor will assign its group ID
+        // to `g1`, the second distinct group ID will be assigned to `g2`,
+        // and if a third group ID is seen, it will reassign `g2` to it, but
+        // will also update `groups_touching`. In the end, `groups_touching`
+        // will be correct, which is all that matters.
+        if Some(group_id) != g1 {
+          if g1.is_none() {
+            g1 = Some(group_id);
+            groups_touching += 1;
+          } else if Some(group_id) != g2 {
+            g2 = Some(group_id);
+            groups_touching += 1;
+          }
+        }
+      }
+    }
+
+    n_satisfied == pawn_meta.neighbors_to_satisfy && groups_touching == pawn_meta.pawn_groups
+  }
+
+  /// Pushes every move `next` would yield onto `moves`, without saving the
+  /// search state between moves. `self` must not have been advanced.
+  fn collect_into(
+    &self,
+    onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+    moves: &mut ArrayVec<Move, MAX_MOVES>,
+  ) {
+    debug_assert!(self.pawn_iter.pawn_idx < 2 && self.pawn_meta.is_none());
+    let mut adjacency_counts = self.adjacency_counts;
+    let mut pawn_iter = self.pawn_iter.clone();
+    while let Some(pawn) = pawn_iter.next(onoro) {
+      let mut pawn_meta = Self::lift_pawn(
+        &mut adjacency_counts,
+        pawn.board_idx as usize,
+        pawn.pos,
+        onoro,
+      );
+      for (adj_cnt_idx, &adj_cnt) in adjacency_counts.iter().enumerate() {
+        let mut adj_cnt_bitmask = adj_cnt;
+        while adj_cnt_bitmask != 0 {
+          let next_idx_ord_off = adj_cnt_bitmask.trailing_zeros() / TILE_BITS as u32;
+          let tb_shift = next_idx_ord_off * TILE_BITS as u32;
+          let next_idx_ord = next_idx_ord_off as usize + adj_cnt_idx * (64 / TILE_BITS);
+          let neighbor_count = (adj_cnt_bitmask >> tb_shift) & TILE_MASK;
+          adj_cnt_bitmask &= !(TILE_MASK << tb_shift);
+
+          let place_to_consider = Onoro::<N, N2, ADJ_CNT_SIZE>::ord_to_hex_pos(next_idx_ord);
+          if neighbor_count <= 1 || onoro.is_occupied(place_to_consider) {
+            continue;
+          }
+          if Self::can_place(&adjacency_counts, &mut pawn_meta, place_to_consider, onoro) {
+            moves.push(Move::Phase2Move {
+              to: PackedIdx::from(place_to_consider),
+              from_idx: pawn_meta.pawn_idx as u32,
+            });
+          }
+        }
+      }
+      Self::drop_pawn(&mut adjacency_counts, pawn.pos);
+    }
   }
 }
 
@@ -1166,7 +2490,7 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> GameMoveGenerat
 
         // Skip this tile if it isn't empty (this will also skip the piece's
         // old location since we haven't removed it, which we want)
-        if onoro.get_tile(place_to_consider_idx) != TileState::Empty
+        if onoro.is_occupied(place_to_consider)
           || ((pawn_meta.adj_cnt_bitmask >> tb_shift) & TILE_MASK) <= 1
         {
           pawn_meta.adj_cnt_bitmask &= !clr_mask;
@@ -1177,52 +2501,7 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> GameMoveGenerat
         // currently, so we don't try it again next loop.
         pawn_meta.adj_cnt_bitmask &= !clr_mask;
 
-        // A count of the number of neighbors with only one other adjacent pawn.
-        let mut n_satisfied = 0;
-        // The first group ID of any neighbor from the union find.
-        let mut g1 = None;
-        // The second group ID of any neighbor from the union find.
-        let mut g2 = None;
-        // The number of distinct groups of pawns adjacent to the place being
-        // considered.
-        let mut groups_touching = 0;
-        for neighbor in place_to_consider.each_neighbor() {
-          if onoro.get_tile(neighbor.into()) == TileState::Empty {
-            continue;
-          }
-          let neighbor_ord = Onoro::<N, N2, ADJ_CNT_SIZE>::hex_pos_ord(&neighbor);
-
-          let tb_idx = neighbor_ord / (64 / TILE_BITS);
-          let tb_shift = TILE_BITS * (neighbor_ord % (64 / TILE_BITS));
-          if ((unsafe { *self.adjacency_counts.get_unchecked(tb_idx) } >> tb_shift) & TILE_MASK)
-            == 1
-          {
-            n_satisfied += 1;
-          }
-
-          if neighbor != pawn_meta.pawn_pos.into() {
-            let group_id = pawn_meta.uf.find(neighbor_ord);
-            // There can be at most 3 distinct groups of pawns adjacent to this
-            // spot, since there are 6 neighboring tiles, and each tile touches
-            // two other neighbors. The first neighbor will assign its group ID
-            // to `g1`, the second distinct group ID will be assigned to `g2`,
-            // and if a third group ID is seen, it will reassign `g2` to it, but
-            // will also update `groups_touching`. In the end, `groups_touching`
-            // will be correct, which is all that matters.
-            if Some(group_id) != g1 {
-              if g1.is_none() {
-                g1 = Some(group_id);
-                groups_touching += 1;
-              } else if Some(group_id) != g2 {
-                g2 = Some(group_id);
-                groups_touching += 1;
-              }
-            }
-          }
-        }
-
-        if n_satisfied == pawn_meta.neighbors_to_satisfy && groups_touching == pawn_meta.pawn_groups
-        {
+        if Self::can_place(&self.adjacency_counts, pawn_meta, place_to_consider, onoro) {
           return Some(Move::Phase2Move {
             to: place_to_consider_idx,
             from_idx: pawn_meta.pawn_idx as u32,
@@ -1239,7 +2518,20 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> GameMoveGenerat
 
 #[cfg(test)]
 mod tests {
-  use crate::{onoro_defs::Onoro8, packed_idx::PackedIdx};
+  use abstract_game::{Compress, Game};
+  use arrayvec::ArrayVec;
+  use rand::{rngs::StdRng, SeedableRng};
+
+  use crate::{
+    groups::D6,
+    hex_pos::{HexPos, HexPosOffset},
+    onoro_defs::{Onoro16, Onoro8, Onoro8View},
+    packed_idx::PackedIdx,
+    r#move::{Move, Phase},
+    rule_violation::RuleViolation,
+    testing::{random_playout, random_states},
+    BoardMetadata, PawnColor, TileState, Variant,
+  };
 
   #[test]
   fn test_get_tile() {
@@ -1254,4 +2546,747 @@ mod tests {
       }
     }
   }
+
+  #[test]
+  fn test_neighbors() {
+    let onoro = Onoro16::hex_start();
+    for pawn in onoro.pawns() {
+      let occupied: Vec<_> = onoro.occupied_neighbors(pawn.pos).collect();
+      let empty: Vec<_> = onoro.empty_adjacent_tiles(pawn.pos).collect();
+      assert_eq!(occupied.len() + empty.len(), 6);
+      assert!(occupied
+        .iter()
+        .all(|&pos| onoro.get_tile(pos) != TileState::Empty));
+      assert!(empty
+        .iter()
+        .all(|&pos| onoro.get_tile(pos) == TileState::Empty));
+    }
+
+    // Every pawn of the hex start touches the empty center and two other
+    // pawns.
+    let center = onoro
+      .pawns()
+      .map(|pawn| onoro.empty_adjacent_tiles(pawn.pos).collect::<Vec<_>>())
+      .reduce(|common, empty| {
+        common
+          .into_iter()
+          .filter(|pos| empty.contains(pos))
+          .collect()
+      })
+      .unwrap();
+    assert_eq!(center.len(), 1);
+    assert_eq!(onoro.occupied_neighbors(center[0]).count(), 6);
+    assert!(onoro
+      .pawns()
+      .all(|pawn| onoro.occupied_neighbors(pawn.pos).count() == 2));
+
+    // Only tiles on the board are adjacent to a corner.
+    assert_eq!(onoro.empty_adjacent_tiles(PackedIdx::new(0, 0)).count(), 3);
+  }
+
+  #[test]
+  fn test_fast_paths_match_scalar() {
+    let states: Vec<Onoro16> = random_states(20, 60, &mut StdRng::seed_from_u64(1234));
+    for onoro in states {
+      for y in 0..Onoro16::board_width() as u32 {
+        for x in 0..Onoro16::board_width() as u32 {
+          let idx = PackedIdx::new(x, y);
+          assert_eq!(onoro.get_pawn_idx(idx), onoro.get_pawn_idx_slow(idx));
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_check_win_matches_scalar() {
+    let onoro = Onoro16::from_board_string(
+      ". . . . . .
+        . B B B B .
+         . W W W . .
+          . . . . . .",
+    )
+    .unwrap();
+    assert_eq!(onoro.finished(), Some(PawnColor::Black));
+
+    // Black was the last to move, so lines through each black pawn are checked.
+    for pawn in onoro.color_pawns(PawnColor::Black) {
+      let pos = HexPos::from(pawn.pos);
+      assert_eq!(onoro.check_win_fast(pos), onoro.check_win_slow(pos));
+    }
+  }
+
+  #[test]
+  fn test_phase() {
+    let mut onoro = Onoro16::default_start();
+    assert_eq!(onoro.phase(), Phase::Phase1);
+    assert_eq!(onoro.plies_until_phase2(), 13);
+
+    while onoro.in_phase1() {
+      let plies = onoro.plies_until_phase2();
+      let m = onoro.each_move().next().unwrap();
+      assert_eq!(m.phase(), Phase::Phase1);
+      onoro.make_move(m);
+      assert_eq!(onoro.plies_until_phase2(), plies - 1);
+    }
+
+    assert_eq!(onoro.phase(), Phase::Phase2);
+    assert_eq!(onoro.plies_until_phase2(), 0);
+    assert_eq!(onoro.pawns_in_play(), 16);
+    let m = onoro.each_move().next().unwrap();
+    assert_eq!(m.phase(), Phase::Phase2);
+  }
+
+  #[test]
+  fn test_parse_pawn_color() {
+    for color in [PawnColor::Black, PawnColor::White] {
+      assert_eq!(color.to_string().parse::<PawnColor>().unwrap(), color);
+    }
+    assert_eq!("B".parse::<PawnColor>().unwrap(), PawnColor::Black);
+    assert_eq!("w".parse::<PawnColor>().unwrap(), PawnColor::White);
+    assert_eq!("White".parse::<PawnColor>().unwrap(), PawnColor::White);
+    assert!("red".parse::<PawnColor>().is_err());
+  }
+
+  #[test]
+  fn test_centralization() {
+    // Every pawn of the starting triangle is 2/3 of a tile from the center of
+    // mass, which scales to 2.
+    let onoro = Onoro16::default_start();
+    assert_eq!(onoro.centralization(PawnColor::Black), 4);
+    assert_eq!(onoro.centralization(PawnColor::White), 2);
+
+    let onoro = Onoro16::from_board_string(
+      ". . . .
+        . W B .
+         . B W B
+          . . W .",
+    )
+    .unwrap();
+    for color in [PawnColor::Black, PawnColor::White] {
+      let centralization = onoro.centralization(color);
+      for op in (0..6).map(D6::Rot).chain((0..6).map(D6::Rfl)) {
+        assert_eq!(onoro.rotated_d6_c(op).centralization(color), centralization);
+      }
+    }
+  }
+
+  #[test]
+  fn test_evaluate_threats() {
+    let onoro = Onoro16::from_board_string(
+      ". . . . .
+        . B B B .
+         . W W . .",
+    )
+    .unwrap();
+    assert_eq!(onoro.player_color(), PawnColor::White);
+    for op in (0..6).map(D6::Rot).chain((0..6).map(D6::Rfl)) {
+      let rotated = onoro.rotated_d6_c(op);
+      assert_eq!(rotated.threats(PawnColor::Black), 1);
+      assert_eq!(rotated.threats(PawnColor::White), 0);
+    }
+
+    // White has to stop black's line of three, so this is bad for white.
+    assert!(onoro.evaluate() < 0);
+  }
+
+  #[test]
+  fn test_winning_squares() {
+    let onoro = Onoro16::from_board_string(
+      ". . . . . .
+        . B B B . .
+         . W W . . .
+          . . . . . .",
+    )
+    .unwrap();
+    let mut squares = onoro.winning_squares(PawnColor::Black);
+    squares.sort_by_key(|pos| (pos.y(), pos.x()));
+    let black: Vec<_> = onoro
+      .color_pawns(PawnColor::Black)
+      .map(|pawn| pawn.pos)
+      .collect();
+    let y = black[0].y();
+    let (min_x, max_x) = (
+      black.iter().map(|pos| pos.x()).min().unwrap(),
+      black.iter().map(|pos| pos.x()).max().unwrap(),
+    );
+    assert_eq!(
+      squares,
+      vec![PackedIdx::new(min_x - 1, y), PackedIdx::new(max_x + 1, y)]
+    );
+    assert!(onoro.winning_squares(PawnColor::White).is_empty());
+
+    let masks = onoro.line_masks(PackedIdx::new(max_x + 1, y), PawnColor::Black);
+    assert_eq!(masks.x_line(), 0b111 << min_x);
+    assert_eq!(masks.diagonal(), 0);
+    assert_eq!(masks.y_line(), 0);
+    assert!(!masks.has_line(4));
+    assert!(masks.with_center(PackedIdx::new(max_x + 1, y)).has_line(4));
+  }
+
+  #[test]
+  fn test_winning_squares_match_completes_line() {
+    let states: Vec<Onoro16> = random_states(20, 13, &mut StdRng::seed_from_u64(11));
+    for onoro in states
+      .iter()
+      .filter(|onoro| onoro.finished().is_none() && onoro.in_phase1())
+    {
+      for color in [PawnColor::Black, PawnColor::White] {
+        let squares = onoro.winning_squares(color);
+        for pawn in onoro.pawns() {
+          for to in onoro.empty_adjacent_tiles(pawn.pos) {
+            assert_eq!(
+              squares.contains(&to),
+              onoro.completes_line(color, Move::Phase1Move { to }),
+              "{to:?} for {color} in\n{onoro}"
+            );
+          }
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_collect_into() {
+    let states: Vec<Onoro16> = random_states(20, 100, &mut StdRng::seed_from_u64(13));
+    let mut moves = ArrayVec::new();
+    for onoro in states {
+      moves.clear();
+      onoro.each_move_gen().collect_into(&onoro, &mut moves);
+      assert!(
+        moves.iter().copied().eq(onoro.each_move()),
+        "Different moves for\n{onoro}"
+      );
+    }
+  }
+
+  #[test]
+  fn test_undo_move() {
+    let mut rng = StdRng::seed_from_u64(1234);
+    for _ in 0..20 {
+      let (states, moves) = random_playout(Onoro16::default_start(), 60, &mut rng);
+      let mut onoro = states[0].clone();
+      let mut undos: Vec<_> = moves
+        .into_iter()
+        .map(|m| Some(onoro.make_move_with_undo(m)))
+        .collect();
+      undos.insert(0, None);
+
+      for (expected, undo) in states.iter().zip(undos).rev() {
+        assert_eq!(onoro.to_string(), expected.to_string());
+        assert_eq!(onoro.sum_of_mass(), expected.sum_of_mass());
+        assert_eq!(onoro.symm_state(), expected.symm_state());
+        assert_eq!(onoro.onoro_state(), expected.onoro_state());
+        if let Some(undo) = undo {
+          onoro.undo_move(undo);
+        }
+      }
+    }
+  }
+
+  #[test]
+  #[cfg(feature = "move-metadata")]
+  fn test_move_metadata() {
+    let mut rng = StdRng::seed_from_u64(1234);
+    for _ in 0..20 {
+      let (states, moves) = random_playout(Onoro16::default_start(), 76, &mut rng);
+      assert_eq!(states[0].last_move(), None);
+      assert_eq!(states[0].move_number(), 3);
+
+      for ((move_number, m), pair) in (4..).zip(moves).zip(states.windows(2)) {
+        let (prev, onoro) = (&pair[0], &pair[1]);
+        let last_move = onoro.last_move().unwrap();
+        assert_eq!(onoro.move_number(), move_number);
+
+        // The last move's tiles are where the pawn is now, and where it was
+        // before, in the new game's coordinates.
+        let pawn = onoro.pawns().find(|pawn| pawn.pos == last_move.to).unwrap();
+        assert_eq!(pawn.color, prev.player_color());
+        match m {
+          Move::Phase1Move { .. } => assert_eq!(last_move.from, None),
+          Move::Phase2Move { from_idx, .. } => {
+            assert_eq!(last_move.pawn_idx as u32, from_idx);
+            let from = last_move.from.unwrap();
+            assert_eq!(onoro.get_tile(from), TileState::Empty);
+            assert_eq!(onoro.pawns().count(), prev.pawns().count());
+          }
+        }
+
+        let mut undone = prev.clone();
+        let undo = undone.make_move_with_undo(m);
+        undone.undo_move(undo);
+        assert_eq!(undone.move_number(), move_number - 1);
+        assert_eq!(undone.last_move(), prev.last_move());
+      }
+    }
+
+    let onoro = Onoro16::from_board_string(". B W\n W* B .").unwrap();
+    assert_eq!(onoro.move_number(), 4);
+    let last_move = onoro.last_move().unwrap();
+    assert_eq!(last_move.from, None);
+    assert_eq!(onoro.get_tile(last_move.to), TileState::White);
+  }
+
+  #[test]
+  fn test_compress_round_trip() {
+    let mut rng = StdRng::seed_from_u64(1234);
+    let mut bytes = [0u8; Onoro16::COMPRESSED_SIZE];
+    let won = Onoro16::from_board_string(
+      ". . . . . .
+        . B B B B .
+         . W W W . .
+          . . . . . .",
+    )
+    .unwrap();
+    assert!(won.finished().is_some());
+
+    // The turn, and with it the phase and the number of pawns in play, and
+    // whether the game is finished all survive the round trip.
+    let mut round_trip = |onoro: &Onoro16| {
+      onoro.compress(&mut bytes);
+      let decompressed = Onoro16::decompress(&bytes).unwrap();
+      assert_eq!(decompressed.to_string(), onoro.to_string());
+      assert_eq!(decompressed.sum_of_mass(), onoro.sum_of_mass());
+      assert_eq!(decompressed.symm_state(), onoro.symm_state());
+      assert_eq!(decompressed.onoro_state(), onoro.onoro_state());
+      assert_eq!(decompressed.pawns_in_play(), onoro.pawns_in_play());
+      assert_eq!(decompressed.finished(), onoro.finished());
+    };
+    round_trip(&won);
+    for onoro in &random_playout(Onoro16::default_start(), 40, &mut rng).0 {
+      round_trip(onoro);
+    }
+
+    // A pawn on the border of the board is never a valid game state.
+    Onoro16::default_start().compress(&mut bytes);
+    bytes[0] = 0;
+    assert!(Onoro16::decompress(&bytes).is_none());
+    assert!(Onoro16::decompress(&bytes[1..]).is_none());
+
+    // The pawns on either end of a line only have one neighbor each.
+    let line = Onoro16::from_board_string(". B W B W").unwrap();
+    line.compress(&mut bytes);
+    assert!(Onoro16::decompress(&bytes).is_none());
+  }
+
+  #[test]
+  fn test_compress_partial_boards() {
+    // Boards from the default start on, with three or more pawns, are covered
+    // by `test_compress_round_trip`.
+    let mut bytes = [0u8; Onoro16::COMPRESSED_SIZE];
+    for board in [
+      "B",
+      ". B
+        W .",
+    ] {
+      let onoro = Onoro16::from_board_string(board).unwrap();
+      onoro.compress(&mut bytes);
+      let decompressed = Onoro16::decompress(&bytes).unwrap();
+      assert_eq!(decompressed.to_string(), onoro.to_string());
+      assert_eq!(decompressed.pawns_in_play(), onoro.pawns_in_play());
+      assert_eq!(decompressed.player_color(), onoro.player_color());
+    }
+  }
+
+  #[test]
+  fn test_move_notation() {
+    let onoro = Onoro16::default_start();
+    let phase2 = Onoro16::from_board_string(
+      ". . . . .
+        . B W W B
+         . W B B W
+          . B W W B
+           . W B B W",
+    )
+    .unwrap();
+    for onoro in [&onoro, &phase2] {
+      for m in onoro.each_move() {
+        let notation = m.to_notation(onoro);
+        assert_eq!(onoro.parse_move(&notation).unwrap(), m, "{notation}");
+      }
+    }
+
+    let m = Move::Phase1Move {
+      to: PackedIdx::new(2, 3),
+    };
+    assert_eq!(m.to_notation(&onoro), "w@c4");
+    assert_eq!(onoro.parse_move(" W@C4 ").unwrap(), m);
+
+    for notation in ["b@c4", "w@q4", "w@c0", "w@c17", "c4", "c4-d5", ""] {
+      assert!(
+        onoro.parse_move(notation).is_err(),
+        "\"{notation}\" should not parse"
+      );
+    }
+  }
+
+  #[test]
+  fn test_explain_legal_moves() {
+    let onoro = Onoro16::default_start();
+    for m in onoro.each_move() {
+      assert_eq!(
+        onoro.explain_illegal(m),
+        vec![],
+        "{}",
+        onoro.print_with_move(m)
+      );
+    }
+
+    let onoro = Onoro16::from_board_string(
+      ". . . . .
+        . B W W B
+         . W B B W
+          . B W W B
+           . W B B W",
+    )
+    .unwrap();
+    assert!(!onoro.in_phase1());
+    assert_eq!(onoro.finished(), None);
+
+    let mut moves = 0;
+    for m in onoro.each_move() {
+      assert_eq!(
+        onoro.explain_illegal(m),
+        vec![],
+        "{}",
+        onoro.print_with_move(m)
+      );
+      moves += 1;
+    }
+    assert!(moves > 0);
+  }
+
+  #[test]
+  fn test_p2_moves_match_rules() {
+    let mut rng = StdRng::seed_from_u64(31415);
+    let states: Vec<Onoro16> = random_states(20, 60, &mut rng);
+    let p2_states: Vec<_> = states
+      .iter()
+      .filter(|onoro| !onoro.in_phase1() && onoro.finished().is_none())
+      .take(20)
+      .collect();
+    assert_eq!(p2_states.len(), 20);
+
+    for onoro in p2_states {
+      let mut moves: Vec<_> = onoro.each_move().collect();
+      let mut legal: Vec<_> = (0..Onoro16::board_size())
+        .map(|ord| PackedIdx::from(Onoro16::ord_to_hex_pos(ord)))
+        .flat_map(|to| (0..16).map(move |from_idx| Move::Phase2Move { to, from_idx }))
+        .filter(|&m| onoro.explain_illegal(m).is_empty())
+        .collect();
+      moves.sort_by_key(|m| format!("{m:?}"));
+      legal.sort_by_key(|m| format!("{m:?}"));
+      assert_eq!(moves, legal, "{onoro}");
+    }
+  }
+
+  #[test]
+  fn test_explain_illegal_phase1() {
+    // Pawns are at (7, 7), (8, 8), and (8, 7), and it is white's turn.
+    let onoro = Onoro16::default_start();
+    let black_pos = PackedIdx::new(7, 7);
+
+    assert_eq!(
+      onoro.explain_illegal(Move::Phase1Move { to: black_pos }),
+      vec![RuleViolation::TileOccupied { pos: black_pos }]
+    );
+
+    let far_away = PackedIdx::new(1, 1);
+    assert_eq!(
+      onoro.explain_illegal(Move::Phase1Move { to: far_away }),
+      vec![RuleViolation::TooFewNeighbors {
+        pos: far_away,
+        neighbors: 0
+      }]
+    );
+
+    assert_eq!(
+      onoro.explain_illegal(Move::Phase2Move {
+        to: far_away,
+        from_idx: 0,
+      }),
+      vec![
+        RuleViolation::WrongPhase {
+          move_phase: Phase::Phase2,
+          game_phase: Phase::Phase1
+        },
+        RuleViolation::NotOwnPawn { from_idx: 0 },
+        RuleViolation::TooFewNeighbors {
+          pos: far_away,
+          neighbors: 0
+        },
+        RuleViolation::PawnIsolated {
+          pos: PackedIdx::new(8, 8),
+          neighbors: 1
+        },
+        RuleViolation::PawnIsolated {
+          pos: PackedIdx::new(8, 7),
+          neighbors: 1
+        },
+        RuleViolation::BoardDisconnected {
+          groups: vec![
+            vec![PackedIdx::new(8, 8), PackedIdx::new(8, 7)],
+            vec![far_away]
+          ]
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_try_make_move() {
+    let mut onoro = Onoro16::default_start();
+    let black_pos = PackedIdx::new(7, 7);
+    let err = onoro
+      .try_make_move(Move::Phase1Move { to: black_pos })
+      .unwrap_err();
+    assert_eq!(
+      err.violations(),
+      [RuleViolation::TileOccupied { pos: black_pos }]
+    );
+    assert_eq!(onoro.pawns_in_play(), 3);
+
+    let m = onoro.each_move().next().unwrap();
+    onoro.try_make_move(m).unwrap();
+    assert_eq!(onoro.pawns_in_play(), 4);
+  }
+
+  #[test]
+  fn test_forced_blocks() {
+    let onoro = Onoro16::from_board_string(
+      ". . W . . .
+        . B B B . .
+         . W W . B .",
+    )
+    .unwrap();
+    assert_eq!(onoro.player_color(), PawnColor::White);
+    assert!(onoro.immediate_wins().is_empty());
+
+    // Black threatens to extend their line on both ends, but only the right
+    // end is next to enough pawns to be placed on.
+    let blocks = onoro.forced_blocks();
+    assert_eq!(blocks.len(), 1);
+    for m in onoro.each_move() {
+      let mut child = onoro.clone();
+      child.make_move(m);
+      assert_eq!(child.immediate_wins().is_empty(), blocks.contains(&m));
+    }
+  }
+
+  #[test]
+  fn test_threats_match_make_move() {
+    let mut rng = StdRng::seed_from_u64(5678);
+    let states: Vec<Onoro16> = random_states(50, 200, &mut rng);
+    for onoro in states.iter().filter(|onoro| onoro.finished().is_none()) {
+      let moves: Vec<_> = onoro.each_move().collect();
+      let wins: Vec<_> = moves
+        .iter()
+        .copied()
+        .filter(|&m| {
+          let mut child = onoro.clone();
+          child.make_move(m);
+          child.finished() == Some(onoro.player_color())
+        })
+        .collect();
+      assert_eq!(onoro.immediate_wins(), wins, "{onoro}");
+
+      if !onoro.in_phase1() {
+        let mut opponent_to_move = onoro.clone();
+        opponent_to_move.mut_onoro_state().swap_player_turn();
+        let threatened: Vec<_> = opponent_to_move
+          .each_move()
+          .filter(|&m| {
+            let mut child = opponent_to_move.clone();
+            child.make_move(m);
+            child.finished().is_some()
+          })
+          .map(|m| m.to())
+          .collect();
+        let blocks: Vec<_> = moves
+          .iter()
+          .copied()
+          .filter(|m| threatened.contains(&m.to()))
+          .collect();
+        assert_eq!(onoro.forced_blocks(), blocks, "{onoro}");
+      }
+    }
+  }
+
+  #[test]
+  fn test_variant_win_length() {
+    let three_in_a_row = Onoro16::from_board_string(
+      ". . . . . .
+        . B B B . .
+         . W W W . .
+          . . . . . .",
+    )
+    .unwrap();
+    assert_eq!(three_in_a_row.player_color(), PawnColor::Black);
+    assert!(!three_in_a_row.immediate_wins().is_empty());
+
+    // Black can extend the line to four, which doesn't win when five in
+    // a row are needed.
+    let five = three_in_a_row
+      .clone()
+      .with_variant(Variant::with_win_length(5).unwrap())
+      .unwrap();
+    assert!(five.immediate_wins().is_empty());
+    assert_eq!(five.threats(PawnColor::Black), 0);
+    for m in three_in_a_row.immediate_wins() {
+      let mut onoro = five.clone();
+      onoro.make_move(m);
+      assert!(onoro.finished().is_none());
+      assert!(!onoro.check_win_slow(HexPos::from(m.to())));
+      assert_eq!(onoro.threats(PawnColor::Black), 1);
+    }
+
+    // Both players already have three in a row.
+    let three = Variant::with_win_length(3).unwrap();
+    assert!(three_in_a_row.clone().with_variant(three).is_err());
+    // Four pawns can't make five in a row.
+    assert!(Onoro8::default_start()
+      .with_variant(Variant::with_win_length(5).unwrap())
+      .is_err());
+    assert!(Variant::with_win_length(2).is_err());
+  }
+
+  /// A phase 2 position with no lines of four.
+  fn perft_phase2_position() -> Onoro16 {
+    Onoro16::from_board_string(
+      ". . . . .
+        . B W W B
+         . W B B W
+          . B W W B
+           . W B B W",
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn test_perft() {
+    // Reference counts from the move generator as of when perft was added.
+    // Any change to these means the move generator now generates different
+    // moves.
+    let default_counts = [1, 3, 12, 60, 336, 2036, 13258];
+    let hex_counts = [1, 7, 54, 438, 3672];
+    let phase2_counts = [1, 66, 3773, 203727];
+    for (onoro, counts) in [
+      (Onoro16::default_start(), &default_counts[..]),
+      (Onoro16::hex_start(), &hex_counts[..]),
+      (perft_phase2_position(), &phase2_counts[..]),
+    ] {
+      for (depth, &count) in counts.iter().enumerate() {
+        assert_eq!(onoro.perft(depth as u32), count, "{onoro}\ndepth {depth}");
+      }
+    }
+
+    let won = Onoro16::from_board_string(
+      ". . . . . .
+        . B B B B .
+         . W W W . .
+          . . . . . .",
+    )
+    .unwrap();
+    assert_eq!(won.perft(0), 1);
+    assert_eq!(won.perft(1), 0);
+  }
+
+  #[test]
+  fn test_perft_divide() {
+    for onoro in [Onoro16::default_start(), perft_phase2_position()] {
+      let divide = onoro.perft_divide(2);
+      assert_eq!(divide.len() as u64, onoro.perft(1));
+      assert_eq!(
+        divide.iter().map(|(_, count)| count).sum::<u64>(),
+        onoro.perft(2)
+      );
+      for (m, count) in divide {
+        let mut child = onoro.clone();
+        child.make_move(m);
+        assert_eq!(child.perft(1), count);
+      }
+    }
+  }
+
+  #[test]
+  fn test_board_string_turn() {
+    let board = ". . . . .
+        . B W W B
+         . W B B W
+          . B W W B
+           . W B B W";
+    let (onoro, metadata) = Onoro16::from_board_string_with_metadata(board).unwrap();
+    assert_eq!(onoro.player_color(), PawnColor::Black);
+    assert_eq!(metadata, BoardMetadata::default());
+
+    let (onoro, metadata) =
+      Onoro16::from_board_string_with_metadata(&format!("turn: W\n{board}")).unwrap();
+    assert_eq!(onoro.player_color(), PawnColor::White);
+    assert_eq!(metadata.turn, Some(PawnColor::White));
+    assert!(onoro.each_move().all(|m| match m {
+      Move::Phase2Move { from_idx, .. } => from_idx & 1 == 1,
+      Move::Phase1Move { .. } => false,
+    }));
+
+    // In phase 1, the pawn counts decide whose turn it is.
+    assert!(Onoro16::from_board_string("turn: B\n. B B W").is_err());
+    assert!(Onoro16::from_board_string("turn: W\n. B B W").is_ok());
+    assert!(Onoro16::from_board_string("moves: 3\n. B B W").is_err());
+  }
+
+  #[test]
+  fn test_board_string_last_moved() {
+    let (onoro, metadata) = Onoro16::from_board_string_with_metadata(
+      ". . . . .
+        . B B* B .
+         . W W . .",
+    )
+    .unwrap();
+    let last_moved = metadata.last_moved.unwrap();
+    assert_eq!(onoro.get_tile(last_moved), TileState::Black);
+    // The marked pawn is in the middle of the line of three.
+    let neighbors = [(1, 0), (-1, 0)].map(|(dx, dy)| {
+      let pos = HexPos::from(last_moved) + HexPosOffset::new(dx, dy);
+      onoro.get_tile(PackedIdx::from(pos))
+    });
+    assert_eq!(neighbors, [TileState::Black, TileState::Black]);
+
+    // White is to move, so black moved last.
+    assert!(Onoro16::from_board_string(". B B B .\n . W* W . .").is_err());
+    assert!(Onoro16::from_board_string(". B* B* B .\n . W W . .").is_err());
+  }
+
+  #[test]
+  fn test_no_legal_moves() {
+    // Every black pawn is either holding the board together or can only move
+    // next to a single pawn.
+    let stuck = Onoro8::from_board_string(
+      ". . . W .
+        . . B B .
+         . W . W .
+          . B B . .
+           . W . . .",
+    )
+    .unwrap();
+    assert_eq!(stuck.player_color(), PawnColor::Black);
+    assert_eq!(stuck.each_move().count(), 0);
+    assert_eq!(stuck.finished(), None);
+    assert_eq!(stuck.winner(), Some(PawnColor::White));
+    assert!(Onoro8View::new(stuck).is_stuck());
+
+    let parent = Onoro8::from_board_string(
+      "turn: W
+       . . . W .
+        . . B B .
+         . W W . .
+          . B B . .
+           . W . . .",
+    )
+    .unwrap();
+    assert_eq!(parent.winner(), None);
+    assert!(parent.each_move().any(|m| {
+      let mut child = parent.clone();
+      child.make_move(m);
+      child.winner() == Some(PawnColor::White)
+    }));
+  }
 }
@@ -1,4 +1,22 @@
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg(feature = "move-metadata")]
+use crate::PackedIdx;
+
+/// The last move made in a game, with the tiles it involved in the
+/// coordinates of the game after it, which may have been shifted by the move.
+#[cfg(feature = "move-metadata")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LastMove {
+  /// The index of the pawn that was placed or moved, which phase 2 moves refer
+  /// to pawns by.
+  pub pawn_idx: u8,
+  /// The tile the pawn was moved from, or `None` if it was placed, or if the
+  /// move is only known from a mark in a board string.
+  pub from: Option<PackedIdx>,
+  /// The tile the pawn was placed or moved to.
+  pub to: PackedIdx,
+}
+
+#[derive(Clone, Debug)]
 pub struct OnoroState {
   /// Layout of data:
   /// ```text
@@ -12,6 +30,11 @@ pub struct OnoroState {
   /// incrementing after the end of phase 1. This allows us to only use 4 bits
   /// for the turn counter.
   data: u8,
+  /// The number of moves made in phase 2, which don't increment the turn.
+  #[cfg(feature = "move-metadata")]
+  phase2_moves: u32,
+  #[cfg(feature = "move-metadata")]
+  last_move: Option<LastMove>,
 }
 
 impl OnoroState {
@@ -20,9 +43,36 @@ impl OnoroState {
     // become 0.
     Self {
       data: Self::pack(0xf, true, false),
+      #[cfg(feature = "move-metadata")]
+      phase2_moves: 0,
+      #[cfg(feature = "move-metadata")]
+      last_move: None,
+    }
+  }
+
+  /// Reconstructs a state from the byte returned by `OnoroState::data`. The
+  /// move metadata isn't part of it, so it starts over.
+  pub const fn from_data(data: u8) -> Self {
+    Self {
+      data,
+      #[cfg(feature = "move-metadata")]
+      phase2_moves: 0,
+      #[cfg(feature = "move-metadata")]
+      last_move: None,
     }
   }
 
+  /// The state of a game on turn `turn`, for games set up without playing
+  /// their moves.
+  pub const fn from_turn(turn: u32, black_turn: bool, finished: bool) -> Self {
+    Self::from_data(Self::pack(turn, black_turn, finished))
+  }
+
+  /// The packed representation of the state.
+  pub const fn data(&self) -> u8 {
+    self.data
+  }
+
   pub const fn turn(&self) -> u32 {
     let (turn, _, _) = Self::unpack(self.data);
     turn
@@ -41,10 +91,10 @@ impl OnoroState {
   }
 
   /// Only swap which player's turn it is. This should be used in phase 2, when
-  /// the turn stops incrementing.
+  /// the turn stops incrementing at the index of the last pawn (15 with 8
+  /// pawns per player, or 7 with 4).
   pub fn swap_player_turn(&mut self) {
     let (turn, black_turn, finished) = Self::unpack(self.data);
-    debug_assert_eq!(turn, 0xf);
     self.data = Self::pack(turn, !black_turn, finished);
   }
 
@@ -58,6 +108,34 @@ impl OnoroState {
     self.data = Self::pack(turn, black_turn, finished);
   }
 
+  /// The number of moves made in phase 2 since the state was created.
+  #[cfg(feature = "move-metadata")]
+  pub const fn phase2_moves(&self) -> u32 {
+    self.phase2_moves
+  }
+
+  #[cfg(feature = "move-metadata")]
+  pub const fn last_move(&self) -> Option<LastMove> {
+    self.last_move
+  }
+
+  /// Records `last_move` as the move that was just made, counting it as a
+  /// phase 2 move if it moved a pawn.
+  #[cfg(feature = "move-metadata")]
+  pub fn record_move(&mut self, last_move: LastMove) {
+    if last_move.from.is_some() {
+      self.phase2_moves += 1;
+    }
+    self.last_move = Some(last_move);
+  }
+
+  /// Replaces the last move without counting a move, for games set up without
+  /// playing their moves.
+  #[cfg(feature = "move-metadata")]
+  pub fn set_last_move(&mut self, last_move: Option<LastMove>) {
+    self.last_move = last_move;
+  }
+
   const fn pack(turn: u32, black_turn: bool, finished: bool) -> u8 {
     debug_assert!(turn < 0x10);
 
@@ -72,3 +150,13 @@ impl OnoroState {
     (turn, black_turn, finished)
   }
 }
+
+/// States are compared by the game state they pack, ignoring the metadata of
+/// how they were reached.
+impl PartialEq for OnoroState {
+  fn eq(&self, other: &Self) -> bool {
+    self.data == other.data
+  }
+}
+
+impl Eq for OnoroState {}
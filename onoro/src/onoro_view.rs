@@ -1,5 +1,5 @@
 use crate::{Move, MoveGenerator};
-use std::{cell::UnsafeCell, fmt::Display, hash::Hash};
+use std::{cell::UnsafeCell, collections::HashSet, fmt::Display, hash::Hash};
 
 use algebra::{
   group::{Group, Trivial},
@@ -7,27 +7,92 @@ use algebra::{
   ordinal::Ordinal,
 };
 
-use abstract_game::{Game, GameMoveGenerator, GameResult};
+#[cfg(feature = "hash128")]
+use const_random::const_random;
+
+use abstract_game::{
+  Compress, Evaluate, Game, GameIterator, GameMoveGenerator, GameResult, MoveArena, Threats,
+};
 
 use crate::{
   canonicalize::{board_symm_state, BoardSymmetryState},
   groups::{SymmetryClass, C2, D3, D6, K4},
   hash::HashTable,
+  hash_export::HashTableExport,
   hex_pos::{HexPos, HexPosOffset},
   tile_hash::HashGroup,
-  Onoro, PawnColor, TileState,
+  Onoro, PackedIdx, PawnColor,
 };
 
+#[cfg(any(not(feature = "hash128"), feature = "verify-simd"))]
+use crate::TileState;
+
 /// Always generate hash tables for the full game. Only a part of the tables
 /// will be used for smaller games.
 type ViewHashTable<G> = HashTable<16, 256, G>;
 
+static D6T: ViewHashTable<D6> = HashTable::new_c();
+static D3T: ViewHashTable<D3> = HashTable::new_v();
+static K4T: ViewHashTable<K4> = HashTable::new_e();
+static C2CVT: ViewHashTable<C2> = HashTable::new_cv();
+static C2CET: ViewHashTable<C2> = HashTable::new_ce();
+static C2EVT: ViewHashTable<C2> = HashTable::new_ev();
+static TT: ViewHashTable<Trivial> = HashTable::new_trivial();
+
+// A second set of tables, generated from independent random numbers, for the
+// high half of `OnoroView::canonical_hash128`.
+#[cfg(feature = "hash128")]
+static D6T_HI: ViewHashTable<D6> =
+  HashTable::new_c_seeded([const_random!(u64), const_random!(u64)]);
+#[cfg(feature = "hash128")]
+static D3T_HI: ViewHashTable<D3> =
+  HashTable::new_v_seeded([const_random!(u64), const_random!(u64)]);
+#[cfg(feature = "hash128")]
+static K4T_HI: ViewHashTable<K4> =
+  HashTable::new_e_seeded([const_random!(u64), const_random!(u64)]);
+#[cfg(feature = "hash128")]
+static C2CVT_HI: ViewHashTable<C2> =
+  HashTable::new_cv_seeded([const_random!(u64), const_random!(u64)]);
+#[cfg(feature = "hash128")]
+static C2CET_HI: ViewHashTable<C2> =
+  HashTable::new_ce_seeded([const_random!(u64), const_random!(u64)]);
+#[cfg(feature = "hash128")]
+static C2EVT_HI: ViewHashTable<C2> =
+  HashTable::new_ev_seeded([const_random!(u64), const_random!(u64)]);
+#[cfg(feature = "hash128")]
+static TT_HI: ViewHashTable<Trivial> =
+  HashTable::new_trivial_seeded([const_random!(u64), const_random!(u64)]);
+
+/// Exports the hash tables used by `OnoroView`, one per symmetry class.
+pub(crate) fn export_view_hash_tables() -> Vec<HashTableExport> {
+  vec![
+    HashTableExport::new(SymmetryClass::C, &D6T),
+    HashTableExport::new(SymmetryClass::V, &D3T),
+    HashTableExport::new(SymmetryClass::E, &K4T),
+    HashTableExport::new(SymmetryClass::CV, &C2CVT),
+    HashTableExport::new(SymmetryClass::CE, &C2CET),
+    HashTableExport::new(SymmetryClass::EV, &C2EVT),
+    HashTableExport::new(SymmetryClass::Trivial, &TT),
+  ]
+}
+
 #[derive(Clone, Debug)]
 struct CanonicalView {
   initialized: bool,
   symm_class: SymmetryClass,
   op_ord: u8,
   hash: u64,
+  /// The high half of the 128-bit canonical hash, see
+  /// `OnoroView::canonical_hash128`.
+  #[cfg(feature = "hash128")]
+  hash_hi: u64,
+  /// The hashes of the game state on the hash table of its symmetry class,
+  /// before canonicalization, as [black to move, white to move] (see
+  /// `HashTable::player_hashes`). These may be known before the view is
+  /// initialized, if `make_move` found them incrementally from the parent
+  /// state.
+  #[cfg(feature = "incremental-hash")]
+  player_hashes: Option<[u64; 2]>,
 }
 
 impl CanonicalView {
@@ -37,6 +102,10 @@ impl CanonicalView {
       symm_class: SymmetryClass::C,
       op_ord: 0,
       hash: 0,
+      #[cfg(feature = "hash128")]
+      hash_hi: 0,
+      #[cfg(feature = "incremental-hash")]
+      player_hashes: None,
     }
   }
 
@@ -54,6 +123,12 @@ impl CanonicalView {
     debug_assert!(self.initialized);
     self.hash
   }
+
+  #[cfg(feature = "hash128")]
+  fn get_hash_hi(&self) -> u64 {
+    debug_assert!(self.initialized);
+    self.hash_hi
+  }
 }
 
 /// A wrapper over Onoro states that caches the hash of the game state and it's
@@ -79,6 +154,246 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> OnoroView<N, N2
     &self.onoro
   }
 
+  /// Views of each of `games`, in order, with their canonical hashes already
+  /// found. This is the entry point for canonicalizing many game states at
+  /// once, e.g. for deduplicating large sets of positions.
+  pub fn canonicalize_batch(games: &[Onoro<N, N2, ADJ_CNT_SIZE>]) -> Vec<Self> {
+    // Grouping the games by symmetry class, so each class's hash table is
+    // used in one stretch, measured slower than hashing them in order (see the
+    // `view_hash` bench), since the tables already fit in cache.
+    games
+      .iter()
+      .map(|onoro| {
+        let view = Self::new(onoro.clone());
+        view.maybe_initialize_canonical_view();
+        view
+      })
+      .collect()
+  }
+
+  /// The hash of the canonical orientation of this game state. All symmetries
+  /// of a game state share the same canonical hash.
+  pub fn canonical_hash(&self) -> u64 {
+    self.maybe_initialize_canonical_view();
+    self.canon_view().get_hash()
+  }
+
+  /// A 128-bit hash of the canonical orientation of this game state. The low
+  /// 64 bits are `canonical_hash`, and the high 64 bits are found the same
+  /// way on a second, independent set of hash tables, so distinct game states
+  /// share a 128-bit hash with negligible probability, even in tables of
+  /// billions of states.
+  #[cfg(feature = "hash128")]
+  pub fn canonical_hash128(&self) -> u128 {
+    self.maybe_initialize_canonical_view();
+    let view = self.canon_view();
+    ((view.get_hash_hi() as u128) << 64) | view.get_hash() as u128
+  }
+
+  /// Every distinct orientation of this game state, from rotating and
+  /// reflecting it by each symmetry of the hex grid. Orientations which are
+  /// the same board up to translation are only included once, so boards with
+  /// no symmetry of their own have 12, and symmetric boards have fewer.
+  pub fn orbit(&self) -> Vec<Onoro<N, N2, ADJ_CNT_SIZE>> {
+    let mut orbit: Vec<Onoro<N, N2, ADJ_CNT_SIZE>> = Vec::new();
+    for op in D6::for_each() {
+      let rotated = self.onoro.rotated_d6_c(op);
+      let key = Self::board_key(&rotated);
+      if !orbit.iter().any(|other| Self::board_key(other) == key) {
+        orbit.push(rotated);
+      }
+    }
+    orbit
+  }
+
+  /// This game state rotated into its canonical orientation, the one whose
+  /// hash is `canonical_hash`. Equal views have the same canonical board, up
+  /// to translation.
+  pub fn canonical_board(&self) -> Onoro<N, N2, ADJ_CNT_SIZE> {
+    self.maybe_initialize_canonical_view();
+    let normalizing_op = board_symm_state(&self.onoro).op;
+    let op_ord = self.canon_view().get_op_ord() as usize;
+    let onoro = &self.onoro;
+    match self.canon_view().get_symm_class() {
+      SymmetryClass::C => onoro.rotated(D6::from_ord(op_ord), |pos, op| {
+        pos.apply_d6_c(&normalizing_op).apply_d6_c(op)
+      }),
+      SymmetryClass::V => onoro.rotated(D3::from_ord(op_ord), |pos, op| {
+        pos.apply_d6_c(&normalizing_op).apply_d3_v(op)
+      }),
+      SymmetryClass::E => onoro.rotated(K4::from_ord(op_ord), |pos, op| {
+        pos.apply_d6_c(&normalizing_op).apply_k4_e(op)
+      }),
+      SymmetryClass::CV => onoro.rotated(C2::from_ord(op_ord), |pos, op| {
+        pos.apply_d6_c(&normalizing_op).apply_c2_cv(op)
+      }),
+      SymmetryClass::CE => onoro.rotated(C2::from_ord(op_ord), |pos, op| {
+        pos.apply_d6_c(&normalizing_op).apply_c2_ce(op)
+      }),
+      SymmetryClass::EV => onoro.rotated(C2::from_ord(op_ord), |pos, op| {
+        pos.apply_d6_c(&normalizing_op).apply_c2_ev(op)
+      }),
+      SymmetryClass::Trivial => onoro.rotated(Trivial::identity(), |pos, _| {
+        pos.apply_d6_c(&normalizing_op)
+      }),
+    }
+  }
+
+  /// The move on `canonical_board` which corresponds to `m`, a move from this
+  /// game state. All views equal to this one map their moves to the same
+  /// canonical moves, so moves can be stored against the canonical hash, e.g.
+  /// in a transposition table or opening book, and mapped back into the
+  /// orientation of another game with `map_move_from_canonical`.
+  ///
+  /// Since equal views may have the colors swapped, a phase 2 move names the
+  /// pawn it moves by its rank among the moving player's pawns, which are
+  /// placed on the canonical board in order of their position: `from_idx / 2`
+  /// is the rank, and `from_idx % 2` the color of this game's player.
+  pub fn map_move_to_canonical(&self, m: Move) -> Move {
+    let to = self.canonical_pos(m.to().into(), false).into();
+    match m {
+      Move::Phase1Move { .. } => Move::Phase1Move { to },
+      Move::Phase2Move { from_idx, .. } => {
+        let from = self
+          .onoro
+          .pawn_position(from_idx)
+          .expect("Phase 2 move from an empty pawn index");
+        let rank = self
+          .canonical_player_pawns()
+          .iter()
+          .position(|&(_, pos)| pos == from)
+          .expect("Phase 2 move of the opponent's pawn");
+        Move::Phase2Move {
+          to,
+          from_idx: 2 * rank as u32 + self.player_parity(),
+        }
+      }
+    }
+  }
+
+  /// The inverse of `map_move_to_canonical`: maps a move on the canonical
+  /// board of this view, or of any view equal to it, to the corresponding
+  /// move from this game state. Returns `None` if `m` names a pawn the
+  /// current player doesn't have.
+  pub fn map_move_from_canonical(&self, m: Move) -> Option<Move> {
+    let to = self.canonical_pos(m.to().into(), true).into();
+    match m {
+      Move::Phase1Move { .. } => Some(Move::Phase1Move { to }),
+      Move::Phase2Move { from_idx, .. } => {
+        let (_, from) = *self.canonical_player_pawns().get(from_idx as usize / 2)?;
+        Some(Move::Phase2Move {
+          to,
+          from_idx: self.onoro.get_pawn_idx(from)?,
+        })
+      }
+    }
+  }
+
+  /// The parity of the pawn indices of the current player, 0 for black and 1
+  /// for white.
+  fn player_parity(&self) -> u32 {
+    match self.onoro.player_color() {
+      PawnColor::Black => 0,
+      PawnColor::White => 1,
+    }
+  }
+
+  /// The current player's pawns as (position on the canonical board, position
+  /// on this board), in the order `Onoro::rotated` places them on the
+  /// canonical board.
+  fn canonical_player_pawns(&self) -> Vec<(HexPos, PackedIdx)> {
+    let mut pawns: Vec<_> = self
+      .onoro
+      .color_pawns(self.onoro.player_color())
+      .map(|pawn| (self.canonical_pos(pawn.pos.into(), false), pawn.pos))
+      .collect();
+    pawns.sort_unstable_by_key(|(pos, _)| (pos.y(), pos.x()));
+    pawns
+  }
+
+  /// Maps `pos` on this board to the same tile on `canonical_board`, or back
+  /// if `inverse` is set. This ignores any shift of the canonical board to
+  /// keep pawns off its edges, which only boards too spread out to fit
+  /// centered on the board need.
+  fn canonical_pos(&self, pos: HexPos, inverse: bool) -> HexPos {
+    self.maybe_initialize_canonical_view();
+    let op_ord = self.canon_view().get_op_ord() as usize;
+    match self.canon_view().get_symm_class() {
+      SymmetryClass::C => {
+        self.canonical_pos_by(pos, inverse, D6::from_ord(op_ord), HexPosOffset::apply_d6_c)
+      }
+      SymmetryClass::V => {
+        self.canonical_pos_by(pos, inverse, D3::from_ord(op_ord), HexPosOffset::apply_d3_v)
+      }
+      SymmetryClass::E => {
+        self.canonical_pos_by(pos, inverse, K4::from_ord(op_ord), HexPosOffset::apply_k4_e)
+      }
+      SymmetryClass::CV => self.canonical_pos_by(
+        pos,
+        inverse,
+        C2::from_ord(op_ord),
+        HexPosOffset::apply_c2_cv,
+      ),
+      SymmetryClass::CE => self.canonical_pos_by(
+        pos,
+        inverse,
+        C2::from_ord(op_ord),
+        HexPosOffset::apply_c2_ce,
+      ),
+      SymmetryClass::EV => self.canonical_pos_by(
+        pos,
+        inverse,
+        C2::from_ord(op_ord),
+        HexPosOffset::apply_c2_ev,
+      ),
+      SymmetryClass::Trivial => self.canonical_pos_by(
+        pos,
+        inverse,
+        Trivial::identity(),
+        HexPosOffset::apply_trivial,
+      ),
+    }
+  }
+
+  /// `canonical_pos` for a board whose canonicalizing op is `canon_op`, which
+  /// `apply` applies, after normalizing the board like `canonical_board`.
+  fn canonical_pos_by<G: Group>(
+    &self,
+    pos: HexPos,
+    inverse: bool,
+    canon_op: G,
+    apply: impl Fn(&HexPosOffset, &G) -> HexPosOffset,
+  ) -> HexPos {
+    let symm_state = board_symm_state(&self.onoro);
+    let origin = self.onoro.origin(&symm_state);
+    let center = HexPos::new(N as u32 / 2, N as u32 / 2);
+    if inverse {
+      let pos = apply(&(pos - center), &canon_op.inverse());
+      pos.apply_d6_c(&symm_state.op.inverse()) + origin
+    } else {
+      apply(&(pos - origin).apply_d6_c(&symm_state.op), &canon_op) + center
+    }
+  }
+
+  /// The pawns of `onoro` relative to its lowest pawn, in sorted order, which
+  /// identifies the board up to translation.
+  fn board_key(onoro: &Onoro<N, N2, ADJ_CNT_SIZE>) -> Vec<(i32, i32, bool)> {
+    let lowest = onoro
+      .pawns()
+      .map(|pawn| HexPos::from(pawn.pos))
+      .min_by_key(|pos| (pos.y(), pos.x()))
+      .unwrap();
+    let mut key: Vec<_> = onoro
+      .pawns()
+      .map(|pawn| {
+        let pos = HexPos::from(pawn.pos) - lowest;
+        (pos.y(), pos.x(), pawn.color == PawnColor::Black)
+      })
+      .collect();
+    key.sort_unstable();
+    key
+  }
+
   fn canon_view(&self) -> &CanonicalView {
     unsafe { &*self.view.get() }
   }
@@ -89,15 +404,14 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> OnoroView<N, N2
     }
 
     let symm_state = board_symm_state(&self.onoro);
-    let (hash, op_ord) = match symm_state.symm_class {
-      SymmetryClass::C => Self::find_canonical_orientation_d6(&self.onoro, &symm_state),
-      SymmetryClass::V => Self::find_canonical_orientation_d3(&self.onoro, &symm_state),
-      SymmetryClass::E => Self::find_canonical_orientation_k4(&self.onoro, &symm_state),
-      SymmetryClass::CV => Self::find_canonical_orientation_c2_cv(&self.onoro, &symm_state),
-      SymmetryClass::CE => Self::find_canonical_orientation_c2_ce(&self.onoro, &symm_state),
-      SymmetryClass::EV => Self::find_canonical_orientation_c2_ev(&self.onoro, &symm_state),
-      SymmetryClass::Trivial => Self::find_canonical_orientation_trivial(&self.onoro, &symm_state),
+    let player_hashes = self.uncanonical_player_hashes(&symm_state);
+    let hash = match self.onoro.player_color() {
+      PawnColor::Black => player_hashes[0],
+      PawnColor::White => player_hashes[1],
     };
+    let (hash, op_ord) = Self::find_canonical_orientation(symm_state.symm_class, hash);
+    #[cfg(feature = "hash128")]
+    let hash_hi = Self::canonical_hash_hi(&self.onoro, &symm_state, op_ord);
 
     unsafe {
       *self.view.get() = CanonicalView {
@@ -105,91 +419,195 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> OnoroView<N, N2
         symm_class: symm_state.symm_class,
         op_ord,
         hash,
+        #[cfg(feature = "hash128")]
+        hash_hi,
+        #[cfg(feature = "incremental-hash")]
+        player_hashes: Some(player_hashes),
       };
     }
   }
 
-  fn find_canonical_orientation_d6(
+  /// The hashes of this game state before canonicalization, reusing the
+  /// hashes found incrementally by `make_move` if there are any.
+  fn uncanonical_player_hashes(&self, symm_state: &BoardSymmetryState) -> [u64; 2] {
+    #[cfg(feature = "incremental-hash")]
+    if let Some(player_hashes) = self.canon_view().player_hashes {
+      #[cfg(feature = "verify-simd")]
+      assert_eq!(
+        player_hashes,
+        Self::player_hashes(&self.onoro, symm_state),
+        "Incremental hash mismatch in\n{}",
+        self.onoro
+      );
+      return player_hashes;
+    }
+    Self::player_hashes(&self.onoro, symm_state)
+  }
+
+  /// Computes the hashes of `onoro` on the hash table of its symmetry class,
+  /// before canonicalization.
+  fn player_hashes(
     onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
     symm_state: &BoardSymmetryState,
-  ) -> (u64, u8) {
-    static D6T: ViewHashTable<D6> = HashTable::new_c();
-    let hash = HashGroup::<D6>::new(D6T.hash(onoro, symm_state));
-
-    // Try all symmetries of the board state with invariant center of mass,
-    // choose the symmetry with the numerically smallest hash code.
-    D6::for_each()
-      .map(|op| (hash.apply(&op).hash(), op.ord() as u8))
-      .min_by(|(hash1, _op1), (hash2, _op2)| hash1.cmp(hash2))
-      .unwrap()
+  ) -> [u64; 2] {
+    match symm_state.symm_class {
+      SymmetryClass::C => D6T.player_hashes(onoro, symm_state),
+      SymmetryClass::V => D3T.player_hashes(onoro, symm_state),
+      SymmetryClass::E => K4T.player_hashes(onoro, symm_state),
+      SymmetryClass::CV => C2CVT.player_hashes(onoro, symm_state),
+      SymmetryClass::CE => C2CET.player_hashes(onoro, symm_state),
+      SymmetryClass::EV => C2EVT.player_hashes(onoro, symm_state),
+      SymmetryClass::Trivial => TT.player_hashes(onoro, symm_state),
+    }
   }
 
-  fn find_canonical_orientation_d3(
-    onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+  #[cfg(feature = "incremental-hash")]
+  fn pawn_hashes(
+    pos: PackedIdx,
+    color: PawnColor,
+    origin: HexPos,
     symm_state: &BoardSymmetryState,
-  ) -> (u64, u8) {
-    static D3T: ViewHashTable<D3> = HashTable::new_v();
-    let hash = HashGroup::<D3>::new(D3T.hash(onoro, symm_state));
+  ) -> [u64; 2] {
+    match symm_state.symm_class {
+      SymmetryClass::C => D6T.pawn_hashes(pos, color, origin, symm_state),
+      SymmetryClass::V => D3T.pawn_hashes(pos, color, origin, symm_state),
+      SymmetryClass::E => K4T.pawn_hashes(pos, color, origin, symm_state),
+      SymmetryClass::CV => C2CVT.pawn_hashes(pos, color, origin, symm_state),
+      SymmetryClass::CE => C2CET.pawn_hashes(pos, color, origin, symm_state),
+      SymmetryClass::EV => C2EVT.pawn_hashes(pos, color, origin, symm_state),
+      SymmetryClass::Trivial => TT.pawn_hashes(pos, color, origin, symm_state),
+    }
+  }
 
-    // Try all symmetries of the board state with invariant center of mass,
-    // choose the symmetry with the numerically smallest hash code.
-    D3::for_each()
-      .map(|op| (hash.apply(&op).hash(), op.ord() as u8))
-      .min_by(|(hash1, _op1), (hash2, _op2)| hash1.cmp(hash2))
-      .unwrap()
+  /// Computes the `player_hashes` of `child`, the game state after making
+  /// move `m` from this one, by adding and removing only the pawn that moved.
+  /// Every pawn's position in the hash table is relative to the origin and
+  /// symmetry op of the board, so this is only possible when the move changes
+  /// neither, and returns `None` otherwise, or if this state's hashes aren't
+  /// known.
+  ///
+  /// Since the center of mass moves by a fraction of a tile with almost every
+  /// move, which usually changes the symmetry op, few moves can be hashed
+  /// incrementally. The `view_hash` bench measures whether this pays off.
+  #[cfg(feature = "incremental-hash")]
+  fn child_player_hashes(&self, child: &Onoro<N, N2, ADJ_CNT_SIZE>, m: Move) -> Option<[u64; 2]> {
+    let mut player_hashes = self.canon_view().player_hashes?;
+    let symm_state = board_symm_state(&self.onoro);
+    let child_symm_state = board_symm_state(child);
+    let origin = self.onoro.origin(&symm_state);
+    if symm_state.symm_class != child_symm_state.symm_class
+      || symm_state.op != child_symm_state.op
+      || origin != child.origin(&child_symm_state)
+    {
+      return None;
+    }
+
+    let (to, from) = match m {
+      Move::Phase1Move { to } => (to, None),
+      Move::Phase2Move { to, from_idx } => (to, self.onoro.pawn_position(from_idx)),
+    };
+    let color = self.onoro.player_color();
+    for pos in [Some(to), from].into_iter().flatten() {
+      let [black, white] = Self::pawn_hashes(pos, color, origin, &symm_state);
+      player_hashes[0] ^= black;
+      player_hashes[1] ^= white;
+    }
+    Some(player_hashes)
   }
 
-  fn find_canonical_orientation_k4(
+  /// The high half of `canonical_hash128`: the hash of `onoro` on the second
+  /// set of hash tables, transformed by the op which canonicalizes the low
+  /// half, `op_ord`. Both halves come from tables with the same symmetries, so
+  /// the op maps the high half to the same value for every symmetric state.
+  #[cfg(feature = "hash128")]
+  fn canonical_hash_hi(
     onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
     symm_state: &BoardSymmetryState,
-  ) -> (u64, u8) {
-    static K4T: ViewHashTable<K4> = HashTable::new_e();
-    let hash = HashGroup::<K4>::new(K4T.hash(onoro, symm_state));
+    op_ord: u8,
+  ) -> u64 {
+    let player_hashes = match symm_state.symm_class {
+      SymmetryClass::C => D6T_HI.player_hashes(onoro, symm_state),
+      SymmetryClass::V => D3T_HI.player_hashes(onoro, symm_state),
+      SymmetryClass::E => K4T_HI.player_hashes(onoro, symm_state),
+      SymmetryClass::CV => C2CVT_HI.player_hashes(onoro, symm_state),
+      SymmetryClass::CE => C2CET_HI.player_hashes(onoro, symm_state),
+      SymmetryClass::EV => C2EVT_HI.player_hashes(onoro, symm_state),
+      SymmetryClass::Trivial => TT_HI.player_hashes(onoro, symm_state),
+    };
+    let hash = match onoro.player_color() {
+      PawnColor::Black => player_hashes[0],
+      PawnColor::White => player_hashes[1],
+    };
+    let op_ord = op_ord as usize;
+    match symm_state.symm_class {
+      SymmetryClass::C => HashGroup::<D6>::new(hash)
+        .apply(&D6::from_ord(op_ord))
+        .hash(),
+      SymmetryClass::V => HashGroup::<D3>::new(hash)
+        .apply(&D3::from_ord(op_ord))
+        .hash(),
+      SymmetryClass::E => HashGroup::<K4>::new(hash)
+        .apply(&K4::from_ord(op_ord))
+        .hash(),
+      SymmetryClass::CV | SymmetryClass::CE | SymmetryClass::EV => HashGroup::<C2>::new(hash)
+        .apply(&C2::from_ord(op_ord))
+        .hash(),
+      SymmetryClass::Trivial => hash,
+    }
+  }
+
+  /// Finds the symmetry op which canonicalizes a game state of class
+  /// `symm_class` with (uncanonicalized) hash `hash`, returning the canonical
+  /// hash and the ordinal of the op.
+  fn find_canonical_orientation(symm_class: SymmetryClass, hash: u64) -> (u64, u8) {
+    match symm_class {
+      SymmetryClass::C => Self::find_canonical_orientation_d6(hash),
+      SymmetryClass::V => Self::find_canonical_orientation_d3(hash),
+      SymmetryClass::E => Self::find_canonical_orientation_k4(hash),
+      SymmetryClass::CV | SymmetryClass::CE | SymmetryClass::EV => {
+        Self::find_canonical_orientation_c2(hash)
+      }
+      SymmetryClass::Trivial => (hash, Trivial::identity().ord() as u8),
+    }
+  }
+
+  fn find_canonical_orientation_d6(hash: u64) -> (u64, u8) {
+    let hash = HashGroup::<D6>::new(hash);
 
     // Try all symmetries of the board state with invariant center of mass,
     // choose the symmetry with the numerically smallest hash code.
-    K4::for_each()
+    D6::for_each()
       .map(|op| (hash.apply(&op).hash(), op.ord() as u8))
       .min_by(|(hash1, _op1), (hash2, _op2)| hash1.cmp(hash2))
       .unwrap()
   }
 
-  fn find_canonical_orientation_c2_cv(
-    onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
-    symm_state: &BoardSymmetryState,
-  ) -> (u64, u8) {
-    static C2CVT: ViewHashTable<C2> = HashTable::new_cv();
-    let hash = HashGroup::<C2>::new(C2CVT.hash(onoro, symm_state));
+  fn find_canonical_orientation_d3(hash: u64) -> (u64, u8) {
+    let hash = HashGroup::<D3>::new(hash);
 
     // Try all symmetries of the board state with invariant center of mass,
     // choose the symmetry with the numerically smallest hash code.
-    C2::for_each()
+    D3::for_each()
       .map(|op| (hash.apply(&op).hash(), op.ord() as u8))
       .min_by(|(hash1, _op1), (hash2, _op2)| hash1.cmp(hash2))
       .unwrap()
   }
 
-  fn find_canonical_orientation_c2_ce(
-    onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
-    symm_state: &BoardSymmetryState,
-  ) -> (u64, u8) {
-    static C2CET: ViewHashTable<C2> = HashTable::new_ce();
-    let hash = HashGroup::<C2>::new(C2CET.hash(onoro, symm_state));
+  fn find_canonical_orientation_k4(hash: u64) -> (u64, u8) {
+    let hash = HashGroup::<K4>::new(hash);
 
     // Try all symmetries of the board state with invariant center of mass,
     // choose the symmetry with the numerically smallest hash code.
-    C2::for_each()
+    K4::for_each()
       .map(|op| (hash.apply(&op).hash(), op.ord() as u8))
       .min_by(|(hash1, _op1), (hash2, _op2)| hash1.cmp(hash2))
       .unwrap()
   }
 
-  fn find_canonical_orientation_c2_ev(
-    onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
-    symm_state: &BoardSymmetryState,
-  ) -> (u64, u8) {
-    static C2EVT: ViewHashTable<C2> = HashTable::new_ev();
-    let hash = HashGroup::<C2>::new(C2EVT.hash(onoro, symm_state));
+  /// The CV, CE and EV symmetry classes all have C2 symmetry. Their tables
+  /// differ, but canonicalizing a hash is the same for each.
+  fn find_canonical_orientation_c2(hash: u64) -> (u64, u8) {
+    let hash = HashGroup::<C2>::new(hash);
 
     // Try all symmetries of the board state with invariant center of mass,
     // choose the symmetry with the numerically smallest hash code.
@@ -199,14 +617,46 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> OnoroView<N, N2
       .unwrap()
   }
 
-  fn find_canonical_orientation_trivial(
-    onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
-    symm_state: &BoardSymmetryState,
-  ) -> (u64, u8) {
-    static TT: ViewHashTable<Trivial> = HashTable::new_trivial();
-    (TT.hash(onoro, symm_state), Trivial::identity().ord() as u8)
+  /// Whether this view and `other`, which have the same canonical hash and
+  /// symmetry class, are views of the same game state.
+  #[cfg(not(feature = "hash128"))]
+  fn same_state(&self, other: &Self) -> bool {
+    self.same_board(other)
+  }
+
+  /// With 128-bit hashes, a collision of the full hash is too unlikely to
+  /// guard against, so states are compared by hash alone, skipping the
+  /// comparison of their boards.
+  #[cfg(feature = "hash128")]
+  fn same_state(&self, other: &Self) -> bool {
+    let same = self.canon_view().get_hash_hi() == other.canon_view().get_hash_hi();
+    #[cfg(feature = "verify-simd")]
+    assert_eq!(
+      same,
+      self.same_board(other),
+      "128-bit hash collision between\n{}\nand\n{}",
+      self.onoro,
+      other.onoro
+    );
+    same
+  }
+
+  /// Compares the boards of this view and `other`, which have the same
+  /// symmetry class, in their canonical orientations.
+  #[cfg(any(not(feature = "hash128"), feature = "verify-simd"))]
+  fn same_board(&self, other: &Self) -> bool {
+    match self.canon_view().get_symm_class() {
+      SymmetryClass::C => Self::cmp_views(self, other, HexPosOffset::apply_d6_c),
+      SymmetryClass::V => Self::cmp_views(self, other, HexPosOffset::apply_d3_v),
+      SymmetryClass::E => Self::cmp_views(self, other, HexPosOffset::apply_k4_e),
+      SymmetryClass::CV => Self::cmp_views(self, other, HexPosOffset::apply_c2_cv),
+      SymmetryClass::CE => Self::cmp_views(self, other, HexPosOffset::apply_c2_ce),
+      SymmetryClass::EV => Self::cmp_views(self, other, HexPosOffset::apply_c2_ev),
+      SymmetryClass::Trivial => Self::cmp_views(self, other, HexPosOffset::apply_trivial),
+    }
   }
 
+  #[cfg(any(not(feature = "hash128"), feature = "verify-simd"))]
   fn cmp_views<G: Group + Ordinal + Display, F>(
     view1: &OnoroView<N, N2, ADJ_CNT_SIZE>,
     view2: &OnoroView<N, N2, ADJ_CNT_SIZE>,
@@ -274,15 +724,7 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> PartialEq
       return false;
     }
 
-    match self.canon_view().get_symm_class() {
-      SymmetryClass::C => Self::cmp_views(self, other, HexPosOffset::apply_d6_c),
-      SymmetryClass::V => Self::cmp_views(self, other, HexPosOffset::apply_d3_v),
-      SymmetryClass::E => Self::cmp_views(self, other, HexPosOffset::apply_k4_e),
-      SymmetryClass::CV => Self::cmp_views(self, other, HexPosOffset::apply_c2_cv),
-      SymmetryClass::CE => Self::cmp_views(self, other, HexPosOffset::apply_c2_ce),
-      SymmetryClass::EV => Self::cmp_views(self, other, HexPosOffset::apply_c2_ev),
-      SymmetryClass::Trivial => Self::cmp_views(self, other, HexPosOffset::apply_trivial),
-    }
+    self.same_state(other)
   }
 }
 
@@ -296,7 +738,10 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Hash
 {
   fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
     self.maybe_initialize_canonical_view();
+    #[cfg(not(feature = "hash128"))]
     state.write_u64(self.canon_view().get_hash());
+    #[cfg(feature = "hash128")]
+    state.write_u128(self.canonical_hash128());
   }
 }
 
@@ -381,6 +826,51 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> GameMoveGenerat
   }
 }
 
+/// Generates the moves from a game state, skipping every move which leads to a
+/// game state symmetric to the result of a move that was already generated.
+/// Symmetric moves are common early in the game, where this greatly reduces
+/// the number of distinct moves to consider.
+pub struct CanonicalMoveGenerator<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> {
+  move_gen: MoveGenerator<N, N2, ADJ_CNT_SIZE>,
+  /// The game states reached by the moves generated so far.
+  seen: HashSet<OnoroView<N, N2, ADJ_CNT_SIZE>>,
+}
+
+impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> GameMoveGenerator
+  for CanonicalMoveGenerator<N, N2, ADJ_CNT_SIZE>
+{
+  type Item = Move;
+  type Game = OnoroView<N, N2, ADJ_CNT_SIZE>;
+
+  fn next(&mut self, view: &Self::Game) -> Option<Self::Item> {
+    while let Some(m) = self.move_gen.next(view.onoro()) {
+      let mut onoro = view.onoro().clone();
+      onoro.make_move(m);
+      if self.seen.insert(OnoroView::new(onoro)) {
+        return Some(m);
+      }
+    }
+    None
+  }
+}
+
+impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> OnoroView<N, N2, ADJ_CNT_SIZE> {
+  pub fn canonical_move_gen(&self) -> CanonicalMoveGenerator<N, N2, ADJ_CNT_SIZE> {
+    CanonicalMoveGenerator {
+      move_gen: self.onoro().each_move_gen(),
+      seen: HashSet::new(),
+    }
+  }
+
+  /// Iterates over the moves from this game state which lead to distinct game
+  /// states, up to symmetry.
+  pub fn each_canonical_move(
+    &self,
+  ) -> GameIterator<'_, CanonicalMoveGenerator<N, N2, ADJ_CNT_SIZE>, Self> {
+    self.canonical_move_gen().to_iter(self)
+  }
+}
+
 impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Game
   for OnoroView<N, N2, ADJ_CNT_SIZE>
 {
@@ -394,22 +884,93 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Game
     }
   }
 
+  /// See `Onoro::expand_into`.
+  fn expand_into<'a>(&self, arena: &'a mut MoveArena<Self::Move>) -> &'a [Self::Move] {
+    self.onoro().expand_into(arena)
+  }
+
   fn make_move(&mut self, m: Self::Move) {
     let mut onoro = self.onoro().clone();
     onoro.make_move(m);
-    *self = OnoroView::new(onoro);
+    #[cfg(feature = "incremental-hash")]
+    let view = CanonicalView {
+      player_hashes: self.child_player_hashes(&onoro, m),
+      ..CanonicalView::new()
+    };
+    #[cfg(not(feature = "incremental-hash"))]
+    let view = CanonicalView::new();
+    *self = OnoroView {
+      onoro,
+      view: view.into(),
+    };
   }
 
   fn current_player(&self) -> Self::PlayerIdentifier {
     self.onoro().player_color()
   }
 
+  /// See `Onoro::with_turn_passed`.
+  fn pass_equivalent(&self) -> Option<Self> {
+    self.onoro().with_turn_passed().map(OnoroView::new)
+  }
+
+  /// No position of Onoro is drawn by itself, so this is never
+  /// `GameResult::Tie`. Games are only drawn by repetition, which depends on
+  /// the line of play, see `abstract_game::Repetitions`.
   fn finished(&self) -> GameResult<Self::PlayerIdentifier> {
     match self.onoro().finished() {
       Some(color) => GameResult::Win(color),
       None => GameResult::NotFinished,
     }
   }
+
+  /// Checks for wins without making each move, see `Onoro::immediate_wins`.
+  fn search_immediate_win(&self) -> Option<Self::Move> {
+    if self.onoro().finished().is_some() {
+      return None;
+    }
+    let color = self.onoro().player_color();
+    self
+      .onoro()
+      .each_move()
+      .find(|&m| self.onoro().completes_line(color, m))
+  }
+}
+
+/// Views are compressed as the game state they wrap. Since views compare equal
+/// under symmetry, any symmetric game state decompresses to an equal view.
+impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Compress
+  for OnoroView<N, N2, ADJ_CNT_SIZE>
+{
+  const COMPRESSED_SIZE: usize = Onoro::<N, N2, ADJ_CNT_SIZE>::COMPRESSED_SIZE;
+
+  fn compress(&self, bytes: &mut [u8]) {
+    self.onoro().compress(bytes);
+  }
+
+  fn decompress(bytes: &[u8]) -> Option<Self> {
+    Onoro::decompress(bytes).map(OnoroView::new)
+  }
+}
+
+impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Evaluate
+  for OnoroView<N, N2, ADJ_CNT_SIZE>
+{
+  fn evaluate(&self) -> i32 {
+    self.onoro().evaluate()
+  }
+}
+
+impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Threats
+  for OnoroView<N, N2, ADJ_CNT_SIZE>
+{
+  fn immediate_wins(&self) -> Vec<Move> {
+    self.onoro().immediate_wins()
+  }
+
+  fn forced_blocks(&self) -> Vec<Move> {
+    self.onoro().forced_blocks()
+  }
 }
 
 impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Clone
@@ -425,7 +986,81 @@ impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Clone
 
 #[cfg(test)]
 mod tests {
-  use crate::{groups::SymmetryClass, Onoro16, OnoroView};
+  use abstract_game::Game;
+  use rand::{rngs::StdRng, SeedableRng};
+
+  use crate::{
+    groups::SymmetryClass,
+    testing::{random_playout, random_states},
+    Move, Onoro16, Onoro16View, OnoroView,
+  };
+
+  #[test]
+  fn test_orbit() {
+    let hex_start = Onoro16View::new(Onoro16::hex_start());
+    assert_eq!(hex_start.orbit().len(), 2);
+
+    let (states, _) = random_playout(
+      Onoro16::default_start(),
+      40,
+      &mut StdRng::seed_from_u64(4321),
+    );
+    for onoro in states.iter().filter(|onoro| onoro.finished().is_none()) {
+      let view = Onoro16View::new(onoro.clone());
+      let orbit = view.orbit();
+      assert!(12 % orbit.len() == 0, "Orbit of {} states", orbit.len());
+
+      let canonical = Onoro16View::board_key(&view.canonical_board());
+      for rotated in orbit {
+        let rotated = Onoro16View::new(rotated);
+        assert_eq!(rotated.canonical_hash(), view.canonical_hash());
+        assert_eq!(
+          Onoro16View::board_key(&rotated.canonical_board()),
+          canonical,
+          "Canonical boards differ for\n{onoro}\nand\n{}",
+          rotated.onoro()
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn test_map_move_to_canonical() {
+    let (states, _) = random_playout(Onoro16::default_start(), 40, &mut StdRng::seed_from_u64(5));
+    for onoro in states.iter().filter(|onoro| onoro.finished().is_none()) {
+      let view = Onoro16View::new(onoro.clone());
+      let canonical_board = view.canonical_board();
+      let orbit: Vec<_> = view.orbit().into_iter().map(Onoro16View::new).collect();
+      for m in onoro.each_move() {
+        let canonical_move = view.map_move_to_canonical(m);
+        assert_eq!(view.map_move_from_canonical(canonical_move), Some(m));
+
+        let mut child = onoro.clone();
+        child.make_move(m);
+        let child = Onoro16View::new(child);
+
+        // The canonical move is the same move on the canonical board.
+        let mut canonical_child = canonical_board.clone();
+        canonical_child.make_move(canonical_move);
+        assert_eq!(Onoro16View::new(canonical_child), child);
+
+        // And maps to the same move in every other orientation.
+        for rotated in &orbit {
+          assert_eq!(
+            rotated.map_move_to_canonical(rotated.map_move_from_canonical(canonical_move).unwrap()),
+            canonical_move
+          );
+          let mut rotated_child = rotated.onoro().clone();
+          rotated_child.make_move(rotated.map_move_from_canonical(canonical_move).unwrap());
+          assert_eq!(
+            Onoro16View::new(rotated_child),
+            child,
+            "{m} maps to {canonical_move} in\n{onoro}"
+          );
+        }
+      }
+    }
+  }
 
   #[test]
   #[allow(non_snake_case)]
@@ -849,4 +1484,112 @@ mod tests {
     assert_ne!(view2, view4);
     assert_eq!(view3, view4);
   }
+
+  #[test]
+  fn test_canonical_moves() {
+    for onoro in [Onoro16::default_start(), Onoro16::hex_start()] {
+      let view = OnoroView::new(onoro);
+      let children = |moves: Vec<Move>| -> Vec<_> {
+        moves
+          .into_iter()
+          .map(|m| {
+            let mut child = view.clone();
+            child.make_move(m);
+            child
+          })
+          .collect()
+      };
+      let all_children = children(view.each_move().collect());
+      let canonical_children = children(view.each_canonical_move().collect());
+
+      assert!(canonical_children.len() < all_children.len());
+      for child in &all_children {
+        assert_eq!(
+          canonical_children
+            .iter()
+            .filter(|canonical_child| *canonical_child == child)
+            .count(),
+          1
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn test_canonicalize_batch() {
+    let games: Vec<Onoro16> = random_states(10, 40, &mut StdRng::seed_from_u64(42));
+
+    let views = OnoroView::canonicalize_batch(&games);
+    assert_eq!(views.len(), games.len());
+    for (view, onoro) in views.iter().zip(&games) {
+      assert!(view.canon_view().initialized);
+      assert_eq!(view.onoro().to_string(), onoro.to_string());
+      let expected = OnoroView::new(onoro.clone());
+      assert_eq!(view.canonical_hash(), expected.canonical_hash());
+      assert_eq!(
+        view.canon_view().get_op_ord(),
+        expected.canon_view().get_op_ord()
+      );
+    }
+  }
+
+  #[test]
+  #[cfg(feature = "incremental-hash")]
+  fn test_incremental_hash() {
+    let mut rng = StdRng::seed_from_u64(1234);
+    let mut incremental_moves = 0;
+    for _ in 0..20 {
+      let (_, moves) = random_playout(Onoro16::default_start(), 60, &mut rng);
+      let mut view = OnoroView::new(Onoro16::default_start());
+      for m in moves {
+        // Hashing the parent lets the child's hash be found incrementally.
+        view.canonical_hash();
+        view.make_move(m);
+        if view.canon_view().player_hashes.is_some() {
+          incremental_moves += 1;
+        }
+
+        let expected = OnoroView::new(view.onoro().clone());
+        assert_eq!(
+          view.canonical_hash(),
+          expected.canonical_hash(),
+          "{}",
+          view.onoro()
+        );
+        assert_eq!(
+          view.canon_view().get_op_ord(),
+          expected.canon_view().get_op_ord()
+        );
+      }
+    }
+    assert!(incremental_moves > 0);
+  }
+
+  #[test]
+  #[cfg(feature = "hash128")]
+  fn test_hash128() {
+    use std::collections::HashMap;
+
+    let mut hashes = HashMap::new();
+    let states: Vec<Onoro16> = random_states(10, 40, &mut StdRng::seed_from_u64(9));
+    for onoro in states {
+      let view = Onoro16View::new(onoro);
+      let hash = view.canonical_hash128();
+      assert_eq!(hash as u64, view.canonical_hash());
+      for rotated in view.orbit() {
+        assert_eq!(Onoro16View::new(rotated).canonical_hash128(), hash);
+      }
+      // Distinct states have distinct 128-bit hashes. States with the colors
+      // and the player to move swapped are the same, so pawns are keyed by
+      // whether they belong to the player to move.
+      let canonical = view.canonical_board();
+      let to_move = canonical.player_color() == crate::PawnColor::Black;
+      let key: Vec<_> = Onoro16View::board_key(&canonical)
+        .into_iter()
+        .map(|(y, x, black)| (y, x, black == to_move))
+        .collect();
+      assert_eq!(*hashes.entry(hash).or_insert_with(|| key.clone()), key);
+    }
+    assert!(hashes.len() > 100);
+  }
 }
@@ -4,7 +4,7 @@ use crate::hex_pos::HexPosOffset;
 
 use super::hex_pos::HexPos;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct PackedIdx {
   bytes: Wrapping<u8>,
 }
@@ -43,6 +43,13 @@ impl PackedIdx {
     self.bytes.0
   }
 
+  /// The inverse of `PackedIdx::bytes`.
+  pub(crate) const fn from_bytes(bytes: u8) -> Self {
+    Self {
+      bytes: Wrapping(bytes),
+    }
+  }
+
   pub const unsafe fn unsafe_add(&self, other: &PackedIdx) -> PackedIdx {
     // Assume no overflow in x or y
     PackedIdx {
@@ -0,0 +1,376 @@
+use std::{fmt::Display, str::FromStr};
+
+use abstract_game::{Compress, Score, ScoreValue};
+
+use crate::{
+  error::{OnoroError, OnoroResult},
+  game_record::{from_hex, to_hex},
+  make_onoro_error, Move, Onoro16,
+};
+
+/// The result a search of a `SuitePosition` should find for its player to
+/// move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExpectedResult {
+  /// The player to move can force a win within this many moves, counting the
+  /// moves of both players.
+  Win(u32),
+  /// The opponent can force a win within this many moves.
+  Loss(u32),
+  /// Neither player can force a win within the search depth.
+  Tie,
+}
+
+impl Display for ExpectedResult {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ExpectedResult::Win(moves) => write!(f, "win {moves}"),
+      ExpectedResult::Loss(moves) => write!(f, "loss {moves}"),
+      ExpectedResult::Tie => write!(f, "tie"),
+    }
+  }
+}
+
+impl FromStr for ExpectedResult {
+  type Err = OnoroError;
+
+  fn from_str(s: &str) -> OnoroResult<Self> {
+    let mut words = s.split_whitespace();
+    let kind = words.next();
+    let moves = words.next().map(|moves| {
+      moves
+        .parse::<u32>()
+        .map_err(|err| make_onoro_error!("Invalid move count \"{moves}\": {err}"))
+    });
+    let result = match (kind, moves) {
+      (Some("win"), Some(moves)) => ExpectedResult::Win(moves?),
+      (Some("loss"), Some(moves)) => ExpectedResult::Loss(moves?),
+      (Some("tie"), None) => ExpectedResult::Tie,
+      _ => {
+        return Err(make_onoro_error!(
+          "Expected a result like \"win 3\", \"loss 2\" or \"tie\", found \"{s}\""
+        ))
+      }
+    };
+    if words.next().is_some() {
+      return Err(make_onoro_error!("Unexpected text after result \"{s}\""));
+    }
+    Ok(result)
+  }
+}
+
+/// A position of a regression suite for the solver, along with what a search
+/// of it should find, in a line-based format modeled after EPD for chess:
+///
+/// ```text
+/// # Comments and blank lines are skipped.
+/// 4a0b...; id "mate in 2"; depth 5; result win 3; bm b@h9
+/// . B W/ W B; depth 4; result tie
+/// ```
+///
+/// Each line starts with the position, either as the hex of the compressed
+/// game state, or as a board string (see `Onoro::from_board_string`) with its
+/// lines separated by "/". It's followed by operations separated by ";",
+/// each an opcode and its operand:
+///
+/// - `depth N`: the number of moves to search the position to. Required.
+/// - `result win N`, `result loss N` or `result tie`: the result the search
+///   must find for the player to move, where wins and losses must come within
+///   N moves, counting the moves of both players.
+/// - `bm M...`: the moves, in the notation of `Move::to_notation`, one of
+///   which the search must find as the best move.
+/// - `id "name"`: a name for the position in reports, which can't contain
+///   `"`.
+///
+/// Every position must have a `result`, a `bm`, or both.
+#[derive(Clone, Debug)]
+pub struct SuitePosition {
+  pub id: Option<String>,
+  pub position: Onoro16,
+  pub depth: u32,
+  pub result: Option<ExpectedResult>,
+  /// If not empty, the best move found by the search must be one of these.
+  pub best_moves: Vec<Move>,
+}
+
+impl SuitePosition {
+  /// A name for the position in reports: its id, or else its position.
+  pub fn name(&self) -> String {
+    match &self.id {
+      Some(id) => id.clone(),
+      None => position_hex(&self.position),
+    }
+  }
+
+  /// Checks the `score` and `best_move` found by a search of the position to
+  /// `depth`, returning a description of what's wrong with them if they don't
+  /// match what was expected.
+  pub fn check(&self, score: &Score, best_move: Option<Move>) -> Result<(), String> {
+    let matches = match self.result {
+      Some(ExpectedResult::Win(moves)) => {
+        score.cur_player_wins() && score.turn_count_win() != 0 && score.turn_count_win() <= moves
+      }
+      Some(ExpectedResult::Loss(moves)) => {
+        !score.cur_player_wins() && score.turn_count_win() != 0 && score.turn_count_win() <= moves
+      }
+      Some(ExpectedResult::Tie) => score.score_at_depth(self.depth) == ScoreValue::Tie,
+      None => true,
+    };
+    if !matches {
+      return Err(format!(
+        "expected {}, but the search found {score}",
+        self.result.unwrap()
+      ));
+    }
+
+    if !self.best_moves.is_empty() && !best_move.is_some_and(|m| self.best_moves.contains(&m)) {
+      let notation = |m: Move| m.to_notation(&self.position);
+      return Err(format!(
+        "expected best move {}, but the search found {}",
+        self
+          .best_moves
+          .iter()
+          .map(|&m| notation(m))
+          .collect::<Vec<_>>()
+          .join(" or "),
+        best_move.map_or("none".to_owned(), notation)
+      ));
+    }
+    Ok(())
+  }
+
+  /// Parses every position in `text`, one per line.
+  pub fn parse_all(text: &str) -> OnoroResult<Vec<Self>> {
+    text
+      .lines()
+      .enumerate()
+      .map(|(idx, line)| (idx, line.trim()))
+      .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+      .map(|(idx, line)| {
+        line
+          .parse()
+          .map_err(|err: OnoroError| make_onoro_error!("Line {}: {}", idx + 1, err.message()))
+      })
+      .collect()
+  }
+}
+
+impl Display for SuitePosition {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", position_hex(&self.position))?;
+    if let Some(id) = &self.id {
+      write!(f, "; id \"{id}\"")?;
+    }
+    write!(f, "; depth {}", self.depth)?;
+    if let Some(result) = &self.result {
+      write!(f, "; result {result}")?;
+    }
+    if !self.best_moves.is_empty() {
+      write!(f, "; bm")?;
+      for m in &self.best_moves {
+        write!(f, " {}", m.to_notation(&self.position))?;
+      }
+    }
+    Ok(())
+  }
+}
+
+impl FromStr for SuitePosition {
+  type Err = OnoroError;
+
+  /// Parses a single line of a suite.
+  fn from_str(s: &str) -> OnoroResult<Self> {
+    let mut fields = split_fields(s)?.into_iter();
+    let position = parse_position(fields.next().unwrap_or_default())?;
+
+    let mut id = None;
+    let mut depth = None;
+    let mut result = None;
+    let mut best_moves = Vec::new();
+    for field in fields {
+      let (opcode, operand) = field.split_once(' ').unwrap_or((field, ""));
+      let operand = operand.trim();
+      match opcode {
+        "id" => {
+          id = Some(
+            operand
+              .strip_prefix('"')
+              .and_then(|id| id.strip_suffix('"'))
+              .ok_or_else(|| make_onoro_error!("The id \"{operand}\" must be quoted"))?
+              .to_owned(),
+          )
+        }
+        "depth" => {
+          depth = Some(
+            operand
+              .parse::<u32>()
+              .map_err(|err| make_onoro_error!("Invalid depth \"{operand}\": {err}"))?,
+          )
+        }
+        "result" => result = Some(operand.parse()?),
+        "bm" => {
+          best_moves = operand
+            .split_whitespace()
+            .map(|notation| {
+              let m = position.parse_move(notation)?;
+              match position.explain_illegal(m).first() {
+                Some(violation) => Err(make_onoro_error!(
+                  "Illegal best move {notation}: {violation}"
+                )),
+                None => Ok(m),
+              }
+            })
+            .collect::<OnoroResult<_>>()?
+        }
+        "" => {}
+        opcode => return Err(make_onoro_error!("Unknown opcode \"{opcode}\"")),
+      }
+    }
+
+    let depth = depth.ok_or_else(|| make_onoro_error!("Missing depth"))?;
+    if result.is_none() && best_moves.is_empty() {
+      return Err(make_onoro_error!("Expected a result or best move"));
+    }
+    if let Some(ExpectedResult::Win(moves) | ExpectedResult::Loss(moves)) = result {
+      if moves == 0 || moves > depth {
+        return Err(make_onoro_error!(
+          "A result within {moves} moves can't be found by a search {depth} moves deep"
+        ));
+      }
+    }
+    if position.finished().is_some() {
+      return Err(make_onoro_error!("The game is already over"));
+    }
+
+    Ok(Self {
+      id,
+      position,
+      depth,
+      result,
+      best_moves,
+    })
+  }
+}
+
+fn position_hex(position: &Onoro16) -> String {
+  let mut bytes = [0; Onoro16::COMPRESSED_SIZE];
+  position.compress(&mut bytes);
+  to_hex(&bytes)
+}
+
+fn parse_position(position: &str) -> OnoroResult<Onoro16> {
+  let position = position.trim();
+  if position.chars().all(|c| c.is_ascii_hexdigit()) {
+    return from_hex(position)
+      .and_then(|bytes| Onoro16::decompress(&bytes))
+      .ok_or_else(|| make_onoro_error!("Invalid compressed position \"{position}\""));
+  }
+  Onoro16::from_board_string(&position.replace('/', "\n"))
+    .map_err(|err| make_onoro_error!("Invalid board \"{position}\": {err}"))
+}
+
+/// Splits a line into its ";"-separated fields, ignoring separators in quotes.
+fn split_fields(line: &str) -> OnoroResult<Vec<&str>> {
+  let mut fields = Vec::new();
+  let mut start = 0;
+  let mut quoted = false;
+  for (idx, c) in line.char_indices() {
+    match c {
+      '"' => quoted = !quoted,
+      ';' if !quoted => {
+        fields.push(line[start..idx].trim());
+        start = idx + 1;
+      }
+      _ => {}
+    }
+  }
+  if quoted {
+    return Err(make_onoro_error!("Unterminated quote in \"{line}\""));
+  }
+  fields.push(line[start..].trim());
+  Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+  use abstract_game::Score;
+
+  use crate::{Move, PackedIdx};
+
+  use super::{ExpectedResult, SuitePosition};
+
+  #[test]
+  fn test_round_trip() {
+    let text = "\
+# A comment.
+. B W/ W B; id \"semicolons; in ids\"; depth 3; result win 1; bm b@d14
+
+. B W/ W B B; depth 5; result tie
+";
+    let positions = SuitePosition::parse_all(text).unwrap();
+    assert_eq!(positions.len(), 2);
+    assert_eq!(positions[0].id.as_deref(), Some("semicolons; in ids"));
+    assert_eq!(positions[0].result, Some(ExpectedResult::Win(1)));
+    assert_eq!(
+      positions[0].best_moves,
+      vec![Move::Phase1Move {
+        to: PackedIdx::new(3, 13)
+      }]
+    );
+    assert_eq!(positions[1].depth, 5);
+    assert!(positions[1].best_moves.is_empty());
+
+    for position in positions {
+      let parsed: SuitePosition = position.to_string().parse().unwrap();
+      assert_eq!(parsed.id, position.id);
+      assert_eq!(parsed.position.to_string(), position.position.to_string());
+      assert_eq!(parsed.depth, position.depth);
+      assert_eq!(parsed.result, position.result);
+      assert_eq!(parsed.best_moves, position.best_moves);
+    }
+  }
+
+  #[test]
+  fn test_parse_errors() {
+    for text in [
+      // No depth.
+      ". B W/ W B; result tie",
+      // Nothing to check.
+      ". B W/ W B; depth 3",
+      // A win deeper than the search.
+      ". B W/ W B; depth 3; result win 5",
+      // Unknown opcode.
+      ". B W/ W B; depth 3; result tie; dm 2",
+      // Not a legal move.
+      ". B W/ W B; depth 3; bm b@a1",
+      // Not a position.
+      "xyz; depth 3; result tie",
+    ] {
+      assert!(
+        text.parse::<SuitePosition>().is_err(),
+        "{text} should not parse"
+      );
+    }
+  }
+
+  #[test]
+  fn test_check() {
+    let position: SuitePosition = ". B W/ W B; depth 4; result win 3; bm b@d14 b@d16"
+      .parse()
+      .unwrap();
+    let good_move = position.best_moves[1];
+    assert!(position.check(&Score::win(3), Some(good_move)).is_ok());
+    assert!(position.check(&Score::win(1), Some(good_move)).is_ok());
+    assert!(position.check(&Score::win(5), Some(good_move)).is_err());
+    assert!(position.check(&Score::lose(2), Some(good_move)).is_err());
+    assert!(position.check(&Score::tie(4), Some(good_move)).is_err());
+
+    let other_move = position
+      .position
+      .each_move()
+      .find(|m| !position.best_moves.contains(m))
+      .unwrap();
+    assert!(position.check(&Score::win(3), Some(other_move)).is_err());
+    assert!(position.check(&Score::win(3), None).is_err());
+  }
+}
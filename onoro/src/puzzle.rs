@@ -0,0 +1,186 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+  error::{OnoroError, OnoroResult},
+  make_onoro_error, GameRecord, Move, Onoro16, PawnColor, RecordResult,
+};
+
+/// A position where the player to move has exactly one winning move ("mate in
+/// N"), along with the line of play that wins.
+///
+/// Puzzles are written as game records (see `GameRecord`) starting from the
+/// puzzle's position, with the solution as the moves, and `MateIn` and
+/// `Difficulty` tags:
+///
+/// ```text
+/// [Source "selfplay.txt, game 3"]
+/// [MateIn "2"]
+/// [Difficulty "41"]
+/// [Start "..."]
+/// [Result "1-0"]
+///
+/// 1. b@h9 w@g9 2. b@j8 1-0
+/// ```
+#[derive(Clone, Debug)]
+pub struct Puzzle {
+  /// Tags describing the puzzle, like where it was found. `MateIn` and
+  /// `Difficulty` are derived from the other fields, so they aren't included
+  /// here.
+  pub tags: Vec<(String, String)>,
+  pub position: Onoro16,
+  /// The moves from `position` which win the game, alternating between the
+  /// winning player's moves and their opponent's best replies, and ending
+  /// with the winning move.
+  pub solution: Vec<Move>,
+}
+
+impl Puzzle {
+  /// A puzzle solved by `solution` from `position`. Fails if any move of the
+  /// solution is illegal, or if it doesn't end with the player to move in
+  /// `position` winning.
+  pub fn new(position: Onoro16, solution: Vec<Move>) -> OnoroResult<Self> {
+    let mut record = GameRecord::new(position.clone());
+    record.moves = solution.clone();
+    let final_position = record.final_position()?;
+    if final_position.finished() != Some(position.player_color()) {
+      return Err(make_onoro_error!(
+        "The solution doesn't end with {:?} winning",
+        position.player_color()
+      ));
+    }
+
+    Ok(Self {
+      tags: Vec::new(),
+      position,
+      solution,
+    })
+  }
+
+  /// The number of moves the winning player makes in the solution, i.e. the N
+  /// of "mate in N".
+  pub fn moves_to_win(&self) -> u32 {
+    (self.solution.len() as u32).div_ceil(2)
+  }
+
+  /// A rough measure of how hard the puzzle is: the number of alternatives the
+  /// winning player passes up along the solution, summed over each of their
+  /// moves.
+  pub fn difficulty(&self) -> u32 {
+    let mut onoro = self.position.clone();
+    let mut difficulty = 0;
+    for (idx, &m) in self.solution.iter().enumerate() {
+      if idx & 1 == 0 {
+        difficulty += onoro.each_move().count() as u32 - 1;
+      }
+      onoro.make_move(m);
+    }
+    difficulty
+  }
+
+  /// The puzzle as a game record, which is how it is written.
+  pub fn to_record(&self) -> GameRecord {
+    let mut record = GameRecord::new(self.position.clone());
+    record.tags = self.tags.clone();
+    record.set_tag("MateIn", self.moves_to_win().to_string());
+    record.set_tag("Difficulty", self.difficulty().to_string());
+    record.moves = self.solution.clone();
+    record.result = match self.position.player_color() {
+      PawnColor::Black => RecordResult::BlackWins,
+      PawnColor::White => RecordResult::WhiteWins,
+    };
+    record
+  }
+
+  /// Reads a puzzle written by `to_record`, checking that its solution wins.
+  pub fn from_record(mut record: GameRecord) -> OnoroResult<Self> {
+    record
+      .tags
+      .retain(|(name, _)| name != "MateIn" && name != "Difficulty");
+    let mut puzzle = Self::new(record.start, record.moves)?;
+    puzzle.tags = record.tags;
+    Ok(puzzle)
+  }
+
+  /// Parses every puzzle in `text`, which may hold any number of puzzles one
+  /// after another.
+  pub fn parse_all(text: &str) -> OnoroResult<Vec<Self>> {
+    GameRecord::parse_all(text)?
+      .into_iter()
+      .map(Self::from_record)
+      .collect()
+  }
+}
+
+impl Display for Puzzle {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.to_record())
+  }
+}
+
+impl FromStr for Puzzle {
+  type Err = OnoroError;
+
+  /// Parses a single puzzle.
+  fn from_str(s: &str) -> OnoroResult<Self> {
+    Self::from_record(s.parse()?)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::{rngs::StdRng, SeedableRng};
+
+  use crate::{testing::random_playout, Onoro16};
+
+  use super::Puzzle;
+
+  /// A random position whose player to move can win immediately.
+  fn position_with_win(rng: &mut StdRng) -> Onoro16 {
+    loop {
+      let (states, _) = random_playout(Onoro16::default_start(), 100, rng);
+      if let Some(onoro) = states
+        .into_iter()
+        .find(|onoro| onoro.finished().is_none() && !onoro.immediate_wins().is_empty())
+      {
+        return onoro;
+      }
+    }
+  }
+
+  #[test]
+  fn test_round_trip() {
+    let mut rng = StdRng::seed_from_u64(4321);
+    let puzzles: Vec<_> = (0..5)
+      .map(|idx| {
+        let position = position_with_win(&mut rng);
+        let solution = vec![position.immediate_wins()[0]];
+        let mut puzzle = Puzzle::new(position, solution).unwrap();
+        puzzle.tags.push(("Source".into(), format!("game {idx}")));
+        puzzle
+      })
+      .collect();
+
+    let text: String = puzzles
+      .iter()
+      .map(|puzzle| puzzle.to_string() + "\n")
+      .collect();
+    let parsed = Puzzle::parse_all(&text).unwrap();
+    assert_eq!(parsed.len(), puzzles.len());
+    for (puzzle, parsed) in puzzles.iter().zip(parsed) {
+      assert_eq!(parsed.tags, puzzle.tags);
+      assert_eq!(parsed.position.to_string(), puzzle.position.to_string());
+      assert_eq!(parsed.solution, puzzle.solution);
+      assert_eq!(parsed.moves_to_win(), 1);
+      assert_eq!(parsed.difficulty(), puzzle.difficulty());
+    }
+  }
+
+  #[test]
+  fn test_rejects_non_winning_solution() {
+    let mut rng = StdRng::seed_from_u64(4321);
+    let position = position_with_win(&mut rng);
+    let wins = position.immediate_wins();
+    let m = position.each_move().find(|m| !wins.contains(m)).unwrap();
+    assert!(Puzzle::new(position, vec![m]).is_err());
+  }
+}
@@ -0,0 +1,310 @@
+//! Ranking of game states: an integer index for each position, up to
+//! symmetry, from which the position can be recovered.
+//!
+//! A position is indexed by the shape its pawns form and which of those pawns
+//! belong to the player to move. Shapes are encoded by a breadth-first walk
+//! from the lowest pawn, recording for each newly reached tile whether it has
+//! a pawn. Colorings are ranked densely among the ways of choosing the
+//! player to move's pawns from the shape. Every orientation of the position is
+//! encoded, and the smallest index is its rank, so symmetric positions share a
+//! rank and distinct positions have distinct ranks.
+//!
+//! Shape codes are not dense: counting only the connected shapes would mean
+//! enumerating them, so ranks are sparse among the integers below their
+//! maximum, which is why they are `u128`s.
+
+use crate::{
+  groups::D6,
+  hex_pos::{HexPos, HexPosOffset},
+  Onoro, OnoroView, PackedIdx,
+};
+
+/// The offsets to the neighbors of a tile, in the order the shape walk visits
+/// them.
+const NEIGHBORS: [HexPosOffset; 6] = [
+  HexPosOffset::new(-1, -1),
+  HexPosOffset::new(0, -1),
+  HexPosOffset::new(-1, 0),
+  HexPosOffset::new(1, 0),
+  HexPosOffset::new(0, 1),
+  HexPosOffset::new(1, 1),
+];
+
+/// A pawn relative to the lowest pawn of the board, and whether it belongs to
+/// the player to move.
+type RankPawn = (HexPosOffset, bool);
+
+fn binomial(n: u32, k: u32) -> u128 {
+  if k > n {
+    return 0;
+  }
+  (0..k).fold(1, |c, i| c * (n - i) as u128 / (i + 1) as u128)
+}
+
+/// True if `pos` comes before the origin in the order the lowest pawn is
+/// chosen by, meaning no pawn can be there.
+fn before_origin(pos: HexPosOffset) -> bool {
+  (pos.y(), pos.x()) < (0, 0)
+}
+
+/// Walks the shape of a board of `n_pawns` pawns breadth-first from the
+/// origin, calling `has_pawn` with each newly reached tile, in order. Returns
+/// the pawns in the order they were reached, or `None` if `has_pawn` runs out
+/// of tiles to answer for before reaching them all.
+fn walk_shape(
+  n_pawns: usize,
+  mut has_pawn: impl FnMut(HexPosOffset) -> Option<bool>,
+) -> Option<Vec<HexPosOffset>> {
+  let mut pawns = vec![HexPosOffset::origin()];
+  let mut reached = vec![HexPosOffset::origin()];
+  let mut next = 0;
+  while pawns.len() < n_pawns {
+    let pos = *pawns.get(next)?;
+    next += 1;
+    for offset in NEIGHBORS {
+      let neighbor = pos + offset;
+      if before_origin(neighbor) || reached.contains(&neighbor) {
+        continue;
+      }
+      reached.push(neighbor);
+      if has_pawn(neighbor)? {
+        pawns.push(neighbor);
+        if pawns.len() == n_pawns {
+          break;
+        }
+      }
+    }
+  }
+  Some(pawns)
+}
+
+/// The index of `pawns` in this orientation, with the lowest pawn at the
+/// origin.
+fn encode<const N: usize>(pawns: &[RankPawn]) -> u128 {
+  let n_pawns = pawns.len();
+  let mut shape = 0u128;
+  let mut bit = 0;
+  let order = walk_shape(n_pawns, |pos| {
+    let has_pawn = pawns.iter().any(|&(pawn, _)| pawn == pos);
+    shape |= (has_pawn as u128) << bit;
+    bit += 1;
+    Some(has_pawn)
+  })
+  .unwrap();
+
+  // Rank the positions of the mover's pawns in walk order in the
+  // combinatorial number system.
+  let coloring: u128 = order
+    .iter()
+    .enumerate()
+    .filter(|&(_, pos)| pawns.iter().any(|&(pawn, mover)| pawn == *pos && mover))
+    .enumerate()
+    .map(|(i, (idx, _))| binomial(idx as u32, i as u32 + 1))
+    .sum();
+
+  let n_colorings = binomial(n_pawns as u32, n_pawns as u32 / 2);
+  (shape * n_colorings + coloring) * N as u128 + (n_pawns - 1) as u128
+}
+
+/// Recovers the pawns encoded by `index`, with the lowest pawn at the origin,
+/// or `None` if `index` isn't a valid encoding.
+fn decode<const N: usize>(index: u128) -> Option<Vec<RankPawn>> {
+  let n_pawns = (index % N as u128) as usize + 1;
+  let n_movers = n_pawns as u32 / 2;
+  let n_colorings = binomial(n_pawns as u32, n_movers);
+  let rest = index / N as u128;
+  let mut coloring = rest % n_colorings;
+  let shape = rest / n_colorings;
+
+  let mut bit = 0;
+  let order = walk_shape(n_pawns, |_| {
+    let has_pawn = bit < u128::BITS && (shape >> bit) & 1 != 0;
+    bit += 1;
+    (bit <= u128::BITS).then_some(has_pawn)
+  })?;
+  if bit < u128::BITS && shape >> bit != 0 {
+    return None;
+  }
+
+  let mut movers = vec![false; n_pawns];
+  for i in (1..=n_movers).rev() {
+    let idx = (0..n_pawns as u32)
+      .rev()
+      .find(|&idx| binomial(idx, i) <= coloring)
+      .unwrap();
+    coloring -= binomial(idx, i);
+    movers[idx as usize] = true;
+  }
+
+  Some(order.into_iter().zip(movers).collect())
+}
+
+/// Translates `pawns` so their lowest pawn, ordered by row and then column, is
+/// at the origin.
+fn to_origin(pawns: &mut [RankPawn]) {
+  let lowest = pawns
+    .iter()
+    .map(|&(pos, _)| pos)
+    .min_by_key(|pos| (pos.y(), pos.x()))
+    .unwrap();
+  for (pos, _) in pawns {
+    *pos -= lowest;
+  }
+}
+
+/// The pawns of `onoro` in each orientation, with the lowest pawn at the
+/// origin.
+fn orientations<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+  onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+) -> impl Iterator<Item = Vec<RankPawn>> {
+  let mover = onoro.player_color();
+  let base = HexPos::from(onoro.pawns().next().unwrap().pos);
+  let pawns: Vec<RankPawn> = onoro
+    .pawns()
+    .map(|pawn| (HexPos::from(pawn.pos) - base, pawn.color == mover))
+    .collect();
+
+  D6::for_each().map(move |op| {
+    let mut pawns: Vec<_> = pawns
+      .iter()
+      .map(|&(pos, mover)| (pos.apply_d6_c(&op), mover))
+      .collect();
+    to_origin(&mut pawns);
+    pawns
+  })
+}
+
+impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> OnoroView<N, N2, ADJ_CNT_SIZE> {
+  /// The index of this game state among all game states up to symmetry. Game
+  /// states have the same rank exactly when one is a rotation or reflection
+  /// of the other, with the colors swapped if the other player is to move.
+  /// `unrank` recovers a game state from its rank.
+  pub fn rank(&self) -> u128 {
+    orientations(self.onoro())
+      .map(|pawns| encode::<N>(&pawns))
+      .min()
+      .unwrap()
+  }
+
+  /// Reconstructs a game state with rank `rank`, or returns `None` if no game
+  /// state has that rank. In phase 2, the player to move is always black.
+  pub fn unrank(rank: u128) -> Option<Self> {
+    let pawns = decode::<N>(rank)?;
+
+    // Center the pawns on the board, keeping them off its edges.
+    let (min_x, max_x, min_y, max_y) = pawns.iter().fold(
+      (i32::MAX, i32::MIN, i32::MAX, i32::MIN),
+      |(min_x, max_x, min_y, max_y), (pos, _)| {
+        (
+          min_x.min(pos.x()),
+          max_x.max(pos.x()),
+          min_y.min(pos.y()),
+          max_y.max(pos.y()),
+        )
+      },
+    );
+    let interior = N as i32 - 2;
+    if max_x - min_x >= interior || max_y - min_y >= interior {
+      return None;
+    }
+    let shift = HexPosOffset::new(
+      1 + (interior - 1 - (max_x - min_x)) / 2 - min_x,
+      1 + (interior - 1 - (max_y - min_y)) / 2 - min_y,
+    );
+
+    // Black places first, so black is to move when an even number of pawns
+    // have been placed.
+    let black_turn = pawns.len().is_multiple_of(2);
+    let positions = |mover: bool| -> Vec<_> {
+      pawns
+        .iter()
+        .filter(|&&(_, is_mover)| is_mover == mover)
+        .map(|&(pos, _)| PackedIdx::from(HexPos::zero() + (pos + shift)))
+        .collect()
+    };
+    let game = Onoro::from_pawns(&positions(black_turn), &positions(!black_turn), black_turn);
+
+    let view = OnoroView::new(game);
+    (view.rank() == rank).then_some(view)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::{rngs::StdRng, SeedableRng};
+
+  use crate::{groups::D6, testing, Onoro16, Onoro16View};
+
+  use super::{decode, encode, orientations, RankPawn};
+
+  /// Game states from random playouts of the default start.
+  fn random_states() -> Vec<Onoro16> {
+    testing::random_states(20, 60, &mut StdRng::seed_from_u64(2718))
+  }
+
+  #[test]
+  fn test_rank_round_trip() {
+    for onoro in random_states() {
+      let rank = Onoro16View::new(onoro.clone()).rank();
+      let unranked = Onoro16View::unrank(rank).unwrap();
+      assert_eq!(unranked.rank(), rank, "Ranked {rank} from\n{onoro}");
+      assert_eq!(unranked.onoro().pawns_in_play(), onoro.pawns_in_play());
+    }
+  }
+
+  #[test]
+  fn test_unrank_won_states() {
+    let won: Vec<_> = random_states()
+      .into_iter()
+      .filter(|onoro| onoro.finished().is_some())
+      .collect();
+    assert!(!won.is_empty());
+    for onoro in won {
+      let rank = Onoro16View::new(onoro.clone()).rank();
+      let unranked = Onoro16View::unrank(rank).unwrap();
+      assert_eq!(unranked.rank(), rank, "Ranked {rank} from\n{onoro}");
+      assert_eq!(unranked.onoro().pawns_in_play(), onoro.pawns_in_play());
+      let winner = unranked.onoro().finished();
+      assert!(winner.is_some(), "Unranked\n{}", unranked.onoro());
+      assert_ne!(winner, Some(unranked.onoro().player_color()));
+    }
+  }
+
+  #[test]
+  fn test_encode_decode() {
+    let sorted = |mut pawns: Vec<RankPawn>| {
+      pawns.sort_by_key(|(pos, _)| (pos.y(), pos.x()));
+      pawns
+    };
+    for onoro in random_states().into_iter().step_by(5) {
+      for pawns in orientations(&onoro) {
+        let decoded = decode::<16>(encode::<16>(&pawns)).unwrap();
+        assert_eq!(sorted(decoded), sorted(pawns));
+      }
+    }
+  }
+
+  #[test]
+  fn test_rank_symmetries() {
+    for onoro in random_states().into_iter().step_by(7) {
+      let rank = Onoro16View::new(onoro.clone()).rank();
+      for op in D6::for_each() {
+        assert_eq!(Onoro16View::new(onoro.rotated_d6_c(op)).rank(), rank);
+      }
+    }
+  }
+
+  #[test]
+  fn test_unrank_invalid() {
+    // Three pawns, but a shape without any tiles.
+    assert!(Onoro16View::unrank(2).is_none());
+
+    // Only the smallest encoding of a position is its rank.
+    let onoro = Onoro16::default_start();
+    let rank = Onoro16View::new(onoro.clone()).rank();
+    for pawns in orientations(&onoro) {
+      let index = encode::<16>(&pawns);
+      assert_eq!(Onoro16View::unrank(index).is_some(), index == rank);
+    }
+  }
+}
@@ -0,0 +1,402 @@
+use std::{collections::BTreeSet, fmt::Write};
+
+use crate::{
+  coords::{Axial, SQRT_3},
+  square_notation, Move, Onoro, PackedIdx, TileState,
+};
+
+/// The radius of a pawn, relative to the radius of its tile.
+const PAWN_RADIUS: f64 = 0.62;
+
+/// The height of coordinate labels, relative to the radius of a tile.
+const LABEL_SIZE: f64 = 0.3;
+
+/// The width of tile and pawn outlines, relative to the radius of a tile.
+const STROKE_WIDTH: f64 = 0.06;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Rgb(u8, u8, u8);
+
+impl Rgb {
+  fn hex(&self) -> String {
+    format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+  }
+}
+
+const TILE_FILL: Rgb = Rgb(0xd9, 0xc7, 0xa3);
+const TILE_STROKE: Rgb = Rgb(0x8c, 0x76, 0x52);
+const LAST_MOVE_FILL: Rgb = Rgb(0xf0, 0xcf, 0x52);
+const BLACK_FILL: Rgb = Rgb(0x22, 0x22, 0x22);
+const BLACK_STROKE: Rgb = Rgb(0x00, 0x00, 0x00);
+const WHITE_FILL: Rgb = Rgb(0xf5, 0xf5, 0xf5);
+const WHITE_STROKE: Rgb = Rgb(0x44, 0x44, 0x44);
+const LABEL_FILL: Rgb = Rgb(0x5a, 0x4a, 0x32);
+
+/// How to draw a board with `svg` (or `png`, with the `png` feature).
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+  /// The distance from the center of each hexagonal tile to its corners, in
+  /// pixels.
+  pub tile_radius: f64,
+  /// If set, each tile is labeled with its name in move notation, e.g. "c4".
+  /// Labels are only drawn in SVGs.
+  pub coordinates: bool,
+  /// The tile to highlight as the destination of the last move, e.g. from
+  /// `moved_pawn_position`.
+  pub last_move: Option<PackedIdx>,
+}
+
+impl Default for RenderOptions {
+  fn default() -> Self {
+    Self {
+      tile_radius: 24.,
+      coordinates: false,
+      last_move: None,
+    }
+  }
+}
+
+/// A tile to draw, with its center in pixels.
+struct Tile {
+  pos: PackedIdx,
+  center: (f64, f64),
+  state: TileState,
+}
+
+/// The tiles of a board drawing, which are the tiles with pawns on them and
+/// the empty tiles next to them, along with the size of the drawing.
+struct Layout {
+  tiles: Vec<Tile>,
+  width: f64,
+  height: f64,
+}
+
+impl Layout {
+  fn new<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+    onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+    tile_radius: f64,
+  ) -> Self {
+    // Ordered top to bottom and left to right, so the drawing is the same for
+    // equal boards.
+    let mut positions = BTreeSet::new();
+    for pawn in onoro.pawns() {
+      positions.insert((std::cmp::Reverse(pawn.pos.y()), pawn.pos.x()));
+      for neighbor in onoro.empty_adjacent_tiles(pawn.pos) {
+        positions.insert((std::cmp::Reverse(neighbor.y()), neighbor.x()));
+      }
+    }
+
+    // Rows are drawn like `Onoro`'s `Display`, with each row shifted half a
+    // tile right of the row above it (see `Axial`).
+    let tile_width = SQRT_3 * tile_radius;
+    let centers: Vec<_> = positions
+      .iter()
+      .map(|&(std::cmp::Reverse(y), x)| Axial::from(PackedIdx::new(x, y)).to_pixel(tile_radius))
+      .collect();
+    let min_x = centers.iter().map(|(x, _)| *x).fold(f64::MAX, f64::min);
+    let max_x = centers.iter().map(|(x, _)| *x).fold(f64::MIN, f64::max);
+    let min_y = centers.iter().map(|(_, y)| *y).fold(f64::MAX, f64::min);
+    let max_y = centers.iter().map(|(_, y)| *y).fold(f64::MIN, f64::max);
+
+    // Leaves a margin of half a tile around the outermost tiles.
+    let margin = tile_radius / 2.;
+    let left = min_x - tile_width / 2. - margin;
+    let top = min_y - tile_radius - margin;
+    let tiles = positions
+      .iter()
+      .zip(centers)
+      .map(|(&(std::cmp::Reverse(y), x), (center_x, center_y))| {
+        let pos = PackedIdx::new(x, y);
+        Tile {
+          pos,
+          center: (center_x - left, center_y - top),
+          state: onoro.get_tile(pos),
+        }
+      })
+      .collect();
+
+    Self {
+      tiles,
+      width: max_x + tile_width / 2. + margin - left,
+      height: max_y + tile_radius + margin - top,
+    }
+  }
+}
+
+/// The corners of the tile centered at `center`, clockwise from the top.
+fn hex_corners(center: (f64, f64), radius: f64) -> [(f64, f64); 6] {
+  let (x, y) = center;
+  let half_width = SQRT_3 / 2. * radius;
+  [
+    (x, y - radius),
+    (x + half_width, y - radius / 2.),
+    (x + half_width, y + radius / 2.),
+    (x, y + radius),
+    (x - half_width, y + radius / 2.),
+    (x - half_width, y - radius / 2.),
+  ]
+}
+
+fn pawn_colors(state: &TileState) -> Option<(Rgb, Rgb)> {
+  match state {
+    TileState::Black => Some((BLACK_FILL, BLACK_STROKE)),
+    TileState::White => Some((WHITE_FILL, WHITE_STROKE)),
+    TileState::Empty => None,
+  }
+}
+
+/// Draws `onoro` as an SVG image, with a hexagon for each tile that has a pawn
+/// on it or next to it.
+pub fn svg<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+  onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+  options: &RenderOptions,
+) -> String {
+  let radius = options.tile_radius;
+  let layout = Layout::new(onoro, radius);
+  let stroke_width = STROKE_WIDTH * radius;
+
+  let mut svg = format!(
+    "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" \
+     viewBox=\"0 0 {w:.1} {h:.1}\">\n",
+    w = layout.width,
+    h = layout.height,
+  );
+  // Writing to a `String` can't fail.
+  for tile in &layout.tiles {
+    let (x, y) = tile.center;
+    let points = hex_corners(tile.center, radius)
+      .iter()
+      .map(|(x, y)| format!("{x:.1},{y:.1}"))
+      .collect::<Vec<_>>()
+      .join(" ");
+    let fill = if options.last_move == Some(tile.pos) {
+      LAST_MOVE_FILL
+    } else {
+      TILE_FILL
+    };
+    writeln!(
+      svg,
+      "  <polygon points=\"{points}\" fill=\"{}\" stroke=\"{}\" \
+       stroke-width=\"{stroke_width:.1}\"/>",
+      fill.hex(),
+      TILE_STROKE.hex(),
+    )
+    .unwrap();
+
+    if let Some((fill, stroke)) = pawn_colors(&tile.state) {
+      writeln!(
+        svg,
+        "  <circle cx=\"{x:.1}\" cy=\"{y:.1}\" r=\"{:.1}\" fill=\"{}\" stroke=\"{}\" \
+         stroke-width=\"{stroke_width:.1}\"/>",
+        PAWN_RADIUS * radius,
+        fill.hex(),
+        stroke.hex(),
+      )
+      .unwrap();
+    }
+
+    if options.coordinates {
+      // Below the pawn, in the bottom corner of the tile.
+      writeln!(
+        svg,
+        "  <text x=\"{x:.1}\" y=\"{:.1}\" font-size=\"{:.1}\" font-family=\"sans-serif\" \
+         text-anchor=\"middle\" fill=\"{}\">{}</text>",
+        y + 0.9 * radius,
+        LABEL_SIZE * radius,
+        LABEL_FILL.hex(),
+        square_notation(tile.pos),
+      )
+      .unwrap();
+    }
+  }
+  svg.push_str("</svg>\n");
+  svg
+}
+
+/// The position of the pawn placed or moved by making `m` from `onoro`, on the
+/// board after the move, which may differ from `m.to()` if the move shifted
+/// the board. Returns `None` if `m` doesn't move any pawn of `onoro`.
+pub fn moved_pawn_position<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+  onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+  m: Move,
+) -> Option<PackedIdx> {
+  let pawn_idx = match m {
+    Move::Phase1Move { .. } => onoro.pawns_in_play(),
+    Move::Phase2Move { from_idx, .. } => from_idx,
+  };
+  let mut onoro = onoro.clone();
+  onoro.try_make_move(m).ok()?;
+  onoro.pawn_position(pawn_idx)
+}
+
+/// Draws `onoro` like `svg`, as a PNG image with a transparent background.
+/// Coordinate labels aren't drawn, since this has no fonts to draw them with.
+#[cfg(feature = "png")]
+pub fn png<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+  onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+  options: &RenderOptions,
+) -> Vec<u8> {
+  /// Each pixel is the average of this many samples squared, to smooth edges.
+  const SAMPLES: u32 = 3;
+
+  let radius = options.tile_radius;
+  let layout = Layout::new(onoro, radius);
+  let stroke_width = STROKE_WIDTH * radius;
+  let width = layout.width.ceil() as u32;
+  let height = layout.height.ceil() as u32;
+
+  // The color at `(x, y)` of the topmost shape covering it, if any.
+  let color_at = |x: f64, y: f64| {
+    let mut color = None;
+    for tile in &layout.tiles {
+      let (dx, dy) = ((x - tile.center.0).abs(), (y - tile.center.1).abs());
+      // A point is inside a pointy-topped hexagon with circumradius `r` if it's
+      // within both its apothem horizontally and its slanted edges.
+      let in_hex = |r: f64| dx <= SQRT_3 / 2. * r && dx / SQRT_3 + dy <= r;
+      if !in_hex(radius) {
+        continue;
+      }
+      color = Some(if !in_hex(radius - 2. / SQRT_3 * stroke_width) {
+        TILE_STROKE
+      } else if options.last_move == Some(tile.pos) {
+        LAST_MOVE_FILL
+      } else {
+        TILE_FILL
+      });
+
+      if let Some((fill, stroke)) = pawn_colors(&tile.state) {
+        let distance = dx.hypot(dy);
+        if distance <= PAWN_RADIUS * radius + stroke_width / 2. {
+          color = Some(if distance >= PAWN_RADIUS * radius - stroke_width / 2. {
+            stroke
+          } else {
+            fill
+          });
+        }
+      }
+    }
+    color
+  };
+
+  let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+  for py in 0..height {
+    for px in 0..width {
+      let mut sum = [0u32; 3];
+      let mut covered = 0;
+      for sy in 0..SAMPLES {
+        for sx in 0..SAMPLES {
+          let x = px as f64 + (sx as f64 + 0.5) / SAMPLES as f64;
+          let y = py as f64 + (sy as f64 + 0.5) / SAMPLES as f64;
+          if let Some(Rgb(r, g, b)) = color_at(x, y) {
+            sum[0] += r as u32;
+            sum[1] += g as u32;
+            sum[2] += b as u32;
+            covered += 1;
+          }
+        }
+      }
+      match covered {
+        0 => pixels.extend([0; 4]),
+        _ => pixels.extend([
+          (sum[0] / covered) as u8,
+          (sum[1] / covered) as u8,
+          (sum[2] / covered) as u8,
+          (covered * 255 / (SAMPLES * SAMPLES)) as u8,
+        ]),
+      }
+    }
+  }
+
+  let mut bytes = Vec::new();
+  let mut encoder = png::Encoder::new(&mut bytes, width, height);
+  encoder.set_color(png::ColorType::Rgba);
+  encoder.set_depth(png::BitDepth::Eight);
+  // Encoding into a `Vec` can only fail on invalid dimensions, and the
+  // dimensions always match the pixels.
+  let mut writer = encoder.write_header().unwrap();
+  writer.write_image_data(&pixels).unwrap();
+  writer.finish().unwrap();
+  bytes
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{square_notation, Onoro16, PackedIdx};
+
+  use super::{moved_pawn_position, svg, RenderOptions, LAST_MOVE_FILL};
+
+  #[test]
+  fn test_svg_tiles() {
+    let onoro = Onoro16::default_start();
+    let svg = svg(&onoro, &RenderOptions::default());
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.ends_with("</svg>\n"));
+
+    let mut tiles = std::collections::HashSet::new();
+    for pawn in onoro.pawns() {
+      tiles.insert(pawn.pos);
+      tiles.extend(onoro.empty_adjacent_tiles(pawn.pos));
+    }
+    assert_eq!(svg.matches("<polygon").count(), tiles.len());
+    assert_eq!(
+      svg.matches("<circle").count(),
+      onoro.pawns_in_play() as usize
+    );
+    assert!(!svg.contains("<text"));
+  }
+
+  #[test]
+  fn test_svg_coordinates() {
+    let onoro = Onoro16::default_start();
+    let svg = svg(
+      &onoro,
+      &RenderOptions {
+        coordinates: true,
+        ..RenderOptions::default()
+      },
+    );
+    for pawn in onoro.pawns() {
+      assert!(svg.contains(&format!(">{}</text>", square_notation(pawn.pos))));
+    }
+  }
+
+  #[test]
+  fn test_last_move() {
+    let onoro = Onoro16::default_start();
+    let m = onoro.each_move().next().unwrap();
+    let mut after = onoro.clone();
+    after.make_move(m);
+
+    let last_move = moved_pawn_position(&onoro, m).unwrap();
+    assert!(after.pawns().any(|pawn| pawn.pos == last_move));
+
+    let highlight = format!("fill=\"{}\"", LAST_MOVE_FILL.hex());
+    let plain = svg(&after, &RenderOptions::default());
+    assert!(!plain.contains(&highlight));
+    let highlighted = svg(
+      &after,
+      &RenderOptions {
+        last_move: Some(last_move),
+        ..RenderOptions::default()
+      },
+    );
+    assert_eq!(highlighted.matches(&highlight).count(), 1);
+
+    // Tiles that aren't drawn can't be highlighted.
+    let far = svg(
+      &after,
+      &RenderOptions {
+        last_move: Some(PackedIdx::new(0, 0)),
+        ..RenderOptions::default()
+      },
+    );
+    assert!(!far.contains(&highlight));
+  }
+
+  #[cfg(feature = "png")]
+  #[test]
+  fn test_png() {
+    let png = super::png(&Onoro16::default_start(), &RenderOptions::default());
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+  }
+}
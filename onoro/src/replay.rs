@@ -0,0 +1,148 @@
+use std::fmt::Display;
+
+use crate::{Move, Onoro, PawnColor, Phase, RuleViolation};
+
+/// The first move of a game history that isn't legal, found by
+/// `Replay::validate`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IllegalReplay {
+  /// The index of the move in the history.
+  pub index: usize,
+  pub game_move: Move,
+  /// Every rule the move breaks.
+  pub violations: Vec<RuleViolation>,
+}
+
+impl Display for IllegalReplay {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Move {} ({}) is illegal", self.index, self.game_move)?;
+    match self.violations.first() {
+      Some(violation) => write!(f, ": {violation}"),
+      None => Ok(()),
+    }
+  }
+}
+
+/// A game history that `Replay::validate` found to be a legal game.
+#[derive(Clone, Debug)]
+pub struct Replay<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> {
+  /// The game after the last move.
+  pub game: Onoro<N, N2, ADJ_CNT_SIZE>,
+  /// The index of the first move of phase 2, if the game got that far.
+  pub phase2_start: Option<usize>,
+  /// The winner of the game, if it's over (see `Onoro::winner`). The game can
+  /// only be over after the last move, since no moves can follow a win.
+  pub winner: Option<PawnColor>,
+}
+
+impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Replay<N, N2, ADJ_CNT_SIZE> {
+  /// Replays `moves` from `start`, checking that each move is legal in the
+  /// game reached by the moves before it. This is meant for histories from
+  /// untrusted sources, e.g. games submitted by users, and checks every rule a
+  /// history can break: pawns are only moved once all have been placed, every
+  /// pawn keeps two neighbors and the board stays connected, and no moves are
+  /// made once someone has won, or once the player to move has none left.
+  ///
+  /// Returns the first illegal move, if any.
+  pub fn validate(
+    start: &Onoro<N, N2, ADJ_CNT_SIZE>,
+    moves: &[Move],
+  ) -> Result<Self, IllegalReplay> {
+    let mut game = start.clone();
+    let mut phase2_start = None;
+    for (index, &m) in moves.iter().enumerate() {
+      if let Err(err) = game.try_make_move(m) {
+        return Err(IllegalReplay {
+          index,
+          game_move: m,
+          violations: err.violations().to_vec(),
+        });
+      }
+      if phase2_start.is_none() && m.phase() == Phase::Phase2 {
+        phase2_start = Some(index);
+      }
+    }
+
+    let winner = game.winner();
+    Ok(Self {
+      game,
+      phase2_start,
+      winner,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::{rngs::StdRng, SeedableRng};
+
+  use crate::{testing::random_playout, Move, Onoro16, PackedIdx, Phase, RuleViolation};
+
+  use super::Replay;
+
+  #[test]
+  fn test_legal_games() {
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..20 {
+      let (states, moves) = random_playout(Onoro16::default_start(), 60, &mut rng);
+      let onoro = states.last().unwrap();
+      let replay = Replay::validate(&Onoro16::default_start(), &moves).unwrap();
+      assert_eq!(replay.game.to_string(), onoro.to_string());
+      assert_eq!(replay.winner, onoro.winner());
+      assert_eq!(
+        replay.phase2_start,
+        moves.iter().position(|m| m.phase() == Phase::Phase2)
+      );
+    }
+  }
+
+  #[test]
+  fn test_illegal_moves() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let start = Onoro16::default_start();
+
+    // A pawn placed away from every other pawn.
+    let (_, mut moves) = random_playout(start.clone(), 2, &mut rng);
+    let index = moves.len();
+    moves.push(Move::Phase1Move {
+      to: PackedIdx::new(1, 1),
+    });
+    let err = Replay::validate(&start, &moves).unwrap_err();
+    assert_eq!(err.index, index);
+    assert!(matches!(
+      err.violations[..],
+      [RuleViolation::TooFewNeighbors { .. }]
+    ));
+
+    // Any move after the game has been won.
+    let (_, mut moves) = (0..100)
+      .map(|_| random_playout(start.clone(), 200, &mut rng))
+      .find(|(states, _)| states.last().unwrap().winner().is_some())
+      .unwrap();
+    let index = moves.len();
+    moves.push(moves[index - 1]);
+    let err = Replay::validate(&start, &moves).unwrap_err();
+    assert_eq!(err.index, index);
+    assert!(err.violations.contains(&RuleViolation::GameFinished));
+
+    // Placing a pawn once every pawn has been placed.
+    let (mut moves, phase2_start) = (0..100)
+      .map(|_| random_playout(start.clone(), 40, &mut rng).1)
+      .find_map(|moves| {
+        let phase2_start = moves.iter().position(|m| m.phase() == Phase::Phase2)?;
+        Some((moves, phase2_start))
+      })
+      .unwrap();
+    moves.truncate(phase2_start + 1);
+    let Move::Phase2Move { to, .. } = moves[phase2_start] else {
+      unreachable!();
+    };
+    moves[phase2_start] = Move::Phase1Move { to };
+    let err = Replay::validate(&start, &moves).unwrap_err();
+    assert_eq!(err.index, phase2_start);
+    assert!(err.violations.contains(&RuleViolation::WrongPhase {
+      move_phase: Phase::Phase1,
+      game_phase: Phase::Phase2,
+    }));
+  }
+}
@@ -0,0 +1,62 @@
+use std::fmt::Display;
+
+use crate::{hex_pos::HexPos, r#move::Phase, PackedIdx};
+
+/// A rule of Onoro that a move breaks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RuleViolation {
+  /// Someone has already won the game, so no more moves can be made.
+  GameFinished,
+  /// The move is for a different phase than the one the game is in.
+  WrongPhase {
+    move_phase: Phase,
+    game_phase: Phase,
+  },
+  /// The pawn being moved does not belong to the current player (or doesn't
+  /// exist).
+  NotOwnPawn { from_idx: u32 },
+  /// The destination tile already has a pawn on it.
+  TileOccupied { pos: PackedIdx },
+  /// The destination tile does not have enough neighboring pawns.
+  TooFewNeighbors { pos: PackedIdx, neighbors: u32 },
+  /// Moving the pawn would leave a pawn it was adjacent to with too few
+  /// neighbors.
+  PawnIsolated { pos: PackedIdx, neighbors: u32 },
+  /// The move would split the pawns into multiple disconnected groups, listed
+  /// here.
+  BoardDisconnected { groups: Vec<Vec<PackedIdx>> },
+}
+
+impl Display for RuleViolation {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      RuleViolation::GameFinished => write!(f, "The game is already over"),
+      RuleViolation::WrongPhase {
+        move_phase,
+        game_phase,
+      } => write!(f, "A {move_phase} move can't be made in {game_phase}"),
+      RuleViolation::NotOwnPawn { from_idx } => write!(
+        f,
+        "Pawn index {from_idx} is not one of the current player's pawns"
+      ),
+      RuleViolation::TileOccupied { pos } => {
+        write!(f, "Tile {} is already occupied", HexPos::from(*pos))
+      }
+      RuleViolation::TooFewNeighbors { pos, neighbors } => write!(
+        f,
+        "Tile {} has {neighbors} neighboring pawns, but needs at least 2",
+        HexPos::from(*pos)
+      ),
+      RuleViolation::PawnIsolated { pos, neighbors } => write!(
+        f,
+        "Pawn at {} would be left with {neighbors} neighboring pawns, but needs at least 2",
+        HexPos::from(*pos)
+      ),
+      RuleViolation::BoardDisconnected { groups } => write!(
+        f,
+        "Pawns would be split into {} disconnected groups",
+        groups.len()
+      ),
+    }
+  }
+}
@@ -0,0 +1,166 @@
+use std::{
+  collections::HashMap,
+  time::{Duration, Instant},
+};
+
+use crate::{groups::SymmetryClass, Onoro, OnoroView};
+
+/// What `SymmetryStats` found for the positions of one symmetry class.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClassStats {
+  /// The number of positions added, counting repeats.
+  pub positions: u64,
+  /// The number of distinct positions, up to symmetry.
+  pub distinct: u64,
+  /// The number of distinct positions whose canonical hash was already taken
+  /// by a different position, of any class.
+  pub hash_collisions: u64,
+  /// The time `SymmetryStats::time_canonicalization` took to canonicalize
+  /// each distinct position `rounds` times.
+  pub canonicalize_time: Duration,
+  /// The number of positions canonicalized in `canonicalize_time`.
+  pub canonicalized: u64,
+}
+
+impl ClassStats {
+  /// The fraction of distinct positions whose hash collided with another's.
+  pub fn collision_rate(&self) -> f64 {
+    self.hash_collisions as f64 / self.distinct.max(1) as f64
+  }
+
+  /// The average time to find the canonical hash of one position, or `None`
+  /// if canonicalization hasn't been timed.
+  pub fn canonicalize_nanos(&self) -> Option<f64> {
+    (self.canonicalized != 0)
+      .then(|| self.canonicalize_time.as_nanos() as f64 / self.canonicalized as f64)
+  }
+}
+
+/// Tallies positions by `SymmetryClass`, to show which classes are common
+/// enough, and slow enough to canonicalize, to be worth optimizing.
+///
+/// Each distinct position is kept, to tell hash collisions apart from repeats
+/// of a position, so this is meant for samples of up to a few million
+/// positions. With the `hash128` feature, views compare equal by hash alone,
+/// so no collisions can be found.
+pub struct SymmetryStats<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> {
+  classes: [ClassStats; SymmetryClass::ALL.len()],
+  /// The distinct positions added so far, by canonical hash.
+  seen: HashMap<u64, Vec<OnoroView<N, N2, ADJ_CNT_SIZE>>>,
+}
+
+impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>
+  SymmetryStats<N, N2, ADJ_CNT_SIZE>
+{
+  pub fn new() -> Self {
+    Self {
+      classes: Default::default(),
+      seen: HashMap::new(),
+    }
+  }
+
+  pub fn add(&mut self, onoro: &Onoro<N, N2, ADJ_CNT_SIZE>) {
+    let stats = &mut self.classes[onoro.symmetry_class() as usize];
+    stats.positions += 1;
+
+    let view = OnoroView::new(onoro.clone());
+    let same_hash = self.seen.entry(view.canonical_hash()).or_default();
+    if same_hash.contains(&view) {
+      return;
+    }
+    stats.distinct += 1;
+    if !same_hash.is_empty() {
+      stats.hash_collisions += 1;
+    }
+    same_hash.push(view);
+  }
+
+  /// Finds the canonical hash of every distinct position added so far
+  /// `rounds` times, timing each class separately.
+  pub fn time_canonicalization(&mut self, rounds: u32) {
+    for class in SymmetryClass::ALL {
+      let positions: Vec<_> = self
+        .seen
+        .values()
+        .flatten()
+        .map(OnoroView::onoro)
+        .filter(|onoro| onoro.symmetry_class() == class)
+        .cloned()
+        .collect();
+
+      let start = Instant::now();
+      for _ in 0..rounds {
+        for onoro in &positions {
+          std::hint::black_box(OnoroView::new(onoro.clone()).canonical_hash());
+        }
+      }
+      let stats = &mut self.classes[class as usize];
+      stats.canonicalize_time += start.elapsed();
+      stats.canonicalized += rounds as u64 * positions.len() as u64;
+    }
+  }
+
+  pub fn class_stats(&self, class: SymmetryClass) -> &ClassStats {
+    &self.classes[class as usize]
+  }
+
+  /// The stats of all classes added together.
+  pub fn total(&self) -> ClassStats {
+    self
+      .classes
+      .iter()
+      .fold(ClassStats::default(), |total, stats| ClassStats {
+        positions: total.positions + stats.positions,
+        distinct: total.distinct + stats.distinct,
+        hash_collisions: total.hash_collisions + stats.hash_collisions,
+        canonicalize_time: total.canonicalize_time + stats.canonicalize_time,
+        canonicalized: total.canonicalized + stats.canonicalized,
+      })
+  }
+}
+
+impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Default
+  for SymmetryStats<N, N2, ADJ_CNT_SIZE>
+{
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::{rngs::StdRng, SeedableRng};
+
+  use crate::{groups::SymmetryClass, testing::random_playout, Onoro16, Onoro16View};
+
+  use super::SymmetryStats;
+
+  #[test]
+  fn test_orbits_are_one_position() {
+    let (states, _) = random_playout(Onoro16::default_start(), 9, &mut StdRng::seed_from_u64(17));
+    assert_eq!(states.len(), 10);
+    let mut stats = SymmetryStats::new();
+    let mut orbit_sizes = 0;
+    for onoro in &states {
+      let orbit = Onoro16View::new(onoro.clone()).orbit();
+      orbit_sizes += orbit.len() as u64;
+      for rotated in &orbit {
+        assert_eq!(rotated.symmetry_class(), onoro.symmetry_class());
+        stats.add(rotated);
+      }
+    }
+
+    let total = stats.total();
+    assert_eq!(total.positions, orbit_sizes);
+    assert_eq!(total.distinct, 10);
+    assert_eq!(total.hash_collisions, 0);
+    assert_eq!(total.canonicalize_nanos(), None);
+
+    stats.time_canonicalization(2);
+    assert_eq!(stats.total().canonicalized, 20);
+    for class in SymmetryClass::ALL {
+      let class_stats = stats.class_stats(class);
+      assert_eq!(class_stats.canonicalized, 2 * class_stats.distinct);
+    }
+  }
+}
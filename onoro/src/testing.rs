@@ -0,0 +1,43 @@
+//! Random games for the unit tests to check positions from.
+
+use rand::{seq::IteratorRandom, Rng};
+
+use crate::{Move, Onoro};
+
+/// Plays random moves from `start` until the game is won, the player to move
+/// has no moves, or `max_moves` moves have been made. Returns every state of
+/// the game, from `start` to the last, and the moves made between them.
+pub fn random_playout<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+  start: Onoro<N, N2, ADJ_CNT_SIZE>,
+  max_moves: usize,
+  rng: &mut impl Rng,
+) -> (Vec<Onoro<N, N2, ADJ_CNT_SIZE>>, Vec<Move>) {
+  let mut states = vec![start];
+  let mut moves = Vec::new();
+  while moves.len() < max_moves {
+    let onoro = states.last().unwrap();
+    if onoro.finished().is_some() {
+      break;
+    }
+    let Some(m) = onoro.each_move().choose(rng) else {
+      break;
+    };
+    let mut next = onoro.clone();
+    next.make_move(m);
+    states.push(next);
+    moves.push(m);
+  }
+  (states, moves)
+}
+
+/// The states of `n_games` random playouts from the default start, of up to
+/// `max_moves` moves each.
+pub fn random_states<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+  n_games: usize,
+  max_moves: usize,
+  rng: &mut impl Rng,
+) -> Vec<Onoro<N, N2, ADJ_CNT_SIZE>> {
+  (0..n_games)
+    .flat_map(|_| random_playout(Onoro::default_start(), max_moves, rng).0)
+    .collect()
+}
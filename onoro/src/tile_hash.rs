@@ -9,6 +9,7 @@ pub(crate) const V_MASK: u64 = 0x0fff_ffff_ffff_ffff;
 pub(crate) const E_MASK: u64 = 0xffff_ffff_ffff_ffff;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(transparent)]
 pub struct HashGroup<G: Group> {
   hash: u64,
   _p: PhantomData<G>,
@@ -460,7 +461,11 @@ impl Display for HashGroup<C2> {
   }
 }
 
+/// The hashes of a pawn on a tile. The layout is fixed as the current
+/// player's hash followed by the other player's, so vectorized hashing can load
+/// both with one 128-bit load.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
 pub struct TileHash<G: Group> {
   cur_hash: HashGroup<G>,
   other_hash: HashGroup<G>,
@@ -0,0 +1,46 @@
+use crate::{error::OnoroResult, make_onoro_error};
+
+/// Rule changes from standard Onoro, for experimenting with variants of the
+/// game. The number of pawns per player is set by the game's type instead,
+/// e.g. 4 for `Onoro8` and 8 for `Onoro16`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Variant {
+  win_length: u8,
+}
+
+impl Variant {
+  /// The rules of standard Onoro, where four in a row wins.
+  pub const STANDARD: Self = Self { win_length: 4 };
+
+  /// Shorter lines are formed by every pawn placed next to another of its
+  /// color, which makes for a trivial game.
+  pub const MIN_WIN_LENGTH: u32 = 3;
+
+  /// Standard rules, except that `win_length` pawns in a row win. Fails if
+  /// `win_length` is below `MIN_WIN_LENGTH`. Lines longer than a player's
+  /// pawn count are rejected when the variant is applied to a game, see
+  /// `Onoro::with_variant`.
+  pub fn with_win_length(win_length: u32) -> OnoroResult<Self> {
+    if win_length < Self::MIN_WIN_LENGTH || win_length > u8::MAX as u32 {
+      return Err(make_onoro_error!(
+        "Win length must be from {} to {}, found {win_length}",
+        Self::MIN_WIN_LENGTH,
+        u8::MAX
+      ));
+    }
+    Ok(Self {
+      win_length: win_length as u8,
+    })
+  }
+
+  /// The number of pawns in a row that win the game.
+  pub const fn win_length(&self) -> u32 {
+    self.win_length as u32
+  }
+}
+
+impl Default for Variant {
+  fn default() -> Self {
+    Self::STANDARD
+  }
+}
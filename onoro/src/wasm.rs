@@ -0,0 +1,103 @@
+use abstract_game::Compress;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+  render::{self, RenderOptions},
+  Move, Onoro16,
+};
+
+/// JavaScript bindings for `Onoro16`, so the web client can validate moves
+/// without a round trip to the server. Moves are passed as strings in the same
+/// notation as `Move`'s `Display` and `FromStr` implementations, e.g. "(8, 9)"
+/// or "(8, 9) from idx 2".
+#[wasm_bindgen(js_name = Onoro)]
+pub struct WasmOnoro {
+  onoro: Onoro16,
+}
+
+#[wasm_bindgen(js_class = Onoro)]
+impl WasmOnoro {
+  /// Starts a new game from the default start.
+  #[wasm_bindgen(constructor)]
+  pub fn new() -> Self {
+    Self {
+      onoro: Onoro16::default_start(),
+    }
+  }
+
+  /// Makes move `m` if it is legal, and throws otherwise.
+  #[wasm_bindgen(js_name = makeMove)]
+  pub fn make_move(&mut self, m: &str) -> Result<(), JsError> {
+    let m: Move = m.parse()?;
+    self
+      .onoro
+      .try_make_move(m)
+      .map_err(|err| JsError::new(err.message()))
+  }
+
+  /// All legal moves for the current player.
+  #[wasm_bindgen(js_name = legalMoves)]
+  pub fn legal_moves(&self) -> Vec<String> {
+    if self.onoro.finished().is_some() {
+      return Vec::new();
+    }
+    self.onoro.each_move().map(|m| m.to_string()).collect()
+  }
+
+  /// The color of the current player, "black" or "white".
+  #[wasm_bindgen(js_name = currentPlayer)]
+  pub fn current_player(&self) -> String {
+    self.onoro.player_color().to_string()
+  }
+
+  /// The color of the winner, "black" or "white", or `undefined` if the game
+  /// isn't over.
+  pub fn finished(&self) -> Option<String> {
+    self.onoro.winner().map(|color| color.to_string())
+  }
+
+  /// Packs the game state into `Onoro16::COMPRESSED_SIZE` bytes.
+  pub fn compress(&self) -> Vec<u8> {
+    let mut bytes = vec![0; Onoro16::COMPRESSED_SIZE];
+    self.onoro.compress(&mut bytes);
+    bytes
+  }
+
+  /// Unpacks a game state packed by `compress`, and throws if `bytes` isn't a
+  /// valid game state.
+  pub fn decompress(bytes: &[u8]) -> Result<WasmOnoro, JsError> {
+    Onoro16::decompress(bytes)
+      .map(|onoro| Self { onoro })
+      .ok_or_else(|| JsError::new("Invalid game state"))
+  }
+
+  /// Draws the board as an SVG image, labeling each tile with its name in move
+  /// notation if `coordinates` is set.
+  #[wasm_bindgen(js_name = toSvg)]
+  pub fn to_svg(&self, coordinates: bool) -> String {
+    render::svg(
+      &self.onoro,
+      &RenderOptions {
+        coordinates,
+        ..RenderOptions::default()
+      },
+    )
+  }
+
+  #[wasm_bindgen(js_name = toString)]
+  pub fn to_js_string(&self) -> String {
+    self.onoro.to_string()
+  }
+}
+
+impl Default for WasmOnoro {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl From<Onoro16> for WasmOnoro {
+  fn from(onoro: Onoro16) -> Self {
+    Self { onoro }
+  }
+}
@@ -0,0 +1,11 @@
+//! Builds the `onoro` JavaScript bindings as a WebAssembly module for the web
+//! client, e.g. with:
+//!
+//! ```text
+//! wasm-pack build onoro_wasm --target web
+//! ```
+//!
+//! The game logic only uses portable bit-parallel code, so it needs no
+//! platform-specific fallbacks to run in the browser.
+
+pub use onoro::WasmOnoro;
@@ -0,0 +1,143 @@
+use std::{
+  fmt::Write,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    OnceLock,
+  },
+};
+
+use cooperate::MetricsRegistry;
+use warp::{Filter, Rejection, Reply};
+
+use crate::{game_manager::GameManager, sessions::GameSessions};
+
+/// Counts a gauge of `ServerMetrics` up by one until dropped.
+pub struct GaugeGuard<'a>(&'a AtomicU64);
+
+impl<'a> GaugeGuard<'a> {
+  fn new(gauge: &'a AtomicU64) -> Self {
+    gauge.fetch_add(1, Ordering::Relaxed);
+    Self(gauge)
+  }
+}
+
+impl Drop for GaugeGuard<'_> {
+  fn drop(&mut self) {
+    self.0.fetch_sub(1, Ordering::Relaxed);
+  }
+}
+
+/// The live state of the server, reported by `GET /metrics`. The search
+/// metrics are shared with the solver through `searches`, which every search
+/// the server runs is registered with.
+pub struct ServerMetrics {
+  /// Every AI move, analysis and opening search, while it runs.
+  pub searches: MetricsRegistry,
+  /// The number of searches waiting for a free solver slot.
+  queued_searches: AtomicU64,
+  /// The number of WebSocket connections relayed to the game socket.
+  connected_sockets: AtomicU64,
+}
+
+impl ServerMetrics {
+  fn new() -> Self {
+    Self {
+      searches: MetricsRegistry::new(),
+      queued_searches: AtomicU64::new(0),
+      connected_sockets: AtomicU64::new(0),
+    }
+  }
+
+  /// The metrics of the whole server.
+  pub fn global() -> &'static Self {
+    static METRICS: OnceLock<ServerMetrics> = OnceLock::new();
+    METRICS.get_or_init(Self::new)
+  }
+
+  /// Counts a search as queued until the returned guard is dropped, which
+  /// should be once it gets a solver slot.
+  pub fn queue_search(&self) -> GaugeGuard<'_> {
+    GaugeGuard::new(&self.queued_searches)
+  }
+
+  /// Counts a socket as connected until the returned guard is dropped.
+  pub fn connect_socket(&self) -> GaugeGuard<'_> {
+    GaugeGuard::new(&self.connected_sockets)
+  }
+
+  /// All metrics in the Prometheus text exposition format.
+  fn render(&self) -> String {
+    let searches = self.searches.report();
+    let mut text = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, value: f64| {
+      // Writing to a `String` can't fail.
+      let _ = write!(
+        text,
+        "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n"
+      );
+    };
+    metric(
+      "onoro_hosted_games",
+      "gauge",
+      "Games between two players being hosted.",
+      GameManager::global().len() as f64,
+    );
+    metric(
+      "onoro_sessions",
+      "gauge",
+      "Games against the AI or on the analysis board being tracked.",
+      GameSessions::global().len() as f64,
+    );
+    metric(
+      "onoro_connected_sockets",
+      "gauge",
+      "WebSocket connections to the game socket.",
+      self.connected_sockets.load(Ordering::Relaxed) as f64,
+    );
+    metric(
+      "onoro_solver_queue_depth",
+      "gauge",
+      "Searches waiting for a free solver slot.",
+      self.queued_searches.load(Ordering::Relaxed) as f64,
+    );
+    metric(
+      "onoro_solver_running_searches",
+      "gauge",
+      "Searches running right now.",
+      searches.running_searches as f64,
+    );
+    metric(
+      "onoro_solver_searches_total",
+      "counter",
+      "Searches that have finished.",
+      searches.finished_searches as f64,
+    );
+    metric(
+      "onoro_solver_nodes_total",
+      "counter",
+      "Game states visited by every search.",
+      searches.nodes as f64,
+    );
+    metric(
+      "onoro_solver_nodes_per_second",
+      "gauge",
+      "Game states visited per second by the running searches.",
+      searches.nodes_per_sec,
+    );
+    text
+  }
+}
+
+/// `GET /metrics`, the server's metrics in the Prometheus text format.
+pub fn metrics_route() -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+  warp::path("metrics")
+    .and(warp::path::end())
+    .and(warp::get())
+    .map(|| {
+      warp::reply::with_header(
+        ServerMetrics::global().render(),
+        "content-type",
+        "text/plain; version=0.0.4",
+      )
+    })
+}
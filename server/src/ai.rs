@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use cooperate::{Engine, Options, TimeLimit};
+use onoro::{Move, Onoro16, Onoro16View};
+use serde::Deserialize;
+
+use crate::{admin::ServerMetrics, governor::Admission};
+
+/// How strongly the AI opponent plays.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum Difficulty {
+  Easy,
+  Medium,
+  Hard,
+}
+
+impl Difficulty {
+  /// The deepest search the AI makes at this difficulty.
+  fn search_depth(self) -> u32 {
+    match self {
+      Difficulty::Easy => 2,
+      Difficulty::Medium => 6,
+      Difficulty::Hard => 12,
+    }
+  }
+
+  /// How long the AI may think at this difficulty.
+  fn time_limit(self) -> TimeLimit {
+    let (soft, hard) = match self {
+      Difficulty::Easy => (Duration::from_millis(100), Duration::from_millis(500)),
+      Difficulty::Medium => (Duration::from_secs(1), Duration::from_secs(3)),
+      Difficulty::Hard => (Duration::from_secs(5), Duration::from_secs(15)),
+    };
+    TimeLimit { soft, hard }
+  }
+
+  fn options(self, num_threads: u32) -> Options {
+    let search_depth = self.search_depth();
+    Options {
+      num_threads,
+      search_depth,
+      unit_depth: search_depth / 2,
+      time_limit: Some(self.time_limit()),
+      ..Default::default()
+    }
+  }
+}
+
+/// Chooses a move for the current player of `game`, or returns `None` if they
+/// have no legal moves. The search runs on the blocking thread pool, so it
+/// doesn't hold up the async runtime, once `admission` gets a slot to run in.
+pub async fn choose_move(
+  game: Onoro16,
+  difficulty: Difficulty,
+  admission: Admission<'_>,
+) -> Option<Move> {
+  if game.finished().is_some() {
+    return None;
+  }
+
+  let permit = admission.start().await;
+  let options = difficulty.options(permit.threads());
+  tokio::task::spawn_blocking(move || {
+    let mut engine = Engine::new(Onoro16View::new(game), options);
+    let _registered = ServerMetrics::global().searches.register(engine.progress());
+    engine.solve();
+    engine.best_move_by_evaluation()
+  })
+  .await
+  .unwrap_or_else(|err| {
+    println!("Error searching for AI move: {:?}", err);
+    None
+  })
+}
@@ -0,0 +1,319 @@
+use std::{
+  collections::HashMap,
+  sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, Mutex, OnceLock,
+  },
+  time::{Duration, Instant},
+};
+
+use cooperate::{CancellationToken, Engine, SearchProgress};
+use onoro::{Onoro16, Onoro16View};
+use serde::Serialize;
+
+use crate::{admin::ServerMetrics, governor::Admission};
+
+/// The deepest search `analyze_moves` and batch jobs will make, so that a
+/// single request can't occupy the solver indefinitely.
+const MAX_MOVE_ANALYSIS_DEPTH: u32 = 12;
+
+/// Finished jobs are forgotten after this long, even if their status was
+/// never polled.
+const FINISHED_JOB_TTL: Duration = Duration::from_secs(10 * 60);
+
+pub type JobId = u64;
+
+/// The result of analyzing a single position of a batch job.
+#[derive(Clone, Debug, Serialize)]
+pub struct AnalysisResult {
+  /// The index of the analyzed position in the submitted list.
+  pub index: u32,
+  /// The score of the position, from the perspective of the player to move.
+  pub score: String,
+  /// The best move for the player to move, if they have any legal moves.
+  pub best_move: Option<String>,
+}
+
+/// The score of one legal move from an analyzed position.
+#[derive(Clone, Debug, Serialize)]
+pub struct MoveAnalysis {
+  /// The move in `Move`'s notation, e.g. "(8, 9) from idx 2".
+  pub game_move: String,
+  /// The score of the position after the move, from the perspective of the
+  /// player making it.
+  pub score: String,
+  /// The number of moves ahead the score is known to be correct for.
+  pub depth: u32,
+}
+
+/// How far along the search of the position currently being analyzed is.
+#[derive(Clone, Debug, Serialize)]
+pub struct SearchStatus {
+  /// The depth being searched to.
+  pub depth: u32,
+  /// The number of states visited so far.
+  pub nodes: u64,
+  pub nodes_per_sec: f64,
+  /// The fraction of table lookups that found an already-resolved state.
+  pub hit_rate: f64,
+  pub elapsed_ms: u64,
+}
+
+/// A snapshot of the progress of a batch job.
+#[derive(Clone, Debug, Serialize)]
+pub struct JobStatus {
+  /// The number of positions that have been analyzed so far.
+  pub completed: u32,
+  /// The total number of positions in the job that will be analyzed.
+  /// Positions of finished games are skipped.
+  pub total: u32,
+  /// True if the job was cancelled before analyzing every position.
+  pub cancelled: bool,
+  /// True once the job will not produce any more results.
+  pub finished: bool,
+  /// All results after the first `from_index` that were requested.
+  pub results: Vec<AnalysisResult>,
+  /// The progress of the position currently being analyzed, if the job is
+  /// still running.
+  pub search: Option<SearchStatus>,
+}
+
+#[derive(Default)]
+struct JobProgress {
+  results: Vec<AnalysisResult>,
+  /// When the job stopped producing results, if it has.
+  finished_at: Option<Instant>,
+}
+
+struct Job {
+  total: u32,
+  cancelled: AtomicBool,
+  /// Cancels the search of the position currently being analyzed.
+  token: CancellationToken,
+  progress: Mutex<JobProgress>,
+  /// The live progress of the position currently being analyzed.
+  search: Mutex<Arc<SearchProgress>>,
+}
+
+/// Tracks batch analysis jobs by ID. Jobs are executed on background workers
+/// and report their results incrementally, so clients can poll for new
+/// results while the rest of the job is still running.
+pub struct AnalysisJobs {
+  next_id: AtomicU64,
+  jobs: Mutex<HashMap<JobId, Arc<Job>>>,
+}
+
+impl AnalysisJobs {
+  fn new() -> Self {
+    Self {
+      next_id: AtomicU64::new(0),
+      jobs: Mutex::new(HashMap::new()),
+    }
+  }
+
+  /// The analysis jobs shared by all client connections.
+  pub fn global() -> &'static Self {
+    static JOBS: OnceLock<AnalysisJobs> = OnceLock::new();
+    JOBS.get_or_init(Self::new)
+  }
+
+  /// Scores every legal move of `game` with a search of at most
+  /// `search_depth`, which is capped at `MAX_MOVE_ANALYSIS_DEPTH`, once
+  /// `admission` gets a slot to run in. Returns no moves if the player to move
+  /// has none.
+  ///
+  /// If the returned future is dropped, e.g. because the client disconnected,
+  /// the search is cancelled rather than left to finish for nobody.
+  pub async fn analyze_moves(
+    &self,
+    game: Onoro16,
+    search_depth: u32,
+    admission: Admission<'_>,
+  ) -> Vec<MoveAnalysis> {
+    if game.finished().is_some() {
+      return Vec::new();
+    }
+
+    let permit = admission.start().await;
+    let search_depth = search_depth.clamp(1, MAX_MOVE_ANALYSIS_DEPTH);
+    let options = cooperate::Options {
+      num_threads: permit.threads(),
+      search_depth,
+      unit_depth: search_depth / 2,
+      ..Default::default()
+    };
+
+    let token = CancellationToken::new();
+    let _cancel_on_drop = token.clone().drop_guard();
+    tokio::task::spawn_blocking(move || {
+      let mut engine = Engine::new(Onoro16View::new(game), options);
+      let _registered = ServerMetrics::global().searches.register(engine.progress());
+      if engine.solve_cancellable(Some(&token)).is_none() {
+        return Vec::new();
+      }
+      engine
+        .move_scores()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(m, score)| MoveAnalysis {
+          game_move: m.to_string(),
+          score: score.to_string(),
+          depth: score.determined_depth(),
+        })
+        .collect()
+    })
+    .await
+    .unwrap_or_else(|err| {
+      println!("Error analyzing moves: {:?}", err);
+      Vec::new()
+    })
+  }
+
+  /// Submits a list of positions to be analyzed to `search_depth`, which is
+  /// capped at `MAX_MOVE_ANALYSIS_DEPTH`, returning the ID of the job. A full
+  /// game can be analyzed by submitting each of the positions it passed
+  /// through. Positions of finished games have nothing to search, and are
+  /// skipped. The job starts once `admission` gets a slot to run in, and holds
+  /// it until every position has been analyzed.
+  pub fn submit(
+    &self,
+    games: Vec<Onoro16>,
+    search_depth: u32,
+    admission: Admission<'static>,
+  ) -> JobId {
+    let search_depth = search_depth.clamp(1, MAX_MOVE_ANALYSIS_DEPTH);
+    let games: Vec<_> = games
+      .into_iter()
+      .enumerate()
+      .filter(|(_, game)| game.finished().is_none())
+      .collect();
+
+    let job_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+    let job = Arc::new(Job {
+      total: games.len() as u32,
+      cancelled: AtomicBool::new(false),
+      token: CancellationToken::new(),
+      progress: Mutex::new(JobProgress::default()),
+      search: Mutex::new(Arc::new(SearchProgress::new())),
+    });
+    let mut jobs = self.jobs.lock().unwrap();
+    Self::remove_expired(&mut jobs);
+    jobs.insert(job_id, job.clone());
+    drop(jobs);
+
+    tokio::spawn(async move {
+      let permit = admission.start().await;
+
+      for (index, game) in games {
+        if job.cancelled.load(Ordering::Relaxed) {
+          break;
+        }
+
+        let options = cooperate::Options {
+          num_threads: permit.threads(),
+          search_depth,
+          unit_depth: search_depth / 2,
+          ..Default::default()
+        };
+        let mut engine = Engine::new(Onoro16View::new(game), options);
+        *job.search.lock().unwrap() = engine.progress();
+        let token = job.token.clone();
+        let solution = tokio::task::spawn_blocking(move || {
+          let _registered = ServerMetrics::global().searches.register(engine.progress());
+          let score = engine.solve_cancellable(Some(&token))?;
+          Some((score, engine.best_move()))
+        })
+        .await;
+
+        match solution {
+          Ok(Some((score, best_move))) => {
+            job.progress.lock().unwrap().results.push(AnalysisResult {
+              index: index as u32,
+              score: score.to_string(),
+              best_move: best_move.map(|m| m.to_string()),
+            })
+          }
+          // The job was cancelled.
+          Ok(None) => break,
+          Err(err) => {
+            println!(
+              "Error analyzing position {index} of job {job_id}: {:?}",
+              err
+            );
+            break;
+          }
+        }
+      }
+
+      job.progress.lock().unwrap().finished_at = Some(Instant::now());
+    });
+
+    job_id
+  }
+
+  /// Drops every job that finished more than `FINISHED_JOB_TTL` ago, so jobs
+  /// whose clients never poll them again don't pile up.
+  fn remove_expired(jobs: &mut HashMap<JobId, Arc<Job>>) {
+    jobs.retain(|_, job| {
+      job
+        .progress
+        .lock()
+        .unwrap()
+        .finished_at
+        .is_none_or(|finished_at| finished_at.elapsed() < FINISHED_JOB_TTL)
+    });
+  }
+
+  /// Returns the status of job `job_id`, including all results from index
+  /// `from_index` on, or `None` if no such job exists. Once a finished job's
+  /// status has been reported, the job is forgotten, and finished jobs that
+  /// are never polled are forgotten after `FINISHED_JOB_TTL`.
+  pub fn status(&self, job_id: JobId, from_index: u32) -> Option<JobStatus> {
+    let mut jobs = self.jobs.lock().unwrap();
+    Self::remove_expired(&mut jobs);
+    let job = jobs.get(&job_id)?.clone();
+    let progress = job.progress.lock().unwrap();
+    let finished = progress.finished_at.is_some();
+
+    let status = JobStatus {
+      completed: progress.results.len() as u32,
+      total: job.total,
+      cancelled: job.cancelled.load(Ordering::Relaxed),
+      finished,
+      results: progress
+        .results
+        .iter()
+        .skip(from_index as usize)
+        .cloned()
+        .collect(),
+      search: (!finished).then(|| {
+        let report = job.search.lock().unwrap().report();
+        SearchStatus {
+          depth: report.depth,
+          nodes: report.nodes,
+          nodes_per_sec: report.nodes_per_sec,
+          hit_rate: report.hit_rate,
+          elapsed_ms: report.elapsed.as_millis() as u64,
+        }
+      }),
+    };
+
+    if finished {
+      jobs.remove(&job_id);
+    }
+    Some(status)
+  }
+
+  /// Stops job `job_id`, abandoning the position currently being analyzed and
+  /// starting no further positions. Returns false if no such job exists.
+  pub fn cancel(&self, job_id: JobId) -> bool {
+    match self.jobs.lock().unwrap().get(&job_id) {
+      Some(job) => {
+        job.cancelled.store(true, Ordering::Relaxed);
+        job.token.cancel();
+        true
+      }
+      None => false,
+    }
+  }
+}
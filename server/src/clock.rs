@@ -0,0 +1,199 @@
+use std::time::{Duration, Instant};
+
+use onoro::PawnColor;
+use serde::Deserialize;
+
+use crate::store::StoredClock;
+
+/// The most time a player can start with. Time controls come from clients, so
+/// longer ones are capped to this.
+const MAX_BASE: Duration = Duration::from_secs(24 * 60 * 60);
+/// The most time that can be added to a player's clock after each move.
+const MAX_INCREMENT: Duration = Duration::from_secs(60 * 60);
+
+/// The time each player starts with, and the time added to a player's clock
+/// after each of their moves.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct TimeControl {
+  pub base_ms: u64,
+  #[serde(default)]
+  pub increment_ms: u64,
+}
+
+/// A snapshot of the clocks of a game.
+#[derive(Clone, Copy, Debug)]
+pub struct ClockState {
+  pub black_remaining: Duration,
+  pub white_remaining: Duration,
+  pub increment: Duration,
+  /// The player whose clock is running, if either's is.
+  pub running: Option<PawnColor>,
+  /// The player who ran out of time, if one has.
+  pub lost_on_time: Option<PawnColor>,
+}
+
+fn color_idx(color: PawnColor) -> usize {
+  match color {
+    PawnColor::Black => 0,
+    PawnColor::White => 1,
+  }
+}
+
+fn opponent(color: PawnColor) -> PawnColor {
+  match color {
+    PawnColor::Black => PawnColor::White,
+    PawnColor::White => PawnColor::Black,
+  }
+}
+
+/// The clocks of the two players of a game. At most one clock runs at a time,
+/// and a player whose clock runs out loses the game.
+pub struct Clock {
+  increment: Duration,
+  remaining: [Duration; 2],
+  /// The player whose clock is running, and when it was started.
+  running: Option<(PawnColor, Instant)>,
+  lost_on_time: Option<PawnColor>,
+}
+
+impl Clock {
+  /// A clock for `time_control`, with its base time and increment capped at
+  /// `MAX_BASE` and `MAX_INCREMENT`.
+  pub fn new(time_control: TimeControl) -> Self {
+    let base = Duration::from_millis(time_control.base_ms).min(MAX_BASE);
+    Self {
+      increment: Duration::from_millis(time_control.increment_ms).min(MAX_INCREMENT),
+      remaining: [base; 2],
+      running: None,
+      lost_on_time: None,
+    }
+  }
+
+  /// A clock with the time left and increment of `stored`, with neither
+  /// player's clock running.
+  pub fn from_stored(stored: StoredClock) -> Self {
+    Self {
+      increment: Duration::from_millis(stored.increment_ms),
+      remaining: [
+        Duration::from_millis(stored.black_remaining_ms),
+        Duration::from_millis(stored.white_remaining_ms),
+      ],
+      running: None,
+      lost_on_time: None,
+    }
+  }
+
+  /// The time left and increment of this clock as of `now`, for storing.
+  pub fn to_stored(&self, now: Instant) -> StoredClock {
+    let state = self.state(now);
+    StoredClock {
+      black_remaining_ms: state.black_remaining.as_millis() as u64,
+      white_remaining_ms: state.white_remaining.as_millis() as u64,
+      increment_ms: state.increment.as_millis() as u64,
+    }
+  }
+
+  /// The player who ran out of time, if one has.
+  pub fn lost_on_time(&self) -> Option<PawnColor> {
+    self.lost_on_time
+  }
+
+  /// Starts the clock of `color`, if no clock is running and nobody has run
+  /// out of time.
+  pub fn start(&mut self, color: PawnColor, now: Instant) {
+    if self.running.is_none() && self.lost_on_time.is_none() {
+      self.running = Some((color, now));
+    }
+  }
+
+  /// Stops the running clock, charging its player for the time it ran.
+  pub fn stop(&mut self, now: Instant) {
+    if let Some((color, started)) = self.running.take() {
+      let remaining = &mut self.remaining[color_idx(color)];
+      *remaining = remaining.saturating_sub(now.saturating_duration_since(started));
+    }
+  }
+
+  /// Ends the turn of the player whose clock is running, adding the increment
+  /// to their clock and starting their opponent's. Does nothing if no clock
+  /// is running. `check_flag` must be called first, so that a player who has
+  /// already run out of time can't end their turn.
+  pub fn press(&mut self, now: Instant) {
+    let Some((color, _)) = self.running else {
+      return;
+    };
+    self.stop(now);
+    let remaining = &mut self.remaining[color_idx(color)];
+    *remaining = remaining.saturating_add(self.increment);
+    self.running = Some((opponent(color), now));
+  }
+
+  /// Checks whether the player whose clock is running has run out of time
+  /// as of `now`. If they have, stops their clock and returns them. Returns
+  /// `None` otherwise, including if they had already been found out of time.
+  pub fn check_flag(&mut self, now: Instant) -> Option<PawnColor> {
+    let (color, started) = self.running?;
+    if now.saturating_duration_since(started) < self.remaining[color_idx(color)] {
+      return None;
+    }
+    self.stop(now);
+    self.lost_on_time = Some(color);
+    Some(color)
+  }
+
+  pub fn state(&self, now: Instant) -> ClockState {
+    let mut remaining = self.remaining;
+    if let Some((color, started)) = self.running {
+      let remaining = &mut remaining[color_idx(color)];
+      *remaining = remaining.saturating_sub(now.saturating_duration_since(started));
+    }
+    ClockState {
+      black_remaining: remaining[0],
+      white_remaining: remaining[1],
+      increment: self.increment,
+      running: self.running.map(|(color, _)| color),
+      lost_on_time: self.lost_on_time,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::{Duration, Instant};
+
+  use onoro::PawnColor;
+
+  use super::{Clock, TimeControl, MAX_BASE, MAX_INCREMENT};
+
+  #[test]
+  fn test_huge_time_control() {
+    let mut clock = Clock::new(TimeControl {
+      base_ms: u64::MAX,
+      increment_ms: u64::MAX,
+    });
+    let now = Instant::now();
+    clock.start(PawnColor::Black, now);
+    for _ in 0..100 {
+      clock.press(now);
+    }
+
+    let state = clock.state(now);
+    assert_eq!(state.increment, MAX_INCREMENT);
+    assert_eq!(state.black_remaining, MAX_BASE + 50 * MAX_INCREMENT);
+    assert_eq!(state.white_remaining, MAX_BASE + 50 * MAX_INCREMENT);
+  }
+
+  #[test]
+  fn test_press_saturates() {
+    let mut clock = Clock::new(TimeControl {
+      base_ms: 1000,
+      increment_ms: 1000,
+    });
+    clock.remaining = [Duration::MAX; 2];
+    let now = Instant::now();
+    clock.start(PawnColor::White, now);
+    clock.press(now);
+    assert_eq!(clock.state(now).white_remaining, Duration::MAX);
+    assert_eq!(clock.state(now).running, Some(PawnColor::Black));
+  }
+}
@@ -0,0 +1,263 @@
+use std::{
+  fmt::Display,
+  net::{Ipv6Addr, SocketAddr},
+  path::{Path, PathBuf},
+  sync::OnceLock,
+};
+
+use clap::Parser;
+use serde::Deserialize;
+use tracing::Level;
+
+/// The port the static file server and REST API listen on by default.
+const DEFAULT_HTTP_PORT: u16 = 2001;
+
+/// The port the game socket listens on by default.
+const DEFAULT_SOCKET_PORT: u16 = 2345;
+
+/// The number of worker threads each solve uses by default.
+const DEFAULT_SOLVER_THREADS: u32 = 8;
+
+#[derive(Debug)]
+pub enum ConfigError {
+  /// The config file couldn't be read or parsed.
+  File(String),
+  /// The settings are inconsistent or out of range.
+  Invalid(String),
+}
+
+impl Display for ConfigError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ConfigError::File(reason) => write!(f, "Error reading config file: {reason}"),
+      ConfigError::Invalid(reason) => write!(f, "Invalid config: {reason}"),
+    }
+  }
+}
+
+pub type ConfigResult<T> = Result<T, ConfigError>;
+
+/// The certificate and private key the static file server serves HTTPS with.
+#[derive(Clone, Debug)]
+pub struct TlsConfig {
+  pub cert_path: PathBuf,
+  pub key_path: PathBuf,
+}
+
+/// The settings of the server. Each is taken from the first of these that sets
+/// it: its command line flag, its `ONORO_*` environment variable, the TOML
+/// config file, or its default.
+#[derive(Clone, Debug)]
+pub struct Config {
+  /// The address the static file server and REST API listen on.
+  pub http_addr: SocketAddr,
+  /// The port the game socket listens on, on every interface. The socket
+  /// library doesn't take an address to bind to.
+  pub socket_port: u16,
+  /// If set, the static file server, and the game socket relayed by it, are
+  /// served over TLS.
+  pub tls: Option<TlsConfig>,
+  /// The segments of the path everything on `http_addr` is served under, e.g.
+  /// `["games", "onoro"]` for "/games/onoro", for deployments that share a
+  /// domain with other sites.
+  pub path_prefix: Vec<String>,
+  /// The directory of the built web client.
+  pub static_dir: PathBuf,
+  /// The number of worker threads the solver may use in all.
+  pub solver_threads: u32,
+  /// The number of `solver_threads` reserved for analysis requests, at least
+  /// one and at most all of them. AI moves get the rest, and at least one.
+  pub analysis_threads: u32,
+  /// The most verbose level of log messages that are printed.
+  pub log_level: Level,
+}
+
+/// The command line flags of the server, which each override the environment
+/// variable named next to them, and then the config file.
+#[derive(Parser)]
+#[command(about = "Hosts Onoro games and the solver for the web client.", long_about = None)]
+struct Args {
+  /// A TOML file to read settings from, with a key named after each of the
+  /// other flags, in snake case.
+  #[arg(long, env = "ONORO_CONFIG")]
+  config: Option<PathBuf>,
+  /// The address to serve static files and the REST API on [default: [::]:2001].
+  #[arg(long, env = "ONORO_HTTP_ADDR")]
+  http_addr: Option<SocketAddr>,
+  /// The port to serve the game socket on [default: 2345].
+  #[arg(long, env = "ONORO_SOCKET_PORT")]
+  socket_port: Option<u16>,
+  /// The PEM certificate chain to serve HTTPS with. Requires --tls-key.
+  #[arg(long, env = "ONORO_TLS_CERT")]
+  tls_cert: Option<PathBuf>,
+  /// The PEM private key to serve HTTPS with. Requires --tls-cert.
+  #[arg(long, env = "ONORO_TLS_KEY")]
+  tls_key: Option<PathBuf>,
+  /// The path to serve everything on --http-addr under, e.g. /games/onoro.
+  #[arg(long, env = "ONORO_PATH_PREFIX")]
+  path_prefix: Option<String>,
+  /// The directory of the built web client [default: ../web/dist/dev/static].
+  #[arg(long, env = "ONORO_STATIC_DIR")]
+  static_dir: Option<PathBuf>,
+  /// The number of worker threads the solver may use in all [default: 8].
+  #[arg(long, env = "ONORO_SOLVER_THREADS")]
+  solver_threads: Option<u32>,
+  /// The number of solver threads reserved for analysis, with the rest
+  /// playing AI moves [default: half of --solver-threads].
+  #[arg(long, env = "ONORO_ANALYSIS_THREADS")]
+  analysis_threads: Option<u32>,
+  /// One of error, warn, info, debug or trace [default: warn].
+  #[arg(long, env = "ONORO_LOG_LEVEL")]
+  log_level: Option<Level>,
+}
+
+/// The contents of the config file, in which every setting is optional.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+  http_addr: Option<SocketAddr>,
+  socket_port: Option<u16>,
+  tls_cert: Option<PathBuf>,
+  tls_key: Option<PathBuf>,
+  path_prefix: Option<String>,
+  static_dir: Option<PathBuf>,
+  solver_threads: Option<u32>,
+  analysis_threads: Option<u32>,
+  log_level: Option<String>,
+}
+
+impl FileConfig {
+  fn read(path: &Path) -> ConfigResult<Self> {
+    let text = std::fs::read_to_string(path)
+      .map_err(|err| ConfigError::File(format!("{}: {err}", path.display())))?;
+    toml::from_str(&text).map_err(|err| ConfigError::File(format!("{}: {err}", path.display())))
+  }
+}
+
+impl Config {
+  /// Loads the config from the command line, environment and config file.
+  /// Exits the process after printing help if asked for it with `--help`.
+  pub fn load() -> ConfigResult<Self> {
+    let args = Args::parse();
+    let file = match &args.config {
+      Some(path) => FileConfig::read(path)?,
+      None => FileConfig::default(),
+    };
+
+    let log_level = match (args.log_level, file.log_level) {
+      (Some(level), _) => level,
+      (None, Some(level)) => level
+        .parse()
+        .map_err(|_| ConfigError::Invalid(format!("Unknown log level \"{level}\"")))?,
+      (None, None) => Level::WARN,
+    };
+
+    let tls = match (
+      args.tls_cert.or(file.tls_cert),
+      args.tls_key.or(file.tls_key),
+    ) {
+      (Some(cert_path), Some(key_path)) => Some(TlsConfig {
+        cert_path,
+        key_path,
+      }),
+      (None, None) => None,
+      _ => {
+        return Err(ConfigError::Invalid(
+          "A TLS certificate and key must be given together".into(),
+        ))
+      }
+    };
+
+    let path_prefix = Self::parse_path_prefix(
+      args
+        .path_prefix
+        .or(file.path_prefix)
+        .as_deref()
+        .unwrap_or_default(),
+    )?;
+
+    let static_dir = match args.static_dir.or(file.static_dir) {
+      Some(static_dir) => static_dir,
+      None => Self::default_static_dir()?,
+    };
+
+    let solver_threads = args
+      .solver_threads
+      .or(file.solver_threads)
+      .unwrap_or(DEFAULT_SOLVER_THREADS);
+    if solver_threads == 0 {
+      return Err(ConfigError::Invalid(
+        "The solver needs at least one thread".into(),
+      ));
+    }
+    let analysis_threads = args
+      .analysis_threads
+      .or(file.analysis_threads)
+      .unwrap_or((solver_threads / 2).max(1));
+    if analysis_threads == 0 || analysis_threads > solver_threads {
+      return Err(ConfigError::Invalid(format!(
+        "Analysis needs between 1 and all {solver_threads} solver threads"
+      )));
+    }
+
+    Ok(Self {
+      http_addr: args.http_addr.or(file.http_addr).unwrap_or(SocketAddr::new(
+        Ipv6Addr::UNSPECIFIED.into(),
+        DEFAULT_HTTP_PORT,
+      )),
+      socket_port: args
+        .socket_port
+        .or(file.socket_port)
+        .unwrap_or(DEFAULT_SOCKET_PORT),
+      tls,
+      path_prefix,
+      static_dir,
+      solver_threads,
+      analysis_threads,
+      log_level,
+    })
+  }
+
+  fn parse_path_prefix(path_prefix: &str) -> ConfigResult<Vec<String>> {
+    path_prefix
+      .split('/')
+      .filter(|segment| !segment.is_empty())
+      .map(|segment| {
+        if segment == "." || segment == ".." || segment.contains(['?', '#', '%']) {
+          Err(ConfigError::Invalid(format!(
+            "Invalid path prefix \"{path_prefix}\""
+          )))
+        } else {
+          Ok(segment.to_owned())
+        }
+      })
+      .collect()
+  }
+
+  /// The web client's build output in the repository, assuming the server is
+  /// run from its own directory.
+  fn default_static_dir() -> ConfigResult<PathBuf> {
+    let current_dir = std::env::current_dir()
+      .map_err(|err| ConfigError::Invalid(format!("Can't find the current directory: {err}")))?;
+    Ok(
+      current_dir
+        .parent()
+        .unwrap_or(&current_dir)
+        .join("web/dist/dev/static"),
+    )
+  }
+
+  /// Makes `self` the config returned by `Config::global`. Must be called
+  /// before anything reads the config, and only once.
+  pub fn install(self) {
+    if CONFIG.set(self).is_err() {
+      panic!("The config was installed twice");
+    }
+  }
+
+  pub fn global() -> &'static Self {
+    CONFIG.get().expect("The config hasn't been installed")
+  }
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
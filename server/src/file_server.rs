@@ -1,34 +1,47 @@
-use std::{
-  env,
-  net::{Ipv6Addr, SocketAddrV6},
-};
 use tokio::task::JoinHandle;
-use warp::Filter;
+use warp::{filters::BoxedFilter, Filter};
+
+use crate::{
+  admin::metrics_route, config::Config, openings::openings_route, replay::replay_route,
+  rest::game_routes, socket_relay::socket_relay_route,
+};
+
+/// Matches and consumes the segments of `Config::path_prefix`.
+fn path_prefix() -> BoxedFilter<()> {
+  Config::global()
+    .path_prefix
+    .iter()
+    .fold(warp::any().boxed(), |filter, segment| {
+      filter.and(warp::path(segment.clone())).boxed()
+    })
+}
 
 pub fn create_static_file_server() -> JoinHandle<()> {
   tokio::spawn(async {
-    tracing_subscriber::fmt()
-      .with_max_level(tracing::Level::WARN)
-      .init();
-
+    let config = Config::global();
     let log_filter = warp::trace::request();
 
-    let route = warp::fs::dir(
-      env::current_dir()
-        .unwrap()
-        .parent()
-        .unwrap()
-        .join("web/dist/dev/static"),
-    )
-    .with(log_filter);
+    let route = path_prefix()
+      .and(
+        socket_relay_route()
+          .or(openings_route())
+          .or(replay_route())
+          .or(game_routes())
+          .or(metrics_route())
+          .or(warp::fs::dir(config.static_dir.clone())),
+      )
+      .with(log_filter);
 
-    warp::serve(route)
-      .run(SocketAddrV6::new(
-        Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0),
-        2001,
-        0,
-        0,
-      ))
-      .await;
+    match &config.tls {
+      Some(tls) => {
+        warp::serve(route)
+          .tls()
+          .cert_path(&tls.cert_path)
+          .key_path(&tls.key_path)
+          .run(config.http_addr)
+          .await
+      }
+      None => warp::serve(route).run(config.http_addr).await,
+    }
   })
 }
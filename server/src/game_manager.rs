@@ -0,0 +1,558 @@
+use std::{
+  collections::HashMap,
+  fmt::Display,
+  num::ParseIntError,
+  str::FromStr,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    mpsc::{self, Receiver, Sender},
+    Mutex, OnceLock,
+  },
+  thread,
+  time::{Duration, Instant, SystemTime},
+};
+
+use abstract_game::Compress;
+use onoro::{Move, Onoro16, PawnColor};
+use rand::{rngs::OsRng, Rng};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+  clock::{Clock, ClockState, TimeControl},
+  store::{GameStore, StoreError, StoreResult, StoredGame},
+};
+
+/// Games that nobody has made a request for in this long are dropped.
+const ABANDONED_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+pub type GameId = u64;
+
+/// A secret given to each player when they take a seat in a game, which they
+/// must present to make moves as that player. Tokens are 128 random bits from
+/// the operating system, so they can't be guessed, and are written as 32 hex
+/// digits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PlayerToken(u128);
+
+impl PlayerToken {
+  /// A new token from the operating system's secure random number generator.
+  pub fn random() -> Self {
+    Self(OsRng.gen())
+  }
+
+  pub fn to_bytes(self) -> [u8; 16] {
+    self.0.to_be_bytes()
+  }
+
+  pub fn from_bytes(bytes: [u8; 16]) -> Self {
+    Self(u128::from_be_bytes(bytes))
+  }
+}
+
+impl Display for PlayerToken {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{:032x}", self.0)
+  }
+}
+
+impl FromStr for PlayerToken {
+  type Err = ParseIntError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    u128::from_str_radix(s, 16).map(Self)
+  }
+}
+
+impl Serialize for PlayerToken {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    serializer.collect_str(self)
+  }
+}
+
+impl<'de> Deserialize<'de> for PlayerToken {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    String::deserialize(deserializer)?
+      .parse()
+      .map_err(de::Error::custom)
+  }
+}
+
+#[derive(Debug)]
+pub enum GameError {
+  UnknownGame,
+  GameFull,
+  NotAPlayer,
+  NotYourTurn,
+  IllegalMove(String),
+  /// The player of this color ran out of time, ending the game.
+  OutOfTime(PawnColor),
+}
+
+impl Display for GameError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      GameError::UnknownGame => write!(f, "No such game"),
+      GameError::GameFull => write!(f, "Both players have already joined this game"),
+      GameError::NotAPlayer => write!(f, "Not a player in this game"),
+      GameError::NotYourTurn => write!(f, "It is the other player's turn"),
+      GameError::IllegalMove(reason) => write!(f, "{reason}"),
+      GameError::OutOfTime(color) => write!(f, "The game is over, {color} ran out of time"),
+    }
+  }
+}
+
+/// A seat in a game, returned to a player when they create or join a game.
+pub struct Seat {
+  pub game_id: GameId,
+  pub player_token: PlayerToken,
+  pub color: PawnColor,
+}
+
+/// Everything that happened in a game after some number of moves, for
+/// relaying moves to the other player.
+pub struct GameUpdate {
+  /// The moves made after the requested index.
+  pub moves: Vec<Move>,
+  /// The index of the first of `moves` among all moves of the game.
+  pub from_index: u32,
+  /// When each of `moves` was made.
+  pub move_times: Vec<SystemTime>,
+  /// The game state after all moves.
+  pub game: Onoro16,
+  /// Every move of the game, from `Onoro::default_start()`.
+  pub history: Vec<Move>,
+  /// True once both players have joined.
+  pub opponent_joined: bool,
+  /// The clocks of the game, if it is timed.
+  pub clock: Option<ClockState>,
+}
+
+struct HostedGame {
+  game: Onoro16,
+  moves: Vec<Move>,
+  move_times: Vec<SystemTime>,
+  /// The clocks of the players, if the game is timed. Black's clock starts
+  /// once white joins.
+  clock: Option<Clock>,
+  /// True once `GameManager::expire_clocks` has reported that a player ran
+  /// out of time.
+  flag_reported: bool,
+  black_player: PlayerToken,
+  white_player: Option<PlayerToken>,
+  last_active: Instant,
+}
+
+impl HostedGame {
+  fn color_of(&self, player_token: PlayerToken) -> Option<PawnColor> {
+    if player_token == self.black_player {
+      Some(PawnColor::Black)
+    } else if Some(player_token) == self.white_player {
+      Some(PawnColor::White)
+    } else {
+      None
+    }
+  }
+
+  /// Checks whether the player to move has run out of time, returning the
+  /// player who has, if either has.
+  fn check_flag(&mut self, now: Instant) -> Option<PawnColor> {
+    let clock = self.clock.as_mut()?;
+    clock.check_flag(now);
+    clock.lost_on_time()
+  }
+
+  fn to_stored(&self, game_id: GameId, now: Instant) -> StoredGame {
+    let mut board = vec![0; Onoro16::COMPRESSED_SIZE];
+    self.game.compress(&mut board);
+    StoredGame {
+      game_id,
+      board,
+      moves: self.moves.clone(),
+      move_times_ms: self
+        .move_times
+        .iter()
+        .map(|time| {
+          time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_millis() as u64)
+        })
+        .collect(),
+      black_player: self.black_player,
+      white_player: self.white_player,
+      clock: self.clock.as_ref().map(|clock| clock.to_stored(now)),
+    }
+  }
+
+  /// Resumes hosting a stored game. Its clock, if it has one, restarts from
+  /// the time each player had left when the game was stored.
+  fn from_stored(stored: StoredGame, now: Instant) -> StoreResult<Self> {
+    let game_id = stored.game_id;
+    let game = Onoro16::decompress(&stored.board)
+      .ok_or_else(|| StoreError::Corrupt(format!("Invalid board for game {game_id}")))?;
+    let mut clock = stored.clock.map(Clock::from_stored);
+    if let (Some(clock), Some(_)) = (&mut clock, stored.white_player) {
+      if game.winner().is_none() {
+        clock.start(game.player_color(), now);
+      }
+    }
+
+    Ok(Self {
+      game,
+      moves: stored.moves,
+      move_times: stored
+        .move_times_ms
+        .into_iter()
+        .map(|time| SystemTime::UNIX_EPOCH + Duration::from_millis(time))
+        .collect(),
+      clock,
+      flag_reported: false,
+      black_player: stored.black_player,
+      white_player: stored.white_player,
+      last_active: now,
+    })
+  }
+
+  /// The updates after the first `from_index` moves.
+  fn update(&self, from_index: usize, now: Instant) -> GameUpdate {
+    GameUpdate {
+      moves: self.moves.iter().skip(from_index).copied().collect(),
+      from_index: from_index as u32,
+      move_times: self.move_times.iter().skip(from_index).copied().collect(),
+      game: self.game.clone(),
+      history: self.moves.clone(),
+      opponent_joined: self.white_player.is_some(),
+      clock: self.clock.as_ref().map(|clock| clock.state(now)),
+    }
+  }
+}
+
+/// A change to a hosted game, waiting to be written to the store.
+enum StoreWrite {
+  Create(StoredGame),
+  Update(StoredGame),
+  Delete(GameId),
+}
+
+/// Hosts games between two human players. One player creates a game and
+/// shares its ID, and the other joins it by ID. Players submit their moves to
+/// the manager, and poll it for the moves of their opponent.
+///
+/// With a `GameStore`, every change to a game is also written to the store,
+/// and the games in the store are hosted again when the server restarts.
+/// Changes are written by a thread of their own, in the order they were made,
+/// so requests never wait on the store while holding the lock on the games.
+pub struct GameManager {
+  next_id: AtomicU64,
+  games: Mutex<HashMap<GameId, HostedGame>>,
+  /// Sends changes to the thread writing them to the store, if there is one.
+  store: OnceLock<Sender<StoreWrite>>,
+}
+
+impl GameManager {
+  fn new() -> Self {
+    Self {
+      next_id: AtomicU64::new(0),
+      games: Mutex::new(HashMap::new()),
+      store: OnceLock::new(),
+    }
+  }
+
+  /// The games shared by all client connections.
+  pub fn global() -> &'static Self {
+    static GAMES: OnceLock<GameManager> = OnceLock::new();
+    GAMES.get_or_init(Self::new)
+  }
+
+  /// Hosts every game in `store`, and writes all future changes to games to
+  /// it. Returns the number of games restored. Can only be called once, even
+  /// if it fails, in which case no games are restored and changes aren't
+  /// stored.
+  pub fn restore(&self, store: Box<dyn GameStore>) -> StoreResult<usize> {
+    let mut games = self.games.lock().unwrap();
+    let (sender, writes) = mpsc::channel();
+    if self.store.set(sender).is_err() {
+      return Err(StoreError::Backend("A game store is already in use".into()));
+    }
+
+    let now = Instant::now();
+    let mut restored = Vec::new();
+    for game_id in store.list()? {
+      let Some(stored) = store.load(game_id)? else {
+        continue;
+      };
+      restored.push((game_id, HostedGame::from_stored(stored, now)?));
+    }
+    for (game_id, game) in restored {
+      games.insert(game_id, game);
+      self.next_id.fetch_max(game_id + 1, Ordering::Relaxed);
+    }
+    thread::spawn(move || Self::write_to_store(store, writes));
+    Ok(games.len())
+  }
+
+  /// Writes each change received from `writes` to `store`, until the manager
+  /// is dropped. Errors are logged, since players can carry on without the
+  /// store.
+  fn write_to_store(store: Box<dyn GameStore>, writes: Receiver<StoreWrite>) {
+    for write in writes {
+      let (game_id, result) = match write {
+        StoreWrite::Create(stored) => (stored.game_id, store.create(&stored)),
+        StoreWrite::Update(stored) => (stored.game_id, store.update(&stored)),
+        StoreWrite::Delete(game_id) => (game_id, store.delete(game_id)),
+      };
+      if let Err(err) = result {
+        println!("Error storing game {game_id}: {err}");
+      }
+    }
+  }
+
+  /// Queues a write to the store, if there is one.
+  fn queue_write(&self, write: impl FnOnce() -> StoreWrite) {
+    if let Some(store) = self.store.get() {
+      // Sending only fails if the writer thread panicked, leaving nothing to
+      // write to.
+      let _ = store.send(write());
+    }
+  }
+
+  /// Queues `game` to be written to the store. `created` is true if the game
+  /// is new.
+  fn persist(&self, game_id: GameId, game: &HostedGame, created: bool) {
+    self.queue_write(|| {
+      let stored = game.to_stored(game_id, Instant::now());
+      if created {
+        StoreWrite::Create(stored)
+      } else {
+        StoreWrite::Update(stored)
+      }
+    });
+  }
+
+  /// Drops every game that hasn't been active in `ABANDONED_TIMEOUT`.
+  fn remove_abandoned(&self, games: &mut HashMap<GameId, HostedGame>) {
+    games.retain(|&game_id, game| {
+      let active = game.last_active.elapsed() < ABANDONED_TIMEOUT;
+      if !active {
+        self.queue_write(|| StoreWrite::Delete(game_id));
+      }
+      active
+    });
+  }
+
+  /// Creates a new game from the default start, seating the creator as black.
+  /// With a `time_control`, each player's moves are timed, and a player who
+  /// runs out of time loses.
+  pub fn create_game(&self, time_control: Option<TimeControl>) -> Seat {
+    let game_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+    let player_token = PlayerToken::random();
+
+    let mut games = self.games.lock().unwrap();
+    self.remove_abandoned(&mut games);
+    let game = HostedGame {
+      game: Onoro16::default_start(),
+      moves: Vec::new(),
+      move_times: Vec::new(),
+      clock: time_control.map(Clock::new),
+      flag_reported: false,
+      black_player: player_token,
+      white_player: None,
+      last_active: Instant::now(),
+    };
+    self.persist(game_id, &game, true);
+    games.insert(game_id, game);
+
+    Seat {
+      game_id,
+      player_token,
+      color: PawnColor::Black,
+    }
+  }
+
+  /// Seats the caller as white in game `game_id`.
+  pub fn join_game(&self, game_id: GameId) -> Result<Seat, GameError> {
+    let mut games = self.games.lock().unwrap();
+    self.remove_abandoned(&mut games);
+    let game = games.get_mut(&game_id).ok_or(GameError::UnknownGame)?;
+    if game.white_player.is_some() {
+      return Err(GameError::GameFull);
+    }
+
+    let player_token = PlayerToken::random();
+    game.white_player = Some(player_token);
+    let now = Instant::now();
+    let to_move = game.game.player_color();
+    if let Some(clock) = &mut game.clock {
+      clock.start(to_move, now);
+    }
+    game.last_active = now;
+    self.persist(game_id, game, false);
+    Ok(Seat {
+      game_id,
+      player_token,
+      color: PawnColor::White,
+    })
+  }
+
+  /// Makes move `m` for the player with `player_token` in game `game_id`,
+  /// returning the move and the updated game state.
+  pub fn make_move(
+    &self,
+    game_id: GameId,
+    player_token: PlayerToken,
+    m: Move,
+  ) -> Result<GameUpdate, GameError> {
+    let mut games = self.games.lock().unwrap();
+    let game = games.get_mut(&game_id).ok_or(GameError::UnknownGame)?;
+    let color = game.color_of(player_token).ok_or(GameError::NotAPlayer)?;
+    let now = Instant::now();
+    if let Some(loser) = game.check_flag(now) {
+      return Err(GameError::OutOfTime(loser));
+    }
+    if color != game.game.player_color() {
+      return Err(GameError::NotYourTurn);
+    }
+
+    game
+      .game
+      .try_make_move(m)
+      .map_err(|err| GameError::IllegalMove(err.message().to_owned()))?;
+    game.moves.push(m);
+    game.move_times.push(SystemTime::now());
+    if let Some(clock) = &mut game.clock {
+      if game.game.winner().is_some() {
+        clock.stop(now);
+      } else {
+        clock.press(now);
+      }
+    }
+    game.last_active = now;
+    self.persist(game_id, game, false);
+    Ok(game.update(game.moves.len() - 1, now))
+  }
+
+  /// Returns the moves made in game `game_id` after the first `from_index`,
+  /// for the player with `player_token`.
+  pub fn updates(
+    &self,
+    game_id: GameId,
+    player_token: PlayerToken,
+    from_index: u32,
+  ) -> Result<GameUpdate, GameError> {
+    let mut games = self.games.lock().unwrap();
+    let game = games.get_mut(&game_id).ok_or(GameError::UnknownGame)?;
+    game.color_of(player_token).ok_or(GameError::NotAPlayer)?;
+
+    let now = Instant::now();
+    game.check_flag(now);
+    game.last_active = now;
+    Ok(game.update(from_index as usize, now))
+  }
+
+  /// Returns every move made in game `game_id`, and its current state, for a
+  /// spectator. Anyone may spectate a game, and spectating doesn't keep an
+  /// abandoned game alive.
+  pub fn spectate(&self, game_id: GameId) -> Result<GameUpdate, GameError> {
+    let mut games = self.games.lock().unwrap();
+    let game = games.get_mut(&game_id).ok_or(GameError::UnknownGame)?;
+    let now = Instant::now();
+    game.check_flag(now);
+    Ok(game.update(0, now))
+  }
+
+  /// Checks the clocks of every timed game, returning each game in which a
+  /// player has run out of time that hasn't been returned before, along with
+  /// that player.
+  pub fn expire_clocks(&self) -> Vec<(GameId, PawnColor)> {
+    let now = Instant::now();
+    let mut games = self.games.lock().unwrap();
+    self.remove_abandoned(&mut games);
+    games
+      .iter_mut()
+      .filter_map(|(&game_id, game)| {
+        let loser = game.check_flag(now)?;
+        if game.flag_reported {
+          return None;
+        }
+        game.flag_reported = true;
+        Some((game_id, loser))
+      })
+      .collect()
+  }
+
+  /// True if game `game_id` exists.
+  pub fn contains(&self, game_id: GameId) -> bool {
+    self.games.lock().unwrap().contains_key(&game_id)
+  }
+
+  /// The number of games being hosted, including ones still waiting for a
+  /// second player.
+  pub fn len(&self) -> usize {
+    self.games.lock().unwrap().len()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use abstract_game::Compress;
+  use onoro::Onoro16;
+
+  use crate::store::{GameStore, SqliteGameStore, StoreError, StoredGame};
+
+  use super::{GameManager, PlayerToken};
+
+  fn compressed(onoro: &Onoro16) -> Vec<u8> {
+    let mut board = vec![0; Onoro16::COMPRESSED_SIZE];
+    onoro.compress(&mut board);
+    board
+  }
+
+  /// A store holding one game, with ID `game_id` and board `board`.
+  fn store_with_game(game_id: u64, board: Vec<u8>) -> Box<dyn GameStore> {
+    let store = SqliteGameStore::in_memory().unwrap();
+    store
+      .create(&StoredGame {
+        game_id,
+        board,
+        moves: Vec::new(),
+        move_times_ms: Vec::new(),
+        black_player: PlayerToken::random(),
+        white_player: None,
+        clock: None,
+      })
+      .unwrap();
+    Box::new(store)
+  }
+
+  #[test]
+  fn test_restore() {
+    let manager = GameManager::new();
+    let store = store_with_game(7, compressed(&Onoro16::default_start()));
+    assert_eq!(manager.restore(store).unwrap(), 1);
+    assert!(manager.contains(7));
+    // New games don't reuse the IDs of restored games.
+    assert_eq!(manager.create_game(None).game_id, 8);
+
+    // Only one store can be used, and the games of another aren't hosted.
+    let store = store_with_game(9, compressed(&Onoro16::default_start()));
+    assert!(matches!(
+      manager.restore(store),
+      Err(StoreError::Backend(_))
+    ));
+    assert!(!manager.contains(9));
+  }
+
+  #[test]
+  fn test_restore_corrupt_board() {
+    let store = store_with_game(1, vec![0xff; 3]);
+    assert!(matches!(
+      GameManager::new().restore(store),
+      Err(StoreError::Corrupt(_))
+    ));
+  }
+}
@@ -0,0 +1,258 @@
+use std::{
+  collections::HashMap,
+  fmt::Display,
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex, OnceLock,
+  },
+};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{
+  admin::{GaugeGuard, ServerMetrics},
+  config::Config,
+};
+
+/// The number of AI moves that may be searched for at the same time. Each
+/// search runs the parallel solver, so additional requests wait for a free
+/// slot instead of competing for cores.
+const MAX_CONCURRENT_PLAY: usize = 2;
+
+/// The number of analysis searches that may run at the same time. Each one
+/// already saturates the analysis threads, so additional searches wait for a
+/// free slot.
+const MAX_CONCURRENT_ANALYSIS: usize = 1;
+
+/// The number of searches of each workload that may wait for a free slot.
+/// Further requests are turned away, rather than left waiting behind a queue
+/// that could take minutes to drain.
+const MAX_QUEUED_SEARCHES: usize = 16;
+
+/// Identifies the socket connection a search was requested over. Limits are
+/// kept per connection rather than per session, since a client can open as
+/// many sessions as it likes.
+pub type ConnectionId = u64;
+
+/// The kinds of search that each get their own share of the solver threads,
+/// so that neither can starve the other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Workload {
+  /// Moves of the AI opponent.
+  Play,
+  /// Move analysis and batch analysis jobs.
+  Analysis,
+}
+
+impl Workload {
+  /// The number of searches of this workload each connection may have running
+  /// or queued at once.
+  fn max_per_connection(self) -> usize {
+    match self {
+      // A client plays one game at a time, with one AI move to search for.
+      Workload::Play => 1,
+      // Enough for a batch job and a move analysis side by side.
+      Workload::Analysis => 2,
+    }
+  }
+}
+
+/// Why a search was turned away by `SolverGovernor::admit`.
+#[derive(Clone, Copy, Debug)]
+pub enum Busy {
+  /// The connection already has `limit` searches of the workload running or
+  /// queued.
+  ConnectionLimit { limit: usize },
+  /// The workload's queue is full, with `queued` searches waiting.
+  QueueFull { queued: usize },
+}
+
+impl Display for Busy {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Busy::ConnectionLimit { limit } => write!(
+        f,
+        "The connection already has {limit} searches running or queued"
+      ),
+      Busy::QueueFull { queued } => write!(
+        f,
+        "The solver is busy, with {queued} searches already queued"
+      ),
+    }
+  }
+}
+
+/// The slots and threads of one workload.
+struct Pool {
+  /// The number of worker threads each search of the workload uses.
+  threads: u32,
+  slots: Arc<Semaphore>,
+  /// The number of admitted searches waiting for a slot.
+  queued: AtomicUsize,
+}
+
+impl Pool {
+  fn new(threads: u32, concurrent_searches: usize) -> Self {
+    Self {
+      threads: (threads / concurrent_searches as u32).max(1),
+      slots: Arc::new(Semaphore::new(concurrent_searches)),
+      queued: AtomicUsize::new(0),
+    }
+  }
+}
+
+/// Decides which searches may use the solver, and with how many threads.
+///
+/// The solver threads are split between the `Workload`s, each of which runs a
+/// fixed number of searches at once and queues the rest. Each connection
+/// may only have a few searches running or queued, so that one client can't
+/// fill the queue, and a search that would overflow the queue is refused with
+/// `Busy` for the client to retry later.
+pub struct SolverGovernor {
+  play: Pool,
+  analysis: Pool,
+  /// The number of searches each connection has running or queued, by
+  /// workload. Connections without any aren't listed.
+  connections: Mutex<HashMap<(ConnectionId, Workload), usize>>,
+}
+
+impl SolverGovernor {
+  fn new() -> Self {
+    let config = Config::global();
+    let play_threads = config
+      .solver_threads
+      .saturating_sub(config.analysis_threads)
+      .max(1);
+    Self {
+      play: Pool::new(play_threads, MAX_CONCURRENT_PLAY),
+      analysis: Pool::new(config.analysis_threads, MAX_CONCURRENT_ANALYSIS),
+      connections: Mutex::new(HashMap::new()),
+    }
+  }
+
+  /// The governor of every search the server runs.
+  pub fn global() -> &'static Self {
+    static GOVERNOR: OnceLock<SolverGovernor> = OnceLock::new();
+    GOVERNOR.get_or_init(Self::new)
+  }
+
+  fn pool(&self, workload: Workload) -> &Pool {
+    match workload {
+      Workload::Play => &self.play,
+      Workload::Analysis => &self.analysis,
+    }
+  }
+
+  /// The number of searches of `workload` waiting for a slot.
+  pub fn queued(&self, workload: Workload) -> usize {
+    self.pool(workload).queued.load(Ordering::Relaxed)
+  }
+
+  /// Queues a search of `workload` requested over connection `connection_id`,
+  /// or returns why it can't be. The search should start once
+  /// `Admission::start` returns, and counts against the connection's limit
+  /// until the permit it returns is dropped.
+  pub fn admit(
+    &self,
+    workload: Workload,
+    connection_id: ConnectionId,
+  ) -> Result<Admission<'_>, Busy> {
+    let connection = {
+      let mut connections = self.connections.lock().unwrap();
+      let searches = connections.entry((connection_id, workload)).or_default();
+      let limit = workload.max_per_connection();
+      if *searches >= limit {
+        return Err(Busy::ConnectionLimit { limit });
+      }
+      *searches += 1;
+      ConnectionSlot {
+        governor: self,
+        key: (connection_id, workload),
+      }
+    };
+
+    // Dropping `connection` gives its slot back if the queue is full.
+    let pool = self.pool(workload);
+    pool
+      .queued
+      .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |queued| {
+        (queued < MAX_QUEUED_SEARCHES).then_some(queued + 1)
+      })
+      .map_err(|queued| Busy::QueueFull { queued })?;
+
+    Ok(Admission {
+      pool,
+      connection,
+      queued: QueuedSlot {
+        pool,
+        _gauge: ServerMetrics::global().queue_search(),
+      },
+    })
+  }
+}
+
+/// Counts a search against its connection's limit until dropped.
+struct ConnectionSlot<'a> {
+  governor: &'a SolverGovernor,
+  key: (ConnectionId, Workload),
+}
+
+impl Drop for ConnectionSlot<'_> {
+  fn drop(&mut self) {
+    let mut connections = self.governor.connections.lock().unwrap();
+    if let Some(searches) = connections.get_mut(&self.key) {
+      *searches -= 1;
+      if *searches == 0 {
+        connections.remove(&self.key);
+      }
+    }
+  }
+}
+
+/// Counts a search as queued until dropped.
+struct QueuedSlot<'a> {
+  pool: &'a Pool,
+  _gauge: GaugeGuard<'a>,
+}
+
+impl Drop for QueuedSlot<'_> {
+  fn drop(&mut self) {
+    self.pool.queued.fetch_sub(1, Ordering::Relaxed);
+  }
+}
+
+/// A search that has been admitted by `SolverGovernor::admit`, waiting in its
+/// workload's queue.
+pub struct Admission<'a> {
+  pool: &'a Pool,
+  connection: ConnectionSlot<'a>,
+  queued: QueuedSlot<'a>,
+}
+
+impl<'a> Admission<'a> {
+  /// Waits for a free slot of the search's workload.
+  pub async fn start(self) -> SolverPermit<'a> {
+    // The semaphore is never closed, so acquiring can't fail.
+    let slot = self.pool.slots.clone().acquire_owned().await.unwrap();
+    drop(self.queued);
+    SolverPermit {
+      threads: self.pool.threads,
+      _slot: slot,
+      _connection: self.connection,
+    }
+  }
+}
+
+/// Lets a search use the solver until dropped.
+pub struct SolverPermit<'a> {
+  threads: u32,
+  _slot: OwnedSemaphorePermit,
+  _connection: ConnectionSlot<'a>,
+}
+
+impl SolverPermit<'_> {
+  /// The number of worker threads the search may use.
+  pub fn threads(&self) -> u32 {
+    self.threads
+  }
+}
@@ -1,7 +1,33 @@
+use crate::config::Config;
 use crate::file_server::create_static_file_server;
+use crate::game_manager::GameManager;
 use crate::socket_init::create_socket_endpoint;
+use crate::store::SqliteGameStore;
 
-pub async fn init() {
+/// The database hosted games are stored in, relative to the directory the
+/// server is run from.
+const GAME_DB_PATH: &str = "games.sqlite3";
+
+/// Resumes hosting the games stored in `GAME_DB_PATH`, and stores all new
+/// games there. The server still runs if the database can't be opened, but
+/// games are then lost when it stops.
+fn restore_games() {
+  match SqliteGameStore::open(GAME_DB_PATH) {
+    Ok(store) => match GameManager::global().restore(Box::new(store)) {
+      Ok(restored) => println!("Restored {restored} games from {GAME_DB_PATH}"),
+      Err(err) => println!("Error restoring games from {GAME_DB_PATH}: {err}"),
+    },
+    Err(err) => println!("Error opening {GAME_DB_PATH}: {err}"),
+  }
+}
+
+pub async fn init(config: Config) {
+  tracing_subscriber::fmt()
+    .with_max_level(config.log_level)
+    .init();
+  config.install();
+
+  restore_games();
   match tokio::join!(create_static_file_server(), create_socket_endpoint()) {
     (Err(err), _) => {
       println!("Error joining static file server: {:?}", err);
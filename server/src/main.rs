@@ -1,10 +1,31 @@
+mod admin;
+mod ai;
+mod analysis;
+mod clock;
+mod config;
 mod error;
 mod file_server;
+mod game_manager;
+mod governor;
 mod initialize;
+mod openings;
 mod proto;
+mod replay;
+mod rest;
+mod resume;
+mod sessions;
 mod socket_init;
+mod socket_relay;
+mod store;
 
 #[tokio::main]
 async fn main() {
-  initialize::init().await;
+  let config = match config::Config::load() {
+    Ok(config) => config,
+    Err(err) => {
+      eprintln!("{err}");
+      std::process::exit(1);
+    }
+  };
+  initialize::init(config).await;
 }
@@ -0,0 +1,135 @@
+use std::{
+  convert::Infallible,
+  sync::{Mutex, OnceLock},
+};
+
+use cooperate::{Engine, Options};
+use onoro::{Onoro16, Onoro16View};
+use serde::{Deserialize, Serialize};
+use warp::{http::StatusCode, Filter, Rejection, Reply};
+
+use crate::{admin::ServerMetrics, config::Config};
+
+/// The depth every opening position is solved to. Scores which are wins or
+/// losses at this depth are proven, while ties only mean that neither player
+/// can force a win within this many moves.
+const OPENING_DEPTH: u32 = 8;
+
+/// Only positions with at most this many pawns in play are served, since
+/// deeper positions are too expensive to solve on demand.
+const MAX_OPENING_PAWNS: u32 = 6;
+
+/// How long clients may cache responses for, in seconds. Scores only depend
+/// on the position and `OPENING_DEPTH`, so they rarely change.
+const CACHE_MAX_AGE: u32 = 24 * 60 * 60;
+
+#[derive(Deserialize)]
+pub struct OpeningQuery {
+  /// The position, as rows of a board string (see
+  /// `Onoro::from_board_string`) separated by '/', e.g. ".BW/W.B/BW.".
+  position: String,
+}
+
+/// The response of the openings endpoint. Moves are listed as
+/// `[move, score]` pairs, with scores from the perspective of the player
+/// making the move:
+/// ```text
+/// {"depth":8,"moves":[["(8, 9)","[tie:7]"],...]}
+/// ```
+#[derive(Serialize)]
+struct OpeningEntry {
+  depth: u32,
+  moves: Vec<(String, String)>,
+}
+
+/// The solver backing the opening explorer. Its table of resolved states is
+/// kept between requests, so it acts as a database of solved openings that
+/// fills in as positions are explored. It uses the threads reserved for
+/// analysis.
+fn opening_engine() -> &'static Mutex<Engine<Onoro16View>> {
+  static ENGINE: OnceLock<Mutex<Engine<Onoro16View>>> = OnceLock::new();
+  ENGINE.get_or_init(|| {
+    Mutex::new(Engine::new(
+      Onoro16View::new(Onoro16::default_start()),
+      Options {
+        num_threads: Config::global().analysis_threads,
+        search_depth: OPENING_DEPTH,
+        unit_depth: OPENING_DEPTH / 2,
+        ..Default::default()
+      },
+    ))
+  })
+}
+
+fn parse_position(position: &str) -> Result<Onoro16, String> {
+  let board = position
+    .split('/')
+    .map(|row| {
+      row
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(String::from)
+        .collect::<Vec<_>>()
+        .join(" ")
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+  Onoro16::from_board_string(&board)
+}
+
+fn solve_opening(onoro: Onoro16) -> OpeningEntry {
+  let mut engine = opening_engine().lock().unwrap();
+  engine.reroot(Onoro16View::new(onoro));
+  let registered = ServerMetrics::global().searches.register(engine.progress());
+  engine.solve();
+  drop(registered);
+
+  OpeningEntry {
+    depth: OPENING_DEPTH,
+    moves: engine
+      .move_scores()
+      .unwrap_or_default()
+      .into_iter()
+      .map(|(m, score)| (m.to_string(), score.to_string()))
+      .collect(),
+  }
+}
+
+async fn handle_opening_query(query: OpeningQuery) -> Result<Box<dyn Reply>, Infallible> {
+  let onoro = match parse_position(&query.position) {
+    Ok(onoro) => onoro,
+    Err(err) => {
+      return Ok(Box::new(warp::reply::with_status(
+        err,
+        StatusCode::BAD_REQUEST,
+      )));
+    }
+  };
+  if onoro.pawns_in_play() > MAX_OPENING_PAWNS {
+    return Ok(Box::new(warp::reply::with_status(
+      format!("Only positions with at most {MAX_OPENING_PAWNS} pawns are in the opening database"),
+      StatusCode::BAD_REQUEST,
+    )));
+  }
+
+  match tokio::task::spawn_blocking(move || solve_opening(onoro)).await {
+    Ok(entry) => Ok(Box::new(warp::reply::with_header(
+      warp::reply::json(&entry),
+      "cache-control",
+      format!("public, max-age={CACHE_MAX_AGE}"),
+    ))),
+    Err(err) => {
+      println!("Error solving opening {}: {:?}", query.position, err);
+      Ok(Box::new(StatusCode::INTERNAL_SERVER_ERROR))
+    }
+  }
+}
+
+/// `GET /api/openings?position=<board>`, which lists every move from an early
+/// position along with its score.
+pub fn openings_route() -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+  warp::path!("api" / "openings")
+    .and(warp::get())
+    .and(warp::query::<OpeningQuery>())
+    .and_then(handle_opening_query)
+}
@@ -1,6 +1,6 @@
 use async_sockets::Status;
 use bytes::BytesMut;
-use itertools::interleave;
+use itertools::{interleave, Itertools};
 use onoro::{Move, Onoro, PackedIdx, Pawn, PawnColor};
 use prost::Message;
 use serde::{
@@ -8,7 +8,7 @@ use serde::{
   ser, Deserialize, Deserializer, Serialize, Serializer,
 };
 
-use crate::error::Error;
+use crate::{clock::ClockState, error::Error};
 
 mod proto_impl {
   include!(concat!(env!("OUT_DIR"), "/onoro.proto.rs"));
@@ -52,13 +52,101 @@ impl GameStateProto {
           .collect(),
         black_turn: Some(onoro.player_color() == PawnColor::Black),
         turn_num: Some(onoro.pawns_in_play() - 1),
-        finished: Some(onoro.finished().is_some()),
+        finished: Some(onoro.winner().is_some()),
+        moves: Vec::new(),
+        clock: None,
+        legal_moves: Vec::new(),
       },
     }
   }
 
+  /// Adds the state of the game's clocks, for timed games.
+  pub fn with_clock(mut self, clock: Option<&ClockState>) -> Self {
+    let is_black = |color| color == PawnColor::Black;
+    self.game_state.clock = clock.map(|clock| proto_impl::game_state::Clock {
+      black_remaining_ms: Some(clock.black_remaining.as_millis() as u64),
+      white_remaining_ms: Some(clock.white_remaining.as_millis() as u64),
+      increment_ms: Some(clock.increment.as_millis() as u64),
+      black_running: clock.running.map(is_black),
+      black_lost_on_time: clock.lost_on_time.map(is_black),
+    });
+    self
+  }
+
+  /// Adds the legal moves of the current player of `onoro`, the game this game
+  /// state was made from, grouped by the pawn they move. This lets clients
+  /// show where each pawn can go without asking for every pawn.
+  pub fn with_legal_moves<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+    mut self,
+    onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+  ) -> Self {
+    let mut legal_moves: Vec<proto_impl::game_state::PawnMoves> = Vec::new();
+    if onoro.winner().is_none() {
+      for m in onoro.each_move() {
+        let (to, pawn_idx) = match m {
+          Move::Phase1Move { to } => (to, None),
+          Move::Phase2Move { to, from_idx } => (to, Some(from_idx)),
+        };
+        let tile = proto_impl::game_state::Tile {
+          x: Some(to.x() as i32),
+          y: Some(to.y() as i32),
+        };
+        // Moves are generated one pawn at a time, so each pawn's moves are
+        // next to each other.
+        match legal_moves.last_mut() {
+          Some(pawn_moves) if pawn_moves.pawn_idx == pawn_idx => pawn_moves.to.push(tile),
+          _ => legal_moves.push(proto_impl::game_state::PawnMoves {
+            pawn_idx,
+            to: vec![tile],
+          }),
+        }
+      }
+    }
+    self.game_state.legal_moves = legal_moves;
+    self
+  }
+
+  /// Like `GameStateProto::from_onoro`, but also records `history`, the moves
+  /// that were made from `Onoro::default_start()` to reach `onoro`.
+  pub fn from_onoro_with_history<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+    onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+    history: &[Move],
+  ) -> Self {
+    let mut proto = Self::from_onoro(onoro);
+    proto.game_state.moves = history
+      .iter()
+      .map(|m| match *m {
+        Move::Phase1Move { to } => proto_impl::game_state::Move {
+          x: Some(to.x() as i32),
+          y: Some(to.y() as i32),
+          from_idx: None,
+        },
+        Move::Phase2Move { to, from_idx } => proto_impl::game_state::Move {
+          x: Some(to.x() as i32),
+          y: Some(to.y() as i32),
+          from_idx: Some(from_idx),
+        },
+      })
+      .collect();
+    proto
+  }
+
+  /// Reconstructs the game. Game states with a move history are replayed
+  /// from it (see `GameStateProto::to_onoro_with_history`), and others are
+  /// rebuilt from their pawns, which must be listed in an order they could
+  /// have been placed in.
   pub fn to_onoro<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
     &self,
+  ) -> Result<Onoro<N, N2, ADJ_CNT_SIZE>, Error> {
+    if self.game_state.moves.is_empty() {
+      self.to_onoro_from_pawns()
+    } else {
+      self.to_onoro_with_history()
+    }
+  }
+
+  fn to_onoro_from_pawns<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+    &self,
   ) -> Result<Onoro<N, N2, ADJ_CNT_SIZE>, Error> {
     let mut black_moves = Vec::new();
     let mut while_moves = Vec::new();
@@ -114,12 +202,120 @@ impl GameStateProto {
       )));
     }
 
+    if while_moves.is_empty() || black_moves.len() < 2 {
+      return Err(Error::ProtoDecode(
+        "Must have at least the three pawns of the starting position.".into(),
+      ));
+    }
+
+    // The first three pawns are the starting position, which is set up
+    // without the two neighbor rule, like in `Onoro::default_start`. They
+    // must still touch each other.
+    let opening = [black_moves[0], while_moves[0], black_moves[1]];
     let mut game = unsafe { Onoro::new() };
-    unsafe {
-      game.make_move_unchecked(black_moves[0]);
+    for (index, m) in opening.into_iter().enumerate() {
+      if opening[..index].iter().any(|placed| {
+        !game
+          .empty_adjacent_tiles(placed.to())
+          .any(|neighbor| neighbor == m.to())
+      }) {
+        return Err(Error::ProtoDecode(
+          "The first three pawns must be next to each other, like in the starting position.".into(),
+        ));
+      }
+      unsafe {
+        game.make_move_unchecked(m);
+      }
     }
-    for m in interleave(while_moves, black_moves.into_iter().skip(1)) {
-      game.make_move(m);
+
+    // The rest are placed in the order they're listed, alternating colors,
+    // and must each be a legal placement, which keeps disconnected boards and
+    // boards with more than one pawn on a tile out.
+    for (index, m) in interleave(
+      while_moves.into_iter().skip(1),
+      black_moves.into_iter().skip(2),
+    )
+    .enumerate()
+    {
+      game
+        .try_make_move(m)
+        .map_err(|err| Error::ProtoDecode(format!("Pawn {}: {}", index + 3, err.message())))?;
+    }
+
+    Ok(game)
+  }
+}
+
+impl GameStateProto {
+  /// Reconstructs the game by replaying its move history from
+  /// `Onoro::default_start()`. Every move must be legal, and the replayed game
+  /// must have the same pawns as this game state, up to translation.
+  fn to_onoro_with_history<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+    &self,
+  ) -> Result<Onoro<N, N2, ADJ_CNT_SIZE>, Error> {
+    let mut game = Onoro::default_start();
+    for (index, move_proto) in self.game_state.moves.iter().enumerate() {
+      let (x, y) = (move_proto.x() as u32, move_proto.y() as u32);
+      if x >= N as u32 || y >= N as u32 {
+        return Err(Error::ProtoDecode(format!(
+          "Move {index} x/y out of bounds: {} {}",
+          move_proto.x(),
+          move_proto.y()
+        )));
+      }
+      let to = PackedIdx::new(x, y);
+      let m = match move_proto.from_idx {
+        Some(from_idx) => Move::Phase2Move { to, from_idx },
+        None => Move::Phase1Move { to },
+      };
+
+      game
+        .try_make_move(m)
+        .map_err(|err| Error::ProtoDecode(format!("Move {index}: {}", err.message())))?;
+    }
+
+    // Pawn positions only matter relative to each other, so compare them
+    // relative to the bottom-left-most corner of each board.
+    let normalize = |pawns: Vec<(i32, i32, bool)>| {
+      let min_x = pawns.iter().map(|&(x, _, _)| x).min().unwrap_or(0);
+      let min_y = pawns.iter().map(|&(_, y, _)| y).min().unwrap_or(0);
+      pawns
+        .into_iter()
+        .map(|(x, y, black)| (x - min_x, y - min_y, black))
+        .sorted()
+        .collect::<Vec<_>>()
+    };
+    let replayed_pawns = normalize(
+      game
+        .pawns()
+        .map(|pawn| {
+          (
+            pawn.pos.x() as i32,
+            pawn.pos.y() as i32,
+            pawn.color == PawnColor::Black,
+          )
+        })
+        .collect(),
+    );
+    let pawns = normalize(
+      self
+        .game_state
+        .pawns
+        .iter()
+        .map(|pawn| (pawn.x(), pawn.y(), pawn.black()))
+        .collect(),
+    );
+    if pawns != replayed_pawns {
+      return Err(Error::ProtoDecode(
+        "Pawns don't match the game reached by replaying the moves".into(),
+      ));
+    }
+    if self.game_state.black_turn.is_some()
+      && self.game_state.black_turn() != (game.player_color() == PawnColor::Black)
+    {
+      return Err(Error::ProtoDecode(
+        "Current player doesn't match the game reached by replaying the moves".into(),
+      ));
     }
 
     Ok(game)
@@ -158,3 +354,120 @@ impl<'de> Deserialize<'de> for GameStateProto {
     Ok(GameStateProto { game_state })
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use onoro::{Move, Onoro16, PackedIdx};
+  use prost::Message;
+
+  use crate::error::Error;
+
+  use super::{proto_impl, GameStateProto};
+
+  /// The game reached by making the first legal move `n_moves` times, or
+  /// until the game is over.
+  fn first_moves(n_moves: usize) -> (Onoro16, Vec<Move>) {
+    let mut game = Onoro16::default_start();
+    let mut history = Vec::new();
+    while history.len() < n_moves && game.winner().is_none() {
+      let m = game.each_move().next().unwrap();
+      game.make_move(m);
+      history.push(m);
+    }
+    (game, history)
+  }
+
+  /// `proto` after being encoded and decoded again, as when it is sent to a
+  /// client and back.
+  fn round_trip(proto: &GameStateProto) -> GameStateProto {
+    let bytes = proto.game_state.encode_to_vec();
+    GameStateProto {
+      game_state: proto_impl::GameState::decode(&bytes[..]).unwrap(),
+    }
+  }
+
+  #[test]
+  fn test_history_round_trip() {
+    for n_moves in [0, 1, 10, 13, 30] {
+      let (game, history) = first_moves(n_moves);
+      let proto = round_trip(&GameStateProto::from_onoro_with_history(&game, &history));
+      assert_eq!(proto.game_state.moves.len(), history.len());
+      let decoded: Result<Onoro16, _> = proto.to_onoro();
+      assert_eq!(decoded.ok().unwrap().to_string(), game.to_string());
+    }
+  }
+
+  #[test]
+  fn test_invalid_history() {
+    let (game, mut history) = first_moves(10);
+
+    // A history that stops short of the game's pawns.
+    let proto = round_trip(&GameStateProto::from_onoro_with_history(
+      &game,
+      &history[..history.len() - 1],
+    ));
+    let decoded: Result<Onoro16, _> = proto.to_onoro();
+    assert!(matches!(decoded, Err(Error::ProtoDecode(_))));
+
+    // A pawn placed away from every other pawn.
+    history.push(Move::Phase1Move {
+      to: PackedIdx::new(1, 1),
+    });
+    let proto = round_trip(&GameStateProto::from_onoro_with_history(&game, &history));
+    let decoded: Result<Onoro16, _> = proto.to_onoro();
+    assert!(matches!(decoded, Err(Error::ProtoDecode(_))));
+  }
+
+  /// A game state of just `pawns`, given as (x, y, black).
+  fn pawn_board(pawns: &[(i32, i32, bool)]) -> GameStateProto {
+    GameStateProto {
+      game_state: proto_impl::GameState {
+        pawns: pawns
+          .iter()
+          .map(|&(x, y, black)| proto_impl::game_state::Pawn {
+            x: Some(x),
+            y: Some(y),
+            black: Some(black),
+          })
+          .collect(),
+        ..Default::default()
+      },
+    }
+  }
+
+  #[test]
+  fn test_pawns_round_trip() {
+    for n_moves in [0, 1, 10, 13] {
+      let (game, _) = first_moves(n_moves);
+      let proto = round_trip(&GameStateProto::from_onoro(&game));
+      let decoded: Result<Onoro16, _> = proto.to_onoro();
+      assert_eq!(decoded.ok().unwrap().to_string(), game.to_string());
+    }
+  }
+
+  #[test]
+  fn test_invalid_pawns() {
+    let start = [(8, 8, true), (9, 9, false), (9, 8, true)];
+    let decoded: Result<Onoro16, _> = pawn_board(&start).to_onoro();
+    assert!(decoded.is_ok());
+
+    // Too few pawns for the starting position.
+    let decoded: Result<Onoro16, _> = pawn_board(&start[..2]).to_onoro();
+    assert!(matches!(decoded, Err(Error::ProtoDecode(_))));
+
+    // A starting position whose pawns don't touch.
+    let decoded: Result<Onoro16, _> =
+      pawn_board(&[(8, 8, true), (9, 9, false), (11, 8, true)]).to_onoro();
+    assert!(matches!(decoded, Err(Error::ProtoDecode(_))));
+
+    // A pawn away from every other pawn.
+    let decoded: Result<Onoro16, _> =
+      pawn_board(&[start[0], start[1], start[2], (2, 12, false)]).to_onoro();
+    assert!(matches!(decoded, Err(Error::ProtoDecode(_))));
+
+    // Two pawns on the same tile.
+    let decoded: Result<Onoro16, _> =
+      pawn_board(&[start[0], start[1], start[2], (9, 8, false)]).to_onoro();
+    assert!(matches!(decoded, Err(Error::ProtoDecode(_))));
+  }
+}
@@ -0,0 +1,100 @@
+use std::convert::Infallible;
+
+use onoro::{Move, Onoro16, Replay};
+use serde::{Deserialize, Serialize};
+use warp::{Filter, Rejection, Reply};
+
+/// The largest request body accepted, which fits games of thousands of moves.
+const MAX_BODY_BYTES: u64 = 64 * 1024;
+
+#[derive(Deserialize)]
+pub struct ReplayRequest {
+  /// Every move of the game from `Onoro::default_start()`, in `Move`'s
+  /// notation, e.g. "(8, 9) from idx 2".
+  moves: Vec<String>,
+}
+
+/// The verdict on a game history. The first three fields are only set for
+/// illegal games, and the last two only for legal ones.
+#[derive(Default, Serialize)]
+struct ReplayJson {
+  legal: bool,
+  /// The index of the first move that is illegal or couldn't be parsed.
+  illegal_move_index: Option<usize>,
+  /// Why that move is illegal.
+  reason: Option<String>,
+  /// Every rule that move breaks, which is empty if it couldn't be parsed.
+  violations: Vec<String>,
+  /// The index of the first move of phase 2, if the game got that far.
+  phase2_start: Option<usize>,
+  /// "black" or "white", if the game has been won.
+  winner: Option<String>,
+}
+
+impl ReplayJson {
+  fn illegal(index: usize, reason: String, violations: Vec<String>) -> Self {
+    Self {
+      illegal_move_index: Some(index),
+      reason: Some(reason),
+      violations,
+      ..Self::default()
+    }
+  }
+}
+
+fn validate(request: &ReplayRequest) -> ReplayJson {
+  // Only the moves up to the first one that can't be parsed are replayed, in
+  // case an earlier move is illegal.
+  let mut moves = Vec::with_capacity(request.moves.len());
+  let mut parse_error = None;
+  for (index, notation) in request.moves.iter().enumerate() {
+    match notation.parse::<Move>() {
+      Ok(m) => moves.push(m),
+      Err(err) => {
+        parse_error = Some((index, err.to_string()));
+        break;
+      }
+    }
+  }
+
+  let replay = match Replay::validate(&Onoro16::default_start(), &moves) {
+    Ok(replay) => replay,
+    Err(illegal) => {
+      return ReplayJson::illegal(
+        illegal.index,
+        illegal.to_string(),
+        illegal
+          .violations
+          .iter()
+          .map(|violation| violation.to_string())
+          .collect(),
+      );
+    }
+  };
+  if let Some((index, reason)) = parse_error {
+    return ReplayJson::illegal(index, reason, Vec::new());
+  }
+
+  ReplayJson {
+    legal: true,
+    phase2_start: replay.phase2_start,
+    winner: replay.winner.map(|color| color.to_string()),
+    ..ReplayJson::default()
+  }
+}
+
+async fn handle_replay(request: ReplayRequest) -> Result<impl Reply, Infallible> {
+  Ok(warp::reply::json(&validate(&request)))
+}
+
+/// `POST /api/replay` with `{"moves":[<move>, ...]}`, which checks that the
+/// moves make up a legal game from the default start, e.g. before a submitted
+/// game is stored. Returns whether the game is legal, and either the index of
+/// its first illegal move and why, or when phase 2 started and who won.
+pub fn replay_route() -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+  warp::path!("api" / "replay")
+    .and(warp::post())
+    .and(warp::body::content_length_limit(MAX_BODY_BYTES))
+    .and(warp::body::json::<ReplayRequest>())
+    .and_then(handle_replay)
+}
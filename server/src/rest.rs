@@ -0,0 +1,228 @@
+use std::{convert::Infallible, time::SystemTime};
+
+use abstract_game::Compress;
+use onoro::{Move, Onoro16, PawnColor};
+use serde::{Deserialize, Serialize};
+use warp::{http::StatusCode, Filter, Rejection, Reply};
+
+use crate::{
+  clock::{ClockState, TimeControl},
+  game_manager::{GameError, GameId, GameManager, GameUpdate, PlayerToken, Seat},
+};
+
+/// The largest request body accepted, which is plenty for a move.
+const MAX_BODY_BYTES: u64 = 1024;
+
+/// The JSON form of a game state, with the board as the hex of its compressed
+/// form (see `Compress`).
+#[derive(Serialize)]
+struct GameJson {
+  board: String,
+  black_turn: bool,
+  finished: bool,
+  clock: Option<ClockJson>,
+}
+
+impl GameJson {
+  fn new(game: &Onoro16, clock: Option<&ClockState>) -> Self {
+    let mut bytes = vec![0; Onoro16::COMPRESSED_SIZE];
+    game.compress(&mut bytes);
+    Self {
+      board: bytes.iter().map(|byte| format!("{byte:02x}")).collect(),
+      black_turn: game.player_color() == PawnColor::Black,
+      finished: game.winner().is_some(),
+      clock: clock.map(ClockJson::new),
+    }
+  }
+
+  fn from_update(update: &GameUpdate) -> Self {
+    Self::new(&update.game, update.clock.as_ref())
+  }
+}
+
+/// The clocks of a timed game, with colors as "black" or "white".
+#[derive(Serialize)]
+struct ClockJson {
+  black_remaining_ms: u64,
+  white_remaining_ms: u64,
+  increment_ms: u64,
+  running: Option<String>,
+  lost_on_time: Option<String>,
+}
+
+impl ClockJson {
+  fn new(clock: &ClockState) -> Self {
+    Self {
+      black_remaining_ms: clock.black_remaining.as_millis() as u64,
+      white_remaining_ms: clock.white_remaining.as_millis() as u64,
+      increment_ms: clock.increment.as_millis() as u64,
+      running: clock.running.map(|color| color.to_string()),
+      lost_on_time: clock.lost_on_time.map(|color| color.to_string()),
+    }
+  }
+}
+
+/// The time control of a new game, which is untimed if `base_ms` is not
+/// given.
+#[derive(Deserialize)]
+pub struct CreateQuery {
+  base_ms: Option<u64>,
+  increment_ms: Option<u64>,
+}
+
+/// The response to creating or joining a game, mirroring the socket API's
+/// `GameSeat`. The player token must be sent with every later request for the
+/// game.
+#[derive(Serialize)]
+struct SeatJson {
+  game_id: GameId,
+  player_token: PlayerToken,
+  color: String,
+  game: GameJson,
+}
+
+#[derive(Deserialize)]
+pub struct UpdatesQuery {
+  player_token: PlayerToken,
+  /// The number of moves the client has already received.
+  #[serde(default)]
+  from_index: u32,
+}
+
+/// The response of `GET /game/{id}`, mirroring the socket API's
+/// `GameUpdates`.
+#[derive(Serialize)]
+struct UpdatesJson {
+  /// The moves made since `from_index` in the request.
+  moves: Vec<String>,
+  /// When each of `moves` was made, in milliseconds since the Unix epoch.
+  move_times_ms: Vec<u64>,
+  game: GameJson,
+  opponent_joined: bool,
+}
+
+#[derive(Deserialize)]
+pub struct MoveRequest {
+  player_token: PlayerToken,
+  /// The move in `Move`'s notation, e.g. "(8, 9) from idx 2".
+  game_move: String,
+}
+
+#[derive(Serialize)]
+struct ErrorJson {
+  error: String,
+}
+
+fn error_reply(reason: String, status: StatusCode) -> Box<dyn Reply> {
+  Box::new(warp::reply::with_status(
+    warp::reply::json(&ErrorJson { error: reason }),
+    status,
+  ))
+}
+
+fn game_error_reply(err: GameError) -> Box<dyn Reply> {
+  let status = match err {
+    GameError::UnknownGame => StatusCode::NOT_FOUND,
+    GameError::NotAPlayer => StatusCode::FORBIDDEN,
+    GameError::GameFull | GameError::NotYourTurn | GameError::OutOfTime(_) => StatusCode::CONFLICT,
+    GameError::IllegalMove(_) => StatusCode::UNPROCESSABLE_ENTITY,
+  };
+  error_reply(err.to_string(), status)
+}
+
+fn seat_reply(seat: Seat) -> Box<dyn Reply> {
+  Box::new(warp::reply::json(&SeatJson {
+    game_id: seat.game_id,
+    player_token: seat.player_token,
+    color: seat.color.to_string(),
+    game: GameJson::new(&Onoro16::default_start(), None),
+  }))
+}
+
+async fn handle_create_game(query: CreateQuery) -> Result<Box<dyn Reply>, Infallible> {
+  let time_control = query.base_ms.map(|base_ms| TimeControl {
+    base_ms,
+    increment_ms: query.increment_ms.unwrap_or(0),
+  });
+  Ok(seat_reply(GameManager::global().create_game(time_control)))
+}
+
+async fn handle_join_game(game_id: GameId) -> Result<Box<dyn Reply>, Infallible> {
+  Ok(match GameManager::global().join_game(game_id) {
+    Ok(seat) => seat_reply(seat),
+    Err(err) => game_error_reply(err),
+  })
+}
+
+async fn handle_game_updates(
+  game_id: GameId,
+  query: UpdatesQuery,
+) -> Result<Box<dyn Reply>, Infallible> {
+  Ok(
+    match GameManager::global().updates(game_id, query.player_token, query.from_index) {
+      Ok(update) => Box::new(warp::reply::json(&UpdatesJson {
+        moves: update.moves.iter().map(|m| m.to_string()).collect(),
+        move_times_ms: update
+          .move_times
+          .iter()
+          .map(|time| {
+            time
+              .duration_since(SystemTime::UNIX_EPOCH)
+              .map_or(0, |since_epoch| since_epoch.as_millis() as u64)
+          })
+          .collect(),
+        game: GameJson::from_update(&update),
+        opponent_joined: update.opponent_joined,
+      })),
+      Err(err) => game_error_reply(err),
+    },
+  )
+}
+
+async fn handle_make_move(
+  game_id: GameId,
+  request: MoveRequest,
+) -> Result<Box<dyn Reply>, Infallible> {
+  let m: Move = match request.game_move.parse() {
+    Ok(m) => m,
+    Err(err) => return Ok(error_reply(format!("{err}"), StatusCode::BAD_REQUEST)),
+  };
+  Ok(
+    match GameManager::global().make_move(game_id, request.player_token, m) {
+      Ok(update) => Box::new(warp::reply::json(&GameJson::from_update(&update))),
+      Err(err) => game_error_reply(err),
+    },
+  )
+}
+
+/// The REST API for games between two players, mirroring the socket API for
+/// clients that can't use WebSockets:
+///
+/// - `POST /game?base_ms=<ms>&increment_ms=<ms>` creates a game, seating the
+///   caller as black. The game is timed if `base_ms` is given.
+/// - `POST /game/{id}/join` seats the caller as white.
+/// - `GET /game/{id}?player_token=<token>&from_index=<n>` returns the moves
+///   made after the first `n`, and the current game state.
+/// - `POST /game/{id}/move` with `{"player_token":<token>,"game_move":<move>}`
+///   makes a move, returning the new game state.
+///
+/// Errors are returned as `{"error":<reason>}` with a matching status code.
+pub fn game_routes() -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+  let create = warp::path!("game")
+    .and(warp::post())
+    .and(warp::query::<CreateQuery>())
+    .and_then(handle_create_game);
+  let join = warp::path!("game" / GameId / "join")
+    .and(warp::post())
+    .and_then(handle_join_game);
+  let updates = warp::path!("game" / GameId)
+    .and(warp::get())
+    .and(warp::query::<UpdatesQuery>())
+    .and_then(handle_game_updates);
+  let make_move = warp::path!("game" / GameId / "move")
+    .and(warp::post())
+    .and(warp::body::content_length_limit(MAX_BODY_BYTES))
+    .and(warp::body::json::<MoveRequest>())
+    .and_then(handle_make_move);
+  create.or(join).or(updates).or(make_move)
+}
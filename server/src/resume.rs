@@ -0,0 +1,83 @@
+use std::{
+  collections::HashMap,
+  sync::{Mutex, OnceLock},
+  time::{Duration, Instant},
+};
+
+use onoro::PawnColor;
+
+use crate::game_manager::{GameId, PlayerToken, Seat};
+
+/// Resume tokens expire after this long without being used, which matches how
+/// long an abandoned game is kept for.
+const RESUME_TOKEN_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// A secret given to a player along with their seat, which lets them take the
+/// seat back from a new connection if theirs drops. It is generated like a
+/// player token, but is a separate secret.
+pub type ResumeToken = PlayerToken;
+
+struct ResumableSeat {
+  game_id: GameId,
+  player_token: PlayerToken,
+  color: PawnColor,
+  expires: Instant,
+}
+
+/// Maps resume tokens to the seats they resume. A client that loses its
+/// connection reconnects and presents its resume token to get its seat back,
+/// without having kept the seat's player token itself.
+pub struct ResumeTokens {
+  seats: Mutex<HashMap<ResumeToken, ResumableSeat>>,
+}
+
+impl ResumeTokens {
+  fn new() -> Self {
+    Self {
+      seats: Mutex::new(HashMap::new()),
+    }
+  }
+
+  /// The resume tokens shared by all client connections.
+  pub fn global() -> &'static Self {
+    static TOKENS: OnceLock<ResumeTokens> = OnceLock::new();
+    TOKENS.get_or_init(Self::new)
+  }
+
+  /// Issues a resume token for `seat`.
+  pub fn issue(&self, seat: &Seat) -> ResumeToken {
+    let resume_token = PlayerToken::random();
+    let mut seats = self.seats.lock().unwrap();
+    let now = Instant::now();
+    seats.retain(|_, seat| seat.expires > now);
+    seats.insert(
+      resume_token,
+      ResumableSeat {
+        game_id: seat.game_id,
+        player_token: seat.player_token,
+        color: seat.color,
+        expires: now + RESUME_TOKEN_TTL,
+      },
+    );
+    resume_token
+  }
+
+  /// The seat `resume_token` was issued for, or `None` if it is unknown or
+  /// has expired. Resuming extends the token's life.
+  pub fn resume(&self, resume_token: ResumeToken) -> Option<Seat> {
+    let mut seats = self.seats.lock().unwrap();
+    let now = Instant::now();
+    let seat = seats.get_mut(&resume_token)?;
+    if seat.expires <= now {
+      seats.remove(&resume_token);
+      return None;
+    }
+
+    seat.expires = now + RESUME_TOKEN_TTL;
+    Some(Seat {
+      game_id: seat.game_id,
+      player_token: seat.player_token,
+      color: seat.color,
+    })
+  }
+}
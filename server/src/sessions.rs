@@ -0,0 +1,157 @@
+use std::{
+  collections::{HashMap, VecDeque},
+  sync::{Mutex, OnceLock},
+};
+
+use onoro::{Move, Onoro16};
+
+use crate::game_manager::PlayerToken;
+
+/// The maximum number of games the server keeps track of. Clients don't say
+/// when they are done with a game, so once this many games exist, the oldest
+/// ones are dropped to make room for new ones.
+const MAX_SESSIONS: usize = 4096;
+
+/// Identifies a session in every request about it, so it is the only thing
+/// standing between a session and anyone else making moves in it. It is
+/// generated like a player token, so it can't be guessed.
+pub type SessionId = PlayerToken;
+
+/// Why a move could not be made in a session.
+pub enum MoveError {
+  UnknownSession,
+  IllegalMove(String),
+}
+
+/// Moves of the current player that win or stop the opponent from winning,
+/// see `Onoro::immediate_wins` and `Onoro::forced_blocks`.
+pub struct Threats {
+  pub immediate_wins: Vec<Move>,
+  pub forced_blocks: Vec<Move>,
+}
+
+/// The game of a session, along with its history.
+#[derive(Clone)]
+pub struct SessionGame {
+  pub game: Onoro16,
+  /// The moves made to reach `game` from `Onoro::default_start()`.
+  pub moves: Vec<Move>,
+}
+
+impl SessionGame {
+  /// A game that hasn't started yet.
+  pub fn default_start() -> Self {
+    Self {
+      game: Onoro16::default_start(),
+      moves: Vec::new(),
+    }
+  }
+}
+
+#[derive(Default)]
+struct Sessions {
+  games: HashMap<SessionId, SessionGame>,
+  /// Session IDs in order of creation, for evicting the oldest sessions.
+  order: VecDeque<SessionId>,
+}
+
+/// Tracks the games being played by clients. Each client connection starts a
+/// game with `new_game`, and refers to it by its session ID in later requests.
+pub struct GameSessions {
+  sessions: Mutex<Sessions>,
+}
+
+impl GameSessions {
+  fn new() -> Self {
+    Self {
+      sessions: Mutex::new(Sessions::default()),
+    }
+  }
+
+  /// The game sessions shared by all client connections.
+  pub fn global() -> &'static Self {
+    static SESSIONS: OnceLock<GameSessions> = OnceLock::new();
+    SESSIONS.get_or_init(Self::new)
+  }
+
+  /// The number of sessions being tracked.
+  pub fn len(&self) -> usize {
+    self.sessions.lock().unwrap().games.len()
+  }
+
+  /// True if session `session_id` exists.
+  pub fn contains(&self, session_id: SessionId) -> bool {
+    self
+      .sessions
+      .lock()
+      .unwrap()
+      .games
+      .contains_key(&session_id)
+  }
+
+  /// Starts a new session playing `game`, returning the ID of the session.
+  pub fn create(&self, game: SessionGame) -> SessionId {
+    let session_id = PlayerToken::random();
+    let mut sessions = self.sessions.lock().unwrap();
+    if sessions.order.len() >= MAX_SESSIONS {
+      if let Some(oldest) = sessions.order.pop_front() {
+        sessions.games.remove(&oldest);
+      }
+    }
+    sessions.games.insert(session_id, game);
+    sessions.order.push_back(session_id);
+    session_id
+  }
+
+  /// The current game of session `session_id`, or `None` if no such session
+  /// exists.
+  pub fn game(&self, session_id: SessionId) -> Option<SessionGame> {
+    self
+      .sessions
+      .lock()
+      .unwrap()
+      .games
+      .get(&session_id)
+      .cloned()
+  }
+
+  /// All legal moves from the current game state of session `session_id`, or
+  /// `None` if no such session exists.
+  pub fn legal_moves(&self, session_id: SessionId) -> Option<Vec<Move>> {
+    let sessions = self.sessions.lock().unwrap();
+    let game = &sessions.games.get(&session_id)?.game;
+    if game.finished().is_some() {
+      return Some(Vec::new());
+    }
+    Some(game.each_move().collect())
+  }
+
+  /// The moves that win on the spot for the current player of session
+  /// `session_id`, and those that block a tile their opponent threatens to
+  /// win on, for hinting. Returns `None` if no such session exists.
+  pub fn threats(&self, session_id: SessionId) -> Option<Threats> {
+    let sessions = self.sessions.lock().unwrap();
+    let game = &sessions.games.get(&session_id)?.game;
+    Some(Threats {
+      immediate_wins: game.immediate_wins(),
+      forced_blocks: game.forced_blocks(),
+    })
+  }
+
+  /// Makes move `m` in session `session_id` if it is legal, returning the
+  /// updated game.
+  pub fn make_move(&self, session_id: SessionId, m: Move) -> Result<SessionGame, MoveError> {
+    let mut sessions = self.sessions.lock().unwrap();
+    let game = sessions
+      .games
+      .get_mut(&session_id)
+      .ok_or(MoveError::UnknownSession)?;
+
+    game
+      .game
+      .try_make_move(m)
+      .map_err(|err| MoveError::IllegalMove(err.message().to_owned()))?;
+    game.moves.push(m);
+    Ok(game.clone())
+  }
+}
@@ -1,17 +1,48 @@
 use serde::Deserialize;
-use std::time::Duration;
+use std::{
+  collections::HashMap,
+  sync::{Mutex, OnceLock},
+  time::{Duration, SystemTime},
+};
 
 use async_sockets::{
   AsyncSocket, AsyncSocketContext, AsyncSocketEmitters, AsyncSocketListeners, AsyncSocketOptions,
   AsyncSocketResponders, Status,
 };
-use onoro::Onoro16;
+use onoro::Move;
 use tokio::task::JoinHandle;
 
-use crate::proto::GameStateProto;
+use crate::{
+  ai::{self, Difficulty},
+  analysis::{AnalysisJobs, JobId, JobStatus, MoveAnalysis},
+  clock::TimeControl,
+  config::Config,
+  error::Error,
+  game_manager::{GameError, GameId, GameManager, GameUpdate, PlayerToken, Seat},
+  governor::{Busy, ConnectionId, SolverGovernor, Workload},
+  proto::GameStateProto,
+  resume::{ResumeToken, ResumeTokens},
+  sessions::{GameSessions, MoveError, SessionGame, SessionId},
+  socket_relay::SOCKET_PATH,
+};
+
+/// How often the clocks of timed games are checked for players who have run
+/// out of time.
+const CLOCK_CHECK_INTERVAL: Duration = Duration::from_millis(100);
 
 #[derive(AsyncSocketEmitters)]
-enum ServerEmitEvents {}
+enum ServerEmitEvents {
+  /// The player of `color` ran out of time in game `game_id`, and lost.
+  LostOnTime { game_id: GameId, color: String },
+  /// `game_move` was made in game `game_id`, as its `move_index`th move,
+  /// reaching `game`.
+  MoveMade {
+    game_id: GameId,
+    game_move: String,
+    move_index: u32,
+    game: GameStateProto,
+  },
+}
 
 #[derive(AsyncSocketListeners)]
 enum ClientEmitEvents {}
@@ -25,23 +56,587 @@ enum FromClientResponses {}
 #[derive(AsyncSocketListeners)]
 enum FromClientRequests {
   NewGame {},
+  MakeMove {
+    session_id: SessionId,
+    /// The move in `Move`'s notation, e.g. "(8, 9) from idx 2".
+    game_move: String,
+  },
+  LegalMoves {
+    session_id: SessionId,
+  },
+  Threats {
+    session_id: SessionId,
+  },
+  GameState {
+    session_id: SessionId,
+  },
+  AiMove {
+    session_id: SessionId,
+    difficulty: Difficulty,
+  },
+  CreateGame {
+    /// The clock settings of the game, or `None` for an untimed game.
+    time_control: Option<TimeControl>,
+  },
+  JoinGame {
+    game_id: GameId,
+  },
+  /// Follows game `game_id` without taking a seat in it. The game's moves are
+  /// emitted to the spectator as `MoveMade` events as they are made.
+  WatchGame {
+    game_id: GameId,
+  },
+  /// Takes back the seat `resume_token` was issued with, e.g. after the
+  /// connection it was taken from dropped.
+  ResumeGame {
+    resume_token: ResumeToken,
+  },
+  PlayMove {
+    game_id: GameId,
+    player_token: PlayerToken,
+    game_move: String,
+  },
+  GameUpdates {
+    game_id: GameId,
+    player_token: PlayerToken,
+    /// The number of moves the client has already received.
+    from_index: u32,
+  },
+  /// Scores every legal move of `game` with a search of at most
+  /// `search_depth`, for the analysis board. Counts against the analysis
+  /// limit of the connection.
+  Analyze {
+    session_id: SessionId,
+    game: GameStateProto,
+    search_depth: u32,
+  },
+  /// Counts against the analysis limit of the connection until the job
+  /// finishes.
+  SubmitAnalysisJob {
+    session_id: SessionId,
+    games: Vec<GameStateProto>,
+    search_depth: u32,
+  },
+  AnalysisJobStatus {
+    job_id: JobId,
+    /// The number of results the client has already received.
+    from_index: u32,
+  },
+  CancelAnalysisJob {
+    job_id: JobId,
+  },
+  /// How many searches are waiting for the solver, e.g. for a client turned
+  /// away with `SolverBusy` to decide when to retry.
+  SolverLoad {},
 }
 
 #[derive(AsyncSocketResponders)]
 enum ToClientResponses {
-  NewGame { game: GameStateProto },
+  NewGame {
+    game: GameStateProto,
+    session_id: SessionId,
+  },
+  MakeMove {
+    game: GameStateProto,
+  },
+  LegalMoves {
+    moves: Vec<String>,
+  },
+  Threats {
+    /// Moves that win the game on the spot.
+    immediate_wins: Vec<String>,
+    /// Moves that block the opponent from winning on their next turn.
+    forced_blocks: Vec<String>,
+  },
+  GameState {
+    game: GameStateProto,
+  },
+  UnknownSession {
+    session_id: SessionId,
+  },
+  IllegalMove {
+    reason: String,
+  },
+  AiMove {
+    game_move: String,
+    game: GameStateProto,
+  },
+  NoLegalMoves {
+    session_id: SessionId,
+  },
+  GameSeat {
+    game_id: GameId,
+    player_token: PlayerToken,
+    color: String,
+    /// Presented in `ResumeGame` to take this seat back from a new
+    /// connection.
+    resume_token: ResumeToken,
+  },
+  GameResumed {
+    game_id: GameId,
+    player_token: PlayerToken,
+    color: String,
+    /// Every move made so far.
+    moves: Vec<String>,
+    game: GameStateProto,
+    opponent_joined: bool,
+  },
+  UnknownResumeToken {},
+  PlayMove {
+    game: GameStateProto,
+  },
+  GameUpdates {
+    /// The moves made since `from_index` in the request.
+    moves: Vec<String>,
+    /// When each of `moves` was made, in milliseconds since the Unix epoch.
+    move_times_ms: Vec<u64>,
+    game: GameStateProto,
+    opponent_joined: bool,
+  },
+  WatchGame {
+    /// Every move made so far.
+    moves: Vec<String>,
+    game: GameStateProto,
+  },
+  UnknownGame {
+    game_id: GameId,
+  },
+  GameRequestDenied {
+    reason: String,
+  },
+  Analyze {
+    /// Every legal move, with its score for the player making it.
+    moves: Vec<MoveAnalysis>,
+  },
+  AnalysisJobSubmitted {
+    job_id: JobId,
+  },
+  AnalysisJobStatus {
+    status: JobStatus,
+  },
+  AnalysisJobCancelled {
+    job_id: JobId,
+  },
+  UnknownAnalysisJob {
+    job_id: JobId,
+  },
+  InvalidGameState {
+    index: u32,
+    reason: String,
+  },
+  /// The search wasn't started, because the session has too many searches
+  /// running or the solver's queue is full. The request may be retried later.
+  SolverBusy {
+    reason: String,
+    /// The number of searches of the same kind waiting for the solver.
+    queued: u32,
+  },
+  SolverLoad {
+    /// The number of AI moves waiting for the solver.
+    play_queued: u32,
+    /// The number of analysis requests waiting for the solver.
+    analysis_queued: u32,
+  },
+}
+
+fn seat_response(seat: Seat) -> ToClientResponses {
+  ToClientResponses::GameSeat {
+    game_id: seat.game_id,
+    player_token: seat.player_token,
+    color: seat.color.to_string(),
+    resume_token: ResumeTokens::global().issue(&seat),
+  }
+}
+
+fn game_proto(update: &GameUpdate) -> GameStateProto {
+  GameStateProto::from_onoro_with_history(&update.game, &update.history)
+    .with_clock(update.clock.as_ref())
+    .with_legal_moves(&update.game)
+}
+
+fn session_proto(session: &SessionGame) -> GameStateProto {
+  GameStateProto::from_onoro_with_history(&session.game, &session.moves)
+    .with_legal_moves(&session.game)
+}
+
+/// A connection that game events can be emitted to.
+trait Subscriber {
+  /// Emits `event` to the connection, returning false if it has closed.
+  fn send(&self, event: ServerEmitEvents) -> bool;
+}
+
+impl Subscriber for AsyncSocketContext<ServerEmitEvents> {
+  fn send(&self, event: ServerEmitEvents) -> bool {
+    self.emit(event).is_ok()
+  }
+}
+
+/// The connections following each game, by game: its players, and any
+/// number of spectators. Connections are dropped once an event can't be
+/// emitted to them, since clients don't say when they disconnect.
+struct Subscribers<S> {
+  games: HashMap<GameId, Vec<S>>,
+}
+
+impl<S: Subscriber> Subscribers<S> {
+  fn new() -> Self {
+    Self {
+      games: HashMap::new(),
+    }
+  }
+
+  fn subscribe(&mut self, game_id: GameId, subscriber: S) {
+    self.games.entry(game_id).or_default().push(subscriber);
+  }
+
+  /// Emits the event made by `event` to every subscriber of game `game_id`,
+  /// dropping those whose connections have closed.
+  fn broadcast(&mut self, game_id: GameId, event: impl Fn() -> ServerEmitEvents) {
+    let Some(subscribers) = self.games.get_mut(&game_id) else {
+      return;
+    };
+    subscribers.retain(|subscriber| subscriber.send(event()));
+    if subscribers.is_empty() {
+      self.games.remove(&game_id);
+    }
+  }
+
+  /// Drops the subscribers of every game that `keep` returns false for.
+  fn retain_games(&mut self, mut keep: impl FnMut(GameId) -> bool) {
+    self.games.retain(|&game_id, _| keep(game_id));
+  }
+}
+
+/// The connections to emit the events of each game to.
+fn subscribers() -> &'static Mutex<Subscribers<AsyncSocketContext<ServerEmitEvents>>> {
+  static SUBSCRIBERS: OnceLock<Mutex<Subscribers<AsyncSocketContext<ServerEmitEvents>>>> =
+    OnceLock::new();
+  SUBSCRIBERS.get_or_init(|| Mutex::new(Subscribers::new()))
+}
+
+fn subscribe(game_id: GameId, context: AsyncSocketContext<ServerEmitEvents>) {
+  subscribers().lock().unwrap().subscribe(game_id, context);
+}
+
+/// Emits the event made by `event` to every subscriber of game `game_id`.
+fn broadcast(game_id: GameId, event: impl Fn() -> ServerEmitEvents) {
+  subscribers().lock().unwrap().broadcast(game_id, event);
+}
+
+fn seat_and_subscribe(
+  seat: Seat,
+  context: AsyncSocketContext<ServerEmitEvents>,
+) -> ToClientResponses {
+  subscribe(seat.game_id, context);
+  seat_response(seat)
+}
+
+/// Tells the subscribers of every game in which someone has run out of time,
+/// and drops the subscribers of games that no longer exist, until the server
+/// shuts down.
+async fn watch_clocks() {
+  let mut interval = tokio::time::interval(CLOCK_CHECK_INTERVAL);
+  loop {
+    interval.tick().await;
+    for (game_id, loser) in GameManager::global().expire_clocks() {
+      broadcast(game_id, || ServerEmitEvents::LostOnTime {
+        game_id,
+        color: loser.to_string(),
+      });
+    }
+    subscribers()
+      .lock()
+      .unwrap()
+      .retain_games(|game_id| GameManager::global().contains(game_id));
+  }
+}
+
+/// The connection `context` belongs to, which searches are limited by.
+fn connection_id(context: &AsyncSocketContext<ServerEmitEvents>) -> ConnectionId {
+  context.client_id()
+}
+
+fn busy_response(workload: Workload, busy: Busy) -> ToClientResponses {
+  ToClientResponses::SolverBusy {
+    reason: busy.to_string(),
+    queued: SolverGovernor::global().queued(workload) as u32,
+  }
+}
+
+fn game_error_response(game_id: GameId, err: GameError) -> ToClientResponses {
+  match err {
+    GameError::UnknownGame => ToClientResponses::UnknownGame { game_id },
+    GameError::IllegalMove(reason) => ToClientResponses::IllegalMove { reason },
+    err => ToClientResponses::GameRequestDenied {
+      reason: err.to_string(),
+    },
+  }
 }
 
 async fn handle_connect_event(_context: AsyncSocketContext<ServerEmitEvents>) {}
 
 async fn handle_call_event(
   event: FromClientRequests,
-  _context: AsyncSocketContext<ServerEmitEvents>,
+  context: AsyncSocketContext<ServerEmitEvents>,
 ) -> Status<ToClientResponses> {
   match event {
-    FromClientRequests::NewGame {} => Status::Ok(ToClientResponses::NewGame {
-      game: GameStateProto::from_onoro(&Onoro16::default_start()),
-    }),
+    FromClientRequests::NewGame {} => {
+      let game = SessionGame::default_start();
+      Status::Ok(ToClientResponses::NewGame {
+        game: session_proto(&game),
+        session_id: GameSessions::global().create(game),
+      })
+    }
+    FromClientRequests::MakeMove {
+      session_id,
+      game_move,
+    } => {
+      let m: Move = match game_move.parse() {
+        Ok(m) => m,
+        Err(err) => {
+          return Status::Ok(ToClientResponses::IllegalMove {
+            reason: format!("{err}"),
+          });
+        }
+      };
+      Status::Ok(match GameSessions::global().make_move(session_id, m) {
+        Ok(game) => ToClientResponses::MakeMove {
+          game: session_proto(&game),
+        },
+        Err(MoveError::UnknownSession) => ToClientResponses::UnknownSession { session_id },
+        Err(MoveError::IllegalMove(reason)) => ToClientResponses::IllegalMove { reason },
+      })
+    }
+    FromClientRequests::LegalMoves { session_id } => {
+      Status::Ok(match GameSessions::global().legal_moves(session_id) {
+        Some(moves) => ToClientResponses::LegalMoves {
+          moves: moves.iter().map(|m| m.to_string()).collect(),
+        },
+        None => ToClientResponses::UnknownSession { session_id },
+      })
+    }
+    FromClientRequests::Threats { session_id } => {
+      Status::Ok(match GameSessions::global().threats(session_id) {
+        Some(threats) => ToClientResponses::Threats {
+          immediate_wins: threats
+            .immediate_wins
+            .iter()
+            .map(|m| m.to_string())
+            .collect(),
+          forced_blocks: threats
+            .forced_blocks
+            .iter()
+            .map(|m| m.to_string())
+            .collect(),
+        },
+        None => ToClientResponses::UnknownSession { session_id },
+      })
+    }
+    FromClientRequests::GameState { session_id } => {
+      Status::Ok(match GameSessions::global().game(session_id) {
+        Some(game) => ToClientResponses::GameState {
+          game: session_proto(&game),
+        },
+        None => ToClientResponses::UnknownSession { session_id },
+      })
+    }
+    FromClientRequests::AiMove {
+      session_id,
+      difficulty,
+    } => {
+      let Some(session) = GameSessions::global().game(session_id) else {
+        return Status::Ok(ToClientResponses::UnknownSession { session_id });
+      };
+      let connection = connection_id(&context);
+      let admission = match SolverGovernor::global().admit(Workload::Play, connection) {
+        Ok(admission) => admission,
+        Err(busy) => return Status::Ok(busy_response(Workload::Play, busy)),
+      };
+      let Some(m) = ai::choose_move(session.game, difficulty, admission).await else {
+        return Status::Ok(ToClientResponses::NoLegalMoves { session_id });
+      };
+      // The session may have changed while searching, so the move is checked
+      // again before being made.
+      Status::Ok(match GameSessions::global().make_move(session_id, m) {
+        Ok(game) => ToClientResponses::AiMove {
+          game_move: m.to_string(),
+          game: session_proto(&game),
+        },
+        Err(MoveError::UnknownSession) => ToClientResponses::UnknownSession { session_id },
+        Err(MoveError::IllegalMove(reason)) => ToClientResponses::IllegalMove { reason },
+      })
+    }
+    FromClientRequests::CreateGame { time_control } => Status::Ok(seat_and_subscribe(
+      GameManager::global().create_game(time_control),
+      context,
+    )),
+    FromClientRequests::JoinGame { game_id } => {
+      Status::Ok(match GameManager::global().join_game(game_id) {
+        Ok(seat) => seat_and_subscribe(seat, context),
+        Err(err) => game_error_response(game_id, err),
+      })
+    }
+    FromClientRequests::WatchGame { game_id } => {
+      Status::Ok(match GameManager::global().spectate(game_id) {
+        Ok(update) => {
+          subscribe(game_id, context);
+          ToClientResponses::WatchGame {
+            moves: update.moves.iter().map(|m| m.to_string()).collect(),
+            game: game_proto(&update),
+          }
+        }
+        Err(err) => game_error_response(game_id, err),
+      })
+    }
+    FromClientRequests::ResumeGame { resume_token } => {
+      let Some(seat) = ResumeTokens::global().resume(resume_token) else {
+        return Status::Ok(ToClientResponses::UnknownResumeToken {});
+      };
+      Status::Ok(
+        match GameManager::global().updates(seat.game_id, seat.player_token, 0) {
+          Ok(update) => {
+            subscribe(seat.game_id, context);
+            ToClientResponses::GameResumed {
+              game_id: seat.game_id,
+              player_token: seat.player_token,
+              color: seat.color.to_string(),
+              moves: update.moves.iter().map(|m| m.to_string()).collect(),
+              game: game_proto(&update),
+              opponent_joined: update.opponent_joined,
+            }
+          }
+          Err(err) => game_error_response(seat.game_id, err),
+        },
+      )
+    }
+    FromClientRequests::PlayMove {
+      game_id,
+      player_token,
+      game_move,
+    } => {
+      let m: Move = match game_move.parse() {
+        Ok(m) => m,
+        Err(err) => {
+          return Status::Ok(ToClientResponses::IllegalMove {
+            reason: format!("{err}"),
+          });
+        }
+      };
+      Status::Ok(
+        match GameManager::global().make_move(game_id, player_token, m) {
+          Ok(update) => {
+            let move_index = update.from_index;
+            broadcast(game_id, || ServerEmitEvents::MoveMade {
+              game_id,
+              game_move: m.to_string(),
+              move_index,
+              game: game_proto(&update),
+            });
+            ToClientResponses::PlayMove {
+              game: game_proto(&update),
+            }
+          }
+          Err(err) => game_error_response(game_id, err),
+        },
+      )
+    }
+    FromClientRequests::GameUpdates {
+      game_id,
+      player_token,
+      from_index,
+    } => Status::Ok(
+      match GameManager::global().updates(game_id, player_token, from_index) {
+        Ok(update) => ToClientResponses::GameUpdates {
+          moves: update.moves.iter().map(|m| m.to_string()).collect(),
+          move_times_ms: update
+            .move_times
+            .iter()
+            .map(|time| {
+              time
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |since_epoch| since_epoch.as_millis() as u64)
+            })
+            .collect(),
+          game: game_proto(&update),
+          opponent_joined: update.opponent_joined,
+        },
+        Err(err) => game_error_response(game_id, err),
+      },
+    ),
+    FromClientRequests::Analyze {
+      session_id,
+      game,
+      search_depth,
+    } => {
+      if !GameSessions::global().contains(session_id) {
+        return Status::Ok(ToClientResponses::UnknownSession { session_id });
+      }
+      let game = match game.to_onoro() {
+        Ok(game) => game,
+        Err(Error::ProtoDecode(reason)) => {
+          return Status::Ok(ToClientResponses::InvalidGameState { index: 0, reason });
+        }
+      };
+      let connection = connection_id(&context);
+      let admission = match SolverGovernor::global().admit(Workload::Analysis, connection) {
+        Ok(admission) => admission,
+        Err(busy) => return Status::Ok(busy_response(Workload::Analysis, busy)),
+      };
+      Status::Ok(ToClientResponses::Analyze {
+        moves: AnalysisJobs::global()
+          .analyze_moves(game, search_depth, admission)
+          .await,
+      })
+    }
+    FromClientRequests::SubmitAnalysisJob {
+      session_id,
+      games,
+      search_depth,
+    } => {
+      if !GameSessions::global().contains(session_id) {
+        return Status::Ok(ToClientResponses::UnknownSession { session_id });
+      }
+      let mut onoros = Vec::with_capacity(games.len());
+      for (index, game) in games.iter().enumerate() {
+        match game.to_onoro() {
+          Ok(onoro) => onoros.push(onoro),
+          Err(Error::ProtoDecode(reason)) => {
+            return Status::Ok(ToClientResponses::InvalidGameState {
+              index: index as u32,
+              reason,
+            });
+          }
+        }
+      }
+      let connection = connection_id(&context);
+      let admission = match SolverGovernor::global().admit(Workload::Analysis, connection) {
+        Ok(admission) => admission,
+        Err(busy) => return Status::Ok(busy_response(Workload::Analysis, busy)),
+      };
+      Status::Ok(ToClientResponses::AnalysisJobSubmitted {
+        job_id: AnalysisJobs::global().submit(onoros, search_depth, admission),
+      })
+    }
+    FromClientRequests::AnalysisJobStatus { job_id, from_index } => {
+      Status::Ok(match AnalysisJobs::global().status(job_id, from_index) {
+        Some(status) => ToClientResponses::AnalysisJobStatus { status },
+        None => ToClientResponses::UnknownAnalysisJob { job_id },
+      })
+    }
+    FromClientRequests::CancelAnalysisJob { job_id } => {
+      Status::Ok(if AnalysisJobs::global().cancel(job_id) {
+        ToClientResponses::AnalysisJobCancelled { job_id }
+      } else {
+        ToClientResponses::UnknownAnalysisJob { job_id }
+      })
+    }
+    FromClientRequests::SolverLoad {} => {
+      let governor = SolverGovernor::global();
+      Status::Ok(ToClientResponses::SolverLoad {
+        play_queued: governor.queued(Workload::Play) as u32,
+        analysis_queued: governor.queued(Workload::Analysis) as u32,
+      })
+    }
   }
 }
 
@@ -53,11 +648,12 @@ async fn handle_emit_event(
 }
 
 pub fn create_socket_endpoint() -> JoinHandle<()> {
+  tokio::spawn(watch_clocks());
   tokio::spawn(async {
     AsyncSocket::new(
       AsyncSocketOptions::new()
-        .with_path("onoro")
-        .with_port(2345)
+        .with_path(SOCKET_PATH)
+        .with_port(Config::global().socket_port)
         .with_timeout(Duration::from_secs(10))
         .with_verbose(false),
       handle_connect_event,
@@ -68,3 +664,83 @@ pub fn create_socket_endpoint() -> JoinHandle<()> {
     .await
   })
 }
+
+#[cfg(test)]
+mod tests {
+  use std::cell::{Cell, RefCell};
+
+  use super::{ServerEmitEvents, Subscriber, Subscribers};
+
+  /// A connection that records the events emitted to it while it is open.
+  #[derive(Default)]
+  struct FakeConnection {
+    open: Cell<bool>,
+    events: RefCell<Vec<u64>>,
+  }
+
+  impl Subscriber for &FakeConnection {
+    fn send(&self, event: ServerEmitEvents) -> bool {
+      if !self.open.get() {
+        return false;
+      }
+      let ServerEmitEvents::LostOnTime { game_id, .. } = event else {
+        unreachable!();
+      };
+      self.events.borrow_mut().push(game_id);
+      true
+    }
+  }
+
+  fn lost_on_time(game_id: u64) -> impl Fn() -> ServerEmitEvents {
+    move || ServerEmitEvents::LostOnTime {
+      game_id,
+      color: "black".to_owned(),
+    }
+  }
+
+  #[test]
+  fn test_broadcast() {
+    let connections: [FakeConnection; 3] = Default::default();
+    for connection in &connections {
+      connection.open.set(true);
+    }
+    let mut subscribers = Subscribers::new();
+    subscribers.subscribe(1, &connections[0]);
+    subscribers.subscribe(1, &connections[1]);
+    subscribers.subscribe(2, &connections[2]);
+
+    subscribers.broadcast(1, lost_on_time(1));
+    subscribers.broadcast(2, lost_on_time(2));
+    subscribers.broadcast(3, lost_on_time(3));
+    assert_eq!(*connections[0].events.borrow(), vec![1]);
+    assert_eq!(*connections[1].events.borrow(), vec![1]);
+    assert_eq!(*connections[2].events.borrow(), vec![2]);
+  }
+
+  #[test]
+  fn test_closed_connections_dropped() {
+    let connections: [FakeConnection; 2] = Default::default();
+    connections[0].open.set(true);
+    let mut subscribers = Subscribers::new();
+    subscribers.subscribe(1, &connections[0]);
+    subscribers.subscribe(1, &connections[1]);
+    subscribers.subscribe(2, &connections[1]);
+
+    // The closed connection is dropped by the first event it misses, even if
+    // it reopens.
+    subscribers.broadcast(1, lost_on_time(1));
+    assert_eq!(subscribers.games[&1].len(), 1);
+    connections[1].open.set(true);
+    subscribers.broadcast(1, lost_on_time(1));
+    assert_eq!(*connections[0].events.borrow(), vec![1, 1]);
+    assert!(connections[1].events.borrow().is_empty());
+
+    // Games are dropped along with their last subscriber.
+    connections[1].open.set(false);
+    subscribers.broadcast(2, lost_on_time(2));
+    assert!(!subscribers.games.contains_key(&2));
+
+    subscribers.retain_games(|game_id| game_id != 1);
+    assert!(subscribers.games.is_empty());
+  }
+}
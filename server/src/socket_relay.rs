@@ -0,0 +1,133 @@
+use std::convert::Infallible;
+
+use futures_util::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+  tungstenite::{self, client::IntoClientRequest, http::HeaderValue},
+  MaybeTlsStream, WebSocketStream,
+};
+use warp::{
+  http::StatusCode,
+  ws::{Message, WebSocket, Ws},
+  Filter, Rejection, Reply,
+};
+
+use crate::{admin::ServerMetrics, config::Config};
+
+/// The path the game socket is served on, both by the socket endpoint and by
+/// the relay.
+pub const SOCKET_PATH: &str = "onoro";
+
+const PROTOCOL_HEADER: &str = "sec-websocket-protocol";
+
+type Upstream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Connects to the game socket endpoint on this machine, offering it the
+/// subprotocols the client offered. Returns the connection and the
+/// subprotocol the endpoint chose, if any.
+async fn connect_upstream(
+  protocols: Option<&str>,
+) -> tungstenite::Result<(Upstream, Option<String>)> {
+  let mut request = format!(
+    "ws://localhost:{}/{SOCKET_PATH}",
+    Config::global().socket_port
+  )
+  .into_client_request()?;
+  if let Some(protocols) = protocols.and_then(|protocols| HeaderValue::from_str(protocols).ok()) {
+    request.headers_mut().insert(PROTOCOL_HEADER, protocols);
+  }
+  let (upstream, response) = tokio_tungstenite::connect_async(request).await?;
+  let protocol = response
+    .headers()
+    .get(PROTOCOL_HEADER)
+    .and_then(|protocol| protocol.to_str().ok())
+    .map(str::to_owned);
+  Ok((upstream, protocol))
+}
+
+fn to_upstream_message(message: Message) -> Option<tungstenite::Message> {
+  if message.is_text() {
+    message
+      .to_str()
+      .ok()
+      .map(|text| tungstenite::Message::Text(text.to_owned()))
+  } else if message.is_binary() {
+    Some(tungstenite::Message::Binary(message.into_bytes()))
+  } else {
+    None
+  }
+}
+
+fn to_client_message(message: tungstenite::Message) -> Option<Message> {
+  match message {
+    tungstenite::Message::Text(text) => Some(Message::text(text)),
+    tungstenite::Message::Binary(bytes) => Some(Message::binary(bytes)),
+    _ => None,
+  }
+}
+
+/// Forwards the text and binary messages of each side to the other, until
+/// either side closes its connection. Pings and pongs are answered by each
+/// connection on its own, and closing one side closes the other.
+///
+/// Each relayed connection is counted by `ServerMetrics` while it's open.
+/// Clients connecting to the game socket port directly aren't counted.
+async fn relay(client: WebSocket, upstream: Upstream) {
+  let _connected = ServerMetrics::global().connect_socket();
+  let (mut client_tx, mut client_rx) = client.split();
+  let (mut upstream_tx, mut upstream_rx) = upstream.split();
+
+  let to_upstream = async {
+    while let Some(Ok(message)) = client_rx.next().await {
+      let Some(message) = to_upstream_message(message) else {
+        continue;
+      };
+      if upstream_tx.send(message).await.is_err() {
+        break;
+      }
+    }
+    let _ = upstream_tx.close().await;
+  };
+  let to_client = async {
+    while let Some(Ok(message)) = upstream_rx.next().await {
+      let Some(message) = to_client_message(message) else {
+        continue;
+      };
+      if client_tx.send(message).await.is_err() {
+        break;
+      }
+    }
+    let _ = client_tx.close().await;
+  };
+  tokio::join!(to_upstream, to_client);
+}
+
+async fn handle_socket_upgrade(
+  ws: Ws,
+  protocols: Option<String>,
+) -> Result<Box<dyn Reply>, Infallible> {
+  let (upstream, protocol) = match connect_upstream(protocols.as_deref()).await {
+    Ok(connection) => connection,
+    Err(err) => {
+      println!("Error connecting to the game socket: {err}");
+      return Ok(Box::new(StatusCode::BAD_GATEWAY));
+    }
+  };
+  let reply = ws.on_upgrade(move |client| relay(client, upstream));
+  Ok(match protocol {
+    Some(protocol) => Box::new(warp::reply::with_header(reply, PROTOCOL_HEADER, protocol)),
+    None => Box::new(reply),
+  })
+}
+
+/// `GET /onoro`, which upgrades to a WebSocket relayed to the game socket
+/// endpoint. This serves the game socket on the same port, under the same
+/// path prefix and behind the same TLS as the static file server, since the
+/// socket library can only listen on a port of its own, without TLS.
+pub fn socket_relay_route() -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+  warp::path(SOCKET_PATH)
+    .and(warp::path::end())
+    .and(warp::ws())
+    .and(warp::header::optional::<String>(PROTOCOL_HEADER))
+    .and_then(handle_socket_upgrade)
+}
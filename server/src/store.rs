@@ -0,0 +1,382 @@
+use std::{fmt::Display, path::Path, sync::Mutex};
+
+use onoro::Move;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::game_manager::{GameId, PlayerToken};
+
+/// Why a `GameStore` operation failed.
+#[derive(Debug)]
+pub enum StoreError {
+  Backend(String),
+  /// A stored game couldn't be decoded.
+  Corrupt(String),
+}
+
+impl Display for StoreError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      StoreError::Backend(reason) => write!(f, "Game store error: {reason}"),
+      StoreError::Corrupt(reason) => write!(f, "Corrupt stored game: {reason}"),
+    }
+  }
+}
+
+impl From<rusqlite::Error> for StoreError {
+  fn from(err: rusqlite::Error) -> Self {
+    StoreError::Backend(err.to_string())
+  }
+}
+
+pub type StoreResult<T> = Result<T, StoreError>;
+
+/// The clocks of a stored game, as of its last move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StoredClock {
+  pub black_remaining_ms: u64,
+  pub white_remaining_ms: u64,
+  pub increment_ms: u64,
+}
+
+/// Everything needed to resume hosting a game.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredGame {
+  pub game_id: GameId,
+  /// The compressed game state (see `Compress`).
+  pub board: Vec<u8>,
+  /// The moves made to reach `board` from the default start.
+  pub moves: Vec<Move>,
+  /// When each of `moves` was made, in milliseconds since the Unix epoch.
+  pub move_times_ms: Vec<u64>,
+  pub black_player: PlayerToken,
+  pub white_player: Option<PlayerToken>,
+  pub clock: Option<StoredClock>,
+}
+
+/// Persistent storage for hosted games, so that they survive restarts of the
+/// server.
+pub trait GameStore: Send + Sync {
+  /// Stores a new game. Fails if a game with the same ID is already stored.
+  fn create(&self, game: &StoredGame) -> StoreResult<()>;
+
+  /// The game with ID `game_id`, or `None` if it isn't stored.
+  fn load(&self, game_id: GameId) -> StoreResult<Option<StoredGame>>;
+
+  /// Replaces the stored game with the same ID as `game`.
+  fn update(&self, game: &StoredGame) -> StoreResult<()>;
+
+  /// The IDs of every stored game.
+  fn list(&self) -> StoreResult<Vec<GameId>>;
+
+  /// Drops game `game_id`, if it is stored.
+  fn delete(&self, game_id: GameId) -> StoreResult<()>;
+}
+
+/// A `GameStore` backed by an SQLite database, with one row per game. Boards
+/// are stored compressed, move histories as `Move`'s notation, one move per
+/// line, move times as a comma-separated list, and player tokens as their 16
+/// bytes.
+pub struct SqliteGameStore {
+  connection: Mutex<Connection>,
+}
+
+impl SqliteGameStore {
+  /// Opens the database at `path`, creating it if it doesn't exist.
+  pub fn open(path: impl AsRef<Path>) -> StoreResult<Self> {
+    Self::from_connection(Connection::open(path)?)
+  }
+
+  /// A store which only lasts as long as it is open.
+  pub fn in_memory() -> StoreResult<Self> {
+    Self::from_connection(Connection::open_in_memory()?)
+  }
+
+  fn from_connection(connection: Connection) -> StoreResult<Self> {
+    connection.execute(
+      "CREATE TABLE IF NOT EXISTS games (
+        game_id INTEGER PRIMARY KEY,
+        board BLOB NOT NULL,
+        moves TEXT NOT NULL,
+        move_times TEXT NOT NULL,
+        black_player BLOB NOT NULL,
+        white_player BLOB,
+        black_remaining_ms INTEGER,
+        white_remaining_ms INTEGER,
+        increment_ms INTEGER
+      )",
+      (),
+    )?;
+    Ok(Self {
+      connection: Mutex::new(connection),
+    })
+  }
+
+  fn encode_moves(moves: &[Move]) -> String {
+    moves
+      .iter()
+      .map(|m| m.to_string())
+      .collect::<Vec<_>>()
+      .join("\n")
+  }
+
+  fn decode_moves(moves: &str) -> StoreResult<Vec<Move>> {
+    moves
+      .lines()
+      .map(|m| {
+        m.parse()
+          .map_err(|err| StoreError::Corrupt(format!("Invalid move \"{m}\": {err}")))
+      })
+      .collect()
+  }
+
+  fn encode_move_times(move_times_ms: &[u64]) -> String {
+    move_times_ms
+      .iter()
+      .map(|time| time.to_string())
+      .collect::<Vec<_>>()
+      .join(",")
+  }
+
+  fn decode_move_times(move_times: &str) -> StoreResult<Vec<u64>> {
+    move_times
+      .split(',')
+      .filter(|time| !time.is_empty())
+      .map(|time| {
+        time
+          .parse()
+          .map_err(|err| StoreError::Corrupt(format!("Invalid move time \"{time}\": {err}")))
+      })
+      .collect()
+  }
+
+  fn decode_player_token(token: Vec<u8>) -> StoreResult<PlayerToken> {
+    let bytes = token.try_into().map_err(|token: Vec<u8>| {
+      StoreError::Corrupt(format!("Invalid {}-byte player token", token.len()))
+    })?;
+    Ok(PlayerToken::from_bytes(bytes))
+  }
+
+  /// The clock columns of `game`.
+  fn clock_columns(game: &StoredGame) -> [Option<i64>; 3] {
+    match game.clock {
+      Some(clock) => [
+        Some(clock.black_remaining_ms as i64),
+        Some(clock.white_remaining_ms as i64),
+        Some(clock.increment_ms as i64),
+      ],
+      None => [None; 3],
+    }
+  }
+}
+
+impl GameStore for SqliteGameStore {
+  fn create(&self, game: &StoredGame) -> StoreResult<()> {
+    let [black_remaining_ms, white_remaining_ms, increment_ms] = Self::clock_columns(game);
+    self.connection.lock().unwrap().execute(
+      "INSERT INTO games VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+      params![
+        game.game_id as i64,
+        game.board,
+        Self::encode_moves(&game.moves),
+        Self::encode_move_times(&game.move_times_ms),
+        game.black_player.to_bytes(),
+        game.white_player.map(PlayerToken::to_bytes),
+        black_remaining_ms,
+        white_remaining_ms,
+        increment_ms,
+      ],
+    )?;
+    Ok(())
+  }
+
+  fn load(&self, game_id: GameId) -> StoreResult<Option<StoredGame>> {
+    let row = self
+      .connection
+      .lock()
+      .unwrap()
+      .query_row(
+        "SELECT board, moves, move_times, black_player, white_player, black_remaining_ms,
+          white_remaining_ms, increment_ms FROM games WHERE game_id = ?1",
+        params![game_id as i64],
+        |row| {
+          Ok((
+            row.get::<_, Vec<u8>>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Vec<u8>>(3)?,
+            row.get::<_, Option<Vec<u8>>>(4)?,
+            [
+              row.get::<_, Option<i64>>(5)?,
+              row.get::<_, Option<i64>>(6)?,
+              row.get::<_, Option<i64>>(7)?,
+            ],
+          ))
+        },
+      )
+      .optional()?;
+    let Some((board, moves, move_times, black_player, white_player, clock)) = row else {
+      return Ok(None);
+    };
+
+    Ok(Some(StoredGame {
+      game_id,
+      board,
+      moves: Self::decode_moves(&moves)?,
+      move_times_ms: Self::decode_move_times(&move_times)?,
+      black_player: Self::decode_player_token(black_player)?,
+      white_player: white_player.map(Self::decode_player_token).transpose()?,
+      clock: match clock {
+        [Some(black_remaining_ms), Some(white_remaining_ms), Some(increment_ms)] => {
+          Some(StoredClock {
+            black_remaining_ms: black_remaining_ms as u64,
+            white_remaining_ms: white_remaining_ms as u64,
+            increment_ms: increment_ms as u64,
+          })
+        }
+        _ => None,
+      },
+    }))
+  }
+
+  fn update(&self, game: &StoredGame) -> StoreResult<()> {
+    let [black_remaining_ms, white_remaining_ms, increment_ms] = Self::clock_columns(game);
+    self.connection.lock().unwrap().execute(
+      "UPDATE games SET board = ?2, moves = ?3, move_times = ?4, black_player = ?5,
+        white_player = ?6, black_remaining_ms = ?7, white_remaining_ms = ?8,
+        increment_ms = ?9 WHERE game_id = ?1",
+      params![
+        game.game_id as i64,
+        game.board,
+        Self::encode_moves(&game.moves),
+        Self::encode_move_times(&game.move_times_ms),
+        game.black_player.to_bytes(),
+        game.white_player.map(PlayerToken::to_bytes),
+        black_remaining_ms,
+        white_remaining_ms,
+        increment_ms,
+      ],
+    )?;
+    Ok(())
+  }
+
+  fn list(&self) -> StoreResult<Vec<GameId>> {
+    let connection = self.connection.lock().unwrap();
+    let mut statement = connection.prepare("SELECT game_id FROM games ORDER BY game_id")?;
+    let game_ids = statement
+      .query_map((), |row| row.get::<_, i64>(0))?
+      .map(|game_id| Ok(game_id? as GameId))
+      .collect();
+    game_ids
+  }
+
+  fn delete(&self, game_id: GameId) -> StoreResult<()> {
+    self.connection.lock().unwrap().execute(
+      "DELETE FROM games WHERE game_id = ?1",
+      params![game_id as i64],
+    )?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use abstract_game::Compress;
+  use onoro::Onoro16;
+  use rusqlite::{params, ToSql};
+
+  use crate::game_manager::PlayerToken;
+
+  use super::{GameStore, SqliteGameStore, StoreError, StoredClock, StoredGame};
+
+  fn compressed(onoro: &Onoro16) -> Vec<u8> {
+    let mut board = vec![0; Onoro16::COMPRESSED_SIZE];
+    onoro.compress(&mut board);
+    board
+  }
+
+  fn stored_game(game_id: u64) -> StoredGame {
+    StoredGame {
+      game_id,
+      board: compressed(&Onoro16::default_start()),
+      moves: Vec::new(),
+      move_times_ms: Vec::new(),
+      black_player: PlayerToken::random(),
+      white_player: None,
+      clock: None,
+    }
+  }
+
+  #[test]
+  fn test_create_load() {
+    let store = SqliteGameStore::in_memory().unwrap();
+    let game = stored_game(3);
+    store.create(&game).unwrap();
+    assert_eq!(store.load(3).unwrap(), Some(game.clone()));
+    assert_eq!(store.load(4).unwrap(), None);
+
+    // Each game can only be created once.
+    assert!(matches!(store.create(&game), Err(StoreError::Backend(_))));
+  }
+
+  #[test]
+  fn test_update() {
+    let store = SqliteGameStore::in_memory().unwrap();
+    let mut game = stored_game(1);
+    store.create(&game).unwrap();
+
+    let mut onoro = Onoro16::default_start();
+    let m = onoro.each_move().next().unwrap();
+    onoro.make_move(m);
+    game.board = compressed(&onoro);
+    game.moves = vec![m];
+    game.move_times_ms = vec![1_700_000_000_000];
+    game.white_player = Some(PlayerToken::random());
+    game.clock = Some(StoredClock {
+      black_remaining_ms: 60_000,
+      white_remaining_ms: 55_000,
+      increment_ms: 2_000,
+    });
+    store.update(&game).unwrap();
+    assert_eq!(store.load(1).unwrap(), Some(game));
+  }
+
+  #[test]
+  fn test_list_delete() {
+    let store = SqliteGameStore::in_memory().unwrap();
+    for game_id in [5, 2, 9] {
+      store.create(&stored_game(game_id)).unwrap();
+    }
+    assert_eq!(store.list().unwrap(), vec![2, 5, 9]);
+
+    store.delete(5).unwrap();
+    // Deleting a game that isn't stored does nothing.
+    store.delete(6).unwrap();
+    assert_eq!(store.list().unwrap(), vec![2, 9]);
+    assert_eq!(store.load(5).unwrap(), None);
+  }
+
+  /// Stores a game with `value` in `column` instead of what was stored, and
+  /// checks that it can't be loaded.
+  fn assert_corrupt(column: &str, value: &dyn ToSql) {
+    let store = SqliteGameStore::in_memory().unwrap();
+    store.create(&stored_game(1)).unwrap();
+    store
+      .connection
+      .lock()
+      .unwrap()
+      .execute(
+        &format!("UPDATE games SET {column} = ?1 WHERE game_id = 1"),
+        params![value],
+      )
+      .unwrap();
+    assert!(matches!(store.load(1), Err(StoreError::Corrupt(_))));
+  }
+
+  #[test]
+  fn test_corrupt() {
+    assert_corrupt("moves", &"not a move");
+    assert_corrupt("move_times", &"12,soon");
+    assert_corrupt("black_player", &vec![0u8; 8]);
+    assert_corrupt("white_player", &vec![0u8; 17]);
+  }
+}
@@ -0,0 +1,125 @@
+use std::{
+  fs::File,
+  io::{BufRead, BufReader},
+};
+
+use abstract_game::{BoardReader, BoardWriter, Compress, Score};
+use onoro::Onoro16;
+
+const USAGE: &str = "\
+Usage: boards decode <board file>
+       boards encode <text file> <board file>
+       boards show <board file>
+
+Converts between board files, which hold streams of compressed Onoro
+positions and optional scores, and text with one position per line. Each
+line is the hex of the compressed position, optionally followed by a space
+and the hex of its score. `decode` writes this text to stdout, and `encode`
+reads it back into a board file, which has scores if the first line does.
+`show` prints every position in a board file as a board, with its score.";
+
+fn hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>, String> {
+  if !hex.is_ascii() || hex.len() & 1 != 0 {
+    return Err(format!("Invalid hex \"{hex}\""));
+  }
+  (0..hex.len())
+    .step_by(2)
+    .map(|idx| {
+      u8::from_str_radix(&hex[idx..idx + 2], 16)
+        .map_err(|err| format!("Invalid hex \"{hex}\": {err}"))
+    })
+    .collect()
+}
+
+fn open(path: &str) -> Result<BoardReader<BufReader<File>, Onoro16>, String> {
+  BoardReader::open(path).map_err(|err| format!("Failed to open {path}: {err}"))
+}
+
+fn decode(path: &str) -> Result<(), String> {
+  let mut bytes = vec![0; Onoro16::COMPRESSED_SIZE];
+  for board in open(path)? {
+    let (onoro, score) = board.map_err(|err| err.to_string())?;
+    onoro.compress(&mut bytes);
+    match score {
+      Some(score) => println!("{} {}", hex(&bytes), hex(&score.to_bytes())),
+      None => println!("{}", hex(&bytes)),
+    }
+  }
+  Ok(())
+}
+
+fn encode(text_path: &str, path: &str) -> Result<(), String> {
+  let text = File::open(text_path).map_err(|err| format!("Failed to open {text_path}: {err}"))?;
+  let mut writer = None;
+  for (line_idx, line) in BufReader::new(text).lines().enumerate() {
+    let line = line.map_err(|err| err.to_string())?;
+    let mut fields = line.split_whitespace();
+    let Some(onoro) = fields.next() else {
+      continue;
+    };
+    let onoro = Onoro16::decompress(&from_hex(onoro)?)
+      .ok_or_else(|| format!("Line {} is not a valid position", line_idx + 1))?;
+    let score = fields
+      .next()
+      .map(|score| -> Result<Score, String> {
+        let bytes = from_hex(score)?;
+        let bytes = bytes
+          .try_into()
+          .map_err(|_| format!("Line {} has an invalid score", line_idx + 1))?;
+        Ok(Score::from_bytes(bytes))
+      })
+      .transpose()?;
+
+    let writer = match &mut writer {
+      Some(writer) => writer,
+      None => writer.insert(
+        BoardWriter::create(path, score.is_some())
+          .map_err(|err| format!("Failed to create {path}: {err}"))?,
+      ),
+    };
+    writer
+      .write(&onoro, score.as_ref())
+      .map_err(|err| format!("Line {}: {err}", line_idx + 1))?;
+  }
+
+  let writer = match writer {
+    Some(writer) => writer,
+    None => {
+      BoardWriter::create(path, false).map_err(|err| format!("Failed to create {path}: {err}"))?
+    }
+  };
+  writer.into_inner().map_err(|err| err.to_string())?;
+  Ok(())
+}
+
+fn show(path: &str) -> Result<(), String> {
+  for board in open(path)? {
+    let (onoro, score) = board.map_err(|err| err.to_string())?;
+    println!("{onoro}");
+    if let Some(score) = score {
+      println!("score: {score}");
+    }
+    println!();
+  }
+  Ok(())
+}
+
+fn main() {
+  let args: Vec<_> = std::env::args().skip(1).collect();
+  let args: Vec<_> = args.iter().map(String::as_str).collect();
+  let result = match args[..] {
+    ["decode", path] => decode(path),
+    ["encode", text_path, path] => encode(text_path, path),
+    ["show", path] => show(path),
+    _ => Err(USAGE.to_string()),
+  };
+
+  if let Err(err) = result {
+    eprintln!("{err}");
+    std::process::exit(1);
+  }
+}
@@ -0,0 +1,338 @@
+use std::io::{BufRead, Write};
+
+use cooperate::{Engine, Options};
+use onoro::{
+  parse_square, square_notation, BoardSize, Color, ColorAttrs, Colored, DynOnoro, Move, Onoro,
+  Onoro16, Onoro8, OnoroView, PackedIdx, PawnColor, Undo,
+};
+
+const USAGE: &str = "\
+Usage: cli-play [--color <b|w>] [--depth <D>] [--threads <T>] [--pawns <4|8>]
+
+Plays a game of Onoro against the solver in the terminal. You play --color
+(default w, which moves first), and the solver searches --depth moves ahead
+(default 6) with --threads threads (default 4). Each player has --pawns pawns
+(default 8).";
+
+const HELP: &str = "\
+Commands:
+  <move>    make a move, e.g. \"w@h9\" to place a pawn on h9, or \"h9-i10\"
+            to move the pawn on h9 to i10
+  <tile>    once all pawns are placed, highlight where the pawn on <tile> can
+            move to
+  hint      ask the solver for the best move
+  undo      take back your last move and the solver's reply
+  help      show this message
+  quit      leave the game
+
+Tiles are named by a column letter and a row number. Rows are labeled on the
+left, and columns under the bottom row, running up and to the left.";
+
+/// The solver, for a game of any board size.
+trait Solver {
+  fn make_move(&mut self, m: Move);
+
+  /// Searches the current position, returning its score and the best move, if
+  /// there is one.
+  fn solve(&mut self) -> (String, Option<Move>);
+
+  fn reroot(&mut self, game: &dyn DynOnoro);
+}
+
+impl<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize> Solver
+  for Engine<OnoroView<N, N2, ADJ_CNT_SIZE>>
+{
+  fn make_move(&mut self, m: Move) {
+    Engine::make_move(self, m);
+  }
+
+  fn solve(&mut self) -> (String, Option<Move>) {
+    let score = Engine::solve(self);
+    (score.to_string(), self.best_move())
+  }
+
+  fn reroot(&mut self, game: &dyn DynOnoro) {
+    let game = game
+      .downcast_ref::<Onoro<N, N2, ADJ_CNT_SIZE>>()
+      .expect("The solver and game have different board sizes");
+    Engine::reroot(self, OnoroView::new(game.clone()));
+  }
+}
+
+struct CliPlay {
+  game: Box<dyn DynOnoro>,
+  solver: Box<dyn Solver>,
+  human: PawnColor,
+  /// Every move made so far, by the color that made it, for taking moves back.
+  history: Vec<(PawnColor, Undo)>,
+  /// The tile of the pawn whose moves are highlighted, if one was selected.
+  selected: Option<PackedIdx>,
+}
+
+impl CliPlay {
+  fn new(board_size: BoardSize, human: PawnColor, options: Options) -> Self {
+    match board_size {
+      BoardSize::Onoro8 => Self::with_game(Onoro8::default_start(), human, options),
+      BoardSize::Onoro16 => Self::with_game(Onoro16::default_start(), human, options),
+    }
+  }
+
+  fn with_game<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+    game: Onoro<N, N2, ADJ_CNT_SIZE>,
+    human: PawnColor,
+    options: Options,
+  ) -> Self {
+    Self {
+      solver: Box::new(Engine::new(OnoroView::new(game.clone()), options)),
+      game: Box::new(game),
+      human,
+      history: Vec::new(),
+      selected: None,
+    }
+  }
+
+  /// The tiles the current player can move to, or only those the selected
+  /// pawn can move to if there is one.
+  fn destinations(&self) -> Vec<PackedIdx> {
+    let selected = self
+      .selected
+      .map(|tile| format!("{}-", square_notation(tile)));
+    self
+      .game
+      .each_move()
+      .filter(|m| {
+        selected
+          .as_ref()
+          .is_none_or(|prefix| self.game.move_notation(*m).starts_with(prefix))
+      })
+      .map(|m| match m {
+        Move::Phase1Move { to } | Move::Phase2Move { to, .. } => to,
+      })
+      .collect()
+  }
+
+  /// Draws the board, with legal destinations of the human player
+  /// highlighted.
+  fn render(&self) -> String {
+    let pawns: Vec<_> = self.game.pawns().collect();
+    let destinations = if self.game.player_color() == self.human {
+      self.destinations()
+    } else {
+      Vec::new()
+    };
+
+    let tiles = pawns
+      .iter()
+      .map(|pawn| pawn.pos)
+      .chain(destinations.iter().copied());
+    let (min_x, min_y, max_x, max_y) = tiles.fold(
+      (u32::MAX, u32::MAX, 0, 0),
+      |(min_x, min_y, max_x, max_y), pos| {
+        (
+          min_x.min(pos.x()),
+          min_y.min(pos.y()),
+          max_x.max(pos.x()),
+          max_y.max(pos.y()),
+        )
+      },
+    );
+    let min_x = min_x.saturating_sub(1);
+    let min_y = min_y.saturating_sub(1);
+    let max_x = (max_x + 1).min(self.game.board_width() as u32 - 1);
+    let max_y = (max_y + 1).min(self.game.board_width() as u32 - 1);
+
+    let mut board = String::new();
+    for y in (min_y..=max_y).rev() {
+      board += &format!("{:>2} {: <width$}", y + 1, "", width = (max_y - y) as usize);
+      for x in min_x..=max_x {
+        let pos = PackedIdx::new(x, y);
+        let bold = ColorAttrs {
+          light: self.selected == Some(pos),
+          bold: true,
+        };
+        let tile = match pawns.iter().find(|pawn| pawn.pos == pos) {
+          Some(pawn) if pawn.color == PawnColor::Black => {
+            Colored::with_attrs("B", Color::Blue, bold)
+          }
+          Some(_) => Colored::with_attrs("W", Color::Yellow, bold),
+          None if destinations.contains(&pos) => Colored::with_attrs(
+            "*",
+            Color::Green,
+            ColorAttrs {
+              light: true,
+              bold: false,
+            },
+          ),
+          None => Colored::new(".", Color::Default),
+        };
+        board += &format!("{tile} ");
+      }
+      board += "\n";
+    }
+
+    // Column labels line up with the bottom row.
+    board += &format!("   {: <width$}", "", width = (max_y - min_y) as usize);
+    for x in min_x..=max_x {
+      board += &format!("{} ", (b'a' + x as u8) as char);
+    }
+    board
+  }
+
+  fn make_move(&mut self, m: Move) {
+    let color = self.game.player_color();
+    self.history.push((color, self.game.make_move_with_undo(m)));
+    self.solver.make_move(m);
+    self.selected = None;
+  }
+
+  /// Searches the current position, returning the best move in notation
+  /// along with its score.
+  fn search(&mut self) -> Option<(Move, String)> {
+    let (score, m) = self.solver.solve();
+    let m = m?;
+    Some((m, format!("{} (score {score})", self.game.move_notation(m))))
+  }
+
+  fn undo(&mut self) -> Result<(), String> {
+    let last_human_move = self
+      .history
+      .iter()
+      .rposition(|&(color, _)| color == self.human)
+      .ok_or("No moves of yours to undo")?;
+    for (_, undo) in self.history.drain(last_human_move..).rev() {
+      self.game.undo_move(undo);
+    }
+    self.solver.reroot(self.game.as_ref());
+    self.selected = None;
+    Ok(())
+  }
+
+  /// Handles one line of input from the human player, returning false if
+  /// they quit.
+  fn handle(&mut self, line: &str) -> Result<bool, String> {
+    match line {
+      "" => {}
+      "quit" => return Ok(false),
+      "help" => println!("{HELP}"),
+      "undo" => self.undo()?,
+      "hint" => match self.search() {
+        Some((_, hint)) => println!("hint: {hint}"),
+        None => println!("hint: you have no legal moves"),
+      },
+      _ if self.game.winner().is_some() || self.game.player_color() != self.human => {
+        return Err("The game is over, you can only undo or quit".into())
+      }
+      _ => {
+        if !self.game.in_phase1() {
+          if let Ok(tile) = parse_square(line) {
+            self.selected = Some(tile);
+            if self.destinations().is_empty() {
+              self.selected = None;
+              return Err(format!("You have no pawn on {line} that can move"));
+            }
+            return Ok(true);
+          }
+        }
+
+        let m = self.game.parse_move(line).map_err(|err| err.to_string())?;
+        if let Some(violation) = self.game.explain_illegal(m).first() {
+          return Err(format!("Illegal move {line}: {violation}"));
+        }
+        self.make_move(m);
+      }
+    }
+    Ok(true)
+  }
+
+  fn run(&mut self) {
+    println!("{HELP}\n");
+    let mut lines = std::io::stdin().lock().lines();
+    loop {
+      println!("{}\n", self.render());
+
+      if let Some(winner) = self.game.winner() {
+        // A player with no legal moves loses.
+        let no_moves = self.game.finished().is_none();
+        match (winner == self.human, no_moves) {
+          (true, false) => println!("You win!"),
+          (true, true) => println!("The solver has no legal moves, you win!"),
+          (false, false) => println!("The solver wins."),
+          (false, true) => println!("You have no legal moves, the solver wins."),
+        }
+      } else if self.game.player_color() != self.human {
+        if let Some((m, description)) = self.search() {
+          println!("solver plays {description}");
+          self.make_move(m);
+          continue;
+        }
+      }
+
+      print!("> ");
+      std::io::stdout().flush().unwrap();
+      let Some(Ok(line)) = lines.next() else {
+        return;
+      };
+      match self.handle(line.trim()) {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(err) => println!("{err}"),
+      }
+    }
+  }
+}
+
+fn main() {
+  let args: Vec<_> = std::env::args().collect();
+  let flag_value = |flag: &str| {
+    args
+      .iter()
+      .position(|arg| arg == flag)
+      .and_then(|idx| args.get(idx + 1))
+  };
+  let parse_flag = |flag: &str, default: u32| -> Result<u32, String> {
+    flag_value(flag).map_or(Ok(default), |value| {
+      value
+        .parse()
+        .map_err(|err| format!("Invalid value for {flag}: {err}"))
+    })
+  };
+  let settings = (|| -> Result<_, String> {
+    let human = match flag_value("--color").map(String::as_str) {
+      Some("b") => PawnColor::Black,
+      Some("w") | None => PawnColor::White,
+      Some(color) => return Err(format!("Unknown color \"{color}\"")),
+    };
+    let search_depth = parse_flag("--depth", 6)?;
+    let num_threads = parse_flag("--threads", 4)?;
+    if search_depth == 0 || num_threads == 0 {
+      return Err("Depth and threads must be positive".into());
+    }
+    let board_size = flag_value("--pawns")
+      .map_or(Ok(BoardSize::default()), |value| value.parse())
+      .map_err(|err| err.message().to_owned())?;
+    Ok((human, search_depth, num_threads, board_size))
+  })();
+
+  let (human, search_depth, num_threads, board_size) = match settings {
+    Ok(settings) if !args.iter().any(|arg| arg == "--help" || arg == "-h") => settings,
+    result => {
+      if let Err(err) = result {
+        eprintln!("{err}");
+      }
+      eprintln!("{USAGE}");
+      std::process::exit(1);
+    }
+  };
+
+  CliPlay::new(
+    board_size,
+    human,
+    Options {
+      num_threads,
+      search_depth,
+      unit_depth: search_depth / 2,
+      ..Default::default()
+    },
+  )
+  .run();
+}
@@ -0,0 +1,204 @@
+use std::{
+  fs::File,
+  io::{BufWriter, Write},
+};
+
+use abstract_game::{Repetitions, Score};
+use cooperate::{Engine, Options};
+use onoro::{Move, Onoro16, Onoro16View, PawnColor, TrainingExample};
+use rand::{
+  distributions::WeightedIndex, prelude::Distribution, rngs::StdRng, seq::IteratorRandom,
+  SeedableRng,
+};
+
+/// Games that go on for this many moves are scored as draws, even if no
+/// position has repeated.
+const MAX_GAME_MOVES: u32 = 200;
+
+const USAGE: &str = "\
+Usage: dataset --out <path> [--games <N>] [--depth <D>] [--threads <T>]
+               [--opening-moves <K>] [--seed <S>] [--augment]
+
+Plays N games (default 10) of self-play labeled by the solver, writing a
+training example for every position to --out. Each game starts with K random
+moves (default 2) from the default start, after which every legal move is
+scored by a depth D search (default 6) on T threads (default 4). The policy of
+each position is uniform over the moves with the best score, and the next
+move is drawn from it.
+
+Examples hold the board relative to the player to move, the policy and the
+final result of the game for the player to move, in the format documented in
+onoro's dataset module. With --augment, each position is written in all 12 of
+its rotations and reflections.";
+
+/// A position of a game, and the probability of each legal move in it.
+type LabeledPosition = (Onoro16, Vec<(Move, f32)>);
+
+/// The rank of `score` for the player it belongs to: faster wins above slower
+/// wins above ties above slower losses above faster losses.
+fn score_rank(score: &Score) -> i64 {
+  let turn_count_win = score.turn_count_win() as i64;
+  if turn_count_win == 0 {
+    0
+  } else if score.cur_player_wins() {
+    i64::MAX - turn_count_win
+  } else {
+    i64::MIN + turn_count_win
+  }
+}
+
+/// The policy over `move_scores`, uniform over the moves with the best score.
+fn policy(move_scores: &[(Move, Score)]) -> Vec<(Move, f32)> {
+  let best = move_scores
+    .iter()
+    .map(|(_, score)| score_rank(score))
+    .max()
+    .unwrap_or(0);
+  let n_best = move_scores
+    .iter()
+    .filter(|(_, score)| score_rank(score) == best)
+    .count();
+  move_scores
+    .iter()
+    .map(|(m, score)| {
+      let probability = if score_rank(score) == best {
+        1. / n_best as f32
+      } else {
+        0.
+      };
+      (*m, probability)
+    })
+    .collect()
+}
+
+/// Plays one game of self-play, returning each position after the opening
+/// with its policy, and the winner of the game, or `None` if it was drawn.
+fn play_game(
+  options: &Options,
+  opening_moves: u32,
+  rng: &mut StdRng,
+) -> (Vec<LabeledPosition>, Option<PawnColor>) {
+  let mut game = Onoro16::default_start();
+  for _ in 0..opening_moves {
+    if game.finished().is_some() {
+      break;
+    }
+    if let Some(m) = game.each_move().choose(rng) {
+      game.make_move(m);
+    }
+  }
+
+  let mut engine = Engine::new(Onoro16View::new(game.clone()), options.clone());
+  let mut positions = Vec::new();
+  // Symmetric positions count as repetitions of each other.
+  let mut repetitions = Repetitions::new();
+  repetitions.push(&Onoro16View::new(game.clone()));
+  loop {
+    if let Some(winner) = game.finished() {
+      return (positions, Some(winner));
+    }
+    if repetitions.is_draw() || positions.len() >= MAX_GAME_MOVES as usize {
+      return (positions, None);
+    }
+
+    engine.solve();
+    let policy = policy(&engine.move_scores().unwrap_or_default());
+    if policy.is_empty() {
+      // A player with no legal moves loses.
+      let winner = match game.player_color() {
+        PawnColor::Black => PawnColor::White,
+        PawnColor::White => PawnColor::Black,
+      };
+      return (positions, Some(winner));
+    }
+
+    let weights = WeightedIndex::new(policy.iter().map(|&(_, probability)| probability)).unwrap();
+    let m = policy[weights.sample(rng)].0;
+    positions.push((game.clone(), policy));
+
+    game.make_move(m);
+    engine.make_move(m);
+    repetitions.push(&Onoro16View::new(game.clone()));
+  }
+}
+
+fn run() -> Result<(), String> {
+  let args: Vec<_> = std::env::args().collect();
+  if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+    println!("{USAGE}");
+    return Ok(());
+  }
+  let flag_value = |flag: &str| {
+    args
+      .iter()
+      .position(|arg| arg == flag)
+      .and_then(|idx| args.get(idx + 1))
+  };
+  let parse_flag = |flag: &str, default: u64| -> Result<u64, String> {
+    flag_value(flag).map_or(Ok(default), |value| {
+      value
+        .parse()
+        .map_err(|err| format!("Invalid value for {flag}: {err}"))
+    })
+  };
+
+  let path = flag_value("--out").ok_or("Missing --out")?;
+  let games = parse_flag("--games", 10)? as u32;
+  let search_depth = parse_flag("--depth", 6)? as u32;
+  let num_threads = parse_flag("--threads", 4)? as u32;
+  let opening_moves = parse_flag("--opening-moves", 2)? as u32;
+  let seed = parse_flag("--seed", 0)?;
+  let augment = args.iter().any(|arg| arg == "--augment");
+  if search_depth == 0 || num_threads == 0 {
+    return Err("Depth and threads must be positive".into());
+  }
+
+  let options = Options {
+    num_threads,
+    search_depth,
+    unit_depth: search_depth / 2,
+    ..Default::default()
+  };
+
+  let mut out =
+    BufWriter::new(File::create(path).map_err(|err| format!("Failed to create {path}: {err}"))?);
+  let write_err = |err| format!("Failed to write {path}: {err}");
+  TrainingExample::write_header(&mut out, Onoro16::board_width()).map_err(write_err)?;
+
+  let mut rng = StdRng::seed_from_u64(seed);
+  let mut examples = 0;
+  for game_idx in 0..games {
+    let (positions, winner) = play_game(&options, opening_moves, &mut rng);
+    for (onoro, policy) in &positions {
+      let value = TrainingExample::value_for(onoro, winner);
+      for example in TrainingExample::from_position(onoro, policy, value, augment) {
+        example.write(&mut out).map_err(write_err)?;
+        examples += 1;
+      }
+    }
+    eprintln!(
+      "Game {}: {} positions, {}",
+      game_idx + 1,
+      positions.len(),
+      match winner {
+        Some(PawnColor::Black) => "black wins",
+        Some(PawnColor::White) => "white wins",
+        None => "draw",
+      }
+    );
+  }
+
+  out.flush().map_err(write_err)?;
+  println!("Wrote {examples} examples to {path}");
+  Ok(())
+}
+
+/// Generates training data for policy and value models from solver-labeled
+/// self-play.
+fn main() {
+  if let Err(err) = run() {
+    eprintln!("{err}");
+    eprintln!("{USAGE}");
+    std::process::exit(1);
+  }
+}
@@ -0,0 +1,213 @@
+use std::{
+  io::BufRead,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+  thread::{self, JoinHandle},
+};
+
+use cooperate::{Engine, Options};
+use onoro::{Move, Onoro16, Onoro16View, OnoroError};
+
+/// The number of worker threads to search with if `go` doesn't specify any.
+const DEFAULT_THREADS: u32 = 4;
+
+type OnoroEngine = Engine<Onoro16View>;
+
+/// A search running in the background. The engine is handed back once the
+/// search finishes.
+struct Search {
+  stop: Arc<AtomicBool>,
+  handle: JoinHandle<OnoroEngine>,
+}
+
+/// Exposes the solver over a line-based text protocol on stdin/stdout, in the
+/// spirit of UCI for chess:
+///
+/// ```text
+/// onoro                      -> id name onoro-rs / onorook
+/// isready                    -> readyok
+/// position startpos
+/// position hexstart
+/// position board <row>/<row>/...   (rows as in `Onoro::from_board_string`)
+/// move <move>                (e.g. "b@h9" or "h9-i10", or "(8, 9) from idx 2")
+/// go depth <N> [threads <K>] -> info depth <d> score <score> ... / bestmove <move>
+/// stop
+/// quit
+/// ```
+///
+/// `go` searches iteratively deeper up to depth N in the background, reporting
+/// the score after each depth. `stop` ends the search once the depth currently
+/// being searched completes, and the best move from the deepest completed
+/// search is reported.
+struct Protocol {
+  engine: Option<OnoroEngine>,
+  search: Option<Search>,
+}
+
+impl Protocol {
+  fn new() -> Self {
+    Self {
+      engine: Some(Engine::new(
+        Onoro16View::new(Onoro16::default_start()),
+        options(1, DEFAULT_THREADS),
+      )),
+      search: None,
+    }
+  }
+
+  /// Waits for the running search, if there is one, to finish.
+  fn join_search(&mut self) {
+    if let Some(search) = self.search.take() {
+      self.engine = Some(search.handle.join().unwrap());
+    }
+  }
+
+  fn engine(&mut self) -> &mut OnoroEngine {
+    self.join_search();
+    self.engine.as_mut().unwrap()
+  }
+
+  /// Handles one line of input, returning false if the program should exit.
+  fn handle(&mut self, line: &str) -> bool {
+    let tokens: Vec<_> = line.split_whitespace().collect();
+    let result = match tokens[..] {
+      [] => Ok(()),
+      ["onoro"] => {
+        println!("id name onoro-rs");
+        println!("onorook");
+        Ok(())
+      }
+      ["isready"] => {
+        println!("readyok");
+        Ok(())
+      }
+      ["position", ref args @ ..] => self.position(args),
+      ["move", ..] => self.make_move(line.trim().trim_start_matches("move").trim()),
+      ["go", ref args @ ..] => self.go(args),
+      ["stop"] => {
+        if let Some(search) = &self.search {
+          search.stop.store(true, Ordering::Relaxed);
+        }
+        self.join_search();
+        Ok(())
+      }
+      ["quit"] => {
+        self.join_search();
+        return false;
+      }
+      _ => Err(format!("Unknown command \"{line}\"")),
+    };
+
+    if let Err(err) = result {
+      println!("info string error: {err}");
+    }
+    true
+  }
+
+  fn position(&mut self, args: &[&str]) -> Result<(), String> {
+    let onoro = match args {
+      ["startpos"] => Onoro16::default_start(),
+      ["hexstart"] => Onoro16::hex_start(),
+      ["board", ref rows @ ..] if !rows.is_empty() => {
+        let board = rows
+          .concat()
+          .split('/')
+          .map(|row| row.chars().map(String::from).collect::<Vec<_>>().join(" "))
+          .collect::<Vec<_>>()
+          .join("\n");
+        Onoro16::from_board_string(&board)?
+      }
+      _ => return Err("Expected \"position startpos|hexstart|board <rows>\"".into()),
+    };
+
+    self.engine().reroot(Onoro16View::new(onoro));
+    Ok(())
+  }
+
+  fn make_move(&mut self, m: &str) -> Result<(), String> {
+    let engine = self.engine();
+    let m = match engine.root().onoro().parse_move(m) {
+      Ok(m) => m,
+      Err(_) => m
+        .parse::<Move>()
+        .map_err(|err: OnoroError| err.to_string())?,
+    };
+    let violations = engine.root().onoro().explain_illegal(m);
+    if let Some(violation) = violations.first() {
+      return Err(format!("Illegal move {m}: {violation}"));
+    }
+
+    engine.make_move(m);
+    Ok(())
+  }
+
+  fn go(&mut self, args: &[&str]) -> Result<(), String> {
+    let mut depth = None;
+    let mut threads = DEFAULT_THREADS;
+    for pair in args.chunks(2) {
+      match pair {
+        ["depth", n] => depth = Some(n.parse().map_err(|err| format!("Invalid depth: {err}"))?),
+        ["threads", k] => threads = k.parse().map_err(|err| format!("Invalid threads: {err}"))?,
+        _ => return Err(format!("Unknown go argument \"{}\"", pair.join(" "))),
+      }
+    }
+    let depth: u32 = depth.ok_or("Expected \"go depth <N>\"")?;
+    if depth == 0 || threads == 0 {
+      return Err("Depth and threads must be positive".into());
+    }
+
+    self.join_search();
+    let mut engine = self.engine.take().unwrap();
+    let stop = Arc::new(AtomicBool::new(false));
+    let handle = {
+      let stop = stop.clone();
+      thread::spawn(move || {
+        let mut best_move = None;
+        for d in 1..=depth {
+          // Always complete at least one depth, so there is a move to report.
+          if d > 1 && stop.load(Ordering::Relaxed) {
+            break;
+          }
+          engine.set_options(options(d, threads));
+          let score = engine.solve();
+          best_move = engine.best_move();
+          println!(
+            "info depth {d} score {score} states {}",
+            engine.cached_states()
+          );
+        }
+
+        match best_move {
+          Some(m) => println!("bestmove {m}"),
+          None => println!("bestmove none"),
+        }
+        engine
+      })
+    };
+
+    self.search = Some(Search { stop, handle });
+    Ok(())
+  }
+}
+
+fn options(search_depth: u32, num_threads: u32) -> Options {
+  Options {
+    num_threads,
+    search_depth,
+    unit_depth: search_depth / 2,
+    ..Default::default()
+  }
+}
+
+fn main() {
+  let mut protocol = Protocol::new();
+  for line in std::io::stdin().lock().lines() {
+    let line = line.unwrap();
+    if !protocol.handle(&line) {
+      break;
+    }
+  }
+  protocol.join_search();
+}
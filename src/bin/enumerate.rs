@@ -0,0 +1,92 @@
+use std::{path::PathBuf, time::Instant};
+
+use onoro::{enumerate_positions, BoardSize, EnumerateOptions, Onoro, Onoro16, Onoro8, PlyCount};
+
+const USAGE: &str = "\
+Usage: enumerate [--plies <P>] [--pawns <4|8>] [--memory-limit <M>]
+                 [--spill-dir <path>] [--approximate <MB>]
+
+Counts every position reachable from the default start in up to P moves
+(default 6), up to symmetry, by the ply it is first reached at. Each player
+has --pawns pawns (default 8).
+
+At most M positions of a ply (default 1048576) are held in memory, and the
+rest are spilled to files in --spill-dir (default the system's temp
+directory). Counts are exact unless --approximate is given, which bounds the
+memory used to remember seen positions to MB megabytes, at the cost of
+missing about 1% of new positions once that memory fills up.";
+
+fn report(count: &PlyCount, start: Instant) {
+  println!(
+    "ply {}: {} positions, {} won, {} spilled ({:?})",
+    count.ply,
+    count.positions,
+    count.finished,
+    count.spilled,
+    start.elapsed()
+  );
+}
+
+fn enumerate<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+  start: Onoro<N, N2, ADJ_CNT_SIZE>,
+  options: &EnumerateOptions,
+) -> Result<(), String> {
+  let timer = Instant::now();
+  let counts = enumerate_positions(&start, options, |count| report(count, timer))
+    .map_err(|err| format!("Failed to spill the frontier: {err}"))?;
+  println!(
+    "total: {} positions",
+    counts.iter().map(|count| count.positions).sum::<u64>()
+  );
+  Ok(())
+}
+
+fn run() -> Result<(), String> {
+  let args: Vec<_> = std::env::args().collect();
+  if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+    println!("{USAGE}");
+    return Ok(());
+  }
+  let flag_value = |flag: &str| {
+    args
+      .iter()
+      .position(|arg| arg == flag)
+      .and_then(|idx| args.get(idx + 1))
+  };
+  let parse_flag = |flag: &str, default: u64| -> Result<u64, String> {
+    flag_value(flag).map_or(Ok(default), |value| {
+      value
+        .parse()
+        .map_err(|err| format!("Invalid value for {flag}: {err}"))
+    })
+  };
+
+  let defaults = EnumerateOptions::default();
+  let options = EnumerateOptions {
+    max_ply: parse_flag("--plies", defaults.max_ply as u64)? as u32,
+    frontier_memory_limit: parse_flag("--memory-limit", defaults.frontier_memory_limit as u64)?
+      as usize,
+    spill_dir: flag_value("--spill-dir").map_or(defaults.spill_dir, PathBuf::from),
+    approximate_dedup_bytes: match flag_value("--approximate") {
+      Some(_) => Some(parse_flag("--approximate", 0)? as usize * (1 << 20)),
+      None => None,
+    },
+  };
+  let board_size = flag_value("--pawns")
+    .map_or(Ok(BoardSize::default()), |value| value.parse())
+    .map_err(|err| err.message().to_owned())?;
+
+  match board_size {
+    BoardSize::Onoro8 => enumerate(Onoro8::default_start(), &options),
+    BoardSize::Onoro16 => enumerate(Onoro16::default_start(), &options),
+  }
+}
+
+/// Counts the positions reachable from the start of the game, ply by ply.
+fn main() {
+  if let Err(err) = run() {
+    eprintln!("{err}");
+    eprintln!("{USAGE}");
+    std::process::exit(1);
+  }
+}
@@ -0,0 +1,80 @@
+use std::time::Instant;
+
+use onoro::Onoro16;
+
+const USAGE: &str = "\
+Usage: perft [--depth <D>] [--start <default|hex>] [--board <path>] [--divide]
+
+Counts the sequences of legal moves of each length up to D (default 5) from a
+starting position, which is the default start unless --start or --board is
+given. --board reads the position from a drawing of the board, in the format
+of Onoro::from_board_string.
+
+With --divide, also prints the count at depth D for each first move. Counts
+that differ from those of a known-good build of the move generator pinpoint
+the moves it gets wrong.";
+
+fn run() -> Result<(), String> {
+  let args: Vec<_> = std::env::args().collect();
+  if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+    println!("{USAGE}");
+    return Ok(());
+  }
+  let flag_value = |flag: &str| {
+    args
+      .iter()
+      .position(|arg| arg == flag)
+      .and_then(|idx| args.get(idx + 1))
+  };
+
+  let depth = flag_value("--depth").map_or(Ok(5), |depth| {
+    depth
+      .parse::<u32>()
+      .map_err(|err| format!("Invalid value for --depth: {err}"))
+  })?;
+  let divide = args.iter().any(|arg| arg == "--divide");
+  let onoro = match (flag_value("--start"), flag_value("--board")) {
+    (Some(_), Some(_)) => return Err("Only one of --start and --board may be given".into()),
+    (None, None) => Onoro16::default_start(),
+    (Some(start), None) => match start.as_str() {
+      "default" => Onoro16::default_start(),
+      "hex" => Onoro16::hex_start(),
+      start => return Err(format!("Unknown start \"{start}\"")),
+    },
+    (None, Some(path)) => {
+      let board =
+        std::fs::read_to_string(path).map_err(|err| format!("Failed to read {path}: {err}"))?;
+      Onoro16::from_board_string(board.trim_end())
+        .map_err(|err| format!("Invalid board in {path}: {err}"))?
+    }
+  };
+
+  println!("{onoro}");
+  for depth in 1..=depth {
+    let start = Instant::now();
+    let count = onoro.perft(depth);
+    let elapsed = start.elapsed();
+    println!(
+      "depth {depth}: {count} ({:.0} nodes/s, {elapsed:?})",
+      count as f64 / elapsed.as_secs_f64()
+    );
+  }
+
+  if divide && depth > 0 {
+    println!();
+    for (m, count) in onoro.perft_divide(depth) {
+      println!("{}: {count}", m.to_notation(&onoro));
+    }
+  }
+  Ok(())
+}
+
+/// Counts move sequences from a position, to validate changes to the move
+/// generator against a known-good build.
+fn main() {
+  if let Err(err) = run() {
+    eprintln!("{err}");
+    eprintln!("{USAGE}");
+    std::process::exit(1);
+  }
+}
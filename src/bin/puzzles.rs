@@ -0,0 +1,134 @@
+use std::{
+  collections::HashSet,
+  fs::File,
+  io::{BufWriter, Write},
+};
+
+use cooperate::{find_unique_win, Options};
+use onoro::{GameRecord, Onoro16View, Puzzle};
+
+const USAGE: &str = "\
+Usage: puzzles --records <path> [--out <path>] [--mate-in <N>] [--threads <T>]
+               [--min-difficulty <D>]
+
+Scans every position of the game records in --records, e.g. as written by
+selfplay, for puzzles: positions where the player to move has exactly one
+move that wins within N of their moves (default 2), and no other move wins
+within N + 1 moves. Each position is checked once, even if it appears in
+several games, using T solver threads (default 4).
+
+Puzzles are written to --out, or to stdout, as game records starting from the
+puzzle's position with the solution as the moves, in the format documented in
+onoro's puzzle module. Puzzles easier than --min-difficulty (default 0) are
+skipped.";
+
+fn run() -> Result<(), String> {
+  let args: Vec<_> = std::env::args().collect();
+  if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+    println!("{USAGE}");
+    return Ok(());
+  }
+  let flag_value = |flag: &str| {
+    args
+      .iter()
+      .position(|arg| arg == flag)
+      .and_then(|idx| args.get(idx + 1))
+  };
+  let parse_flag = |flag: &str, default: u64| -> Result<u64, String> {
+    flag_value(flag).map_or(Ok(default), |value| {
+      value
+        .parse()
+        .map_err(|err| format!("Invalid value for {flag}: {err}"))
+    })
+  };
+
+  let records_path = flag_value("--records").ok_or("Missing --records")?;
+  let mate_in = parse_flag("--mate-in", 2)? as u32;
+  let num_threads = parse_flag("--threads", 4)? as u32;
+  let min_difficulty = parse_flag("--min-difficulty", 0)? as u32;
+  if mate_in == 0 || num_threads == 0 {
+    return Err("Mate-in and threads must be positive".into());
+  }
+
+  let text = std::fs::read_to_string(records_path)
+    .map_err(|err| format!("Failed to read {records_path}: {err}"))?;
+  let records = GameRecord::parse_all(&text).map_err(|err| format!("{records_path}: {err}"))?;
+
+  let mut out: Box<dyn Write> = match flag_value("--out") {
+    Some(path) => Box::new(BufWriter::new(
+      File::create(path).map_err(|err| format!("Failed to create {path}: {err}"))?,
+    )),
+    None => Box::new(std::io::stdout()),
+  };
+  let write_err = |err| format!("Failed to write puzzle: {err}");
+
+  let options = Options {
+    num_threads,
+    search_depth: 2 * mate_in + 1,
+    unit_depth: mate_in,
+    ..Default::default()
+  };
+
+  // Symmetric positions make the same puzzle, so positions are told apart by
+  // their canonical hashes.
+  let mut seen = HashSet::new();
+  let mut puzzles = 0;
+  for (game_idx, record) in records.iter().enumerate() {
+    let mut onoro = record.start.clone();
+    let positions: Vec<_> = record
+      .moves
+      .iter()
+      .map(|&m| {
+        let position = onoro.clone();
+        onoro.make_move(m);
+        position
+      })
+      .collect();
+    for (move_idx, view) in Onoro16View::canonicalize_batch(&positions)
+      .into_iter()
+      .enumerate()
+    {
+      if !seen.insert(view.canonical_hash()) {
+        continue;
+      }
+
+      let Some(solution) = find_unique_win(&view, mate_in, options.clone()) else {
+        continue;
+      };
+      let mut puzzle = Puzzle::new(view.onoro().clone(), solution)
+        .map_err(|err| format!("Invalid solution: {err}"))?;
+      if puzzle.difficulty() < min_difficulty {
+        continue;
+      }
+
+      let source = match record.tag("Game") {
+        Some(game) => format!("{records_path}, game {game}, move {}", move_idx + 1),
+        None => format!(
+          "{records_path}, record {}, move {}",
+          game_idx + 1,
+          move_idx + 1
+        ),
+      };
+      puzzle.tags.push(("Source".into(), source));
+      writeln!(out, "{puzzle}").map_err(write_err)?;
+      puzzles += 1;
+    }
+  }
+
+  out.flush().map_err(write_err)?;
+  eprintln!(
+    "Found {puzzles} puzzles in {} positions of {} games",
+    seen.len(),
+    records.len()
+  );
+  Ok(())
+}
+
+/// Finds puzzles with a unique winning move in recorded games.
+fn main() {
+  if let Err(err) = run() {
+    eprintln!("{err}");
+    eprintln!("{USAGE}");
+    std::process::exit(1);
+  }
+}
@@ -0,0 +1,108 @@
+use std::time::Instant;
+
+use cooperate::{solve, Options};
+use onoro::{Onoro16View, SuitePosition};
+
+const USAGE: &str = "\
+Usage: regression <suite> [--threads <T>]
+
+Solves every position of the suite file, in the format documented in onoro's
+position_suite module, and reports whether the solver found the expected
+result and best move for each, using T solver threads (default 4). Exits
+with an error if any position fails.";
+
+/// Solves `position`, returning a description of what the solver got wrong,
+/// if anything.
+fn check_position(position: &SuitePosition, num_threads: u32) -> Result<(), String> {
+  let options = Options {
+    num_threads,
+    search_depth: position.depth,
+    unit_depth: position.depth / 2,
+    ..Default::default()
+  };
+  let solution = solve(&Onoro16View::new(position.position.clone()), options);
+  position.check(&solution.score, solution.best_move())
+}
+
+/// Solves every position of `suite`, printing whether each passed, and
+/// returns the number that failed.
+fn run_suite(suite: &[SuitePosition], num_threads: u32) -> usize {
+  let mut failures = 0;
+  for position in suite {
+    let start = Instant::now();
+    match check_position(position, num_threads) {
+      Ok(()) => println!("PASS {} ({:?})", position.name(), start.elapsed()),
+      Err(err) => {
+        println!("FAIL {}: {err}", position.name());
+        failures += 1;
+      }
+    }
+  }
+  println!(
+    "{} of {} positions passed",
+    suite.len() - failures,
+    suite.len()
+  );
+  failures
+}
+
+fn run() -> Result<(), String> {
+  let args: Vec<_> = std::env::args().collect();
+  if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+    println!("{USAGE}");
+    return Ok(());
+  }
+  let flag_value = |flag: &str| {
+    args
+      .iter()
+      .position(|arg| arg == flag)
+      .and_then(|idx| args.get(idx + 1))
+  };
+
+  let suite_path = args.get(1).ok_or("Missing suite")?;
+  let num_threads = flag_value("--threads").map_or(Ok(4), |value| {
+    value
+      .parse::<u32>()
+      .map_err(|err| format!("Invalid value for --threads: {err}"))
+  })?;
+  if num_threads == 0 {
+    return Err("Threads must be positive".into());
+  }
+
+  let text = std::fs::read_to_string(suite_path)
+    .map_err(|err| format!("Failed to read {suite_path}: {err}"))?;
+  let suite = SuitePosition::parse_all(&text).map_err(|err| format!("{suite_path}: {err}"))?;
+  match run_suite(&suite, num_threads) {
+    0 => Ok(()),
+    failures => {
+      eprintln!("{failures} positions failed");
+      std::process::exit(1);
+    }
+  }
+}
+
+/// Checks the solver against a suite of positions with known results.
+fn main() {
+  if let Err(err) = run() {
+    eprintln!("{err}");
+    eprintln!("{USAGE}");
+    std::process::exit(1);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use onoro::SuitePosition;
+
+  use super::run_suite;
+
+  /// Won and lost positions, which each search resolves within its depth.
+  /// Run with `cargo test --release -- --ignored`.
+  #[test]
+  #[ignore]
+  fn test_known_results() {
+    let suite = SuitePosition::parse_all(include_str!("../../suites/known_results.txt")).unwrap();
+    assert!(!suite.is_empty());
+    assert_eq!(run_suite(&suite, 4), 0);
+  }
+}
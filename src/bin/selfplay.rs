@@ -0,0 +1,504 @@
+use std::{
+  fmt::Display,
+  fs::File,
+  io::{BufWriter, Write},
+  sync::Arc,
+  time::Duration,
+};
+
+use abstract_game::Repetitions;
+use cooperate::{Engine, MoveOrdering, Options, StandardOrdering, TimeLimit};
+use onoro::{GameRecord, Move, Onoro16, Onoro16View, PawnColor, RecordResult};
+use rand::{rngs::StdRng, seq::IteratorRandom, SeedableRng};
+
+/// Games that go on for this many moves are scored as draws, even if no
+/// position has repeated.
+const MAX_GAME_MOVES: u32 = 200;
+
+/// The depth of the search used to check that openings are balanced. An
+/// opening is balanced if neither player can force a win within this depth.
+const BALANCE_CHECK_DEPTH: u32 = 4;
+
+/// Random openings are retried this many times to find a balanced one.
+const MAX_OPENING_ATTEMPTS: u32 = 1000;
+
+/// The z-score of a two-sided 95% confidence interval.
+const Z_95: f64 = 1.96;
+
+/// The type 1 and type 2 error rates of the sequential probability ratio test.
+const SPRT_ALPHA: f64 = 0.05;
+const SPRT_BETA: f64 = 0.05;
+
+const USAGE: &str = "\
+Usage: selfplay [--games <N>] [--a <config>] [--b <config>] [--opening-moves <K>]
+                [--seed <S>] [--sprt <elo0>,<elo1>] [--records <path>]
+
+Plays configuration A against configuration B for N games (default 100), in
+pairs from the same random opening with colors swapped. Openings are K random
+moves (default 4) from the default start in which neither player has a forced
+win.
+
+A configuration is a comma-separated list of depth=<D>, threads=<T>,
+time=<ms>, eval=<0|1>, tt=<0|1>, threats=<0|1> and history=<0|1>, e.g.
+\"depth=8,threads=4,time=500\". With a time limit, each move is searched
+iteratively deeper until the time runs out, with a hard limit of 3x the time.
+With eval=1, moves the search finds equally good are chosen between by their
+heuristic evaluation.
+
+tt, threats and history turn on tiers of move ordering in the search: moves
+to the best child in the table of resolved states first, then moves that win
+or block a win, then the rest by the history heuristic. With all of them off
+(the default), moves are searched in the order they are generated in.
+
+With --sprt, the match stops early once a sequential probability ratio test
+accepts either the hypothesis that A is elo0 stronger than B, or that it is
+elo1 stronger.
+
+Game records are written to --records, or to stdout.";
+
+/// The search settings of one side of the match.
+#[derive(Clone, Debug)]
+struct PlayerConfig {
+  search_depth: u32,
+  num_threads: u32,
+  time_limit: Option<Duration>,
+  /// If true, chooses between equally scored moves by their heuristic
+  /// evaluation.
+  evaluate: bool,
+  /// The tiers of `StandardOrdering` to search with, see `ordering`.
+  table_move: bool,
+  threats: bool,
+  history: bool,
+}
+
+impl PlayerConfig {
+  fn parse(config: &str) -> Result<Self, String> {
+    let mut player = Self::default();
+    for setting in config.split(',').filter(|setting| !setting.is_empty()) {
+      let (key, value) = setting
+        .split_once('=')
+        .ok_or_else(|| format!("Expected <key>=<value>, found \"{setting}\""))?;
+      let value: u64 = value
+        .parse()
+        .map_err(|err| format!("Invalid value for {key}: {err}"))?;
+      match key {
+        "depth" => player.search_depth = value as u32,
+        "threads" => player.num_threads = value as u32,
+        "time" => player.time_limit = Some(Duration::from_millis(value)),
+        "eval" => player.evaluate = value != 0,
+        "tt" => player.table_move = value != 0,
+        "threats" => player.threats = value != 0,
+        "history" => player.history = value != 0,
+        _ => return Err(format!("Unknown setting \"{key}\"")),
+      }
+    }
+
+    if player.search_depth == 0 || player.num_threads == 0 {
+      return Err("Depth and threads must be positive".into());
+    }
+    Ok(player)
+  }
+
+  fn options(&self) -> Options {
+    Options {
+      num_threads: self.num_threads,
+      search_depth: self.search_depth,
+      unit_depth: self.search_depth / 2,
+      time_limit: self.time_limit.map(|soft| TimeLimit {
+        soft,
+        hard: soft * 3,
+      }),
+      ..Default::default()
+    }
+  }
+
+  /// The move ordering to search with, or `None` to search moves in the order
+  /// they are generated in.
+  fn ordering(&self) -> Option<Arc<dyn MoveOrdering<Onoro16View>>> {
+    if !(self.table_move || self.threats || self.history) {
+      return None;
+    }
+    let mut ordering = StandardOrdering::new();
+    ordering.table_move = self.table_move;
+    ordering.threats = self.threats;
+    ordering.history = self.history;
+    Some(Arc::new(ordering))
+  }
+}
+
+impl Default for PlayerConfig {
+  fn default() -> Self {
+    Self {
+      search_depth: 6,
+      num_threads: 4,
+      time_limit: None,
+      evaluate: false,
+      table_move: false,
+      threats: false,
+      history: false,
+    }
+  }
+}
+
+impl Display for PlayerConfig {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "depth={},threads={}",
+      self.search_depth, self.num_threads
+    )?;
+    if let Some(time_limit) = self.time_limit {
+      write!(f, ",time={}", time_limit.as_millis())?;
+    }
+    if self.evaluate {
+      write!(f, ",eval=1")?;
+    }
+    for (name, enabled) in [
+      ("tt", self.table_move),
+      ("threats", self.threats),
+      ("history", self.history),
+    ] {
+      if enabled {
+        write!(f, ",{name}=1")?;
+      }
+    }
+    Ok(())
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum GameOutcome {
+  BlackWins,
+  WhiteWins,
+  Draw,
+}
+
+impl From<GameOutcome> for RecordResult {
+  fn from(outcome: GameOutcome) -> Self {
+    match outcome {
+      GameOutcome::BlackWins => RecordResult::BlackWins,
+      GameOutcome::WhiteWins => RecordResult::WhiteWins,
+      GameOutcome::Draw => RecordResult::Draw,
+    }
+  }
+}
+
+/// Wins, draws and losses from the perspective of configuration A.
+#[derive(Default)]
+struct MatchStats {
+  wins: u32,
+  draws: u32,
+  losses: u32,
+}
+
+impl MatchStats {
+  fn games(&self) -> u32 {
+    self.wins + self.draws + self.losses
+  }
+
+  /// The average points per game of A, counting draws as half a point.
+  fn score(&self) -> f64 {
+    (self.wins as f64 + self.draws as f64 / 2.) / self.games() as f64
+  }
+
+  /// The variance of the points A earns in a single game.
+  fn per_game_variance(&self) -> f64 {
+    let score = self.score();
+    let games = self.games() as f64;
+    (self.wins as f64 * (1. - score).powi(2)
+      + self.draws as f64 * (0.5 - score).powi(2)
+      + self.losses as f64 * score.powi(2))
+      / games
+  }
+
+  /// The 95% confidence interval of `score()`.
+  fn score_interval(&self) -> (f64, f64) {
+    let margin = Z_95 * (self.per_game_variance() / self.games() as f64).sqrt();
+    let score = self.score();
+    ((score - margin).max(0.), (score + margin).min(1.))
+  }
+
+  /// The log-likelihood ratio of A being `elo1` stronger than B versus being
+  /// `elo0` stronger, using the normal approximation of the score.
+  fn log_likelihood_ratio(&self, elo0: f64, elo1: f64) -> f64 {
+    let variance = self.per_game_variance();
+    if variance == 0. {
+      return 0.;
+    }
+    let (score0, score1) = (elo_to_score(elo0), elo_to_score(elo1));
+    (score1 - score0) * (2. * self.score() - score0 - score1) * self.games() as f64
+      / (2. * variance)
+  }
+}
+
+impl Display for MatchStats {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let (low, high) = self.score_interval();
+    write!(
+      f,
+      "+{} ={} -{} ({} games), score {:.3} [{:.3}, {:.3}], elo {:.1} [{:.1}, {:.1}]",
+      self.wins,
+      self.draws,
+      self.losses,
+      self.games(),
+      self.score(),
+      low,
+      high,
+      score_to_elo(self.score()),
+      score_to_elo(low),
+      score_to_elo(high)
+    )
+  }
+}
+
+fn elo_to_score(elo: f64) -> f64 {
+  1. / (1. + 10f64.powf(-elo / 400.))
+}
+
+fn score_to_elo(score: f64) -> f64 {
+  -400. * (1. / score - 1.).log10()
+}
+
+/// Plays random moves from the default start until finding an opening of
+/// `opening_moves` moves in which neither player can force a win.
+fn balanced_opening(opening_moves: u32, rng: &mut StdRng) -> Result<Vec<Move>, String> {
+  let check_options = Options {
+    num_threads: 1,
+    search_depth: BALANCE_CHECK_DEPTH,
+    unit_depth: BALANCE_CHECK_DEPTH / 2,
+    ..Default::default()
+  };
+
+  for _ in 0..MAX_OPENING_ATTEMPTS {
+    let mut game = Onoro16::default_start();
+    let mut moves = Vec::new();
+    for _ in 0..opening_moves {
+      if game.finished().is_some() {
+        break;
+      }
+      match game.each_move().choose(rng) {
+        Some(m) => {
+          game.make_move(m);
+          moves.push(m);
+        }
+        None => break,
+      }
+    }
+    if moves.len() != opening_moves as usize || game.finished().is_some() {
+      continue;
+    }
+
+    let mut engine = Engine::new(Onoro16View::new(game), check_options.clone());
+    if engine.solve().turn_count_win() == 0 {
+      return Ok(moves);
+    }
+  }
+
+  Err(format!(
+    "No balanced opening found in {MAX_OPENING_ATTEMPTS} attempts"
+  ))
+}
+
+/// Plays one game from `opening` between `black` and `white`, returning the
+/// outcome and every move made after the opening. The game is a draw once a
+/// position occurs for the third time.
+fn play_game(
+  opening: &[Move],
+  black: &PlayerConfig,
+  white: &PlayerConfig,
+) -> (GameOutcome, Vec<Move>) {
+  let mut game = Onoro16::default_start();
+  for &m in opening {
+    game.make_move(m);
+  }
+
+  let mut engines = [black, white].map(|config| {
+    let mut engine = Engine::new(Onoro16View::new(game.clone()), config.options());
+    engine.set_move_ordering(config.ordering());
+    engine
+  });
+  let mut moves = Vec::new();
+  // Symmetric positions count as repetitions of each other.
+  let mut repetitions = Repetitions::new();
+  repetitions.push(&Onoro16View::new(game.clone()));
+  loop {
+    if let Some(winner) = game.finished() {
+      let outcome = match winner {
+        PawnColor::Black => GameOutcome::BlackWins,
+        PawnColor::White => GameOutcome::WhiteWins,
+      };
+      return (outcome, moves);
+    }
+    if repetitions.is_draw() || moves.len() >= MAX_GAME_MOVES as usize {
+      return (GameOutcome::Draw, moves);
+    }
+
+    let to_move = game.player_color();
+    let (engine, config) = match to_move {
+      PawnColor::Black => (&mut engines[0], black),
+      PawnColor::White => (&mut engines[1], white),
+    };
+    engine.solve();
+    let best_move = if config.evaluate {
+      engine.best_move_by_evaluation()
+    } else {
+      engine.best_move()
+    };
+    let m = match best_move {
+      Some(m) => m,
+      // A player with no legal moves loses.
+      None => {
+        let outcome = match to_move {
+          PawnColor::Black => GameOutcome::WhiteWins,
+          PawnColor::White => GameOutcome::BlackWins,
+        };
+        return (outcome, moves);
+      }
+    };
+
+    game.make_move(m);
+    moves.push(m);
+    repetitions.push(&Onoro16View::new(game.clone()));
+    for engine in engines.iter_mut() {
+      engine.make_move(m);
+    }
+  }
+}
+
+fn write_record(
+  out: &mut impl Write,
+  game_idx: u32,
+  black: &str,
+  white: &str,
+  opening: &[Move],
+  moves: &[Move],
+  outcome: GameOutcome,
+) -> std::io::Result<()> {
+  let mut record = GameRecord::new(Onoro16::default_start());
+  record.set_tag("Game", game_idx.to_string());
+  record.set_tag("Black", black);
+  record.set_tag("White", white);
+  record.set_tag("OpeningMoves", opening.len().to_string());
+  record.moves = opening.iter().chain(moves).copied().collect();
+  record.result = outcome.into();
+  writeln!(out, "{record}")
+}
+
+fn run() -> Result<(), String> {
+  let args: Vec<_> = std::env::args().collect();
+  if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+    println!("{USAGE}");
+    return Ok(());
+  }
+  let flag_value = |flag: &str| {
+    args
+      .iter()
+      .position(|arg| arg == flag)
+      .and_then(|idx| args.get(idx + 1))
+  };
+  let parse_flag = |flag: &str, default: u64| -> Result<u64, String> {
+    flag_value(flag).map_or(Ok(default), |value| {
+      value
+        .parse()
+        .map_err(|err| format!("Invalid value for {flag}: {err}"))
+    })
+  };
+
+  let games = parse_flag("--games", 100)? as u32;
+  let opening_moves = parse_flag("--opening-moves", 4)? as u32;
+  let seed = parse_flag("--seed", 0)?;
+  let config_a = flag_value("--a").map_or(Ok(PlayerConfig::default()), |config| {
+    PlayerConfig::parse(config)
+  })?;
+  let config_b = flag_value("--b").map_or(Ok(PlayerConfig::default()), |config| {
+    PlayerConfig::parse(config)
+  })?;
+  let sprt = flag_value("--sprt")
+    .map(|bounds| -> Result<(f64, f64), String> {
+      let (elo0, elo1) = bounds
+        .split_once(',')
+        .ok_or("Expected --sprt <elo0>,<elo1>")?;
+      let parse_elo = |elo: &str| -> Result<f64, String> {
+        elo.parse().map_err(|err| format!("Invalid elo: {err}"))
+      };
+      Ok((parse_elo(elo0)?, parse_elo(elo1)?))
+    })
+    .transpose()?;
+
+  let mut records: Box<dyn Write> = match flag_value("--records") {
+    Some(path) => Box::new(BufWriter::new(
+      File::create(path).map_err(|err| format!("Failed to create {path}: {err}"))?,
+    )),
+    None => Box::new(std::io::stdout()),
+  };
+
+  let name_a = format!("A ({config_a})");
+  let name_b = format!("B ({config_b})");
+  eprintln!("{name_a} vs {name_b}");
+
+  let mut rng = StdRng::seed_from_u64(seed);
+  let mut stats = MatchStats::default();
+  let mut opening = Vec::new();
+  for game_idx in 0..games {
+    // Each opening is played twice, with A playing black in the first game
+    // and white in the second.
+    let a_is_black = game_idx & 1 == 0;
+    if a_is_black {
+      opening = balanced_opening(opening_moves, &mut rng)?;
+    }
+
+    let (black, white, black_name, white_name) = if a_is_black {
+      (&config_a, &config_b, &name_a, &name_b)
+    } else {
+      (&config_b, &config_a, &name_b, &name_a)
+    };
+    let (outcome, moves) = play_game(&opening, black, white);
+    write_record(
+      &mut records,
+      game_idx + 1,
+      black_name,
+      white_name,
+      &opening,
+      &moves,
+      outcome,
+    )
+    .map_err(|err| format!("Failed to write game record: {err}"))?;
+
+    match (outcome, a_is_black) {
+      (GameOutcome::Draw, _) => stats.draws += 1,
+      (GameOutcome::BlackWins, true) | (GameOutcome::WhiteWins, false) => stats.wins += 1,
+      _ => stats.losses += 1,
+    }
+    eprintln!("{stats}");
+
+    if let Some((elo0, elo1)) = sprt {
+      let llr = stats.log_likelihood_ratio(elo0, elo1);
+      let lower = (SPRT_BETA / (1. - SPRT_ALPHA)).ln();
+      let upper = ((1. - SPRT_BETA) / SPRT_ALPHA).ln();
+      eprintln!("SPRT: llr {llr:.3} [{lower:.3}, {upper:.3}]");
+      if llr <= lower {
+        eprintln!("SPRT: accepted H0 (elo {elo0})");
+        break;
+      } else if llr >= upper {
+        eprintln!("SPRT: accepted H1 (elo {elo1})");
+        break;
+      }
+    }
+  }
+
+  records
+    .flush()
+    .map_err(|err| format!("Failed to write game records: {err}"))?;
+  println!("Final: {stats}");
+  Ok(())
+}
+
+/// Plays two solver configurations against each other, for validating changes
+/// to the search.
+fn main() {
+  if let Err(err) = run() {
+    eprintln!("{err}");
+    eprintln!("{USAGE}");
+    std::process::exit(1);
+  }
+}
@@ -0,0 +1,151 @@
+use onoro::{BoardSize, Onoro, Onoro16, Onoro8, SymmetryClass, SymmetryStats};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+const USAGE: &str = "\
+Usage: symm_stats [--games <G>] [--plies <P>] [--pawns <4|8>] [--rounds <R>]
+                  [--seed <S>]
+
+Reports how often each symmetry class comes up, how often distinct positions
+of each class share a canonical hash, and how long positions of each class
+take to canonicalize, to show which symmetry classes are worth optimizing.
+
+Positions are taken from every move of G random games from the default start
+(default 1000), seeded with S (default 0). With --plies, every position
+within P moves of the start is taken instead. Each player has --pawns pawns
+(default 8). Canonicalizing each distinct position is timed R times (default
+10).";
+
+/// Adds every position of `games` random games, until each is won or the
+/// player to move has no moves.
+fn sample_games<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+  start: &Onoro<N, N2, ADJ_CNT_SIZE>,
+  games: u64,
+  seed: u64,
+  stats: &mut SymmetryStats<N, N2, ADJ_CNT_SIZE>,
+) {
+  // Phase 2 can go on forever, so end games that haven't been won by then.
+  const MAX_MOVES: u32 = 200;
+
+  let mut rng = StdRng::seed_from_u64(seed);
+  for _ in 0..games {
+    let mut onoro = start.clone();
+    for _ in 0..MAX_MOVES {
+      stats.add(&onoro);
+      if onoro.finished().is_some() {
+        break;
+      }
+      let moves: Vec<_> = onoro.each_move().collect();
+      let Some(&m) = moves.choose(&mut rng) else {
+        break;
+      };
+      onoro.make_move(m);
+    }
+  }
+}
+
+/// Adds every position within `plies` moves of `onoro`, as many times as it's
+/// reached.
+fn sample_tree<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+  onoro: &Onoro<N, N2, ADJ_CNT_SIZE>,
+  plies: u32,
+  stats: &mut SymmetryStats<N, N2, ADJ_CNT_SIZE>,
+) {
+  stats.add(onoro);
+  if plies == 0 || onoro.finished().is_some() {
+    return;
+  }
+  for m in onoro.each_move() {
+    let mut child = onoro.clone();
+    child.make_move(m);
+    sample_tree(&child, plies - 1, stats);
+  }
+}
+
+fn report<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+  stats: &SymmetryStats<N, N2, ADJ_CNT_SIZE>,
+) {
+  let total = stats.total();
+  println!(
+    "{:<8} {:>10} {:>7} {:>10} {:>10} {:>10} {:>10}",
+    "class", "positions", "share", "distinct", "collisions", "rate", "ns/canon"
+  );
+  let rows = SymmetryClass::ALL
+    .iter()
+    .map(|&class| (class.name(), stats.class_stats(class).clone()))
+    .chain([("total", total.clone())]);
+  for (name, class_stats) in rows {
+    println!(
+      "{:<8} {:>10} {:>6.2}% {:>10} {:>10} {:>10.2e} {:>10.1}",
+      name,
+      class_stats.positions,
+      100. * class_stats.positions as f64 / total.positions.max(1) as f64,
+      class_stats.distinct,
+      class_stats.hash_collisions,
+      class_stats.collision_rate(),
+      class_stats.canonicalize_nanos().unwrap_or(0.)
+    );
+  }
+}
+
+fn collect_stats<const N: usize, const N2: usize, const ADJ_CNT_SIZE: usize>(
+  start: Onoro<N, N2, ADJ_CNT_SIZE>,
+  plies: Option<u32>,
+  games: u64,
+  seed: u64,
+  rounds: u32,
+) {
+  let mut stats = SymmetryStats::new();
+  match plies {
+    Some(plies) => sample_tree(&start, plies, &mut stats),
+    None => sample_games(&start, games, seed, &mut stats),
+  }
+  stats.time_canonicalization(rounds);
+  report(&stats);
+}
+
+fn run() -> Result<(), String> {
+  let args: Vec<_> = std::env::args().collect();
+  if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+    println!("{USAGE}");
+    return Ok(());
+  }
+  let flag_value = |flag: &str| {
+    args
+      .iter()
+      .position(|arg| arg == flag)
+      .and_then(|idx| args.get(idx + 1))
+  };
+  let parse_flag = |flag: &str, default: u64| -> Result<u64, String> {
+    flag_value(flag).map_or(Ok(default), |value| {
+      value
+        .parse()
+        .map_err(|err| format!("Invalid value for {flag}: {err}"))
+    })
+  };
+
+  let games = parse_flag("--games", 1000)?;
+  let plies = match flag_value("--plies") {
+    Some(_) => Some(parse_flag("--plies", 0)? as u32),
+    None => None,
+  };
+  let seed = parse_flag("--seed", 0)?;
+  let rounds = parse_flag("--rounds", 10)? as u32;
+  let board_size = flag_value("--pawns")
+    .map_or(Ok(BoardSize::default()), |value| value.parse())
+    .map_err(|err| err.message().to_owned())?;
+
+  match board_size {
+    BoardSize::Onoro8 => collect_stats(Onoro8::default_start(), plies, games, seed, rounds),
+    BoardSize::Onoro16 => collect_stats(Onoro16::default_start(), plies, games, seed, rounds),
+  }
+  Ok(())
+}
+
+/// Reports statistics of the symmetry classes of sampled positions.
+fn main() {
+  if let Err(err) = run() {
+    eprintln!("{err}");
+    eprintln!("{USAGE}");
+    std::process::exit(1);
+  }
+}
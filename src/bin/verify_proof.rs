@@ -0,0 +1,44 @@
+use std::time::SystemTime;
+
+use cooperate::Certificate;
+use onoro::{Onoro16, OnoroView};
+
+/// Checks a proof certificate written by the solver for the default start
+/// position, without repeating the search.
+fn main() {
+  let path = match std::env::args().nth(1) {
+    Some(path) => path,
+    None => {
+      eprintln!("Usage: verify_proof <certificate file>");
+      std::process::exit(1);
+    }
+  };
+
+  let contents = std::fs::read_to_string(&path).unwrap();
+  let certificate: Certificate = match contents.parse() {
+    Ok(certificate) => certificate,
+    Err(err) => {
+      eprintln!("{err}");
+      std::process::exit(1);
+    }
+  };
+
+  let start = SystemTime::now();
+  let result = certificate.verify(&OnoroView::new(Onoro16::default_start()));
+  let end = SystemTime::now();
+
+  match result {
+    Ok(score) => {
+      println!(
+        "Verified {} positions in {:?}",
+        certificate.len(),
+        end.duration_since(start).unwrap()
+      );
+      println!("Score: {score}");
+    }
+    Err(err) => {
+      eprintln!("{err}");
+      std::process::exit(1);
+    }
+  }
+}
@@ -1,13 +1,35 @@
-use std::time::SystemTime;
+use std::{path::Path, time::SystemTime};
 
-use cooperate::solve_with_hasher;
+use cooperate::{solve_with_metrics, Engine, Solution};
 use onoro::{Onoro16, OnoroView};
 
 use crate::passthrough_hasher::BuildPassThroughHasher;
 
 mod passthrough_hasher;
 
+/// The width of the hash `OnoroView` writes, which the hasher of the
+/// transposition table has to expect.
+#[cfg(not(feature = "hash128"))]
+type ViewHash = u64;
+#[cfg(feature = "hash128")]
+type ViewHash = u128;
+
 fn main() {
+  // Passing `--proof <path>` writes a certificate of the score to `path`,
+  // which can be checked with the `verify_proof` binary.
+  //
+  // Passing `--table <path>` resumes from the analysis saved in `path`, if it
+  // exists, and saves the analysis there once the search finishes.
+  let args: Vec<_> = std::env::args().collect();
+  let flag_value = |flag: &str| {
+    args
+      .iter()
+      .position(|arg| arg == flag)
+      .and_then(|idx| args.get(idx + 1))
+  };
+  let proof_path = flag_value("--proof");
+  let table_path = flag_value("--table");
+
   let game = Onoro16::default_start();
 
   println!("size of game state: {}", std::mem::size_of::<Onoro16>());
@@ -29,12 +51,49 @@ fn main() {
     num_threads: 16,
     search_depth: 15,
     unit_depth: 8,
+    ..Default::default()
+  };
+  let (solution, metrics) = match (proof_path, table_path) {
+    (None, None) => solve_with_metrics(
+      &OnoroView::new(Onoro16::default_start()),
+      options,
+      BuildPassThroughHasher::<ViewHash>::new(),
+    ),
+    _ => {
+      let mut engine = Engine::with_hasher(
+        OnoroView::new(Onoro16::default_start()),
+        options,
+        BuildPassThroughHasher::<ViewHash>::new(),
+      );
+      if let Some(table_path) = table_path {
+        if Path::new(table_path).exists() {
+          let states = engine.load_table(table_path).unwrap();
+          println!("Loaded {states} positions from {table_path}");
+        }
+      }
+
+      let score = engine.solve();
+      if let Some(proof_path) = proof_path {
+        if let Some(certificate) = engine.certificate() {
+          std::fs::write(proof_path, certificate.to_string()).unwrap();
+          println!(
+            "Wrote proof with {} positions to {proof_path}",
+            certificate.len()
+          );
+        }
+      }
+      if let Some(table_path) = table_path {
+        engine.save_table(table_path).unwrap();
+        println!("Saved {} positions to {table_path}", engine.cached_states());
+      }
+      let solution = Solution {
+        score,
+        principal_variation: engine.principal_variation(),
+        depth: engine.solved_depth(),
+      };
+      (solution, engine.metrics().clone())
+    }
   };
-  let score = solve_with_hasher(
-    &OnoroView::new(Onoro16::default_start()),
-    options,
-    BuildPassThroughHasher,
-  );
   let end = SystemTime::now();
 
   if let Ok(report) = guard.report().build() {
@@ -43,5 +102,15 @@ fn main() {
   };
 
   println!("Done: {:?}", end.duration_since(start).unwrap());
-  println!("Score: {score}");
+  println!("Score: {}", solution.score);
+  println!(
+    "Principal variation: {}",
+    solution
+      .principal_variation
+      .iter()
+      .map(|m| m.to_string())
+      .collect::<Vec<_>>()
+      .join(", ")
+  );
+  println!("Metrics: {metrics:?}");
 }
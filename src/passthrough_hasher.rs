@@ -1,11 +1,46 @@
-pub struct PassThroughHasher {
+use std::marker::PhantomData;
+
+/// The width of the hashes keys write to a `PassThroughHasher`: `u64` for the
+/// 64-bit canonical hash of `OnoroView`, or `u128` for
+/// `OnoroView::canonical_hash128`, with onoro's `hash128` feature.
+pub trait HashWidth {
+  const BYTES: usize;
+
+  /// The 64 bits of a hash of this width, as written in `bytes`, to use for
+  /// bucketing in a hash table.
+  fn fold(bytes: &[u8]) -> u64;
+}
+
+impl HashWidth for u64 {
+  const BYTES: usize = 8;
+
+  fn fold(bytes: &[u8]) -> u64 {
+    u64::from_ne_bytes(bytes.try_into().unwrap())
+  }
+}
+
+impl HashWidth for u128 {
+  const BYTES: usize = 16;
+
+  /// Both halves are uniformly random, so the low half alone is as good a
+  /// bucket index as any mix of the two. The high half only matters for
+  /// telling keys apart, which their `Eq` does.
+  fn fold(bytes: &[u8]) -> u64 {
+    u128::from_ne_bytes(bytes.try_into().unwrap()) as u64
+  }
+}
+
+/// A hasher for keys which write a single, already uniformly distributed hash
+/// of width `W`, which it passes through unchanged.
+pub struct PassThroughHasher<W = u64> {
   state: u64,
+  _width: PhantomData<W>,
 }
 
-impl std::hash::Hasher for PassThroughHasher {
+impl<W: HashWidth> std::hash::Hasher for PassThroughHasher<W> {
   fn write(&mut self, bytes: &[u8]) {
-    debug_assert!(bytes.len() == 8 && self.state == 0);
-    self.state = unsafe { *(bytes.as_ptr() as *const u64) };
+    debug_assert!(bytes.len() == W::BYTES && self.state == 0);
+    self.state = W::fold(bytes);
   }
 
   fn finish(&self) -> u64 {
@@ -13,12 +48,36 @@ impl std::hash::Hasher for PassThroughHasher {
   }
 }
 
-#[derive(Clone)]
-pub struct BuildPassThroughHasher;
+pub struct BuildPassThroughHasher<W = u64> {
+  _width: PhantomData<W>,
+}
+
+impl<W> BuildPassThroughHasher<W> {
+  pub const fn new() -> Self {
+    Self {
+      _width: PhantomData,
+    }
+  }
+}
+
+impl<W> Clone for BuildPassThroughHasher<W> {
+  fn clone(&self) -> Self {
+    Self::new()
+  }
+}
+
+impl<W> Default for BuildPassThroughHasher<W> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
 
-impl std::hash::BuildHasher for BuildPassThroughHasher {
-  type Hasher = PassThroughHasher;
-  fn build_hasher(&self) -> PassThroughHasher {
-    PassThroughHasher { state: 0 }
+impl<W: HashWidth> std::hash::BuildHasher for BuildPassThroughHasher<W> {
+  type Hasher = PassThroughHasher<W>;
+  fn build_hasher(&self) -> PassThroughHasher<W> {
+    PassThroughHasher {
+      state: 0,
+      _width: PhantomData,
+    }
   }
 }
@@ -6,10 +6,17 @@ import { AsyncSocketContext } from 'client/util/async_sockets';
 import { isOk } from 'client/util/status';
 import { GameState } from 'proto/game_state';
 
-const socket: OnoroSocket = new AsyncSocketContext(
-  'ws://[::]:2345/onoro',
-  true
-);
+/**
+ * The game socket, as relayed by the server the page was served from, under
+ * the same path prefix and with the same TLS.
+ */
+function socketUrl(): string {
+  const url = new URL('onoro', window.location.href);
+  url.protocol = url.protocol === 'https:' ? 'wss:' : 'ws:';
+  return url.toString();
+}
+
+const socket: OnoroSocket = new AsyncSocketContext(socketUrl(), true);
 
 export function App() {
   const [game, setGame] = React.useState<GameState | null>(null);